@@ -25,6 +25,7 @@ async fn create_test_services() -> (
         connect_timeout: std::time::Duration::from_secs(5),
         idle_timeout: std::time::Duration::from_secs(60),
         max_lifetime: std::time::Duration::from_secs(300),
+        ..Default::default()
     };
 
     let db_manager =
@@ -60,6 +61,10 @@ fn bench_claude_provider_creation(c: &mut Criterion) {
                 opus_model: todo!(),
                 sonnet_model: todo!(),
                 haiku_model: todo!(),
+                models: None,
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
             };
 
             let result = service.create_provider(black_box(request)).await;
@@ -91,6 +96,10 @@ fn bench_claude_provider_query(c: &mut Criterion) {
             opus_model: todo!(),
             sonnet_model: todo!(),
             haiku_model: todo!(),
+            models: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+            model_auto_update: None,
         };
         service.create_provider(request).await.unwrap();
     });
@@ -101,6 +110,7 @@ fn bench_claude_provider_query(c: &mut Criterion) {
                 page: Some(1),
                 limit: Some(10),
                 offset: todo!(),
+                with_total: None,
             };
 
             let result = service.list_providers(black_box(params)).await;
@@ -164,6 +174,10 @@ fn bench_json_serialization(c: &mut Criterion) {
         opus_model: todo!(),
         sonnet_model: todo!(),
         haiku_model: todo!(),
+        models: std::collections::HashMap::new(),
+        custom_headers: Default::default(),
+        accepted_status_codes: Default::default(),
+        model_auto_update: 0,
         created_at: Some(chrono::Utc::now().to_rfc3339()),
         updated_at: Some(chrono::Utc::now().to_rfc3339()),
     };
@@ -214,6 +228,10 @@ fn bench_concurrent_requests(c: &mut Criterion) {
                         opus_model: todo!(),
                         sonnet_model: todo!(),
                         haiku_model: todo!(),
+                        models: None,
+                        custom_headers: None,
+                        accepted_status_codes: None,
+                        model_auto_update: None,
                     };
 
                     service_clone.create_provider(request).await
@@ -245,6 +263,9 @@ fn bench_memory_allocation(c: &mut Criterion) {
                     // description: Some(format!("Test provider number {}", i)),
                     timeout: Some(30),
                     // retry_count: 3,
+                    custom_headers: Default::default(),
+                    accepted_status_codes: Default::default(),
+                    model_auto_update: 0,
                     created_at: Some(chrono::Utc::now().to_rfc3339()),
                     updated_at: Some(chrono::Utc::now().to_rfc3339()),
                     auto_update: todo!(),
@@ -252,6 +273,7 @@ fn bench_memory_allocation(c: &mut Criterion) {
                     opus_model: todo!(),
                     sonnet_model: todo!(),
                     haiku_model: todo!(),
+                    models: std::collections::HashMap::new(),
                 })
                 .collect();
             black_box(providers)
@@ -24,6 +24,7 @@ async fn create_test_database() -> (
         connect_timeout: std::time::Duration::from_secs(10),
         idle_timeout: std::time::Duration::from_secs(60),
         max_lifetime: std::time::Duration::from_secs(300),
+        ..Default::default()
     };
 
     let db_manager =
@@ -71,7 +72,11 @@ fn bench_batch_insert(c: &mut Criterion) {
                         opus_model: todo!(),
                         sonnet_model: todo!(),
                         haiku_model: todo!(),
+                        models: None,
                         // retry_count: Some(3),
+                        custom_headers: None,
+                        accepted_status_codes: None,
+                        model_auto_update: None,
                     };
 
                     repo.create_claude_provider(&request).await
@@ -108,7 +113,11 @@ fn bench_paginated_query(c: &mut Criterion) {
                 opus_model: todo!(),
                 sonnet_model: todo!(),
                 haiku_model: todo!(),
+                models: None,
                 // retry_count: Some(3),
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
             };
             repository.create_claude_provider(&request).await.unwrap();
         }
@@ -120,6 +129,7 @@ fn bench_paginated_query(c: &mut Criterion) {
                 page: black_box(Some(5)), // 查询第5页
                 limit: black_box(Some(20)),
                 offset: todo!(),
+                with_total: None,
             };
 
             let result = repository.paginate::<ClaudeProvider>(&params).await;
@@ -153,7 +163,11 @@ fn bench_search_query(c: &mut Criterion) {
                 opus_model: todo!(),
                 sonnet_model: todo!(),
                 haiku_model: todo!(),
+                models: None,
                 // retry_count: Some(3),
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
             };
             repository.create_claude_provider(&request).await.unwrap();
         }
@@ -162,7 +176,7 @@ fn bench_search_query(c: &mut Criterion) {
     c.bench_function("search_query", |b| {
         b.to_async(&rt).iter(|| async {
             let search_term = black_box("Alpha");
-            let result = repository.search_claude_providers(search_term, Some(10)).await;
+            let result = repository.search_claude_providers(search_term, Some(10), None).await;
             black_box(result)
         });
     });
@@ -249,7 +263,11 @@ fn bench_complex_query(c: &mut Criterion) {
                 opus_model: todo!(),
                 sonnet_model: todo!(),
                 haiku_model: todo!(),
+                models: None,
                 // retry_count: Some(i % 5),
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
             };
             repository.create_claude_provider(&request).await.unwrap();
         }
@@ -315,7 +333,11 @@ fn bench_indexed_query(c: &mut Criterion) {
                 opus_model: todo!(),
                 sonnet_model: todo!(),
                 haiku_model: todo!(),
+                models: None,
                 // retry_count: Some(3),
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
             };
             repository.create_claude_provider(&request).await.unwrap();
         }
@@ -324,7 +346,7 @@ fn bench_indexed_query(c: &mut Criterion) {
     c.bench_function("indexed_query", |b| {
         b.to_async(&rt).iter(|| async {
             // 使用索引查询启用的供应商
-            let result = repository.list_active_providers().await;
+            let result = repository.list_active_providers(None).await;
             black_box(result)
         });
     });
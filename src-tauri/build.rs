@@ -1,4 +1,15 @@
 // Tauri 2.x build script
 fn main() {
+    // 记录编译器版本，供诊断信息使用
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = std::process::Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=AI_MANAGER_RUSTC_VERSION={}", rustc_version);
+
     tauri_build::build();
 }
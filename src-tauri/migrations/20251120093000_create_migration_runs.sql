@@ -0,0 +1,20 @@
+-- 数据迁移运行记录表
+-- 持久化每次数据迁移的结果，便于用户回顾历史迁移
+
+CREATE TABLE IF NOT EXISTS "migration_runs" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "source" TEXT NOT NULL,  -- 数据来源，如 json、file:/path/to/export.json
+    "started_at" TEXT NOT NULL,
+    "finished_at" TEXT NOT NULL,
+    "total_migrated" INTEGER NOT NULL DEFAULT 0,
+    "claude_providers" INTEGER NOT NULL DEFAULT 0,
+    "codex_providers" INTEGER NOT NULL DEFAULT 0,
+    "agent_guides" INTEGER NOT NULL DEFAULT 0,
+    "mcp_servers" INTEGER NOT NULL DEFAULT 0,
+    "common_configs" INTEGER NOT NULL DEFAULT 0,
+    "errors_json" TEXT NOT NULL DEFAULT '[]',  -- 错误列表，存储为JSON字符串
+    "warnings_json" TEXT NOT NULL DEFAULT '[]',  -- 警告列表，存储为JSON字符串
+    "created_at" TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS "idx_migration_runs_started_at" ON "migration_runs"("started_at");
@@ -0,0 +1,13 @@
+-- 通用配置历史版本表
+-- 每次更新通用配置值时记录旧值，支持查看历史和回滚
+
+CREATE TABLE IF NOT EXISTS "common_config_history" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "config_id" INTEGER NOT NULL REFERENCES "common_configs"("id") ON DELETE CASCADE,
+    "version" INTEGER NOT NULL,  -- 每个配置内递增的版本号
+    "value" TEXT NOT NULL,  -- 与 common_configs.value 存储格式一致
+    "created_at" TEXT DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE ("config_id", "version")
+);
+
+CREATE INDEX IF NOT EXISTS "idx_common_config_history_config" ON "common_config_history"("config_id", "version");
@@ -0,0 +1,19 @@
+-- Claude供应商模型映射
+-- 新增 models 列（角色 -> 模型名称，JSON存储），并从旧的三个模型列回填
+
+ALTER TABLE "claude_providers" ADD COLUMN "models" TEXT NOT NULL DEFAULT '{}';
+
+UPDATE "claude_providers"
+SET "models" = json_patch(
+    json_patch(
+        json_patch(
+            '{}',
+            CASE WHEN "opus_model" IS NOT NULL AND "opus_model" != ''
+                THEN json_object('opus', "opus_model") ELSE '{}' END
+        ),
+        CASE WHEN "sonnet_model" IS NOT NULL AND "sonnet_model" != ''
+            THEN json_object('sonnet', "sonnet_model") ELSE '{}' END
+    ),
+    CASE WHEN "haiku_model" IS NOT NULL AND "haiku_model" != ''
+        THEN json_object('haiku', "haiku_model") ELSE '{}' END
+);
@@ -0,0 +1,20 @@
+-- 供应商分组表
+-- 一个分组包含每种工具各至多一个供应商，激活分组时同时启用组内供应商
+
+CREATE TABLE IF NOT EXISTS "provider_groups" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "name" TEXT NOT NULL UNIQUE,
+    "description" TEXT,
+    "created_at" TEXT DEFAULT CURRENT_TIMESTAMP,
+    "updated_at" TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS "provider_group_members" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "group_id" INTEGER NOT NULL REFERENCES "provider_groups"("id") ON DELETE CASCADE,
+    "provider_type" TEXT NOT NULL CHECK ("provider_type" IN ('claude', 'codex')),
+    "provider_id" INTEGER NOT NULL,  -- 对应 claude_providers / codex_providers 的 id
+    UNIQUE ("group_id", "provider_type")
+);
+
+CREATE INDEX IF NOT EXISTS "idx_provider_group_members_group" ON "provider_group_members"("group_id");
@@ -0,0 +1,4 @@
+-- MCP服务器机密环境变量
+-- 新增 secret_env_keys 列（JSON数组），列出的环境变量值在 env 中加密存储
+
+ALTER TABLE "mcp_servers" ADD COLUMN "secret_env_keys" TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,5 @@
+-- 供应商自定义请求头
+-- 新增 custom_headers 列（请求头名称 -> 值，JSON存储），用于连接测试和生成的配置文件
+
+ALTER TABLE "claude_providers" ADD COLUMN "custom_headers" TEXT NOT NULL DEFAULT '{}';
+ALTER TABLE "codex_providers" ADD COLUMN "custom_headers" TEXT NOT NULL DEFAULT '{}';
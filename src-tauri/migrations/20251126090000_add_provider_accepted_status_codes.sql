@@ -0,0 +1,5 @@
+-- 供应商连接测试的可接受状态码
+-- 新增 accepted_status_codes 列（JSON数组），为空时只接受2xx
+
+ALTER TABLE "claude_providers" ADD COLUMN "accepted_status_codes" TEXT NOT NULL DEFAULT '[]';
+ALTER TABLE "codex_providers" ADD COLUMN "accepted_status_codes" TEXT NOT NULL DEFAULT '[]';
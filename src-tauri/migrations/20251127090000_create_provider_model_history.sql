@@ -0,0 +1,14 @@
+-- 供应商模型变更历史表
+-- 自动更新服务修改供应商的模型名称时记录旧值和新值
+
+CREATE TABLE IF NOT EXISTS "provider_model_history" (
+    "id" INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    "provider_id" INTEGER NOT NULL REFERENCES "claude_providers"("id") ON DELETE CASCADE,
+    "role" TEXT NOT NULL,  -- 模型角色，如 opus、sonnet、haiku
+    "old_model" TEXT,  -- 之前未配置时为 NULL
+    "new_model" TEXT NOT NULL,
+    "source" TEXT NOT NULL DEFAULT 'auto_update',
+    "created_at" TEXT DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS "idx_provider_model_history_provider" ON "provider_model_history"("provider_id", "id");
@@ -0,0 +1,4 @@
+-- Claude供应商模型自动更新开关
+-- auto_update 列表示禁用遥测（生成配置文件时使用），模型自动更新使用独立的列，默认关闭
+
+ALTER TABLE "claude_providers" ADD COLUMN "model_auto_update" INTEGER NOT NULL DEFAULT 0;
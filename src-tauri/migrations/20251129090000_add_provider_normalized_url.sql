@@ -0,0 +1,53 @@
+-- 供应商规范化URL
+-- 按URL查找供应商时直接查询规范化后的URL，不再读取并解密所有供应商。
+-- 规范化规则在应用中实现：仓库写入供应商时同时写入该列，导入等其他途径写入的记录为NULL，查询前补全
+
+ALTER TABLE "claude_providers" ADD COLUMN "normalized_url" TEXT;
+ALTER TABLE "codex_providers" ADD COLUMN "normalized_url" TEXT;
+
+CREATE INDEX "idx_claude_providers_normalized_url" ON "claude_providers"("normalized_url");
+CREATE INDEX "idx_codex_providers_normalized_url" ON "codex_providers"("normalized_url");
+
+-- URL被修改但没有同时写入规范化URL时清空，查询前重新计算
+CREATE TRIGGER "reset_claude_providers_normalized_url"
+    AFTER UPDATE OF "url" ON "claude_providers"
+    FOR EACH ROW
+    WHEN NEW."url" IS NOT OLD."url" AND NEW."normalized_url" IS OLD."normalized_url"
+BEGIN
+    UPDATE "claude_providers"
+    SET "normalized_url" = NULL
+    WHERE "id" = NEW."id";
+END;
+
+CREATE TRIGGER "reset_codex_providers_normalized_url"
+    AFTER UPDATE OF "url" ON "codex_providers"
+    FOR EACH ROW
+    WHEN NEW."url" IS NOT OLD."url" AND NEW."normalized_url" IS OLD."normalized_url"
+BEGIN
+    UPDATE "codex_providers"
+    SET "normalized_url" = NULL
+    WHERE "id" = NEW."id";
+END;
+
+-- 只补全或清空规范化URL时不刷新修改时间
+DROP TRIGGER IF EXISTS "update_claude_providers_updated_at";
+CREATE TRIGGER "update_claude_providers_updated_at"
+    AFTER UPDATE ON "claude_providers"
+    FOR EACH ROW
+    WHEN NEW."url" IS NOT OLD."url" OR NEW."normalized_url" IS OLD."normalized_url"
+BEGIN
+    UPDATE "claude_providers"
+    SET "updated_at" = CURRENT_TIMESTAMP
+    WHERE "id" = NEW."id";
+END;
+
+DROP TRIGGER IF EXISTS "update_codex_providers_updated_at";
+CREATE TRIGGER "update_codex_providers_updated_at"
+    AFTER UPDATE ON "codex_providers"
+    FOR EACH ROW
+    WHEN NEW."url" IS NOT OLD."url" OR NEW."normalized_url" IS OLD."normalized_url"
+BEGIN
+    UPDATE "codex_providers"
+    SET "updated_at" = CURRENT_TIMESTAMP
+    WHERE "id" = NEW."id";
+END;
@@ -0,0 +1,8 @@
+-- 加密字段的密钥指纹
+-- 记录加密该行数据所用密钥的指纹（SHA-256摘要的前8位十六进制），密文本身保持Python版本可以读取的标准Fernet格式。
+-- 读取时只有解密失败才对照该列，与当前密钥不一致时提示密钥不匹配；明文和旧数据为NULL
+
+ALTER TABLE "claude_providers" ADD COLUMN "key_fingerprint" TEXT;
+ALTER TABLE "codex_providers" ADD COLUMN "key_fingerprint" TEXT;
+ALTER TABLE "common_configs" ADD COLUMN "key_fingerprint" TEXT;
+ALTER TABLE "mcp_servers" ADD COLUMN "key_fingerprint" TEXT;
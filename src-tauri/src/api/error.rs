@@ -26,7 +26,7 @@
 //! ```
 
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -34,6 +34,9 @@ use serde_json::json;
 use thiserror::Error;
 use tracing::error;
 
+/// 数据库连接池耗尽时建议客户端重试的等待时间（秒）
+pub const DB_TIMEOUT_RETRY_AFTER_SECS: u64 = 5;
+
 /// API错误类型
 ///
 /// 统一的应用程序错误处理，提供用户友好的中文错误信息
@@ -47,6 +50,10 @@ pub enum ApiError {
     #[error("输入验证失败: {message}")]
     ValidationError { message: String, field: Option<String> },
 
+    /// 多项输入验证失败 (400)
+    #[error("输入验证失败: {0}")]
+    InvalidFields(crate::ValidationErrors),
+
     /// 业务规则冲突 (409)
     #[error("业务规则冲突: {message}")]
     BusinessRule { message: String },
@@ -87,6 +94,18 @@ pub enum ApiError {
     #[error("服务暂时不可用，请稍后重试")]
     ServiceUnavailable,
 
+    /// 获取数据库连接超时 (503)
+    #[error("数据库繁忙，请稍后重试")]
+    DatabaseTimeout,
+
+    /// 请求处理超时 (504)
+    #[error("请求处理超时: {message}")]
+    GatewayTimeout { message: String },
+
+    /// 上游供应商响应超时 (504)
+    #[error("上游供应商响应超时（{timeout_ms} 毫秒）")]
+    UpstreamTimeout { provider_id: i64, timeout_ms: u64 },
+
     /// 配置错误 (500)
     #[error("配置错误: {message}")]
     Configuration { message: String },
@@ -98,11 +117,17 @@ impl ApiError {
         Self::ValidationError { message: message.into(), field: None }
     }
 
+    /// 创建上游超时错误，与请求超时中间件返回的504区分
+    pub fn upstream_timeout(provider_id: i64, timeout_ms: u64) -> Self {
+        Self::UpstreamTimeout { provider_id, timeout_ms }
+    }
+
     /// 获取HTTP状态码
     pub fn status_code(&self) -> StatusCode {
         match self {
             ApiError::BadRequest { .. } => StatusCode::BAD_REQUEST,
             ApiError::ValidationError { .. } => StatusCode::BAD_REQUEST,
+            ApiError::InvalidFields(_) => StatusCode::BAD_REQUEST,
             ApiError::BusinessRule { .. } => StatusCode::CONFLICT,
             ApiError::Unauthorized { .. } => StatusCode::UNAUTHORIZED,
             ApiError::Forbidden { .. } => StatusCode::FORBIDDEN,
@@ -113,6 +138,9 @@ impl ApiError {
             ApiError::Crypto { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             ApiError::Internal { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             ApiError::ServiceUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::DatabaseTimeout => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::GatewayTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            ApiError::UpstreamTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
             ApiError::Configuration { .. } => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -122,6 +150,7 @@ impl ApiError {
         match self {
             ApiError::BadRequest { .. } => "BAD_REQUEST",
             ApiError::ValidationError { .. } => "VALIDATION_ERROR",
+            ApiError::InvalidFields(_) => "VALIDATION_ERROR",
             ApiError::BusinessRule { .. } => "BUSINESS_RULE_VIOLATION",
             ApiError::Unauthorized { .. } => "UNAUTHORIZED",
             ApiError::Forbidden { .. } => "FORBIDDEN",
@@ -132,6 +161,9 @@ impl ApiError {
             ApiError::Crypto { .. } => "CRYPTO_ERROR",
             ApiError::Internal { .. } => "INTERNAL_ERROR",
             ApiError::ServiceUnavailable => "SERVICE_UNAVAILABLE",
+            ApiError::DatabaseTimeout => "DB_TIMEOUT",
+            ApiError::GatewayTimeout { .. } => "GATEWAY_TIMEOUT",
+            ApiError::UpstreamTimeout { .. } => "UPSTREAM_TIMEOUT",
             ApiError::Configuration { .. } => "CONFIGURATION_ERROR",
         }
     }
@@ -160,7 +192,13 @@ impl IntoResponse for ApiError {
             "timestamp": chrono::Utc::now().to_rfc3339()
         });
 
-        (status_code, Json(error_response)).into_response()
+        let mut response = (status_code, Json(error_response)).into_response();
+        if let ApiError::DatabaseTimeout = self {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, DB_TIMEOUT_RETRY_AFTER_SECS.into());
+        }
+        response
     }
 }
 
@@ -168,17 +206,34 @@ impl ApiError {
     /// 获取错误详情
     fn get_details(&self) -> Option<serde_json::Value> {
         match self {
-            ApiError::ValidationError { field, .. } => field.as_ref().map(|f| {
-                json!({
-                    "field": f
-                })
-            }),
+            ApiError::ValidationError { message, field } => Some(json!({
+                "field": field,
+                "errors": [{ "field": field, "message": message }]
+            })),
+            ApiError::InvalidFields(errors) => Some(json!({
+                "errors": errors
+                    .errors
+                    .iter()
+                    .map(|e| {
+                        let mut detail = json!({ "field": e.field, "message": e.message });
+                        if let (Some(max), Some(actual)) = (e.max, e.actual) {
+                            detail["max"] = json!(max);
+                            detail["actual"] = json!(actual);
+                        }
+                        detail
+                    })
+                    .collect::<Vec<_>>()
+            })),
             ApiError::Database { .. } => Some(json!({
                 "type": "database_operation"
             })),
             ApiError::Crypto { .. } => Some(json!({
                 "type": "encryption_operation"
             })),
+            ApiError::UpstreamTimeout { provider_id, timeout_ms } => Some(json!({
+                "provider_id": provider_id,
+                "timeout_ms": timeout_ms
+            })),
             _ => None,
         }
     }
@@ -192,6 +247,7 @@ impl From<sqlx::Error> for ApiError {
     fn from(err: sqlx::Error) -> Self {
         error!("数据库错误: {}", err);
         match err {
+            sqlx::Error::PoolTimedOut => ApiError::DatabaseTimeout,
             sqlx::Error::RowNotFound => {
                 ApiError::NotFound { resource: "记录不存在".to_string() }
             }
@@ -211,9 +267,42 @@ impl From<sqlx::Error> for ApiError {
     }
 }
 
+/// 从数据库管理错误转换
+impl From<crate::database::DatabaseError> for ApiError {
+    fn from(err: crate::database::DatabaseError) -> Self {
+        match err {
+            crate::database::DatabaseError::Timeout => ApiError::DatabaseTimeout,
+            err => {
+                error!("数据库错误: {}", err);
+                ApiError::Database { message: err.to_string() }
+            }
+        }
+    }
+}
+
+/// 从Repository错误转换，连接池获取连接超时返回503
+impl From<crate::repositories::base_repository::RepositoryError> for ApiError {
+    fn from(err: crate::repositories::base_repository::RepositoryError) -> Self {
+        match err {
+            crate::repositories::base_repository::RepositoryError::Database(
+                sqlx::Error::PoolTimedOut,
+            ) => ApiError::DatabaseTimeout,
+            err => {
+                error!("数据库错误: {}", err);
+                ApiError::Database { message: err.to_string() }
+            }
+        }
+    }
+}
+
 /// 从加密错误转换
 impl From<crate::crypto::CryptoError> for ApiError {
     fn from(err: crate::crypto::CryptoError) -> Self {
+        // 明文过大是请求数据的问题，按验证错误返回400
+        if let crate::crypto::CryptoError::TooLarge { .. } = err {
+            return ApiError::validation(err.to_string());
+        }
+
         error!("加密错误: {}", err);
         ApiError::Crypto { message: format!("数据处理失败: {}", err) }
     }
@@ -246,8 +335,103 @@ impl From<serde_json::Error> for ApiError {
     }
 }
 
+/// 从单个验证错误转换
 impl From<crate::ValidationError> for ApiError {
     fn from(err: crate::ValidationError) -> Self {
-        ApiError::validation(err.to_string())
+        // 超长错误需要在详情中保留 max/actual
+        if err.max.is_some() {
+            return ApiError::InvalidFields(err.into());
+        }
+        ApiError::ValidationError { message: err.message, field: err.field }
+    }
+}
+
+/// 从多项验证错误转换
+impl From<crate::ValidationErrors> for ApiError {
+    fn from(err: crate::ValidationErrors) -> Self {
+        ApiError::InvalidFields(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ValidationError, ValidationErrors, Validator};
+
+    #[tokio::test]
+    async fn test_validation_errors_into_api_error() {
+        let mut errors = ValidationErrors::new();
+        errors.check(Validator::validate_provider_name(""));
+        errors.check(Validator::validate_url("not-a-url"));
+        errors.push(ValidationError::with_field("Token不能为空", "token"));
+
+        let api_error: ApiError = errors.into_result().unwrap_err().into();
+        assert_eq!(api_error.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(api_error.error_code(), "VALIDATION_ERROR");
+
+        let response = api_error.into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let details = body["error"]["details"]["errors"].as_array().unwrap();
+
+        assert_eq!(details.len(), 3);
+        assert_eq!(details[2]["field"], "token");
+        assert_eq!(details[2]["message"], "Token不能为空");
+    }
+
+    #[tokio::test]
+    async fn test_database_timeout_returns_503_with_retry_after() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = crate::database::DatabaseConfig {
+            url: format!(
+                "sqlite:{}",
+                temp_dir.path().join("test_timeout.db").display()
+            ),
+            max_connections: 1,
+            min_connections: 1,
+            connect_timeout: std::time::Duration::from_millis(200),
+            ..Default::default()
+        };
+        let db_manager = crate::database::DatabaseManager::new(config).await.unwrap();
+
+        // 占用唯一的连接，之后的查询无法获取连接
+        let _conn = db_manager.pool().acquire().await.unwrap();
+        let err = db_manager.test_connection().await.unwrap_err();
+        assert!(
+            matches!(err, crate::database::DatabaseError::Timeout),
+            "{:?}",
+            err
+        );
+
+        let api_error: ApiError = err.into();
+        assert_eq!(api_error.error_code(), "DB_TIMEOUT");
+        let response = api_error.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(
+            response.headers()[header::RETRY_AFTER],
+            DB_TIMEOUT_RETRY_AFTER_SECS.to_string().as_str()
+        );
+    }
+
+    #[test]
+    fn test_repository_pool_timeout_maps_to_database_timeout() {
+        use crate::repositories::base_repository::RepositoryError;
+
+        let api_error: ApiError = RepositoryError::Database(sqlx::Error::PoolTimedOut).into();
+        assert_eq!(api_error.error_code(), "DB_TIMEOUT");
+        assert_eq!(api_error.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let api_error: ApiError = RepositoryError::Query("无效的查询".to_string()).into();
+        assert_eq!(api_error.error_code(), "DATABASE_ERROR");
+    }
+
+    #[test]
+    fn test_single_validation_error_keeps_field() {
+        let api_error: ApiError = ValidationError::with_field("无效的ID", "id").into();
+
+        assert_eq!(api_error.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(api_error.get_details().unwrap()["field"], "id");
     }
 }
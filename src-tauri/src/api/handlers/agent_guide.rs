@@ -3,7 +3,7 @@
 // 提供Agent指导文件的HTTP API接口实现
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{OriginalUri, Path, Query, State},
     response::Json,
     Router,
 };
@@ -11,10 +11,12 @@ use serde::Deserialize;
 use tracing::{error, info, warn};
 
 use crate::api::error::ApiError;
-use crate::api::responses::{ApiResponse, PagedResponse};
+use crate::api::responses::{ApiResponse, PagedJson};
 use crate::models::{
-    AgentGuide, CreateAgentGuideRequest, PaginationParams, UpdateAgentGuideRequest,
+    AgentGuide, CreateAgentGuideRequest, OrderBy, PaginationParams, UpdateAgentGuideRequest,
+    UpdateResult,
 };
+use crate::repositories::base_repository::RepositoryError;
 use crate::repositories::{AgentGuideRepository, BaseRepository};
 use crate::Validator;
 
@@ -29,6 +31,12 @@ pub struct AgentGuideQuery {
     pub page: Option<i64>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// 为 `false` 时不查询总数
+    pub with_total: Option<bool>,
+    /// 排序列，如 `name`、`updated_at`，未指定时使用默认顺序
+    pub sort: Option<String>,
+    /// 排序方向，`asc`（默认）或 `desc`
+    pub dir: Option<String>,
 }
 
 /// 创建Agent指导文件
@@ -76,7 +84,7 @@ pub async fn create_agent_guide(
             name = %request.name,
             "创建Agent指导文件失败"
         );
-        ApiError::Database { message: format!("创建Agent指导文件失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     // 获取创建的记录
@@ -86,7 +94,7 @@ pub async fn create_agent_guide(
             id = %id,
             "获取新创建的Agent指导文件失败"
         );
-        ApiError::Database { message: format!("获取Agent指导文件失败: {}", e) }
+        ApiError::from(e)
     })? {
         info!(
             id = %id,
@@ -131,10 +139,7 @@ pub async fn get_agent_guide(
 
     let repository = AgentGuideRepository::new(&state.db_manager, &state.crypto_service);
 
-    if id <= 0 {
-        Validator::validate_id(id, "id")
-            .map_err(|_| ApiError::validation("无效的ID".to_string()))?;
-    }
+    Validator::validate_id(id, "id")?;
 
     match repository.find_by_id_decrypted(id).await {
         Ok(Some(guide)) => {
@@ -162,17 +167,17 @@ pub async fn get_agent_guide(
                 id = %id,
                 "获取Agent指导文件详情失败"
             );
-            Err(ApiError::Database { message: format!("获取Agent指导文件失败: {}", e) })
+            Err(ApiError::from(e))
         }
     }
 }
 
-/// 更新Agent指导文件
+/// 更新Agent指导文件，返回更新后的记录和发生变化的字段
 pub async fn update_agent_guide(
     State(state): State<ApiState>,
     Path(id): Path<i64>,
     Json(request): Json<UpdateAgentGuideRequest>,
-) -> Result<Json<ApiResponse<AgentGuide>>, ApiError> {
+) -> Result<Json<ApiResponse<UpdateResult<AgentGuide>>>, ApiError> {
     info!(
         id = %id,
         "更新Agent指导文件请求"
@@ -180,10 +185,7 @@ pub async fn update_agent_guide(
 
     let repository = AgentGuideRepository::new(&state.db_manager, &state.crypto_service);
 
-    if id <= 0 {
-        Validator::validate_id(id, "id")
-            .map_err(|_| ApiError::validation("无效的ID".to_string()))?;
-    }
+    Validator::validate_id(id, "id")?;
 
     // 检查记录是否存在
     let existing = repository.find_by_id_decrypted(id).await.map_err(|e| {
@@ -192,22 +194,20 @@ pub async fn update_agent_guide(
             id = %id,
             "检查Agent指导文件是否存在失败"
         );
-        ApiError::Database { message: format!("检查Agent指导文件失败: {}", e) }
+        ApiError::from(e)
     })?;
 
-    if existing.is_none() {
+    let Some(existing) = existing else {
         warn!(
             id = %id,
             "尝试更新不存在的Agent指导文件"
         );
         return Err(ApiError::NotFound { resource: "Agent指导文件不存在".to_string() });
-    }
+    };
 
     // 验证更新数据
     if let Some(ref name) = request.name {
-        if name.trim().is_empty() {
-            return Err(ApiError::validation("指导文件名称不能为空".to_string()));
-        }
+        Validator::validate_agent_guide_name(name)?;
     }
 
     if let Some(ref text) = request.text {
@@ -229,7 +229,7 @@ pub async fn update_agent_guide(
             id = %id,
             "更新Agent指导文件失败"
         );
-        ApiError::Database { message: format!("更新Agent指导文件失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     if !updated {
@@ -247,16 +247,18 @@ pub async fn update_agent_guide(
             id = %id,
             "获取更新后的Agent指导文件失败"
         );
-        ApiError::Database { message: format!("获取Agent指导文件失败: {}", e) }
+        ApiError::from(e)
     })? {
+        let result = UpdateResult::diff(&existing, guide);
         info!(
             id = %id,
-            name = %guide.name,
+            name = %result.entity.name,
+            changed_fields = ?result.changed_fields,
             "Agent指导文件更新成功"
         );
 
         Ok(Json(ApiResponse::success_with_message(
-            guide,
+            result,
             "Agent指导文件更新成功".to_string(),
         )))
     } else {
@@ -282,10 +284,7 @@ pub async fn delete_agent_guide(
 
     let repository = AgentGuideRepository::new(&state.db_manager, &state.crypto_service);
 
-    if id <= 0 {
-        Validator::validate_id(id, "id")
-            .map_err(|_| ApiError::validation("无效的ID".to_string()))?;
-    }
+    Validator::validate_id(id, "id")?;
 
     // 检查记录是否存在
     let existing = repository.find_by_id::<AgentGuide>(id).await.map_err(|e| {
@@ -294,7 +293,7 @@ pub async fn delete_agent_guide(
             id = %id,
             "检查Agent指导文件是否存在失败"
         );
-        ApiError::Database { message: format!("检查Agent指导文件失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     if existing.is_none() {
@@ -312,7 +311,7 @@ pub async fn delete_agent_guide(
             id = %id,
             "删除Agent指导文件失败"
         );
-        ApiError::Database { message: format!("删除Agent指导文件失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     if !deleted {
@@ -338,7 +337,8 @@ pub async fn delete_agent_guide(
 pub async fn list_agent_guides(
     State(state): State<ApiState>,
     Query(query): Query<AgentGuideQuery>,
-) -> Result<Json<ApiResponse<PagedResponse<AgentGuide>>>, ApiError> {
+    OriginalUri(uri): OriginalUri,
+) -> Result<PagedJson<AgentGuide>, ApiError> {
     info!(
         search = ?query.search,
         guide_type = ?query.guide_type,
@@ -349,17 +349,30 @@ pub async fn list_agent_guides(
 
     let repository = AgentGuideRepository::new(&state.db_manager, &state.crypto_service);
 
+    // 所有列表模式都按 `sort` 排序
+    let order_by = OrderBy::from_query(
+        query.sort.as_deref(),
+        query.dir.as_deref(),
+        AgentGuideRepository::sortable_columns(),
+    )
+    .map_err(ApiError::validation)?;
+
     let result = if let Some(search_term) = query.search {
-        // 搜索模式
+        // 搜索模式，与供应商搜索使用相同的搜索词校验
+        Validator::validate_search_term(search_term.trim())?;
         let limit = query.limit.or(Some(50));
-        let guides = repository.search_agent_guides(&search_term, limit).await.map_err(|e| {
-            error!(
-                error = %e,
-                search_term = %search_term,
-                "搜索Agent指导文件失败"
-            );
-            ApiError::Database { message: format!("搜索Agent指导文件失败: {}", e) }
-        })?;
+        let guides =
+            repository
+                .search_agent_guides(&search_term, limit, order_by)
+                .await
+                .map_err(|e| {
+                    error!(
+                        error = %e,
+                        search_term = %search_term,
+                        "搜索Agent指导文件失败"
+                    );
+                    ApiError::from(e)
+                })?;
 
         // 转换为分页响应格式
         let total = guides.len() as i64;
@@ -374,15 +387,13 @@ pub async fn list_agent_guides(
         paged_result
     } else if let Some(guide_type) = query.guide_type {
         // 按类型筛选
-        let guides = repository.find_by_type(&guide_type).await.map_err(|e| {
+        let guides = repository.find_by_type(&guide_type, order_by).await.map_err(|e| {
             error!(
                 error = %e,
                 guide_type = %guide_type,
                 "根据类型获取Agent指导文件列表失败"
             );
-            ApiError::Database {
-                message: format!("获取Agent指导文件列表失败: {}", e)
-            }
+            ApiError::from(e)
         })?;
 
         // 转换为分页响应格式
@@ -398,17 +409,20 @@ pub async fn list_agent_guides(
         paged_result
     } else {
         // 分页获取所有指导文件
-        let pagination_params =
-            PaginationParams { page: query.page, limit: query.limit, offset: query.offset };
+        let pagination_params = PaginationParams {
+            page: query.page,
+            limit: query.limit,
+            offset: query.offset,
+            with_total: query.with_total,
+            order_by,
+        };
 
         repository.paginate::<AgentGuide>(&pagination_params).await.map_err(|e| {
             error!(
                 error = %e,
                 "分页获取Agent指导文件列表失败"
             );
-            ApiError::Database {
-                message: format!("获取Agent指导文件列表失败: {}", e)
-            }
+            ApiError::from(e)
         })?
     };
 
@@ -417,7 +431,7 @@ pub async fn list_agent_guides(
         "获取Agent指导文件列表成功".to_string(),
     );
 
-    Ok(Json(ApiResponse::success(paged_response)))
+    Ok(PagedJson::new(paged_response, &uri))
 }
 
 /// 验证Agent指导文件内容
@@ -432,10 +446,7 @@ pub async fn validate_agent_guide(
 
     let repository = AgentGuideRepository::new(&state.db_manager, &state.crypto_service);
 
-    if id <= 0 {
-        Validator::validate_id(id, "id")
-            .map_err(|_| ApiError::validation("无效的ID".to_string()))?;
-    }
+    Validator::validate_id(id, "id")?;
 
     match repository.validate_guide_content(id).await {
         Ok(is_valid) => {
@@ -455,13 +466,14 @@ pub async fn validate_agent_guide(
                 message.to_string(),
             )))
         }
+        Err(RepositoryError::NotFound(resource)) => Err(ApiError::NotFound { resource }),
         Err(e) => {
             error!(
                 error = %e,
                 id = %id,
                 "Agent指导文件内容验证失败"
             );
-            Err(ApiError::Database { message: format!("内容验证失败: {}", e) })
+            Err(ApiError::from(e))
         }
     }
 }
@@ -480,7 +492,7 @@ pub async fn get_agent_guide_stats(
             error = %e,
             "获取Agent指导文件总数失败"
         );
-        ApiError::Database { message: format!("获取统计信息失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     // 获取only类型数量
@@ -489,7 +501,7 @@ pub async fn get_agent_guide_stats(
             error = %e,
             "获取only类型Agent指导文件数量失败"
         );
-        ApiError::Database { message: format!("获取统计信息失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     // 获取and类型数量
@@ -498,7 +510,7 @@ pub async fn get_agent_guide_stats(
             error = %e,
             "获取and类型Agent指导文件数量失败"
         );
-        ApiError::Database { message: format!("获取统计信息失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     let stats = serde_json::json!({
@@ -3,33 +3,44 @@
 // 提供Claude供应商的HTTP API接口实现
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{OriginalUri, Path, Query, State},
+    response::sse::{Event, KeepAlive, Sse},
     response::Json,
     routing::{delete, get, post, put},
     Router,
 };
+use futures::channel::mpsc;
+use futures::{SinkExt, StreamExt};
 use serde::Deserialize;
-use tracing::{error, info, warn};
+use std::time::Instant;
+use tracing::{error, info, warn, Instrument};
 
 use crate::api::error::ApiError;
-use crate::api::responses::{ApiResponse, PagedResponse};
+use crate::api::responses::{ApiResponse, PagedJson};
 use crate::models::{
-    ClaudeProvider, CreateClaudeProviderRequest, PaginationParams, UpdateClaudeProviderRequest,
+    ClaudeProvider, CreateClaudeProviderRequest, OrderBy, PaginationParams,
+    TestProviderCredentialsRequest, UpdateClaudeProviderRequest, UpdateResult,
 };
 use crate::services::claude_service::ClaudeServiceError;
+use crate::services::connection_test::{
+    ConnectionTestResult, ProviderTestSummary, DEFAULT_BATCH_TEST_CONCURRENCY,
+    MAX_BATCH_TEST_CONCURRENCY,
+};
+use crate::services::outcome::ServiceOutcome;
+use crate::utils::redaction::redact_url;
 
 // 使用服务器模块中的ApiState
 use crate::api::server::ApiState;
+use crate::repositories::{BaseRepository, ClaudeProviderRepository};
 
 /// 将Service错误转换为API错误
 impl From<ClaudeServiceError> for ApiError {
     fn from(err: ClaudeServiceError) -> Self {
         match err {
             ClaudeServiceError::Validation(msg) => ApiError::validation(msg),
+            ClaudeServiceError::InvalidField(error) => error.into(),
             ClaudeServiceError::BusinessRule(msg) => ApiError::BusinessRule { message: msg },
-            ClaudeServiceError::Repository(repo_err) => {
-                ApiError::Database { message: repo_err.to_string() }
-            }
+            ClaudeServiceError::Repository(repo_err) => repo_err.into(),
             ClaudeServiceError::ProviderNotFound(id) => {
                 ApiError::NotFound { resource: format!("供应商 {} 不存在", id) }
             }
@@ -39,6 +50,10 @@ impl From<ClaudeServiceError> for ApiError {
             ClaudeServiceError::NoActiveProvider => {
                 ApiError::NotFound { resource: "没有启用的供应商".to_string() }
             }
+            ClaudeServiceError::UpstreamTimeout { id, timeout_ms } => {
+                ApiError::upstream_timeout(id, timeout_ms)
+            }
+            ClaudeServiceError::ConnectionTest(message) => ApiError::Internal { message },
         }
     }
 }
@@ -51,6 +66,12 @@ pub struct ClaudeProviderQuery {
     pub page: Option<i64>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// 为 `false` 时不查询总数
+    pub with_total: Option<bool>,
+    /// 排序列，如 `name`、`updated_at`，未指定时使用默认顺序
+    pub sort: Option<String>,
+    /// 排序方向，`asc`（默认）或 `desc`
+    pub dir: Option<String>,
 }
 
 /// 创建Claude供应商
@@ -65,13 +86,14 @@ pub async fn create_claude_provider(
     );
 
     // 使用Service层创建供应商
-    let id = state.claude_service.create_provider(request).await.map_err(|e| {
-        error!(
-            error = %e,
-            "创建Claude供应商失败"
-        );
-        ApiError::from(e)
-    })?;
+    let ServiceOutcome { value: id, warnings } =
+        state.claude_service.create_provider_with_warnings(request).await.map_err(|e| {
+            error!(
+                error = %e,
+                "创建Claude供应商失败"
+            );
+            ApiError::from(e)
+        })?;
 
     // 获取创建的记录
     if let Some(provider) = state.claude_service.get_provider(id).await.map_err(|e| {
@@ -88,10 +110,10 @@ pub async fn create_claude_provider(
             "Claude供应商创建成功"
         );
 
-        Ok(Json(ApiResponse::success_with_message(
-            provider,
-            "Claude供应商创建成功".to_string(),
-        )))
+        Ok(Json(
+            ApiResponse::success_with_message(provider, "Claude供应商创建成功".to_string())
+                .with_warnings(warnings),
+        ))
     } else {
         error!(
             id = %id,
@@ -144,76 +166,65 @@ pub async fn get_claude_provider(
     }
 }
 
-/// 更新Claude供应商
+/// 更新Claude供应商，返回更新后的记录和发生变化的字段
 pub async fn update_claude_provider(
     State(state): State<ApiState>,
     Path(id): Path<i64>,
     Json(request): Json<UpdateClaudeProviderRequest>,
-) -> Result<Json<ApiResponse<ClaudeProvider>>, ApiError> {
+) -> Result<Json<ApiResponse<UpdateResult<ClaudeProvider>>>, ApiError> {
     info!(
         id = %id,
         "更新Claude供应商请求"
     );
 
     // 执行更新
-    let updated = state.claude_service.update_provider(id, request).await.map_err(|e| {
-        error!(
-            error = %e,
-            id = %id,
-            "更新Claude供应商失败"
-        );
-        ApiError::from(e)
-    })?;
+    let ServiceOutcome { value: result, warnings } = state
+        .claude_service
+        .update_provider_with_warnings(id, request)
+        .await
+        .map_err(|e| {
+            error!(
+                error = %e,
+                id = %id,
+                "更新Claude供应商失败"
+            );
+            ApiError::from(e)
+        })?;
 
-    if !updated {
-        error!(
-            id = %id,
-            "更新Claude供应商未影响任何记录"
-        );
-        return Err(ApiError::Internal { message: "更新Claude供应商失败".to_string() });
-    }
+    info!(
+        id = %id,
+        name = %result.entity.name,
+        changed_fields = ?result.changed_fields,
+        "Claude供应商更新成功"
+    );
 
-    // 获取更新后的记录
-    if let Some(provider) = state.claude_service.get_provider(id).await.map_err(|e| {
-        error!(
-            error = %e,
-            id = %id,
-            "获取更新后的Claude供应商失败"
-        );
-        ApiError::from(e)
-    })? {
-        info!(
-            id = %id,
-            name = %provider.name,
-            "Claude供应商更新成功"
-        );
+    Ok(Json(
+        ApiResponse::success_with_message(result, "Claude供应商更新成功".to_string())
+            .with_warnings(warnings),
+    ))
+}
 
-        Ok(Json(ApiResponse::success_with_message(
-            provider,
-            "Claude供应商更新成功".to_string(),
-        )))
-    } else {
-        error!(
-            id = %id,
-            "更新Claude供应商后无法找到记录"
-        );
-        Err(ApiError::Internal {
-            message: "更新Claude供应商后无法找到记录".to_string()
-        })
-    }
+/// 删除供应商的查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct DeleteProviderQuery {
+    /// 为 `true` 时允许删除唯一启用的供应商
+    pub force: Option<bool>,
 }
 
 /// 删除Claude供应商
 pub async fn delete_claude_provider(
     State(state): State<ApiState>,
     Path(id): Path<i64>,
+    Query(query): Query<DeleteProviderQuery>,
 ) -> Result<Json<ApiResponse<()>>, ApiError> {
+    let force = query.force.unwrap_or(false);
     info!(
         id = %id,
+        force = %force,
         "删除Claude供应商请求"
     );
 
-    let deleted = state.claude_service.delete_provider(id).await.map_err(|e| {
+    let deleted = state.claude_service.delete_provider(id, force).await.map_err(|e| {
         error!(
             error = %e,
             id = %id,
@@ -245,7 +256,8 @@ pub async fn delete_claude_provider(
 pub async fn list_claude_providers(
     State(state): State<ApiState>,
     Query(query): Query<ClaudeProviderQuery>,
-) -> Result<Json<ApiResponse<PagedResponse<ClaudeProvider>>>, ApiError> {
+    OriginalUri(uri): OriginalUri,
+) -> Result<PagedJson<ClaudeProvider>, ApiError> {
     info!(
         search = ?query.search,
         active_only = ?query.active_only,
@@ -254,11 +266,22 @@ pub async fn list_claude_providers(
         "获取Claude供应商列表请求"
     );
 
+    // 所有列表模式都按 `sort` 排序
+    let order_by = OrderBy::from_query(
+        query.sort.as_deref(),
+        query.dir.as_deref(),
+        ClaudeProviderRepository::sortable_columns(),
+    )
+    .map_err(ApiError::validation)?;
+
     let result = if let Some(search_term) = query.search {
         // 搜索模式
         let limit = query.limit.or(Some(50));
-        let providers =
-            state.claude_service.search_providers(&search_term, limit).await.map_err(|e| {
+        let providers = state
+            .claude_service
+            .search_providers(&search_term, limit, order_by)
+            .await
+            .map_err(|e| {
                 error!(
                     error = %e,
                     search_term = %search_term,
@@ -280,13 +303,14 @@ pub async fn list_claude_providers(
         paged_result
     } else if query.active_only.unwrap_or(false) {
         // 仅获取活跃供应商
-        let providers = state.claude_service.list_active_providers().await.map_err(|e| {
-            error!(
-                error = %e,
-                "获取活跃Claude供应商列表失败"
-            );
-            ApiError::from(e)
-        })?;
+        let providers =
+            state.claude_service.list_active_providers(order_by).await.map_err(|e| {
+                error!(
+                    error = %e,
+                    "获取活跃Claude供应商列表失败"
+                );
+                ApiError::from(e)
+            })?;
 
         // 转换为分页响应格式
         let total = providers.len() as i64;
@@ -300,8 +324,13 @@ pub async fn list_claude_providers(
         paged_result
     } else {
         // 分页获取所有供应商
-        let pagination_params =
-            PaginationParams { page: query.page, limit: query.limit, offset: query.offset };
+        let pagination_params = PaginationParams {
+            page: query.page,
+            limit: query.limit,
+            offset: query.offset,
+            with_total: query.with_total,
+            order_by,
+        };
 
         state.claude_service.list_providers(pagination_params).await.map_err(|e| {
             error!(
@@ -317,34 +346,34 @@ pub async fn list_claude_providers(
         "获取Claude供应商列表成功".to_string(),
     );
 
-    Ok(Json(ApiResponse::success(paged_response)))
+    Ok(PagedJson::new(paged_response, &uri))
 }
 
 /// 测试Claude供应商连接
 pub async fn test_claude_provider_connection(
     State(state): State<ApiState>,
     Path(id): Path<i64>,
-) -> Result<Json<ApiResponse<bool>>, ApiError> {
+) -> Result<Json<ApiResponse<ConnectionTestResult>>, ApiError> {
     info!(
         id = %id,
         "测试Claude供应商连接请求"
     );
 
     match state.claude_service.test_provider_connection(id).await {
-        Ok(success) => {
+        Ok(result) => {
             info!(
                 id = %id,
-                success = %success,
+                success = %result.success,
                 "Claude供应商连接测试完成"
             );
 
-            let message = if success {
+            let message = if result.success {
                 "连接测试成功"
             } else {
                 "连接测试失败"
             };
             Ok(Json(ApiResponse::success_with_message(
-                success,
+                result,
                 message.to_string(),
             )))
         }
@@ -359,6 +388,96 @@ pub async fn test_claude_provider_connection(
     }
 }
 
+/// 使用未保存的凭据测试Claude供应商连接
+pub async fn test_claude_provider_credentials(
+    State(state): State<ApiState>,
+    Json(request): Json<TestProviderCredentialsRequest>,
+) -> Result<Json<ApiResponse<ConnectionTestResult>>, ApiError> {
+    info!(
+        url = %redact_url(&request.url),
+        "测试未保存的Claude供应商凭据请求"
+    );
+
+    let result = state.claude_service.test_credentials(&request).await;
+    // 测试结束后立即清零请求中的Token
+    drop(request);
+    let result = result.map_err(|e| {
+        error!(
+            error = %e,
+            "测试未保存的Claude供应商凭据失败"
+        );
+        ApiError::from(e)
+    })?;
+
+    let message = if result.success {
+        "连接测试成功"
+    } else {
+        "连接测试失败"
+    };
+    Ok(Json(ApiResponse::success_with_message(
+        result,
+        message.to_string(),
+    )))
+}
+
+/// 批量连接测试的查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct TestAllQuery {
+    /// 同时测试的供应商数量
+    pub concurrency: Option<usize>,
+}
+
+/// 测试所有Claude供应商的连接，以SSE逐个推送结果
+///
+/// 每个供应商测试完成后发送一个 `result` 事件，全部完成后发送一个 `summary` 事件
+pub async fn stream_test_all_claude_providers(
+    State(state): State<ApiState>,
+    Query(query): Query<TestAllQuery>,
+) -> Result<Sse<mpsc::Receiver<Result<Event, axum::Error>>>, ApiError> {
+    let concurrency = query.concurrency.unwrap_or(DEFAULT_BATCH_TEST_CONCURRENCY);
+    if !(1..=MAX_BATCH_TEST_CONCURRENCY).contains(&concurrency) {
+        return Err(ApiError::validation(format!(
+            "并发数必须在 1 到 {} 之间",
+            MAX_BATCH_TEST_CONCURRENCY
+        )));
+    }
+    info!(concurrency = %concurrency, "批量测试Claude供应商连接请求");
+
+    let results = state.claude_service.test_all_providers(concurrency).await?;
+    let (mut sender, receiver) = mpsc::channel(concurrency);
+
+    tokio::spawn(
+        async move {
+            let started = Instant::now();
+            let mut results = std::pin::pin!(results);
+            let mut summary = ProviderTestSummary::default();
+
+            while let Some(outcome) = results.next().await {
+                summary.record(&outcome);
+                let event = Event::default().event("result").json_data(&outcome);
+                if sender.send(event).await.is_err() {
+                    warn!(tested = %summary.total, "客户端已断开，停止批量连接测试");
+                    return;
+                }
+            }
+
+            summary.duration_ms = started.elapsed().as_millis() as u64;
+            info!(
+                total = %summary.total,
+                succeeded = %summary.succeeded,
+                failed = %summary.failed,
+                duration_ms = %summary.duration_ms,
+                "Claude供应商批量连接测试完成"
+            );
+            let event = Event::default().event("summary").json_data(&summary);
+            let _ = sender.send(event).await;
+        }
+        .in_current_span(),
+    );
+
+    Ok(Sse::new(receiver).keep_alive(KeepAlive::default()))
+}
+
 /// 获取Claude供应商统计信息
 pub async fn get_claude_provider_stats(
     State(state): State<ApiState>,
@@ -391,14 +510,15 @@ pub async fn enable_claude_provider(
         "启用Claude供应商请求"
     );
 
-    let enabled = state.claude_service.enable_provider(id).await.map_err(|e| {
-        error!(
-            error = %e,
-            id = %id,
-            "启用Claude供应商失败"
-        );
-        ApiError::from(e)
-    })?;
+    let ServiceOutcome { value: enabled, warnings } =
+        state.claude_service.enable_provider_with_warnings(id).await.map_err(|e| {
+            error!(
+                error = %e,
+                id = %id,
+                "启用Claude供应商失败"
+            );
+            ApiError::from(e)
+        })?;
 
     if !enabled {
         error!(
@@ -413,10 +533,10 @@ pub async fn enable_claude_provider(
         "Claude供应商启用成功"
     );
 
-    Ok(Json(ApiResponse::success_with_message(
-        (),
-        "Claude供应商启用成功".to_string(),
-    )))
+    Ok(Json(
+        ApiResponse::success_with_message((), "Claude供应商启用成功".to_string())
+            .with_warnings(warnings),
+    ))
 }
 
 /// 禁用Claude供应商
@@ -513,4 +633,8 @@ pub fn routes() -> Router<ApiState> {
         .route("/:id/disable", post(disable_claude_provider))
         // 测试Claude供应商连接
         .route("/:id/test", get(test_claude_provider_connection))
+        // 使用未保存的凭据测试连接
+        .route("/test", post(test_claude_provider_credentials))
+        // 测试所有供应商的连接，以SSE推送进度
+        .route("/test-all/stream", get(stream_test_all_claude_providers))
 }
@@ -356,6 +356,7 @@ pub async fn list_claude_providers(
             page: query.page,
             limit: query.limit,
             offset: query.offset,
+            with_total: None,
         };
 
         repository.paginate::<ClaudeProvider>(&pagination_params).await.map_err(|e| {
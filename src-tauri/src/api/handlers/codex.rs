@@ -3,7 +3,7 @@
 // 提供Codex供应商的HTTP API接口实现
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{OriginalUri, Path, Query, State},
     response::Json,
     routing::{delete, get, post, put},
     Router,
@@ -12,13 +12,44 @@ use serde::Deserialize;
 use tracing::{error, info, warn};
 
 use crate::api::error::ApiError;
-use crate::api::responses::{ApiResponse, PagedResponse};
+use crate::api::responses::{ApiResponse, PagedJson};
 use crate::models::{
-    CodexProvider, CreateCodexProviderRequest, PaginationParams, UpdateCodexProviderRequest,
+    CodexProvider, CreateCodexProviderRequest, OrderBy, PaginationParams,
+    UpdateCodexProviderRequest, UpdateResult,
 };
 
+use super::claude::DeleteProviderQuery;
 // 使用服务器模块中的ApiState
 use crate::api::server::ApiState;
+use crate::repositories::{BaseRepository, CodexProviderRepository};
+use crate::services::codex_service::CodexServiceError;
+use crate::services::connection_test::ConnectionTestResult;
+use crate::{ValidationErrors, Validator};
+
+/// 将Service错误转换为API错误
+impl From<CodexServiceError> for ApiError {
+    fn from(err: CodexServiceError) -> Self {
+        match err {
+            CodexServiceError::Validation(msg) => ApiError::validation(msg),
+            CodexServiceError::InvalidField(error) => error.into(),
+            CodexServiceError::BusinessRule(msg) => ApiError::BusinessRule { message: msg },
+            CodexServiceError::Repository(repo_err) => repo_err.into(),
+            CodexServiceError::ProviderNotFound(id) => {
+                ApiError::NotFound { resource: format!("供应商 {} 不存在", id) }
+            }
+            CodexServiceError::NameAlreadyExists(name) => {
+                ApiError::Conflict { message: format!("供应商名称 '{}' 已存在", name) }
+            }
+            CodexServiceError::NoActiveProvider => {
+                ApiError::NotFound { resource: "没有启用的供应商".to_string() }
+            }
+            CodexServiceError::UpstreamTimeout { id, timeout_ms } => {
+                ApiError::upstream_timeout(id, timeout_ms)
+            }
+            CodexServiceError::ConnectionTest(message) => ApiError::Internal { message },
+        }
+    }
+}
 
 /// 查询参数
 #[derive(Debug, Deserialize)]
@@ -28,6 +59,12 @@ pub struct CodexProviderQuery {
     pub page: Option<i64>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// 为 `false` 时不查询总数
+    pub with_total: Option<bool>,
+    /// 排序列，如 `name`、`updated_at`，未指定时使用默认顺序
+    pub sort: Option<String>,
+    /// 排序方向，`asc`（默认）或 `desc`
+    pub dir: Option<String>,
 }
 
 /// 创建Codex供应商
@@ -41,14 +78,12 @@ pub async fn create_codex_provider(
         "创建Codex供应商请求"
     );
 
-    // 验证请求
-    if request.name.trim().is_empty() {
-        return Err(ApiError::validation("供应商名称不能为空".to_string()));
-    }
-
-    if request.token.trim().is_empty() {
-        return Err(ApiError::validation("Token不能为空".to_string()));
-    }
+    // 验证请求，一次性返回所有错误
+    let mut errors = ValidationErrors::new();
+    errors.check(Validator::validate_provider_name(&request.name));
+    errors.check(Validator::validate_non_empty(&request.token, "token"));
+    errors.check(Validator::validate_url(&request.url));
+    errors.into_result()?;
 
     // 创建记录
     let id = state.codex_service.create_provider(&request).await.map_err(|e| {
@@ -57,18 +92,7 @@ pub async fn create_codex_provider(
             name = %request.name,
             "创建Codex供应商失败"
         );
-        match e {
-            crate::services::codex_service::CodexServiceError::Validation(msg) => {
-                ApiError::validation(msg)
-            }
-            crate::services::codex_service::CodexServiceError::BusinessRule(msg) => {
-                ApiError::BusinessRule { message: msg }
-            }
-            crate::services::codex_service::CodexServiceError::Repository(repo_err) => {
-                ApiError::Database { message: format!("数据库错误: {}", repo_err) }
-            }
-            _ => ApiError::Internal { message: format!("创建Codex供应商失败: {}", e) },
-        }
+        ApiError::from(e)
     })?;
 
     // 获取创建的记录
@@ -78,7 +102,7 @@ pub async fn create_codex_provider(
             id = %id,
             "获取新创建的Codex供应商失败"
         );
-        ApiError::Database { message: format!("获取Codex供应商失败: {}", e) }
+        ApiError::from(e)
     })? {
         info!(
             id = %id,
@@ -111,9 +135,7 @@ pub async fn get_codex_provider(
         "获取Codex供应商详情请求"
     );
 
-    if id <= 0 {
-        return Err(ApiError::validation("无效的ID".to_string()));
-    }
+    Validator::validate_id(id, "id")?;
 
     match state.codex_service.get_provider(id).await {
         Ok(Some(provider)) => {
@@ -141,129 +163,75 @@ pub async fn get_codex_provider(
                 id = %id,
                 "获取Codex供应商详情失败"
             );
-            Err(match e {
-                crate::services::codex_service::CodexServiceError::Validation(msg) => {
-                    ApiError::validation(msg)
-                }
-                crate::services::codex_service::CodexServiceError::ProviderNotFound(_) => {
-                    ApiError::NotFound { resource: "Codex供应商不存在".to_string() }
-                }
-                _ => ApiError::Database { message: format!("获取Codex供应商失败: {}", e) },
-            })
+            Err(ApiError::from(e))
         }
     }
 }
 
-/// 更新Codex供应商
+/// 更新Codex供应商，返回更新后的记录和发生变化的字段
 pub async fn update_codex_provider(
     State(state): State<ApiState>,
     Path(id): Path<i64>,
     Json(request): Json<UpdateCodexProviderRequest>,
-) -> Result<Json<ApiResponse<CodexProvider>>, ApiError> {
+) -> Result<Json<ApiResponse<UpdateResult<CodexProvider>>>, ApiError> {
     info!(
         id = %id,
         "更新Codex供应商请求"
     );
 
-    if id <= 0 {
-        return Err(ApiError::validation("无效的ID".to_string()));
-    }
+    Validator::validate_id(id, "id")?;
 
     // 更新记录
-    let updated = state.codex_service.update_provider(id, request).await.map_err(|e| {
-        error!(
-            error = %e,
-            id = %id,
-            "更新Codex供应商失败"
-        );
-        match e {
-            crate::services::codex_service::CodexServiceError::Validation(msg) => {
-                ApiError::validation(msg)
-            }
-            crate::services::codex_service::CodexServiceError::BusinessRule(msg) => {
-                ApiError::BusinessRule { message: msg }
-            }
-            crate::services::codex_service::CodexServiceError::ProviderNotFound(_) => {
-                ApiError::NotFound { resource: "Codex供应商不存在".to_string() }
-            }
-            crate::services::codex_service::CodexServiceError::Repository(repo_err) => {
-                ApiError::Database { message: format!("数据库错误: {}", repo_err) }
-            }
-            _ => ApiError::Internal { message: format!("更新Codex供应商失败: {}", e) },
-        }
-    })?;
-
-    if !updated {
-        error!(
-            id = %id,
-            "更新Codex供应商未影响任何记录"
-        );
-        return Err(ApiError::Internal { message: "更新Codex供应商失败".to_string() });
-    }
+    let result =
+        state
+            .codex_service
+            .update_provider_with_changes(id, request)
+            .await
+            .map_err(|e| {
+                error!(
+                    error = %e,
+                    id = %id,
+                    "更新Codex供应商失败"
+                );
+                ApiError::from(e)
+            })?;
 
-    // 获取更新后的记录
-    if let Some(provider) = state.codex_service.get_provider(id).await.map_err(|e| {
-        error!(
-            error = %e,
-            id = %id,
-            "获取更新后的Codex供应商失败"
-        );
-        ApiError::Database { message: format!("获取Codex供应商失败: {}", e) }
-    })? {
-        info!(
-            id = %id,
-            name = %provider.name,
-            "Codex供应商更新成功"
-        );
+    info!(
+        id = %id,
+        name = %result.entity.name,
+        changed_fields = ?result.changed_fields,
+        "Codex供应商更新成功"
+    );
 
-        Ok(Json(ApiResponse::success_with_message(
-            provider,
-            "Codex供应商更新成功".to_string(),
-        )))
-    } else {
-        error!(
-            id = %id,
-            "更新Codex供应商后无法找到记录"
-        );
-        Err(ApiError::Internal {
-            message: "更新Codex供应商后无法找到记录".to_string()
-        })
-    }
+    Ok(Json(ApiResponse::success_with_message(
+        result,
+        "Codex供应商更新成功".to_string(),
+    )))
 }
 
 /// 删除Codex供应商
 pub async fn delete_codex_provider(
     State(state): State<ApiState>,
     Path(id): Path<i64>,
+    Query(query): Query<DeleteProviderQuery>,
 ) -> Result<Json<ApiResponse<()>>, ApiError> {
+    let force = query.force.unwrap_or(false);
     info!(
         id = %id,
+        force = %force,
         "删除Codex供应商请求"
     );
 
-    if id <= 0 {
-        return Err(ApiError::validation("无效的ID".to_string()));
-    }
+    Validator::validate_id(id, "id")?;
 
     // 删除记录
-    let deleted = state.codex_service.delete_provider(id).await.map_err(|e| {
+    let deleted = state.codex_service.delete_provider(id, force).await.map_err(|e| {
         error!(
             error = %e,
             id = %id,
             "删除Codex供应商失败"
         );
-        match e {
-            crate::services::codex_service::CodexServiceError::Validation(msg) => {
-                ApiError::validation(msg)
-            }
-            crate::services::codex_service::CodexServiceError::ProviderNotFound(_) => {
-                ApiError::NotFound { resource: "Codex供应商不存在".to_string() }
-            }
-            crate::services::codex_service::CodexServiceError::Repository(repo_err) => {
-                ApiError::Database { message: format!("数据库错误: {}", repo_err) }
-            }
-            _ => ApiError::Internal { message: format!("删除Codex供应商失败: {}", e) },
-        }
+        ApiError::from(e)
     })?;
 
     if !deleted {
@@ -289,7 +257,8 @@ pub async fn delete_codex_provider(
 pub async fn list_codex_providers(
     State(state): State<ApiState>,
     Query(query): Query<CodexProviderQuery>,
-) -> Result<Json<ApiResponse<PagedResponse<CodexProvider>>>, ApiError> {
+    OriginalUri(uri): OriginalUri,
+) -> Result<PagedJson<CodexProvider>, ApiError> {
     info!(
         search = ?query.search,
         active_only = ?query.active_only,
@@ -298,18 +267,29 @@ pub async fn list_codex_providers(
         "获取Codex供应商列表请求"
     );
 
+    // 所有列表模式都按 `sort` 排序
+    let order_by = OrderBy::from_query(
+        query.sort.as_deref(),
+        query.dir.as_deref(),
+        CodexProviderRepository::sortable_columns(),
+    )
+    .map_err(ApiError::validation)?;
+
     let result = if let Some(search_term) = query.search {
         // 搜索模式
         let limit = query.limit.or(Some(50));
-        let providers =
-            state.codex_service.search_providers(&search_term, limit).await.map_err(|e| {
-                error!(
-                    error = %e,
-                    search_term = %search_term,
-                    "搜索Codex供应商失败"
-                );
-                ApiError::Database { message: format!("搜索Codex供应商失败: {}", e) }
-            })?;
+        let providers = state
+            .codex_service
+            .search_providers(&search_term, limit, order_by)
+            .await
+            .map_err(|e| {
+            error!(
+                error = %e,
+                search_term = %search_term,
+                "搜索Codex供应商失败"
+            );
+            ApiError::from(e)
+        })?;
 
         // 转换为分页响应格式
         let total = providers.len() as i64;
@@ -324,12 +304,12 @@ pub async fn list_codex_providers(
         paged_result
     } else if query.active_only.unwrap_or(false) {
         // 仅获取活跃供应商
-        let providers = state.codex_service.list_active_providers().await.map_err(|e| {
+        let providers = state.codex_service.list_active_providers(order_by).await.map_err(|e| {
             error!(
                 error = %e,
                 "获取活跃Codex供应商列表失败"
             );
-            ApiError::Database { message: format!("获取Codex供应商列表失败: {}", e) }
+            ApiError::from(e)
         })?;
 
         // 转换为分页响应格式
@@ -344,15 +324,20 @@ pub async fn list_codex_providers(
         paged_result
     } else {
         // 分页获取所有供应商
-        let pagination_params =
-            PaginationParams { page: query.page, limit: query.limit, offset: query.offset };
+        let pagination_params = PaginationParams {
+            page: query.page,
+            limit: query.limit,
+            offset: query.offset,
+            with_total: query.with_total,
+            order_by,
+        };
 
         state.codex_service.list_providers(pagination_params).await.map_err(|e| {
             error!(
                 error = %e,
                 "分页获取Codex供应商列表失败"
             );
-            ApiError::Database { message: format!("获取Codex供应商列表失败: {}", e) }
+            ApiError::from(e)
         })?
     };
 
@@ -361,38 +346,36 @@ pub async fn list_codex_providers(
         "获取Codex供应商列表成功".to_string(),
     );
 
-    Ok(Json(ApiResponse::success(paged_response)))
+    Ok(PagedJson::new(paged_response, &uri))
 }
 
 /// 测试Codex供应商连接
 pub async fn test_codex_provider_connection(
     State(state): State<ApiState>,
     Path(id): Path<i64>,
-) -> Result<Json<ApiResponse<bool>>, ApiError> {
+) -> Result<Json<ApiResponse<ConnectionTestResult>>, ApiError> {
     info!(
         id = %id,
         "测试Codex供应商连接请求"
     );
 
-    if id <= 0 {
-        return Err(ApiError::validation("无效的ID".to_string()));
-    }
+    Validator::validate_id(id, "id")?;
 
     match state.codex_service.test_provider_connection(id).await {
-        Ok(success) => {
+        Ok(result) => {
             info!(
                 id = %id,
-                success = %success,
+                success = %result.success,
                 "Codex供应商连接测试完成"
             );
 
-            let message = if success {
+            let message = if result.success {
                 "连接测试成功"
             } else {
                 "连接测试失败"
             };
             Ok(Json(ApiResponse::success_with_message(
-                success,
+                result,
                 message.to_string(),
             )))
         }
@@ -402,15 +385,7 @@ pub async fn test_codex_provider_connection(
                 id = %id,
                 "Codex供应商连接测试失败"
             );
-            Err(match e {
-                crate::services::codex_service::CodexServiceError::Validation(msg) => {
-                    ApiError::validation(msg)
-                }
-                crate::services::codex_service::CodexServiceError::ProviderNotFound(_) => {
-                    ApiError::NotFound { resource: "Codex供应商不存在".to_string() }
-                }
-                _ => ApiError::Database { message: format!("连接测试失败: {}", e) },
-            })
+            Err(ApiError::from(e))
         }
     }
 }
@@ -426,7 +401,7 @@ pub async fn get_codex_provider_stats(
             error = %e,
             "获取Codex供应商统计信息失败"
         );
-        ApiError::Database { message: format!("获取统计信息失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     info!("Codex供应商统计信息获取完成");
@@ -3,23 +3,49 @@
 // 提供通用配置的HTTP API接口实现
 
 use axum::{
-    extract::{Path, Query, State},
-    response::Json,
+    extract::{OriginalUri, Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
     Router,
 };
+use futures::StreamExt;
 use serde::Deserialize;
-use tracing::{error, info, warn};
+use tracing::{error, info, warn, Instrument};
 
 use crate::api::error::ApiError;
-use crate::api::responses::{ApiResponse, PagedResponse};
+use crate::api::responses::{streaming_list, ApiResponse, PagedJson};
 use crate::models::{
-    CommonConfig, CreateCommonConfigRequest, PaginationParams, UpdateCommonConfigRequest,
+    BatchUpdateFailure, BatchUpdateResult, CommonConfig, CreateCommonConfigRequest, OrderBy,
+    PaginationParams, UpdateCommonConfigRequest, UpdateResult, MAX_CATEGORY_LENGTH,
+    MAX_DESCRIPTION_LENGTH,
 };
+use crate::repositories::common_config_repository::BatchConfigUpdate;
 use crate::repositories::{BaseRepository, CommonConfigRepository};
+use crate::services::common_config_service::{
+    typed_config_value, validate_secret_patterns, CommonConfigService, CommonConfigServiceError,
+    EffectiveConfig,
+};
+use crate::{ValidationErrors, Validator};
 
 /// 重用API服务器的ApiState
 pub use super::super::server::ApiState;
 
+/// 将Service错误转换为API错误，无效的敏感信息匹配规则属于输入错误
+impl From<CommonConfigServiceError> for ApiError {
+    fn from(err: CommonConfigServiceError) -> Self {
+        match err {
+            CommonConfigServiceError::Repository(repo_err) => repo_err.into(),
+            CommonConfigServiceError::InvalidSecretPatterns(_) => {
+                ApiError::validation(err.to_string())
+            }
+            CommonConfigServiceError::ConfigNotFound(_)
+            | CommonConfigServiceError::VersionNotFound { .. } => {
+                ApiError::NotFound { resource: err.to_string() }
+            }
+        }
+    }
+}
+
 /// 查询参数
 #[derive(Debug, Deserialize)]
 pub struct CommonConfigQuery {
@@ -29,6 +55,52 @@ pub struct CommonConfigQuery {
     pub page: Option<i64>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// 为 `false` 时不查询总数
+    pub with_total: Option<bool>,
+    /// 排序列，如 `name`、`updated_at`，未指定时使用默认顺序
+    pub sort: Option<String>,
+    /// 排序方向，`asc`（默认）或 `desc`
+    pub dir: Option<String>,
+    /// 为 `true` 时按数据类型输出 `value`
+    pub typed: Option<bool>,
+    /// 为 `true` 时逐条流式输出全部结果，`data` 为数组且忽略分页参数
+    pub stream: Option<bool>,
+}
+
+/// 单个配置查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct TypedQuery {
+    /// 为 `true` 时按数据类型输出 `value`（数字、布尔值或JSON对象），默认输出字符串
+    pub typed: Option<bool>,
+}
+
+/// 序列化配置，`typed` 为 `true` 时将 `value` 转换为对应数据类型的JSON值
+fn config_to_json(config: CommonConfig, typed: bool) -> serde_json::Value {
+    let typed_value = typed.then(|| typed_config_value(&config.key, &config.value));
+    let mut json = serde_json::to_value(config).unwrap_or_default();
+    if let Some(value) = typed_value {
+        json["value"] = value;
+    }
+    json
+}
+
+/// 解密配置列表，每个配置使用其类别对应的密钥
+async fn decrypt_configs(
+    service: &CommonConfigService,
+    configs: Vec<CommonConfig>,
+) -> Result<Vec<CommonConfig>, ApiError> {
+    let mut decrypted = Vec::with_capacity(configs.len());
+    for config in configs {
+        let config = service.decrypt_config(config).await.map_err(|e| {
+            error!(
+                error = %e,
+                "解密通用配置失败"
+            );
+            ApiError::from(e)
+        })?;
+        decrypted.push(config);
+    }
+    Ok(decrypted)
 }
 
 /// 批量更新配置请求
@@ -37,11 +109,77 @@ pub struct BatchUpdateRequest {
     pub configs: Vec<ConfigItem>,
 }
 
-/// 配置项
+/// 批量更新查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct BatchUpdateQuery {
+    /// 为 `true` 时提交成功的项并报告失败的项，默认任一项失败即全部回滚
+    pub partial: Option<bool>,
+}
+
+/// 配置项，按 `id` 指定配置；未指定 `id` 时按 `key` 查找
 #[derive(Debug, Deserialize)]
 pub struct ConfigItem {
-    pub key: String,
-    pub value: String,
+    pub id: Option<i64>,
+    pub key: Option<String>,
+    pub value: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    #[serde(alias = "enabled")]
+    pub is_active: Option<i64>,
+}
+
+impl ConfigItem {
+    /// 验证配置项，与单个更新的规则一致
+    fn validate(&self) -> Result<(), ApiError> {
+        match (self.id, &self.key) {
+            (Some(id), _) => {
+                Validator::validate_id(id, "id")?;
+            }
+            (None, None) => return Err(ApiError::validation("必须指定配置ID或配置键")),
+            (None, Some(_)) => {}
+        }
+
+        if let Some(ref key) = self.key {
+            if key.trim().is_empty() {
+                return Err(ApiError::validation("配置键不能为空"));
+            }
+            Validator::validate_config_key(key)?;
+        }
+
+        if let Some(ref value) = self.value {
+            if value.trim().is_empty() {
+                return Err(ApiError::validation("配置值不能为空"));
+            }
+            Validator::validate_config_value(value)?;
+            if let Some(ref key) = self.key {
+                validate_secret_patterns(key, value).map_err(ApiError::from)?;
+            }
+        }
+
+        if let Some(ref description) = self.description {
+            Validator::validate_max_length(description, "配置描述", MAX_DESCRIPTION_LENGTH)?;
+        }
+
+        if let Some(ref category) = self.category {
+            Validator::validate_max_length(category, "配置类别", MAX_CATEGORY_LENGTH)?;
+        }
+
+        Ok(())
+    }
+
+    fn into_update(self) -> BatchConfigUpdate {
+        BatchConfigUpdate {
+            id: self.id,
+            changes: UpdateCommonConfigRequest {
+                key: self.key,
+                value: self.value,
+                description: self.description,
+                category: self.category,
+                is_active: self.is_active,
+            },
+            key_fingerprint: None,
+        }
+    }
 }
 
 /// 创建通用配置
@@ -55,20 +193,34 @@ pub async fn create_common_config(
         "创建通用配置请求"
     );
 
-    // 创建Repository
-    let repository = CommonConfigRepository::new(&state.db_manager, &state.crypto_service);
-
-    // 验证请求
-    if request.key.trim().is_empty() {
-        return Err(ApiError::validation("配置键不能为空".to_string()));
+    let service = state.common_config_service();
+
+    // 验证请求，一次性返回所有错误
+    let mut errors = ValidationErrors::new();
+    errors.check(Validator::validate_config_key(&request.key));
+    errors.check(Validator::validate_non_empty(&request.value, "配置值"));
+    errors.check(Validator::validate_config_value(&request.value));
+    if let Some(ref description) = request.description {
+        errors.check(Validator::validate_max_length(
+            description,
+            "配置描述",
+            MAX_DESCRIPTION_LENGTH,
+        ));
     }
-
-    if request.value.trim().is_empty() {
-        return Err(ApiError::validation("配置值不能为空".to_string()));
+    if let Some(ref category) = request.category {
+        errors.check(Validator::validate_max_length(
+            category,
+            "配置类别",
+            MAX_CATEGORY_LENGTH,
+        ));
     }
+    errors.into_result()?;
 
     // 检查key是否已存在
-    if repository.find_by_key(&request.key).await.is_ok() {
+    if matches!(
+        service.repository().find_by_key(&request.key).await,
+        Ok(Some(_))
+    ) {
         warn!(
             key = %request.key,
             "配置键已存在"
@@ -76,24 +228,24 @@ pub async fn create_common_config(
         return Err(ApiError::validation("配置键已存在".to_string()));
     }
 
-    // 创建记录
-    let id = repository.create_common_config(&request).await.map_err(|e| {
+    // 创建记录，值使用类别对应的密钥加密
+    let id = service.create_config(&request).await.map_err(|e| {
         error!(
             error = %e,
             key = %request.key,
             "创建通用配置失败"
         );
-        ApiError::Database { message: format!("创建通用配置失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     // 获取创建的记录
-    if let Some(config) = repository.find_by_id_decrypted(id).await.map_err(|e| {
+    if let Some(config) = service.get_config_by_id(id).await.map_err(|e| {
         error!(
             error = %e,
             id = %id,
             "获取新创建的通用配置失败"
         );
-        ApiError::Database { message: format!("获取通用配置失败: {}", e) }
+        ApiError::from(e)
     })? {
         info!(
             id = %id,
@@ -120,19 +272,18 @@ pub async fn create_common_config(
 pub async fn get_common_config(
     State(state): State<ApiState>,
     Path(id): Path<i64>,
-) -> Result<Json<ApiResponse<CommonConfig>>, ApiError> {
+    Query(query): Query<TypedQuery>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, ApiError> {
     info!(
         id = %id,
         "获取通用配置详情请求"
     );
 
-    let repository = CommonConfigRepository::new(&state.db_manager, &state.crypto_service);
+    let service = state.common_config_service();
 
-    if id <= 0 {
-        return Err(ApiError::validation("无效的ID".to_string()));
-    }
+    Validator::validate_id(id, "id")?;
 
-    match repository.find_by_id_decrypted(id).await {
+    match service.get_config_by_id(id).await {
         Ok(Some(config)) => {
             info!(
                 id = %id,
@@ -141,7 +292,7 @@ pub async fn get_common_config(
             );
 
             Ok(Json(ApiResponse::success_with_message(
-                config,
+                config_to_json(config, query.typed.unwrap_or(false)),
                 "获取通用配置详情成功".to_string(),
             )))
         }
@@ -158,7 +309,7 @@ pub async fn get_common_config(
                 id = %id,
                 "获取通用配置详情失败"
             );
-            Err(ApiError::Database { message: format!("获取通用配置失败: {}", e) })
+            Err(ApiError::from(e))
         }
     }
 }
@@ -167,19 +318,20 @@ pub async fn get_common_config(
 pub async fn get_common_config_by_key(
     State(state): State<ApiState>,
     Path(key): Path<String>,
-) -> Result<Json<ApiResponse<CommonConfig>>, ApiError> {
+    Query(query): Query<TypedQuery>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, ApiError> {
     info!(
         key = %key,
         "根据key获取通用配置请求"
     );
 
-    let repository = CommonConfigRepository::new(&state.db_manager, &state.crypto_service);
+    let service = state.common_config_service();
 
     if key.trim().is_empty() {
         return Err(ApiError::validation("配置键不能为空".to_string()));
     }
 
-    match repository.find_by_key(&key).await {
+    match service.get_config(&key).await {
         Ok(Some(config)) => {
             info!(
                 key = %key,
@@ -187,7 +339,7 @@ pub async fn get_common_config_by_key(
             );
 
             Ok(Json(ApiResponse::success_with_message(
-                config,
+                config_to_json(config, query.typed.unwrap_or(false)),
                 "根据key获取通用配置成功".to_string(),
             )))
         }
@@ -204,7 +356,7 @@ pub async fn get_common_config_by_key(
                 key = %key,
                 "根据key获取通用配置失败"
             );
-            Err(ApiError::Database { message: format!("获取通用配置失败: {}", e) })
+            Err(ApiError::from(e))
         }
     }
 }
@@ -214,51 +366,50 @@ pub async fn update_common_config(
     State(state): State<ApiState>,
     Path(id): Path<i64>,
     Json(request): Json<UpdateCommonConfigRequest>,
-) -> Result<Json<ApiResponse<CommonConfig>>, ApiError> {
+) -> Result<Json<ApiResponse<UpdateResult<CommonConfig>>>, ApiError> {
     info!(
         id = %id,
         "更新通用配置请求"
     );
 
-    let repository = CommonConfigRepository::new(&state.db_manager, &state.crypto_service);
+    let service = state.common_config_service();
 
-    if id <= 0 {
-        return Err(ApiError::validation("无效的ID".to_string()));
-    }
+    Validator::validate_id(id, "id")?;
 
     // 检查记录是否存在
-    let existing = repository.find_by_id_decrypted(id).await.map_err(|e| {
+    let existing = service.get_config_by_id(id).await.map_err(|e| {
         error!(
             error = %e,
             id = %id,
             "检查通用配置是否存在失败"
         );
-        ApiError::Database { message: format!("检查通用配置失败: {}", e) }
+        ApiError::from(e)
     })?;
 
-    if existing.is_none() {
+    let Some(existing) = existing else {
         warn!(
             id = %id,
             "尝试更新不存在的通用配置"
         );
         return Err(ApiError::NotFound { resource: "通用配置不存在".to_string() });
-    }
+    };
 
     // 验证更新数据
     if let Some(ref key) = request.key {
         if key.trim().is_empty() {
             return Err(ApiError::validation("配置键不能为空".to_string()));
         }
+        Validator::validate_config_key(key)?;
 
         // 如果更新key，检查新key是否已存在
-        if let Some(existing_config) = existing.as_ref() {
-            if key != &existing_config.key && repository.find_by_key(key).await.is_ok() {
-                warn!(
-                    key = %key,
-                    "新配置键已存在"
-                );
-                return Err(ApiError::validation("新配置键已存在".to_string()));
-            }
+        if key != &existing.key
+            && matches!(service.repository().find_by_key(key).await, Ok(Some(_)))
+        {
+            warn!(
+                key = %key,
+                "新配置键已存在"
+            );
+            return Err(ApiError::validation("新配置键已存在".to_string()));
         }
     }
 
@@ -266,16 +417,25 @@ pub async fn update_common_config(
         if value.trim().is_empty() {
             return Err(ApiError::validation("配置值不能为空".to_string()));
         }
+        Validator::validate_config_value(value)?;
+    }
+
+    if let Some(ref description) = request.description {
+        Validator::validate_max_length(description, "配置描述", MAX_DESCRIPTION_LENGTH)?;
     }
 
-    // 更新记录
-    let updated = repository.update_common_config(id, &request).await.map_err(|e| {
+    if let Some(ref category) = request.category {
+        Validator::validate_max_length(category, "配置类别", MAX_CATEGORY_LENGTH)?;
+    }
+
+    // 更新记录，值使用更新后类别对应的密钥加密
+    let updated = service.update_config(id, &request).await.map_err(|e| {
         error!(
             error = %e,
             id = %id,
             "更新通用配置失败"
         );
-        ApiError::Database { message: format!("更新通用配置失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     if !updated {
@@ -287,22 +447,24 @@ pub async fn update_common_config(
     }
 
     // 获取更新后的记录
-    if let Some(config) = repository.find_by_id_decrypted(id).await.map_err(|e| {
+    if let Some(config) = service.get_config_by_id(id).await.map_err(|e| {
         error!(
             error = %e,
             id = %id,
             "获取更新后的通用配置失败"
         );
-        ApiError::Database { message: format!("获取通用配置失败: {}", e) }
+        ApiError::from(e)
     })? {
+        let result = UpdateResult::diff(&existing, config);
         info!(
             id = %id,
-            key = %config.key,
+            key = %result.entity.key,
+            changed_fields = ?result.changed_fields,
             "通用配置更新成功"
         );
 
         Ok(Json(ApiResponse::success_with_message(
-            config,
+            result,
             "通用配置更新成功".to_string(),
         )))
     } else {
@@ -328,9 +490,7 @@ pub async fn delete_common_config(
 
     let repository = CommonConfigRepository::new(&state.db_manager, &state.crypto_service);
 
-    if id <= 0 {
-        return Err(ApiError::validation("无效的ID".to_string()));
-    }
+    Validator::validate_id(id, "id")?;
 
     // 检查记录是否存在
     let existing = repository.find_by_id::<CommonConfig>(id).await.map_err(|e| {
@@ -339,7 +499,7 @@ pub async fn delete_common_config(
             id = %id,
             "检查通用配置是否存在失败"
         );
-        ApiError::Database { message: format!("检查通用配置失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     if existing.is_none() {
@@ -357,7 +517,7 @@ pub async fn delete_common_config(
             id = %id,
             "删除通用配置失败"
         );
-        ApiError::Database { message: format!("删除通用配置失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     if !deleted {
@@ -383,29 +543,49 @@ pub async fn delete_common_config(
 pub async fn list_common_configs(
     State(state): State<ApiState>,
     Query(query): Query<CommonConfigQuery>,
-) -> Result<Json<ApiResponse<PagedResponse<CommonConfig>>>, ApiError> {
+    OriginalUri(uri): OriginalUri,
+) -> Result<Response, ApiError> {
     info!(
         search = ?query.search,
         category = ?query.category,
         active_only = ?query.active_only,
         page = ?query.page,
         limit = ?query.limit,
+        stream = ?query.stream,
         "获取通用配置列表请求"
     );
 
-    let repository = CommonConfigRepository::new(&state.db_manager, &state.crypto_service);
+    let typed = query.typed.unwrap_or(false);
+    if query.stream.unwrap_or(false) {
+        return stream_common_configs(state, query, typed);
+    }
+
+    let service = state.common_config_service();
+    let repository = service.repository();
+
+    // 所有列表模式都按 `sort` 排序
+    let order_by = OrderBy::from_query(
+        query.sort.as_deref(),
+        query.dir.as_deref(),
+        CommonConfigRepository::sortable_columns(),
+    )
+    .map_err(ApiError::validation)?;
 
     let result = if let Some(search_term) = query.search {
         // 搜索模式
         let limit = query.limit.or(Some(50));
-        let configs = repository.search_common_configs(&search_term, limit).await.map_err(|e| {
-            error!(
-                error = %e,
-                search_term = %search_term,
-                "搜索通用配置失败"
-            );
-            ApiError::Database { message: format!("搜索通用配置失败: {}", e) }
-        })?;
+        let configs = repository
+            .search_common_configs(&search_term, limit, order_by)
+            .await
+            .map_err(|e| {
+                error!(
+                    error = %e,
+                    search_term = %search_term,
+                    "搜索通用配置失败"
+                );
+                ApiError::from(e)
+            })?;
+        let configs = decrypt_configs(&service, configs).await?;
 
         // 转换为分页响应格式
         let total = configs.len() as i64;
@@ -420,14 +600,15 @@ pub async fn list_common_configs(
         paged_result
     } else if let Some(category) = query.category {
         // 按类别筛选
-        let configs = repository.find_by_category(&category).await.map_err(|e| {
+        let configs = repository.find_by_category(&category, order_by).await.map_err(|e| {
             error!(
                 error = %e,
                 category = %category,
                 "根据类别获取通用配置列表失败"
             );
-            ApiError::Database { message: format!("获取通用配置列表失败: {}", e) }
+            ApiError::from(e)
         })?;
+        let configs = decrypt_configs(&service, configs).await?;
 
         // 转换为分页响应格式
         let total = configs.len() as i64;
@@ -442,13 +623,14 @@ pub async fn list_common_configs(
         paged_result
     } else if query.active_only.unwrap_or(false) {
         // 仅获取活跃配置
-        let configs = repository.list_active_configs().await.map_err(|e| {
+        let configs = repository.list_active_configs(order_by).await.map_err(|e| {
             error!(
                 error = %e,
                 "获取活跃通用配置列表失败"
             );
-            ApiError::Database { message: format!("获取通用配置列表失败: {}", e) }
+            ApiError::from(e)
         })?;
+        let configs = decrypt_configs(&service, configs).await?;
 
         // 转换为分页响应格式
         let total = configs.len() as i64;
@@ -462,37 +644,106 @@ pub async fn list_common_configs(
         paged_result
     } else {
         // 分页获取所有配置
-        let pagination_params =
-            PaginationParams { page: query.page, limit: query.limit, offset: query.offset };
+        let pagination_params = PaginationParams {
+            page: query.page,
+            limit: query.limit,
+            offset: query.offset,
+            with_total: query.with_total,
+            order_by,
+        };
+
+        let mut paged_result =
+            repository.paginate::<CommonConfig>(&pagination_params).await.map_err(|e| {
+                error!(
+                    error = %e,
+                    "分页获取通用配置列表失败"
+                );
+                ApiError::from(e)
+            })?;
+        paged_result.data = decrypt_configs(&service, paged_result.data).await?;
 
-        repository.paginate::<CommonConfig>(&pagination_params).await.map_err(|e| {
-            error!(
-                error = %e,
-                "分页获取通用配置列表失败"
-            );
-            ApiError::Database { message: format!("获取通用配置列表失败: {}", e) }
-        })?
+        paged_result
     };
 
     let paged_response = crate::api::responses::PagedResponse::from_paged_result_with_message(
-        result,
+        result.map(|config| config_to_json(config, typed)),
         "获取通用配置列表成功".to_string(),
     );
 
-    Ok(Json(ApiResponse::success(paged_response)))
+    Ok(PagedJson::new(paged_response, &uri).into_response())
+}
+
+/// 流式输出通用配置列表，逐行读取数据库并写出，内存占用不随结果数量增长
+fn stream_common_configs(
+    state: ApiState,
+    query: CommonConfigQuery,
+    typed: bool,
+) -> Result<Response, ApiError> {
+    if query.search.is_some() {
+        return Err(ApiError::validation("流式输出不支持搜索".to_string()));
+    }
+
+    let service = state.common_config_service();
+    let category = query.category;
+    let active_only = query.active_only.unwrap_or(false);
+    let (mut writer, response) = streaming_list("获取通用配置列表成功");
+
+    tokio::spawn(
+        async move {
+            let mut rows = service.repository().stream_configs(category.as_deref(), active_only);
+            let mut count = 0usize;
+
+            while let Some(row) = rows.next().await {
+                let row = match row {
+                    Ok(config) => service.decrypt_config(config).await.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                match row {
+                    Ok(config) => {
+                        if !writer.push(&config_to_json(config, typed)).await {
+                            warn!(count = %count, "客户端已断开，停止流式输出通用配置");
+                            return;
+                        }
+                        count += 1;
+                    }
+                    Err(e) => {
+                        error!(
+                            error = %e,
+                            count = %count,
+                            "流式读取通用配置失败"
+                        );
+                        writer.abort(format!("读取通用配置失败: {}", e)).await;
+                        return;
+                    }
+                }
+            }
+
+            writer.finish().await;
+            info!(count = %count, "通用配置列表流式输出完成");
+        }
+        .in_current_span(),
+    );
+
+    Ok(response)
 }
 
 /// 批量更新配置
+///
+/// 默认在一个事务中应用全部修改，任一项失败时不修改任何配置并返回400；
+/// 使用 `?partial=true` 时提交成功的项，失败的项在 `failed` 中报告
 pub async fn batch_update_common_configs(
     State(state): State<ApiState>,
+    Query(query): Query<BatchUpdateQuery>,
     Json(request): Json<BatchUpdateRequest>,
-) -> Result<Json<ApiResponse<usize>>, ApiError> {
+) -> Result<Response, ApiError> {
+    let partial = query.partial.unwrap_or(false);
     info!(
         config_count = %request.configs.len(),
+        partial = %partial,
         "批量更新通用配置请求"
     );
 
-    let repository = CommonConfigRepository::new(&state.db_manager, &state.crypto_service);
+    let service = state.common_config_service();
 
     if request.configs.is_empty() {
         return Err(ApiError::validation("配置列表不能为空".to_string()));
@@ -504,39 +755,65 @@ pub async fn batch_update_common_configs(
         ));
     }
 
-    // 验证配置项
-    for config in &request.configs {
-        if config.key.trim().is_empty() {
-            return Err(ApiError::validation("配置键不能为空".to_string()));
-        }
-        if config.value.trim().is_empty() {
-            return Err(ApiError::validation("配置值不能为空".to_string()));
+    // 验证全部配置项，收集所有失败的项而不是遇到第一个就返回
+    let total = request.configs.len();
+    let mut failed = Vec::new();
+    let mut updates = Vec::new();
+    for item in request.configs {
+        match item.validate() {
+            Ok(()) => updates.push(item.into_update()),
+            Err(e) => {
+                failed.push(BatchUpdateFailure { id: item.id, key: item.key, error: e.to_string() })
+            }
         }
     }
 
-    // 转换为元组格式
-    let configs: Vec<(String, String)> =
-        request.configs.into_iter().map(|item| (item.key, item.value)).collect();
-
-    // 批量更新
-    let updated_count = repository.batch_update_configs(&configs).await.map_err(|e| {
-        error!(
-            error = %e,
-            "批量更新通用配置失败"
-        );
-        ApiError::Database { message: format!("批量更新通用配置失败: {}", e) }
-    })?;
+    // 整体模式下存在无效项时不需要访问数据库
+    let mut result = if partial || failed.is_empty() {
+        service.batch_update(updates, partial).await.map_err(|e| {
+            error!(
+                error = %e,
+                "批量更新通用配置失败"
+            );
+            ApiError::from(e)
+        })?
+    } else {
+        BatchUpdateResult::default()
+    };
+    failed.append(&mut result.failed);
+    result.failed = failed;
 
     info!(
-        updated_count = %updated_count,
-        total_configs = %configs.len(),
+        updated_count = %result.updated.len(),
+        failed_count = %result.failed.len(),
+        total_configs = %total,
         "批量更新通用配置完成"
     );
 
-    Ok(Json(ApiResponse::success_with_message(
-        updated_count,
-        format!("成功更新{}个配置", updated_count),
-    )))
+    if result.failed.is_empty() {
+        let message = format!("成功更新{}个配置", result.updated.len());
+        return Ok(Json(ApiResponse::success_with_message(result, message)).into_response());
+    }
+
+    if partial {
+        let message = format!(
+            "成功更新{}个配置，{}个配置更新失败",
+            result.updated.len(),
+            result.failed.len()
+        );
+        return Ok(Json(ApiResponse::success_with_message(result, message)).into_response());
+    }
+
+    warn!(
+        failed_count = %result.failed.len(),
+        "批量更新存在失败的配置，已回滚全部修改"
+    );
+    let message = format!("{}个配置更新失败，未应用任何修改", result.failed.len());
+    Ok((
+        StatusCode::BAD_REQUEST,
+        Json(ApiResponse::failure(result, message)),
+    )
+        .into_response())
 }
 
 /// 验证通用配置值
@@ -551,9 +828,7 @@ pub async fn validate_common_config(
 
     let repository = CommonConfigRepository::new(&state.db_manager, &state.crypto_service);
 
-    if id <= 0 {
-        return Err(ApiError::validation("无效的ID".to_string()));
-    }
+    Validator::validate_id(id, "id")?;
 
     match repository.validate_config_value(id).await {
         Ok(is_valid) => {
@@ -579,7 +854,7 @@ pub async fn validate_common_config(
                 id = %id,
                 "通用配置值验证失败"
             );
-            Err(ApiError::Database { message: format!("配置值验证失败: {}", e) })
+            Err(ApiError::from(e))
         }
     }
 }
@@ -598,7 +873,7 @@ pub async fn get_common_config_stats(
             error = %e,
             "获取通用配置总数失败"
         );
-        ApiError::Database { message: format!("获取统计信息失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     // 获取活跃数量
@@ -607,7 +882,7 @@ pub async fn get_common_config_stats(
             error = %e,
             "获取活跃通用配置数量失败"
         );
-        ApiError::Database { message: format!("获取统计信息失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     // 获取非活跃数量
@@ -619,7 +894,7 @@ pub async fn get_common_config_stats(
             error = %e,
             "获取配置类别失败"
         );
-        ApiError::Database { message: format!("获取统计信息失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     let stats = serde_json::json!({
@@ -645,6 +920,33 @@ pub async fn get_common_config_stats(
     )))
 }
 
+/// 获取生效配置（环境变量 > 数据库 > 默认值）
+pub async fn get_effective_common_configs(
+    State(state): State<ApiState>,
+) -> Result<Json<ApiResponse<Vec<EffectiveConfig>>>, ApiError> {
+    info!("获取生效配置请求");
+
+    let service = state.common_config_service();
+
+    let effective = service.effective_config().await.map_err(|e| {
+        error!(
+            error = %e,
+            "解析生效配置失败"
+        );
+        ApiError::from(e)
+    })?;
+
+    info!(
+        count = %effective.len(),
+        "生效配置获取完成"
+    );
+
+    Ok(Json(ApiResponse::success_with_message(
+        effective,
+        "获取生效配置成功".to_string(),
+    )))
+}
+
 /// 通用配置API路由
 pub fn routes() -> Router<ApiState> {
     use axum::routing::{delete, get, post, put};
@@ -658,6 +960,8 @@ pub fn routes() -> Router<ApiState> {
         .route("/batch", post(batch_update_common_configs))
         // 获取通用配置统计信息
         .route("/stats", get(get_common_config_stats))
+        // 获取生效配置
+        .route("/effective", get(get_effective_common_configs))
         // 获取单个通用配置
         .route("/:id", get(get_common_config))
         // 更新通用配置
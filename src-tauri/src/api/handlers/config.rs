@@ -1,6 +1,85 @@
-// 通用配置API处理器
+// 配置预览API处理器
 //
-// 通用配置的HTTP请求处理器占位符
-// 将在后续任务中实现完整功能
+// 按工具模式返回将要生成的配置文件内容，不写入任何文件
 
-// 占位符实现，将在任务5中完成
\ No newline at end of file
+use axum::{
+    extract::{Path, Query, State},
+    response::Json,
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+use tracing::{error, info};
+
+use crate::api::error::ApiError;
+use crate::api::responses::ApiResponse;
+use crate::migration::config_generator::ConfigGenerator;
+use crate::services::mode_service::{ConfigPreview, Mode, ModeService, ModeServiceError};
+
+/// 重用API服务器的ApiState
+pub use super::super::server::ApiState;
+
+impl From<ModeServiceError> for ApiError {
+    fn from(error: ModeServiceError) -> Self {
+        match error {
+            ModeServiceError::UnknownMode(_) => ApiError::validation(error.to_string()),
+            ModeServiceError::NoProvider(_) => ApiError::NotFound { resource: error.to_string() },
+            ModeServiceError::InvalidProvider { .. } => {
+                ApiError::BusinessRule { message: error.to_string() }
+            }
+            ModeServiceError::Claude(e) => e.into(),
+            ModeServiceError::Codex(e) => e.into(),
+            ModeServiceError::ConfigGenerator(e) => {
+                ApiError::Configuration { message: e.to_string() }
+            }
+        }
+    }
+}
+
+/// 配置预览的查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct ConfigPreviewQuery {
+    /// 为 `true` 时返回明文Token
+    pub reveal: Option<bool>,
+}
+
+/// 获取指定工具模式的配置内容
+pub async fn get_config_preview(
+    State(state): State<ApiState>,
+    Path(mode): Path<String>,
+    Query(query): Query<ConfigPreviewQuery>,
+) -> Result<Json<ApiResponse<ConfigPreview>>, ApiError> {
+    let reveal = query.reveal.unwrap_or(false);
+    info!(mode = %mode, reveal = %reveal, "获取配置预览请求");
+
+    let mode = mode.parse::<Mode>()?;
+    let generator = match &state.config_generator {
+        Some(generator) => generator.clone(),
+        None => ConfigGenerator::new()
+            .map_err(|e| ApiError::Configuration { message: e.to_string() })?,
+    };
+
+    let preview = ModeService::new(
+        state.db_manager.clone(),
+        state.crypto_service.clone(),
+        generator,
+    )
+    .preview_config(mode, reveal)
+    .await
+    .map_err(|e| {
+        error!(mode = %mode, error = %e, "获取配置预览失败");
+        ApiError::from(e)
+    })?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        preview,
+        "获取配置预览成功".to_string(),
+    )))
+}
+
+/// 创建配置预览路由
+pub fn routes() -> Router<ApiState> {
+    Router::new()
+        // 获取指定工具模式的配置内容
+        .route("/:mode", get(get_config_preview))
+}
@@ -0,0 +1,96 @@
+// 维护操作API处理器
+//
+// 提供密钥轮换、统计信息刷新、时间戳补全等维护操作的HTTP API接口实现
+
+use axum::{extract::State, response::Json, Router};
+use tracing::{error, info};
+
+use crate::api::error::ApiError;
+use crate::api::responses::ApiResponse;
+use crate::database::{RefreshReport, TimestampBackfillReport};
+use crate::migration::encryption_migration::{EncryptionMigration, ReencryptionReport};
+
+/// 重用API服务器的ApiState
+pub use super::super::server::ApiState;
+
+/// 使用最新密钥重新加密所有令牌
+///
+/// 只有配置了旧密钥时才能执行，先进入维护模式并等待进行中的写请求完成，再开始重新加密
+pub async fn rotate_tokens(
+    State(state): State<ApiState>,
+) -> Result<Json<ApiResponse<ReencryptionReport>>, ApiError> {
+    info!(key_count = %state.rotation_keys.key_count(), "密钥轮换请求");
+
+    if state.rotation_keys.key_count() < 2 {
+        return Err(ApiError::validation("未配置旧密钥，无需轮换"));
+    }
+
+    let Some(_guard) = state.maintenance.enter() else {
+        return Err(ApiError::Conflict { message: "其他维护操作正在进行".to_string() });
+    };
+    // 进入维护模式后拒绝新的写请求，等待已开始的写请求完成后再轮换，避免覆盖轮换结果
+    state.maintenance.wait_for_writes().await;
+
+    let migration =
+        EncryptionMigration::new((*state.db_manager).clone(), state.rotation_keys.clone());
+    let report = migration.reencrypt_all().await.map_err(|e| {
+        error!(error = %e, "密钥轮换失败");
+        ApiError::Database { message: format!("密钥轮换失败: {}", e) }
+    })?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        report,
+        "令牌已使用最新密钥重新加密".to_string(),
+    )))
+}
+
+/// 重新计算所有数据表的统计信息
+///
+/// 不进入维护模式，可以在批量操作或恢复备份后随时执行
+pub async fn refresh(
+    State(state): State<ApiState>,
+) -> Result<Json<ApiResponse<RefreshReport>>, ApiError> {
+    info!("刷新统计信息请求");
+
+    let report = state.db_manager.refresh_statistics().await.map_err(|e| {
+        error!(error = %e, "刷新统计信息失败");
+        ApiError::Database { message: format!("刷新统计信息失败: {}", e) }
+    })?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        report,
+        "统计信息已刷新".to_string(),
+    )))
+}
+
+/// 补全所有数据表中缺失的创建和更新时间
+///
+/// 启动时会自动执行一次，导入旧数据后也可以手动执行
+pub async fn backfill_timestamps(
+    State(state): State<ApiState>,
+) -> Result<Json<ApiResponse<TimestampBackfillReport>>, ApiError> {
+    info!("补全时间戳请求");
+
+    let report = state.db_manager.backfill_timestamps().await.map_err(|e| {
+        error!(error = %e, "补全时间戳失败");
+        ApiError::Database { message: format!("补全时间戳失败: {}", e) }
+    })?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        report,
+        "缺失的时间戳已补全".to_string(),
+    )))
+}
+
+/// 创建维护操作路由
+pub fn routes() -> Router<ApiState> {
+    use axum::routing::post;
+
+    Router::new()
+        // 使用最新密钥重新加密所有令牌
+        .route("/rotate-tokens", post(rotate_tokens))
+        // 重新计算统计信息
+        .route("/refresh", post(refresh))
+        // 补全缺失的时间戳
+        .route("/backfill-timestamps", post(backfill_timestamps))
+}
@@ -3,7 +3,7 @@
 // 提供MCP服务器的HTTP API接口实现
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{OriginalUri, Path, Query, State},
     response::Json,
     Router,
 };
@@ -11,9 +11,15 @@ use serde::Deserialize;
 use tracing::{error, info, warn};
 
 use crate::api::error::ApiError;
-use crate::api::responses::{ApiResponse, PagedResponse};
-use crate::models::{CreateMcpServerRequest, McpServer, PaginationParams, UpdateMcpServerRequest};
+use crate::api::responses::{ApiResponse, PagedJson};
+use crate::models::{
+    CreateMcpServerRequest, McpServer, OrderBy, PaginationParams, TimeoutMs,
+    UpdateMcpServerRequest, UpdateResult, MAX_COMMAND_LENGTH,
+};
+use crate::repositories::base_repository::RepositoryError;
+use crate::repositories::mcp_server_repository::mask_secret_env;
 use crate::repositories::{BaseRepository, McpServerRepository};
+use crate::Validator;
 
 /// 重用API服务器的ApiState
 pub use super::super::server::ApiState;
@@ -27,6 +33,19 @@ pub struct McpServerQuery {
     pub page: Option<i64>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// 为 `false` 时不查询总数
+    pub with_total: Option<bool>,
+    /// 排序列，如 `name`、`updated_at`，未指定时使用默认顺序
+    pub sort: Option<String>,
+    /// 排序方向，`asc`（默认）或 `desc`
+    pub dir: Option<String>,
+}
+
+/// 单个服务器查询参数
+#[derive(Debug, Default, Deserialize)]
+pub struct RevealQuery {
+    /// 为 `true` 时返回机密环境变量的明文，默认返回占位符
+    pub reveal: Option<bool>,
 }
 
 /// 创建MCP服务器
@@ -53,10 +72,11 @@ pub async fn create_mcp_server(
         return Err(ApiError::validation("启动命令不能为空".to_string()));
     }
 
+    Validator::validate_server_name(&request.name)?;
+    Validator::validate_max_length(&request.command, "启动命令", MAX_COMMAND_LENGTH)?;
+
     if let Some(timeout) = request.timeout {
-        if timeout <= 0 {
-            return Err(ApiError::validation("超时时间必须大于0".to_string()));
-        }
+        TimeoutMs::new(timeout).map_err(ApiError::validation)?;
     }
 
     // 验证服务器类型（如果提供）
@@ -75,7 +95,7 @@ pub async fn create_mcp_server(
             name = %request.name,
             "创建MCP服务器失败"
         );
-        ApiError::Database { message: format!("创建MCP服务器失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     // 获取创建的记录
@@ -85,7 +105,7 @@ pub async fn create_mcp_server(
             id = %id,
             "获取新创建的MCP服务器失败"
         );
-        ApiError::Database { message: format!("获取MCP服务器失败: {}", e) }
+        ApiError::from(e)
     })? {
         info!(
             id = %id,
@@ -93,6 +113,8 @@ pub async fn create_mcp_server(
             "MCP服务器创建成功"
         );
 
+        let mut server = server;
+        mask_secret_env(&mut server);
         Ok(Json(ApiResponse::success_with_message(
             server,
             "MCP服务器创建成功".to_string(),
@@ -112,20 +134,34 @@ pub async fn create_mcp_server(
 pub async fn get_mcp_server(
     State(state): State<ApiState>,
     Path(id): Path<i64>,
+    Query(query): Query<RevealQuery>,
 ) -> Result<Json<ApiResponse<McpServer>>, ApiError> {
+    let reveal = query.reveal.unwrap_or(false);
     info!(
         id = %id,
+        reveal = %reveal,
         "获取MCP服务器详情请求"
     );
 
     let repository = McpServerRepository::new(&state.db_manager, &state.crypto_service);
 
-    if id <= 0 {
-        return Err(ApiError::validation("无效的ID".to_string()));
-    }
+    Validator::validate_id(id, "id")?;
 
     match repository.find_by_id_parsed(id).await {
-        Ok(Some(server)) => {
+        Ok(Some(mut server)) => {
+            if reveal {
+                repository.reveal_secret_env(&mut server).await.map_err(|e| {
+                    error!(
+                        error = %e,
+                        id = %id,
+                        "解密MCP服务器机密环境变量失败"
+                    );
+                    ApiError::Crypto { message: format!("解密机密环境变量失败: {}", e) }
+                })?;
+            } else {
+                mask_secret_env(&mut server);
+            }
+
             info!(
                 id = %id,
                 name = %server.name,
@@ -150,7 +186,7 @@ pub async fn get_mcp_server(
                 id = %id,
                 "获取MCP服务器详情失败"
             );
-            Err(ApiError::Database { message: format!("获取MCP服务器失败: {}", e) })
+            Err(ApiError::from(e))
         }
     }
 }
@@ -160,7 +196,7 @@ pub async fn update_mcp_server(
     State(state): State<ApiState>,
     Path(id): Path<i64>,
     Json(request): Json<UpdateMcpServerRequest>,
-) -> Result<Json<ApiResponse<McpServer>>, ApiError> {
+) -> Result<Json<ApiResponse<UpdateResult<McpServer>>>, ApiError> {
     info!(
         id = %id,
         "更新MCP服务器请求"
@@ -168,9 +204,7 @@ pub async fn update_mcp_server(
 
     let repository = McpServerRepository::new(&state.db_manager, &state.crypto_service);
 
-    if id <= 0 {
-        return Err(ApiError::validation("无效的ID".to_string()));
-    }
+    Validator::validate_id(id, "id")?;
 
     // 检查记录是否存在
     let existing = repository.find_by_id_parsed(id).await.map_err(|e| {
@@ -179,34 +213,34 @@ pub async fn update_mcp_server(
             id = %id,
             "检查MCP服务器是否存在失败"
         );
-        ApiError::Database { message: format!("检查MCP服务器失败: {}", e) }
+        ApiError::from(e)
     })?;
 
-    if existing.is_none() {
+    let Some(existing) = existing else {
         warn!(
             id = %id,
             "尝试更新不存在的MCP服务器"
         );
         return Err(ApiError::NotFound { resource: "MCP服务器不存在".to_string() });
-    }
+    };
 
     // 验证更新数据
     if let Some(ref name) = request.name {
         if name.trim().is_empty() {
             return Err(ApiError::validation("服务器名称不能为空".to_string()));
         }
+        Validator::validate_server_name(name)?;
     }
 
     if let Some(ref command) = request.command {
         if command.trim().is_empty() {
             return Err(ApiError::validation("启动命令不能为空".to_string()));
         }
+        Validator::validate_max_length(command, "启动命令", MAX_COMMAND_LENGTH)?;
     }
 
     if let Some(timeout) = request.timeout {
-        if timeout <= 0 {
-            return Err(ApiError::validation("超时时间必须大于0".to_string()));
-        }
+        TimeoutMs::new(timeout).map_err(ApiError::validation)?;
     }
 
     if let Some(ref server_type) = request.r#type {
@@ -224,7 +258,7 @@ pub async fn update_mcp_server(
             id = %id,
             "更新MCP服务器失败"
         );
-        ApiError::Database { message: format!("更新MCP服务器失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     if !updated {
@@ -242,16 +276,20 @@ pub async fn update_mcp_server(
             id = %id,
             "获取更新后的MCP服务器失败"
         );
-        ApiError::Database { message: format!("获取MCP服务器失败: {}", e) }
+        ApiError::from(e)
     })? {
+        // 使用解密后的环境变量比较，返回前再遮盖密钥值
+        let mut result = UpdateResult::diff(&existing, server);
         info!(
             id = %id,
-            name = %server.name,
+            name = %result.entity.name,
+            changed_fields = ?result.changed_fields,
             "MCP服务器更新成功"
         );
 
+        mask_secret_env(&mut result.entity);
         Ok(Json(ApiResponse::success_with_message(
-            server,
+            result,
             "MCP服务器更新成功".to_string(),
         )))
     } else {
@@ -277,9 +315,7 @@ pub async fn delete_mcp_server(
 
     let repository = McpServerRepository::new(&state.db_manager, &state.crypto_service);
 
-    if id <= 0 {
-        return Err(ApiError::validation("无效的ID".to_string()));
-    }
+    Validator::validate_id(id, "id")?;
 
     // 检查记录是否存在
     let existing = repository.find_by_id::<McpServer>(id).await.map_err(|e| {
@@ -288,7 +324,7 @@ pub async fn delete_mcp_server(
             id = %id,
             "检查MCP服务器是否存在失败"
         );
-        ApiError::Database { message: format!("检查MCP服务器失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     if existing.is_none() {
@@ -306,7 +342,7 @@ pub async fn delete_mcp_server(
             id = %id,
             "删除MCP服务器失败"
         );
-        ApiError::Database { message: format!("删除MCP服务器失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     if !deleted {
@@ -332,7 +368,8 @@ pub async fn delete_mcp_server(
 pub async fn list_mcp_servers(
     State(state): State<ApiState>,
     Query(query): Query<McpServerQuery>,
-) -> Result<Json<ApiResponse<PagedResponse<McpServer>>>, ApiError> {
+    OriginalUri(uri): OriginalUri,
+) -> Result<PagedJson<McpServer>, ApiError> {
     info!(
         search = ?query.search,
         server_type = ?query.server_type,
@@ -344,17 +381,30 @@ pub async fn list_mcp_servers(
 
     let repository = McpServerRepository::new(&state.db_manager, &state.crypto_service);
 
+    // 所有列表模式都按 `sort` 排序
+    let order_by = OrderBy::from_query(
+        query.sort.as_deref(),
+        query.dir.as_deref(),
+        McpServerRepository::sortable_columns(),
+    )
+    .map_err(ApiError::validation)?;
+
     let result = if let Some(search_term) = query.search {
-        // 搜索模式
+        // 搜索模式，与供应商搜索使用相同的搜索词校验
+        Validator::validate_search_term(search_term.trim())?;
         let limit = query.limit.or(Some(50));
-        let servers = repository.search_mcp_servers(&search_term, limit).await.map_err(|e| {
-            error!(
-                error = %e,
-                search_term = %search_term,
-                "搜索MCP服务器失败"
-            );
-            ApiError::Database { message: format!("搜索MCP服务器失败: {}", e) }
-        })?;
+        let servers =
+            repository
+                .search_mcp_servers(&search_term, limit, order_by)
+                .await
+                .map_err(|e| {
+                    error!(
+                        error = %e,
+                        search_term = %search_term,
+                        "搜索MCP服务器失败"
+                    );
+                    ApiError::from(e)
+                })?;
 
         // 转换为分页响应格式
         let total = servers.len() as i64;
@@ -369,13 +419,13 @@ pub async fn list_mcp_servers(
         paged_result
     } else if let Some(server_type) = query.server_type {
         // 按类型筛选
-        let servers = repository.find_by_type(&server_type).await.map_err(|e| {
+        let servers = repository.find_by_type(&server_type, order_by).await.map_err(|e| {
             error!(
                 error = %e,
                 server_type = %server_type,
                 "根据类型获取MCP服务器列表失败"
             );
-            ApiError::Database { message: format!("获取MCP服务器列表失败: {}", e) }
+            ApiError::from(e)
         })?;
 
         // 转换为分页响应格式
@@ -391,12 +441,12 @@ pub async fn list_mcp_servers(
         paged_result
     } else if query.active_only.unwrap_or(false) {
         // 仅获取活跃服务器
-        let servers = repository.list_active_servers().await.map_err(|e| {
+        let servers = repository.list_active_servers(order_by).await.map_err(|e| {
             error!(
                 error = %e,
                 "获取活跃MCP服务器列表失败"
             );
-            ApiError::Database { message: format!("获取MCP服务器列表失败: {}", e) }
+            ApiError::from(e)
         })?;
 
         // 转换为分页响应格式
@@ -411,24 +461,32 @@ pub async fn list_mcp_servers(
         paged_result
     } else {
         // 分页获取所有服务器
-        let pagination_params =
-            PaginationParams { page: query.page, limit: query.limit, offset: query.offset };
+        let pagination_params = PaginationParams {
+            page: query.page,
+            limit: query.limit,
+            offset: query.offset,
+            with_total: query.with_total,
+            order_by,
+        };
 
         repository.paginate::<McpServer>(&pagination_params).await.map_err(|e| {
             error!(
                 error = %e,
                 "分页获取MCP服务器列表失败"
             );
-            ApiError::Database { message: format!("获取MCP服务器列表失败: {}", e) }
+            ApiError::from(e)
         })?
     };
 
     let paged_response = crate::api::responses::PagedResponse::from_paged_result_with_message(
-        result,
+        result.map(|mut server| {
+            mask_secret_env(&mut server);
+            server
+        }),
         "获取MCP服务器列表成功".to_string(),
     );
 
-    Ok(Json(ApiResponse::success(paged_response)))
+    Ok(PagedJson::new(paged_response, &uri))
 }
 
 /// 测试MCP服务器配置
@@ -443,9 +501,7 @@ pub async fn test_mcp_server(
 
     let repository = McpServerRepository::new(&state.db_manager, &state.crypto_service);
 
-    if id <= 0 {
-        return Err(ApiError::validation("无效的ID".to_string()));
-    }
+    Validator::validate_id(id, "id")?;
 
     match repository.test_server_config(id).await {
         Ok(is_valid) => {
@@ -465,13 +521,14 @@ pub async fn test_mcp_server(
                 message.to_string(),
             )))
         }
+        Err(RepositoryError::NotFound(resource)) => Err(ApiError::NotFound { resource }),
         Err(e) => {
             error!(
                 error = %e,
                 id = %id,
                 "MCP服务器配置测试失败"
             );
-            Err(ApiError::Database { message: format!("配置测试失败: {}", e) })
+            Err(ApiError::from(e))
         }
     }
 }
@@ -490,7 +547,7 @@ pub async fn get_mcp_server_stats(
             error = %e,
             "获取MCP服务器总数失败"
         );
-        ApiError::Database { message: format!("获取统计信息失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     // 获取stdio类型数量
@@ -499,7 +556,7 @@ pub async fn get_mcp_server_stats(
             error = %e,
             "获取stdio类型MCP服务器数量失败"
         );
-        ApiError::Database { message: format!("获取统计信息失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     // 获取sse类型数量
@@ -508,16 +565,16 @@ pub async fn get_mcp_server_stats(
             error = %e,
             "获取sse类型MCP服务器数量失败"
         );
-        ApiError::Database { message: format!("获取统计信息失败: {}", e) }
+        ApiError::from(e)
     })?;
 
     // 获取活跃服务器数量
-    let active_servers = repository.list_active_servers().await.map_err(|e| {
+    let active_servers = repository.list_active_servers(None).await.map_err(|e| {
         error!(
             error = %e,
             "获取活跃MCP服务器数量失败"
         );
-        ApiError::Database { message: format!("获取统计信息失败: {}", e) }
+        ApiError::from(e)
     })?;
     let active_count = active_servers.len() as i64;
 
@@ -0,0 +1,60 @@
+// 数据迁移API处理器
+//
+// 提供数据迁移历史等HTTP API接口实现
+
+use axum::{
+    extract::{Query, State},
+    response::Json,
+    Router,
+};
+use serde::Deserialize;
+use tracing::{error, info};
+
+use crate::api::error::ApiError;
+use crate::api::responses::ApiResponse;
+use crate::models::MigrationRun;
+use crate::repositories::MigrationRunRepository;
+
+/// 重用API服务器的ApiState
+pub use super::super::server::ApiState;
+
+/// 迁移历史查询参数
+#[derive(Debug, Deserialize)]
+pub struct MigrationHistoryQuery {
+    pub limit: Option<i64>,
+}
+
+/// 获取数据迁移历史
+pub async fn get_migration_history(
+    State(state): State<ApiState>,
+    Query(query): Query<MigrationHistoryQuery>,
+) -> Result<Json<ApiResponse<Vec<MigrationRun>>>, ApiError> {
+    info!(
+        limit = ?query.limit,
+        "获取数据迁移历史请求"
+    );
+
+    let repository = MigrationRunRepository::new(&state.db_manager);
+
+    let runs = repository.list_migration_runs(query.limit).await.map_err(|e| {
+        error!(
+            error = %e,
+            "获取数据迁移历史失败"
+        );
+        ApiError::from(e)
+    })?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        runs,
+        "获取数据迁移历史成功".to_string(),
+    )))
+}
+
+/// 创建数据迁移路由
+pub fn routes() -> Router<ApiState> {
+    use axum::routing::get;
+
+    Router::new()
+        // 获取数据迁移历史
+        .route("/history", get(get_migration_history))
+}
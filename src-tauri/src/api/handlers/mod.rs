@@ -7,8 +7,13 @@ pub mod agent_guide;
 pub mod claude;
 pub mod codex;
 pub mod common_config;
+pub mod config;
+pub mod maintenance;
 pub mod mcp_server;
+pub mod migration;
+pub mod provider_group;
+pub mod schema;
+pub mod tasks;
 // TODO: 暂时注释掉其他处理器，等待后续实现
 // pub mod agent;
 // pub mod mcp;
-// pub mod config;
@@ -0,0 +1,122 @@
+// 供应商分组API处理器
+//
+// 提供供应商分组的列表、创建和激活HTTP API接口实现
+
+use axum::{
+    extract::{Path, State},
+    response::Json,
+    Router,
+};
+use tracing::{error, info};
+
+use crate::api::error::ApiError;
+use crate::api::responses::ApiResponse;
+use crate::migration::config_generator::ConfigGenerator;
+use crate::models::CreateProviderGroupRequest;
+use crate::services::provider_group_service::{
+    GroupActivation, ProviderGroupDetail, ProviderGroupError, ProviderGroupService,
+};
+
+/// 重用API服务器的ApiState
+pub use super::super::server::ApiState;
+
+impl From<ProviderGroupError> for ApiError {
+    fn from(error: ProviderGroupError) -> Self {
+        match error {
+            ProviderGroupError::Validation(message) => ApiError::validation(message),
+            ProviderGroupError::GroupNotFound(id) => {
+                ApiError::NotFound { resource: format!("供应商分组 {} 不存在", id) }
+            }
+            ProviderGroupError::NameAlreadyExists(name) => ApiError::Conflict {
+                message: format!("供应商分组名称 '{}' 已存在", name),
+            },
+            ProviderGroupError::ProviderNotFound { mode, id } => {
+                ApiError::NotFound { resource: format!("{} 供应商 {} 不存在", mode, id) }
+            }
+            ProviderGroupError::Database(e) => ApiError::Database { message: e.to_string() },
+            ProviderGroupError::Repository(e) => e.into(),
+            ProviderGroupError::ConfigGenerator(e) => {
+                ApiError::Configuration { message: e.to_string() }
+            }
+        }
+    }
+}
+
+/// 创建供应商分组服务，未配置生成目录时使用用户主目录
+fn group_service(state: &ApiState) -> Result<ProviderGroupService, ApiError> {
+    let generator = match &state.config_generator {
+        Some(generator) => generator.clone(),
+        None => ConfigGenerator::new()
+            .map_err(|e| ApiError::Configuration { message: e.to_string() })?,
+    };
+
+    Ok(ProviderGroupService::new(
+        state.db_manager.clone(),
+        state.crypto_service.clone(),
+        generator,
+    ))
+}
+
+/// 获取所有供应商分组
+pub async fn list_provider_groups(
+    State(state): State<ApiState>,
+) -> Result<Json<ApiResponse<Vec<ProviderGroupDetail>>>, ApiError> {
+    info!("获取供应商分组列表请求");
+
+    let groups = group_service(&state)?.list_groups().await?;
+    Ok(Json(ApiResponse::success_with_message(
+        groups,
+        "获取供应商分组列表成功".to_string(),
+    )))
+}
+
+/// 创建供应商分组
+pub async fn create_provider_group(
+    State(state): State<ApiState>,
+    Json(request): Json<CreateProviderGroupRequest>,
+) -> Result<Json<ApiResponse<ProviderGroupDetail>>, ApiError> {
+    info!(
+        name = %request.name,
+        members = %request.members.len(),
+        "创建供应商分组请求"
+    );
+
+    let group = group_service(&state)?.create_group(&request).await.map_err(|e| {
+        error!(name = %request.name, error = %e, "创建供应商分组失败");
+        ApiError::from(e)
+    })?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        group,
+        "供应商分组创建成功".to_string(),
+    )))
+}
+
+/// 激活供应商分组
+pub async fn activate_provider_group(
+    State(state): State<ApiState>,
+    Path(id): Path<i64>,
+) -> Result<Json<ApiResponse<GroupActivation>>, ApiError> {
+    info!(id = %id, "激活供应商分组请求");
+
+    let activation = group_service(&state)?.activate(id).await.map_err(|e| {
+        error!(id = %id, error = %e, "激活供应商分组失败");
+        ApiError::from(e)
+    })?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        activation,
+        "供应商分组已激活".to_string(),
+    )))
+}
+
+/// 创建供应商分组路由
+pub fn routes() -> Router<ApiState> {
+    use axum::routing::{get, post};
+
+    Router::new()
+        // 获取分组列表 / 创建分组
+        .route("/", get(list_provider_groups).post(create_provider_group))
+        // 激活分组
+        .route("/:id/activate", post(activate_provider_group))
+}
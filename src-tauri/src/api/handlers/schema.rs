@@ -0,0 +1,54 @@
+// 数据库结构诊断API处理器
+//
+// 将当前数据库的表结构与迁移脚本生成的期望结构进行对比
+
+use axum::{extract::State, response::Json, Router};
+use tracing::{error, info, warn};
+
+use crate::api::error::ApiError;
+use crate::api::responses::ApiResponse;
+use crate::migration::schema_diff::{expected_schema_pool, schema_diff, SchemaDiff};
+
+/// 重用API服务器的ApiState
+pub use super::super::server::ApiState;
+
+/// 对比当前数据库与期望结构
+pub async fn get_schema_diff(
+    State(state): State<ApiState>,
+) -> Result<Json<ApiResponse<SchemaDiff>>, ApiError> {
+    info!("数据库结构对比请求");
+
+    let expected = expected_schema_pool().await.map_err(|e| {
+        error!(error = %e, "创建期望数据库结构失败");
+        ApiError::Database { message: format!("创建期望数据库结构失败: {}", e) }
+    })?;
+
+    let diff = schema_diff(&expected, state.db_manager.pool()).await.map_err(|e| {
+        error!(error = %e, "数据库结构对比失败");
+        ApiError::Database { message: format!("数据库结构对比失败: {}", e) }
+    })?;
+    expected.close().await;
+
+    if !diff.is_empty() {
+        warn!(
+            added_tables = %diff.added_tables.len(),
+            removed_tables = %diff.removed_tables.len(),
+            changed_tables = %diff.changed_tables.len(),
+            "当前数据库结构与期望结构不一致"
+        );
+    }
+
+    Ok(Json(ApiResponse::success_with_message(
+        diff,
+        "数据库结构对比完成".to_string(),
+    )))
+}
+
+/// 创建数据库结构诊断路由
+pub fn routes() -> Router<ApiState> {
+    use axum::routing::get;
+
+    Router::new()
+        // 对比当前数据库与期望结构
+        .route("/diff", get(get_schema_diff))
+}
@@ -0,0 +1,100 @@
+// 后台任务API处理器
+//
+// 提供后台任务状态查询、立即触发和暂停/恢复的HTTP API接口实现
+
+use axum::{
+    extract::{Path, State},
+    response::Json,
+    Router,
+};
+use tracing::{error, info};
+
+use crate::api::error::ApiError;
+use crate::api::responses::ApiResponse;
+use crate::task_registry::{TaskInfo, TaskRegistryError};
+
+/// 重用API服务器的ApiState
+pub use super::super::server::ApiState;
+
+impl From<TaskRegistryError> for ApiError {
+    fn from(error: TaskRegistryError) -> Self {
+        match error {
+            TaskRegistryError::NotFound(name) => {
+                ApiError::NotFound { resource: format!("后台任务 {} 不存在", name) }
+            }
+            other => ApiError::BadRequest { message: other.to_string() },
+        }
+    }
+}
+
+/// 获取所有后台任务的状态
+pub async fn list_tasks(
+    State(state): State<ApiState>,
+) -> Result<Json<ApiResponse<Vec<TaskInfo>>>, ApiError> {
+    info!("获取后台任务列表请求");
+
+    Ok(Json(ApiResponse::success_with_message(
+        state.task_registry.list(),
+        "获取后台任务列表成功".to_string(),
+    )))
+}
+
+/// 立即执行一次后台任务
+pub async fn run_task(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> Result<Json<ApiResponse<TaskInfo>>, ApiError> {
+    info!(task = %name, "手动执行后台任务请求");
+
+    let task = state.task_registry.run_now(&name).await.map_err(|e| {
+        error!(task = %name, error = %e, "手动执行后台任务失败");
+        ApiError::from(e)
+    })?;
+
+    Ok(Json(ApiResponse::success_with_message(
+        task,
+        "后台任务执行完成".to_string(),
+    )))
+}
+
+/// 暂停后台任务的定时执行
+pub async fn pause_task(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> Result<Json<ApiResponse<TaskInfo>>, ApiError> {
+    info!(task = %name, "暂停后台任务请求");
+
+    let task = state.task_registry.set_paused(&name, true)?;
+    Ok(Json(ApiResponse::success_with_message(
+        task,
+        "后台任务已暂停".to_string(),
+    )))
+}
+
+/// 恢复后台任务的定时执行
+pub async fn resume_task(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> Result<Json<ApiResponse<TaskInfo>>, ApiError> {
+    info!(task = %name, "恢复后台任务请求");
+
+    let task = state.task_registry.set_paused(&name, false)?;
+    Ok(Json(ApiResponse::success_with_message(
+        task,
+        "后台任务已恢复".to_string(),
+    )))
+}
+
+/// 创建后台任务路由
+pub fn routes() -> Router<ApiState> {
+    use axum::routing::{get, post};
+
+    Router::new()
+        // 获取后台任务列表
+        .route("/", get(list_tasks))
+        // 立即执行后台任务
+        .route("/:name/run", post(run_task))
+        // 暂停/恢复后台任务
+        .route("/:name/pause", post(pause_task))
+        .route("/:name/resume", post(resume_task))
+}
@@ -3,14 +3,17 @@
 // 提供CORS、日志记录、认证等中间件功能
 
 use crate::api::error::ApiError;
+use crate::database::DatabaseManager;
 use axum::{
-    extract::Request,
-    http::{header, StatusCode},
+    extract::{Request, State},
+    http::{header, Method, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
-use std::time::Instant;
-use tracing::{info, warn};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{info, info_span, warn, Instrument};
 use uuid::Uuid;
 
 /// 请求上下文信息
@@ -25,6 +28,9 @@ pub struct RequestContext {
 }
 
 /// 请求追踪中间件
+///
+/// 为每个请求生成请求ID，并在 `request` span 中执行后续处理。
+/// 新建的后台任务需要通过 `in_current_span()` 继承该span
 pub async fn request_tracking_middleware(
     request: Request,
     next: Next,
@@ -75,8 +81,9 @@ pub async fn request_tracking_middleware(
     let mut request = request;
     request.extensions_mut().insert(context);
 
-    // 继续处理请求
-    let response = next.run(request).await;
+    // 在请求span中继续处理，之后同一任务内的日志都带有请求ID
+    let span = info_span!("request", request_id = %request_id, method = %method, uri = %uri);
+    let response = next.run(request).instrument(span).await;
 
     // 计算处理时间
     let duration = start_time.elapsed();
@@ -214,3 +221,215 @@ pub async fn global_error_handler(request: Request, next: Next) -> Response {
 
     response
 }
+
+/// 请求超时配置
+#[derive(Debug, Clone)]
+pub struct RequestTimeoutConfig {
+    /// 单个请求的最长处理时间
+    pub timeout: Duration,
+    /// 不受超时限制的路径前缀（如数据迁移等长耗时接口）
+    pub exempt_paths: Arc<Vec<String>>,
+}
+
+impl RequestTimeoutConfig {
+    pub fn new(timeout: Duration, exempt_paths: Vec<String>) -> Self {
+        Self { timeout, exempt_paths: Arc::new(exempt_paths) }
+    }
+
+    /// 判断路径是否豁免超时限制
+    ///
+    /// 不含 `*` 的条目按前缀匹配；含 `*` 的条目按路径段完整匹配，`*` 匹配任意单个路径段
+    pub fn is_exempt(&self, path: &str) -> bool {
+        self.exempt_paths.iter().any(|pattern| {
+            if pattern.contains('*') {
+                matches_segments(pattern, path)
+            } else {
+                path.starts_with(pattern.as_str())
+            }
+        })
+    }
+}
+
+/// 按路径段匹配，段数必须相同
+fn matches_segments(pattern: &str, path: &str) -> bool {
+    let mut pattern = pattern.split('/');
+    let mut path = path.split('/');
+    loop {
+        match (pattern.next(), path.next()) {
+            (None, None) => return true,
+            (Some(expected), Some(segment)) if expected == "*" || expected == segment => {}
+            _ => return false,
+        }
+    }
+}
+
+impl Default for RequestTimeoutConfig {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30), Vec::new())
+    }
+}
+
+/// 请求超时中间件
+///
+/// 超过配置时间仍未完成的请求会被中止，并返回504错误
+pub async fn request_timeout_middleware(
+    State(config): State<RequestTimeoutConfig>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+    if config.is_exempt(&path) {
+        return next.run(request).await;
+    }
+
+    let method = request.method().to_string();
+    let request_id = request
+        .extensions()
+        .get::<RequestContext>()
+        .map(|ctx| ctx.request_id.clone())
+        .unwrap_or_else(|| "unknown".to_string());
+    let start_time = Instant::now();
+
+    match tokio::time::timeout(config.timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            let elapsed = start_time.elapsed();
+            warn!(
+                request_id = %request_id,
+                method = %method,
+                path = %path,
+                elapsed_ms = %elapsed.as_millis(),
+                timeout_ms = %config.timeout.as_millis(),
+                "API请求处理超时"
+            );
+
+            ApiError::GatewayTimeout {
+                message: format!("请求超过 {} 毫秒未完成", config.timeout.as_millis()),
+            }
+            .into_response()
+        }
+    }
+}
+
+/// 写操作计数中间件
+///
+/// 成功的写请求（POST/PUT/PATCH/DELETE）计入数据库写操作次数，用于触发自动优化
+pub async fn write_tracking_middleware(
+    State(db_manager): State<Arc<DatabaseManager>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let is_write = matches!(
+        *request.method(),
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    );
+
+    let response = next.run(request).await;
+    if is_write && response.status().is_success() {
+        db_manager.record_write();
+    }
+    response
+}
+
+/// 维护接口路径前缀，维护期间不受写请求限制
+pub const MAINTENANCE_PATH_PREFIX: &str = "/api/v1/maintenance";
+
+/// 维护模式开关，在所有克隆之间共享
+///
+/// 同时记录正在处理的写请求数，维护操作在这些请求完成后才开始修改数据
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceMode {
+    active: Arc<AtomicBool>,
+    writes_in_flight: Arc<AtomicUsize>,
+    writes_drained: Arc<tokio::sync::Notify>,
+}
+
+impl MaintenanceMode {
+    /// 是否处于维护模式
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Acquire)
+    }
+
+    /// 进入维护模式，已处于维护模式时返回 `None`
+    ///
+    /// 返回的守卫释放时自动退出维护模式
+    pub fn enter(&self) -> Option<MaintenanceGuard> {
+        self.active
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .ok()
+            .map(|_| MaintenanceGuard { active: self.active.clone() })
+    }
+
+    /// 等待进入维护模式前已开始的写请求全部完成
+    pub async fn wait_for_writes(&self) {
+        loop {
+            // 先注册通知再检查计数，避免错过两者之间完成的请求
+            let drained = self.writes_drained.notified();
+            if self.writes_in_flight.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            drained.await;
+        }
+    }
+
+    /// 开始处理写请求，处于维护模式时返回 `None`
+    ///
+    /// 先计数再检查开关，保证 `wait_for_writes` 不会漏掉与进入维护模式同时开始的请求
+    fn begin_write(&self) -> Option<WriteGuard> {
+        self.writes_in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = WriteGuard { maintenance: self.clone() };
+        if self.active.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(guard)
+    }
+}
+
+/// 正在处理的写请求，释放时减少计数
+struct WriteGuard {
+    maintenance: MaintenanceMode,
+}
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        if self.maintenance.writes_in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.maintenance.writes_drained.notify_waiters();
+        }
+    }
+}
+
+/// 维护模式守卫
+pub struct MaintenanceGuard {
+    active: Arc<AtomicBool>,
+}
+
+impl Drop for MaintenanceGuard {
+    fn drop(&mut self) {
+        self.active.store(false, Ordering::Release);
+    }
+}
+
+/// 维护模式中间件
+///
+/// 维护期间拒绝维护接口以外的写请求，返回503错误；其他写请求在处理期间计入进行中的写请求
+pub async fn maintenance_middleware(
+    State(maintenance): State<MaintenanceMode>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let is_write = matches!(
+        *request.method(),
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    );
+    let path = request.uri().path();
+
+    if !is_write || path.starts_with(MAINTENANCE_PATH_PREFIX) {
+        return next.run(request).await;
+    }
+
+    let Some(_write) = maintenance.begin_write() else {
+        warn!(method = %request.method(), path = %path, "维护期间拒绝写请求");
+        return ApiError::ServiceUnavailable.into_response();
+    };
+    next.run(request).await
+}
@@ -7,7 +7,10 @@ pub mod error;
 pub mod handlers;
 pub mod middleware;
 pub mod responses;
+pub mod rpc;
 pub mod server;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 // 重新导出主要组件
 pub use error::{ApiError, ApiResult};
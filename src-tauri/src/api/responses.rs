@@ -3,14 +3,26 @@
 // 定义统一的API响应格式和分页响应
 
 use crate::models::PagedResult;
+use crate::services::outcome::Warning;
+use axum::body::{Body, Bytes};
+use axum::http::{header, HeaderMap, HeaderName, HeaderValue, Uri};
+use axum::response::{IntoResponse, Json, Response};
+use futures::channel::mpsc;
+use futures::SinkExt;
 use serde::{Deserialize, Serialize};
 
+/// 流式列表响应的缓冲块数，客户端读取较慢时写入端会等待
+const STREAM_BUFFER_CHUNKS: usize = 16;
+
 /// 统一API响应格式
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
     pub message: Option<String>,
+    /// 操作成功但有值得注意的副作用时的提示，如启用供应商时禁用了其他供应商
+    #[serde(default)]
+    pub warnings: Vec<Warning>,
     pub timestamp: String,
 }
 
@@ -21,6 +33,7 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             message: None,
+            warnings: Vec::new(),
             timestamp: chrono::Utc::now().to_rfc3339(),
         }
     }
@@ -31,6 +44,7 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             message: Some(message),
+            warnings: Vec::new(),
             timestamp: chrono::Utc::now().to_rfc3339(),
         }
     }
@@ -41,6 +55,7 @@ impl<T> ApiResponse<T> {
             success: true,
             data: None,
             message: Some("操作成功".to_string()),
+            warnings: Vec::new(),
             timestamp: chrono::Utc::now().to_rfc3339(),
         }
     }
@@ -51,9 +66,27 @@ impl<T> ApiResponse<T> {
             success: true,
             data: None,
             message: Some(message),
+            warnings: Vec::new(),
             timestamp: chrono::Utc::now().to_rfc3339(),
         }
     }
+
+    /// 创建失败响应，`data` 中为结构化的错误信息
+    pub fn failure(data: T, message: String) -> Self {
+        Self {
+            success: false,
+            data: Some(data),
+            message: Some(message),
+            warnings: Vec::new(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// 附加警告信息
+    pub fn with_warnings(mut self, warnings: Vec<Warning>) -> Self {
+        self.warnings = warnings;
+        self
+    }
 }
 
 /// 分页响应格式
@@ -105,8 +138,200 @@ impl<T> PagedResponse<T> {
 pub struct PaginationInfo {
     pub page: i64,
     pub limit: i64,
-    pub total: i64,
-    pub total_pages: i64,
+    /// 请求 `with_total=false` 时为 `None`
+    pub total: Option<i64>,
+    pub total_pages: Option<i64>,
+}
+
+/// 列表接口响应：保留JSON信封，同时通过响应头提供分页信息
+///
+/// 设置 `X-Page`、`X-Per-Page`、已知总数时的 `X-Total-Count`，以及带 `rel="next"`/`rel="prev"` 的
+/// `Link` 头。请求带 `envelope=false` 时响应体只包含数据数组
+pub struct PagedJson<T> {
+    response: PagedResponse<T>,
+    uri: Uri,
+}
+
+impl<T> PagedJson<T> {
+    /// 基于请求地址创建分页响应，`Link` 头中的地址在其基础上替换页码
+    pub fn new(response: PagedResponse<T>, uri: &Uri) -> Self {
+        Self { response, uri: uri.clone() }
+    }
+
+    /// 是否保留JSON信封，请求 `envelope=false` 时返回纯数组
+    fn envelope(&self) -> bool {
+        !query_pairs(&self.uri).any(|(name, value)| name == "envelope" && value == "false")
+    }
+
+    /// 分页响应头
+    fn headers(&self) -> HeaderMap {
+        let pagination = &self.response.pagination;
+        let mut headers = HeaderMap::new();
+
+        let mut insert = |name: &'static str, value: String| {
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                headers.insert(HeaderName::from_static(name), value);
+            }
+        };
+        insert("x-page", pagination.page.to_string());
+        insert("x-per-page", pagination.limit.to_string());
+        if let Some(total) = pagination.total {
+            insert("x-total-count", total.to_string());
+        }
+
+        let has_next = match pagination.total_pages {
+            Some(total_pages) => pagination.page < total_pages,
+            // 未查询总数时，返回满页即认为可能还有下一页
+            None => pagination.limit > 0 && self.response.data.len() as i64 >= pagination.limit,
+        };
+        let mut links = Vec::new();
+        if has_next {
+            links.push(format!(
+                "<{}>; rel=\"next\"",
+                self.page_link(pagination.page + 1)
+            ));
+        }
+        if pagination.page > 1 {
+            links.push(format!(
+                "<{}>; rel=\"prev\"",
+                self.page_link(pagination.page - 1)
+            ));
+        }
+        if !links.is_empty() {
+            insert("link", links.join(", "));
+        }
+
+        headers
+    }
+
+    /// 指定页码的请求地址，保留其他查询参数（`offset` 会覆盖页码，因此去掉）
+    fn page_link(&self, page: i64) -> String {
+        let mut params: Vec<&str> = self
+            .uri
+            .query()
+            .unwrap_or_default()
+            .split('&')
+            .filter(|pair| {
+                let name = pair.split('=').next().unwrap_or_default();
+                !pair.is_empty() && name != "page" && name != "offset"
+            })
+            .collect();
+        let page = format!("page={}", page);
+        params.push(&page);
+
+        format!("{}?{}", self.uri.path(), params.join("&"))
+    }
+}
+
+impl<T: Serialize> IntoResponse for PagedJson<T> {
+    fn into_response(self) -> Response {
+        let headers = self.headers();
+        let body = if self.envelope() {
+            Json(ApiResponse::success(self.response)).into_response()
+        } else {
+            Json(self.response.data).into_response()
+        };
+
+        (headers, body).into_response()
+    }
+}
+
+/// 创建流式列表响应
+///
+/// 响应体与默认列表接口的 `ApiResponse<PagedResponse<T>>` 格式相同，`data.data` 数组的元素由返回的
+/// 写入端逐个写出，全部结果作为一页，`data.pagination` 在 `finish` 时写出。
+/// 写入端未调用 `finish` 就被丢弃时响应体不完整，客户端可以据此发现读取中途失败
+pub fn streaming_list(message: impl Into<String>) -> (StreamingListWriter, Response) {
+    let (sender, receiver) = mpsc::channel(STREAM_BUFFER_CHUNKS);
+    let timestamp = chrono::Utc::now().to_rfc3339();
+
+    // 去掉对象的右括号，后面接着写出 `data` 字段
+    let open = |value: serde_json::Value| {
+        let mut json = value.to_string();
+        json.pop();
+        json
+    };
+    let prefix = format!(
+        "{},\"data\":{},\"data\":[",
+        open(serde_json::json!({
+            "success": true,
+            "message": null,
+            "warnings": [],
+            "timestamp": timestamp,
+        })),
+        open(serde_json::json!({
+            "success": true,
+            "message": message.into(),
+            "timestamp": timestamp,
+        }))
+    );
+
+    let response = (
+        [(header::CONTENT_TYPE, "application/json")],
+        Body::from_stream(receiver),
+    )
+        .into_response();
+    (
+        StreamingListWriter { sender, prefix: Some(prefix), count: 0 },
+        response,
+    )
+}
+
+/// 流式列表响应的写入端
+pub struct StreamingListWriter {
+    sender: mpsc::Sender<Result<Bytes, std::io::Error>>,
+    /// 响应外层字段，随第一个数据块写出
+    prefix: Option<String>,
+    /// 已写出的元素数
+    count: i64,
+}
+
+impl StreamingListWriter {
+    /// 写出一个元素，客户端已断开连接时返回 `false`
+    pub async fn push<T: Serialize>(&mut self, item: &T) -> bool {
+        let mut chunk = match self.prefix.take() {
+            Some(prefix) => prefix.into_bytes(),
+            None => vec![b','],
+        };
+        if let Err(e) = serde_json::to_writer(&mut chunk, item) {
+            self.abort(format!("序列化列表元素失败: {}", e)).await;
+            return false;
+        }
+
+        self.count += 1;
+        self.sender.send(Ok(Bytes::from(chunk))).await.is_ok()
+    }
+
+    /// 结束 `data.data` 数组，写出分页信息并结束响应体
+    pub async fn finish(mut self) {
+        let pagination = PaginationInfo {
+            page: 1,
+            limit: self.count,
+            total: Some(self.count),
+            total_pages: Some(i64::from(self.count > 0)),
+        };
+        let mut chunk = self.prefix.take().unwrap_or_default();
+        chunk.push_str("],\"pagination\":");
+        chunk.push_str(&serde_json::json!(pagination).to_string());
+        chunk.push_str("}}");
+        let _ = self.sender.send(Ok(Bytes::from(chunk))).await;
+    }
+
+    /// 以错误中止响应体
+    pub async fn abort(&mut self, reason: String) {
+        let error = std::io::Error::other(reason);
+        let _ = self.sender.send(Err(error)).await;
+        self.sender.close_channel();
+    }
+}
+
+/// 解析请求地址中的查询参数（不做URL解码，只用于匹配简单的开关参数）
+fn query_pairs(uri: &Uri) -> impl Iterator<Item = (&str, &str)> {
+    uri.query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
 }
 
 /// API错误响应格式
@@ -0,0 +1,342 @@
+// 前端命令调用接口
+//
+// 通过单个 Tauri 命令 `rpc(method, params)` 按名称分发到已注册的处理器，
+// 新增操作只需注册处理器，无需新增 Tauri 命令。前端可以调用 `list_methods` 查询可用方法
+
+use futures::future::{BoxFuture, FutureExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::api::error::{ApiError, ApiResult};
+use crate::api::responses::ApiResponse;
+use crate::crypto::{CryptoService, KeyRing};
+use crate::database::DatabaseManager;
+use crate::models::PaginationParams;
+use crate::services::claude_service::ClaudeProviderService;
+use crate::services::codex_service::CodexProviderService;
+use crate::services::common_config_service::CommonConfigService;
+use crate::services::http::HttpClient;
+
+/// 列出所有可用方法的内置方法名
+pub const LIST_METHODS: &str = "list_methods";
+
+/// 方法不存在时的错误代码
+pub const METHOD_NOT_FOUND: &str = "METHOD_NOT_FOUND";
+
+/// 处理器使用的共享资源
+#[derive(Clone)]
+pub struct RpcContext {
+    pub db_manager: Arc<DatabaseManager>,
+    pub crypto_service: Arc<CryptoService>,
+    /// 通用配置按类别选择加密密钥的密钥环
+    pub key_ring: KeyRing,
+    /// 连接测试和模型更新共用的上游HTTP客户端
+    pub http_client: HttpClient,
+}
+
+impl RpcContext {
+    /// 创建共享资源，所有类别的通用配置使用同一密钥
+    pub fn new(db_manager: Arc<DatabaseManager>, crypto_service: Arc<CryptoService>) -> Self {
+        let key_ring = KeyRing::new((*crypto_service).clone());
+        Self {
+            db_manager,
+            crypto_service,
+            key_ring,
+            http_client: HttpClient::default(),
+        }
+    }
+
+    /// 使用按类别区分密钥的密钥环
+    pub fn with_key_ring(mut self, key_ring: KeyRing) -> Self {
+        self.key_ring = key_ring;
+        self
+    }
+
+    /// 使用密钥环的通用配置服务
+    pub fn common_config_service(&self) -> CommonConfigService {
+        CommonConfigService::new(self.db_manager.clone(), self.crypto_service.clone())
+            .with_key_ring(self.key_ring.clone())
+    }
+}
+
+type RpcHandler =
+    Arc<dyn Fn(RpcContext, Value) -> BoxFuture<'static, ApiResult<Value>> + Send + Sync>;
+
+struct RpcMethod {
+    description: &'static str,
+    handler: RpcHandler,
+}
+
+/// 可用方法的描述
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcMethodInfo {
+    pub name: String,
+    pub description: String,
+}
+
+/// 按名称分发调用的方法注册表
+#[derive(Clone, Default)]
+pub struct RpcRegistry {
+    methods: BTreeMap<String, Arc<RpcMethod>>,
+}
+
+impl RpcRegistry {
+    /// 创建空注册表，只包含内置的 `list_methods`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 创建包含所有内置方法的注册表
+    pub fn with_default_methods() -> Self {
+        let mut registry = Self::new();
+
+        registry.register(
+            "claude.list_providers",
+            "分页获取Claude供应商",
+            |ctx, params| async move {
+                let params: PaginationParams = parse_params(params)?;
+                let result = claude_service(&ctx).list_providers(params).await?;
+                to_value(result)
+            },
+        );
+        registry.register(
+            "claude.get_provider",
+            "根据ID获取Claude供应商",
+            |ctx, params| async move {
+                let IdParams { id } = parse_params(params)?;
+                let provider = claude_service(&ctx).get_provider(id).await?.ok_or_else(|| {
+                    ApiError::NotFound { resource: "Claude供应商不存在".to_string() }
+                })?;
+                to_value(provider)
+            },
+        );
+        registry.register(
+            "claude.current_provider",
+            "获取当前启用的Claude供应商",
+            |ctx, _| async move {
+                let provider = claude_service(&ctx).get_current_provider().await?;
+                to_value(provider)
+            },
+        );
+        registry.register(
+            "codex.list_providers",
+            "分页获取Codex供应商",
+            |ctx, params| async move {
+                let params: PaginationParams = parse_params(params)?;
+                let result = codex_service(&ctx).list_providers(params).await?;
+                to_value(result)
+            },
+        );
+        registry.register(
+            "codex.get_provider",
+            "根据ID获取Codex供应商",
+            |ctx, params| async move {
+                let IdParams { id } = parse_params(params)?;
+                let provider = codex_service(&ctx).get_provider(id).await?.ok_or_else(|| {
+                    ApiError::NotFound { resource: "Codex供应商不存在".to_string() }
+                })?;
+                to_value(provider)
+            },
+        );
+        registry.register(
+            "codex.current_provider",
+            "获取当前启用的Codex供应商",
+            |ctx, _| async move {
+                let provider = codex_service(&ctx).get_current_provider().await?;
+                to_value(provider)
+            },
+        );
+        registry.register(
+            "config.effective",
+            "获取所有配置的生效值",
+            |ctx, _| async move {
+                let configs = ctx.common_config_service().effective_config().await?;
+                to_value(configs)
+            },
+        );
+
+        registry
+    }
+
+    /// 注册方法，同名方法会被替换
+    pub fn register<F, Fut>(&mut self, name: &str, description: &'static str, handler: F)
+    where
+        F: Fn(RpcContext, Value) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ApiResult<Value>> + Send + 'static,
+    {
+        let handler: RpcHandler = Arc::new(move |ctx, params| handler(ctx, params).boxed());
+        if self
+            .methods
+            .insert(
+                name.to_string(),
+                Arc::new(RpcMethod { description, handler }),
+            )
+            .is_some()
+        {
+            debug!(method = %name, "替换已注册的RPC方法");
+        }
+    }
+
+    /// 所有可用方法，按名称排序
+    pub fn list_methods(&self) -> Vec<RpcMethodInfo> {
+        let builtin = RpcMethodInfo {
+            name: LIST_METHODS.to_string(),
+            description: "列出所有可用方法".to_string(),
+        };
+        let mut methods: Vec<_> = std::iter::once(builtin)
+            .chain(self.methods.iter().map(|(name, method)| RpcMethodInfo {
+                name: name.clone(),
+                description: method.description.to_string(),
+            }))
+            .collect();
+        methods.sort_by(|a, b| a.name.cmp(&b.name));
+        methods
+    }
+
+    /// 调用方法，失败时返回 `success: false`，`data` 中为错误代码和方法名
+    pub async fn dispatch(
+        &self,
+        ctx: RpcContext,
+        method: &str,
+        params: Value,
+    ) -> ApiResponse<Value> {
+        debug!(method = %method, "RPC调用");
+
+        if method == LIST_METHODS {
+            return ApiResponse::success(json!(self.list_methods()));
+        }
+
+        let Some(entry) = self.methods.get(method).cloned() else {
+            warn!(method = %method, "RPC方法不存在");
+            return ApiResponse::failure(
+                json!({ "code": METHOD_NOT_FOUND, "method": method }),
+                format!("方法不存在: {}", method),
+            );
+        };
+
+        match (entry.handler)(ctx, params).await {
+            Ok(data) => ApiResponse::success(data),
+            Err(e) => {
+                warn!(method = %method, error = %e, "RPC调用失败");
+                ApiResponse::failure(
+                    json!({ "code": e.error_code(), "method": method }),
+                    e.to_string(),
+                )
+            }
+        }
+    }
+}
+
+/// 只需要ID的参数
+#[derive(Debug, Deserialize)]
+struct IdParams {
+    id: i64,
+}
+
+/// 解析调用参数，`null` 视为空对象
+fn parse_params<T: DeserializeOwned>(params: Value) -> ApiResult<T> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| ApiError::validation(format!("参数无效: {}", e)))
+}
+
+fn to_value<T: Serialize>(value: T) -> ApiResult<Value> {
+    Ok(serde_json::to_value(value)?)
+}
+
+fn claude_service(ctx: &RpcContext) -> ClaudeProviderService {
+    ClaudeProviderService::new(ctx.db_manager.clone(), ctx.crypto_service.clone())
+        .with_http_client(ctx.http_client.clone())
+}
+
+fn codex_service(ctx: &RpcContext) -> CodexProviderService {
+    CodexProviderService::new(ctx.db_manager.clone(), ctx.crypto_service.clone())
+        .with_http_client(ctx.http_client.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseConfig;
+    use crate::models::CreateClaudeProviderRequest;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_dispatch_methods_and_unknown_method() {
+        let temp_dir = tempdir().unwrap();
+        let config = DatabaseConfig {
+            url: format!("sqlite:{}", temp_dir.path().join("test_rpc.db").display()),
+            ..Default::default()
+        };
+        let db_manager = Arc::new(DatabaseManager::new(config).await.unwrap());
+        db_manager.ensure_initialized().await.unwrap();
+        let crypto_service =
+            Arc::new(CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap());
+        let ctx = RpcContext::new(db_manager.clone(), crypto_service.clone());
+        let registry = RpcRegistry::with_default_methods();
+
+        let id = ClaudeProviderService::new(db_manager, crypto_service)
+            .create_provider(CreateClaudeProviderRequest {
+                name: "rpc".to_string(),
+                url: "https://api.anthropic.com".to_string(),
+                token: "sk-ant-rpc".to_string(),
+                timeout: None,
+                auto_update: None,
+                r#type: None,
+                opus_model: None,
+                sonnet_model: None,
+                haiku_model: None,
+                models: None,
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
+            })
+            .await
+            .unwrap();
+
+        let response = registry.dispatch(ctx.clone(), LIST_METHODS, Value::Null).await;
+        assert!(response.success);
+        let names: Vec<String> = response
+            .data
+            .unwrap()
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|method| method["name"].as_str().unwrap().to_string())
+            .collect();
+        assert!(names.contains(&LIST_METHODS.to_string()));
+        assert!(names.contains(&"claude.get_provider".to_string()));
+
+        let response =
+            registry.dispatch(ctx.clone(), "claude.get_provider", json!({ "id": id })).await;
+        assert!(response.success);
+        assert_eq!(response.data.unwrap()["name"], "rpc");
+
+        let response = registry.dispatch(ctx.clone(), "claude.list_providers", Value::Null).await;
+        assert!(response.success);
+        assert_eq!(response.data.unwrap()["data"].as_array().unwrap().len(), 1);
+
+        // 供应商不存在
+        let response = registry
+            .dispatch(ctx.clone(), "claude.get_provider", json!({ "id": 9999 }))
+            .await;
+        assert!(!response.success);
+        assert_eq!(response.data.unwrap()["code"], "NOT_FOUND");
+
+        // 参数无效
+        let response = registry.dispatch(ctx.clone(), "claude.get_provider", json!({})).await;
+        assert!(!response.success);
+        assert_eq!(response.data.unwrap()["code"], "VALIDATION_ERROR");
+
+        // 方法不存在
+        let response = registry.dispatch(ctx, "claude.unknown", json!({})).await;
+        assert!(!response.success);
+        let data = response.data.unwrap();
+        assert_eq!(data["code"], METHOD_NOT_FOUND);
+        assert_eq!(data["method"], "claude.unknown");
+    }
+}
@@ -4,17 +4,42 @@
 // 支持环境配置和优雅关闭
 
 use crate::api::error::ApiError;
-use crate::api::handlers::{agent_guide, claude, codex, common_config, mcp_server};
-use crate::crypto::CryptoService;
+use crate::api::handlers::{
+    agent_guide, claude, codex, common_config, config, maintenance, mcp_server, migration,
+    provider_group, schema, tasks,
+};
+use crate::api::middleware::{
+    add_request_id_header, maintenance_middleware, request_timeout_middleware,
+    request_tracking_middleware, write_tracking_middleware, MaintenanceMode, RequestTimeoutConfig,
+    MAINTENANCE_PATH_PREFIX,
+};
+use crate::crypto::{CryptoError, CryptoService, KeyRing, RotationKeys};
 use crate::database::DatabaseManager;
+use crate::migration::config_generator::ConfigGenerator;
+use crate::services::auto_update_service::{AutoUpdateService, DEFAULT_AUTO_UPDATE_INTERVAL};
+use crate::services::common_config_service::CommonConfigService;
+use crate::services::http::{HttpClient, HttpClientConfig};
+use crate::services::retention_service::{RetentionService, DEFAULT_RETENTION_INTERVAL};
+use crate::task_registry::{TaskRegistry, TaskRegistryError};
 use axum::{http::StatusCode, response::IntoResponse, Router};
+use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 use tower_http::{
     cors::{Any, CorsLayer},
     trace::TraceLayer,
 };
-use tracing::info;
+use tracing::{info, warn};
+
+/// 默认数据加密密钥
+pub const DEFAULT_ENCRYPTION_KEY: &str = "T4jCbDRQ6Z10_dzcJlhvyn2EfK-tTS4-dbpf27Lc1k8=";
+
+/// 数据加密密钥的环境变量，设置为空时不加密
+pub const ENCRYPTION_KEY_ENV: &str = "FERNET_KEY";
+
+/// 通用配置类别密钥的环境变量，格式为 `类别=密钥`，多个类别用逗号分隔
+pub const CATEGORY_ENCRYPTION_KEYS_ENV: &str = "FERNET_CATEGORY_KEYS";
 
 /// 统一的API状态
 #[derive(Clone)]
@@ -23,6 +48,26 @@ pub struct ApiState {
     pub crypto_service: std::sync::Arc<CryptoService>,
     pub claude_service: crate::services::claude_service::ClaudeProviderService,
     pub codex_service: crate::services::codex_service::CodexProviderService,
+    /// 后台任务注册表
+    pub task_registry: TaskRegistry,
+    /// 配置文件生成器，为 `None` 时使用用户主目录下的默认位置
+    pub config_generator: Option<ConfigGenerator>,
+    /// 密钥轮换使用的密钥组（当前密钥和旧密钥）
+    pub rotation_keys: RotationKeys,
+    /// 通用配置按类别选择加密密钥的密钥环
+    pub key_ring: KeyRing,
+    /// 维护模式开关
+    pub maintenance: MaintenanceMode,
+    /// 连接测试和模型更新共用的上游HTTP客户端
+    pub http_client: HttpClient,
+}
+
+impl ApiState {
+    /// 使用密钥环的通用配置服务，读写通用配置都应通过它进行
+    pub fn common_config_service(&self) -> CommonConfigService {
+        CommonConfigService::new(self.db_manager.clone(), self.crypto_service.clone())
+            .with_key_ring(self.key_ring.clone())
+    }
 }
 
 /// API服务器配置
@@ -32,6 +77,77 @@ pub struct ApiServerConfig {
     pub port: u16,
     pub enable_cors: bool,
     pub enable_tracing: bool,
+    /// 单个请求的超时时间
+    pub request_timeout: Duration,
+    /// 不受请求超时限制的路径前缀，含 `*` 时按路径段匹配（见 [`RequestTimeoutConfig::is_exempt`]）
+    pub timeout_exempt_paths: Vec<String>,
+    /// 数据库地址
+    pub database_url: String,
+    /// 配置文件生成器，为 `None` 时使用用户主目录下的默认位置
+    pub config_generator: Option<ConfigGenerator>,
+    /// 数据加密密钥，为空时不加密，供应商令牌以明文存储（仅用于开发和测试）
+    pub encryption_key: String,
+    /// 轮换前使用过的旧密钥，仅用于解密和重新加密
+    pub previous_encryption_keys: Vec<String>,
+    /// 通用配置按类别使用的独立密钥（类别 -> 密钥），未列出的类别使用 `encryption_key`
+    pub category_encryption_keys: HashMap<String, String>,
+}
+
+/// 按 [`ApiServerConfig`] 创建的加密服务、轮换密钥组和密钥环
+#[derive(Clone)]
+pub struct EncryptionKeys {
+    pub crypto_service: Arc<CryptoService>,
+    pub rotation_keys: RotationKeys,
+    pub key_ring: KeyRing,
+}
+
+impl ApiServerConfig {
+    /// 使用环境变量中的密钥覆盖加密配置，未设置的环境变量不影响对应配置
+    pub fn with_env_keys(mut self) -> Result<Self, CryptoError> {
+        if let Ok(key) = std::env::var(ENCRYPTION_KEY_ENV) {
+            self.encryption_key = key;
+        }
+        if let Ok(keys) = std::env::var(CATEGORY_ENCRYPTION_KEYS_ENV) {
+            for entry in keys.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+                // 密钥以 `=` 结尾，只按第一个 `=` 拆分
+                let (category, key) = entry.split_once('=').ok_or(CryptoError::InvalidKey)?;
+                self.category_encryption_keys
+                    .insert(category.trim().to_string(), key.trim().to_string());
+            }
+        }
+        Ok(self)
+    }
+
+    /// 创建加密服务、轮换密钥组和通用配置密钥环，`encryption_key` 为空时不加密
+    pub fn encryption_keys(&self) -> Result<EncryptionKeys, CryptoError> {
+        let crypto_service = if self.encryption_key.trim().is_empty() {
+            warn!("⚠️ 未配置加密密钥，供应商令牌将以明文存储，仅可用于开发和测试环境");
+            Arc::new(CryptoService::disabled())
+        } else {
+            Arc::new(CryptoService::new(&self.encryption_key)?)
+        };
+        let previous_keys = self
+            .previous_encryption_keys
+            .iter()
+            .map(|key| CryptoService::new(key))
+            .collect::<Result<Vec<_>, _>>()?;
+        info!(
+            crypto_enabled = %crypto_service.is_enabled(),
+            key_fingerprint = %crypto_service.key_fingerprint(),
+            previous_keys = %previous_keys.len(),
+            category_keys = %self.category_encryption_keys.len(),
+            "加密服务已初始化"
+        );
+        let rotation_keys = RotationKeys::new((*crypto_service).clone(), previous_keys);
+        let key_ring = self.category_encryption_keys.iter().try_fold(
+            KeyRing::new((*crypto_service).clone()),
+            |key_ring, (category, key)| {
+                CryptoService::new(key).map(|service| key_ring.with_key(category.clone(), service))
+            },
+        )?;
+
+        Ok(EncryptionKeys { crypto_service, rotation_keys, key_ring })
+    }
 }
 
 impl Default for ApiServerConfig {
@@ -41,13 +157,46 @@ impl Default for ApiServerConfig {
             port: 8080,
             enable_cors: true,
             enable_tracing: true,
+            request_timeout: Duration::from_secs(30),
+            timeout_exempt_paths: vec![
+                "/api/v1/migration".to_string(),
+                MAINTENANCE_PATH_PREFIX.to_string(),
+                // 连接测试由上游超时时间控制，请求超时先触发时调用方收不到 UPSTREAM_TIMEOUT
+                "/api/v1/claude-providers/*/test".to_string(),
+                "/api/v1/claude-providers/test".to_string(),
+                "/api/v1/codex-providers/*/test".to_string(),
+            ],
+            database_url: "sqlite:data/ai_manager.db".to_string(),
+            config_generator: None,
+            encryption_key: DEFAULT_ENCRYPTION_KEY.to_string(),
+            previous_encryption_keys: Vec::new(),
+            category_encryption_keys: HashMap::new(),
         }
     }
 }
 
+/// 在注册表中启动数据库健康检查和优化、保留策略清理和模型自动更新任务
+///
+/// 需要在tokio运行时中调用；注册表的最后一个克隆被释放时任务随之取消，调用方需要持有注册表
+pub fn start_background_tasks(
+    db_manager: &Arc<DatabaseManager>,
+    crypto_service: &Arc<CryptoService>,
+    http_client: &HttpClient,
+    task_registry: &TaskRegistry,
+) -> Result<(), TaskRegistryError> {
+    db_manager.register_background_tasks(task_registry)?;
+    RetentionService::new(db_manager.clone())
+        .register(task_registry, DEFAULT_RETENTION_INTERVAL)?;
+    AutoUpdateService::new(db_manager.clone(), crypto_service.clone())
+        .with_http_client(http_client.clone())
+        .register(task_registry, DEFAULT_AUTO_UPDATE_INTERVAL)?;
+    Ok(())
+}
+
 /// API服务器
 pub struct ApiServer {
     config: ApiServerConfig,
+    state: ApiState,
     app: Router,
 }
 
@@ -61,18 +210,21 @@ impl ApiServer {
     pub async fn with_config(config: ApiServerConfig) -> Result<Self, Box<dyn std::error::Error>> {
         // 初始化数据库和加密服务
         let db_config = crate::database::DatabaseConfig {
-            url: "sqlite:data/ai_manager.db".to_string(),
+            url: config.database_url.clone(),
             max_connections: 10,
             min_connections: 1,
             connect_timeout: std::time::Duration::from_secs(30),
             idle_timeout: std::time::Duration::from_secs(600),
             max_lifetime: std::time::Duration::from_secs(1800),
+            ..Default::default()
         };
 
         let db_manager = Arc::new(DatabaseManager::new(db_config).await?);
-        let crypto_service = Arc::new(CryptoService::new(
-            "T4jCbDRQ6Z10_dzcJlhvyn2EfK-tTS4-dbpf27Lc1k8=",
-        )?);
+        // 后台任务由 `start_background_tasks` 启动，只创建服务器和路由时注册表为空
+        let task_registry = TaskRegistry::new();
+        let EncryptionKeys { crypto_service, rotation_keys, key_ring } =
+            config.encryption_keys()?;
+        let http_client = HttpClient::new(&HttpClientConfig::default())?;
 
         // 创建API状态
         let api_state = ApiState {
@@ -81,22 +233,48 @@ impl ApiServer {
             claude_service: crate::services::claude_service::ClaudeProviderService::new(
                 db_manager.clone(),
                 crypto_service.clone(),
-            ),
+            )
+            .with_http_client(http_client.clone()),
             codex_service: crate::services::codex_service::CodexProviderService::new(
                 db_manager,
                 crypto_service,
-            ),
+            )
+            .with_http_client(http_client.clone()),
+            task_registry,
+            config_generator: config.config_generator.clone(),
+            rotation_keys,
+            key_ring,
+            maintenance: MaintenanceMode::default(),
+            http_client,
         };
 
-        let app = Self::create_app(&config, api_state);
-        Ok(Self { config, app })
+        let app = Self::create_app(&config, api_state.clone());
+        Ok(Self { config, state: api_state, app })
+    }
+
+    /// 启动后台任务，见 [`start_background_tasks`]
+    ///
+    /// [`Self::run`] 启动服务前会调用，测试和只使用路由时不会启动后台任务
+    pub fn start_background_tasks(&self) -> Result<(), TaskRegistryError> {
+        start_background_tasks(
+            &self.state.db_manager,
+            &self.state.crypto_service,
+            &self.state.http_client,
+            &self.state.task_registry,
+        )
     }
 
     /// 创建Axum应用
     fn create_app(config: &ApiServerConfig, api_state: ApiState) -> Router {
+        let db_manager = api_state.db_manager.clone();
+        let maintenance = api_state.maintenance.clone();
         let app = Router::new()
             // 健康检查端点
             .route("/health", axum::routing::get(health_check))
+            .route(
+                "/health/detailed",
+                axum::routing::get(detailed_health_check),
+            )
             // API版本信息
             .route("/api/v1/info", axum::routing::get(api_info))
             // Claude供应商管理路由
@@ -109,9 +287,42 @@ impl ApiServer {
             .nest("/api/v1/mcp-servers", mcp_server::routes())
             // 通用配置管理路由
             .nest("/api/v1/common-configs", common_config::routes())
+            // 配置预览路由
+            .nest("/api/v1/config", config::routes())
+            // 数据迁移路由
+            .nest("/api/v1/migration", migration::routes())
+            // 数据库结构诊断路由
+            .nest("/api/v1/schema", schema::routes())
+            // 后台任务管理路由
+            .nest("/api/v1/tasks", tasks::routes())
+            // 供应商分组路由
+            .nest("/api/v1/provider-groups", provider_group::routes())
+            // 维护操作路由
+            .nest(MAINTENANCE_PATH_PREFIX, maintenance::routes())
             .with_state(api_state)
             // 404处理
-            .fallback(handle_404);
+            .fallback(handle_404)
+            // 写操作计数，用于触发数据库自动优化
+            .layer(axum::middleware::from_fn_with_state(
+                db_manager,
+                write_tracking_middleware,
+            ))
+            // 维护期间拒绝写请求
+            .layer(axum::middleware::from_fn_with_state(
+                maintenance,
+                maintenance_middleware,
+            ))
+            // 请求超时控制
+            .layer(axum::middleware::from_fn_with_state(
+                RequestTimeoutConfig::new(
+                    config.request_timeout,
+                    config.timeout_exempt_paths.clone(),
+                ),
+                request_timeout_middleware,
+            ))
+            .layer(axum::middleware::from_fn(add_request_id_header))
+            // 生成请求ID，之后的中间件、处理器、服务和Repository日志都在该请求的span中
+            .layer(axum::middleware::from_fn(request_tracking_middleware));
 
         // 根据配置添加中间件
         if config.enable_cors || config.enable_tracing {
@@ -140,9 +351,10 @@ impl ApiServer {
         app
     }
 
-    /// 启动服务器
+    /// 启动后台任务并启动服务器
     pub async fn run(self) -> Result<(), Box<dyn std::error::Error>> {
         let addr = format!("{}:{}", self.config.host, self.config.port).parse::<SocketAddr>()?;
+        self.start_background_tasks()?;
 
         info!("🚀 启动AI Manager API服务器");
         info!("📍 监听地址: http://{}", addr);
@@ -176,8 +388,18 @@ impl ApiServer {
 
     /// 获取应用路由（用于测试）
     pub fn app(&self) -> Router {
+        self.router()
+    }
+
+    /// 获取完整的应用路由，可配合 `tower::ServiceExt::oneshot` 在进程内调用，无需监听端口
+    pub fn router(&self) -> Router {
         self.app.clone()
     }
+
+    /// 消耗服务器并返回应用路由，用于嵌入到其他应用中
+    pub fn into_router(self) -> Router {
+        self.app
+    }
 }
 
 /// 健康检查处理器
@@ -186,6 +408,33 @@ async fn health_check() -> impl IntoResponse {
     StatusCode::OK
 }
 
+/// 详细健康检查处理器，包含数据库状态和是否启用加密
+///
+/// 该接口无需认证，不返回密钥指纹等密钥相关信息。数据库不可用时返回503
+async fn detailed_health_check(
+    axum::extract::State(state): axum::extract::State<ApiState>,
+) -> impl IntoResponse {
+    tracing::debug!("详细健康检查请求");
+
+    let database = state.db_manager.health_check().await;
+    let status = if database.is_ok() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    let health = serde_json::json!({
+        "status": if database.is_ok() { "ok" } else { "degraded" },
+        "database": {
+            "healthy": database.is_ok(),
+            "error": database.err().map(|e| e.to_string()),
+        },
+        "crypto_enabled": state.crypto_service.is_enabled(),
+    });
+
+    (status, axum::Json(health))
+}
+
 /// API信息处理器
 async fn api_info() -> impl IntoResponse {
     tracing::debug!("API信息请求");
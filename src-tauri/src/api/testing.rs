@@ -0,0 +1,266 @@
+// API测试辅助工具
+//
+// 在进程内调用路由的测试客户端，每个实例使用独立的临时数据库，
+// 测试不需要启动真实服务器，也不依赖固定端口
+
+use axum::body::Body;
+use axum::http::{HeaderMap, Method, Request, StatusCode};
+use axum::Router;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+use crate::api::responses::ApiResponse;
+use crate::api::server::{ApiServer, ApiServerConfig, DEFAULT_ENCRYPTION_KEY};
+use crate::database::{DatabaseConfig, DatabaseManager, QueryBuilder, KNOWN_TABLES};
+use crate::migration_tool::{DataMigrationTool, ImportOptions, MigrationReport, PythonExportData};
+use crate::models::{
+    ClaudeProvider, CodexProvider, CommonConfig, CreateClaudeProviderRequest,
+    CreateCodexProviderRequest, CreateCommonConfigRequest,
+};
+
+/// 进程内API测试客户端
+pub struct ApiTestClient {
+    router: Router,
+    db_manager: DatabaseManager,
+    // 保持临时目录在客户端生命周期内存在
+    temp_dir: TempDir,
+}
+
+impl ApiTestClient {
+    /// 使用独立的临时数据库创建测试客户端
+    pub async fn new() -> Self {
+        Self::with_config(ApiServerConfig::default()).await
+    }
+
+    /// 使用自定义服务器配置创建测试客户端，数据库地址总是指向临时目录
+    pub async fn with_config(config: ApiServerConfig) -> Self {
+        let temp_dir = tempfile::tempdir().expect("创建临时目录失败");
+        let database_url = format!("sqlite:{}", temp_dir.path().join("api_test.db").display());
+
+        let config = ApiServerConfig {
+            database_url: database_url.clone(),
+            enable_tracing: false,
+            ..config
+        };
+        let router = ApiServer::with_config(config).await.expect("创建API服务器失败").into_router();
+
+        let db_manager =
+            DatabaseManager::new(DatabaseConfig { url: database_url, ..Default::default() })
+                .await
+                .expect("连接测试数据库失败");
+
+        Self { router, db_manager, temp_dir }
+    }
+
+    /// 路由实例
+    pub fn router(&self) -> &Router {
+        &self.router
+    }
+
+    /// 测试数据库
+    pub fn db_manager(&self) -> &DatabaseManager {
+        &self.db_manager
+    }
+
+    /// 临时目录，可用于存放测试生成的文件
+    pub fn temp_path(&self) -> &std::path::Path {
+        self.temp_dir.path()
+    }
+
+    /// 发送请求，返回状态码和JSON响应体（响应体不是JSON时为 `Value::Null`）
+    pub async fn send(
+        &self,
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let (status, _, value) = self.send_with_headers(method, uri, body).await;
+        (status, value)
+    }
+
+    /// 发送请求，同时返回响应头
+    pub async fn send_with_headers(
+        &self,
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, HeaderMap, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(body.map(|b| Body::from(b.to_string())).unwrap_or_else(Body::empty))
+            .expect("构建请求失败");
+
+        let response = self.router.clone().oneshot(request).await.expect("路由调用失败");
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("读取响应体失败");
+        let value = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, headers, value)
+    }
+
+    /// 发送请求并将响应体解析为 `ApiResponse<T>`
+    pub async fn send_typed<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+    ) -> (StatusCode, ApiResponse<T>) {
+        let (status, value) = self.send(method, uri, body).await;
+        let response = serde_json::from_value(value.clone())
+            .unwrap_or_else(|e| panic!("无法解析响应 {}: {}", value, e));
+        (status, response)
+    }
+
+    /// GET请求
+    pub async fn get(&self, uri: &str) -> (StatusCode, Value) {
+        self.send(Method::GET, uri, None).await
+    }
+
+    /// POST请求
+    pub async fn post(&self, uri: &str, body: Value) -> (StatusCode, Value) {
+        self.send(Method::POST, uri, Some(body)).await
+    }
+
+    /// PUT请求
+    pub async fn put(&self, uri: &str, body: Value) -> (StatusCode, Value) {
+        self.send(Method::PUT, uri, Some(body)).await
+    }
+
+    /// DELETE请求
+    pub async fn delete(&self, uri: &str) -> (StatusCode, Value) {
+        self.send(Method::DELETE, uri, None).await
+    }
+
+    /// 创建Claude供应商
+    pub async fn create_claude_provider(
+        &self,
+        request: &CreateClaudeProviderRequest,
+    ) -> (StatusCode, ApiResponse<ClaudeProvider>) {
+        self.send_typed(
+            Method::POST,
+            "/api/v1/claude-providers",
+            Some(to_json(request)),
+        )
+        .await
+    }
+
+    /// 创建Codex供应商
+    pub async fn create_codex_provider(
+        &self,
+        request: &CreateCodexProviderRequest,
+    ) -> (StatusCode, ApiResponse<CodexProvider>) {
+        self.send_typed(
+            Method::POST,
+            "/api/v1/codex-providers",
+            Some(to_json(request)),
+        )
+        .await
+    }
+
+    /// 创建通用配置
+    pub async fn create_common_config(
+        &self,
+        request: &CreateCommonConfigRequest,
+    ) -> (StatusCode, ApiResponse<CommonConfig>) {
+        self.send_typed(
+            Method::POST,
+            "/api/v1/common-configs",
+            Some(to_json(request)),
+        )
+        .await
+    }
+
+    /// 使用导入工具写入测试数据（合并导入，不清空已有数据）
+    pub async fn seed(&self, data: &PythonExportData) -> MigrationReport {
+        let tool = DataMigrationTool::new(self.db_manager.clone(), DEFAULT_ENCRYPTION_KEY)
+            .await
+            .expect("创建迁移工具失败");
+        let json = serde_json::to_string(data).expect("序列化测试数据失败");
+
+        tool.import_from_json_with_options(
+            &json,
+            &ImportOptions { merge: true, ..Default::default() },
+        )
+        .await
+        .expect("写入测试数据失败")
+    }
+
+    /// 从JSON文件写入测试数据
+    pub async fn seed_file(&self, path: impl AsRef<std::path::Path>) -> MigrationReport {
+        let content = std::fs::read_to_string(path).expect("读取测试数据文件失败");
+        let data = PythonExportData::from_versioned_str(&content).expect("解析测试数据失败");
+        self.seed(&data).await
+    }
+
+    /// 清空所有数据表
+    pub async fn reset(&self) {
+        let query_builder = QueryBuilder::new(self.db_manager.pool());
+
+        // 逆序删除，先删除引用其他表的记录
+        for table in KNOWN_TABLES.iter().rev() {
+            query_builder
+                .table(table)
+                .expect("未知数据表")
+                .delete_all()
+                .await
+                .unwrap_or_else(|e| panic!("清空表 {} 失败: {}", table, e));
+        }
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Value {
+    serde_json::to_value(value).expect("序列化请求失败")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_api_test_client_seed_and_reset() {
+        let client = ApiTestClient::new().await;
+
+        let (status, created) = client
+            .create_common_config(&CreateCommonConfigRequest {
+                key: "test_client.key".to_string(),
+                value: "value".to_string(),
+                description: None,
+                category: None,
+                is_active: None,
+            })
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(created.data.unwrap().key, "test_client.key");
+
+        let data: PythonExportData = serde_json::from_value(serde_json::json!({
+            "version": "1.0.0",
+            "claude_providers": [
+                { "name": "seeded", "url": "https://api.example.com", "token": "sk-seeded" }
+            ],
+            "codex_providers": [],
+            "agent_guides": [],
+            "mcp_servers": [],
+            "common_configs": []
+        }))
+        .unwrap();
+        let report = client.seed(&data).await;
+        assert_eq!(report.claude_providers, 1);
+
+        let (status, list) = client.get("/api/v1/claude-providers").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(list["data"]["data"][0]["name"], "seeded");
+
+        client.reset().await;
+        let (_, list) = client.get("/api/v1/claude-providers").await;
+        assert_eq!(list["data"]["data"].as_array().unwrap().len(), 0);
+        let (status, _) = client.get("/api/v1/common-configs/key/test_client.key").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -5,14 +5,20 @@
 // 支持命令行参数配置和优雅关闭
 
 use clap::{Arg, Command};
-use migration_ai_manager_lib::{api::server::ApiServerConfig, ApiServer};
+use migration_ai_manager_lib::{api::server::ApiServerConfig, runtime::RuntimeMode, ApiServer};
 use std::net::SocketAddr;
 use tokio::signal;
 use tracing::{error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // 运行时模型由 AI_MANAGER_RUNTIME / AI_MANAGER_WORKER_THREADS 环境变量决定
+    let runtime_mode = RuntimeMode::from_env();
+    let runtime = runtime_mode.build()?;
+    runtime.block_on(run(runtime_mode))
+}
+
+async fn run(runtime_mode: RuntimeMode) -> Result<(), Box<dyn std::error::Error>> {
     // 解析命令行参数
     let matches = Command::new("AI Manager API Server")
         .version("1.0.0")
@@ -46,6 +52,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("禁用请求追踪日志")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("request-timeout")
+                .long("request-timeout")
+                .value_name("SECONDS")
+                .help("单个请求的超时时间（秒）")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("30"),
+        )
         .arg(
             Arg::new("log-level")
                 .short('l')
@@ -82,6 +96,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let port = *matches.get_one::<u16>("port").unwrap();
     let enable_cors = !matches.get_flag("no-cors");
     let enable_tracing = !matches.get_flag("no-tracing");
+    let request_timeout = *matches.get_one::<u64>("request-timeout").unwrap();
 
     // 验证配置
     let addr = format!("{}:{}", host, port)
@@ -91,13 +106,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("🚀 启动AI Manager API服务器");
     info!("📍 监听地址: http://{}", addr);
     info!("🔧 CORS支持: {}", if enable_cors { "启用" } else { "禁用" });
+    info!("🧵 异步运行时: {}", runtime_mode);
     info!(
         "📊 追踪日志: {}",
         if enable_tracing { "启用" } else { "禁用" }
     );
 
     // 创建API服务器配置
-    let config = ApiServerConfig { host, port, enable_cors, enable_tracing };
+    let config = ApiServerConfig {
+        host,
+        port,
+        enable_cors,
+        enable_tracing,
+        request_timeout: std::time::Duration::from_secs(request_timeout),
+        ..Default::default()
+    };
 
     // 创建API服务器
     let server = ApiServer::with_config(config).await?;
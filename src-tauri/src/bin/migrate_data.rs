@@ -158,6 +158,7 @@ async fn migrate_data(
             connect_timeout: std::time::Duration::from_secs(30),
             idle_timeout: std::time::Duration::from_secs(600),
             max_lifetime: std::time::Duration::from_secs(1800),
+            ..Default::default()
         };
 
         let db = DatabaseManager::new(target_config).await?;
@@ -171,6 +172,11 @@ async fn migrate_data(
 
     let old_crypto = CryptoService::new(&old_key)?;
     let new_crypto = CryptoService::new(&new_key)?;
+    info!(
+        source_key = %old_crypto.key_fingerprint(),
+        target_key = %new_crypto.key_fingerprint(),
+        "🔑 密钥轮换"
+    );
 
     info!("🔐 开始迁移数据...");
 
@@ -292,12 +298,13 @@ async fn migrate_claude_providers(
         if !dry_run {
             // 插入到目标数据库
             sqlx::query(r#"
-                INSERT INTO claude_providers (name, url, token, timeout, auto_update, type, enabled, opus_model, sonnet_model, haiku_model)
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                INSERT INTO claude_providers (name, url, token, key_fingerprint, timeout, auto_update, type, enabled, opus_model, sonnet_model, haiku_model)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#)
             .bind(name.clone())
             .bind(row.get::<Option<String>, _>("url").unwrap_or_else(|| "https://api.anthropic.com".to_string()))
             .bind(new_encrypted_token)
+            .bind(new_crypto.storage_fingerprint())
             .bind(row.get::<Option<i64>, _>("timeout").unwrap_or(30000))
             .bind(row.get::<Option<i64>, _>("auto_update").unwrap_or(1))
             .bind(row.get::<Option<String>, _>("type").unwrap_or_else(|| "public_welfare".to_string()))
@@ -349,8 +356,8 @@ async fn migrate_codex_providers(
             // 插入到目标数据库
             sqlx::query(
                 r#"
-                INSERT INTO codex_providers (name, url, token, type, enabled)
-                VALUES (?, ?, ?, ?, ?)
+                INSERT INTO codex_providers (name, url, token, key_fingerprint, type, enabled)
+                VALUES (?, ?, ?, ?, ?, ?)
             "#,
             )
             .bind(name.clone())
@@ -359,6 +366,7 @@ async fn migrate_codex_providers(
                     .unwrap_or_else(|| "https://api.openai.com".to_string()),
             )
             .bind(new_encrypted_token)
+            .bind(new_crypto.storage_fingerprint())
             .bind(
                 row.get::<Option<String>, _>("type")
                     .unwrap_or_else(|| "public_welfare".to_string()),
@@ -0,0 +1,175 @@
+// 数据迁移命令行工具
+//
+// 提供数据导出和导出格式转换功能，路径为 `-` 时使用标准输入/输出，便于通过管道传递给其他进程
+// 所有日志都输出到标准错误，保证标准输出中只有导出数据
+
+use clap::{Arg, ArgAction, Command};
+use migration_ai_manager_lib::api::server::DEFAULT_ENCRYPTION_KEY;
+use migration_ai_manager_lib::migration::{convert_export, SchemaVersion};
+use migration_ai_manager_lib::migration_tool::DataMigrationTool;
+use migration_ai_manager_lib::runtime::RuntimeMode;
+use migration_ai_manager_lib::{DatabaseConfig, DatabaseManager};
+use std::io::{Read, Write};
+use std::process::ExitCode;
+use tracing::{error, info};
+
+fn main() -> ExitCode {
+    let matches = Command::new("AI Manager Migration Tool")
+        .version("1.0.0")
+        .about("AI Manager 数据迁移工具")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("database")
+                .short('d')
+                .long("database")
+                .value_name("URL")
+                .help("数据库地址")
+                .global(true)
+                .default_value("sqlite:data/ai_manager.db"),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("导出数据为JSON")
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("PATH")
+                        .help("导出文件路径，使用 - 写入标准输出")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("gzip")
+                        .long("gzip")
+                        .help("使用gzip压缩导出内容")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("convert")
+                .about("将旧版本导出数据转换为新的导出格式")
+                .arg(
+                    Arg::new("input")
+                        .short('i')
+                        .long("input")
+                        .value_name("PATH")
+                        .help("导出文件路径，使用 - 读取标准输入")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("PATH")
+                        .help("转换结果路径，使用 - 写入标准输出")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("VERSION")
+                        .help("源导出格式版本，默认从数据中识别"),
+                )
+                .arg(
+                    Arg::new("to")
+                        .long("to")
+                        .value_name("VERSION")
+                        .help("目标导出格式版本，默认为当前版本"),
+                ),
+        )
+        .get_matches();
+
+    // 日志只写入标准错误，避免污染导出数据
+    tracing_subscriber::fmt().with_writer(std::io::stderr).init();
+
+    let database_url = matches.get_one::<String>("database").unwrap().clone();
+
+    let runtime = match RuntimeMode::from_env().build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            error!("❌ 创建异步运行时失败: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = match matches.subcommand() {
+        Some(("export", sub_matches)) => {
+            let output = sub_matches.get_one::<String>("output").unwrap();
+            let gzip = sub_matches.get_flag("gzip");
+            runtime.block_on(export(&database_url, output, gzip))
+        }
+        Some(("convert", sub_matches)) => convert(
+            sub_matches.get_one::<String>("input").unwrap(),
+            sub_matches.get_one::<String>("output").unwrap(),
+            sub_matches.get_one::<String>("from").map(String::as_str),
+            sub_matches.get_one::<String>("to").map(String::as_str),
+        ),
+        _ => unreachable!("clap 已保证子命令存在"),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            error!("❌ 执行失败: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn export(
+    database_url: &str,
+    output: &str,
+    gzip: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = DatabaseConfig { url: database_url.to_string(), ..Default::default() };
+    let db_manager = DatabaseManager::new(config).await?;
+
+    let encryption_key =
+        std::env::var("FERNET_KEY").unwrap_or_else(|_| DEFAULT_ENCRYPTION_KEY.to_string());
+    let migration_tool = DataMigrationTool::new(db_manager, &encryption_key).await?;
+
+    info!(output = %output, gzip = %gzip, "开始导出数据");
+    migration_tool.export_to_output(output, gzip).await?;
+    info!("✅ 数据导出完成");
+
+    Ok(())
+}
+
+fn convert(
+    input: &str,
+    output: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let content = if input == "-" {
+        let mut content = String::new();
+        std::io::stdin().read_to_string(&mut content)?;
+        content
+    } else {
+        std::fs::read_to_string(input)?
+    };
+    let value: serde_json::Value = serde_json::from_str(&content)?;
+
+    let from = match from {
+        Some(version) => version.parse()?,
+        None => SchemaVersion::detect(&value)?,
+    };
+    let to = match to {
+        Some(version) => version.parse()?,
+        None => SchemaVersion::current(),
+    };
+
+    info!(input = %input, output = %output, from = %from, to = %to, "开始转换导出格式");
+    let converted = serde_json::to_string_pretty(&convert_export(value, from, to)?)?;
+
+    if output == "-" {
+        let mut stdout = std::io::stdout().lock();
+        stdout.write_all(converted.as_bytes())?;
+        stdout.flush()?;
+    } else {
+        std::fs::write(output, converted)?;
+    }
+    info!("✅ 导出格式转换完成");
+
+    Ok(())
+}
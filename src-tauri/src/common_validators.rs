@@ -2,6 +2,18 @@
 //!
 //! 提供统一的输入验证功能，减少重复代码
 
+use crate::models::{
+    MAX_AGENT_GUIDE_NAME_LENGTH, MAX_AGENT_GUIDE_TEXT_LENGTH, MAX_CATEGORY_LENGTH,
+    MAX_CONFIG_KEY_LENGTH, MAX_CONFIG_VALUE_LENGTH, MAX_NAME_LENGTH, MAX_SEARCH_TERM_LENGTH,
+};
+use std::collections::HashMap;
+
+/// HTTP请求头名称中除字母和数字外允许的字符（RFC 7230 token）
+const HEADER_NAME_SYMBOLS: &[u8] = b"!#$%&'*+-.^_`|~";
+
+/// 超长错误中附带的内容预览长度
+const TOO_LONG_PREVIEW_LENGTH: usize = 32;
+
 /// 验证函数结果类型
 pub type ValidationResult<T> = Result<T, ValidationError>;
 
@@ -10,17 +22,46 @@ pub type ValidationResult<T> = Result<T, ValidationError>;
 pub struct ValidationError {
     pub message: String,
     pub field: Option<String>,
+    /// 超长错误时允许的最大长度
+    pub max: Option<usize>,
+    /// 超长错误时的实际长度
+    pub actual: Option<usize>,
 }
 
 impl ValidationError {
     /// 创建新的验证错误
     pub fn new(message: impl Into<String>) -> Self {
-        Self { message: message.into(), field: None }
+        Self {
+            message: message.into(),
+            field: None,
+            max: None,
+            actual: None,
+        }
     }
 
     /// 创建带字段的验证错误
     pub fn with_field(message: impl Into<String>, field: impl Into<String>) -> Self {
-        Self { message: message.into(), field: Some(field.into()) }
+        Self {
+            message: message.into(),
+            field: Some(field.into()),
+            max: None,
+            actual: None,
+        }
+    }
+
+    /// 创建字段超长的验证错误
+    pub fn too_long(
+        message: impl Into<String>,
+        field: impl Into<String>,
+        max: usize,
+        actual: usize,
+    ) -> Self {
+        Self {
+            message: message.into(),
+            field: Some(field.into()),
+            max: Some(max),
+            actual: Some(actual),
+        }
     }
 }
 
@@ -35,6 +76,71 @@ impl std::fmt::Display for ValidationError {
 
 impl std::error::Error for ValidationError {}
 
+/// 多项验证错误集合
+///
+/// 用于一次性收集请求中的所有验证错误，而不是遇到第一个错误就返回
+#[derive(Debug, Clone, Default)]
+pub struct ValidationErrors {
+    pub errors: Vec<ValidationError>,
+}
+
+impl ValidationErrors {
+    /// 创建空的错误集合
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录验证结果，失败时收集错误并返回 `None`
+    pub fn check<T>(&mut self, result: ValidationResult<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.errors.push(error);
+                None
+            }
+        }
+    }
+
+    /// 添加一个验证错误
+    pub fn push(&mut self, error: ValidationError) {
+        self.errors.push(error);
+    }
+
+    /// 是否没有错误
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// 错误数量
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// 没有错误时返回 `Ok(())`，否则返回错误集合
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl From<ValidationError> for ValidationErrors {
+    fn from(error: ValidationError) -> Self {
+        Self { errors: vec![error] }
+    }
+}
+
+impl std::fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let messages: Vec<String> = self.errors.iter().map(|e| e.to_string()).collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
 /// 通用验证器
 pub struct Validator;
 
@@ -68,17 +174,56 @@ impl Validator {
         min: usize,
         max: usize,
     ) -> ValidationResult<&'a str> {
-        let len = value.len();
+        let len = value.chars().count();
         if len < min {
             return Err(ValidationError::with_field(
                 format!("{}长度不能少于{}个字符", field_name, min),
                 field_name,
             ));
         }
+        Self::validate_max_length(value, field_name, max)
+    }
+
+    /// 验证字符串不超过最大长度（按字符计算），错误信息中附带截断后的内容预览
+    pub fn validate_max_length<'a>(
+        value: &'a str,
+        field_name: &str,
+        max: usize,
+    ) -> ValidationResult<&'a str> {
+        let len = value.chars().count();
+        if len > max {
+            return Err(ValidationError::too_long(
+                format!(
+                    "{}长度不能超过{}个字符，当前为{}个字符: {}",
+                    field_name,
+                    max,
+                    len,
+                    value.chars().take(TOO_LONG_PREVIEW_LENGTH).collect::<String>() + "..."
+                ),
+                field_name,
+                max,
+                len,
+            ));
+        }
+        Ok(value)
+    }
+
+    /// 验证敏感字段不超过最大长度，错误信息中不包含字段内容
+    pub fn validate_secret_max_length<'a>(
+        value: &'a str,
+        field_name: &str,
+        max: usize,
+    ) -> ValidationResult<&'a str> {
+        let len = value.chars().count();
         if len > max {
-            return Err(ValidationError::with_field(
-                format!("{}长度不能超过{}个字符", field_name, max),
+            return Err(ValidationError::too_long(
+                format!(
+                    "{}长度不能超过{}个字符，当前为{}个字符",
+                    field_name, max, len
+                ),
                 field_name,
+                max,
+                len,
             ));
         }
         Ok(value)
@@ -86,49 +231,88 @@ impl Validator {
 
     /// 验证搜索词
     pub fn validate_search_term(value: &str) -> ValidationResult<&str> {
-        Self::validate_non_empty(value, "搜索词")
-            .and_then(|term| Self::validate_string_length(term, "搜索词", 1, 100))
+        Self::validate_non_empty(value, "搜索词").and_then(|term| {
+            Self::validate_string_length(term, "搜索词", 1, MAX_SEARCH_TERM_LENGTH)
+        })
     }
 
     /// 验证供应商名称
     pub fn validate_provider_name(value: &str) -> ValidationResult<&str> {
         Self::validate_non_empty(value, "供应商名称")
-            .and_then(|name| Self::validate_string_length(name, "供应商名称", 1, 100))
+            .and_then(|name| Self::validate_string_length(name, "供应商名称", 1, MAX_NAME_LENGTH))
     }
 
     /// 验证Agent指导文件名称
     pub fn validate_agent_guide_name(value: &str) -> ValidationResult<&str> {
-        Self::validate_non_empty(value, "指导文件名称")
-            .and_then(|name| Self::validate_string_length(name, "指导文件名称", 1, 200))
+        Self::validate_non_empty(value, "指导文件名称").and_then(|name| {
+            Self::validate_string_length(name, "指导文件名称", 1, MAX_AGENT_GUIDE_NAME_LENGTH)
+        })
     }
 
     /// 验证Agent指导文件内容
     pub fn validate_agent_guide_content(value: &str) -> ValidationResult<&str> {
-        Self::validate_non_empty(value, "指导文件内容")
-            .and_then(|content| Self::validate_string_length(content, "指导文件内容", 1, 100000))
+        Self::validate_non_empty(value, "指导文件内容").and_then(|content| {
+            Self::validate_string_length(content, "指导文件内容", 1, MAX_AGENT_GUIDE_TEXT_LENGTH)
+        })
     }
 
     /// 验证配置键名
     pub fn validate_config_key(value: &str) -> ValidationResult<&str> {
         Self::validate_non_empty(value, "配置键名")
-            .and_then(|key| Self::validate_string_length(key, "配置键名", 1, 100))
+            .and_then(|key| Self::validate_string_length(key, "配置键名", 1, MAX_CONFIG_KEY_LENGTH))
     }
 
     /// 验证配置值
     pub fn validate_config_value(value: &str) -> ValidationResult<&str> {
-        Self::validate_string_length(value, "配置值", 0, 10000)
+        Self::validate_string_length(value, "配置值", 0, MAX_CONFIG_VALUE_LENGTH)
     }
 
     /// 验证配置类别
     pub fn validate_config_category(value: &str) -> ValidationResult<&str> {
-        Self::validate_non_empty(value, "配置类别")
-            .and_then(|category| Self::validate_string_length(category, "配置类别", 1, 50))
+        Self::validate_non_empty(value, "配置类别").and_then(|category| {
+            Self::validate_string_length(category, "配置类别", 1, MAX_CATEGORY_LENGTH)
+        })
     }
 
     /// 验证服务器名称
     pub fn validate_server_name(value: &str) -> ValidationResult<&str> {
         Self::validate_non_empty(value, "服务器名称")
-            .and_then(|name| Self::validate_string_length(name, "服务器名称", 1, 100))
+            .and_then(|name| Self::validate_string_length(name, "服务器名称", 1, MAX_NAME_LENGTH))
+    }
+
+    /// 验证自定义请求头，名称只能包含HTTP token字符，值不能包含换行等控制字符
+    pub fn validate_custom_headers(headers: &HashMap<String, String>) -> ValidationResult<()> {
+        for (name, value) in headers {
+            let valid_name = !name.is_empty()
+                && name
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || HEADER_NAME_SYMBOLS.contains(&b));
+            if !valid_name {
+                return Err(ValidationError::with_field(
+                    format!("请求头名称包含非法字符: {:?}", name),
+                    "custom_headers",
+                ));
+            }
+            // 与发送请求时的校验一致，非ASCII字符和控制字符（制表符除外）无法作为请求头的值
+            if reqwest::header::HeaderValue::from_str(value).is_err() {
+                return Err(ValidationError::with_field(
+                    format!("请求头 {} 的值包含非法字符", name),
+                    "custom_headers",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// 验证连接测试的可接受状态码，必须是有效的HTTP状态码（100-599）
+    pub fn validate_status_codes(codes: &[u16]) -> ValidationResult<&[u16]> {
+        if let Some(code) = codes.iter().find(|code| !(100..=599).contains(*code)) {
+            return Err(ValidationError::with_field(
+                format!("无效的HTTP状态码: {}", code),
+                "accepted_status_codes",
+            ));
+        }
+        Ok(codes)
     }
 
     /// 验证URL格式
@@ -174,6 +358,23 @@ mod tests {
         assert!(Validator::validate_string_length("very long string", "field", 1, 5).is_err());
     }
 
+    #[test]
+    fn test_validate_max_length_boundary() {
+        let at_limit = "名".repeat(10);
+        assert!(Validator::validate_max_length(&at_limit, "名称", 10).is_ok());
+
+        let over_limit = "名".repeat(11);
+        let error = Validator::validate_max_length(&over_limit, "名称", 10).unwrap_err();
+        assert_eq!(error.field.as_deref(), Some("名称"));
+        assert_eq!(error.max, Some(10));
+        assert_eq!(error.actual, Some(11));
+
+        let error =
+            Validator::validate_secret_max_length("sk-secret-token", "Token", 5).unwrap_err();
+        assert_eq!(error.actual, Some(15));
+        assert!(!error.message.contains("sk-secret"));
+    }
+
     #[test]
     fn test_validate_url() {
         assert!(Validator::validate_url("https://example.com").is_ok());
@@ -181,4 +382,26 @@ mod tests {
         assert!(Validator::validate_url("ftp://example.com").is_err());
         assert!(Validator::validate_url("").is_ok()); // 允许空
     }
+
+    #[test]
+    fn test_validate_custom_headers() {
+        let headers =
+            |name: &str, value: &str| HashMap::from([(name.to_string(), value.to_string())]);
+
+        assert!(
+            Validator::validate_custom_headers(&headers("anthropic-version", "2023-06-01")).is_ok()
+        );
+        assert!(Validator::validate_custom_headers(&headers("X-Proxy-Auth", "a\tb")).is_ok());
+        assert!(Validator::validate_custom_headers(&headers("", "value")).is_err());
+        assert!(Validator::validate_custom_headers(&headers("bad header", "value")).is_err());
+        assert!(Validator::validate_custom_headers(&headers("X-Name:", "value")).is_err());
+        assert!(
+            Validator::validate_custom_headers(&headers("X-Inject", "a\r\nX-Evil: 1")).is_err()
+        );
+        assert!(Validator::validate_custom_headers(&headers("X-Region", "华东")).is_err());
+
+        assert!(Validator::validate_status_codes(&[200, 404]).is_ok());
+        assert!(Validator::validate_status_codes(&[99]).is_err());
+        assert!(Validator::validate_status_codes(&[600]).is_err());
+    }
 }
@@ -1,4 +1,5 @@
 use fernet::Fernet;
+use std::collections::HashMap;
 use std::env;
 use thiserror::Error;
 
@@ -15,17 +16,37 @@ pub enum CryptoError {
     InvalidKey,
     #[error("环境变量错误: {0}")]
     EnvVar(#[from] env::VarError),
+    #[error("密钥不匹配: 当前密钥指纹为 {expected}，数据由指纹为 {found} 的密钥加密")]
+    KeyMismatch { expected: String, found: String },
+    #[error("待加密文本过大: {size} 字节，超过上限 {max} 字节")]
+    TooLarge { size: usize, max: usize },
 }
 
+/// 默认允许整体加密的最大明文字节数，更大的数据应分块处理
+pub const DEFAULT_MAX_PLAINTEXT_SIZE: usize = 1024 * 1024;
+
+/// 密钥指纹的长度（十六进制字符数）
+const FINGERPRINT_LEN: usize = 8;
+/// 未配置密钥时使用的指纹
+pub const PLAINTEXT_FINGERPRINT: &str = "plaintext";
+
 /// 加密服务结构体（优化内存使用）
+///
+/// 未配置密钥时（见 [`CryptoService::disabled`]）不加密，数据以明文存储和返回
 #[derive(Clone)]
 pub struct CryptoService {
-    fernet: Fernet,
+    fernet: Option<Fernet>,
+    key_fingerprint: String,
+    max_plaintext_size: usize,
 }
 
 impl std::fmt::Debug for CryptoService {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("CryptoService").field("fernet", &"Fernet Instance").finish()
+        f.debug_struct("CryptoService")
+            .field("fernet", &self.fernet.as_ref().map(|_| "Fernet Instance"))
+            .field("key_fingerprint", &self.key_fingerprint)
+            .field("max_plaintext_size", &self.max_plaintext_size)
+            .finish()
     }
 }
 
@@ -33,7 +54,77 @@ impl CryptoService {
     /// 使用Base64编码的密钥创建新的加密服务实例
     pub fn new(key: &str) -> Result<Self, CryptoError> {
         let fernet = Fernet::new(key).ok_or(CryptoError::InvalidKey)?;
-        Ok(Self { fernet })
+        Ok(Self {
+            fernet: Some(fernet),
+            key_fingerprint: Self::fingerprint_of(key),
+            max_plaintext_size: DEFAULT_MAX_PLAINTEXT_SIZE,
+        })
+    }
+
+    /// 创建不加密的服务，仅用于开发和测试
+    ///
+    /// 加密时原样返回明文；解密时明文原样返回，已加密的数据返回 `Decryption` 错误
+    pub fn disabled() -> Self {
+        Self {
+            fernet: None,
+            key_fingerprint: PLAINTEXT_FINGERPRINT.to_string(),
+            max_plaintext_size: DEFAULT_MAX_PLAINTEXT_SIZE,
+        }
+    }
+
+    /// 是否配置了加密密钥
+    pub fn is_enabled(&self) -> bool {
+        self.fernet.is_some()
+    }
+
+    /// 设置允许加密的最大明文字节数
+    pub fn with_max_plaintext_size(mut self, max_plaintext_size: usize) -> Self {
+        self.max_plaintext_size = max_plaintext_size;
+        self
+    }
+
+    /// 允许加密的最大明文字节数
+    pub fn max_plaintext_size(&self) -> usize {
+        self.max_plaintext_size
+    }
+
+    /// 当前密钥的指纹，可安全地用于日志和界面展示
+    pub fn key_fingerprint(&self) -> String {
+        self.key_fingerprint.clone()
+    }
+
+    /// 计算密钥指纹：SHA-256摘要的前8位十六进制，无法反推出密钥
+    fn fingerprint_of(key: &str) -> String {
+        use sha2::{Digest, Sha256};
+
+        Sha256::digest(key.trim().as_bytes())
+            .iter()
+            .take(FINGERPRINT_LEN / 2)
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// 与密文一起写入 `key_fingerprint` 列的指纹，未配置密钥时数据以明文保存，返回 `None`
+    ///
+    /// 密文本身保持标准Fernet格式，Python版本可以直接读取
+    pub fn storage_fingerprint(&self) -> Option<String> {
+        self.is_enabled().then(|| self.key_fingerprint.clone())
+    }
+
+    /// 解密失败后对照记录的密钥指纹
+    ///
+    /// 只有解密失败且记录的指纹与当前密钥不一致时才返回 `KeyMismatch`，
+    /// 没有记录指纹的旧数据和其他错误原样返回
+    pub fn check_key_fingerprint(&self, error: CryptoError, stored: Option<&str>) -> CryptoError {
+        match (error, stored) {
+            (CryptoError::Decryption(_), Some(found)) if found != self.key_fingerprint => {
+                CryptoError::KeyMismatch {
+                    expected: self.key_fingerprint.clone(),
+                    found: found.to_string(),
+                }
+            }
+            (error, _) => error,
+        }
     }
 
     /// 从环境变量获取密钥并创建加密服务
@@ -42,6 +133,36 @@ impl CryptoService {
         Self::new(&key)
     }
 
+    /// 由口令派生加密服务，同一口令总是得到同一密钥
+    pub fn from_password(password: &str) -> Result<Self, CryptoError> {
+        Self::new(&Self::derive_key_from_password(password)?)
+    }
+
+    /// 由口令派生Fernet密钥（URL安全Base64编码）
+    ///
+    /// 使用 PBKDF2-HMAC-SHA256：迭代 600,000 次（OWASP 推荐值），输出 32 字节，即Fernet密钥长度。
+    /// 同一口令需要总是得到同一密钥，因此使用固定的应用盐值而不是随机盐值。
+    /// 派生结果也用作数据库文件的加密口令，修改以上参数会使已有的派生密钥失效
+    pub fn derive_key_from_password(password: &str) -> Result<String, CryptoError> {
+        use base64::engine::general_purpose::URL_SAFE;
+        use base64::Engine as _;
+        use zeroize::Zeroize;
+
+        const SALT: &[u8] = b"ai-manager.password-key.v2";
+        const ITERATIONS: u32 = 600_000;
+
+        if password.is_empty() {
+            return Err(CryptoError::KeyGeneration("口令不能为空".to_string()));
+        }
+
+        let mut key = [0u8; 32];
+        pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password.as_bytes(), SALT, ITERATIONS, &mut key);
+        let encoded = URL_SAFE.encode(key);
+        key.zeroize();
+
+        Ok(encoded)
+    }
+
     /// 生成新的Fernet密钥（Base64编码）
     /// 注意：这个函数使用固定的测试密钥，生产环境应该使用Python生成
     pub fn generate_key() -> Result<String, CryptoError> {
@@ -51,25 +172,44 @@ impl CryptoService {
     }
 
     /// 加密文本数据（优化内存使用）
+    ///
+    /// 明文超过 `max_plaintext_size` 时返回 `TooLarge`
     pub fn encrypt(&self, plaintext: &str) -> Result<String, CryptoError> {
         if plaintext.is_empty() {
             return Err(CryptoError::Encryption("待加密文本不能为空".to_string()));
         }
+        if plaintext.len() > self.max_plaintext_size {
+            return Err(CryptoError::TooLarge {
+                size: plaintext.len(),
+                max: self.max_plaintext_size,
+            });
+        }
 
-        let encrypted = self.fernet.encrypt(plaintext.as_bytes());
-        Ok(encrypted)
+        match &self.fernet {
+            Some(fernet) => Ok(fernet.encrypt(plaintext.as_bytes())),
+            None => Ok(plaintext.to_string()),
+        }
     }
 
     /// 解密文本数据（优化内存使用）
+    ///
+    /// 解密失败时可以通过 [`Self::check_key_fingerprint`] 对照记录的密钥指纹
     pub fn decrypt(&self, ciphertext: &str) -> Result<String, CryptoError> {
         if ciphertext.is_empty() {
             return Err(CryptoError::Decryption("待解密文本不能为空".to_string()));
         }
 
-        let decrypted = self
-            .fernet
-            .decrypt(ciphertext)
-            .map_err(|e| CryptoError::Decryption(e.to_string()))?;
+        let Some(fernet) = &self.fernet else {
+            if Self::is_fernet_token(ciphertext) {
+                return Err(CryptoError::Decryption(
+                    "未配置加密密钥，无法解密已加密的数据".to_string(),
+                ));
+            }
+            return Ok(ciphertext.to_string());
+        };
+
+        let decrypted =
+            fernet.decrypt(ciphertext).map_err(|e| CryptoError::Decryption(e.to_string()))?;
 
         // 直接从bytes转换为String，避免中间分配
         match String::from_utf8(decrypted) {
@@ -128,6 +268,26 @@ impl CryptoService {
         Ok(())
     }
 
+    /// 判断字符串是否为Fernet令牌格式（`gAAAA` 开头且可按URL安全Base64解码）
+    ///
+    /// 只检查格式，不验证签名
+    pub fn is_fernet_token(value: &str) -> bool {
+        use base64::engine::general_purpose::{URL_SAFE, URL_SAFE_NO_PAD};
+        use base64::Engine as _;
+
+        if !value.starts_with("gAAAA") {
+            return false;
+        }
+
+        // 版本(1) + 时间戳(8) + IV(16) + 至少一个密文块(16) + HMAC(32)
+        const MIN_TOKEN_LEN: usize = 73;
+        URL_SAFE
+            .decode(value)
+            .or_else(|_| URL_SAFE_NO_PAD.decode(value))
+            .map(|bytes| bytes.len() >= MIN_TOKEN_LEN && bytes[0] == 0x80)
+            .unwrap_or(false)
+    }
+
     /// 验证数据完整性（通过尝试解密）
     pub fn validate_encryption(&self, test_data: &str) -> Result<bool, CryptoError> {
         let encrypted = self.encrypt(test_data)?;
@@ -136,6 +296,86 @@ impl CryptoService {
     }
 }
 
+/// 按配置类别选择加密密钥的密钥环
+///
+/// 未映射的类别使用默认密钥
+#[derive(Debug, Clone)]
+pub struct KeyRing {
+    default: CryptoService,
+    keys: HashMap<String, CryptoService>,
+}
+
+impl KeyRing {
+    /// 使用默认密钥创建密钥环
+    pub fn new(default: CryptoService) -> Self {
+        Self { default, keys: HashMap::new() }
+    }
+
+    /// 为指定类别设置独立密钥
+    pub fn with_key(mut self, category: impl Into<String>, crypto_service: CryptoService) -> Self {
+        self.keys.insert(category.into(), crypto_service);
+        self
+    }
+
+    /// 获取类别对应的加密服务
+    pub fn for_category(&self, category: &str) -> &CryptoService {
+        self.keys.get(category).unwrap_or(&self.default)
+    }
+
+    /// 类别是否设置了独立密钥
+    pub fn has_key(&self, category: &str) -> bool {
+        self.keys.contains_key(category)
+    }
+
+    /// 默认加密服务
+    pub fn default_service(&self) -> &CryptoService {
+        &self.default
+    }
+}
+
+/// 密钥轮换使用的多密钥组
+///
+/// 使用最新密钥加密，解密时依次尝试最新密钥和所有旧密钥
+#[derive(Debug, Clone)]
+pub struct RotationKeys {
+    current: CryptoService,
+    previous: Vec<CryptoService>,
+}
+
+impl RotationKeys {
+    /// 使用最新密钥和旧密钥创建密钥组
+    pub fn new(current: CryptoService, previous: Vec<CryptoService>) -> Self {
+        Self { current, previous }
+    }
+
+    /// 最新密钥
+    pub fn current(&self) -> &CryptoService {
+        &self.current
+    }
+
+    /// 密钥总数（包括最新密钥）
+    pub fn key_count(&self) -> usize {
+        self.previous.len() + 1
+    }
+
+    /// 密文是否已由最新密钥加密（能用最新密钥解密）
+    pub fn is_current(&self, ciphertext: &str) -> bool {
+        self.current.decrypt(ciphertext).is_ok()
+    }
+
+    /// 使用密钥组中的任一密钥解密，所有密钥都失败时返回最后一个错误
+    pub fn decrypt(&self, ciphertext: &str) -> Result<String, CryptoError> {
+        let mut result = self.current.decrypt(ciphertext);
+        for key in &self.previous {
+            if result.is_ok() {
+                break;
+            }
+            result = key.decrypt(ciphertext);
+        }
+        result
+    }
+}
+
 /// 用于测试的加密工具函数
 pub mod testing {
     use super::*;
@@ -178,6 +418,88 @@ pub mod testing {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_key_mismatch_after_decrypt_failure() {
+        let original = CryptoService::new(&testing::generate_test_key()).unwrap();
+        let other =
+            CryptoService::new(&CryptoService::derive_key_from_password("another key").unwrap())
+                .unwrap();
+
+        // 密文保持标准Fernet格式，指纹单独保存
+        let encrypted = original.encrypt("sk-ant-secret").unwrap();
+        let stored = original.storage_fingerprint();
+        assert!(encrypted.starts_with("gAAAA"));
+        assert_eq!(stored, Some(original.key_fingerprint()));
+
+        let error = other.decrypt(&encrypted).unwrap_err();
+        match other.check_key_fingerprint(error, stored.as_deref()) {
+            CryptoError::KeyMismatch { expected, found } => {
+                assert_eq!(expected, other.key_fingerprint());
+                assert_eq!(found, original.key_fingerprint());
+            }
+            error => panic!("应返回KeyMismatch: {:?}", error),
+        }
+
+        // 没有记录指纹的旧数据仍按普通解密失败处理
+        let error = other.decrypt(&encrypted).unwrap_err();
+        assert!(matches!(
+            other.check_key_fingerprint(error, None),
+            CryptoError::Decryption(_)
+        ));
+
+        // 形如指纹前缀的明文不会被当作密文
+        assert!(!CryptoService::is_fernet_token("deadbeef:gAAAAplaintext"));
+        assert_eq!(
+            CryptoService::disabled().decrypt("deadbeef:value").unwrap(),
+            "deadbeef:value"
+        );
+    }
+
+    #[test]
+    fn test_disabled_service_passes_plaintext_through() {
+        let disabled = CryptoService::disabled();
+        assert!(!disabled.is_enabled());
+        assert_eq!(disabled.key_fingerprint(), PLAINTEXT_FINGERPRINT);
+
+        let stored = disabled.encrypt("sk-ant-secret").unwrap();
+        assert_eq!(stored, "sk-ant-secret");
+        assert_eq!(disabled.storage_fingerprint(), None);
+        assert_eq!(disabled.decrypt(&stored).unwrap(), "sk-ant-secret");
+
+        // 已加密的数据无法在未配置密钥时读取
+        let enabled = CryptoService::new(&testing::generate_test_key()).unwrap();
+        let encrypted = enabled.encrypt("sk-ant-secret").unwrap();
+        assert!(matches!(
+            disabled.decrypt(&encrypted),
+            Err(CryptoError::Decryption(_))
+        ));
+    }
+
+    #[test]
+    fn test_encrypt_too_large() {
+        let crypto = CryptoService::new(&testing::generate_test_key()).unwrap();
+        assert_eq!(crypto.max_plaintext_size(), DEFAULT_MAX_PLAINTEXT_SIZE);
+
+        let at_limit = "a".repeat(DEFAULT_MAX_PLAINTEXT_SIZE);
+        assert!(crypto.encrypt(&at_limit).is_ok());
+
+        let over_limit = "a".repeat(DEFAULT_MAX_PLAINTEXT_SIZE + 1);
+        match crypto.encrypt(&over_limit) {
+            Err(CryptoError::TooLarge { size, max }) => {
+                assert_eq!(size, DEFAULT_MAX_PLAINTEXT_SIZE + 1);
+                assert_eq!(max, DEFAULT_MAX_PLAINTEXT_SIZE);
+            }
+            other => panic!("期望 TooLarge 错误，实际为 {:?}", other),
+        }
+
+        // 调高上限后可以加密
+        let crypto = crypto.with_max_plaintext_size(DEFAULT_MAX_PLAINTEXT_SIZE * 2);
+        assert_eq!(
+            crypto.decrypt(&crypto.encrypt(&over_limit).unwrap()).unwrap(),
+            over_limit
+        );
+    }
+
     #[test]
     fn test_key_generation() {
         let key = CryptoService::generate_key();
@@ -214,6 +536,37 @@ mod tests {
         println!("✅ 批量加密/解密测试通过");
     }
 
+    #[test]
+    fn test_key_fingerprint() {
+        let key = testing::generate_test_key();
+        let crypto = CryptoService::new(&key).unwrap();
+        let same = CryptoService::new(&key).unwrap();
+        let other = CryptoService::from_password("another-key").unwrap();
+
+        assert_eq!(crypto.key_fingerprint(), same.key_fingerprint());
+        assert_ne!(crypto.key_fingerprint(), other.key_fingerprint());
+        assert_eq!(crypto.key_fingerprint().len(), 8);
+        assert!(!key.contains(&crypto.key_fingerprint()));
+    }
+
+    #[test]
+    fn test_derive_key_from_password() {
+        let key = CryptoService::derive_key_from_password("correct horse").unwrap();
+        assert_eq!(
+            key,
+            CryptoService::derive_key_from_password("correct horse").unwrap()
+        );
+        assert_ne!(
+            key,
+            CryptoService::derive_key_from_password("correct horse!").unwrap()
+        );
+        assert!(CryptoService::new(&key).is_ok());
+        assert!(matches!(
+            CryptoService::derive_key_from_password(""),
+            Err(CryptoError::KeyGeneration(_))
+        ));
+    }
+
     #[test]
     fn test_invalid_key() {
         let result = CryptoService::new("invalid_key");
@@ -245,6 +598,16 @@ mod tests {
         assert_eq!(unicode_data, decrypted);
         println!("✅ Unicode字符加密/解密测试通过");
     }
+
+    #[test]
+    fn test_is_fernet_token() {
+        let crypto = CryptoService::new(&testing::generate_test_key()).unwrap();
+        let encrypted = crypto.encrypt("sk-test").unwrap();
+
+        assert!(CryptoService::is_fernet_token(&encrypted));
+        assert!(!CryptoService::is_fernet_token("sk-test"));
+        assert!(!CryptoService::is_fernet_token("gAAAA-not-base64!"));
+    }
 }
 
 /// Python兼容性测试工具
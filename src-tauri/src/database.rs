@@ -1,5 +1,14 @@
+use crate::performance::{MetricType, PerformanceMetric, PerformanceMonitor};
+use crate::task_registry::{TaskRegistry, TaskRegistryError};
+use futures::future::{self, BoxFuture};
+use futures::stream::{self, BoxStream, StreamExt};
 use sqlx::migrate::MigrateDatabase;
-use sqlx::{Pool, Row, Sqlite};
+use sqlx::sqlite::{SqliteQueryResult, SqliteRow, SqliteStatement, SqliteTypeInfo};
+use sqlx::{ConnectOptions, Pool, Row, Sqlite, SqliteConnection};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
 use tracing::{debug, error, info, warn};
@@ -8,13 +17,38 @@ use tracing::{debug, error, info, warn};
 #[derive(Error, Debug)]
 pub enum DatabaseError {
     #[error("数据库连接失败: {0}")]
-    Connection(#[from] sqlx::Error),
+    Connection(sqlx::Error),
+    #[error("获取数据库连接超时，连接池已耗尽")]
+    Timeout,
     #[error("数据库迁移失败: {0}")]
     Migration(String),
     #[error("数据库查询失败: {0}")]
     Query(String),
     #[error("数据库配置错误: {0}")]
     Config(String),
+    #[error("数据库初始化失败（不会自动重试，请重启应用）: {0}")]
+    Initialization(String),
+    #[error("不允许访问的数据表: {0}")]
+    UnknownTable(String),
+}
+
+impl DatabaseError {
+    /// 转换查询错误，连接池获取连接超时时返回 `Timeout`
+    pub fn query(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::PoolTimedOut => DatabaseError::Timeout,
+            err => DatabaseError::Query(err.to_string()),
+        }
+    }
+}
+
+impl From<sqlx::Error> for DatabaseError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::PoolTimedOut => DatabaseError::Timeout,
+            err => DatabaseError::Connection(err),
+        }
+    }
 }
 
 /// 数据库配置
@@ -26,6 +60,16 @@ pub struct DatabaseConfig {
     pub connect_timeout: Duration,
     pub idle_timeout: Duration,
     pub max_lifetime: Duration,
+    /// 定时优化间隔，`None` 表示不按时间调度
+    pub optimize_interval: Option<Duration>,
+    /// 写操作累计达到该次数后触发优化，`None` 表示不按写入次数调度
+    pub optimize_after_writes: Option<u64>,
+    /// 调度优化时是否执行 VACUUM
+    pub vacuum_on_optimize: bool,
+    /// 数据库文件加密口令，需要启用 `sqlcipher` 功能构建
+    pub passphrase: Option<String>,
+    /// 仓库查询耗时超过该值时记录警告，`None` 表示不记录慢查询
+    pub slow_query_threshold: Option<Duration>,
 }
 
 impl Default for DatabaseConfig {
@@ -37,8 +81,340 @@ impl Default for DatabaseConfig {
             connect_timeout: Duration::from_secs(5), // 快速连接超时
             idle_timeout: Duration::from_secs(180), // 优化空闲超时
             max_lifetime: Duration::from_secs(600), // 优化连接生命周期
+            optimize_interval: None,
+            optimize_after_writes: None,
+            vacuum_on_optimize: false,
+            passphrase: None,
+            slow_query_threshold: None,
+        }
+    }
+}
+
+impl DatabaseConfig {
+    /// 进程内共享的命名内存数据库，仅用于测试
+    ///
+    /// 同名的连接（包括不同的 `DatabaseManager`）访问同一个数据库，不会在磁盘上创建文件。
+    /// 最后一个连接关闭后数据库即被释放，`DatabaseManager` 会一直持有一个连接；
+    /// 并行运行的测试需要使用不同的名称
+    pub fn in_memory_shared(name: &str) -> Self {
+        Self {
+            url: format!("sqlite:file:{}?mode=memory&cache=shared", name),
+            ..Default::default()
+        }
+    }
+
+    /// 是否为内存数据库
+    pub fn is_in_memory(&self) -> bool {
+        is_memory_url(&self.url)
+    }
+}
+
+/// 重置数据库时需要提供的确认口令
+pub const RESET_CONFIRMATION_TOKEN: &str = "RESET ALL DATA";
+
+/// 重置前备份文件的路径：数据库所在目录下的 `backups` 目录
+fn reset_backup_path(db_path: &std::path::Path) -> PathBuf {
+    let dir = db_path.parent().unwrap_or_else(|| std::path::Path::new("."));
+    let stem = db_path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("database");
+    let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S%.3f");
+    dir.join("backups").join(format!("{}-reset-{}.db", stem, timestamp))
+}
+
+/// SQLite数据库文件头
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+
+/// 拆分数据库URL中的路径和查询参数
+fn split_sqlite_url(url: &str) -> (&str, &str) {
+    let path = url
+        .strip_prefix("sqlite://")
+        .or_else(|| url.strip_prefix("sqlite:"))
+        .unwrap_or(url);
+    path.split_once('?').unwrap_or((path, ""))
+}
+
+/// 判断URL是否指向内存数据库：`:memory:` 或带 `mode=memory` 参数
+fn is_memory_url(url: &str) -> bool {
+    let (path, query) = split_sqlite_url(url);
+    path == ":memory:" || query.split('&').any(|param| param == "mode=memory")
+}
+
+/// 从数据库URL中取出文件路径，内存数据库返回 `None`
+fn sqlite_file_path(url: &str) -> Option<std::path::PathBuf> {
+    let (path, _) = split_sqlite_url(url);
+    if path.is_empty() || is_memory_url(url) {
+        return None;
+    }
+    Some(std::path::PathBuf::from(path))
+}
+
+/// 判断数据库文件是否已加密：非空且文件头不是SQLite标准文件头
+fn is_encrypted_database_file(path: &std::path::Path) -> bool {
+    use std::io::Read;
+
+    let mut header = [0u8; 16];
+    match std::fs::File::open(path).and_then(|mut file| file.read_exact(&mut header)) {
+        Ok(()) => &header != SQLITE_HEADER,
+        Err(_) => false,
+    }
+}
+
+/// 检查当前构建是否支持打开配置的数据库
+///
+/// 未启用 `sqlcipher` 功能时，设置了口令或数据库文件已加密都会在启动时报错，
+/// 而不是在首次查询时才出现 "file is not a database"
+fn check_encryption_support(config: &DatabaseConfig) -> Result<(), DatabaseError> {
+    if cfg!(feature = "sqlcipher") {
+        return Ok(());
+    }
+
+    if config.passphrase.is_some() {
+        return Err(DatabaseError::Config(
+            "配置了数据库口令，但当前构建未启用 sqlcipher 功能".to_string(),
+        ));
+    }
+
+    if let Some(path) = sqlite_file_path(&config.url) {
+        if is_encrypted_database_file(&path) {
+            return Err(DatabaseError::Config(format!(
+                "数据库文件已加密，需要使用 sqlcipher 功能构建并提供口令: {}",
+                path.display()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// 构建连接选项，启用 `sqlcipher` 功能时首先设置 `PRAGMA key`
+fn connect_options(
+    config: &DatabaseConfig,
+) -> Result<sqlx::sqlite::SqliteConnectOptions, DatabaseError> {
+    let options: sqlx::sqlite::SqliteConnectOptions = config.url.parse()?;
+
+    #[cfg(feature = "sqlcipher")]
+    let options = match &config.passphrase {
+        Some(passphrase) => {
+            let key = crate::crypto::CryptoService::derive_key_from_password(passphrase)
+                .map_err(|e| DatabaseError::Config(format!("派生数据库口令失败: {}", e)))?;
+            // 派生结果只包含URL安全Base64字符，可以直接放入引号
+            options.pragma("key", format!("'{}'", key))
+        }
+        None => options,
+    };
+
+    Ok(options)
+}
+
+/// 查询语句的形状：字面量替换为 `?`，连续的空白合并为一个空格
+///
+/// 用于日志输出，内联在语句中的值（可能是密钥）不会被记录
+pub fn sql_shape(sql: &str) -> String {
+    let mut shape = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            // 字符串字面量，其中的 '' 是转义的单引号
+            '\'' => {
+                loop {
+                    match chars.next() {
+                        Some('\'') if chars.peek() == Some(&'\'') => {
+                            chars.next();
+                        }
+                        Some('\'') | None => break,
+                        Some(_) => {}
+                    }
+                }
+                shape.push('?');
+            }
+            c if c.is_whitespace() => {
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+                if !shape.is_empty() {
+                    shape.push(' ');
+                }
+            }
+            // 数字字面量，标识符中的数字（如 `t1`）保持不变
+            c if c.is_ascii_digit()
+                && !shape.ends_with(|p: char| p.is_alphanumeric() || p == '_') =>
+            {
+                while chars.peek().is_some_and(|c| c.is_ascii_alphanumeric() || *c == '.') {
+                    chars.next();
+                }
+                shape.push('?');
+            }
+            c => shape.push(c),
         }
     }
+
+    shape.trim_end().to_string()
+}
+
+/// 仓库查询计时器，耗时记入性能监控，超过慢查询阈值时记录警告
+///
+/// 通过 [`DatabaseManager::query_timer`] 获取，与管理器共用性能监控器
+#[derive(Clone)]
+pub struct QueryTimer {
+    slow_query_threshold: Option<Duration>,
+    monitor: PerformanceMonitor,
+}
+
+impl QueryTimer {
+    /// 执行查询并计时，`sql` 只用于日志，输出前会去掉其中的字面量
+    pub async fn time<T>(
+        &self,
+        operation: &str,
+        sql: &str,
+        query: impl std::future::Future<Output = T>,
+    ) -> T {
+        let started = std::time::Instant::now();
+        let result = query.await;
+        self.record(operation, sql, started.elapsed()).await;
+        result
+    }
+
+    /// 包装执行器，经过它的查询都会计时
+    pub fn executor<E>(&self, operation: impl Into<String>, executor: E) -> TimedExecutor<E> {
+        TimedExecutor { executor, timer: self.clone(), operation: operation.into() }
+    }
+
+    /// 记录一次查询耗时
+    async fn record(&self, operation: &str, sql: &str, elapsed: Duration) {
+        if self.slow_query_threshold.is_some_and(|threshold| elapsed > threshold) {
+            warn!(
+                operation = %operation,
+                elapsed_ms = %elapsed.as_millis(),
+                sql = %sql_shape(sql),
+                "慢查询"
+            );
+        }
+
+        self.monitor
+            .record_metric(PerformanceMetric::new(
+                MetricType::DatabaseQuery,
+                operation,
+                elapsed,
+            ))
+            .await;
+    }
+}
+
+/// 计时的sqlx执行器，查询耗时记入 [`QueryTimer`]
+///
+/// 通过 [`QueryTimer::executor`] 创建，可以传给任何接受执行器的sqlx查询。
+/// 返回多行的查询在结果读完时记录耗时，提前丢弃的结果流不记录
+pub struct TimedExecutor<E> {
+    executor: E,
+    timer: QueryTimer,
+    operation: String,
+}
+
+impl<E: std::fmt::Debug> std::fmt::Debug for TimedExecutor<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimedExecutor")
+            .field("executor", &self.executor)
+            .field("operation", &self.operation)
+            .finish()
+    }
+}
+
+// sqlx只为具体的连接类型实现执行器，这里同样逐个实现。
+// `execute`、`fetch_all` 等默认方法都经过 `fetch_many` 或 `fetch_optional`，只需在这两处计时
+macro_rules! impl_timed_executor {
+    ($executor:ty) => {
+        impl<'c> sqlx::Executor<'c> for TimedExecutor<$executor> {
+            type Database = Sqlite;
+
+            fn fetch_many<'e, 'q: 'e, Q: 'q>(
+                self,
+                query: Q,
+            ) -> BoxStream<'e, Result<sqlx::Either<SqliteQueryResult, SqliteRow>, sqlx::Error>>
+            where
+                'c: 'e,
+                Q: sqlx::Execute<'q, Sqlite>,
+            {
+                let Self { executor, timer, operation } = self;
+                let sql = query.sql();
+                let started = std::time::Instant::now();
+                let finished = stream::once(async move {
+                    timer.record(&operation, sql, started.elapsed()).await;
+                })
+                .filter_map(|()| future::ready(None));
+                executor.fetch_many(query).chain(finished).boxed()
+            }
+
+            fn fetch_optional<'e, 'q: 'e, Q: 'q>(
+                self,
+                query: Q,
+            ) -> BoxFuture<'e, Result<Option<SqliteRow>, sqlx::Error>>
+            where
+                'c: 'e,
+                Q: sqlx::Execute<'q, Sqlite>,
+            {
+                let Self { executor, timer, operation } = self;
+                let sql = query.sql();
+                Box::pin(async move {
+                    timer.time(&operation, sql, executor.fetch_optional(query)).await
+                })
+            }
+
+            fn prepare_with<'e, 'q: 'e>(
+                self,
+                sql: &'q str,
+                parameters: &'e [SqliteTypeInfo],
+            ) -> BoxFuture<'e, Result<SqliteStatement<'q>, sqlx::Error>>
+            where
+                'c: 'e,
+            {
+                self.executor.prepare_with(sql, parameters)
+            }
+
+            fn describe<'e, 'q: 'e>(
+                self,
+                sql: &'q str,
+            ) -> BoxFuture<'e, Result<sqlx::Describe<Sqlite>, sqlx::Error>>
+            where
+                'c: 'e,
+            {
+                self.executor.describe(sql)
+            }
+        }
+    };
+}
+
+impl_timed_executor!(&'c Pool<Sqlite>);
+impl_timed_executor!(&'c mut SqliteConnection);
+
+/// 一次性初始化的结果，失败时保存错误信息
+type InitCell = Arc<tokio::sync::OnceCell<Result<(), String>>>;
+
+/// 获取数据库对应的初始化状态
+///
+/// 同一数据库文件在进程内共享同一个状态，内存数据库每个实例单独初始化
+fn init_cell_for(url: &str) -> InitCell {
+    static CELLS: std::sync::OnceLock<std::sync::Mutex<HashMap<PathBuf, InitCell>>> =
+        std::sync::OnceLock::new();
+
+    let Some(path) = sqlite_file_path(url) else {
+        return InitCell::default();
+    };
+    let path = std::fs::canonicalize(&path).unwrap_or(path);
+
+    let mut cells = CELLS.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    cells.entry(path).or_default().clone()
+}
+
+/// 在 `cell` 上单次执行初始化：并发调用者等待同一次执行，失败后不再重试
+async fn run_once<F, Fut>(cell: &InitCell, init: F) -> Result<(), DatabaseError>
+where
+    F: FnOnce() -> Fut,
+    Fut: std::future::Future<Output = Result<(), DatabaseError>>,
+{
+    cell.get_or_init(|| async move { init().await.map_err(|e| e.to_string()) })
+        .await
+        .clone()
+        .map_err(DatabaseError::Initialization)
 }
 
 /// 数据库连接池管理器
@@ -46,6 +422,61 @@ impl Default for DatabaseConfig {
 pub struct DatabaseManager {
     pool: Pool<Sqlite>,
     config: DatabaseConfig,
+    maintenance: Arc<MaintenanceState>,
+    init: InitCell,
+    /// 仓库查询的耗时统计，在所有克隆之间共享
+    performance: PerformanceMonitor,
+    /// 内存数据库在最后一个连接关闭时释放，单独持有一个不受连接池回收影响的连接
+    _keepalive: Option<Arc<tokio::sync::Mutex<sqlx::SqliteConnection>>>,
+}
+
+/// 数据库维护状态，在所有克隆之间共享
+#[derive(Default)]
+struct MaintenanceState {
+    /// 上次优化后的写操作次数
+    writes_since_optimize: AtomicU64,
+    /// 防止多个优化任务同时执行
+    optimize_lock: tokio::sync::Mutex<()>,
+}
+
+/// 数据库优化结果
+#[derive(Debug, Default, Clone, serde::Serialize)]
+pub struct OptimizeReport {
+    /// 是否执行了 VACUUM
+    pub vacuumed: bool,
+    /// 是否因其他写操作或优化任务正在进行而跳过
+    pub skipped: bool,
+    /// VACUUM 回收的空间（字节）
+    pub reclaimed_bytes: i64,
+}
+
+/// 单个数据表的统计信息刷新结果
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TableStatsRefresh {
+    pub table: String,
+    /// 刷新后的实际记录数
+    pub row_count: i64,
+}
+
+/// 统计信息刷新结果
+#[derive(Debug, Default, Clone, serde::Serialize)]
+pub struct RefreshReport {
+    pub tables: Vec<TableStatsRefresh>,
+    pub duration_ms: u64,
+}
+
+/// 缺失时间戳的补全结果
+#[derive(Debug, Default, Clone, serde::Serialize)]
+pub struct TimestampBackfillReport {
+    /// 每个数据表补全的记录数，没有需要补全的记录的表不包含在内
+    pub tables: std::collections::BTreeMap<String, u64>,
+}
+
+impl TimestampBackfillReport {
+    /// 所有数据表补全的记录总数
+    pub fn total(&self) -> u64 {
+        self.tables.values().sum()
+    }
 }
 
 impl DatabaseManager {
@@ -53,6 +484,8 @@ impl DatabaseManager {
     pub async fn new(config: DatabaseConfig) -> Result<Self, DatabaseError> {
         info!("初始化数据库连接池，URL: {}", config.url);
 
+        check_encryption_support(&config)?;
+
         // 使用连接池建立和迁移并行执行来优化启动时间
         let pool_fut = async {
             // 检查并创建数据库
@@ -73,7 +506,7 @@ impl DatabaseManager {
                 .min_connections(config.min_connections)
                 .idle_timeout(config.idle_timeout)
                 .max_lifetime(config.max_lifetime)
-                .acquire_timeout(Duration::from_secs(10)) // 减少获取连接超时
+                .acquire_timeout(config.connect_timeout) // 连接池耗尽时等待空闲连接的时间
                 .test_before_acquire(true) // 连接前测试，避免使用损坏的连接
                 // 启用连接池的性能优化设置
                 .after_connect(|conn, _meta| {
@@ -100,7 +533,10 @@ impl DatabaseManager {
                 });
 
             // 创建连接池
-            pool_options.connect(&config.url).await.map_err(DatabaseError::Connection)
+            pool_options
+                .connect_with(connect_options(&config)?)
+                .await
+                .map_err(DatabaseError::from)
         };
 
         // 等待连接池建立
@@ -108,31 +544,75 @@ impl DatabaseManager {
 
         info!("✅ 数据库连接池创建成功");
 
-        let manager = Self { pool, config };
+        // 连接池的连接会因空闲或超过生命周期被关闭，内存数据库需要额外保持一个连接
+        let keepalive = if config.is_in_memory() {
+            let conn = connect_options(&config)?.connect().await?;
+            Some(Arc::new(tokio::sync::Mutex::new(conn)))
+        } else {
+            None
+        };
+
+        let manager = Self {
+            pool,
+            init: init_cell_for(&config.url),
+            config,
+            maintenance: Arc::new(MaintenanceState::default()),
+            performance: PerformanceMonitor::new(),
+            _keepalive: keepalive,
+        };
 
         // 异步运行数据库迁移和性能优化，不阻塞返回
         let manager_clone = manager.clone();
         tokio::spawn(async move {
-            // 运行数据库迁移
-            if let Err(e) = manager_clone.run_migrations().await {
-                error!("数据库迁移失败: {}", e);
+            if let Err(e) = manager_clone.ensure_initialized().await {
+                error!("{}", e);
             }
+        });
 
-            // 创建性能索引
-            let query_builder = QueryBuilder::new(manager_clone.pool());
-            if let Err(e) = query_builder.create_performance_indexes().await {
-                warn!("性能索引创建失败: {}", e);
-            }
+        Ok(manager)
+    }
+
+    /// 确保数据库迁移、索引创建和连接池预热已完成
+    ///
+    /// 同一数据库在进程内只会初始化一次，并发调用者等待同一次初始化完成；
+    /// 初始化失败后再次调用直接返回当时的错误，不会静默重试
+    pub async fn ensure_initialized(&self) -> Result<(), DatabaseError> {
+        run_once(&self.init, || self.initialize()).await
+    }
 
-            // 连接池预热：创建最小连接数，优化首次查询性能
-            if let Err(e) = manager_clone.warmup_connection_pool().await {
-                warn!("连接池预热失败: {}", e);
+    /// 初始化数据库结构并进行性能优化
+    async fn initialize(&self) -> Result<(), DatabaseError> {
+        // 运行数据库迁移
+        self.run_migrations().await?;
+
+        // 旧版本导入的数据可能缺少时间戳，影响排序和增量导出
+        if let Err(e) = self.backfill_timestamps().await {
+            warn!("补全缺失的时间戳失败: {}", e);
+        }
+
+        // 实体与表结构不一致时，读取到NULL等值才会报错，启动时提前记录
+        match crate::models::verify_schema_compat(self.pool()).await {
+            Ok(mismatches) => {
+                for mismatch in &mismatches {
+                    warn!(table = %mismatch.table, "实体与表结构不一致: {}", mismatch);
+                }
             }
+            Err(e) => warn!("检查实体与表结构一致性失败: {}", e),
+        }
 
-            info!("✅ 数据库初始化和性能优化完成");
-        });
+        // 创建性能索引
+        let query_builder = QueryBuilder::new(self.pool());
+        if let Err(e) = query_builder.create_performance_indexes().await {
+            warn!("性能索引创建失败: {}", e);
+        }
 
-        Ok(manager)
+        // 连接池预热：创建最小连接数，优化首次查询性能
+        if let Err(e) = self.warmup_connection_pool().await {
+            warn!("连接池预热失败: {}", e);
+        }
+
+        info!("✅ 数据库初始化和性能优化完成");
+        Ok(())
     }
 
     /// 使用默认配置创建数据库管理器
@@ -158,6 +638,24 @@ impl DatabaseManager {
         &self.pool
     }
 
+    /// 仓库查询使用的计时器
+    pub fn query_timer(&self) -> QueryTimer {
+        QueryTimer {
+            slow_query_threshold: self.config.slow_query_threshold,
+            monitor: self.performance.clone(),
+        }
+    }
+
+    /// 仓库查询的耗时统计
+    pub fn performance_monitor(&self) -> &PerformanceMonitor {
+        &self.performance
+    }
+
+    /// 数据库文件路径，内存数据库返回 `None`
+    pub fn database_path(&self) -> Option<std::path::PathBuf> {
+        sqlite_file_path(&self.config.url)
+    }
+
     /// 测试数据库连接
     pub async fn test_connection(&self) -> Result<(), DatabaseError> {
         debug!("测试数据库连接");
@@ -176,7 +674,7 @@ impl DatabaseManager {
             }
             Err(e) => {
                 error!("❌ 数据库连接测试失败: {}", e);
-                Err(DatabaseError::Connection(e))
+                Err(e.into())
             }
         }
     }
@@ -201,10 +699,7 @@ impl DatabaseManager {
         let warmup_tasks: Vec<_> = (0..self.config.min_connections)
             .map(|_| async {
                 // 直接在池上执行查询来创建和测试连接
-                sqlx::query("SELECT 1")
-                    .fetch_one(pool)
-                    .await
-                    .map_err(DatabaseError::Connection)?;
+                sqlx::query("SELECT 1").fetch_one(pool).await.map_err(DatabaseError::from)?;
 
                 Ok::<(), DatabaseError>(())
             })
@@ -236,6 +731,269 @@ impl DatabaseManager {
         Ok(())
     }
 
+    /// 优化数据库：执行 `PRAGMA optimize`，可选执行 `VACUUM`
+    ///
+    /// `VACUUM` 在事务之外的独立连接上执行；若其他连接正在写入则跳过，不会等待
+    pub async fn optimize(&self, vacuum: bool) -> Result<OptimizeReport, DatabaseError> {
+        let Ok(_guard) = self.maintenance.optimize_lock.try_lock() else {
+            debug!("已有优化任务在执行，跳过本次优化");
+            return Ok(OptimizeReport { skipped: true, ..Default::default() });
+        };
+
+        sqlx::query("PRAGMA optimize")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::Query(format!("PRAGMA optimize 失败: {}", e)))?;
+        self.maintenance.writes_since_optimize.store(0, Ordering::Relaxed);
+
+        let mut report = OptimizeReport::default();
+        if !vacuum {
+            info!("✅ 数据库优化完成");
+            return Ok(report);
+        }
+
+        let mut conn = self.pool.acquire().await?;
+        let size_before = Self::database_size(&mut conn).await?;
+
+        // 临时关闭忙等待，有其他写操作时 VACUUM 立即失败而不是阻塞
+        let busy_timeout: i64 =
+            sqlx::query_scalar("PRAGMA busy_timeout").fetch_one(&mut *conn).await?;
+        sqlx::query("PRAGMA busy_timeout = 0").execute(&mut *conn).await?;
+        let result = sqlx::query("VACUUM").execute(&mut *conn).await;
+        sqlx::query(&format!("PRAGMA busy_timeout = {}", busy_timeout))
+            .execute(&mut *conn)
+            .await?;
+
+        match result {
+            Ok(_) => {
+                let size_after = Self::database_size(&mut conn).await?;
+                report.vacuumed = true;
+                report.reclaimed_bytes = (size_before - size_after).max(0);
+                info!(
+                    size_before = %size_before,
+                    size_after = %size_after,
+                    reclaimed_bytes = %report.reclaimed_bytes,
+                    "✅ VACUUM 完成"
+                );
+            }
+            Err(sqlx::Error::Database(e))
+                if e.message().contains("locked")
+                    || e.message().contains("busy")
+                    || e.message().contains("in progress") =>
+            {
+                warn!("有其他写操作正在进行，跳过 VACUUM: {}", e);
+                report.skipped = true;
+            }
+            Err(e) => return Err(DatabaseError::Query(format!("VACUUM 失败: {}", e))),
+        }
+
+        Ok(report)
+    }
+
+    /// 数据库文件大小（页数 × 页大小）
+    async fn database_size(
+        conn: &mut sqlx::pool::PoolConnection<Sqlite>,
+    ) -> Result<i64, DatabaseError> {
+        let page_count: i64 =
+            sqlx::query_scalar("PRAGMA page_count").fetch_one(&mut **conn).await?;
+        let page_size: i64 = sqlx::query_scalar("PRAGMA page_size").fetch_one(&mut **conn).await?;
+        Ok(page_count * page_size)
+    }
+
+    /// 重新计算 [`KNOWN_TABLES`] 的统计信息（`sqlite_stat1`）
+    ///
+    /// 逐表执行 `ANALYZE`，每次只短暂持有单个表的锁，可以随时执行
+    pub async fn refresh_statistics(&self) -> Result<RefreshReport, DatabaseError> {
+        self.ensure_initialized().await?;
+        let started = std::time::Instant::now();
+
+        let mut tables = Vec::with_capacity(KNOWN_TABLES.len());
+        for table in KNOWN_TABLES {
+            sqlx::query(&format!(r#"ANALYZE "{}""#, table))
+                .execute(&self.pool)
+                .await
+                .map_err(|e| DatabaseError::Query(format!("ANALYZE {} 失败: {}", table, e)))?;
+            let row_count: i64 =
+                sqlx::query_scalar(&format!(r#"SELECT COUNT(*) FROM "{}""#, table))
+                    .fetch_one(&self.pool)
+                    .await?;
+            tables.push(TableStatsRefresh { table: table.to_string(), row_count });
+        }
+
+        let report = RefreshReport { tables, duration_ms: started.elapsed().as_millis() as u64 };
+        info!(
+            tables = %report.tables.len(),
+            duration_ms = %report.duration_ms,
+            "✅ 数据表统计信息已刷新"
+        );
+        Ok(report)
+    }
+
+    /// 将 [`TIMESTAMPED_TABLES`] 中为空的 `created_at` / `updated_at` 补全
+    ///
+    /// `created_at` 优先使用同一记录的 `updated_at`，两者都为空时使用当前时间。
+    /// 修改记录会触发 `updated_at` 触发器，被补全的记录 `updated_at` 为当前时间
+    pub async fn backfill_timestamps(&self) -> Result<TimestampBackfillReport, DatabaseError> {
+        let mut report = TimestampBackfillReport::default();
+        for table in TIMESTAMPED_TABLES {
+            let result = sqlx::query(&format!(
+                r#"UPDATE "{}"
+                   SET created_at = COALESCE(created_at, updated_at, CURRENT_TIMESTAMP),
+                       updated_at = COALESCE(updated_at, created_at, CURRENT_TIMESTAMP)
+                   WHERE created_at IS NULL OR updated_at IS NULL"#,
+                table
+            ))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::Query(format!("补全 {} 的时间戳失败: {}", table, e)))?;
+
+            if result.rows_affected() > 0 {
+                info!(table = %table, rows = %result.rows_affected(), "已补全缺失的时间戳");
+                report.tables.insert(table.to_string(), result.rows_affected());
+            }
+        }
+
+        debug!(total = %report.total(), "时间戳补全完成");
+        Ok(report)
+    }
+
+    /// 使用 `VACUUM INTO` 将数据库备份到指定文件，目标文件已存在时返回错误
+    pub async fn backup_to(&self, path: &std::path::Path) -> Result<(), DatabaseError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| DatabaseError::Query(format!("无法创建备份目录: {}", e)))?;
+        }
+
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().into_owned())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::Query(format!("数据库备份失败: {}", e)))?;
+        info!(path = %path.display(), "✅ 数据库备份完成");
+        Ok(())
+    }
+
+    /// 清空数据库：先自动备份，再在一个事务中删除并按当前表结构重建所有数据表
+    ///
+    /// 返回备份文件路径，内存数据库不备份，返回 `None`
+    pub async fn reset(&self) -> Result<Option<PathBuf>, DatabaseError> {
+        self.ensure_initialized().await?;
+
+        let backup_path = match self.database_path() {
+            Some(db_path) => {
+                let backup_path = reset_backup_path(&db_path);
+                self.backup_to(&backup_path).await?;
+                Some(backup_path)
+            }
+            None => None,
+        };
+
+        let mut conn = self.pool.acquire().await?;
+        // 外键约束不能在事务中修改，删除有引用关系的表前先关闭
+        let foreign_keys: i64 =
+            sqlx::query_scalar("PRAGMA foreign_keys").fetch_one(&mut *conn).await?;
+        sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await?;
+        let result = Self::recreate_tables(&mut conn).await;
+        sqlx::query(&format!("PRAGMA foreign_keys = {}", foreign_keys))
+            .execute(&mut *conn)
+            .await?;
+        result?;
+
+        warn!(backup_path = ?backup_path, "数据库已重置，所有数据已清空");
+        Ok(backup_path)
+    }
+
+    /// 删除 [`KNOWN_TABLES`] 中的所有表，再执行原有的建表、索引和触发器语句
+    async fn recreate_tables(conn: &mut sqlx::SqliteConnection) -> Result<(), DatabaseError> {
+        use sqlx::Connection;
+
+        let mut tx = conn.begin().await?;
+
+        let mut schema = Vec::with_capacity(KNOWN_TABLES.len());
+        for table in KNOWN_TABLES {
+            // 建表语句排在索引和触发器之前
+            let statements: Vec<String> = sqlx::query_scalar(
+                "SELECT sql FROM sqlite_master
+                 WHERE tbl_name = ? AND sql IS NOT NULL
+                 ORDER BY type = 'table' DESC, rowid",
+            )
+            .bind(table)
+            .fetch_all(&mut *tx)
+            .await?;
+            if statements.is_empty() {
+                return Err(DatabaseError::Query(format!("数据表 {} 不存在", table)));
+            }
+            schema.push(statements);
+        }
+
+        // 逆序删除，先删除引用其他表的表
+        for table in KNOWN_TABLES.iter().rev() {
+            sqlx::query(&format!(r#"DROP TABLE "{}""#, table)).execute(&mut *tx).await?;
+        }
+        for statement in schema.iter().flatten() {
+            sqlx::query(statement).execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// 记录一次写操作，累计达到 `optimize_after_writes` 时在后台触发优化
+    pub fn record_write(&self) {
+        let Some(threshold) = self.config.optimize_after_writes else {
+            return;
+        };
+
+        let writes = self.maintenance.writes_since_optimize.fetch_add(1, Ordering::Relaxed) + 1;
+        if writes < threshold {
+            return;
+        }
+
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let manager = self.clone();
+            handle.spawn(async move {
+                debug!(writes = %writes, "写操作次数达到阈值，开始优化数据库");
+                if let Err(e) = manager.optimize(manager.config.vacuum_on_optimize).await {
+                    warn!("数据库优化失败: {}", e);
+                }
+            });
+        }
+    }
+
+    /// 将数据库相关的后台任务注册到任务注册表
+    ///
+    /// 健康检查固定每分钟执行；配置了 `optimize_interval` 时同时注册定时优化任务
+    pub fn register_background_tasks(
+        &self,
+        registry: &TaskRegistry,
+    ) -> Result<(), TaskRegistryError> {
+        let manager = self.clone();
+        registry.register(
+            "database_health_check",
+            Duration::from_secs(60),
+            move || {
+                let manager = manager.clone();
+                async move { manager.health_check().await.map_err(|e| e.to_string()) }
+            },
+        )?;
+
+        if let Some(interval) = self.config.optimize_interval {
+            let manager = self.clone();
+            registry.register("database_optimize", interval, move || {
+                let manager = manager.clone();
+                async move {
+                    manager
+                        .optimize(manager.config.vacuum_on_optimize)
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                }
+            })?;
+        }
+
+        Ok(())
+    }
+
     /// 关闭连接池
     pub async fn close(self) {
         info!("关闭数据库连接池");
@@ -283,6 +1041,239 @@ impl std::fmt::Display for TablePerformanceStats {
     }
 }
 
+/// SQLite单条语句允许绑定的最大参数数量
+pub const SQLITE_MAX_BIND_PARAMS: usize = 999;
+
+/// 允许通过 [`QueryBuilder::table`] 访问的数据表
+pub const KNOWN_TABLES: &[&str] = &[
+    "claude_providers",
+    "codex_providers",
+    "agent_guides",
+    "mcp_servers",
+    "common_configs",
+    "common_config_history",
+    "migration_runs",
+    "provider_groups",
+    "provider_group_members",
+    "provider_model_history",
+];
+
+/// 带有 `created_at` / `updated_at` 列的数据表
+pub const TIMESTAMPED_TABLES: &[&str] = &[
+    "claude_providers",
+    "codex_providers",
+    "agent_guides",
+    "mcp_servers",
+    "common_configs",
+    "provider_groups",
+];
+
+/// 经过白名单校验的数据表
+///
+/// 表名来自 [`KNOWN_TABLES`]，列名来自数据库自身的表结构，值全部通过参数绑定
+pub struct TableHandle<'a> {
+    pool: &'a Pool<Sqlite>,
+    name: &'static str,
+}
+
+impl<'a> TableHandle<'a> {
+    /// 表名
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// 删除表中所有记录，返回删除的行数
+    pub async fn delete_all(&self) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(&format!(r#"DELETE FROM "{}""#, self.name))
+            .execute(self.pool)
+            .await
+            .map_err(DatabaseError::query)?;
+        Ok(result.rows_affected())
+    }
+
+    /// 按ID删除记录，返回删除的行数
+    pub async fn delete_by_id(&self, id: i64) -> Result<u64, DatabaseError> {
+        let result = sqlx::query(&format!(r#"DELETE FROM "{}" WHERE id = ?"#, self.name))
+            .bind(id)
+            .execute(self.pool)
+            .await
+            .map_err(DatabaseError::query)?;
+        Ok(result.rows_affected())
+    }
+
+    /// 插入一条记录，返回新记录的ID
+    ///
+    /// 列名必须是表中已有的列，否则返回错误
+    pub async fn insert(&self, columns: &[&str], values: &[&str]) -> Result<i64, DatabaseError> {
+        let values: Vec<Option<&str>> = values.iter().copied().map(Some).collect();
+        self.insert_row(columns, &values, false).await
+    }
+
+    /// 插入一条记录，`created_at` / `updated_at` 使用当前时间，返回新记录的ID
+    ///
+    /// 值为 `None` 时写入NULL；表必须在 [`TIMESTAMPED_TABLES`] 中
+    pub async fn insert_timestamped(
+        &self,
+        columns: &[&str],
+        values: &[Option<&str>],
+    ) -> Result<i64, DatabaseError> {
+        self.insert_row(columns, values, true).await
+    }
+
+    /// 按ID更新指定列，同时刷新 `updated_at`，返回更新的行数
+    ///
+    /// 值为 `None` 时写入NULL；列名必须是表中已有的列
+    pub async fn update_by_id(
+        &self,
+        id: i64,
+        columns: &[&str],
+        values: &[Option<&str>],
+    ) -> Result<u64, DatabaseError> {
+        check_values(columns, values)?;
+        let mut assignments: Vec<String> = self
+            .quoted_columns(columns)
+            .await?
+            .into_iter()
+            .map(|column| format!("{} = ?", column))
+            .collect();
+        if TIMESTAMPED_TABLES.contains(&self.name) {
+            assignments.push("updated_at = CURRENT_TIMESTAMP".to_string());
+        }
+
+        let query = format!(
+            r#"UPDATE "{}" SET {} WHERE id = ?"#,
+            self.name,
+            assignments.join(", ")
+        );
+        let result = values
+            .iter()
+            .fold(sqlx::query(&query), |query, value| query.bind(*value))
+            .bind(id)
+            .execute(self.pool)
+            .await
+            .map_err(DatabaseError::query)?;
+        Ok(result.rows_affected())
+    }
+
+    /// 查询全部记录，按ID排序
+    pub async fn select_all(&self) -> Result<Vec<SqliteRow>, DatabaseError> {
+        sqlx::query(&format!(r#"SELECT * FROM "{}" ORDER BY id"#, self.name))
+            .fetch_all(self.pool)
+            .await
+            .map_err(DatabaseError::query)
+    }
+
+    /// 以文本形式查询指定列及 `id`，按ID排序
+    ///
+    /// 各列转换为TEXT后以原列名返回；`key` 为 `(列名, 值)` 时只返回该列等于该值的记录
+    pub async fn select_as_text(
+        &self,
+        columns: &[&str],
+        key: Option<(&str, &str)>,
+    ) -> Result<Vec<SqliteRow>, DatabaseError> {
+        let selected: Vec<String> = self
+            .quoted_columns(columns)
+            .await?
+            .into_iter()
+            .map(|column| format!("CAST({0} AS TEXT) AS {0}", column))
+            .collect();
+        let mut query = format!(r#"SELECT id, {} FROM "{}""#, selected.join(", "), self.name);
+        if let Some((key_column, _)) = key {
+            let key_column = self.quoted_columns(&[key_column]).await?.remove(0);
+            query.push_str(&format!(" WHERE {} = ?", key_column));
+        }
+        query.push_str(" ORDER BY id");
+
+        let mut query = sqlx::query(&query);
+        if let Some((_, key_value)) = key {
+            query = query.bind(key_value);
+        }
+        query.fetch_all(self.pool).await.map_err(DatabaseError::query)
+    }
+
+    /// 插入一条记录，`timestamped` 为真时同时写入当前时间
+    async fn insert_row(
+        &self,
+        columns: &[&str],
+        values: &[Option<&str>],
+        timestamped: bool,
+    ) -> Result<i64, DatabaseError> {
+        check_values(columns, values)?;
+        if timestamped && !TIMESTAMPED_TABLES.contains(&self.name) {
+            return Err(DatabaseError::Query(format!(
+                "表 {} 没有时间戳列",
+                self.name
+            )));
+        }
+
+        let mut quoted = self.quoted_columns(columns).await?;
+        let mut placeholders = vec!["?"; values.len()];
+        if timestamped {
+            quoted.extend(["created_at".to_string(), "updated_at".to_string()]);
+            placeholders.extend(["CURRENT_TIMESTAMP", "CURRENT_TIMESTAMP"]);
+        }
+
+        let query = format!(
+            r#"INSERT INTO "{}" ({}) VALUES ({})"#,
+            self.name,
+            quoted.join(", "),
+            placeholders.join(", ")
+        );
+        let result = values
+            .iter()
+            .fold(sqlx::query(&query), |query, value| query.bind(*value))
+            .execute(self.pool)
+            .await
+            .map_err(DatabaseError::query)?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// 表中是否存在指定列
+    pub async fn has_column(&self, column: &str) -> Result<bool, DatabaseError> {
+        Ok(self.column_names().await?.iter().any(|name| name == column))
+    }
+
+    /// 表中所有列名
+    async fn column_names(&self) -> Result<Vec<String>, DatabaseError> {
+        sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+            .bind(self.name)
+            .fetch_all(self.pool)
+            .await
+            .map_err(DatabaseError::query)
+    }
+
+    /// 校验列名并加上引号，列名必须是表中已有的列
+    async fn quoted_columns(&self, columns: &[&str]) -> Result<Vec<String>, DatabaseError> {
+        let table_columns = self.column_names().await?;
+
+        columns
+            .iter()
+            .map(|column| {
+                table_columns
+                    .iter()
+                    .find(|known| known.as_str() == *column)
+                    .map(|known| format!(r#""{}""#, known))
+                    .ok_or_else(|| {
+                        DatabaseError::Query(format!("表 {} 中不存在列: {}", self.name, column))
+                    })
+            })
+            .collect()
+    }
+}
+
+/// 检查列数与值数一致且不为空
+fn check_values<T>(columns: &[&str], values: &[T]) -> Result<(), DatabaseError> {
+    if columns.is_empty() || columns.len() != values.len() {
+        return Err(DatabaseError::Query(format!(
+            "列数({})与值数({})不匹配",
+            columns.len(),
+            values.len()
+        )));
+    }
+    Ok(())
+}
+
 /// 数据库查询构建器
 pub struct QueryBuilder<'a> {
     pool: &'a Pool<Sqlite>,
@@ -305,10 +1296,48 @@ impl<'a> QueryBuilder<'a> {
             query_builder = query_builder.bind(param);
         }
 
-        query_builder
-            .execute(self.pool)
-            .await
-            .map_err(|e| DatabaseError::Query(e.to_string()))
+        query_builder.execute(self.pool).await.map_err(DatabaseError::query)
+    }
+
+    /// 获取经过白名单校验的数据表句柄
+    ///
+    /// 表名必须在 [`KNOWN_TABLES`] 中，之后拼接的SQL只使用白名单中的静态表名
+    pub fn table(&self, name: &str) -> Result<TableHandle<'a>, DatabaseError> {
+        let name = KNOWN_TABLES
+            .iter()
+            .copied()
+            .find(|table| *table == name)
+            .ok_or_else(|| DatabaseError::UnknownTable(name.to_string()))?;
+
+        Ok(TableHandle { pool: self.pool, name })
+    }
+
+    /// 生成参数化的 `IN` 子句
+    ///
+    /// 返回 `(片段, 参数)` 列表，每个片段形如 `column IN (?, ?, ...)`；
+    /// 超过SQLite参数上限时按 [`SQLITE_MAX_BIND_PARAMS`] 分块，调用方需逐块执行。
+    /// 空列表返回恒假条件 `1 = 0`
+    pub fn bind_in<'v>(
+        column: &str,
+        values: &[&'v str],
+    ) -> Result<Vec<(String, Vec<&'v str>)>, DatabaseError> {
+        let is_valid_column = !column.is_empty()
+            && column.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+        if !is_valid_column {
+            return Err(DatabaseError::Query(format!("无效的列名: {}", column)));
+        }
+
+        if values.is_empty() {
+            return Ok(vec![("1 = 0".to_string(), Vec::new())]);
+        }
+
+        Ok(values
+            .chunks(SQLITE_MAX_BIND_PARAMS)
+            .map(|chunk| {
+                let placeholders = vec!["?"; chunk.len()].join(", ");
+                (format!("{} IN ({})", column, placeholders), chunk.to_vec())
+            })
+            .collect())
     }
 
     /// 检查表是否存在
@@ -317,7 +1346,7 @@ impl<'a> QueryBuilder<'a> {
             .bind(table_name)
             .fetch_optional(self.pool)
             .await
-            .map_err(|e| DatabaseError::Query(e.to_string()))?;
+            .map_err(DatabaseError::query)?;
 
         Ok(result.is_some())
     }
@@ -325,10 +1354,8 @@ impl<'a> QueryBuilder<'a> {
     /// 获取表的记录数（优化版本，使用预编译语句）
     pub async fn count_records(&self, table_name: &str) -> Result<i64, DatabaseError> {
         let query = format!("SELECT COUNT(*) as count FROM {}", table_name);
-        let result = sqlx::query(&query)
-            .fetch_one(self.pool)
-            .await
-            .map_err(|e| DatabaseError::Query(e.to_string()))?;
+        let result =
+            sqlx::query(&query).fetch_one(self.pool).await.map_err(DatabaseError::query)?;
 
         let count: i64 = result.get("count");
         Ok(count)
@@ -453,7 +1480,7 @@ impl<'a> QueryBuilder<'a> {
             .bind(table_name)
             .fetch_one(self.pool)
             .await
-            .map_err(|e| DatabaseError::Query(e.to_string()))?;
+            .map_err(DatabaseError::query)?;
 
         let estimated_size: i64 = size_result.get("estimated_size");
 
@@ -464,7 +1491,7 @@ impl<'a> QueryBuilder<'a> {
             .bind(table_name)
             .fetch_one(self.pool)
             .await
-            .map_err(|e| DatabaseError::Query(e.to_string()))?;
+            .map_err(DatabaseError::query)?;
 
         let index_count: i64 = index_result.get("index_count");
 
@@ -502,24 +1529,25 @@ mod tests {
     use super::*;
     use tempfile::NamedTempFile;
 
+    /// 每个测试使用独立的命名内存数据库，不在磁盘上留下文件
     async fn create_test_database() -> DatabaseManager {
-        let temp_file = NamedTempFile::new().unwrap();
-        let db_url = temp_file.path().to_str().unwrap().to_string();
-
-        // 保持文件不被删除，通过复制到新路径
-        let persistent_db = format!("{}_test.db", db_url);
-        std::fs::copy(&db_url, &persistent_db).unwrap();
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        let name = format!("database_test_{}", NEXT_ID.fetch_add(1, Ordering::Relaxed));
 
         let config = DatabaseConfig {
-            url: persistent_db,
             max_connections: 5,
             min_connections: 1,
             connect_timeout: Duration::from_secs(10),
             idle_timeout: Duration::from_secs(60),
             max_lifetime: Duration::from_secs(300),
+            ..DatabaseConfig::in_memory_shared(&name)
         };
 
-        DatabaseManager::new(config).await.unwrap()
+        let db_manager = DatabaseManager::new(config).await.unwrap();
+        assert!(db_manager.database_path().is_none());
+        assert!(!std::path::Path::new(&name).exists());
+        assert!(!std::path::Path::new(&format!("file:{}", name)).exists());
+        db_manager
     }
 
     #[tokio::test]
@@ -548,6 +1576,351 @@ mod tests {
         assert_eq!(count, 0); // 应该是空表
     }
 
+    #[tokio::test]
+    async fn test_table_handle_rejects_unknown_tables() {
+        let db_manager = create_test_database().await;
+        let query_builder = QueryBuilder::new(db_manager.pool());
+
+        for name in [
+            "sqlite_master",
+            "users",
+            "claude_providers; DROP TABLE mcp_servers",
+            "",
+        ] {
+            assert!(matches!(
+                query_builder.table(name),
+                Err(DatabaseError::UnknownTable(_))
+            ));
+        }
+
+        let table = query_builder.table("mcp_servers").unwrap();
+        let id = table
+            .insert(
+                &["name", "command", "args"],
+                &["handle-server", "npx", "[]"],
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            table.insert(&["name) VALUES ('x'); --"], &["x"]).await,
+            Err(DatabaseError::Query(_))
+        ));
+        assert_eq!(table.delete_by_id(id).await.unwrap(), 1);
+
+        table
+            .insert(&["name", "command", "args"], &["other", "npx", "[]"])
+            .await
+            .unwrap();
+        assert_eq!(table.delete_all().await.unwrap(), 1);
+        assert!(query_builder.table_exists("mcp_servers").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_table_handle_binds_row_values() {
+        let db_manager = create_test_database().await;
+        let table = QueryBuilder::new(db_manager.pool()).table("common_configs").unwrap();
+
+        let columns = ["key", "value", "key_fingerprint"];
+        let id = table
+            .insert_timestamped(&columns, &[Some("handle.key"), Some("1'; --"), None])
+            .await
+            .unwrap();
+
+        let rows = table
+            .select_as_text(&["value", "is_active"], Some(("key", "handle.key")))
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get::<i64, _>("id"), id);
+        assert_eq!(rows[0].get::<String, _>("value"), "1'; --");
+        assert_eq!(rows[0].get::<String, _>("is_active"), "1");
+        assert!(table
+            .select_as_text(&["value"], Some(("key", "other")))
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            table
+                .update_by_id(id, &["value", "key_fingerprint"], &[Some("2"), Some("fp")])
+                .await
+                .unwrap(),
+            1
+        );
+        let row = &table.select_all().await.unwrap()[0];
+        assert_eq!(row.get::<String, _>("value"), "2");
+        assert_eq!(
+            row.get::<Option<String>, _>("key_fingerprint").as_deref(),
+            Some("fp")
+        );
+        assert!(row.get::<Option<String>, _>("created_at").is_some());
+
+        assert!(table.has_column("key_fingerprint").await.unwrap());
+        assert!(!table.has_column("tags").await.unwrap());
+        for result in [
+            table.select_as_text(&["value"], Some(("key = key OR 1", "x"))).await.map(|_| 0),
+            table.update_by_id(id, &["value = value, key"], &[Some("x")]).await,
+        ] {
+            assert!(matches!(result, Err(DatabaseError::Query(_))));
+        }
+    }
+
+    #[cfg(not(feature = "sqlcipher"))]
+    #[tokio::test]
+    async fn test_encrypted_database_requires_sqlcipher() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("encrypted.db");
+        // 加密数据库的文件头是随机字节
+        std::fs::write(&db_path, [0x5Au8; 4096]).unwrap();
+
+        let config = DatabaseConfig {
+            url: format!("sqlite:{}", db_path.display()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            DatabaseManager::new(config).await,
+            Err(DatabaseError::Config(_))
+        ));
+
+        let config = DatabaseConfig {
+            url: format!("sqlite:{}", temp_dir.path().join("plain.db").display()),
+            passphrase: Some("secret".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            DatabaseManager::new(config).await,
+            Err(DatabaseError::Config(_))
+        ));
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[tokio::test]
+    async fn test_encrypted_database_passphrase() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("encrypted.db");
+        let config_with = |passphrase: Option<&str>| DatabaseConfig {
+            url: format!("sqlite:{}", db_path.display()),
+            max_connections: 1,
+            passphrase: passphrase.map(str::to_string),
+            ..Default::default()
+        };
+
+        let db_manager = DatabaseManager::new(config_with(Some("right"))).await.unwrap();
+        sqlx::query("CREATE TABLE IF NOT EXISTS secrets (value TEXT)")
+            .execute(db_manager.pool())
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO secrets (value) VALUES ('plaintext-marker')")
+            .execute(db_manager.pool())
+            .await
+            .unwrap();
+        db_manager.pool().close().await;
+
+        // 文件内容已加密
+        let raw = std::fs::read(&db_path).unwrap();
+        assert!(!raw.starts_with(SQLITE_HEADER));
+        assert!(!raw.windows(16).any(|w| w == b"plaintext-marker"));
+
+        let db_manager = DatabaseManager::new(config_with(Some("right"))).await.unwrap();
+        let value: String = sqlx::query_scalar("SELECT value FROM secrets")
+            .fetch_one(db_manager.pool())
+            .await
+            .unwrap();
+        assert_eq!(value, "plaintext-marker");
+        db_manager.pool().close().await;
+
+        assert!(DatabaseManager::new(config_with(Some("wrong"))).await.is_err());
+    }
+
+    #[test]
+    fn test_sql_shape_strips_literals() {
+        let sql = "SELECT * FROM t1\n  WHERE token = 'sk-it''s-secret' AND id = 42\n  LIMIT 1";
+        assert_eq!(
+            sql_shape(sql),
+            "SELECT * FROM t1 WHERE token = ? AND id = ? LIMIT ?"
+        );
+        assert_eq!(sql_shape("SELECT ?  FROM t"), "SELECT ? FROM t");
+    }
+
+    #[tokio::test]
+    async fn test_slow_query_threshold_logs_repository_queries() {
+        use crate::repositories::CommonConfigRepository;
+        use std::io;
+        use std::sync::Mutex;
+
+        /// 收集日志输出
+        #[derive(Clone, Default)]
+        struct Captured(Arc<Mutex<Vec<u8>>>);
+
+        impl io::Write for Captured {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let captured = Captured::default();
+        let output = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || output.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let config = DatabaseConfig {
+            slow_query_threshold: Some(Duration::from_nanos(1)),
+            ..DatabaseConfig::in_memory_shared("database_test_slow_query")
+        };
+        let db_manager = DatabaseManager::new(config).await.unwrap();
+        db_manager.ensure_initialized().await.unwrap();
+        sqlx::query(
+            "INSERT INTO common_configs (key, value, category) VALUES ('slow.token', 'sk-slow-query-secret', 'test')",
+        )
+        .execute(db_manager.pool())
+        .await
+        .unwrap();
+
+        let crypto_service = crate::crypto::CryptoService::new("test_key_for_slow_query").unwrap();
+        let repository = CommonConfigRepository::new(&db_manager, &crypto_service);
+        let found = repository.search_common_configs("slow.token", None, None).await.unwrap();
+        assert_eq!(found.len(), 1);
+
+        let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("慢查询"), "{}", logs);
+        assert!(logs.contains("common_configs.search"), "{}", logs);
+        assert!(
+            logs.contains("SELECT * FROM common_configs WHERE key LIKE ?"),
+            "{}",
+            logs
+        );
+        assert!(!logs.contains("sk-slow-query-secret"), "{}", logs);
+
+        let summary = db_manager
+            .performance_monitor()
+            .get_summary(&MetricType::DatabaseQuery)
+            .await
+            .unwrap();
+        assert!(summary.total_operations >= 1);
+    }
+
+    #[tokio::test]
+    async fn test_query_timer_covers_writes() {
+        use crate::models::{
+            CreateCommonConfigRequest, CreateProviderGroupRequest, UpdateCommonConfigRequest,
+        };
+        use crate::repositories::{CommonConfigRepository, ProviderGroupRepository};
+
+        let db_manager = DatabaseManager::new(DatabaseConfig::in_memory_shared(
+            "database_test_timed_writes",
+        ))
+        .await
+        .unwrap();
+        db_manager.ensure_initialized().await.unwrap();
+
+        let crypto_service =
+            crate::crypto::CryptoService::new("test_key_for_timed_writes").unwrap();
+        let configs = CommonConfigRepository::new(&db_manager, &crypto_service);
+        let id = configs
+            .create_common_config(
+                &CreateCommonConfigRequest {
+                    key: "timed.key".to_string(),
+                    value: "v1".to_string(),
+                    description: None,
+                    category: Some("test".to_string()),
+                    is_active: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        configs
+            .update_common_config(
+                id,
+                &UpdateCommonConfigRequest {
+                    key: None,
+                    value: Some("v2".to_string()),
+                    description: None,
+                    category: None,
+                    is_active: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let groups = ProviderGroupRepository::new(&db_manager);
+        groups
+            .create_group(&CreateProviderGroupRequest {
+                name: "timed".to_string(),
+                description: None,
+                members: Vec::new(),
+            })
+            .await
+            .unwrap();
+        assert_eq!(groups.list_groups().await.unwrap().len(), 1);
+
+        let operations: Vec<String> = db_manager
+            .performance_monitor()
+            .get_all_metrics()
+            .await
+            .into_iter()
+            .filter(|metric| matches!(metric.metric_type, MetricType::DatabaseQuery))
+            .map(|metric| metric.operation)
+            .collect();
+        for expected in [
+            "common_configs.create",
+            "common_configs.update",
+            "provider_groups.create",
+            "provider_groups.list",
+        ] {
+            assert!(
+                operations.iter().any(|op| op == expected),
+                "{}: {:?}",
+                expected,
+                operations
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_shared_database_survives_pool_recycling() {
+        let config = DatabaseConfig {
+            min_connections: 0,
+            idle_timeout: Duration::from_millis(50),
+            max_lifetime: Duration::from_millis(50),
+            ..DatabaseConfig::in_memory_shared("database_test_recycling")
+        };
+        assert!(config.is_in_memory());
+        let db_manager = DatabaseManager::new(config.clone()).await.unwrap();
+        db_manager.ensure_initialized().await.unwrap();
+
+        sqlx::query("INSERT INTO common_configs (key, value, category) VALUES ('k', 'v', 'test')")
+            .execute(db_manager.pool())
+            .await
+            .unwrap();
+
+        // 连接池中的连接全部过期关闭后，数据仍然存在
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM common_configs")
+            .fetch_one(db_manager.pool())
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        // 同名的另一个管理器访问同一个数据库
+        let other = DatabaseManager::new(config).await.unwrap();
+        let value: String = sqlx::query_scalar("SELECT value FROM common_configs WHERE key = 'k'")
+            .fetch_one(other.pool())
+            .await
+            .unwrap();
+        assert_eq!(value, "v");
+        assert!(other.database_path().is_none());
+    }
+
     #[tokio::test]
     async fn test_pool_status() {
         let db_manager = create_test_database().await;
@@ -575,4 +1948,214 @@ mod tests {
         let count = query_builder.count_records("common_configs").await.unwrap();
         assert_eq!(count, 1);
     }
+
+    #[tokio::test]
+    async fn test_bind_in_chunks_large_value_list() {
+        let db_manager = create_test_database().await;
+        let query_builder = QueryBuilder::new(db_manager.pool());
+
+        query_builder
+            .execute_raw(
+                "INSERT INTO common_configs (key, value, category) VALUES (?, ?, ?)",
+                &["key_0", "value", "test"],
+            )
+            .await
+            .unwrap();
+
+        let keys: Vec<String> = (0..1000).map(|i| format!("key_{}", i)).collect();
+        let values: Vec<&str> = keys.iter().map(String::as_str).collect();
+
+        let chunks = QueryBuilder::bind_in("key", &values).unwrap();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].1.len(), SQLITE_MAX_BIND_PARAMS);
+        assert_eq!(chunks[1].1.len(), 1);
+        assert_eq!(chunks[1].0, "key IN (?)");
+
+        let mut total = 0;
+        for (fragment, params) in &chunks {
+            let query = format!(
+                "SELECT COUNT(*) as count FROM common_configs WHERE {}",
+                fragment
+            );
+            let row = params
+                .iter()
+                .fold(sqlx::query(&query), |q, value| q.bind(*value))
+                .fetch_one(db_manager.pool())
+                .await
+                .unwrap();
+            total += row.get::<i64, _>("count");
+        }
+        assert_eq!(total, 1);
+
+        assert_eq!(QueryBuilder::bind_in("key", &[]).unwrap()[0].0, "1 = 0");
+        assert!(QueryBuilder::bind_in("key; DROP TABLE x", &values).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reset_empties_tables_and_keeps_backup() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db_path = temp_dir.path().join("reset.db");
+        let db_manager = DatabaseManager::new(DatabaseConfig {
+            url: format!("sqlite:{}", db_path.display()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        db_manager.ensure_initialized().await.unwrap();
+        let pool = db_manager.pool();
+
+        sqlx::query(
+            "INSERT INTO claude_providers (name, url, token) VALUES ('p', 'https://a.example.com', 't')",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO common_configs (key, value) VALUES ('k', 'v')")
+            .execute(pool)
+            .await
+            .unwrap();
+        const COUNT_INDEXES: &str =
+            "SELECT COUNT(*) FROM sqlite_master WHERE type IN ('index', 'trigger')";
+        let indexes_before: i64 = sqlx::query_scalar(COUNT_INDEXES).fetch_one(pool).await.unwrap();
+
+        let backup_path = db_manager.reset().await.unwrap().unwrap();
+        assert!(backup_path.starts_with(temp_dir.path().join("backups")));
+
+        let query_builder = QueryBuilder::new(pool);
+        for table in KNOWN_TABLES {
+            assert!(
+                query_builder.table_exists(table).await.unwrap(),
+                "{}",
+                table
+            );
+            assert_eq!(
+                query_builder.count_records(table).await.unwrap(),
+                0,
+                "{}",
+                table
+            );
+        }
+        let indexes_after: i64 = sqlx::query_scalar(COUNT_INDEXES).fetch_one(pool).await.unwrap();
+        assert_eq!(indexes_after, indexes_before);
+
+        // 重建后的表可以正常写入，自增ID重新开始
+        let id = sqlx::query(
+            "INSERT INTO claude_providers (name, url, token) VALUES ('n', 'https://b.example.com', 't')",
+        )
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+        assert_eq!(id, 1);
+
+        // 备份中保留重置前的数据
+        let backup = sqlx::SqlitePool::connect(&format!("sqlite:{}", backup_path.display()))
+            .await
+            .unwrap();
+        let name: String = sqlx::query_scalar("SELECT name FROM claude_providers")
+            .fetch_one(&backup)
+            .await
+            .unwrap();
+        assert_eq!(name, "p");
+    }
+
+    #[tokio::test]
+    async fn test_optimize_after_bulk_delete() {
+        let db_manager = create_test_database().await;
+        let pool = db_manager.pool();
+
+        sqlx::query("CREATE TABLE optimize_test (id INTEGER PRIMARY KEY, payload TEXT NOT NULL)")
+            .execute(pool)
+            .await
+            .unwrap();
+
+        let payload = "x".repeat(1024);
+        let mut tx = pool.begin().await.unwrap();
+        for _ in 0..500 {
+            sqlx::query("INSERT INTO optimize_test (payload) VALUES (?)")
+                .bind(&payload)
+                .execute(&mut *tx)
+                .await
+                .unwrap();
+        }
+        tx.commit().await.unwrap();
+
+        sqlx::query("DELETE FROM optimize_test").execute(pool).await.unwrap();
+
+        let report = db_manager.optimize(true).await.unwrap();
+        assert!(report.vacuumed || report.skipped);
+        assert!(report.reclaimed_bytes >= 0);
+
+        // 不执行 VACUUM 的优化同样可以正常完成
+        let report = db_manager.optimize(false).await.unwrap();
+        assert!(!report.vacuumed);
+    }
+
+    #[tokio::test]
+    async fn test_run_once_runs_init_body_once_for_concurrent_callers() {
+        use std::sync::atomic::AtomicUsize;
+
+        let cell = InitCell::default();
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let callers: Vec<_> = (0..8)
+            .map(|_| {
+                let cell = cell.clone();
+                let runs = runs.clone();
+                tokio::spawn(async move {
+                    run_once(&cell, || async {
+                        runs.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        Ok::<(), DatabaseError>(())
+                    })
+                    .await
+                })
+            })
+            .collect();
+
+        for caller in callers {
+            assert!(caller.await.unwrap().is_ok());
+        }
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_run_once_does_not_retry_after_failure() {
+        use std::sync::atomic::AtomicBool;
+
+        let cell = InitCell::default();
+
+        let first = run_once(&cell, || async {
+            Err::<(), _>(DatabaseError::Migration("迁移脚本错误".to_string()))
+        })
+        .await;
+        assert!(matches!(first, Err(DatabaseError::Initialization(_))));
+
+        // 之后的调用直接返回之前的错误，不会执行新的初始化
+        let retried = AtomicBool::new(false);
+        let second = run_once(&cell, || async {
+            retried.store(true, Ordering::SeqCst);
+            Ok::<(), DatabaseError>(())
+        })
+        .await;
+        assert!(!retried.load(Ordering::SeqCst));
+        match second {
+            Err(DatabaseError::Initialization(message)) => {
+                assert!(message.contains("迁移脚本错误"))
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_managers_for_same_file_share_initialization() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let url = format!("sqlite:{}", temp_file.path().display());
+
+        assert!(Arc::ptr_eq(&init_cell_for(&url), &init_cell_for(&url)));
+        assert!(!Arc::ptr_eq(
+            &init_cell_for("sqlite::memory:"),
+            &init_cell_for("sqlite::memory:")
+        ));
+    }
 }
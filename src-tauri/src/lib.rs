@@ -11,19 +11,24 @@ pub mod models;
 pub mod performance;
 pub mod python_compatibility_test;
 pub mod repositories;
+pub mod runtime;
 pub mod services;
 pub mod simple_migration;
+pub mod task_registry;
 pub mod utils;
 
 // 通用验证器
-pub use common_validators::{ValidationError, ValidationResult, Validator};
+pub use common_validators::{ValidationError, ValidationErrors, ValidationResult, Validator};
 
 // 重新导出主要功能
 pub use api::{ApiError, ApiResponse, ApiResult, ApiServer, PagedResponse, RequestContext};
-pub use crypto::{CryptoError, CryptoService};
-pub use database::{DatabaseConfig, DatabaseError, DatabaseManager, PoolStatus, QueryBuilder};
-pub use logging_manager::LogConfig;
+pub use crypto::{CryptoError, CryptoService, KeyRing, RotationKeys};
+pub use database::{
+    DatabaseConfig, DatabaseError, DatabaseManager, OptimizeReport, PoolStatus, QueryBuilder,
+    QueryTimer, RefreshReport, TableStatsRefresh, TimedExecutor,
+};
 pub use logging_manager::LoggingManager;
+pub use logging_manager::{LogConfig, RedactingMakeWriter};
 pub use models::*;
 pub use performance::{
     MetricType, PerformanceMetric, PerformanceMonitor, PerformanceSummary, PerformanceTimer,
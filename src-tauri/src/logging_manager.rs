@@ -1,17 +1,91 @@
 //! 日志管理器
 //!
-//! 提供统一的日志配置和管理功能
+//! 提供统一的日志配置和管理功能。写入控制台和文件的日志都会按共用的
+//! [`SecretMatcher`] 脱敏
 
+use crate::utils::redaction::{secret_matcher, SecretMatcher};
 use std::fs;
+use std::io;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tracing::Level;
 use tracing_subscriber::{
-    fmt::{self, format::FmtSpan},
+    fmt::{self, format::FmtSpan, MakeWriter},
     layer::SubscriberExt,
     util::SubscriberInitExt,
     EnvFilter, Layer, Registry,
 };
 
+/// 写入前替换敏感信息的日志输出
+///
+/// 未指定匹配器时使用 [`secret_matcher`]，启动后安装的规则对已初始化的日志同样生效
+pub struct RedactingMakeWriter<M> {
+    inner: M,
+    matcher: Option<Arc<SecretMatcher>>,
+}
+
+impl<M> RedactingMakeWriter<M> {
+    /// 使用共用的匹配器
+    pub fn new(inner: M) -> Self {
+        Self { inner, matcher: None }
+    }
+
+    /// 使用指定的匹配器
+    pub fn with_matcher(inner: M, matcher: Arc<SecretMatcher>) -> Self {
+        Self { inner, matcher: Some(matcher) }
+    }
+}
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<'a, M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter {
+            inner: self.inner.make_writer(),
+            matcher: self.matcher.as_deref().unwrap_or_else(|| secret_matcher()),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+/// 缓存一条日志，刷新或释放时脱敏后写入
+pub struct RedactingWriter<'a, W: io::Write> {
+    inner: W,
+    matcher: &'a SecretMatcher,
+    buffer: Vec<u8>,
+}
+
+impl<W: io::Write> RedactingWriter<'_, W> {
+    fn write_buffered(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let text = String::from_utf8_lossy(&self.buffer);
+        let redacted = self.matcher.redact(&text);
+        self.inner.write_all(redacted.as_bytes())?;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl<W: io::Write> io::Write for RedactingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_buffered()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: io::Write> Drop for RedactingWriter<'_, W> {
+    fn drop(&mut self) {
+        let _ = self.write_buffered();
+    }
+}
+
 /// 日志管理器
 pub struct LoggingManager {
     app_name: String,
@@ -50,7 +124,7 @@ impl LoggingManager {
             .with(env_filter)
             .with(
                 fmt::layer()
-                    .with_writer(file)
+                    .with_writer(RedactingMakeWriter::new(file))
                     .with_span_events(FmtSpan::CLOSE)
                     .with_ansi(false)
                     .with_target(true)
@@ -60,7 +134,7 @@ impl LoggingManager {
             )
             .with(
                 fmt::layer()
-                    .with_writer(error_file)
+                    .with_writer(RedactingMakeWriter::new(error_file))
                     .with_span_events(FmtSpan::CLOSE)
                     .with_ansi(false)
                     .with_target(true)
@@ -90,6 +164,7 @@ impl LoggingManager {
         });
 
         let subscriber = tracing_subscriber::fmt()
+            .with_writer(RedactingMakeWriter::new(io::stdout))
             .with_max_level(Level::DEBUG)
             .with_target(true)
             .with_thread_ids(false)
@@ -241,6 +316,7 @@ impl LoggingManager {
         if config.console_logging {
             let console_layer = if config.pretty_output {
                 fmt::layer()
+                    .with_writer(RedactingMakeWriter::new(io::stdout))
                     .with_target(config.include_target)
                     .with_span_events(if config.include_spans {
                         FmtSpan::CLOSE
@@ -251,6 +327,7 @@ impl LoggingManager {
                     .boxed()
             } else {
                 fmt::layer()
+                    .with_writer(RedactingMakeWriter::new(io::stdout))
                     .with_target(config.include_target)
                     .with_span_events(if config.include_spans {
                         FmtSpan::CLOSE
@@ -273,7 +350,7 @@ impl LoggingManager {
             let file = std::fs::OpenOptions::new().create(true).append(true).open(&log_file)?;
 
             let file_layer = fmt::layer()
-                .with_writer(file)
+                .with_writer(RedactingMakeWriter::new(file))
                 .with_target(config.include_target)
                 .with_span_events(if config.include_spans {
                     FmtSpan::CLOSE
@@ -337,6 +414,46 @@ mod tests {
         tracing::info!("测试开发环境日志初始化");
     }
 
+    #[test]
+    fn test_custom_secret_pattern_redacted_in_logs() {
+        use std::sync::Mutex;
+
+        /// 收集日志输出
+        #[derive(Clone, Default)]
+        struct Captured(Arc<Mutex<Vec<u8>>>);
+
+        impl io::Write for Captured {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let captured = Captured::default();
+        let output = captured.clone();
+        let matcher = Arc::new(SecretMatcher::new(&["corp_[A-Za-z0-9]{12,}"]).unwrap());
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(RedactingMakeWriter::with_matcher(
+                move || output.clone(),
+                matcher,
+            ))
+            .with_ansi(false)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(token = "corp_abcdef123456", "使用企业Token请求上游");
+        });
+
+        let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("使用企业Token请求上游"), "{}", logs);
+        assert!(!logs.contains("corp_abcdef123456"), "{}", logs);
+        assert!(logs.contains(crate::utils::redaction::REDACTED), "{}", logs);
+    }
+
     #[test]
     fn test_test_init() {
         init_logging();
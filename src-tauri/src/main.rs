@@ -6,8 +6,27 @@
 //! 从 Python/FastAPI 迁移到 Rust/Tauri 的桌面应用程序
 
 // 从 library crate 导入必要的模块
-use migration_ai_manager_lib::LoggingManager;
-use tauri::Emitter;
+use migration_ai_manager_lib::api::rpc::{RpcContext, RpcRegistry};
+use migration_ai_manager_lib::api::server::{start_background_tasks, ApiServerConfig};
+use migration_ai_manager_lib::database::RESET_CONFIRMATION_TOKEN;
+use migration_ai_manager_lib::migration::config_generator::ConfigGenerator;
+use migration_ai_manager_lib::migration_tool::{DataDiff, DataMigrationTool};
+use migration_ai_manager_lib::runtime::RuntimeMode;
+use migration_ai_manager_lib::services::auto_update_service::{
+    AutoUpdateReport, AutoUpdateService,
+};
+use migration_ai_manager_lib::services::diagnostics_service::{
+    DiagnosticsBundle, DiagnosticsService,
+};
+use migration_ai_manager_lib::services::mode_service::{Mode, ModeService, ModeSwitchResult};
+use migration_ai_manager_lib::services::retention_service::{RetentionReport, RetentionService};
+use migration_ai_manager_lib::services::seed_service::{SeedReport, SeedService};
+use migration_ai_manager_lib::task_registry::TaskRegistry;
+use migration_ai_manager_lib::utils::redaction::install_secret_matcher;
+use migration_ai_manager_lib::{ApiResponse, DatabaseConfig, DatabaseManager, LoggingManager};
+use serde_json::{json, Value};
+use std::sync::{Arc, OnceLock};
+use tauri::{Emitter, Manager, State};
 
 // Tauri 基础命令
 #[tauri::command]
@@ -15,6 +34,167 @@ fn greet(name: &str) -> String {
     format!("你好, {}! AI Manager 后端已就绪。", name)
 }
 
+/// 获取启动时创建并由Tauri托管的共享资源，并确保数据库已完成迁移
+async fn app_context(state: State<'_, RpcContext>) -> Result<RpcContext, String> {
+    let ctx = state.inner().clone();
+    ctx.db_manager.ensure_initialized().await.map_err(|e| e.to_string())?;
+    Ok(ctx)
+}
+
+/// 导出脱敏后的诊断信息，用于附加到问题反馈中
+#[tauri::command]
+async fn export_diagnostics(state: State<'_, RpcContext>) -> Result<serde_json::Value, String> {
+    let ctx = app_context(state).await?;
+
+    DiagnosticsService::new(ctx.db_manager, ctx.crypto_service)
+        .with_key_ring(ctx.key_ring)
+        .export_diagnostics()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 生成诊断包（zip），包含脱敏的诊断信息、数据库统计和最近的日志文件
+#[tauri::command]
+async fn generate_diagnostics_bundle(
+    state: State<'_, RpcContext>,
+    path: String,
+) -> Result<DiagnosticsBundle, String> {
+    let ctx = app_context(state).await?;
+    let log_dir = LoggingManager::new("migration_ai_manager").log_dir().clone();
+
+    DiagnosticsService::new(ctx.db_manager, ctx.crypto_service)
+        .with_key_ring(ctx.key_ring)
+        .generate_bundle(std::path::Path::new(&path), &log_dir)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 切换工具模式（claude / codex），并重新生成对应的配置文件
+#[tauri::command]
+async fn switch_mode(
+    state: State<'_, RpcContext>,
+    mode: String,
+) -> Result<ModeSwitchResult, String> {
+    let mode = mode.parse::<Mode>().map_err(|e| e.to_string())?;
+    let ctx = app_context(state).await?;
+    let generator = ConfigGenerator::new().map_err(|e| e.to_string())?;
+
+    ModeService::new(ctx.db_manager, ctx.crypto_service, generator)
+        .switch_mode(mode)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 向空数据库写入默认配置和示例供应商，数据库已有数据时不做任何修改
+#[tauri::command]
+async fn seed_defaults(state: State<'_, RpcContext>) -> Result<SeedReport, String> {
+    let ctx = app_context(state).await?;
+
+    SeedService::new(ctx.db_manager, ctx.crypto_service)
+        .seed_defaults()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 立即按保留策略清理配置历史和迁移记录
+#[tauri::command]
+async fn run_retention_cleanup(state: State<'_, RpcContext>) -> Result<RetentionReport, String> {
+    let ctx = app_context(state).await?;
+
+    RetentionService::new(ctx.db_manager).cleanup().await.map_err(|e| e.to_string())
+}
+
+/// 立即检查开启自动更新的供应商，将模型更新为最新版本
+///
+/// 每个模型发生变化的供应商都会向前端发送 `provider_models_updated` 事件
+#[tauri::command]
+async fn run_model_auto_update(
+    app_handle: tauri::AppHandle,
+    state: State<'_, RpcContext>,
+) -> Result<AutoUpdateReport, String> {
+    let ctx = app_context(state).await?;
+
+    let report = AutoUpdateService::new(ctx.db_manager, ctx.crypto_service)
+        .with_http_client(ctx.http_client)
+        .update_all()
+        .await
+        .map_err(|e| e.to_string())?;
+    for event in &report.updated {
+        if let Err(e) = app_handle.emit("provider_models_updated", event) {
+            tracing::warn!(error = %e, "发送模型更新事件失败");
+        }
+    }
+    Ok(report)
+}
+
+/// 清空所有数据并重建数据表，需要提供确认口令
+///
+/// 执行前自动备份数据库，返回备份文件路径，可用于恢复
+#[tauri::command]
+async fn reset_database(
+    state: State<'_, RpcContext>,
+    confirmation: String,
+) -> Result<Option<String>, String> {
+    if confirmation != RESET_CONFIRMATION_TOKEN {
+        return Err(format!(
+            "确认口令不正确，请输入 \"{}\" 以清空数据库",
+            RESET_CONFIRMATION_TOKEN
+        ));
+    }
+
+    // 使用共享的连接池，不另外打开数据库连接
+    state
+        .db_manager
+        .reset()
+        .await
+        .map(|backup_path| backup_path.map(|path| path.display().to_string()))
+        .map_err(|e| e.to_string())
+}
+
+/// 对比导出文件与当前数据库，返回重新导入会新增、更新的记录以及导出文件中没有的记录
+#[tauri::command]
+async fn diff_import(state: State<'_, RpcContext>, path: String) -> Result<DataDiff, String> {
+    let ctx = app_context(state).await?;
+
+    DataMigrationTool::with_crypto_service((*ctx.db_manager).clone(), (*ctx.crypto_service).clone())
+        .diff_json_file(path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// 按方法名调用已注册的操作，`list_methods` 返回所有可用方法
+///
+/// 使用启动时创建并由Tauri托管的 `RpcContext`，不会在每次调用时重新创建数据库连接和加密服务
+#[tauri::command]
+async fn rpc(app_handle: tauri::AppHandle, method: String, params: Value) -> ApiResponse<Value> {
+    static REGISTRY: OnceLock<RpcRegistry> = OnceLock::new();
+    let registry = REGISTRY.get_or_init(RpcRegistry::with_default_methods);
+
+    let ctx = app_handle.state::<RpcContext>().inner().clone();
+    if let Err(e) = ctx.db_manager.ensure_initialized().await {
+        return ApiResponse::failure(
+            json!({ "code": "DATABASE_ERROR", "method": method }),
+            e.to_string(),
+        );
+    }
+
+    registry.dispatch(ctx, &method, params).await
+}
+
+/// 创建所有命令共用的数据库连接、加密服务和通用配置密钥环
+///
+/// 密钥按 [`ApiServerConfig::with_env_keys`] 读取环境变量，`FERNET_KEY` 为空时以明文存储
+async fn create_app_context() -> Result<RpcContext, String> {
+    let keys = ApiServerConfig::default()
+        .with_env_keys()
+        .and_then(|config| config.encryption_keys())
+        .map_err(|e| format!("加密服务初始化失败: {}", e))?;
+    let db_manager = DatabaseManager::new(DatabaseConfig::default())
+        .await
+        .map_err(|e| format!("数据库初始化失败: {}", e))?;
+    Ok(RpcContext::new(Arc::new(db_manager), keys.crypto_service).with_key_ring(keys.key_ring))
+}
+
 /// 主函数（高度优化启动时间）
 ///
 /// 使用延迟初始化和并行处理来最小化启动延迟
@@ -27,21 +207,70 @@ fn main() {
     tracing::info!("AI Manager 应用程序启动");
     tracing::info!("版本: 0.1.0");
 
+    // 按配置创建异步运行时，Tauri和后台任务共用该运行时
+    let runtime_mode = RuntimeMode::from_env();
+    let runtime = match runtime_mode.build() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            tracing::error!("异步运行时创建失败: {}", e);
+            std::process::exit(1);
+        }
+    };
+    tracing::info!("异步运行时: {}", runtime_mode);
+    tauri::async_runtime::set(runtime.handle().clone());
+
+    let _runtime = match runtime_mode {
+        RuntimeMode::CurrentThread => {
+            // 单线程运行时没有自己的工作线程，需要专门的线程驱动后台任务
+            std::thread::spawn(move || runtime.block_on(std::future::pending::<()>()));
+            None
+        }
+        RuntimeMode::MultiThread { .. } => Some(runtime),
+    };
+
     // 启动Tauri应用
     let result = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
-        .invoke_handler(tauri::generate_handler![greet])
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            export_diagnostics,
+            generate_diagnostics_bundle,
+            switch_mode,
+            seed_defaults,
+            run_retention_cleanup,
+            run_model_auto_update,
+            reset_database,
+            diff_import,
+            rpc
+        ])
         .setup(|app| {
+            // 创建所有命令共用的资源，由Tauri托管
+            let app_context = tauri::async_runtime::block_on(create_app_context())?;
+            app.manage(app_context.clone());
+
+            // 启动后台任务，注册表由Tauri托管，释放时任务随之取消
+            let task_registry = TaskRegistry::new();
+            tauri::async_runtime::block_on(async {
+                start_background_tasks(
+                    &app_context.db_manager,
+                    &app_context.crypto_service,
+                    &app_context.http_client,
+                    &task_registry,
+                )
+            })?;
+            app.manage(task_registry);
+
             // 在Tauri设置阶段启动后台初始化任务
             let app_handle = app.handle().clone();
-            tokio::spawn(async move {
-                // 延迟初始化非关键组件
-                delayed_initialization().await;
-
-                // 可选：通知前端初始化完成
-                if let Err(e) = app_handle.emit_to("main", "initialization_complete", ()) {
-                    eprintln!("发送初始化完成事件失败: {}", e);
+            tauri::async_runtime::spawn(async move {
+                // 延迟初始化非关键组件，配置无效时通知前端初始化失败
+                let emitted = match delayed_initialization(app_context).await {
+                    Ok(()) => app_handle.emit_to("main", "initialization_complete", ()),
+                    Err(e) => app_handle.emit_to("main", "initialization_failed", e),
+                };
+                if let Err(e) = emitted {
+                    eprintln!("发送初始化事件失败: {}", e);
                 }
             });
 
@@ -56,15 +285,41 @@ fn main() {
 }
 
 /// 延迟初始化非关键组件
-async fn delayed_initialization() {
+///
+/// 配置无效（如敏感信息匹配规则无法编译）时返回错误，不会带着不完整的配置继续运行
+async fn delayed_initialization(ctx: RpcContext) -> Result<(), String> {
     let start = std::time::Instant::now();
 
     // 并行执行所有延迟初始化阶段
-    tokio::join!(
+    let (loaded, _, _) = tokio::join!(
         async {
-            // 阶段1：预热数据库连接（如果需要）
+            // 阶段1：执行数据库迁移和预热，同一数据库文件在进程内只执行一次
             tracing::debug!("开始延迟初始化 - 阶段1");
-            tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+            if let Err(e) = ctx.db_manager.ensure_initialized().await {
+                tracing::error!(error = %e, "数据库初始化失败");
+                return Ok(());
+            }
+            let common_config_service = ctx.common_config_service();
+            // 加载敏感信息匹配规则，日志、诊断和导入共用
+            match common_config_service.secret_matcher().await {
+                Ok(matcher) => {
+                    install_secret_matcher(matcher);
+                }
+                Err(e) => {
+                    tracing::error!(error = %e, "敏感信息匹配规则无效，配置加载失败");
+                    return Err(e.to_string());
+                }
+            }
+            // 首次运行（数据库为空）时写入默认数据
+            let seed = SeedService::new(ctx.db_manager.clone(), ctx.crypto_service.clone());
+            if let Err(e) = seed.seed_defaults().await {
+                tracing::warn!(error = %e, "默认数据初始化失败");
+            }
+            // 升级后补充新增的默认配置，不修改已有配置
+            if let Err(e) = common_config_service.ensure_defaults().await {
+                tracing::warn!(error = %e, "补充默认配置失败");
+            }
+            Ok(())
         },
         async {
             // 阶段2：预加载常用配置
@@ -78,6 +333,8 @@ async fn delayed_initialization() {
         }
     );
 
+    loaded?;
     let elapsed = start.elapsed();
     tracing::info!("✅ 延迟初始化完成，耗时: {:?}", elapsed);
+    Ok(())
 }
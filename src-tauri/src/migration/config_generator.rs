@@ -0,0 +1,920 @@
+//! 配置文件生成器
+//!
+//! 根据当前启用的供应商生成 Claude Code 的 `settings.json`
+//! 以及 Codex 的 `auth.json` / `config.toml`，并写入启用的MCP服务器
+
+use crate::models::{ClaudeProvider, CodexProvider};
+use crate::repositories::base_repository::RepositoryError;
+use crate::repositories::McpServerRepository;
+use crate::utils::redaction::REDACTED;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use thiserror::Error;
+use tokio::sync::OwnedMutexGuard;
+use tracing::{debug, info, warn};
+
+/// 配置生成错误类型
+#[derive(Error, Debug)]
+pub enum ConfigGeneratorError {
+    #[error("文件错误: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON处理错误: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("无法确定用户主目录")]
+    HomeDirNotFound,
+    #[error("读取数据失败: {0}")]
+    Repository(#[from] RepositoryError),
+}
+
+pub type ConfigGeneratorResult<T> = Result<T, ConfigGeneratorError>;
+
+/// 生成的配置文件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeneratedConfigKind {
+    /// ~/.claude/settings.json
+    ClaudeSettings,
+    /// ~/.codex/auth.json
+    CodexAuth,
+    /// ~/.codex/config.toml
+    CodexConfig,
+}
+
+impl GeneratedConfigKind {
+    /// 所有配置文件类型
+    pub const ALL: [GeneratedConfigKind; 3] =
+        [Self::ClaudeSettings, Self::CodexAuth, Self::CodexConfig];
+
+    /// 配置文件名
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::ClaudeSettings => "settings.json",
+            Self::CodexAuth => "auth.json",
+            Self::CodexConfig => "config.toml",
+        }
+    }
+}
+
+/// [`ConfigGenerator::generate_all_atomic`] 的生成目标
+#[derive(Debug, Clone, Copy)]
+pub enum ConfigTarget<'a> {
+    /// 生成 `settings.json`
+    Claude(&'a ClaudeProvider),
+    /// 生成 `auth.json` 和 `config.toml`
+    Codex(&'a CodexProvider),
+}
+
+impl ConfigTarget<'_> {
+    /// 目标对应的配置文件类型
+    pub fn kinds(&self) -> &'static [GeneratedConfigKind] {
+        match self {
+            Self::Claude(_) => &[GeneratedConfigKind::ClaudeSettings],
+            Self::Codex(_) => &[
+                GeneratedConfigKind::CodexAuth,
+                GeneratedConfigKind::CodexConfig,
+            ],
+        }
+    }
+}
+
+/// 写入供应商自定义请求头的环境变量
+const CUSTOM_HEADERS_ENV: &str = "ANTHROPIC_CUSTOM_HEADERS";
+
+/// Claude配置中MCP服务器所在的键
+const MCP_SERVERS_KEY: &str = "mcpServers";
+
+/// 模型环境变量前缀和后缀
+const MODEL_ENV_PREFIX: &str = "ANTHROPIC_DEFAULT_";
+const MODEL_ENV_SUFFIX: &str = "_MODEL";
+
+/// 模型角色对应的环境变量名，如 `opus` -> `ANTHROPIC_DEFAULT_OPUS_MODEL`
+fn model_env_key(role: &str) -> String {
+    let role: String = role
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}{}{}", MODEL_ENV_PREFIX, role, MODEL_ENV_SUFFIX)
+}
+
+/// 写入配置文件的MCP服务器
+///
+/// 环境变量中的机密值已解密，`${VAR}` 引用在生成配置时展开
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct McpServerConfig {
+    pub name: String,
+    /// stdio、sse 等，为空时按 stdio 处理
+    pub r#type: Option<String>,
+    /// stdio 服务器的启动命令，sse/http 服务器的地址
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    /// 值为机密的环境变量名
+    pub secret_env_keys: Vec<String>,
+}
+
+impl McpServerConfig {
+    /// 读取所有启用的MCP服务器并解密机密环境变量，按名称排序
+    ///
+    /// 与 [`McpServerRepository::list_active_servers`] 一致，超时时间未设置或不大于0的服务器视为已停用
+    pub async fn load_enabled(
+        repository: &McpServerRepository,
+    ) -> ConfigGeneratorResult<Vec<Self>> {
+        let mut servers = Vec::new();
+        for server in repository.list_active_servers(None).await? {
+            let env = repository.decrypted_env(&server).await?.unwrap_or_default();
+            servers.push(Self {
+                args: serde_json::from_str(&server.args)?,
+                env: env.into_iter().collect(),
+                name: server.name,
+                r#type: server.r#type,
+                command: server.command,
+                secret_env_keys: server.secret_env_keys,
+            });
+        }
+        servers.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(servers)
+    }
+
+    /// 将机密环境变量的值替换为 [`REDACTED`]，用于预览
+    pub fn redact_secrets(&mut self) {
+        for key in &self.secret_env_keys {
+            if let Some(value) = self.env.get_mut(key) {
+                *value = REDACTED.to_string();
+            }
+        }
+    }
+
+    /// 是否为通过地址连接的远程服务器
+    fn is_remote(&self) -> bool {
+        matches!(self.r#type.as_deref(), Some("sse" | "http"))
+    }
+
+    /// 展开 `${VAR}` 引用后的命令、参数和环境变量
+    fn expanded(&self) -> (String, Vec<String>, BTreeMap<String, String>) {
+        (
+            expand_env_vars(&self.command),
+            self.args.iter().map(|arg| expand_env_vars(arg)).collect(),
+            self.env
+                .iter()
+                .map(|(key, value)| (key.clone(), expand_env_vars(value)))
+                .collect(),
+        )
+    }
+
+    /// Claude `mcpServers` 中的条目
+    fn to_claude_json(&self) -> serde_json::Value {
+        let (command, args, env) = self.expanded();
+        if self.is_remote() {
+            json!({ "type": self.r#type, "url": command })
+        } else {
+            json!({ "type": "stdio", "command": command, "args": args, "env": env })
+        }
+    }
+
+    /// Codex `config.toml` 中的 `[mcp_servers.<name>]` 表
+    fn to_codex_toml(&self) -> ConfigGeneratorResult<String> {
+        let (command, args, env) = self.expanded();
+        let mut table = format!("\n[mcp_servers.{}]\n", serde_json::to_string(&self.name)?);
+        if self.is_remote() {
+            table.push_str(&format!("url = {}\n", serde_json::to_string(&command)?));
+            return Ok(table);
+        }
+
+        table.push_str(&format!("command = {}\n", serde_json::to_string(&command)?));
+        table.push_str(&format!("args = {}\n", serde_json::to_string(&args)?));
+        if !env.is_empty() {
+            let mut entries = Vec::with_capacity(env.len());
+            for (key, value) in &env {
+                entries.push(format!(
+                    "{} = {}",
+                    serde_json::to_string(key)?,
+                    serde_json::to_string(value)?
+                ));
+            }
+            table.push_str(&format!("env = {{ {} }}\n", entries.join(", ")));
+        }
+        Ok(table)
+    }
+}
+
+/// 使用进程环境变量展开 `${VAR}` 引用，未定义的变量保持原样
+fn expand_env_vars(value: &str) -> String {
+    expand_vars_with(value, |name| std::env::var(name).ok())
+}
+
+fn expand_vars_with(value: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        let reference = &rest[start..start + len + 3];
+        let name = &reference[2..reference.len() - 1];
+        match lookup(name) {
+            Some(resolved) => expanded.push_str(&resolved),
+            None => {
+                warn!(variable = %name, "MCP服务器配置引用了未定义的环境变量");
+                expanded.push_str(reference);
+            }
+        }
+        rest = &rest[start + len + 3..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// 按规范化路径区分的写入锁，进程内所有生成器共享
+type PathLocks = Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>;
+
+fn path_locks() -> &'static PathLocks {
+    static LOCKS: OnceLock<PathLocks> = OnceLock::new();
+    LOCKS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// 配置文件生成器
+#[derive(Debug, Clone)]
+pub struct ConfigGenerator {
+    claude_dir: PathBuf,
+    codex_dir: PathBuf,
+    /// 为 `None` 时不修改已有的MCP服务器配置
+    mcp_servers: Option<Vec<McpServerConfig>>,
+}
+
+impl ConfigGenerator {
+    /// 使用用户主目录下的默认位置创建生成器
+    pub fn new() -> ConfigGeneratorResult<Self> {
+        let home = std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(PathBuf::from)
+            .ok_or(ConfigGeneratorError::HomeDirNotFound)?;
+
+        Ok(Self::with_dirs(home.join(".claude"), home.join(".codex")))
+    }
+
+    /// 使用自定义目录创建生成器
+    pub fn with_dirs(claude_dir: impl Into<PathBuf>, codex_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            claude_dir: claude_dir.into(),
+            codex_dir: codex_dir.into(),
+            mcp_servers: None,
+        }
+    }
+
+    /// 设置写入配置文件的MCP服务器，替换配置文件中已有的MCP服务器
+    pub fn with_mcp_servers(mut self, servers: Vec<McpServerConfig>) -> Self {
+        self.mcp_servers = Some(servers);
+        self
+    }
+
+    /// 获取配置文件路径
+    pub fn path_for(&self, kind: GeneratedConfigKind) -> PathBuf {
+        match kind {
+            GeneratedConfigKind::ClaudeSettings => self.claude_dir.join(kind.file_name()),
+            GeneratedConfigKind::CodexAuth | GeneratedConfigKind::CodexConfig => {
+                self.codex_dir.join(kind.file_name())
+            }
+        }
+    }
+
+    /// 生成Claude配置文件
+    ///
+    /// `provider.token` 必须是解密后的明文，已有的其他设置项会被保留
+    pub fn generate_claude_settings(
+        &self,
+        provider: &ClaudeProvider,
+    ) -> ConfigGeneratorResult<PathBuf> {
+        let content = self.render_claude_settings(provider)?;
+        let path = self.write_config_file(GeneratedConfigKind::ClaudeSettings, &content)?;
+
+        info!("生成Claude配置文件: {}", path.display());
+        Ok(path)
+    }
+
+    /// 生成Claude配置文件内容，合并已有的 `settings.json`
+    fn render_claude_settings(&self, provider: &ClaudeProvider) -> ConfigGeneratorResult<String> {
+        let path = self.path_for(GeneratedConfigKind::ClaudeSettings);
+
+        let mut settings = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|_| json!({})),
+            Err(_) => json!({}),
+        };
+        if !settings.is_object() {
+            settings = json!({});
+        }
+        if !settings["env"].is_object() {
+            settings["env"] = json!({});
+        }
+
+        let env = &mut settings["env"];
+        env["ANTHROPIC_AUTH_TOKEN"] = json!(provider.token);
+        env["ANTHROPIC_BASE_URL"] = json!(provider.url);
+        if let Some(timeout) = provider.timeout {
+            env["API_TIMEOUT_MS"] = json!(timeout.to_string());
+        }
+        // auto_update 为 1 时禁用遥测
+        if provider.auto_update == Some(1) {
+            env["CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC"] = json!("1");
+        } else if let Some(env) = env.as_object_mut() {
+            env.remove("CLAUDE_CODE_DISABLE_NONESSENTIAL_TRAFFIC");
+        }
+
+        // 自定义请求头按 `名称: 值` 逐行写入 ANTHROPIC_CUSTOM_HEADERS
+        if provider.custom_headers.is_empty() {
+            if let Some(env) = env.as_object_mut() {
+                env.remove(CUSTOM_HEADERS_ENV);
+            }
+        } else {
+            let headers: BTreeMap<_, _> = provider.custom_headers.iter().collect();
+            let lines: Vec<String> =
+                headers.iter().map(|(name, value)| format!("{}: {}", name, value)).collect();
+            env[CUSTOM_HEADERS_ENV] = json!(lines.join("\n"));
+        }
+
+        // 按模型映射生成 ANTHROPIC_DEFAULT_<ROLE>_MODEL，移除映射中已不存在的角色
+        if let Some(env) = env.as_object_mut() {
+            env.retain(|key, _| {
+                !(key.starts_with(MODEL_ENV_PREFIX) && key.ends_with(MODEL_ENV_SUFFIX))
+            });
+            for (role, model) in provider.effective_models() {
+                if !model.is_empty() {
+                    env.insert(model_env_key(&role), json!(model));
+                }
+            }
+        }
+
+        // MCP服务器由数据库管理，没有启用的服务器时移除整个 mcpServers
+        match &self.mcp_servers {
+            Some(servers) if !servers.is_empty() => {
+                let servers: serde_json::Map<_, _> = servers
+                    .iter()
+                    .map(|server| (server.name.clone(), server.to_claude_json()))
+                    .collect();
+                settings[MCP_SERVERS_KEY] = serde_json::Value::Object(servers);
+            }
+            Some(_) => {
+                if let Some(settings) = settings.as_object_mut() {
+                    settings.remove(MCP_SERVERS_KEY);
+                }
+            }
+            None => {}
+        }
+
+        Ok(serde_json::to_string_pretty(&settings)?)
+    }
+
+    /// 生成Codex配置文件（auth.json 和 config.toml）
+    ///
+    /// `provider.token` 必须是解密后的明文
+    pub fn generate_codex_config(
+        &self,
+        provider: &CodexProvider,
+    ) -> ConfigGeneratorResult<(PathBuf, PathBuf)> {
+        let (auth, config) = self.render_codex_config(provider)?;
+        let auth_path = self.write_config_file(GeneratedConfigKind::CodexAuth, &auth)?;
+        let config_path = self.write_config_file(GeneratedConfigKind::CodexConfig, &config)?;
+
+        info!(
+            "生成Codex配置文件: {}, {}",
+            auth_path.display(),
+            config_path.display()
+        );
+        Ok((auth_path, config_path))
+    }
+
+    /// 生成Codex配置文件内容，返回 `(auth.json, config.toml)`
+    fn render_codex_config(
+        &self,
+        provider: &CodexProvider,
+    ) -> ConfigGeneratorResult<(String, String)> {
+        let auth = json!({ "OPENAI_API_KEY": provider.token });
+        let mut config = format!(
+            r#"model_provider = "ai_manager"
+
+[model_providers.ai_manager]
+name = {}
+base_url = {}
+wire_api = "responses"
+"#,
+            serde_json::to_string(&provider.name)?,
+            serde_json::to_string(&provider.url)?,
+        );
+        if !provider.custom_headers.is_empty() {
+            let headers: BTreeMap<_, _> = provider.custom_headers.iter().collect();
+            let mut entries = Vec::with_capacity(headers.len());
+            for (name, value) in headers {
+                entries.push(format!(
+                    "{} = {}",
+                    serde_json::to_string(name)?,
+                    serde_json::to_string(value)?
+                ));
+            }
+            config.push_str(&format!("http_headers = {{ {} }}\n", entries.join(", ")));
+        }
+        for server in self.mcp_servers.iter().flatten() {
+            config.push_str(&server.to_codex_toml()?);
+        }
+
+        Ok((serde_json::to_string_pretty(&auth)?, config))
+    }
+
+    /// 生成目标的配置文件内容，不写入任何文件
+    ///
+    /// Claude配置会合并已有的 `settings.json`，与实际写入的内容一致
+    pub fn render(
+        &self,
+        target: &ConfigTarget<'_>,
+    ) -> ConfigGeneratorResult<Vec<(GeneratedConfigKind, String)>> {
+        Ok(match target {
+            ConfigTarget::Claude(provider) => vec![(
+                GeneratedConfigKind::ClaudeSettings,
+                self.render_claude_settings(provider)?,
+            )],
+            ConfigTarget::Codex(provider) => {
+                let (auth, config) = self.render_codex_config(provider)?;
+                vec![
+                    (GeneratedConfigKind::CodexAuth, auth),
+                    (GeneratedConfigKind::CodexConfig, config),
+                ]
+            }
+        })
+    }
+
+    /// 生成所有目标的配置文件，任一文件写入失败时恢复已写入的文件
+    ///
+    /// 先生成全部内容，全部成功后才逐个原子写入。涉及的文件按固定顺序加锁，
+    /// 返回每个已写入文件的类型和路径
+    pub async fn generate_all_atomic(
+        &self,
+        targets: &[ConfigTarget<'_>],
+    ) -> ConfigGeneratorResult<Vec<(GeneratedConfigKind, PathBuf)>> {
+        let kinds: Vec<_> =
+            targets.iter().flat_map(|target| target.kinds().iter().copied()).collect();
+        let mut guards = Vec::new();
+        for kind in GeneratedConfigKind::ALL.into_iter().filter(|kind| kinds.contains(kind)) {
+            guards.push(self.lock_path(&self.path_for(kind)).await?);
+        }
+
+        self.generate_all_with(targets, |kind, content| {
+            self.write_config_file(kind, content)
+        })
+    }
+
+    /// [`Self::generate_all_atomic`] 的实现，`write` 负责写入单个文件
+    fn generate_all_with<F>(
+        &self,
+        targets: &[ConfigTarget<'_>],
+        mut write: F,
+    ) -> ConfigGeneratorResult<Vec<(GeneratedConfigKind, PathBuf)>>
+    where
+        F: FnMut(GeneratedConfigKind, &str) -> ConfigGeneratorResult<PathBuf>,
+    {
+        // 同一文件出现多次时使用最后一个目标的内容
+        let mut files: Vec<(GeneratedConfigKind, String)> = Vec::new();
+        for target in targets {
+            for (kind, content) in self.render(target)? {
+                files.retain(|(existing, _)| *existing != kind);
+                files.push((kind, content));
+            }
+        }
+
+        let mut previous = Vec::with_capacity(files.len());
+        for (kind, _) in &files {
+            previous.push((*kind, self.read_config_file(*kind)?));
+        }
+
+        let mut written = Vec::with_capacity(files.len());
+        for (kind, content) in &files {
+            match write(*kind, content) {
+                Ok(path) => written.push((*kind, path)),
+                Err(e) => {
+                    warn!(kind = ?kind, error = %e, "写入配置文件失败，恢复已写入的文件");
+                    self.restore_files(&previous[..written.len()]);
+                    return Err(e);
+                }
+            }
+        }
+
+        info!(files = %written.len(), "已生成所有配置文件");
+        Ok(written)
+    }
+
+    /// 将文件恢复为写入前的内容，原本不存在的文件被删除
+    fn restore_files(&self, previous: &[(GeneratedConfigKind, Option<String>)]) {
+        for (kind, content) in previous.iter().rev() {
+            let result = match content {
+                Some(content) => self.write_config_file(*kind, content).map(|_| ()),
+                None => fs::remove_file(self.path_for(*kind)).map_err(Into::into),
+            };
+            if let Err(e) = result {
+                warn!(kind = ?kind, error = %e, "恢复配置文件失败");
+            }
+        }
+    }
+
+    /// 写入配置文件，同一路径的并发写入按顺序执行
+    pub async fn generate_to_disk(
+        &self,
+        kind: GeneratedConfigKind,
+        content: &str,
+    ) -> ConfigGeneratorResult<PathBuf> {
+        let _guard = self.lock_path(&self.path_for(kind)).await?;
+        self.write_config_file(kind, content)
+    }
+
+    /// 生成Claude配置文件，与同一文件的其他生成操作互斥
+    pub async fn generate_claude_settings_to_disk(
+        &self,
+        provider: &ClaudeProvider,
+    ) -> ConfigGeneratorResult<PathBuf> {
+        let _guard = self.lock_path(&self.path_for(GeneratedConfigKind::ClaudeSettings)).await?;
+        self.generate_claude_settings(provider)
+    }
+
+    /// 生成Codex配置文件，与同一文件的其他生成操作互斥
+    pub async fn generate_codex_config_to_disk(
+        &self,
+        provider: &CodexProvider,
+    ) -> ConfigGeneratorResult<(PathBuf, PathBuf)> {
+        // 固定加锁顺序，避免死锁
+        let _auth_guard = self.lock_path(&self.path_for(GeneratedConfigKind::CodexAuth)).await?;
+        let _config_guard =
+            self.lock_path(&self.path_for(GeneratedConfigKind::CodexConfig)).await?;
+        self.generate_codex_config(provider)
+    }
+
+    /// 获取目标路径的写入锁
+    async fn lock_path(&self, path: &Path) -> ConfigGeneratorResult<OwnedMutexGuard<()>> {
+        let key = canonical_lock_key(path)?;
+        let lock = {
+            let mut locks = path_locks().lock().unwrap_or_else(|e| e.into_inner());
+            locks.entry(key).or_default().clone()
+        };
+        Ok(lock.lock_owned().await)
+    }
+
+    /// 读取已生成的配置文件内容，文件不存在时返回 `None`
+    pub fn read_config_file(
+        &self,
+        kind: GeneratedConfigKind,
+    ) -> ConfigGeneratorResult<Option<String>> {
+        let path = self.path_for(kind);
+        match fs::read_to_string(&path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                debug!("配置文件不存在，跳过: {}", path.display());
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 写入配置文件，必要时创建目录
+    ///
+    /// 先写入同目录下的临时文件再重命名，保证读取方不会看到写了一半的文件
+    pub fn write_config_file(
+        &self,
+        kind: GeneratedConfigKind,
+        content: &str,
+    ) -> ConfigGeneratorResult<PathBuf> {
+        let path = self.path_for(kind);
+        let parent = path.parent().unwrap_or_else(|| Path::new("."));
+        fs::create_dir_all(parent)?;
+
+        let mut temp_file = tempfile::NamedTempFile::new_in(parent)?;
+        temp_file.write_all(content.as_bytes())?;
+        temp_file.as_file().sync_all()?;
+        temp_file.persist(&path).map_err(|e| e.error)?;
+
+        Ok(path)
+    }
+}
+
+/// 计算写入锁使用的路径：规范化父目录后拼接文件名，文件不存在时同样适用
+fn canonical_lock_key(path: &Path) -> ConfigGeneratorResult<PathBuf> {
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(parent)?;
+    let parent = fs::canonicalize(parent)?;
+
+    Ok(match path.file_name() {
+        Some(file_name) => parent.join(file_name),
+        None => parent,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn test_claude_provider() -> ClaudeProvider {
+        ClaudeProvider {
+            id: 1,
+            name: "Test Claude".to_string(),
+            url: "https://api.anthropic.com".to_string(),
+            token: "sk-ant-test".to_string(),
+            timeout: Some(30000),
+            auto_update: Some(1),
+            r#type: "public_welfare".to_string(),
+            enabled: 1,
+            opus_model: None,
+            sonnet_model: Some("claude-sonnet".to_string()),
+            haiku_model: None,
+            models: HashMap::from([("reasoning".to_string(), "claude-reasoning".to_string())]),
+            custom_headers: Default::default(),
+            accepted_status_codes: Default::default(),
+            model_auto_update: 0,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_generate_claude_settings_preserves_existing_keys() {
+        let temp_dir = tempdir().unwrap();
+        let generator = ConfigGenerator::with_dirs(
+            temp_dir.path().join(".claude"),
+            temp_dir.path().join(".codex"),
+        );
+
+        generator
+            .write_config_file(GeneratedConfigKind::ClaudeSettings, r#"{"theme":"dark"}"#)
+            .unwrap();
+        generator.generate_claude_settings(&test_claude_provider()).unwrap();
+
+        let content = generator
+            .read_config_file(GeneratedConfigKind::ClaudeSettings)
+            .unwrap()
+            .unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&content).unwrap();
+
+        assert_eq!(settings["theme"], "dark");
+        assert_eq!(settings["env"]["ANTHROPIC_AUTH_TOKEN"], "sk-ant-test");
+        assert_eq!(
+            settings["env"]["ANTHROPIC_DEFAULT_SONNET_MODEL"],
+            "claude-sonnet"
+        );
+        assert!(settings["env"].get("ANTHROPIC_DEFAULT_OPUS_MODEL").is_none());
+        assert_eq!(
+            settings["env"]["ANTHROPIC_DEFAULT_REASONING_MODEL"],
+            "claude-reasoning"
+        );
+    }
+
+    #[test]
+    fn test_read_missing_config_file() {
+        let temp_dir = tempdir().unwrap();
+        let generator = ConfigGenerator::with_dirs(
+            temp_dir.path().join(".claude"),
+            temp_dir.path().join(".codex"),
+        );
+
+        assert!(generator.read_config_file(GeneratedConfigKind::CodexAuth).unwrap().is_none());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_concurrent_generation_is_serialized() {
+        let temp_dir = tempdir().unwrap();
+        let generator = ConfigGenerator::with_dirs(
+            temp_dir.path().join(".claude"),
+            temp_dir.path().join(".codex"),
+        );
+
+        let mut first = test_claude_provider();
+        first.token = "sk-first".to_string();
+        let mut second = test_claude_provider();
+        second.token = "sk-second".to_string();
+        second.url = "https://second.example.com".to_string();
+
+        let tasks: Vec<_> = [first, second]
+            .into_iter()
+            .map(|provider| {
+                let generator = generator.clone();
+                tokio::spawn(async move {
+                    for _ in 0..20 {
+                        generator.generate_claude_settings_to_disk(&provider).await.unwrap();
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        let content = generator
+            .read_config_file(GeneratedConfigKind::ClaudeSettings)
+            .unwrap()
+            .unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&content).unwrap();
+        let env = &settings["env"];
+
+        match env["ANTHROPIC_AUTH_TOKEN"].as_str() {
+            Some("sk-first") => assert_eq!(env["ANTHROPIC_BASE_URL"], "https://api.anthropic.com"),
+            Some("sk-second") => {
+                assert_eq!(env["ANTHROPIC_BASE_URL"], "https://second.example.com")
+            }
+            other => panic!("意外的token: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_all_atomic_restores_files_when_write_fails() {
+        let temp_dir = tempdir().unwrap();
+        let generator = ConfigGenerator::with_dirs(
+            temp_dir.path().join(".claude"),
+            temp_dir.path().join(".codex"),
+        );
+        let prior = r#"{"theme":"dark"}"#;
+        generator.write_config_file(GeneratedConfigKind::ClaudeSettings, prior).unwrap();
+
+        let claude = test_claude_provider();
+        let codex = CodexProvider {
+            id: 1,
+            name: "Test Codex".to_string(),
+            url: "https://api.openai.com".to_string(),
+            token: "sk-codex".to_string(),
+            r#type: "paid".to_string(),
+            enabled: 1,
+            custom_headers: Default::default(),
+            accepted_status_codes: Default::default(),
+            created_at: None,
+            updated_at: None,
+        };
+        let targets = [ConfigTarget::Claude(&claude), ConfigTarget::Codex(&codex)];
+
+        // 第二次写入失败
+        let mut writes = 0;
+        let result = generator.generate_all_with(&targets, |kind, content| {
+            writes += 1;
+            if writes == 2 {
+                return Err(std::io::Error::other("写入失败").into());
+            }
+            generator.write_config_file(kind, content)
+        });
+        assert!(result.is_err());
+        assert_eq!(writes, 2);
+        assert_eq!(
+            generator
+                .read_config_file(GeneratedConfigKind::ClaudeSettings)
+                .unwrap()
+                .as_deref(),
+            Some(prior)
+        );
+        assert!(generator.read_config_file(GeneratedConfigKind::CodexAuth).unwrap().is_none());
+
+        let written = generator.generate_all_atomic(&targets).await.unwrap();
+        assert_eq!(
+            written.iter().map(|(kind, _)| *kind).collect::<Vec<_>>(),
+            GeneratedConfigKind::ALL.to_vec()
+        );
+        let content = generator
+            .read_config_file(GeneratedConfigKind::ClaudeSettings)
+            .unwrap()
+            .unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(settings["theme"], "dark");
+        assert_eq!(settings["env"]["ANTHROPIC_AUTH_TOKEN"], "sk-ant-test");
+    }
+
+    #[tokio::test]
+    async fn test_generate_configs_with_enabled_mcp_servers() {
+        use crate::crypto::CryptoService;
+        use crate::database::{DatabaseConfig, DatabaseManager};
+        use crate::models::CreateMcpServerRequest;
+
+        let temp_dir = tempdir().unwrap();
+        let config = DatabaseConfig {
+            url: format!("sqlite:{}", temp_dir.path().join("test_mcp.db").display()),
+            ..Default::default()
+        };
+        let db_manager = DatabaseManager::new(config).await.unwrap();
+        db_manager.ensure_initialized().await.unwrap();
+        let crypto_service =
+            CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap();
+        let repository = McpServerRepository::new(&db_manager, &crypto_service);
+
+        std::env::set_var("AI_MANAGER_TEST_MCP_ROOT", "/srv/projects");
+        let request =
+            |name: &str, r#type: &str, timeout: i64, command: &str| CreateMcpServerRequest {
+                name: name.to_string(),
+                r#type: Some(r#type.to_string()),
+                timeout: Some(timeout),
+                command: command.to_string(),
+                args: Vec::new(),
+                env: None,
+                secret_env_keys: Vec::new(),
+            };
+        repository
+            .create_mcp_server(&CreateMcpServerRequest {
+                args: vec![
+                    "-y".to_string(),
+                    "${AI_MANAGER_TEST_MCP_ROOT}/docs".to_string(),
+                ],
+                env: Some(HashMap::from([
+                    ("API_KEY".to_string(), "secret-value".to_string()),
+                    (
+                        "LOG_LEVEL".to_string(),
+                        "${AI_MANAGER_TEST_MCP_UNSET}".to_string(),
+                    ),
+                ])),
+                secret_env_keys: vec!["API_KEY".to_string()],
+                ..request("filesystem", "stdio", 30000, "npx")
+            })
+            .await
+            .unwrap();
+        repository
+            .create_mcp_server(&request(
+                "remote",
+                "sse",
+                30000,
+                "https://mcp.example.com/sse",
+            ))
+            .await
+            .unwrap();
+        // 已停用的服务器不写入配置
+        repository
+            .create_mcp_server(&request("disabled", "stdio", 0, "uvx"))
+            .await
+            .unwrap();
+
+        let servers = McpServerConfig::load_enabled(&repository).await.unwrap();
+        let generator = ConfigGenerator::with_dirs(
+            temp_dir.path().join(".claude"),
+            temp_dir.path().join(".codex"),
+        )
+        .with_mcp_servers(servers);
+
+        let content = generator.render_claude_settings(&test_claude_provider()).unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(
+            settings["mcpServers"],
+            json!({
+                "filesystem": {
+                    "type": "stdio",
+                    "command": "npx",
+                    "args": ["-y", "/srv/projects/docs"],
+                    "env": {
+                        "API_KEY": "secret-value",
+                        "LOG_LEVEL": "${AI_MANAGER_TEST_MCP_UNSET}",
+                    },
+                },
+                "remote": {
+                    "type": "sse",
+                    "url": "https://mcp.example.com/sse",
+                },
+            })
+        );
+
+        let codex = CodexProvider {
+            id: 1,
+            name: "Test Codex".to_string(),
+            url: "https://api.openai.com".to_string(),
+            token: "sk-codex".to_string(),
+            r#type: "paid".to_string(),
+            enabled: 1,
+            custom_headers: Default::default(),
+            accepted_status_codes: Default::default(),
+            created_at: None,
+            updated_at: None,
+        };
+        let (_, config) = generator.render_codex_config(&codex).unwrap();
+        let config: toml::Value = toml::from_str(&config).unwrap();
+        let mcp_servers = config["mcp_servers"].as_table().unwrap();
+        assert_eq!(mcp_servers.len(), 2);
+        assert_eq!(mcp_servers["filesystem"]["command"].as_str(), Some("npx"));
+        assert_eq!(
+            mcp_servers["filesystem"]["args"][1].as_str(),
+            Some("/srv/projects/docs")
+        );
+        assert_eq!(
+            mcp_servers["filesystem"]["env"]["API_KEY"].as_str(),
+            Some("secret-value")
+        );
+        assert_eq!(
+            mcp_servers["remote"]["url"].as_str(),
+            Some("https://mcp.example.com/sse")
+        );
+    }
+
+    #[test]
+    fn test_expand_vars_keeps_unknown_and_unterminated_references() {
+        let lookup = |name: &str| (name == "HOME").then(|| "/home/user".to_string());
+        assert_eq!(
+            expand_vars_with("${HOME}/bin:${MISSING}", lookup),
+            "/home/user/bin:${MISSING}"
+        );
+        assert_eq!(expand_vars_with("a${HOME", lookup), "a${HOME");
+    }
+}
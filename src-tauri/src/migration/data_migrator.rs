@@ -4,10 +4,16 @@
 use crate::crypto::CryptoService;
 use crate::database::DatabaseManager;
 use crate::models::*;
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use sqlx::{Row, SqlitePool};
+use std::path::Path;
+use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
+/// 等待Python数据库写锁释放的最长时间
+const PYTHON_DB_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// 数据迁移统计信息
 #[derive(Debug, Default)]
 pub struct MigrationStats {
@@ -33,12 +39,21 @@ pub struct DataMigrator {
     db_manager: DatabaseManager,
     #[allow(dead_code)]
     crypto_service: CryptoService,
+    immutable_source: bool,
 }
 
 impl DataMigrator {
     /// 创建新的数据迁移器实例
     pub fn new(db_manager: DatabaseManager, crypto_service: CryptoService) -> Self {
-        Self { db_manager, crypto_service }
+        Self { db_manager, crypto_service, immutable_source: false }
+    }
+
+    /// 将Python数据库视为不可变文件（如备份副本或只读介质上的文件）
+    ///
+    /// SQLite不再加锁也不检查其他进程的修改，只能用于确定没有其他进程写入的文件
+    pub fn with_immutable_source(mut self, immutable: bool) -> Self {
+        self.immutable_source = immutable;
+        self
     }
 
     /// 从Python数据库迁移数据
@@ -184,11 +199,9 @@ impl DataMigrator {
         Ok(stats)
     }
 
-    /// 连接到Python数据库
+    /// 以只读方式连接到Python数据库
     async fn connect_to_python_db(&self, db_path: &str) -> Result<SqlitePool> {
-        let connection_string = format!("sqlite:{}", db_path);
-        let pool = SqlitePool::connect(&connection_string).await?;
-        Ok(pool)
+        open_python_db(Path::new(db_path), self.immutable_source).await
     }
 
     /// 迁移表结构
@@ -241,7 +254,7 @@ impl DataMigrator {
                     model TEXT DEFAULT 'gpt-4',
                     enabled INTEGER DEFAULT 1,
                     description TEXT,
-                    timeout INTEGER DEFAULT 30,
+                    timeout INTEGER DEFAULT 30000,
                     retry_count INTEGER DEFAULT 3,
                     created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
                     updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
@@ -329,12 +342,16 @@ impl DataMigrator {
                 name: row.get("name"),
                 url: row.get("url"),
                 token: row.get("token"), // 保持加密状态
-                timeout: row.try_get("timeout").ok(),
+                timeout: Some(TimeoutMs::from_legacy(row.try_get("timeout").ok()).as_millis()),
                 auto_update: None,
                 r#type: None,
                 opus_model: None,
                 sonnet_model: None,
                 haiku_model: None,
+                models: None,
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
             };
 
             match self.create_claude_provider(&provider).await {
@@ -372,6 +389,8 @@ impl DataMigrator {
                 url: row.get("url"),
                 token: row.get("token"), // 保持加密状态
                 r#type: row.try_get("type").ok(),
+                custom_headers: None,
+                accepted_status_codes: None,
             };
 
             match self.create_codex_provider(&provider).await {
@@ -452,10 +471,11 @@ impl DataMigrator {
             let server = CreateMcpServerRequest {
                 name: row.get("name"),
                 r#type: row.try_get("type").ok(),
-                timeout: row.try_get("timeout").ok(),
+                timeout: Some(TimeoutMs::from_legacy(row.try_get("timeout").ok()).as_millis()),
                 command: row.get("command"),
                 args,
                 env,
+                secret_env_keys: Vec::new(),
             };
 
             match self.create_mcp_server(&server).await {
@@ -661,6 +681,42 @@ impl DataMigrator {
     }
 }
 
+/// 以只读方式打开Python数据库
+///
+/// 不会创建文件或写入任何内容，Python应用正在运行时也可以安全读取。
+/// 数据库被其他进程的写事务锁定超过等待时间时返回明确的错误
+pub async fn open_python_db(path: &Path, immutable: bool) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .create_if_missing(false)
+        .immutable(immutable)
+        .busy_timeout(PYTHON_DB_BUSY_TIMEOUT);
+
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .with_context(|| format!("无法以只读方式打开Python数据库: {}", path.display()))?;
+
+    // 建立连接时不读取数据，先读取一次表结构以尽早发现写锁
+    match sqlx::query("SELECT COUNT(*) FROM sqlite_master").fetch_one(&pool).await {
+        Ok(_) => {
+            info!(path = %path.display(), immutable = %immutable, "已以只读方式打开Python数据库");
+            Ok(pool)
+        }
+        Err(sqlx::Error::Database(e))
+            if e.message().contains("locked") || e.message().contains("busy") =>
+        {
+            bail!(
+                "Python数据库正被其他进程写入锁定，请关闭Python版AI Manager后重试: {}",
+                path.display()
+            )
+        }
+        Err(e) => Err(e).with_context(|| format!("无法读取Python数据库: {}", path.display())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -692,6 +748,7 @@ mod tests {
             connect_timeout: std::time::Duration::from_secs(5),
             idle_timeout: std::time::Duration::from_secs(60),
             max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = DatabaseManager::new(db_config).await.unwrap();
@@ -702,4 +759,44 @@ mod tests {
         // 测试创建成功
         assert!(!migrator.db_manager.pool().is_closed());
     }
+
+    #[tokio::test]
+    async fn test_open_python_db_is_read_only() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("python.db");
+
+        let writable = SqlitePool::connect_with(
+            SqliteConnectOptions::new().filename(&db_path).create_if_missing(true),
+        )
+        .await
+        .unwrap();
+        sqlx::query("CREATE TABLE claude_providers (id INTEGER PRIMARY KEY, name TEXT)")
+            .execute(&writable)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO claude_providers (name) VALUES ('python')")
+            .execute(&writable)
+            .await
+            .unwrap();
+
+        let pool = open_python_db(&db_path, false).await.unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM claude_providers")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        assert!(
+            sqlx::query("INSERT INTO claude_providers (name) VALUES ('rust')")
+                .execute(&pool)
+                .await
+                .is_err()
+        );
+        assert!(sqlx::query("CREATE TABLE extra (id INTEGER)").execute(&pool).await.is_err());
+
+        // 不存在的文件不会被创建
+        let missing = temp_dir.path().join("missing.db");
+        assert!(open_python_db(&missing, false).await.is_err());
+        assert!(!missing.exists());
+    }
 }
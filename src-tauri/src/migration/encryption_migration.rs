@@ -0,0 +1,242 @@
+//! 加密密钥轮换
+//!
+//! 使用密钥组解密所有加密字段，再用最新密钥重新加密并更新记录的密钥指纹。每个表在一个事务中更新，
+//! 单条记录解密失败时记录到报告中并继续处理其他记录
+
+use crate::crypto::{CryptoError, CryptoService, RotationKeys};
+use crate::database::DatabaseManager;
+use crate::repositories::base_repository::update_statement;
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// 保存单个加密值的列
+struct EncryptedColumn {
+    table: &'static str,
+    column: &'static str,
+    /// 表是否有 `updated_at` 和 `key_fingerprint` 列
+    ///
+    /// 配置历史只记录创建时间，也不记录密钥指纹，重新加密时只更新值
+    tracked: bool,
+}
+
+const ENCRYPTED_COLUMNS: &[EncryptedColumn] = &[
+    EncryptedColumn { table: "claude_providers", column: "token", tracked: true },
+    EncryptedColumn { table: "codex_providers", column: "token", tracked: true },
+    EncryptedColumn { table: "common_configs", column: "value", tracked: true },
+    EncryptedColumn {
+        table: "common_config_history",
+        column: "value",
+        tracked: false,
+    },
+];
+
+/// 环境变量中包含机密值的表
+const MCP_SERVERS_TABLE: &str = "mcp_servers";
+
+/// 单个表的重新加密结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TableReencryption {
+    pub table: String,
+    /// 已使用最新密钥重新加密的记录数
+    pub rotated: u64,
+    /// 明文或已使用最新密钥加密、无需处理的记录数
+    pub skipped: u64,
+}
+
+/// 无法重新加密的记录
+#[derive(Debug, Clone, Serialize)]
+pub struct ReencryptionFailure {
+    pub table: String,
+    pub id: i64,
+    pub error: String,
+}
+
+/// 重新加密结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReencryptionReport {
+    /// 最新密钥的指纹
+    pub key_fingerprint: String,
+    pub tables: Vec<TableReencryption>,
+    pub failures: Vec<ReencryptionFailure>,
+    pub total_rotated: u64,
+}
+
+/// 加密密钥轮换工具
+pub struct EncryptionMigration {
+    db_manager: DatabaseManager,
+    keys: RotationKeys,
+}
+
+impl EncryptionMigration {
+    /// 创建密钥轮换工具
+    pub fn new(db_manager: DatabaseManager, keys: RotationKeys) -> Self {
+        Self { db_manager, keys }
+    }
+
+    /// 使用最新密钥重新加密所有加密字段
+    pub async fn reencrypt_all(&self) -> Result<ReencryptionReport, sqlx::Error> {
+        let mut report = ReencryptionReport {
+            key_fingerprint: self.keys.current().key_fingerprint(),
+            ..Default::default()
+        };
+
+        for column in ENCRYPTED_COLUMNS {
+            let summary = self.reencrypt_column(column, &mut report.failures).await?;
+            report.tables.push(summary);
+        }
+        let summary = self.reencrypt_mcp_env(&mut report.failures).await?;
+        report.tables.push(summary);
+
+        report.total_rotated = report.tables.iter().map(|t| t.rotated).sum();
+        info!(
+            key_fingerprint = %report.key_fingerprint,
+            total_rotated = %report.total_rotated,
+            failures = %report.failures.len(),
+            "加密数据已使用最新密钥重新加密"
+        );
+        Ok(report)
+    }
+
+    /// 重新加密单个值，明文或已使用最新密钥加密的值返回 `None`
+    fn rotate_value(&self, value: &str) -> Result<Option<String>, CryptoError> {
+        if !CryptoService::is_fernet_token(value) || self.keys.is_current(value) {
+            return Ok(None);
+        }
+
+        let plaintext = self.keys.decrypt(value)?;
+        self.keys.current().encrypt(&plaintext).map(Some)
+    }
+
+    async fn reencrypt_column(
+        &self,
+        encrypted: &EncryptedColumn,
+        failures: &mut Vec<ReencryptionFailure>,
+    ) -> Result<TableReencryption, sqlx::Error> {
+        let EncryptedColumn { table, column, tracked } = *encrypted;
+        let mut summary = TableReencryption { table: table.to_string(), ..Default::default() };
+        let mut tx = self.db_manager.pool().begin().await?;
+
+        // 表名和列名均来自 ENCRYPTED_COLUMNS 白名单
+        let fingerprint_column = if tracked { "key_fingerprint" } else { "NULL" };
+        let rows: Vec<(i64, Option<String>, Option<String>)> = sqlx::query_as(&format!(
+            "SELECT id, {}, {} FROM {}",
+            column, fingerprint_column, table
+        ))
+        .fetch_all(&mut *tx)
+        .await?;
+        let assignment = format!("{} = ?", column);
+        let update = if tracked {
+            update_statement(
+                table,
+                &[assignment.as_str(), "key_fingerprint = ?"],
+                "id = ?",
+            )
+        } else {
+            format!("UPDATE {} SET {} WHERE id = ?", table, assignment)
+        };
+
+        for (id, value, stored_fingerprint) in rows {
+            match value.as_deref().map(|value| self.rotate_value(value)) {
+                Some(Ok(Some(rotated))) => {
+                    let mut query = sqlx::query(&update).bind(rotated);
+                    if tracked {
+                        query = query.bind(self.keys.current().storage_fingerprint());
+                    }
+                    query.bind(id).execute(&mut *tx).await?;
+                    summary.rotated += 1;
+                }
+                Some(Ok(None)) | None => summary.skipped += 1,
+                Some(Err(e)) => {
+                    let e =
+                        self.keys.current().check_key_fingerprint(e, stored_fingerprint.as_deref());
+                    record_failure(failures, table, id, e)
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(summary)
+    }
+
+    async fn reencrypt_mcp_env(
+        &self,
+        failures: &mut Vec<ReencryptionFailure>,
+    ) -> Result<TableReencryption, sqlx::Error> {
+        let mut summary =
+            TableReencryption { table: MCP_SERVERS_TABLE.to_string(), ..Default::default() };
+        let mut tx = self.db_manager.pool().begin().await?;
+
+        let rows: Vec<(i64, Option<String>, String, Option<String>)> =
+            sqlx::query_as("SELECT id, env, secret_env_keys, key_fingerprint FROM mcp_servers")
+                .fetch_all(&mut *tx)
+                .await?;
+
+        for (id, env, secret_env_keys, stored_fingerprint) in rows {
+            let env = env
+                .as_deref()
+                .and_then(|env| serde_json::from_str::<HashMap<String, String>>(env).ok());
+            let secret_keys: Vec<String> =
+                serde_json::from_str(&secret_env_keys).unwrap_or_default();
+            let Some(mut env) = env.filter(|_| !secret_keys.is_empty()) else {
+                summary.skipped += 1;
+                continue;
+            };
+
+            match self.rotate_env(&mut env, &secret_keys) {
+                Ok(true) => {
+                    let encoded = serde_json::to_string(&env)
+                        .map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+                    sqlx::query(&update_statement(
+                        MCP_SERVERS_TABLE,
+                        &["env = ?", "key_fingerprint = ?"],
+                        "id = ?",
+                    ))
+                    .bind(encoded)
+                    .bind(self.keys.current().storage_fingerprint())
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await?;
+                    summary.rotated += 1;
+                }
+                Ok(false) => summary.skipped += 1,
+                Err(e) => {
+                    let e =
+                        self.keys.current().check_key_fingerprint(e, stored_fingerprint.as_deref());
+                    record_failure(failures, MCP_SERVERS_TABLE, id, e)
+                }
+            }
+        }
+
+        tx.commit().await?;
+        Ok(summary)
+    }
+
+    /// 重新加密环境变量中的机密值，返回是否有值被修改
+    fn rotate_env(
+        &self,
+        env: &mut HashMap<String, String>,
+        secret_keys: &[String],
+    ) -> Result<bool, CryptoError> {
+        let mut changed = false;
+        for key in secret_keys {
+            if let Some(value) = env.get_mut(key) {
+                if let Some(rotated) = self.rotate_value(value)? {
+                    *value = rotated;
+                    changed = true;
+                }
+            }
+        }
+        Ok(changed)
+    }
+}
+
+fn record_failure(
+    failures: &mut Vec<ReencryptionFailure>,
+    table: &str,
+    id: i64,
+    error: CryptoError,
+) {
+    warn!(table = %table, id = %id, error = %error, "重新加密失败");
+    failures.push(ReencryptionFailure { table: table.to_string(), id, error: error.to_string() });
+}
@@ -0,0 +1,44 @@
+//! 导出目标
+//!
+//! 迁移工具只负责生成导出内容，写到哪里由 [`ExportSink`] 决定。
+//! 默认的 [`FileSink`] 写入本地文件，下游可以提供对象存储、内存、加密或压缩等其他实现
+
+use std::path::{Path, PathBuf};
+
+/// 导出内容的写入目标
+#[allow(async_fn_in_trait)]
+pub trait ExportSink {
+    /// 写入完整的导出内容，每次导出只调用一次
+    async fn write_export(&mut self, bytes: &[u8]) -> std::io::Result<()>;
+}
+
+/// 写入本地文件的默认实现，文件已存在时覆盖
+#[derive(Debug, Clone)]
+pub struct FileSink {
+    path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// 导出文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl ExportSink for FileSink {
+    async fn write_export(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        tokio::fs::write(&self.path, bytes).await
+    }
+}
+
+/// 写入内存，导出内容追加到末尾
+impl ExportSink for Vec<u8> {
+    async fn write_export(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+}
@@ -0,0 +1,342 @@
+// 导出格式版本转换
+//
+// 跨多个版本升级时，旧备份先按版本逐级转换为当前导出格式，再交给 `import_from_json` 导入。
+// 每次升级导出格式时在 `STEPS` 末尾追加一步，只处理相邻两个版本之间的差异
+
+use crate::migration_tool::{parse_schema_version, EXPORT_SCHEMA_VERSION};
+use crate::models::{rename_legacy_fields, TimeoutMs};
+use serde_json::{Map, Value};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+use tracing::{debug, info};
+
+/// 包含记录数组的导出字段
+const RECORD_SECTIONS: &[&str] = &[
+    "claude_providers",
+    "codex_providers",
+    "agent_guides",
+    "mcp_servers",
+    "common_configs",
+];
+
+/// 格式转换错误
+#[derive(Error, Debug)]
+pub enum ConvertError {
+    #[error("无法识别的导出格式版本: {0}")]
+    InvalidVersion(String),
+    #[error("不支持将导出格式从 {from} 降级到 {to}")]
+    Downgrade { from: SchemaVersion, to: SchemaVersion },
+    #[error("导出格式版本 {0} 高于当前支持的 {}", EXPORT_SCHEMA_VERSION)]
+    Unsupported(SchemaVersion),
+    #[error("导出数据格式错误: {0}")]
+    InvalidFormat(String),
+}
+
+/// 导出格式版本（主版本.次版本）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SchemaVersion {
+    pub major: u64,
+    pub minor: u64,
+}
+
+impl SchemaVersion {
+    /// Python版本导出的格式，没有 `schema_version` 字段
+    pub const PYTHON: SchemaVersion = SchemaVersion::new(1, 0);
+
+    pub const fn new(major: u64, minor: u64) -> Self {
+        Self { major, minor }
+    }
+
+    /// 当前导出格式版本，与 [`EXPORT_SCHEMA_VERSION`] 对应
+    pub fn current() -> Self {
+        EXPORT_SCHEMA_VERSION.parse().expect("EXPORT_SCHEMA_VERSION 格式错误")
+    }
+
+    /// 根据导出数据中的 `schema_version`（或元信息中的版本）判断格式版本，
+    /// 两者都没有时视为Python版本导出
+    pub fn detect(value: &Value) -> Result<Self, ConvertError> {
+        let declared = value
+            .get("schema_version")
+            .or_else(|| value.get("metadata").and_then(|m| m.get("schema_version")))
+            .and_then(Value::as_str);
+        match declared {
+            Some(version) => version.parse(),
+            None => Ok(Self::PYTHON),
+        }
+    }
+}
+
+impl FromStr for SchemaVersion {
+    type Err = ConvertError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_schema_version(s)
+            .map(|(major, minor)| Self::new(major, minor))
+            .ok_or_else(|| ConvertError::InvalidVersion(s.to_string()))
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// 单步转换：把 `from` 版本的导出数据升级为 `to` 版本
+struct ConvertStep {
+    from: SchemaVersion,
+    to: SchemaVersion,
+    description: &'static str,
+    apply: fn(&mut Map<String, Value>) -> Result<(), ConvertError>,
+}
+
+/// 按版本顺序排列的转换步骤，相邻步骤首尾相接
+const STEPS: &[ConvertStep] = &[
+    ConvertStep {
+        from: SchemaVersion::new(1, 0),
+        to: SchemaVersion::new(2, 0),
+        description: "Python导出：超时时间换算为毫秒、args转为数组、free改为public_welfare",
+        apply: upgrade_python_export,
+    },
+    ConvertStep {
+        from: SchemaVersion::new(2, 0),
+        to: SchemaVersion::new(2, 1),
+        description: "新增 schema_version 和导出元信息",
+        apply: no_record_changes,
+    },
+    ConvertStep {
+        from: SchemaVersion::new(2, 1),
+        to: SchemaVersion::new(2, 2),
+        description: "MCP服务器新增 secret_env_keys",
+        apply: no_record_changes,
+    },
+];
+
+/// 将导出数据从 `from` 版本逐级转换为 `to` 版本
+///
+/// 只支持升级；`from` 不在转换链的节点上时，从包含它的那一步开始转换。
+/// 版本发生变化时，转换后的 `schema_version`（以及元信息中的版本）设置为 `to`
+pub fn convert_export(
+    value: Value,
+    from: SchemaVersion,
+    to: SchemaVersion,
+) -> Result<Value, ConvertError> {
+    if from > to {
+        return Err(ConvertError::Downgrade { from, to });
+    }
+    let current = SchemaVersion::current();
+    if to > current {
+        return Err(ConvertError::Unsupported(to));
+    }
+
+    let Value::Object(mut export) = value else {
+        return Err(ConvertError::InvalidFormat(
+            "导出数据不是JSON对象".to_string(),
+        ));
+    };
+
+    for step in STEPS.iter().filter(|step| step.to > from && step.to <= to) {
+        debug!(from = %step.from, to = %step.to, step = step.description, "转换导出格式");
+        (step.apply)(&mut export)?;
+    }
+
+    if from != to {
+        set_schema_version(&mut export, to);
+    }
+    info!(from = %from, to = %to, "导出格式转换完成");
+
+    Ok(Value::Object(export))
+}
+
+/// 将导出数据转换为当前格式，源版本从数据中自动识别
+pub fn convert_to_current(value: Value) -> Result<Value, ConvertError> {
+    let from = SchemaVersion::detect(&value)?;
+    convert_export(value, from, SchemaVersion::current())
+}
+
+/// 设置顶层和元信息中的格式版本
+fn set_schema_version(export: &mut Map<String, Value>, version: SchemaVersion) {
+    let version = Value::String(version.to_string());
+    if let Some(metadata) = export.get_mut("metadata").and_then(Value::as_object_mut) {
+        metadata.insert("schema_version".to_string(), version.clone());
+    }
+    export.insert("schema_version".to_string(), version);
+}
+
+/// 遍历某类记录
+fn records_mut<'a>(
+    export: &'a mut Map<String, Value>,
+    section: &str,
+) -> impl Iterator<Item = &'a mut Map<String, Value>> {
+    export
+        .get_mut(section)
+        .and_then(Value::as_array_mut)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_object_mut)
+}
+
+fn no_record_changes(_: &mut Map<String, Value>) -> Result<(), ConvertError> {
+    Ok(())
+}
+
+/// Python导出 -> 2.0
+fn upgrade_python_export(export: &mut Map<String, Value>) -> Result<(), ConvertError> {
+    for section in RECORD_SECTIONS {
+        for record in records_mut(export, section) {
+            rename_legacy_fields(section, record);
+        }
+    }
+
+    for section in ["claude_providers", "codex_providers"] {
+        for record in records_mut(export, section) {
+            if record.get("type").and_then(Value::as_str) == Some("free") {
+                record.insert("type".to_string(), Value::from("public_welfare"));
+            }
+        }
+    }
+
+    convert_legacy_timeouts(export);
+
+    for record in records_mut(export, "mcp_servers") {
+        let Some(Value::String(args)) = record.get("args") else {
+            continue;
+        };
+        let args = parse_legacy_args(args).map_err(|e| {
+            let name = record.get("name").and_then(Value::as_str).unwrap_or_default();
+            ConvertError::InvalidFormat(format!("MCP服务器 {} 的 args 无法解析: {}", name, e))
+        })?;
+        record.insert("args".to_string(), Value::from(args));
+    }
+
+    Ok(())
+}
+
+/// 将Python版本按秒保存的超时时间换算为毫秒
+///
+/// 只用于Python版本的数据；本工具导出的超时时间已是毫秒，超出范围的值在导入时报告
+pub(crate) fn convert_legacy_timeouts(export: &mut Map<String, Value>) {
+    for section in ["claude_providers", "mcp_servers"] {
+        for record in records_mut(export, section) {
+            if let Some(timeout) = record.get("timeout").and_then(Value::as_i64) {
+                record.insert(
+                    "timeout".to_string(),
+                    Value::from(TimeoutMs::legacy_seconds_to_millis(timeout)),
+                );
+            }
+        }
+    }
+}
+
+/// 解析Python版本存储为字符串的命令参数
+///
+/// 以 `[` 开头时按JSON数组解析，否则按shell规则分词，引号内的空白不拆分，引号未闭合时返回错误
+fn parse_legacy_args(args: &str) -> Result<Vec<String>, String> {
+    let trimmed = args.trim();
+    if trimmed.starts_with('[') {
+        serde_json::from_str(trimmed).map_err(|e| e.to_string())
+    } else {
+        shlex::split(trimmed).ok_or_else(|| "引号未闭合".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_steps_form_a_chain_to_current() {
+        assert_eq!(STEPS.first().unwrap().from, SchemaVersion::PYTHON);
+        assert_eq!(STEPS.last().unwrap().to, SchemaVersion::current());
+        for pair in STEPS.windows(2) {
+            assert_eq!(pair[0].to, pair[1].from);
+        }
+    }
+
+    #[test]
+    fn test_detect_schema_version() {
+        assert_eq!(
+            SchemaVersion::detect(&json!({})).unwrap(),
+            SchemaVersion::PYTHON
+        );
+        assert_eq!(
+            SchemaVersion::detect(&json!({ "schema_version": "2.1" })).unwrap(),
+            SchemaVersion::new(2, 1)
+        );
+        assert_eq!(
+            SchemaVersion::detect(&json!({ "metadata": { "schema_version": "2.2.1" } })).unwrap(),
+            SchemaVersion::new(2, 2)
+        );
+        assert!(matches!(
+            SchemaVersion::detect(&json!({ "schema_version": "abc" })),
+            Err(ConvertError::InvalidVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_legacy_args() {
+        assert_eq!(
+            parse_legacy_args(r#"["-y", "a b"]"#).unwrap(),
+            vec!["-y", "a b"]
+        );
+        assert_eq!(
+            parse_legacy_args(" -y  server ").unwrap(),
+            vec!["-y", "server"]
+        );
+        assert_eq!(
+            parse_legacy_args(r#"server --root "/My Docs" --name 'a b'"#).unwrap(),
+            vec!["server", "--root", "/My Docs", "--name", "a b"]
+        );
+        assert!(parse_legacy_args(r#"server --root "/My Docs"#).is_err());
+        assert!(parse_legacy_args("").unwrap().is_empty());
+        assert!(parse_legacy_args("[broken").is_err());
+    }
+
+    #[test]
+    fn test_rejects_downgrade_and_future_versions() {
+        let current = SchemaVersion::current();
+        assert!(matches!(
+            convert_export(json!({}), current, SchemaVersion::PYTHON),
+            Err(ConvertError::Downgrade { .. })
+        ));
+        let future = SchemaVersion::new(current.major + 1, 0);
+        assert!(matches!(
+            convert_export(json!({}), current, future),
+            Err(ConvertError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_partial_conversion_stops_at_target() {
+        let export = json!({
+            "mcp_servers": [{ "name": "fs", "type": "stdio", "args": "-y fs" }]
+        });
+        let converted =
+            convert_export(export, SchemaVersion::PYTHON, SchemaVersion::new(2, 0)).unwrap();
+        assert_eq!(converted["schema_version"], "2.0");
+        assert_eq!(converted["mcp_servers"][0]["type"], "stdio");
+        assert_eq!(converted["mcp_servers"][0]["args"], json!(["-y", "fs"]));
+    }
+
+    #[test]
+    fn test_python_timeouts_converted_to_millis() {
+        let export = json!({
+            "claude_providers": [{ "name": "a", "timeout": 30 }, { "name": "b", "timeout": 45000 }],
+            "mcp_servers": [{ "name": "fs", "timeout": null }]
+        });
+        let converted = convert_to_current(export).unwrap();
+        assert_eq!(converted["claude_providers"][0]["timeout"], 30_000);
+        assert_eq!(converted["claude_providers"][1]["timeout"], 45_000);
+        assert!(converted["mcp_servers"][0]["timeout"].is_null());
+
+        // 已是毫秒的导出不再换算
+        let export = json!({
+            "schema_version": "2.1",
+            "claude_providers": [{ "name": "a", "timeout": 500 }]
+        });
+        let converted = convert_to_current(export).unwrap();
+        assert_eq!(converted["claude_providers"][0]["timeout"], 500);
+    }
+}
@@ -1,10 +1,20 @@
 // 数据迁移模块
 // 提供从原Python项目到Rust项目的数据迁移功能
 
+pub mod config_generator;
 pub mod data_migrator;
-// pub mod config_generator;
-// pub mod encryption_migration;
+pub mod encryption_migration;
+pub mod export_sink;
+pub mod format_converter;
+pub mod preflight;
+pub mod schema_diff;
 
+pub use config_generator::{
+    ConfigGenerator, ConfigGeneratorError, ConfigTarget, GeneratedConfigKind,
+};
 pub use data_migrator::DataMigrator;
-// pub use config_generator::ConfigGenerator;
-// pub use encryption_migration::EncryptionMigration;
+pub use encryption_migration::{EncryptionMigration, ReencryptionReport};
+pub use export_sink::{ExportSink, FileSink};
+pub use format_converter::{convert_export, convert_to_current, ConvertError, SchemaVersion};
+pub use preflight::{DiskSpaceChecker, FsDiskSpaceChecker, PreflightCheck, PreflightReport};
+pub use schema_diff::{schema_diff, SchemaDiff};
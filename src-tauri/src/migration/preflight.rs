@@ -0,0 +1,201 @@
+//! 导入前检查
+//!
+//! 在写入任何数据之前估算导入所需的磁盘空间，检查数据库所在文件系统的剩余空间，
+//! 以及数据库和备份目录是否可写，避免大批量导入进行到一半才因磁盘已满失败
+
+use serde::Serialize;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// 估算所需空间时相对导出文件大小的倍数（数据、索引以及WAL日志）
+pub const REQUIRED_SPACE_FACTOR: u64 = 3;
+/// 估算所需空间时额外预留的字节数
+pub const REQUIRED_SPACE_MARGIN_BYTES: u64 = 10 * 1024 * 1024;
+
+/// 磁盘剩余空间查询，测试时可以替换为固定值
+pub trait DiskSpaceChecker: Send + Sync {
+    /// 返回 `path` 所在文件系统的可用字节数
+    fn available_space(&self, path: &Path) -> std::io::Result<u64>;
+}
+
+/// 查询真实文件系统的默认实现
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FsDiskSpaceChecker;
+
+impl DiskSpaceChecker for FsDiskSpaceChecker {
+    fn available_space(&self, path: &Path) -> std::io::Result<u64> {
+        fs2::available_space(path)
+    }
+}
+
+/// 单项检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightCheck {
+    pub name: String,
+    pub passed: bool,
+    /// 检查结果说明，失败时为失败原因
+    pub detail: String,
+}
+
+/// 导入前检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct PreflightReport {
+    /// 所有检查是否通过
+    pub passed: bool,
+    /// 估算的导入所需空间
+    pub required_bytes: u64,
+    /// 数据库所在文件系统的可用空间，内存数据库或查询失败时为 `None`
+    pub available_bytes: Option<u64>,
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    /// 未通过的检查
+    pub fn failures(&self) -> impl Iterator<Item = &PreflightCheck> {
+        self.checks.iter().filter(|check| !check.passed)
+    }
+
+    /// 失败原因汇总，用于错误信息
+    pub fn failure_summary(&self) -> String {
+        self.failures()
+            .map(|check| format!("{}: {}", check.name, check.detail))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    fn push(&mut self, name: &str, passed: bool, detail: String) {
+        if !passed {
+            warn!(check = %name, detail = %detail, "导入前检查未通过");
+        }
+        self.passed &= passed;
+        self.checks.push(PreflightCheck { name: name.to_string(), passed, detail });
+    }
+}
+
+/// 估算导入 `export_size` 字节的导出文件需要的磁盘空间
+pub fn estimate_required_space(export_size: u64) -> u64 {
+    export_size
+        .saturating_mul(REQUIRED_SPACE_FACTOR)
+        .saturating_add(REQUIRED_SPACE_MARGIN_BYTES)
+}
+
+/// 执行导入前检查
+///
+/// `db_path` 为 `None` 表示内存数据库，跳过磁盘相关检查
+pub fn run_preflight(
+    db_path: Option<&Path>,
+    backup_dir: Option<&Path>,
+    export_size: u64,
+    checker: &dyn DiskSpaceChecker,
+) -> PreflightReport {
+    let required_bytes = estimate_required_space(export_size);
+    let mut report = PreflightReport {
+        passed: true,
+        required_bytes,
+        available_bytes: None,
+        checks: Vec::new(),
+    };
+
+    match db_path {
+        Some(db_path) => {
+            let db_dir = parent_dir(db_path);
+            match checker.available_space(db_dir) {
+                Ok(available) => {
+                    report.available_bytes = Some(available);
+                    report.push(
+                        "disk_space",
+                        available >= required_bytes,
+                        format!("需要 {} 字节，可用 {} 字节", required_bytes, available),
+                    );
+                }
+                Err(e) => report.push("disk_space", false, format!("无法获取可用空间: {}", e)),
+            }
+
+            let writable = check_writable(db_dir).and_then(|()| match std::fs::metadata(db_path) {
+                Ok(metadata) if metadata.permissions().readonly() => {
+                    Err("数据库文件为只读".to_string())
+                }
+                _ => Ok(()),
+            });
+            push_writable(&mut report, "database_writable", db_dir, writable);
+        }
+        None => report.push("disk_space", true, "内存数据库，跳过磁盘检查".to_string()),
+    }
+
+    if let Some(backup_dir) = backup_dir {
+        let writable = std::fs::create_dir_all(backup_dir)
+            .map_err(|e| format!("无法创建目录: {}", e))
+            .and_then(|()| check_writable(backup_dir));
+        push_writable(&mut report, "backup_writable", backup_dir, writable);
+    }
+
+    info!(
+        passed = %report.passed,
+        required_bytes = %report.required_bytes,
+        available_bytes = ?report.available_bytes,
+        "导入前检查完成"
+    );
+    report
+}
+
+fn push_writable(report: &mut PreflightReport, name: &str, dir: &Path, result: Result<(), String>) {
+    match result {
+        Ok(()) => report.push(name, true, format!("{} 可写", dir.display())),
+        Err(reason) => report.push(name, false, format!("{} 不可写: {}", dir.display(), reason)),
+    }
+}
+
+/// 通过在目录中创建临时文件检查写权限
+fn check_writable(dir: &Path) -> Result<(), String> {
+    tempfile::NamedTempFile::new_in(dir).map(|_| ()).map_err(|e| e.to_string())
+}
+
+fn parent_dir(path: &Path) -> &Path {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    struct FixedSpace(u64);
+
+    impl DiskSpaceChecker for FixedSpace {
+        fn available_space(&self, _path: &Path) -> std::io::Result<u64> {
+            Ok(self.0)
+        }
+    }
+
+    #[test]
+    fn test_preflight_reports_insufficient_space() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("preflight.db");
+        let backup_dir = temp_dir.path().join("backups");
+        let export_size = 50 * 1024 * 1024;
+
+        let report = run_preflight(
+            Some(&db_path),
+            Some(&backup_dir),
+            export_size,
+            &FixedSpace(1024),
+        );
+        assert!(!report.passed);
+        assert_eq!(report.required_bytes, estimate_required_space(export_size));
+        assert_eq!(report.available_bytes, Some(1024));
+        let failures: Vec<&str> = report.failures().map(|check| check.name.as_str()).collect();
+        assert_eq!(failures, vec!["disk_space"]);
+        assert!(backup_dir.is_dir());
+
+        let report = run_preflight(
+            Some(&db_path),
+            Some(&backup_dir),
+            export_size,
+            &FixedSpace(u64::MAX),
+        );
+        assert!(report.passed, "{}", report.failure_summary());
+    }
+}
@@ -0,0 +1,234 @@
+// 数据库结构对比
+//
+// 按表比较两个SQLite数据库的列定义（类型、非空、默认值、主键），
+// 供测试和线上诊断（与迁移脚本生成的期望结构对比）共用
+
+use serde::Serialize;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{Row, SqlitePool};
+use std::collections::BTreeMap;
+
+/// 对比时忽略的内部表
+const IGNORED_TABLES: &[&str] = &["_sqlx_migrations"];
+
+/// 列定义
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// 声明类型（统一为大写）
+    pub data_type: String,
+    pub not_null: bool,
+    pub default_value: Option<String>,
+    pub primary_key: bool,
+}
+
+/// 同名列在两边定义不一致
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ColumnChange {
+    pub name: String,
+    /// 基准数据库中的定义
+    pub expected: ColumnInfo,
+    /// 被比较数据库中的定义
+    pub actual: ColumnInfo,
+}
+
+/// 单个表的差异
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TableDiff {
+    pub table: String,
+    /// 仅存在于被比较数据库中的列
+    pub added_columns: Vec<ColumnInfo>,
+    /// 仅存在于基准数据库中的列
+    pub removed_columns: Vec<ColumnInfo>,
+    pub changed_columns: Vec<ColumnChange>,
+}
+
+impl TableDiff {
+    /// 是否没有任何差异
+    pub fn is_empty(&self) -> bool {
+        self.added_columns.is_empty()
+            && self.removed_columns.is_empty()
+            && self.changed_columns.is_empty()
+    }
+}
+
+/// 两个数据库之间的结构差异
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SchemaDiff {
+    /// 仅存在于被比较数据库中的表
+    pub added_tables: Vec<String>,
+    /// 仅存在于基准数据库中的表
+    pub removed_tables: Vec<String>,
+    /// 两边都存在但列定义不同的表
+    pub changed_tables: Vec<TableDiff>,
+}
+
+impl SchemaDiff {
+    /// 两个数据库结构是否一致
+    pub fn is_empty(&self) -> bool {
+        self.added_tables.is_empty()
+            && self.removed_tables.is_empty()
+            && self.changed_tables.is_empty()
+    }
+
+    /// 获取指定表的列差异
+    pub fn table(&self, table: &str) -> Option<&TableDiff> {
+        self.changed_tables.iter().find(|diff| diff.table == table)
+    }
+}
+
+/// 以 `expected` 为基准，比较 `actual` 的表结构
+pub async fn schema_diff(
+    expected: &SqlitePool,
+    actual: &SqlitePool,
+) -> Result<SchemaDiff, sqlx::Error> {
+    let expected_tables = read_schema(expected).await?;
+    let mut actual_tables = read_schema(actual).await?;
+    let mut diff = SchemaDiff::default();
+
+    for (table, expected_columns) in expected_tables {
+        match actual_tables.remove(&table) {
+            Some(actual_columns) => {
+                let table_diff = diff_columns(&table, expected_columns, actual_columns);
+                if !table_diff.is_empty() {
+                    diff.changed_tables.push(table_diff);
+                }
+            }
+            None => diff.removed_tables.push(table),
+        }
+    }
+    diff.added_tables = actual_tables.into_keys().collect();
+
+    Ok(diff)
+}
+
+/// 创建只包含迁移脚本生成的期望结构的内存数据库
+pub async fn expected_schema_pool() -> Result<SqlitePool, sqlx::Error> {
+    // 内存数据库随连接关闭而消失，保持唯一的连接常驻
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await?;
+    sqlx::migrate!("./migrations").run(&pool).await?;
+    Ok(pool)
+}
+
+/// 读取所有用户表的列定义，按表名和列名索引
+pub(crate) async fn read_schema(
+    pool: &SqlitePool,
+) -> Result<BTreeMap<String, BTreeMap<String, ColumnInfo>>, sqlx::Error> {
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut schema = BTreeMap::new();
+    for table in tables.into_iter().filter(|t| !IGNORED_TABLES.contains(&t.as_str())) {
+        let rows = sqlx::query(
+            r#"SELECT name, type, "notnull", dflt_value, pk FROM pragma_table_info(?)"#,
+        )
+        .bind(&table)
+        .fetch_all(pool)
+        .await?;
+
+        let columns = rows
+            .iter()
+            .map(|row| {
+                let column = ColumnInfo {
+                    name: row.get("name"),
+                    data_type: row.get::<String, _>("type").to_uppercase(),
+                    not_null: row.get::<i64, _>("notnull") != 0,
+                    default_value: row.get("dflt_value"),
+                    primary_key: row.get::<i64, _>("pk") != 0,
+                };
+                (column.name.clone(), column)
+            })
+            .collect();
+        schema.insert(table, columns);
+    }
+
+    Ok(schema)
+}
+
+fn diff_columns(
+    table: &str,
+    expected: BTreeMap<String, ColumnInfo>,
+    mut actual: BTreeMap<String, ColumnInfo>,
+) -> TableDiff {
+    let mut diff = TableDiff { table: table.to_string(), ..Default::default() };
+
+    for (name, expected_column) in expected {
+        match actual.remove(&name) {
+            Some(actual_column) if actual_column != expected_column => {
+                diff.changed_columns.push(ColumnChange {
+                    name,
+                    expected: expected_column,
+                    actual: actual_column,
+                });
+            }
+            Some(_) => {}
+            None => diff.removed_columns.push(expected_column),
+        }
+    }
+    diff.added_columns = actual.into_values().collect();
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn memory_pool(statements: &[&str]) -> SqlitePool {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        for statement in statements {
+            sqlx::query(statement).execute(&pool).await.unwrap();
+        }
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_schema_diff_missing_column_and_changed_type() {
+        let expected = memory_pool(&[
+            "CREATE TABLE providers (id INTEGER PRIMARY KEY, name TEXT NOT NULL, timeout INTEGER DEFAULT 30)",
+            "CREATE TABLE guides (id INTEGER PRIMARY KEY)",
+        ])
+        .await;
+        let actual = memory_pool(&[
+            "CREATE TABLE providers (id INTEGER PRIMARY KEY, name TEXT NOT NULL, timeout TEXT DEFAULT 30, extra TEXT)",
+            "CREATE TABLE other (id INTEGER PRIMARY KEY)",
+        ])
+        .await;
+
+        let diff = schema_diff(&expected, &actual).await.unwrap();
+        assert_eq!(diff.removed_tables, vec!["guides".to_string()]);
+        assert_eq!(diff.added_tables, vec!["other".to_string()]);
+
+        let providers = diff.table("providers").unwrap();
+        assert_eq!(providers.added_columns.len(), 1);
+        assert_eq!(providers.added_columns[0].name, "extra");
+        assert_eq!(providers.changed_columns.len(), 1);
+        assert_eq!(providers.changed_columns[0].expected.data_type, "INTEGER");
+        assert_eq!(providers.changed_columns[0].actual.data_type, "TEXT");
+
+        // 反向比较时新增的列变为缺失的列
+        let reverse = schema_diff(&actual, &expected).await.unwrap();
+        let providers = reverse.table("providers").unwrap();
+        assert_eq!(providers.removed_columns[0].name, "extra");
+    }
+
+    #[tokio::test]
+    async fn test_migrated_database_matches_expected_schema() {
+        let expected = expected_schema_pool().await.unwrap();
+        let actual = expected_schema_pool().await.unwrap();
+
+        assert!(schema_diff(&expected, &actual).await.unwrap().is_empty());
+    }
+}
@@ -3,15 +3,43 @@
 //! 这个模块提供从Python版本AI Manager迁移数据到Rust版本的工具
 
 use crate::crypto::{CryptoError, CryptoService};
-use crate::database::{DatabaseManager, QueryBuilder};
+use crate::database::{DatabaseError, DatabaseManager, QueryBuilder, TableHandle};
+use crate::migration::config_generator::{
+    ConfigGenerator, ConfigGeneratorError, GeneratedConfigKind,
+};
+use crate::migration::export_sink::{ExportSink, FileSink};
+use crate::migration::format_converter::{convert_legacy_timeouts, SchemaVersion};
+use crate::migration::preflight::{self, DiskSpaceChecker, FsDiskSpaceChecker, PreflightReport};
+use crate::models::{
+    rename_legacy_fields, CreateMigrationRunRequest, MigrationRun, TimeoutMs, DEFAULT_TIMEOUT_MS,
+    MODEL_ROLE_HAIKU, MODEL_ROLE_OPUS, MODEL_ROLE_SONNET,
+};
+use crate::repositories::base_repository::{EncryptedField, RepositoryError};
+use crate::repositories::mcp_server_repository::{decrypt_secret_env, encrypt_secret_env};
+use crate::repositories::MigrationRunRepository;
+use crate::utils::redaction::secret_matcher;
 use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
 use sqlx::Row;
 use std::clone::Clone;
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use thiserror::Error;
 use tracing::{debug, error, info, warn};
 
+/// 表示标准输出的导出路径
+pub const STDOUT_OUTPUT: &str = "-";
+
+/// 当前导出格式版本（主版本.次版本）
+///
+/// 次版本升级只会新增字段，旧版本导入时忽略未知字段即可；主版本升级表示格式不兼容
+pub const EXPORT_SCHEMA_VERSION: &str = "2.2";
+
+/// 导入时无法识别的字段，保留下来用于生成警告
+pub type ExtraFields = serde_json::Map<String, serde_json::Value>;
+
 /// 迁移错误类型
 #[derive(Error, Debug)]
 pub enum MigrationError {
@@ -27,6 +55,10 @@ pub enum MigrationError {
     Validation(String),
     #[error("版本不兼容: {0}")]
     VersionMismatch(String),
+    #[error("配置文件错误: {0}")]
+    ConfigFile(#[from] ConfigGeneratorError),
+    #[error("导入前检查未通过: {0}")]
+    PreflightFailed(String),
 }
 
 /// Python导出的数据格式
@@ -38,6 +70,94 @@ pub struct PythonExportData {
     pub agent_guides: Vec<PythonAgentGuide>,
     pub mcp_servers: Vec<PythonMcpServer>,
     pub common_configs: Vec<PythonCommonConfig>,
+    /// 已生成的配置文件（内容加密存储）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generated_configs: Vec<GeneratedConfigFile>,
+    /// 导出格式版本，Python版本导出的数据没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<String>,
+    /// 导出元信息，较早版本导出的数据没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<ExportMetadata>,
+    /// 未知字段（来自更新版本的导出）
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: ExtraFields,
+}
+
+impl PythonExportData {
+    /// 解析任意历史版本的导出数据
+    ///
+    /// 各类记录中登记在 [`FIELD_ALIASES`](crate::models::FIELD_ALIASES) 中的旧字段名先改为当前名称，新旧字段同时存在时以当前字段为准。
+    /// Python版本导出中按秒保存的超时时间换算为毫秒
+    pub fn from_versioned_value(mut value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        if matches!(SchemaVersion::detect(&value), Ok(SchemaVersion::PYTHON)) {
+            if let Some(export) = value.as_object_mut() {
+                convert_legacy_timeouts(export);
+            }
+        }
+
+        for kind in [
+            EntityKind::ClaudeProviders,
+            EntityKind::CodexProviders,
+            EntityKind::AgentGuides,
+            EntityKind::McpServers,
+            EntityKind::CommonConfigs,
+        ] {
+            let table = kind.table_name();
+            let Some(records) = value.get_mut(table).and_then(|v| v.as_array_mut()) else {
+                continue;
+            };
+            for record in records.iter_mut().filter_map(|r| r.as_object_mut()) {
+                let renamed = rename_legacy_fields(table, record);
+                if !renamed.is_empty() {
+                    debug!(table = %table, fields = ?renamed, "导入数据使用了旧字段名");
+                }
+            }
+        }
+
+        serde_json::from_value(value)
+    }
+
+    /// 从JSON字符串解析任意历史版本的导出数据
+    pub fn from_versioned_str(content: &str) -> Result<Self, serde_json::Error> {
+        Self::from_versioned_value(serde_json::from_str(content)?)
+    }
+}
+
+/// 导出元信息
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportMetadata {
+    /// 导出数据的应用版本
+    pub app_version: String,
+    /// 导出格式版本，与 [`EXPORT_SCHEMA_VERSION`] 对应
+    pub schema_version: String,
+    /// 导出时间（RFC3339）
+    pub exported_at: String,
+    /// 导出时的操作系统
+    pub source_os: String,
+    /// 各类数据的记录数，键为表名
+    #[serde(default)]
+    pub record_counts: BTreeMap<String, usize>,
+}
+
+impl ExportMetadata {
+    /// 根据导出数据生成当前应用的元信息
+    fn for_export(data: &PythonExportData) -> Self {
+        Self {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            schema_version: EXPORT_SCHEMA_VERSION.to_string(),
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            source_os: std::env::consts::OS.to_string(),
+            record_counts: record_counts(data),
+        }
+    }
+}
+
+/// 导出包中的配置文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeneratedConfigFile {
+    pub kind: GeneratedConfigKind,
+    pub encrypted_content: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,8 +173,19 @@ pub struct PythonClaudeProvider {
     pub opus_model: Option<String>,
     pub sonnet_model: Option<String>,
     pub haiku_model: Option<String>,
+    /// 自定义请求头，较早版本导出的数据没有该字段
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom_headers: HashMap<String, String>,
+    /// 连接测试视为可用的状态码，较早版本导出的数据没有该字段
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accepted_status_codes: Vec<u16>,
+    /// 是否定期从上游模型列表更新模型，较早版本导出的数据没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_auto_update: Option<i64>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: ExtraFields,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,8 +196,16 @@ pub struct PythonCodexProvider {
     pub token: String,
     pub r#type: Option<String>,
     pub enabled: Option<i64>,
+    /// 自定义请求头，较早版本导出的数据没有该字段
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom_headers: HashMap<String, String>,
+    /// 连接测试视为可用的状态码，较早版本导出的数据没有该字段
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accepted_status_codes: Vec<u16>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: ExtraFields,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,6 +216,8 @@ pub struct PythonAgentGuide {
     pub text: String,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: ExtraFields,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -88,8 +229,13 @@ pub struct PythonMcpServer {
     pub command: String,
     pub args: Vec<String>,
     pub env: Option<HashMap<String, String>>,
+    /// 值需要加密存储的环境变量名，导出文件中这些值为明文
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secret_env_keys: Vec<String>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: ExtraFields,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,6 +248,8 @@ pub struct PythonCommonConfig {
     pub is_active: Option<i64>,
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: ExtraFields,
 }
 
 /// 迁移报告
@@ -113,15 +261,573 @@ pub struct MigrationReport {
     pub agent_guides: usize,
     pub mcp_servers: usize,
     pub common_configs: usize,
+    #[serde(default)]
+    pub generated_configs: usize,
+    /// 新建的记录数
+    #[serde(default)]
+    pub created: usize,
+    /// 合并模式下原地更新的记录数
+    #[serde(default)]
+    pub updated: usize,
+    /// 合并模式下无变化的记录数
+    #[serde(default)]
+    pub unchanged: usize,
     pub errors: Vec<String>,
     pub warnings: Vec<String>,
     pub duration_secs: u64,
 }
 
+impl MigrationReport {
+    /// 统计单条记录的导入结果，返回计入迁移数量的条数
+    fn record_outcome(&mut self, outcome: ImportOutcome) -> usize {
+        match outcome {
+            ImportOutcome::Created => {
+                self.created += 1;
+                1
+            }
+            ImportOutcome::Updated => {
+                self.updated += 1;
+                1
+            }
+            ImportOutcome::Unchanged => {
+                self.unchanged += 1;
+                0
+            }
+        }
+    }
+}
+
+/// 导入选项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportOptions {
+    /// 合并导入：按自然键（供应商/指导/服务器的 `name`，配置的 `key`）更新已有记录，
+    /// 不清空现有数据，导入中不存在的记录保持不变
+    #[serde(default)]
+    pub merge: bool,
+    /// 严格模式：导入文件包含无效的UTF-8字节时直接失败；
+    /// 默认将无效字节替换为 U+FFFD 并记录警告
+    #[serde(default)]
+    pub strict_utf8: bool,
+    /// 导入前检查磁盘空间和写权限，未通过时不写入任何数据
+    #[serde(default)]
+    pub preflight: bool,
+}
+
+/// 可导出的数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    ClaudeProviders,
+    CodexProviders,
+    AgentGuides,
+    McpServers,
+    CommonConfigs,
+}
+
+impl EntityKind {
+    /// 对应的数据表
+    pub fn table_name(self) -> &'static str {
+        match self {
+            Self::ClaudeProviders => "claude_providers",
+            Self::CodexProviders => "codex_providers",
+            Self::AgentGuides => "agent_guides",
+            Self::McpServers => "mcp_servers",
+            Self::CommonConfigs => "common_configs",
+        }
+    }
+
+    /// 表示启用状态的列，没有启用状态的数据类型返回 `None`
+    fn status_column(self) -> Option<&'static str> {
+        match self {
+            Self::ClaudeProviders | Self::CodexProviders => Some("enabled"),
+            Self::CommonConfigs => Some("is_active"),
+            Self::AgentGuides | Self::McpServers => None,
+        }
+    }
+}
+
+/// 导出过滤条件，所有条件为空时导出全部数据
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportFilter {
+    /// 只导出带有其中任一标签的记录；数据表没有标签列时忽略该条件
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 只导出指定启用状态的记录，不影响没有启用状态的数据类型
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// 只导出指定的数据类型
+    #[serde(default)]
+    pub entities: Vec<EntityKind>,
+}
+
+impl ExportFilter {
+    /// 是否导出该数据类型
+    pub fn includes(&self, kind: EntityKind) -> bool {
+        self.entities.is_empty() || self.entities.contains(&kind)
+    }
+
+    /// 判断单行记录是否满足启用状态和标签条件
+    fn matches_row(&self, kind: EntityKind, row: &SqliteRow, filter_tags: bool) -> bool {
+        if let (Some(enabled), Some(column)) = (self.enabled, kind.status_column()) {
+            let status: Option<i64> = row.try_get(column).unwrap_or(None);
+            if status.map(|status| status != 0) != Some(enabled) {
+                return false;
+            }
+        }
+
+        if filter_tags {
+            let tags: Option<String> = row.try_get("tags").unwrap_or(None);
+            let row_tags = parse_tags(tags.as_deref().unwrap_or_default());
+            if !row_tags.iter().any(|tag| self.tags.contains(tag)) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// 解析标签列，兼容JSON数组和逗号分隔两种格式
+fn parse_tags(value: &str) -> Vec<String> {
+    serde_json::from_str::<Vec<String>>(value).unwrap_or_else(|_| {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect()
+    })
+}
+
+/// 单条记录的导入结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImportOutcome {
+    Created,
+    Updated,
+    Unchanged,
+}
+
+/// 导出数据与当前数据库的差异，按数据类型列出
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DataDiff {
+    pub entities: Vec<EntityDiff>,
+}
+
+impl DataDiff {
+    /// 重新导入是否会修改数据库，或数据库中有导出数据之外的记录
+    pub fn has_changes(&self) -> bool {
+        self.entities.iter().any(|entity| {
+            !entity.added.is_empty()
+                || !entity.updated.is_empty()
+                || !entity.absent_from_source.is_empty()
+        })
+    }
+
+    /// 指定数据类型的差异
+    pub fn entity(&self, kind: EntityKind) -> Option<&EntityDiff> {
+        self.entities.iter().find(|entity| entity.entity == kind)
+    }
+}
+
+/// 单类数据的差异，记录以自然键（`name` 或 `key`）标识
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntityDiff {
+    pub entity: EntityKind,
+    /// 数据库中不存在、导入时会新增的记录
+    pub added: Vec<String>,
+    /// 导入时会更新的记录
+    pub updated: Vec<RowUpdate>,
+    /// 数据库中存在但导出数据中没有的记录
+    pub absent_from_source: Vec<String>,
+}
+
+/// 会被更新的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RowUpdate {
+    pub key: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// 单个字段的变化，机密字段只标记为已变化，不包含值
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    /// 数据库中的当前值
+    pub current: Option<String>,
+    /// 导入后的值
+    pub incoming: Option<String>,
+    pub secret: bool,
+}
+
+/// 加密存储的列
+const ENCRYPTED_COLUMN: &str = "token";
+/// Claude供应商的模型映射列（JSON对象）
+const MODELS_COLUMN: &str = "models";
+/// 供应商的自定义请求头列（JSON对象）
+const CUSTOM_HEADERS_COLUMN: &str = "custom_headers";
+/// 供应商连接测试可接受状态码列（JSON数组）
+const ACCEPTED_STATUS_CODES_COLUMN: &str = "accepted_status_codes";
+/// MCP服务器的环境变量列（JSON对象），以明文传入，机密值写入前加密
+const MCP_ENV_COLUMN: &str = "env";
+/// MCP服务器的机密环境变量名列（JSON数组）
+const MCP_SECRET_KEYS_COLUMN: &str = "secret_env_keys";
+
+/// 解析JSON列，列不存在或内容无效时返回默认值
+fn json_column<T: serde::de::DeserializeOwned + Default>(row: &SqliteRow, column: &str) -> T {
+    row.try_get::<Option<String>, _>(column)
+        .ok()
+        .flatten()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// 解析机密环境变量名列
+fn parse_secret_keys(value: Option<&str>) -> Vec<String> {
+    value.and_then(|keys| serde_json::from_str(keys).ok()).unwrap_or_default()
+}
+
+/// 解析 `主版本.次版本[.修订号]` 格式的版本号
+pub(crate) fn parse_schema_version(version: &str) -> Option<(u64, u64)> {
+    let mut parts = version.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map_or(Some(0), |minor| minor.parse().ok())?;
+    Some((major, minor))
+}
+
+/// 检查导出格式版本，返回需要记录的警告
+///
+/// 主版本更高时拒绝导入，次版本更高时仅警告并尽量导入
+fn check_schema_version(schema_version: Option<&str>) -> Result<Vec<String>, MigrationError> {
+    let Some(version) = schema_version else {
+        return Ok(Vec::new());
+    };
+    let current = parse_schema_version(EXPORT_SCHEMA_VERSION).unwrap_or_default();
+
+    let warning = match parse_schema_version(version) {
+        None => format!("无法识别的导出格式版本 {}，将尽量导入", version),
+        Some((major, _)) if major > current.0 => {
+            return Err(MigrationError::VersionMismatch(format!(
+                "导出格式版本 {} 高于当前支持的 {}",
+                version, EXPORT_SCHEMA_VERSION
+            )));
+        }
+        Some(parsed) if parsed > current => format!(
+            "导出数据来自更新的版本（格式版本 {}，当前 {}），将尽量导入",
+            version, EXPORT_SCHEMA_VERSION
+        ),
+        Some(_) => return Ok(Vec::new()),
+    };
+
+    warn!("{}", warning);
+    Ok(vec![warning])
+}
+
+/// 汇总一类记录中的未知字段，没有未知字段时返回 `None`
+fn unknown_fields_warning<'a>(
+    section: &str,
+    extras: impl IntoIterator<Item = &'a ExtraFields>,
+) -> Option<String> {
+    let fields: std::collections::BTreeSet<&str> =
+        extras.into_iter().flat_map(|extra| extra.keys().map(String::as_str)).collect();
+    if fields.is_empty() {
+        return None;
+    }
+
+    let fields: Vec<&str> = fields.into_iter().collect();
+    Some(format!(
+        "{} 中包含未知字段，已忽略: {}",
+        section,
+        fields.join(", ")
+    ))
+}
+
+/// 收集导入数据中所有未知字段的警告
+fn unknown_field_warnings(data: &PythonExportData) -> Vec<String> {
+    [
+        unknown_fields_warning("导出数据", [&data.extra]),
+        unknown_fields_warning(
+            "claude_providers",
+            data.claude_providers.iter().map(|p| &p.extra),
+        ),
+        unknown_fields_warning(
+            "codex_providers",
+            data.codex_providers.iter().map(|p| &p.extra),
+        ),
+        unknown_fields_warning("agent_guides", data.agent_guides.iter().map(|g| &g.extra)),
+        unknown_fields_warning("mcp_servers", data.mcp_servers.iter().map(|s| &s.extra)),
+        unknown_fields_warning(
+            "common_configs",
+            data.common_configs.iter().map(|c| &c.extra),
+        ),
+    ]
+    .into_iter()
+    .flatten()
+    .inspect(|warning| warn!("{}", warning))
+    .collect()
+}
+
+/// 统计导出数据中各类记录的数量
+fn record_counts(data: &PythonExportData) -> BTreeMap<String, usize> {
+    let mut counts: BTreeMap<String, usize> = [
+        (EntityKind::ClaudeProviders, data.claude_providers.len()),
+        (EntityKind::CodexProviders, data.codex_providers.len()),
+        (EntityKind::AgentGuides, data.agent_guides.len()),
+        (EntityKind::McpServers, data.mcp_servers.len()),
+        (EntityKind::CommonConfigs, data.common_configs.len()),
+    ]
+    .into_iter()
+    .map(|(kind, count)| (kind.table_name().to_string(), count))
+    .collect();
+    if !data.generated_configs.is_empty() {
+        counts.insert(
+            "generated_configs".to_string(),
+            data.generated_configs.len(),
+        );
+    }
+    counts
+}
+
+/// 记录并校验导出元信息，返回需要记录的警告
+///
+/// 元信息中的格式版本与顶层 `schema_version` 使用相同规则校验；
+/// 记录数与实际数据不一致时仅警告
+fn check_metadata(data: &PythonExportData) -> Result<Vec<String>, MigrationError> {
+    let Some(metadata) = &data.metadata else {
+        return check_schema_version(data.schema_version.as_deref());
+    };
+
+    info!(
+        app_version = %metadata.app_version,
+        schema_version = %metadata.schema_version,
+        exported_at = %metadata.exported_at,
+        source_os = %metadata.source_os,
+        "导入数据的导出元信息"
+    );
+
+    let mut mismatches = Vec::new();
+    if let Some(top_level) = data.schema_version.as_deref() {
+        if top_level != metadata.schema_version {
+            mismatches.push(format!(
+                "导出格式版本不一致：schema_version 为 {}，元信息中为 {}",
+                top_level, metadata.schema_version
+            ));
+        }
+    }
+
+    let actual = record_counts(data);
+    for (table, expected) in &metadata.record_counts {
+        let found = actual.get(table).copied().unwrap_or(0);
+        if found != *expected {
+            mismatches.push(format!(
+                "{} 的记录数与元信息不一致：元信息中为 {}，实际为 {}",
+                table, expected, found
+            ));
+        }
+    }
+
+    for mismatch in &mismatches {
+        warn!("{}", mismatch);
+    }
+
+    let mut warnings = check_schema_version(Some(&metadata.schema_version))?;
+    warnings.extend(mismatches);
+    Ok(warnings)
+}
+
+/// 解码并解析导出文件
+///
+/// 严格模式下无效的UTF-8字节直接返回错误；否则替换为 U+FFFD，
+/// 并为包含替换字符的字段生成警告
+fn decode_export_bytes(
+    bytes: Vec<u8>,
+    strict: bool,
+) -> Result<(PythonExportData, Vec<String>), MigrationError> {
+    let content = match String::from_utf8(bytes) {
+        Ok(content) => return Ok((PythonExportData::from_versioned_str(&content)?, Vec::new())),
+        Err(e) if strict => {
+            return Err(MigrationError::Validation(format!(
+                "导入文件不是有效的UTF-8编码: {}",
+                e.utf8_error()
+            )));
+        }
+        Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+    };
+
+    let value: serde_json::Value = serde_json::from_str(&content)?;
+    let mut fields = Vec::new();
+    collect_replaced_fields(&value, String::new(), &mut fields);
+
+    let warning = format!(
+        "导入文件包含无效的UTF-8字节，已替换为 U+FFFD: {}",
+        if fields.is_empty() {
+            "未知字段".to_string()
+        } else {
+            fields.join(", ")
+        }
+    );
+    warn!("{}", warning);
+
+    Ok((
+        PythonExportData::from_versioned_value(value)?,
+        vec![warning],
+    ))
+}
+
+/// 收集包含 U+FFFD 替换字符的字段路径，如 `claude_providers[0].name`
+fn collect_replaced_fields(value: &serde_json::Value, path: String, fields: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(s) if s.contains(char::REPLACEMENT_CHARACTER) => {
+            fields.push(path)
+        }
+        serde_json::Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_replaced_fields(item, format!("{}[{}]", path, index), fields);
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (key, item) in map {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                collect_replaced_fields(item, path, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 由Python导出的旧模型字段构造模型映射JSON，忽略空值
+fn legacy_models_json(provider: &PythonClaudeProvider) -> Result<String, MigrationError> {
+    let models: std::collections::BTreeMap<&str, &str> = [
+        (MODEL_ROLE_OPUS, provider.opus_model.as_deref()),
+        (MODEL_ROLE_SONNET, provider.sonnet_model.as_deref()),
+        (MODEL_ROLE_HAIKU, provider.haiku_model.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(role, model)| model.filter(|m| !m.is_empty()).map(|m| (role, m)))
+    .collect();
+
+    Ok(serde_json::to_string(&models)?)
+}
+
+/// 导入数据中的超时时间（毫秒），缺失或超出范围时使用默认值
+fn imported_timeout(value: Option<i64>) -> TimeoutMs {
+    value.and_then(|millis| TimeoutMs::new(millis).ok()).unwrap_or_default()
+}
+
+/// 超时时间超出范围时的警告信息
+fn timeout_warning(kind: &str, name: &str, value: Option<i64>) -> Option<String> {
+    let error = TimeoutMs::new(value?).err()?;
+    Some(format!(
+        "{} {} 的超时时间无效，已使用默认值 {} 毫秒: {}",
+        kind, name, DEFAULT_TIMEOUT_MS, error
+    ))
+}
+
+/// Claude供应商的列值，第一列为自然键
+fn claude_provider_columns(
+    provider: &PythonClaudeProvider,
+) -> Result<Vec<(&'static str, String)>, MigrationError> {
+    Ok(vec![
+        ("name", provider.name.clone()),
+        ("url", provider.url.clone()),
+        (ENCRYPTED_COLUMN, provider.token.clone()),
+        (
+            "timeout",
+            imported_timeout(provider.timeout).as_millis().to_string(),
+        ),
+        ("auto_update", provider.auto_update.unwrap_or(1).to_string()),
+        (
+            "type",
+            provider.r#type.clone().unwrap_or_else(|| "public_welfare".to_string()),
+        ),
+        ("enabled", provider.enabled.unwrap_or(0).to_string()),
+        (
+            "opus_model",
+            provider.opus_model.clone().unwrap_or_default(),
+        ),
+        (
+            "sonnet_model",
+            provider.sonnet_model.clone().unwrap_or_default(),
+        ),
+        (
+            "haiku_model",
+            provider.haiku_model.clone().unwrap_or_default(),
+        ),
+        (MODELS_COLUMN, legacy_models_json(provider)?),
+        (
+            CUSTOM_HEADERS_COLUMN,
+            serde_json::to_string(&provider.custom_headers)?,
+        ),
+        (
+            ACCEPTED_STATUS_CODES_COLUMN,
+            serde_json::to_string(&provider.accepted_status_codes)?,
+        ),
+        (
+            "model_auto_update",
+            provider.model_auto_update.unwrap_or(0).to_string(),
+        ),
+    ])
+}
+
+/// Codex供应商的列值，第一列为自然键
+fn codex_provider_columns(
+    provider: &PythonCodexProvider,
+) -> Result<Vec<(&'static str, String)>, MigrationError> {
+    Ok(vec![
+        ("name", provider.name.clone()),
+        ("url", provider.url.clone()),
+        (ENCRYPTED_COLUMN, provider.token.clone()),
+        (
+            "type",
+            provider.r#type.clone().unwrap_or_else(|| "public_welfare".to_string()),
+        ),
+        ("enabled", provider.enabled.unwrap_or(0).to_string()),
+        (
+            CUSTOM_HEADERS_COLUMN,
+            serde_json::to_string(&provider.custom_headers)?,
+        ),
+        (
+            ACCEPTED_STATUS_CODES_COLUMN,
+            serde_json::to_string(&provider.accepted_status_codes)?,
+        ),
+    ])
+}
+
+/// Agent指导文件的列值，第一列为自然键
+fn agent_guide_columns(guide: &PythonAgentGuide) -> Vec<(&'static str, String)> {
+    vec![
+        ("name", guide.name.clone()),
+        ("type", guide.r#type.clone()),
+        ("text", guide.text.clone()),
+    ]
+}
+
+/// 通用配置的列值，第一列为自然键
+fn common_config_columns(config: &PythonCommonConfig) -> Vec<(&'static str, String)> {
+    vec![
+        ("key", config.key.clone()),
+        ("value", config.value.clone()),
+        (
+            "description",
+            config.description.clone().unwrap_or_default(),
+        ),
+        (
+            "category",
+            config.category.clone().unwrap_or_else(|| "general".to_string()),
+        ),
+        ("is_active", config.is_active.unwrap_or(1).to_string()),
+    ]
+}
+
 /// 数据迁移工具
 pub struct DataMigrationTool {
     crypto_service: CryptoService,
     db_manager: DatabaseManager,
+    disk_checker: Arc<dyn DiskSpaceChecker>,
+    backup_dir: Option<PathBuf>,
 }
 
 impl DataMigrationTool {
@@ -131,8 +837,59 @@ impl DataMigrationTool {
         encryption_key: &str,
     ) -> Result<Self, MigrationError> {
         let crypto_service = CryptoService::new(encryption_key)?;
+        Ok(Self::with_crypto_service(db_manager, crypto_service))
+    }
+
+    /// 使用已创建的加密服务创建迁移工具实例，可以共用应用的连接池和加密服务
+    pub fn with_crypto_service(db_manager: DatabaseManager, crypto_service: CryptoService) -> Self {
+        info!(key_fingerprint = %crypto_service.key_fingerprint(), "迁移工具使用的加密密钥");
 
-        Ok(Self { crypto_service, db_manager })
+        Self {
+            crypto_service,
+            db_manager,
+            disk_checker: Arc::new(FsDiskSpaceChecker),
+            backup_dir: None,
+        }
+    }
+
+    /// 替换导入前检查使用的磁盘空间查询
+    pub fn with_disk_space_checker(mut self, checker: Arc<dyn DiskSpaceChecker>) -> Self {
+        self.disk_checker = checker;
+        self
+    }
+
+    /// 设置备份目录，导入前检查会确认该目录可写
+    pub fn with_backup_dir(mut self, backup_dir: impl Into<PathBuf>) -> Self {
+        self.backup_dir = Some(backup_dir.into());
+        self
+    }
+
+    /// 导入前检查：按导出文件大小估算所需空间，检查剩余空间以及数据库和备份目录的写权限
+    pub fn preflight(&self, export_size: u64) -> PreflightReport {
+        preflight::run_preflight(
+            self.db_manager.database_path().as_deref(),
+            self.backup_dir.as_deref(),
+            export_size,
+            self.disk_checker.as_ref(),
+        )
+    }
+
+    /// 按导入选项执行导入前检查，未通过时返回 `PreflightFailed`
+    fn ensure_preflight(
+        &self,
+        options: &ImportOptions,
+        export_size: u64,
+    ) -> Result<(), MigrationError> {
+        if !options.preflight {
+            return Ok(());
+        }
+
+        let report = self.preflight(export_size);
+        if report.passed {
+            Ok(())
+        } else {
+            Err(MigrationError::PreflightFailed(report.failure_summary()))
+        }
     }
 
     /// 从JSON文件导入Python数据
@@ -140,8 +897,24 @@ impl DataMigrationTool {
         &self,
         file_path: P,
     ) -> Result<MigrationReport, MigrationError> {
-        let content = std::fs::read_to_string(file_path)?;
-        self.import_from_json(&content).await
+        self.import_from_json_file_with_options(file_path, &ImportOptions::default())
+            .await
+    }
+
+    /// 按导入选项从JSON文件导入数据
+    ///
+    /// 文件内容不是有效的UTF-8时，按 `options.strict_utf8` 决定失败还是替换无效字节后继续导入
+    pub async fn import_from_json_file_with_options<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        options: &ImportOptions,
+    ) -> Result<MigrationReport, MigrationError> {
+        let source = format!("file:{}", file_path.as_ref().display());
+        let bytes = std::fs::read(file_path)?;
+        self.ensure_preflight(options, bytes.len() as u64)?;
+
+        let (python_data, warnings) = decode_export_bytes(bytes, options.strict_utf8)?;
+        self.import_data(&python_data, &source, options, warnings).await
     }
 
     /// 从JSON字符串导入Python数据
@@ -150,9 +923,52 @@ impl DataMigrationTool {
         json_content: &str,
     ) -> Result<MigrationReport, MigrationError> {
         info!("开始从JSON导入数据...");
-        let start_time = std::time::Instant::now();
 
-        let python_data: PythonExportData = serde_json::from_str(json_content)?;
+        let python_data = PythonExportData::from_versioned_str(json_content)?;
+        self.import_data(&python_data, "json", &ImportOptions::default(), Vec::new())
+            .await
+    }
+
+    /// 按导入选项从JSON字符串导入数据
+    pub async fn import_from_json_with_options(
+        &self,
+        json_content: &str,
+        options: &ImportOptions,
+    ) -> Result<MigrationReport, MigrationError> {
+        info!(merge = %options.merge, "开始从JSON导入数据...");
+        self.ensure_preflight(options, json_content.len() as u64)?;
+
+        let python_data = PythonExportData::from_versioned_str(json_content)?;
+        self.import_data(&python_data, "json", options, Vec::new()).await
+    }
+
+    /// 从JSON字符串导入数据，并恢复导出包中的配置文件
+    pub async fn import_from_json_with_configs(
+        &self,
+        json_content: &str,
+        generator: &ConfigGenerator,
+    ) -> Result<MigrationReport, MigrationError> {
+        let python_data = PythonExportData::from_versioned_str(json_content)?;
+        let mut report = self
+            .import_data(&python_data, "json", &ImportOptions::default(), Vec::new())
+            .await?;
+
+        report.generated_configs =
+            self.restore_generated_configs(&python_data.generated_configs, generator, &mut report);
+
+        Ok(report)
+    }
+
+    /// 导入已解析的数据，`warnings` 为解析阶段产生的警告
+    async fn import_data(
+        &self,
+        python_data: &PythonExportData,
+        source: &str,
+        options: &ImportOptions,
+        warnings: Vec<String>,
+    ) -> Result<MigrationReport, MigrationError> {
+        let start_time = std::time::Instant::now();
+        let started_at = chrono::Utc::now().to_rfc3339();
 
         // 验证版本兼容性
         self.validate_version(&python_data.version)?;
@@ -164,32 +980,47 @@ impl DataMigrationTool {
             agent_guides: 0,
             mcp_servers: 0,
             common_configs: 0,
+            generated_configs: 0,
+            created: 0,
+            updated: 0,
+            unchanged: 0,
             errors: Vec::new(),
-            warnings: Vec::new(),
+            warnings,
             duration_secs: 0,
         };
 
-        // 清空现有数据（可选）
-        self.clear_existing_data(&mut report).await?;
+        // 更新版本的导出尽量导入，未知字段忽略并记录警告
+        report.warnings.extend(check_metadata(python_data)?);
+        report.warnings.extend(unknown_field_warnings(python_data));
+
+        // 合并模式保留现有数据，否则先清空
+        if !options.merge {
+            self.clear_existing_data(&mut report).await?;
+        }
 
         // 导入Claude供应商
-        report.claude_providers =
-            self.import_claude_providers(&python_data.claude_providers, &mut report).await?;
+        report.claude_providers = self
+            .import_claude_providers(&python_data.claude_providers, options, &mut report)
+            .await?;
 
         // 导入Codex供应商
-        report.codex_providers =
-            self.import_codex_providers(&python_data.codex_providers, &mut report).await?;
+        report.codex_providers = self
+            .import_codex_providers(&python_data.codex_providers, options, &mut report)
+            .await?;
 
         // 导入Agent指导文件
-        report.agent_guides =
-            self.import_agent_guides(&python_data.agent_guides, &mut report).await?;
+        report.agent_guides = self
+            .import_agent_guides(&python_data.agent_guides, options, &mut report)
+            .await?;
 
         // 导入MCP服务器
-        report.mcp_servers = self.import_mcp_servers(&python_data.mcp_servers, &mut report).await?;
+        report.mcp_servers =
+            self.import_mcp_servers(&python_data.mcp_servers, options, &mut report).await?;
 
         // 导入通用配置
-        report.common_configs =
-            self.import_common_configs(&python_data.common_configs, &mut report).await?;
+        report.common_configs = self
+            .import_common_configs(&python_data.common_configs, options, &mut report)
+            .await?;
 
         report.total_migrated = report.claude_providers
             + report.codex_providers
@@ -199,15 +1030,91 @@ impl DataMigrationTool {
 
         report.duration_secs = start_time.elapsed().as_secs();
 
+        // 持久化迁移记录，失败不影响本次迁移结果
+        if let Err(e) = self.record_migration_run(source, started_at, &report).await {
+            let msg = format!("保存迁移记录失败: {}", e);
+            warn!("{}", msg);
+            report.warnings.push(msg);
+        }
+
         info!("✅ 数据迁移完成: {:?}", report);
         Ok(report)
     }
 
+    /// 保存迁移运行记录
+    async fn record_migration_run(
+        &self,
+        source: &str,
+        started_at: String,
+        report: &MigrationReport,
+    ) -> Result<i64, RepositoryError> {
+        let request = CreateMigrationRunRequest {
+            source: source.to_string(),
+            started_at,
+            finished_at: chrono::Utc::now().to_rfc3339(),
+            total_migrated: report.total_migrated as i64,
+            claude_providers: report.claude_providers as i64,
+            codex_providers: report.codex_providers as i64,
+            agent_guides: report.agent_guides as i64,
+            mcp_servers: report.mcp_servers as i64,
+            common_configs: report.common_configs as i64,
+            errors: report.errors.clone(),
+            warnings: report.warnings.clone(),
+        };
+
+        MigrationRunRepository::new(&self.db_manager)
+            .create_migration_run(&request)
+            .await
+    }
+
+    /// 获取迁移历史（最新的在前）
+    pub async fn list_migration_runs(&self) -> Result<Vec<MigrationRun>, MigrationError> {
+        MigrationRunRepository::new(&self.db_manager)
+            .list_migration_runs(None)
+            .await
+            .map_err(|e| MigrationError::Database(DatabaseError::query(e)))
+    }
+
+    /// 将导出包中的配置文件写回磁盘
+    fn restore_generated_configs(
+        &self,
+        files: &[GeneratedConfigFile],
+        generator: &ConfigGenerator,
+        report: &mut MigrationReport,
+    ) -> usize {
+        let mut restored = 0;
+
+        for file in files {
+            let result = self
+                .crypto_service
+                .decrypt(&file.encrypted_content)
+                .map_err(MigrationError::from)
+                .and_then(|content| {
+                    generator.write_config_file(file.kind, &content).map_err(MigrationError::from)
+                });
+
+            match result {
+                Ok(path) => {
+                    restored += 1;
+                    debug!("✅ 恢复配置文件: {}", path.display());
+                }
+                Err(e) => {
+                    let msg = format!("恢复配置文件失败 {}: {}", file.kind.file_name(), e);
+                    error!("{}", msg);
+                    report.errors.push(msg);
+                }
+            }
+        }
+
+        restored
+    }
+
     /// 验证版本兼容性
     fn validate_version(&self, version: &str) -> Result<(), MigrationError> {
         // 这里可以添加版本兼容性检查逻辑
         match version {
-            v if v.starts_with("1.") => {
+            // 1.x 为Python版本导出，2.x 为本工具导出
+            v if v.starts_with("1.") || v.starts_with("2.") => {
                 info!("✅ 版本 {} 兼容", version);
                 Ok(())
             }
@@ -236,8 +1143,8 @@ impl DataMigrationTool {
             "common_configs",
         ];
         for table in tables {
-            match query_builder.execute_raw(&format!("DELETE FROM {}", table), &[]).await {
-                Ok(_) => debug!("清空表 {}", table),
+            match query_builder.table(table)?.delete_all().await {
+                Ok(deleted) => debug!("清空表 {}，删除 {} 条记录", table, deleted),
                 Err(e) => {
                     let msg = format!("清空表 {} 失败: {}", table, e);
                     warn!("{}", msg);
@@ -253,16 +1160,25 @@ impl DataMigrationTool {
     async fn import_claude_providers(
         &self,
         providers: &[PythonClaudeProvider],
+        options: &ImportOptions,
         report: &mut MigrationReport,
     ) -> Result<usize, MigrationError> {
         info!("导入 {} 个Claude供应商", providers.len());
         let mut imported = 0;
+        let table = self.table(EntityKind::ClaudeProviders)?;
 
         for provider in providers {
-            match self.import_claude_provider(provider).await {
-                Ok(_) => {
-                    imported += 1;
-                    debug!("✅ 导入Claude供应商: {}", provider.name);
+            if let Some(msg) = timeout_warning("Claude供应商", &provider.name, provider.timeout)
+            {
+                warn!("{}", msg);
+                report.warnings.push(msg);
+            }
+            let columns = claude_provider_columns(provider)?;
+
+            match self.upsert_row(&table, &columns, options.merge).await {
+                Ok(outcome) => {
+                    imported += report.record_outcome(outcome);
+                    debug!("✅ 导入Claude供应商: {} ({:?})", provider.name, outcome);
                 }
                 Err(e) => {
                     let msg = format!("导入Claude供应商失败 {}: {}", provider.name, e);
@@ -275,65 +1191,24 @@ impl DataMigrationTool {
         Ok(imported)
     }
 
-    /// 导入单个Claude供应商
-    async fn import_claude_provider(
-        &self,
-        provider: &PythonClaudeProvider,
-    ) -> Result<(), MigrationError> {
-        // 加密token
-        let encrypted_token = self.crypto_service.encrypt(&provider.token)?;
-
-        let query = r#"
-            INSERT INTO claude_providers
-            (name, url, token, timeout, auto_update, type, enabled, opus_model, sonnet_model, haiku_model)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-        "#;
-
-        let timeout_val = provider.timeout.unwrap_or(30000).to_string();
-        let auto_update_val = provider.auto_update.unwrap_or(1).to_string();
-        let type_val = provider.r#type.clone().unwrap_or_else(|| "public_welfare".to_string());
-        let enabled_val = provider.enabled.unwrap_or(0).to_string();
-        let opus_val = provider.opus_model.as_deref().unwrap_or("").to_string();
-        let sonnet_val = provider.sonnet_model.as_deref().unwrap_or("").to_string();
-        let haiku_val = provider.haiku_model.as_deref().unwrap_or("").to_string();
-
-        let params = [
-            &provider.name,
-            &provider.url,
-            &encrypted_token,
-            &timeout_val,
-            &auto_update_val,
-            &type_val,
-            &enabled_val,
-            &opus_val,
-            &sonnet_val,
-            &haiku_val,
-        ];
-
-        QueryBuilder::new(self.db_manager.pool())
-            .execute_raw(
-                query,
-                &params.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
-            )
-            .await?;
-
-        Ok(())
-    }
-
     /// 导入Codex供应商
     async fn import_codex_providers(
         &self,
         providers: &[PythonCodexProvider],
+        options: &ImportOptions,
         report: &mut MigrationReport,
     ) -> Result<usize, MigrationError> {
         info!("导入 {} 个Codex供应商", providers.len());
         let mut imported = 0;
+        let table = self.table(EntityKind::CodexProviders)?;
 
         for provider in providers {
-            match self.import_codex_provider(provider).await {
-                Ok(_) => {
-                    imported += 1;
-                    debug!("✅ 导入Codex供应商: {}", provider.name);
+            let columns = codex_provider_columns(provider)?;
+
+            match self.upsert_row(&table, &columns, options.merge).await {
+                Ok(outcome) => {
+                    imported += report.record_outcome(outcome);
+                    debug!("✅ 导入Codex供应商: {} ({:?})", provider.name, outcome);
                 }
                 Err(e) => {
                     let msg = format!("导入Codex供应商失败 {}: {}", provider.name, e);
@@ -346,51 +1221,24 @@ impl DataMigrationTool {
         Ok(imported)
     }
 
-    /// 导入单个Codex供应商
-    async fn import_codex_provider(
-        &self,
-        provider: &PythonCodexProvider,
-    ) -> Result<(), MigrationError> {
-        let encrypted_token = self.crypto_service.encrypt(&provider.token)?;
-
-        let query = r#"
-            INSERT INTO codex_providers
-            (name, url, token, type, enabled)
-            VALUES (?, ?, ?, ?, ?)
-        "#;
-
-        let params = [
-            &provider.name,
-            &provider.url,
-            &encrypted_token,
-            &provider.r#type.clone().unwrap_or_else(|| "public_welfare".to_string()),
-            &provider.enabled.unwrap_or(0).to_string(),
-        ];
-
-        QueryBuilder::new(self.db_manager.pool())
-            .execute_raw(
-                query,
-                &params.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
-            )
-            .await?;
-
-        Ok(())
-    }
-
     /// 导入Agent指导文件
     async fn import_agent_guides(
         &self,
         guides: &[PythonAgentGuide],
+        options: &ImportOptions,
         report: &mut MigrationReport,
     ) -> Result<usize, MigrationError> {
         info!("导入 {} 个Agent指导文件", guides.len());
         let mut imported = 0;
+        let table = self.table(EntityKind::AgentGuides)?;
 
         for guide in guides {
-            match self.import_agent_guide(guide).await {
-                Ok(_) => {
-                    imported += 1;
-                    debug!("✅ 导入Agent指导: {}", guide.name);
+            let columns = agent_guide_columns(guide);
+
+            match self.upsert_row(&table, &columns, options.merge).await {
+                Ok(outcome) => {
+                    imported += report.record_outcome(outcome);
+                    debug!("✅ 导入Agent指导: {} ({:?})", guide.name, outcome);
                 }
                 Err(e) => {
                     let msg = format!("导入Agent指导失败 {}: {}", guide.name, e);
@@ -403,39 +1251,31 @@ impl DataMigrationTool {
         Ok(imported)
     }
 
-    /// 导入单个Agent指导文件
-    async fn import_agent_guide(&self, guide: &PythonAgentGuide) -> Result<(), MigrationError> {
-        let query = r#"
-            INSERT INTO agent_guides
-            (name, type, text)
-            VALUES (?, ?, ?)
-        "#;
-
-        let params = [
-            guide.name.as_str(),
-            guide.r#type.as_str(),
-            guide.text.as_str(),
-        ];
-
-        QueryBuilder::new(self.db_manager.pool()).execute_raw(query, &params).await?;
-
-        Ok(())
-    }
-
     /// 导入MCP服务器
     async fn import_mcp_servers(
         &self,
         servers: &[PythonMcpServer],
+        options: &ImportOptions,
         report: &mut MigrationReport,
     ) -> Result<usize, MigrationError> {
         info!("导入 {} 个MCP服务器", servers.len());
         let mut imported = 0;
+        let table = self.table(EntityKind::McpServers)?;
 
         for server in servers {
-            match self.import_mcp_server(server).await {
-                Ok(_) => {
-                    imported += 1;
-                    debug!("✅ 导入MCP服务器: {}", server.name);
+            if let Some(msg) = timeout_warning("MCP服务器", &server.name, server.timeout) {
+                warn!("{}", msg);
+                report.warnings.push(msg);
+            }
+            let result = match self.mcp_server_columns(server) {
+                Ok(columns) => self.upsert_row(&table, &columns, options.merge).await,
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(outcome) => {
+                    imported += report.record_outcome(outcome);
+                    debug!("✅ 导入MCP服务器: {} ({:?})", server.name, outcome);
                 }
                 Err(e) => {
                     let msg = format!("导入MCP服务器失败 {}: {}", server.name, e);
@@ -448,53 +1288,52 @@ impl DataMigrationTool {
         Ok(imported)
     }
 
-    /// 导入单个MCP服务器
-    async fn import_mcp_server(&self, server: &PythonMcpServer) -> Result<(), MigrationError> {
+    /// MCP服务器的列值
+    fn mcp_server_columns(
+        &self,
+        server: &PythonMcpServer,
+    ) -> Result<Vec<(&'static str, String)>, MigrationError> {
         let args_json = serde_json::to_string(&server.args)?;
         let env_json = server.env.as_ref().map(serde_json::to_string).transpose()?;
 
-        let query = r#"
-            INSERT INTO mcp_servers
-            (name, type, timeout, command, args, env)
-            VALUES (?, ?, ?, ?, ?, ?)
-        "#;
-
-        let server_type = server.r#type.as_ref().unwrap_or(&"stdio".to_string()).clone();
-        let env_value = env_json.as_ref().unwrap_or(&"".to_string()).clone();
-
-        let params = [
-            &server.name,
-            &server_type,
-            &server.timeout.unwrap_or(30000).to_string(),
-            &server.command,
-            &args_json,
-            &env_value,
-        ];
-
-        QueryBuilder::new(self.db_manager.pool())
-            .execute_raw(
-                query,
-                &params.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
-            )
-            .await?;
-
-        Ok(())
+        Ok(vec![
+            ("name", server.name.clone()),
+            (
+                "type",
+                server.r#type.clone().unwrap_or_else(|| "stdio".to_string()),
+            ),
+            (
+                "timeout",
+                imported_timeout(server.timeout).as_millis().to_string(),
+            ),
+            ("command", server.command.clone()),
+            ("args", args_json),
+            (MCP_ENV_COLUMN, env_json.unwrap_or_default()),
+            (
+                MCP_SECRET_KEYS_COLUMN,
+                serde_json::to_string(&server.secret_env_keys)?,
+            ),
+        ])
     }
 
     /// 导入通用配置
     async fn import_common_configs(
         &self,
         configs: &[PythonCommonConfig],
+        options: &ImportOptions,
         report: &mut MigrationReport,
     ) -> Result<usize, MigrationError> {
         info!("导入 {} 个通用配置", configs.len());
         let mut imported = 0;
+        let table = self.table(EntityKind::CommonConfigs)?;
 
         for config in configs {
-            match self.import_common_config(config).await {
-                Ok(_) => {
-                    imported += 1;
-                    debug!("✅ 导入配置: {}", config.key);
+            let columns = common_config_columns(config);
+
+            match self.upsert_row(&table, &columns, options.merge).await {
+                Ok(outcome) => {
+                    imported += report.record_outcome(outcome);
+                    debug!("✅ 导入配置: {} ({:?})", config.key, outcome);
                 }
                 Err(e) => {
                     let msg = format!("导入配置失败 {}: {}", config.key, e);
@@ -507,31 +1346,251 @@ impl DataMigrationTool {
         Ok(imported)
     }
 
-    /// 导入单个通用配置
-    async fn import_common_config(
+    /// 写入一条记录
+    ///
+    /// 第一列为自然键（`name` 或 `key`）。合并模式下按自然键查找已有记录：
+    /// 值有变化时原地更新（保留 id 和 created_at），无变化时跳过；不存在时插入。
+    /// `token` 列以明文传入，写入前加密，比较时先解密已有值；写入加密列时同时记录密钥指纹
+    async fn upsert_row(
         &self,
-        config: &PythonCommonConfig,
-    ) -> Result<(), MigrationError> {
-        let query = r#"
-            INSERT INTO common_configs
-            (key, value, description, category, is_active)
-            VALUES (?, ?, ?, ?, ?)
-        "#;
-
-        let description = config.description.as_ref().unwrap_or(&"".to_string()).clone();
-        let category = config.category.as_ref().unwrap_or(&"general".to_string()).clone();
-
-        let params = [
-            config.key.as_str(),
-            config.value.as_str(),
-            description.as_str(),
-            category.as_str(),
-            &config.is_active.unwrap_or(1).to_string(),
-        ];
+        table: &TableHandle<'_>,
+        columns: &[(&str, String)],
+        merge: bool,
+    ) -> Result<ImportOutcome, MigrationError> {
+        let (key_column, key_value) = &columns[0];
+        let mut names: Vec<&str> = columns.iter().map(|(column, _)| *column).collect();
+        // 未配置密钥时指纹写入NULL
+        let key_fingerprint = names
+            .iter()
+            .any(|column| [ENCRYPTED_COLUMN, MCP_ENV_COLUMN].contains(column))
+            .then(|| self.crypto_service.storage_fingerprint());
+
+        let existing = if merge {
+            table
+                .select_as_text(&names, Some((*key_column, key_value.as_str())))
+                .await?
+                .into_iter()
+                .next()
+        } else {
+            None
+        };
+        if let Some(row) = &existing {
+            let unchanged =
+                columns.iter().all(|(column, value)| self.column_matches(row, column, value));
+            if unchanged {
+                return Ok(ImportOutcome::Unchanged);
+            }
+        }
 
-        QueryBuilder::new(self.db_manager.pool()).execute_raw(query, &params).await?;
+        let stored = self.stored_values(columns)?;
+        let mut values: Vec<Option<&str>> =
+            stored.iter().map(|value| Some(value.as_str())).collect();
+        if let Some(key_fingerprint) = &key_fingerprint {
+            names.push("key_fingerprint");
+            values.push(key_fingerprint.as_deref());
+        }
 
-        Ok(())
+        match existing {
+            // 原地更新，保留 id 和 created_at
+            Some(row) => {
+                table.update_by_id(row.get("id"), &names, &values).await?;
+                Ok(ImportOutcome::Updated)
+            }
+            // 导出数据中的时间戳可能缺失，插入时统一使用导入时间
+            None => {
+                table.insert_timestamped(&names, &values).await?;
+                Ok(ImportOutcome::Created)
+            }
+        }
+    }
+
+    /// 导入目标数据表
+    fn table(&self, kind: EntityKind) -> Result<TableHandle<'_>, MigrationError> {
+        Ok(QueryBuilder::new(self.db_manager.pool()).table(kind.table_name())?)
+    }
+
+    /// 比较数据库中的列值与导入值是否相同
+    ///
+    /// `row` 为 [`TableHandle::select_as_text`] 查询的结果；`token` 列和机密环境变量按解密后的值比较
+    fn column_matches(&self, row: &SqliteRow, column: &str, value: &str) -> bool {
+        let current: String = row.get::<Option<String>, _>(column).unwrap_or_default();
+        if column == ENCRYPTED_COLUMN {
+            EncryptedField::decrypt_field_or_plaintext(&current, &self.crypto_service)
+                .map(|plain| plain == value)
+                .unwrap_or(false)
+        } else if [MODELS_COLUMN, CUSTOM_HEADERS_COLUMN].contains(&column) {
+            // JSON对象的键顺序不固定，按解析后的值比较
+            serde_json::from_str::<serde_json::Value>(&current).ok()
+                == serde_json::from_str::<serde_json::Value>(value).ok()
+        } else if column == MCP_ENV_COLUMN {
+            // 机密值每次加密的密文都不同，按解密后的值比较
+            let secret_keys =
+                parse_secret_keys(row.get::<Option<String>, _>(MCP_SECRET_KEYS_COLUMN).as_deref());
+            let current = serde_json::from_str::<HashMap<String, String>>(&current).ok().and_then(
+                |mut env| {
+                    decrypt_secret_env(&mut env, &secret_keys, &self.crypto_service)
+                        .ok()
+                        .map(|()| env)
+                },
+            );
+            current == serde_json::from_str::<HashMap<String, String>>(value).ok()
+        } else {
+            current == value
+        }
+    }
+
+    /// 读取导出文件并与当前数据库对比，见 [`Self::diff_against_db`]
+    pub async fn diff_json_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+    ) -> Result<DataDiff, MigrationError> {
+        let bytes = std::fs::read(file_path)?;
+        let (python_data, _) = decode_export_bytes(bytes, false)?;
+        self.diff_against_db(&python_data).await
+    }
+
+    /// 对比导出数据与当前数据库，列出重新导入（合并模式）会新增、更新的记录，
+    /// 以及数据库中存在但导出数据中没有的记录。不修改任何数据
+    pub async fn diff_against_db(
+        &self,
+        source: &PythonExportData,
+    ) -> Result<DataDiff, MigrationError> {
+        let mut entities = Vec::new();
+
+        let rows = source
+            .claude_providers
+            .iter()
+            .map(claude_provider_columns)
+            .collect::<Result<Vec<_>, _>>()?;
+        entities.push(self.diff_entity(EntityKind::ClaudeProviders, &rows).await?);
+
+        let rows = source
+            .codex_providers
+            .iter()
+            .map(codex_provider_columns)
+            .collect::<Result<Vec<_>, _>>()?;
+        entities.push(self.diff_entity(EntityKind::CodexProviders, &rows).await?);
+
+        let rows: Vec<_> = source.agent_guides.iter().map(agent_guide_columns).collect();
+        entities.push(self.diff_entity(EntityKind::AgentGuides, &rows).await?);
+
+        let rows = source
+            .mcp_servers
+            .iter()
+            .map(|server| self.mcp_server_columns(server))
+            .collect::<Result<Vec<_>, _>>()?;
+        entities.push(self.diff_entity(EntityKind::McpServers, &rows).await?);
+
+        let rows: Vec<_> = source.common_configs.iter().map(common_config_columns).collect();
+        entities.push(self.diff_entity(EntityKind::CommonConfigs, &rows).await?);
+
+        let diff = DataDiff { entities };
+        info!(has_changes = %diff.has_changes(), "导出数据与数据库对比完成");
+        Ok(diff)
+    }
+
+    /// 对比单类数据，`rows` 的第一列为自然键
+    async fn diff_entity(
+        &self,
+        kind: EntityKind,
+        rows: &[Vec<(&'static str, String)>],
+    ) -> Result<EntityDiff, MigrationError> {
+        let mut diff = EntityDiff {
+            entity: kind,
+            added: Vec::new(),
+            updated: Vec::new(),
+            absent_from_source: Vec::new(),
+        };
+        let Some(first) = rows.first() else {
+            // 导出数据中没有该类记录时，只需列出数据库中的记录
+            let key_column = match kind {
+                EntityKind::CommonConfigs => "key",
+                _ => "name",
+            };
+            diff.absent_from_source = self
+                .table(kind)?
+                .select_as_text(&[key_column], None)
+                .await?
+                .into_iter()
+                .map(|row| row.get::<Option<String>, _>(key_column).unwrap_or_default())
+                .collect();
+            return Ok(diff);
+        };
+
+        let (key_column, _) = first[0];
+        let names: Vec<&str> = first.iter().map(|(column, _)| *column).collect();
+        let existing = self.table(kind)?.select_as_text(&names, None).await?;
+        let mut existing: Vec<(String, SqliteRow)> = existing
+            .into_iter()
+            .map(|row| {
+                (
+                    row.get::<Option<String>, _>(key_column).unwrap_or_default(),
+                    row,
+                )
+            })
+            .collect();
+
+        for columns in rows {
+            let key = &columns[0].1;
+            let Some(index) = existing.iter().position(|(existing_key, _)| existing_key == key)
+            else {
+                diff.added.push(key.clone());
+                continue;
+            };
+            let (_, row) = existing.remove(index);
+
+            let changes: Vec<FieldChange> = columns
+                .iter()
+                .filter(|(column, value)| !self.column_matches(&row, column, value))
+                .map(|(column, value)| {
+                    let current: Option<String> = row.get(*column);
+                    // 密文和符合敏感信息匹配规则的值同样视为机密
+                    let looks_secret = |value: &str| {
+                        CryptoService::is_fernet_token(value) || secret_matcher().is_match(value)
+                    };
+                    let secret = *column == ENCRYPTED_COLUMN
+                        || *column == MCP_ENV_COLUMN
+                        || current.as_deref().is_some_and(looks_secret)
+                        || looks_secret(value.as_str());
+                    FieldChange {
+                        field: column.to_string(),
+                        current: if secret { None } else { current },
+                        incoming: if secret { None } else { Some(value.clone()) },
+                        secret,
+                    }
+                })
+                .collect();
+            if !changes.is_empty() {
+                diff.updated.push(RowUpdate { key: key.clone(), changes });
+            }
+        }
+
+        diff.absent_from_source = existing.into_iter().map(|(key, _)| key).collect();
+        Ok(diff)
+    }
+
+    /// 计算写入数据库的列值，加密 `token` 列和MCP服务器的机密环境变量
+    fn stored_values(&self, columns: &[(&str, String)]) -> Result<Vec<String>, MigrationError> {
+        columns
+            .iter()
+            .map(|(column, value)| -> Result<String, MigrationError> {
+                if *column == ENCRYPTED_COLUMN {
+                    Ok(self.crypto_service.encrypt(value)?)
+                } else if *column == MCP_ENV_COLUMN && !value.is_empty() {
+                    let secret_keys = parse_secret_keys(
+                        columns
+                            .iter()
+                            .find(|(column, _)| *column == MCP_SECRET_KEYS_COLUMN)
+                            .map(|(_, keys)| keys.as_str()),
+                    );
+                    let mut env: HashMap<String, String> = serde_json::from_str(value)?;
+                    encrypt_secret_env(&mut env, &secret_keys, &self.crypto_service)?;
+                    Ok(serde_json::to_string(&env)?)
+                } else {
+                    Ok(value.clone())
+                }
+            })
+            .collect()
     }
 
     /// 导出数据到JSON文件
@@ -539,45 +1598,157 @@ impl DataMigrationTool {
         &self,
         file_path: P,
     ) -> Result<(), MigrationError> {
-        let data = self.export_to_json().await?;
-        std::fs::write(file_path, serde_json::to_string_pretty(&data)?)?;
+        self.export_to_sink(&mut FileSink::new(file_path.as_ref())).await
+    }
+
+    /// 导出全部数据为格式化的JSON，写入指定的导出目标
+    pub async fn export_to_sink<S: ExportSink>(&self, sink: &mut S) -> Result<(), MigrationError> {
+        let data = self.export_to_json(&ExportFilter::default()).await?;
+        sink.write_export(&serde_json::to_vec_pretty(&data)?).await?;
         Ok(())
     }
 
-    /// 导出数据到JSON字符串
-    pub async fn export_to_json(&self) -> Result<PythonExportData, MigrationError> {
-        info!("开始导出数据...");
+    /// 导出数据到输出路径，`-` 表示写入标准输出
+    ///
+    /// `gzip` 为 true 时输出gzip压缩后的JSON
+    pub async fn export_to_output(&self, output: &str, gzip: bool) -> Result<(), MigrationError> {
+        if output == STDOUT_OUTPUT {
+            let stdout = std::io::stdout();
+            self.export_to_writer(stdout.lock(), gzip).await
+        } else if gzip {
+            let mut compressed = Vec::new();
+            self.export_to_writer(&mut compressed, true).await?;
+            FileSink::new(output).write_export(&compressed).await?;
+            Ok(())
+        } else {
+            self.export_to_sink(&mut FileSink::new(output)).await
+        }
+    }
+
+    /// 导出数据到任意输出流
+    pub async fn export_to_writer<W: Write>(
+        &self,
+        mut writer: W,
+        gzip: bool,
+    ) -> Result<(), MigrationError> {
+        let data = self.export_to_json(&ExportFilter::default()).await?;
+
+        if gzip {
+            let mut encoder = flate2::write::GzEncoder::new(writer, flate2::Compression::default());
+            serde_json::to_writer_pretty(&mut encoder, &data)?;
+            encoder.finish()?.flush()?;
+        } else {
+            serde_json::to_writer_pretty(&mut writer, &data)?;
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// 导出数据，并附带当前已生成的配置文件
+    ///
+    /// 配置文件内容使用当前密钥加密后写入导出包，不存在的文件会被跳过
+    pub async fn export_to_json_with_configs(
+        &self,
+        generator: &ConfigGenerator,
+    ) -> Result<PythonExportData, MigrationError> {
+        let mut data = self.export_to_json(&ExportFilter::default()).await?;
+        data.generated_configs = self.export_generated_configs(generator)?;
+        let counts = record_counts(&data);
+        if let Some(metadata) = data.metadata.as_mut() {
+            metadata.record_counts = counts;
+        }
+        Ok(data)
+    }
+
+    /// 导出已生成的配置文件
+    fn export_generated_configs(
+        &self,
+        generator: &ConfigGenerator,
+    ) -> Result<Vec<GeneratedConfigFile>, MigrationError> {
+        let mut files = Vec::new();
+
+        for kind in GeneratedConfigKind::ALL {
+            if let Some(content) = generator.read_config_file(kind)? {
+                files.push(GeneratedConfigFile {
+                    kind,
+                    encrypted_content: self.crypto_service.encrypt(&content)?,
+                });
+            }
+        }
+
+        info!("导出 {} 个配置文件", files.len());
+        Ok(files)
+    }
+
+    /// 导出满足过滤条件的数据，默认过滤条件导出全部数据
+    pub async fn export_to_json(
+        &self,
+        filter: &ExportFilter,
+    ) -> Result<PythonExportData, MigrationError> {
+        info!(filter = ?filter, "开始导出数据...");
 
         let query_builder = QueryBuilder::new(self.db_manager.pool());
 
         // 导出Claude供应商
-        let claude_providers = self.export_claude_providers(&query_builder).await?;
-        let codex_providers = self.export_codex_providers(&query_builder).await?;
-        let agent_guides = self.export_agent_guides(&query_builder).await?;
-        let mcp_servers = self.export_mcp_servers(&query_builder).await?;
-        let common_configs = self.export_common_configs(&query_builder).await?;
+        let claude_providers = self.export_claude_providers(&query_builder, filter).await?;
+        let codex_providers = self.export_codex_providers(&query_builder, filter).await?;
+        let agent_guides = self.export_agent_guides(&query_builder, filter).await?;
+        let mcp_servers = self.export_mcp_servers(&query_builder, filter).await?;
+        let common_configs = self.export_common_configs(&query_builder, filter).await?;
 
-        Ok(PythonExportData {
+        let mut data = PythonExportData {
             version: "2.0.0".to_string(), // Rust版本号
             claude_providers,
             codex_providers,
             agent_guides,
             mcp_servers,
             common_configs,
-        })
+            generated_configs: Vec::new(),
+            schema_version: Some(EXPORT_SCHEMA_VERSION.to_string()),
+            metadata: None,
+            extra: Default::default(),
+        };
+        data.metadata = Some(ExportMetadata::for_export(&data));
+        Ok(data)
+    }
+
+    /// 读取某类数据中满足过滤条件的记录
+    async fn fetch_export_rows(
+        &self,
+        kind: EntityKind,
+        filter: &ExportFilter,
+    ) -> Result<Vec<SqliteRow>, MigrationError> {
+        if !filter.includes(kind) {
+            return Ok(Vec::new());
+        }
+
+        let table = self.table(kind)?;
+        let filter_tags = if filter.tags.is_empty() {
+            false
+        } else {
+            let has_tags = table.has_column("tags").await?;
+            if !has_tags {
+                warn!(table = %table.name(), "数据表不支持标签，忽略标签过滤条件");
+            }
+            has_tags
+        };
+
+        let rows = table.select_all().await?;
+
+        Ok(rows
+            .into_iter()
+            .filter(|row| filter.matches_row(kind, row, filter_tags))
+            .collect())
     }
 
     /// 导出Claude供应商
     async fn export_claude_providers(
         &self,
         _query_builder: &QueryBuilder<'_>,
+        filter: &ExportFilter,
     ) -> Result<Vec<PythonClaudeProvider>, MigrationError> {
-        let rows = sqlx::query("SELECT * FROM claude_providers")
-            .fetch_all(self.db_manager.pool())
-            .await
-            .map_err(|e| {
-                MigrationError::Database(crate::database::DatabaseError::Query(e.to_string()))
-            })?;
+        let rows = self.fetch_export_rows(EntityKind::ClaudeProviders, filter).await?;
 
         let mut providers = Vec::new();
         for row in rows {
@@ -602,8 +1773,12 @@ impl DataMigrationTool {
                 opus_model: row.get("opus_model"),
                 sonnet_model: row.get("sonnet_model"),
                 haiku_model: row.get("haiku_model"),
+                custom_headers: json_column(&row, CUSTOM_HEADERS_COLUMN),
+                accepted_status_codes: json_column(&row, ACCEPTED_STATUS_CODES_COLUMN),
+                model_auto_update: Some(row.get("model_auto_update")),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
+                extra: Default::default(),
             });
         }
 
@@ -614,13 +1789,9 @@ impl DataMigrationTool {
     async fn export_codex_providers(
         &self,
         _query_builder: &QueryBuilder<'_>,
+        filter: &ExportFilter,
     ) -> Result<Vec<PythonCodexProvider>, MigrationError> {
-        let rows = sqlx::query("SELECT * FROM codex_providers")
-            .fetch_all(self.db_manager.pool())
-            .await
-            .map_err(|e| {
-                MigrationError::Database(crate::database::DatabaseError::Query(e.to_string()))
-            })?;
+        let rows = self.fetch_export_rows(EntityKind::CodexProviders, filter).await?;
 
         let mut providers = Vec::new();
         for row in rows {
@@ -637,8 +1808,11 @@ impl DataMigrationTool {
                 token: decrypted_token,
                 r#type: Some(row.get("type")),
                 enabled: Some(row.get("enabled")),
+                custom_headers: json_column(&row, CUSTOM_HEADERS_COLUMN),
+                accepted_status_codes: json_column(&row, ACCEPTED_STATUS_CODES_COLUMN),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
+                extra: Default::default(),
             });
         }
 
@@ -649,13 +1823,9 @@ impl DataMigrationTool {
     async fn export_agent_guides(
         &self,
         _query_builder: &QueryBuilder<'_>,
+        filter: &ExportFilter,
     ) -> Result<Vec<PythonAgentGuide>, MigrationError> {
-        let rows = sqlx::query("SELECT * FROM agent_guides")
-            .fetch_all(self.db_manager.pool())
-            .await
-            .map_err(|e| {
-                MigrationError::Database(crate::database::DatabaseError::Query(e.to_string()))
-            })?;
+        let rows = self.fetch_export_rows(EntityKind::AgentGuides, filter).await?;
 
         let mut guides = Vec::new();
         for row in rows {
@@ -666,6 +1836,7 @@ impl DataMigrationTool {
                 text: row.get("text"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
+                extra: Default::default(),
             });
         }
 
@@ -676,21 +1847,25 @@ impl DataMigrationTool {
     async fn export_mcp_servers(
         &self,
         _query_builder: &QueryBuilder<'_>,
+        filter: &ExportFilter,
     ) -> Result<Vec<PythonMcpServer>, MigrationError> {
-        let rows = sqlx::query("SELECT * FROM mcp_servers")
-            .fetch_all(self.db_manager.pool())
-            .await
-            .map_err(|e| {
-                MigrationError::Database(crate::database::DatabaseError::Query(e.to_string()))
-            })?;
+        let rows = self.fetch_export_rows(EntityKind::McpServers, filter).await?;
 
         let mut servers = Vec::new();
         for row in rows {
             let args_str: String = row.get("args");
             let args: Vec<String> = serde_json::from_str(&args_str).unwrap_or_default();
 
+            let secret_env_keys =
+                parse_secret_keys(row.get::<Option<String>, _>(MCP_SECRET_KEYS_COLUMN).as_deref());
             let env_str: Option<String> = row.get("env");
-            let env = env_str.and_then(|s| serde_json::from_str(&s).ok());
+            let mut env: Option<HashMap<String, String>> =
+                env_str.and_then(|s| serde_json::from_str(&s).ok());
+            if let Some(env) = env.as_mut() {
+                if decrypt_secret_env(env, &secret_env_keys, &self.crypto_service).is_err() {
+                    warn!("无法解密MCP服务器机密环境变量，保持加密状态");
+                }
+            }
 
             servers.push(PythonMcpServer {
                 id: Some(row.get("id")),
@@ -700,8 +1875,10 @@ impl DataMigrationTool {
                 command: row.get("command"),
                 args,
                 env,
+                secret_env_keys,
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
+                extra: Default::default(),
             });
         }
 
@@ -712,13 +1889,9 @@ impl DataMigrationTool {
     async fn export_common_configs(
         &self,
         _query_builder: &QueryBuilder<'_>,
+        filter: &ExportFilter,
     ) -> Result<Vec<PythonCommonConfig>, MigrationError> {
-        let rows = sqlx::query("SELECT * FROM common_configs")
-            .fetch_all(self.db_manager.pool())
-            .await
-            .map_err(|e| {
-                MigrationError::Database(crate::database::DatabaseError::Query(e.to_string()))
-            })?;
+        let rows = self.fetch_export_rows(EntityKind::CommonConfigs, filter).await?;
 
         let mut configs = Vec::new();
         for row in rows {
@@ -731,6 +1904,7 @@ impl DataMigrationTool {
                 is_active: Some(row.get("is_active")),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
+                extra: Default::default(),
             });
         }
 
@@ -757,6 +1931,7 @@ mod tests {
             connect_timeout: Duration::from_secs(10),
             idle_timeout: Duration::from_secs(60),
             max_lifetime: Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = DatabaseManager::new(config).await.unwrap();
@@ -798,13 +1973,21 @@ mod tests {
                 opus_model: Some("claude-3-opus-20240229".to_string()),
                 sonnet_model: Some("claude-3-sonnet-20240229".to_string()),
                 haiku_model: Some("claude-3-haiku-20240307".to_string()),
+                custom_headers: Default::default(),
+                accepted_status_codes: Default::default(),
+                model_auto_update: None,
                 created_at: None,
                 updated_at: None,
+                extra: Default::default(),
             }],
             codex_providers: vec![],
             agent_guides: vec![],
             mcp_servers: vec![],
             common_configs: vec![],
+            generated_configs: vec![],
+            schema_version: None,
+            metadata: None,
+            extra: Default::default(),
         };
 
         // 导入数据
@@ -816,7 +1999,7 @@ mod tests {
         println!("✅ 数据导入测试通过: {:?}", report);
 
         // 导出数据
-        let exported_data = migration_tool.export_to_json().await.unwrap();
+        let exported_data = migration_tool.export_to_json(&ExportFilter::default()).await.unwrap();
 
         assert_eq!(exported_data.claude_providers.len(), 1);
         assert_eq!(
@@ -825,4 +2008,664 @@ mod tests {
         );
         println!("✅ 数据导出测试通过");
     }
+
+    #[tokio::test]
+    async fn test_roundtrip_with_generated_configs() {
+        let (migration_tool, _) = create_test_migration_tool().await;
+
+        let source_dir = tempdir().unwrap();
+        let source = ConfigGenerator::with_dirs(
+            source_dir.path().join(".claude"),
+            source_dir.path().join(".codex"),
+        );
+        let settings = r#"{"env":{"ANTHROPIC_AUTH_TOKEN":"sk-ant-secret"}}"#;
+        source.write_config_file(GeneratedConfigKind::ClaudeSettings, settings).unwrap();
+
+        // 导出时只包含存在的配置文件，且内容已加密
+        let exported = migration_tool.export_to_json_with_configs(&source).await.unwrap();
+        assert_eq!(exported.generated_configs.len(), 1);
+        assert_eq!(
+            exported.generated_configs[0].kind,
+            GeneratedConfigKind::ClaudeSettings
+        );
+
+        let json = serde_json::to_string(&exported).unwrap();
+        assert!(!json.contains("sk-ant-secret"));
+
+        // 导入到新的目录后配置文件被恢复
+        let target_dir = tempdir().unwrap();
+        let target = ConfigGenerator::with_dirs(
+            target_dir.path().join(".claude"),
+            target_dir.path().join(".codex"),
+        );
+        let report = migration_tool.import_from_json_with_configs(&json, &target).await.unwrap();
+
+        assert_eq!(report.generated_configs, 1);
+        assert_eq!(
+            target.read_config_file(GeneratedConfigKind::ClaudeSettings).unwrap().as_deref(),
+            Some(settings)
+        );
+        assert!(target.read_config_file(GeneratedConfigKind::CodexAuth).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_migration_runs_are_persisted() {
+        let (migration_tool, _) = create_test_migration_tool().await;
+
+        let test_data = PythonExportData {
+            version: "1.0.0".to_string(),
+            claude_providers: vec![],
+            codex_providers: vec![],
+            agent_guides: vec![PythonAgentGuide {
+                id: None,
+                name: "迁移记录测试".to_string(),
+                r#type: "only".to_string(),
+                text: "测试内容".to_string(),
+                created_at: None,
+                updated_at: None,
+                extra: Default::default(),
+            }],
+            mcp_servers: vec![],
+            common_configs: vec![],
+            generated_configs: vec![],
+            schema_version: None,
+            metadata: None,
+            extra: Default::default(),
+        };
+        let json = serde_json::to_string(&test_data).unwrap();
+
+        migration_tool.import_from_json(&json).await.unwrap();
+        migration_tool.import_from_json(&json).await.unwrap();
+
+        let runs = migration_tool.list_migration_runs().await.unwrap();
+        assert_eq!(runs.len(), 2);
+        assert!(runs[0].id > runs[1].id);
+        assert_eq!(runs[0].source, "json");
+        assert_eq!(runs[0].agent_guides, 1);
+        assert_eq!(runs[0].errors_json, "[]");
+    }
+
+    #[tokio::test]
+    async fn test_export_to_writer_plain_and_gzip() {
+        let (migration_tool, _) = create_test_migration_tool().await;
+
+        let test_data = PythonExportData {
+            version: "1.0.0".to_string(),
+            claude_providers: vec![],
+            codex_providers: vec![],
+            agent_guides: vec![PythonAgentGuide {
+                id: None,
+                name: "标准输出导出".to_string(),
+                r#type: "only".to_string(),
+                text: "测试内容".to_string(),
+                created_at: None,
+                updated_at: None,
+                extra: Default::default(),
+            }],
+            mcp_servers: vec![],
+            common_configs: vec![],
+            generated_configs: vec![],
+            schema_version: None,
+            metadata: None,
+            extra: Default::default(),
+        };
+        migration_tool
+            .import_from_json(&serde_json::to_string(&test_data).unwrap())
+            .await
+            .unwrap();
+
+        let mut stdout = Vec::new();
+        migration_tool.export_to_writer(&mut stdout, false).await.unwrap();
+        let exported: PythonExportData = serde_json::from_slice(&stdout).unwrap();
+        assert_eq!(exported.agent_guides.len(), 1);
+        assert_eq!(exported.agent_guides[0].name, "标准输出导出");
+
+        let mut compressed = Vec::new();
+        migration_tool.export_to_writer(&mut compressed, true).await.unwrap();
+        let decoder = flate2::read::GzDecoder::new(compressed.as_slice());
+        let exported: PythonExportData = serde_json::from_reader(decoder).unwrap();
+        assert_eq!(exported.agent_guides.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_export_to_memory_sink_matches_file_export() {
+        let (migration_tool, _) = create_test_migration_tool().await;
+
+        let test_data = PythonExportData {
+            version: "1.0.0".to_string(),
+            claude_providers: vec![],
+            codex_providers: vec![],
+            agent_guides: vec![PythonAgentGuide {
+                id: None,
+                name: "内存导出".to_string(),
+                r#type: "only".to_string(),
+                text: "测试内容".to_string(),
+                created_at: None,
+                updated_at: None,
+                extra: Default::default(),
+            }],
+            mcp_servers: vec![],
+            common_configs: vec![],
+            generated_configs: vec![],
+            schema_version: None,
+            metadata: None,
+            extra: Default::default(),
+        };
+        migration_tool
+            .import_from_json(&serde_json::to_string(&test_data).unwrap())
+            .await
+            .unwrap();
+
+        let mut memory = Vec::new();
+        migration_tool.export_to_sink(&mut memory).await.unwrap();
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("export.json");
+        migration_tool.export_to_json_file(&file_path).await.unwrap();
+        let file = std::fs::read(&file_path).unwrap();
+
+        // 两次导出只有导出时间不同
+        let without_timestamp = |bytes: &[u8]| -> Vec<String> {
+            String::from_utf8(bytes.to_vec())
+                .unwrap()
+                .lines()
+                .filter(|line| !line.contains("\"exported_at\""))
+                .map(str::to_string)
+                .collect()
+        };
+        assert!(!memory.is_empty());
+        assert_eq!(without_timestamp(&memory), without_timestamp(&file));
+        let exported: PythonExportData = serde_json::from_slice(&memory).unwrap();
+        assert_eq!(exported.agent_guides[0].name, "内存导出");
+    }
+
+    #[tokio::test]
+    async fn test_merge_import_updates_existing_rows() {
+        let (migration_tool, _) = create_test_migration_tool().await;
+
+        let provider = |name: &str, url: &str| PythonClaudeProvider {
+            id: None,
+            name: name.to_string(),
+            url: url.to_string(),
+            token: format!("sk-ant-{}", name),
+            timeout: Some(30000),
+            auto_update: Some(1),
+            r#type: Some("public_welfare".to_string()),
+            enabled: Some(0),
+            opus_model: Some("claude-opus".to_string()),
+            sonnet_model: None,
+            haiku_model: None,
+            custom_headers: Default::default(),
+            accepted_status_codes: Default::default(),
+            model_auto_update: None,
+            created_at: None,
+            updated_at: None,
+            extra: Default::default(),
+        };
+        let initial = PythonExportData {
+            version: "1.0.0".to_string(),
+            claude_providers: vec![
+                provider("first", "https://first.example.com"),
+                provider("second", "https://second.example.com"),
+            ],
+            codex_providers: vec![],
+            agent_guides: vec![],
+            mcp_servers: vec![],
+            common_configs: vec![PythonCommonConfig {
+                id: None,
+                key: "theme".to_string(),
+                value: "dark".to_string(),
+                description: None,
+                category: None,
+                is_active: None,
+                created_at: None,
+                updated_at: None,
+                extra: Default::default(),
+            }],
+            generated_configs: vec![],
+            schema_version: None,
+            metadata: None,
+            extra: Default::default(),
+        };
+        migration_tool
+            .import_from_json(&serde_json::to_string(&initial).unwrap())
+            .await
+            .unwrap();
+
+        let before = migration_tool.export_to_json(&ExportFilter::default()).await.unwrap();
+        let first_before =
+            before.claude_providers.iter().find(|p| p.name == "first").unwrap().clone();
+
+        // 修改一个供应商并新增一个，第三个供应商和配置不出现在变化中
+        let mut merged = initial.clone();
+        merged.claude_providers[0].url = "https://first-updated.example.com".to_string();
+        merged.claude_providers.push(provider("third", "https://third.example.com"));
+
+        let report = migration_tool
+            .import_from_json_with_options(
+                &serde_json::to_string(&merged).unwrap(),
+                &ImportOptions { merge: true, ..Default::default() },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(report.created, 1);
+        assert_eq!(report.updated, 1);
+        assert_eq!(report.unchanged, 2);
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+        let after = migration_tool.export_to_json(&ExportFilter::default()).await.unwrap();
+        assert_eq!(after.claude_providers.len(), 3);
+        assert_eq!(after.common_configs.len(), 1);
+
+        let first_after = after.claude_providers.iter().find(|p| p.name == "first").unwrap();
+        assert_eq!(first_after.id, first_before.id);
+        assert_eq!(first_after.created_at, first_before.created_at);
+        assert_eq!(first_after.url, "https://first-updated.example.com");
+        assert_eq!(first_after.token, "sk-ant-first");
+    }
+
+    #[tokio::test]
+    async fn test_diff_against_db_reports_changes() {
+        let (migration_tool, _) = create_test_migration_tool().await;
+
+        let provider = |name: &str, url: &str| PythonClaudeProvider {
+            id: None,
+            name: name.to_string(),
+            url: url.to_string(),
+            token: format!("sk-ant-{}", name),
+            timeout: Some(30000),
+            auto_update: Some(1),
+            r#type: Some("public_welfare".to_string()),
+            enabled: Some(0),
+            opus_model: None,
+            sonnet_model: None,
+            haiku_model: None,
+            custom_headers: Default::default(),
+            accepted_status_codes: Default::default(),
+            model_auto_update: None,
+            created_at: None,
+            updated_at: None,
+            extra: Default::default(),
+        };
+        let seeded = PythonExportData {
+            version: "1.0.0".to_string(),
+            claude_providers: vec![
+                provider("first", "https://first.example.com"),
+                provider("second", "https://second.example.com"),
+            ],
+            codex_providers: vec![],
+            agent_guides: vec![],
+            mcp_servers: vec![],
+            common_configs: vec![],
+            generated_configs: vec![],
+            schema_version: None,
+            metadata: None,
+            extra: Default::default(),
+        };
+        migration_tool
+            .import_from_json(&serde_json::to_string(&seeded).unwrap())
+            .await
+            .unwrap();
+
+        // 修改第一个供应商的URL和Token，新增第三个，第二个不在导出数据中
+        let mut source = seeded.clone();
+        source.claude_providers[0].url = "https://first-updated.example.com".to_string();
+        source.claude_providers[0].token = "sk-ant-rotated".to_string();
+        source.claude_providers[1] = provider("third", "https://third.example.com");
+
+        let diff = migration_tool.diff_against_db(&source).await.unwrap();
+        assert!(diff.has_changes());
+
+        let claude = diff.entity(EntityKind::ClaudeProviders).unwrap();
+        assert_eq!(claude.added, vec!["third".to_string()]);
+        assert_eq!(claude.absent_from_source, vec!["second".to_string()]);
+        assert_eq!(claude.updated.len(), 1);
+        let update = &claude.updated[0];
+        assert_eq!(update.key, "first");
+        let fields: Vec<&str> = update.changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["url", "token"]);
+        assert_eq!(
+            update.changes[0].current.as_deref(),
+            Some("https://first.example.com")
+        );
+        assert_eq!(
+            update.changes[0].incoming.as_deref(),
+            Some("https://first-updated.example.com")
+        );
+        // 机密字段只标记为已变化
+        assert!(update.changes[1].secret);
+        assert!(update.changes[1].current.is_none());
+        assert!(update.changes[1].incoming.is_none());
+        assert!(!serde_json::to_string(&diff).unwrap().contains("sk-ant-"));
+
+        let codex = diff.entity(EntityKind::CodexProviders).unwrap();
+        assert!(codex.added.is_empty() && codex.updated.is_empty());
+
+        // 对比不修改数据库
+        let exported = migration_tool.export_to_json(&ExportFilter::default()).await.unwrap();
+        assert_eq!(exported.claude_providers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_tolerates_unknown_fields_from_newer_version() {
+        let (migration_tool, _) = create_test_migration_tool().await;
+
+        let json = serde_json::json!({
+            "version": "2.0.0",
+            "schema_version": "2.9",
+            "exported_by": "future-app",
+            "claude_providers": [{
+                "id": null,
+                "name": "Future Provider",
+                "url": "https://api.anthropic.com",
+                "token": "sk-ant-future",
+                "timeout": 30000,
+                "auto_update": 1,
+                "type": "public_welfare",
+                "enabled": 1,
+                "opus_model": null,
+                "sonnet_model": null,
+                "haiku_model": null,
+                "created_at": null,
+                "updated_at": null,
+                "region": "us-east-1"
+            }],
+            "codex_providers": [],
+            "agent_guides": [],
+            "mcp_servers": [],
+            "common_configs": []
+        });
+
+        let report = migration_tool.import_from_json(&json.to_string()).await.unwrap();
+
+        assert_eq!(report.claude_providers, 1);
+        assert!(
+            report.warnings.iter().any(|w| w.contains("2.9")),
+            "{:?}",
+            report.warnings
+        );
+        assert!(
+            report
+                .warnings
+                .iter()
+                .any(|w| w.contains("claude_providers") && w.contains("region")),
+            "{:?}",
+            report.warnings
+        );
+        assert!(report.warnings.iter().any(|w| w.contains("exported_by")));
+
+        // 已知的必填字段仍然校验
+        let mut missing_name = json.clone();
+        missing_name["claude_providers"][0].as_object_mut().unwrap().remove("name");
+        assert!(matches!(
+            migration_tool.import_from_json(&missing_name.to_string()).await,
+            Err(MigrationError::Json(_))
+        ));
+
+        // 主版本更高时拒绝导入
+        let mut newer_major = json.clone();
+        newer_major["schema_version"] = serde_json::json!("3.0");
+        assert!(matches!(
+            migration_tool.import_from_json(&newer_major.to_string()).await,
+            Err(MigrationError::VersionMismatch(_))
+        ));
+
+        let exported = migration_tool.export_to_json(&ExportFilter::default()).await.unwrap();
+        assert_eq!(
+            exported.schema_version.as_deref(),
+            Some(EXPORT_SCHEMA_VERSION)
+        );
+        assert!(exported.claude_providers[0].extra.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_converts_legacy_timeout_seconds() {
+        let (migration_tool, _) = create_test_migration_tool().await;
+
+        let json = serde_json::json!({
+            "version": "1.0.0",
+            "claude_providers": [
+                { "name": "seconds", "url": "https://a.example.com", "token": "sk-a", "timeout": 30 },
+                { "name": "millis", "url": "https://b.example.com", "token": "sk-b", "timeout": 45000 },
+                { "name": "missing", "url": "https://c.example.com", "token": "sk-c" }
+            ],
+            "codex_providers": [],
+            "agent_guides": [],
+            "mcp_servers": [
+                { "name": "mcp", "timeout": 60, "command": "npx", "args": [], "env": null }
+            ],
+            "common_configs": []
+        });
+        migration_tool.import_from_json(&json.to_string()).await.unwrap();
+
+        let exported = migration_tool.export_to_json(&ExportFilter::default()).await.unwrap();
+        let timeout =
+            |name: &str| exported.claude_providers.iter().find(|p| p.name == name).unwrap().timeout;
+        assert_eq!(timeout("seconds"), Some(30_000));
+        assert_eq!(timeout("millis"), Some(45_000));
+        assert_eq!(timeout("missing"), Some(crate::models::DEFAULT_TIMEOUT_MS));
+        assert_eq!(exported.mcp_servers[0].timeout, Some(60_000));
+    }
+
+    #[tokio::test]
+    async fn test_import_own_export_keeps_millis_and_warns_out_of_range() {
+        let (migration_tool, _) = create_test_migration_tool().await;
+
+        let json = serde_json::json!({
+            "version": "2.0.0",
+            "schema_version": EXPORT_SCHEMA_VERSION,
+            "claude_providers": [
+                { "name": "short", "url": "https://a.example.com", "token": "sk-a", "timeout": 500 },
+                { "name": "invalid", "url": "https://b.example.com", "token": "sk-b", "timeout": 30 }
+            ],
+            "codex_providers": [],
+            "agent_guides": [],
+            "mcp_servers": [
+                { "name": "mcp", "timeout": 60, "command": "npx", "args": [], "env": null }
+            ],
+            "common_configs": []
+        });
+        let report = migration_tool.import_from_json(&json.to_string()).await.unwrap();
+
+        // 本工具导出的超时时间已是毫秒，不再按秒换算
+        let exported = migration_tool.export_to_json(&ExportFilter::default()).await.unwrap();
+        let timeout =
+            |name: &str| exported.claude_providers.iter().find(|p| p.name == name).unwrap().timeout;
+        assert_eq!(timeout("short"), Some(500));
+        assert_eq!(timeout("invalid"), Some(DEFAULT_TIMEOUT_MS));
+        assert_eq!(exported.mcp_servers[0].timeout, Some(DEFAULT_TIMEOUT_MS));
+
+        // 超出范围的值作为警告报告
+        assert_eq!(
+            report.warnings.iter().filter(|w| w.contains("超时时间无效")).count(),
+            2,
+            "{:?}",
+            report.warnings
+        );
+        assert!(report.warnings.iter().any(|w| w.contains("invalid")));
+        assert!(report.warnings.iter().any(|w| w.contains("mcp")));
+    }
+
+    #[tokio::test]
+    async fn test_export_filter_only_enabled_providers() {
+        let (migration_tool, _) = create_test_migration_tool().await;
+
+        let provider = |name: &str, enabled: i64| {
+            serde_json::json!({
+                "name": name,
+                "url": format!("https://{}.example.com", name),
+                "token": format!("sk-{}", name),
+                "enabled": enabled,
+            })
+        };
+        let json = serde_json::json!({
+            "version": "1.0.0",
+            "claude_providers": [provider("prod", 1), provider("staging", 0)],
+            "codex_providers": [provider("codex-prod", 1), provider("codex-dev", 0)],
+            "agent_guides": [{"name": "guide", "type": "only", "text": "内容"}],
+            "mcp_servers": [],
+            "common_configs": [],
+        });
+        migration_tool.import_from_json(&json.to_string()).await.unwrap();
+
+        let everything = migration_tool.export_to_json(&ExportFilter::default()).await.unwrap();
+        assert_eq!(everything.claude_providers.len(), 2);
+        assert_eq!(everything.agent_guides.len(), 1);
+
+        let filter = ExportFilter {
+            // 当前数据表没有标签列，标签条件被忽略
+            tags: vec!["production".to_string()],
+            enabled: Some(true),
+            entities: vec![EntityKind::ClaudeProviders, EntityKind::CodexProviders],
+        };
+        let exported = migration_tool.export_to_json(&filter).await.unwrap();
+
+        let names: Vec<&str> = exported
+            .claude_providers
+            .iter()
+            .map(|p| p.name.as_str())
+            .chain(exported.codex_providers.iter().map(|p| p.name.as_str()))
+            .collect();
+        assert_eq!(names, vec!["prod", "codex-prod"]);
+        assert!(exported.agent_guides.is_empty());
+        assert_eq!(exported.claude_providers[0].token, "sk-prod");
+    }
+
+    #[tokio::test]
+    async fn test_export_metadata_roundtrip_and_major_check() {
+        let (migration_tool, _) = create_test_migration_tool().await;
+
+        let json = serde_json::json!({
+            "version": "1.0.0",
+            "claude_providers": [{"name": "claude", "url": "https://api.anthropic.com", "token": "sk-ant"}],
+            "codex_providers": [],
+            "agent_guides": [{"name": "guide", "type": "only", "text": "内容"}],
+            "mcp_servers": [],
+            "common_configs": [],
+        });
+        migration_tool.import_from_json(&json.to_string()).await.unwrap();
+
+        let exported = migration_tool.export_to_json(&ExportFilter::default()).await.unwrap();
+        let metadata = exported.metadata.clone().unwrap();
+        assert_eq!(metadata.app_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(metadata.schema_version, EXPORT_SCHEMA_VERSION);
+        assert_eq!(metadata.source_os, std::env::consts::OS);
+        assert_eq!(metadata.record_counts["claude_providers"], 1);
+        assert_eq!(metadata.record_counts["agent_guides"], 1);
+        assert_eq!(metadata.record_counts["mcp_servers"], 0);
+
+        // 元信息序列化后原样读回，顶层 version 保持不变
+        let serialized = serde_json::to_string(&exported).unwrap();
+        let parsed: PythonExportData = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(parsed.version, "2.0.0");
+        assert_eq!(parsed.metadata.as_ref(), Some(&metadata));
+
+        let report = migration_tool.import_from_json(&serialized).await.unwrap();
+        assert_eq!(report.claude_providers, 1);
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+
+        // 记录数不一致只警告
+        let mut miscounted = parsed.clone();
+        miscounted
+            .metadata
+            .as_mut()
+            .unwrap()
+            .record_counts
+            .insert("claude_providers".to_string(), 5);
+        let report = migration_tool
+            .import_from_json(&serde_json::to_string(&miscounted).unwrap())
+            .await
+            .unwrap();
+        assert!(
+            report
+                .warnings
+                .iter()
+                .any(|w| w.contains("claude_providers") && w.contains('5')),
+            "{:?}",
+            report.warnings
+        );
+
+        // 元信息中的主版本更高时拒绝导入
+        let mut incompatible = parsed;
+        incompatible.metadata.as_mut().unwrap().schema_version = "99.0".to_string();
+        assert!(matches!(
+            migration_tool
+                .import_from_json(&serde_json::to_string(&incompatible).unwrap())
+                .await,
+            Err(MigrationError::VersionMismatch(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_import_file_with_invalid_utf8() {
+        let (migration_tool, _) = create_test_migration_tool().await;
+        let temp_dir = tempdir().unwrap();
+        let file_path = temp_dir.path().join("latin1_export.json");
+
+        // "Caf\xe9" 是latin-1编码的 "Café"
+        let mut content = br#"{"version":"1.0.0","claude_providers":[],"codex_providers":[],"agent_guides":[{"name":"guide","type":"only","text":"Caf"#.to_vec();
+        content.push(0xE9);
+        content.extend_from_slice(br#""}],"mcp_servers":[],"common_configs":[]}"#);
+        std::fs::write(&file_path, &content).unwrap();
+
+        let report = migration_tool.import_from_json_file(&file_path).await.unwrap();
+        assert_eq!(report.agent_guides, 1);
+        assert!(
+            report.warnings.iter().any(|w| w.contains("agent_guides[0].text")),
+            "{:?}",
+            report.warnings
+        );
+        let exported = migration_tool.export_to_json(&ExportFilter::default()).await.unwrap();
+        assert_eq!(exported.agent_guides[0].text, "Caf\u{FFFD}");
+
+        let strict = ImportOptions { strict_utf8: true, ..Default::default() };
+        assert!(matches!(
+            migration_tool.import_from_json_file_with_options(&file_path, &strict).await,
+            Err(MigrationError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_import_preflight_insufficient_space() {
+        struct NoSpace;
+
+        impl DiskSpaceChecker for NoSpace {
+            fn available_space(&self, _path: &Path) -> std::io::Result<u64> {
+                Ok(1024)
+            }
+        }
+
+        // 检查报告本身由 preflight 模块的单元测试覆盖，这里只验证导入路径会拒绝且不写入数据
+        let (migration_tool, db_manager) = create_test_migration_tool().await;
+        db_manager.ensure_initialized().await.unwrap();
+        let temp_dir = tempdir().unwrap();
+        let migration_tool = migration_tool
+            .with_disk_space_checker(Arc::new(NoSpace))
+            .with_backup_dir(temp_dir.path().join("backups"));
+
+        let file_path = temp_dir.path().join("export.json");
+        std::fs::write(
+            &file_path,
+            br#"{"version":"1.0.0","claude_providers":[{"name":"p","url":"https://api.example.com","token":"sk-test"}],"codex_providers":[],"agent_guides":[],"mcp_servers":[],"common_configs":[]}"#,
+        )
+        .unwrap();
+        let options = ImportOptions { preflight: true, ..Default::default() };
+        assert!(matches!(
+            migration_tool.import_from_json_file_with_options(&file_path, &options).await,
+            Err(MigrationError::PreflightFailed(_))
+        ));
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM claude_providers")
+            .fetch_one(db_manager.pool())
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(parse_tags(r#"["prod", "eu"]"#), vec!["prod", "eu"]);
+        assert_eq!(parse_tags("prod, eu ,"), vec!["prod", "eu"]);
+        assert!(parse_tags("").is_empty());
+    }
 }
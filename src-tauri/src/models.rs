@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
 // chrono 在将来的时间处理功能中会用到
 
 // Claude供应商数据模型
@@ -8,18 +9,226 @@ pub struct ClaudeProvider {
     pub id: i64,
     pub name: String,
     pub url: String,
-    pub token: String, // 加密存储
-    pub timeout: Option<i64>,
+    pub token: String,            // 加密存储
+    pub timeout: Option<i64>,     // 毫秒
     pub auto_update: Option<i64>, // 1-禁用遥测，0-启用遥测
     pub r#type: String,           // paid 或 public_welfare
     pub enabled: i64,             // 0-未启用，1-启用
     pub opus_model: Option<String>,
     pub sonnet_model: Option<String>,
     pub haiku_model: Option<String>,
+    #[sqlx(json)]
+    #[serde(default)]
+    pub models: HashMap<String, String>, // 角色 -> 模型名称，JSON存储
+    #[sqlx(json)]
+    #[serde(default)]
+    pub custom_headers: HashMap<String, String>, // 请求头名称 -> 值，JSON存储
+    #[sqlx(json)]
+    #[serde(default)]
+    pub accepted_status_codes: Vec<u16>, // 连接测试视为可用的状态码，为空时接受2xx，JSON存储
+    #[serde(default)]
+    pub model_auto_update: i64, // 1-定期从上游模型列表更新模型，0-不更新
     pub created_at: Option<String>, // ISO 8601 字符串
     pub updated_at: Option<String>, // ISO 8601 字符串
 }
 
+// 旧版本固定的三个模型角色
+pub const MODEL_ROLE_OPUS: &str = "opus";
+pub const MODEL_ROLE_SONNET: &str = "sonnet";
+pub const MODEL_ROLE_HAIKU: &str = "haiku";
+
+// 各文本字段允许的最大长度（按字符计算）
+// 数据库中这些列都是 TEXT，长度限制统一在写入前校验
+pub const MAX_NAME_LENGTH: usize = 100;
+pub const MAX_URL_LENGTH: usize = 2048;
+pub const MAX_TOKEN_LENGTH: usize = 4096;
+pub const MAX_MODEL_NAME_LENGTH: usize = 200;
+pub const MAX_AGENT_GUIDE_NAME_LENGTH: usize = 200;
+pub const MAX_AGENT_GUIDE_TEXT_LENGTH: usize = 100_000;
+pub const MAX_COMMAND_LENGTH: usize = 1024;
+pub const MAX_CONFIG_KEY_LENGTH: usize = 100;
+pub const MAX_CONFIG_VALUE_LENGTH: usize = 10_000;
+pub const MAX_DESCRIPTION_LENGTH: usize = 1000;
+pub const MAX_CATEGORY_LENGTH: usize = 50;
+pub const MAX_SEARCH_TERM_LENGTH: usize = 100;
+
+// 所有实体的 `timeout` 字段统一以毫秒为单位
+pub const DEFAULT_TIMEOUT_MS: i64 = 30_000;
+pub const MIN_TIMEOUT_MS: i64 = 100;
+pub const MAX_TIMEOUT_MS: i64 = 60 * 60 * 1000;
+// 旧数据中小于该值的超时时间按秒保存（Python版本的表结构默认值为30秒）
+pub const LEGACY_SECONDS_THRESHOLD: i64 = 1000;
+
+/// 超时时间（毫秒），取值范围为 `MIN_TIMEOUT_MS..=MAX_TIMEOUT_MS`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "i64", into = "i64")]
+pub struct TimeoutMs(i64);
+
+impl TimeoutMs {
+    /// 校验毫秒数是否在允许范围内
+    pub fn new(millis: i64) -> Result<Self, String> {
+        if (MIN_TIMEOUT_MS..=MAX_TIMEOUT_MS).contains(&millis) {
+            Ok(Self(millis))
+        } else {
+            Err(format!(
+                "超时时间必须在 {} 到 {} 毫秒之间，当前为 {}",
+                MIN_TIMEOUT_MS, MAX_TIMEOUT_MS, millis
+            ))
+        }
+    }
+
+    /// 解析Python版本数据中的超时时间
+    ///
+    /// 小于 `LEGACY_SECONDS_THRESHOLD` 的正数视为秒并换算为毫秒，缺失或无效时使用默认值
+    pub fn from_legacy(value: Option<i64>) -> Self {
+        value
+            .map(Self::legacy_seconds_to_millis)
+            .and_then(|millis| Self::new(millis).ok())
+            .unwrap_or_default()
+    }
+
+    /// 将Python版本中按秒保存的超时时间换算为毫秒，其他值原样返回
+    pub fn legacy_seconds_to_millis(value: i64) -> i64 {
+        if (1..LEGACY_SECONDS_THRESHOLD).contains(&value) {
+            value * 1000
+        } else {
+            value
+        }
+    }
+
+    pub fn as_millis(self) -> i64 {
+        self.0
+    }
+
+    pub fn as_duration(self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.0 as u64)
+    }
+}
+
+impl Default for TimeoutMs {
+    fn default() -> Self {
+        Self(DEFAULT_TIMEOUT_MS)
+    }
+}
+
+impl TryFrom<i64> for TimeoutMs {
+    type Error = String;
+
+    fn try_from(millis: i64) -> Result<Self, Self::Error> {
+        Self::new(millis)
+    }
+}
+
+impl From<TimeoutMs> for i64 {
+    fn from(timeout: TimeoutMs) -> Self {
+        timeout.0
+    }
+}
+
+impl ClaudeProvider {
+    /// 获取完整的模型映射，旧的三个模型字段作为补充
+    pub fn effective_models(&self) -> HashMap<String, String> {
+        let mut models = merge_legacy_models(
+            HashMap::new(),
+            &self.opus_model,
+            &self.sonnet_model,
+            &self.haiku_model,
+        );
+        models.extend(self.models.clone());
+        models
+    }
+
+    /// 获取指定角色的模型
+    pub fn model(&self, role: &str) -> Option<String> {
+        self.effective_models().remove(role)
+    }
+}
+
+/// 将旧的三个模型字段写入模型映射
+///
+/// `Some("")` 表示移除该角色，`None` 表示保持不变
+pub fn merge_legacy_models(
+    mut models: HashMap<String, String>,
+    opus_model: &Option<String>,
+    sonnet_model: &Option<String>,
+    haiku_model: &Option<String>,
+) -> HashMap<String, String> {
+    let legacy = [
+        (MODEL_ROLE_OPUS, opus_model),
+        (MODEL_ROLE_SONNET, sonnet_model),
+        (MODEL_ROLE_HAIKU, haiku_model),
+    ];
+    for (role, model) in legacy {
+        match model.as_deref() {
+            Some("") => {
+                models.remove(role);
+            }
+            Some(model) => {
+                models.insert(role.to_string(), model.to_string());
+            }
+            None => {}
+        }
+    }
+    models
+}
+
+// 字段改名记录
+//
+// 重命名模型字段时，旧名称必须通过 `#[serde(alias = "...")]` 保留，并登记在 `FIELD_ALIASES` 中，
+// 否则旧版本的导出和备份数据会被当作未知字段忽略。
+// `tests/export_fixture_compat_test.rs` 使用各历史版本的导出样本校验登记的别名
+
+/// 字段的历史名称
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldAlias {
+    /// 所属数据表
+    pub table: &'static str,
+    /// 当前字段名
+    pub field: &'static str,
+    /// 旧字段名
+    pub alias: &'static str,
+    /// 开始使用当前字段名的导出格式版本
+    pub renamed_in: &'static str,
+}
+
+/// 所有字段改名记录
+///
+/// 目前导出数据中还没有改名的字段：Python版本和各版本的Rust导出都使用相同的字段名
+pub const FIELD_ALIASES: &[FieldAlias] = &[];
+
+/// 数据表的字段改名记录
+pub fn field_aliases(table: &str) -> impl Iterator<Item = &'static FieldAlias> + '_ {
+    FIELD_ALIASES.iter().filter(move |alias| alias.table == table)
+}
+
+/// 将记录中登记过的旧字段名改为当前名称，返回被改名或丢弃的旧字段
+pub fn rename_legacy_fields(
+    table: &str,
+    record: &mut serde_json::Map<String, serde_json::Value>,
+) -> Vec<&'static str> {
+    rename_fields(field_aliases(table), record)
+}
+
+/// 按改名记录修改字段名
+///
+/// 新旧字段同时存在时以当前字段为准，避免反序列化时报重复字段
+fn rename_fields<'a>(
+    aliases: impl Iterator<Item = &'a FieldAlias>,
+    record: &mut serde_json::Map<String, serde_json::Value>,
+) -> Vec<&'static str> {
+    let mut renamed = Vec::new();
+    for alias in aliases {
+        if let Some(value) = record.remove(alias.alias) {
+            record.entry(alias.field).or_insert(value);
+            renamed.push(alias.alias);
+        }
+    }
+    renamed
+}
+
+// 请求结构中的类型字段统一使用 `#[serde(rename = "type", alias = "provider_type")]`：
+// JSON中使用 `type`，同时兼容旧版前端发送的 `provider_type`
+
 // 创建Claude供应商的请求结构
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateClaudeProviderRequest {
@@ -28,10 +237,37 @@ pub struct CreateClaudeProviderRequest {
     pub token: String,
     pub timeout: Option<i64>,
     pub auto_update: Option<i64>,
+    #[serde(rename = "type", alias = "provider_type")]
     pub r#type: Option<String>,
     pub opus_model: Option<String>,
     pub sonnet_model: Option<String>,
     pub haiku_model: Option<String>,
+    #[serde(default)]
+    pub models: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub custom_headers: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub accepted_status_codes: Option<Vec<u16>>,
+    #[serde(default)]
+    pub model_auto_update: Option<i64>,
+}
+
+// 测试未保存的供应商凭据的请求结构，Token在请求结束后清零
+#[derive(Deserialize)]
+pub struct TestProviderCredentialsRequest {
+    pub url: String,
+    pub token: zeroize::Zeroizing<String>,
+    pub timeout: Option<i64>,
+}
+
+impl std::fmt::Debug for TestProviderCredentialsRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestProviderCredentialsRequest")
+            .field("url", &self.url)
+            .field("token", &"***")
+            .field("timeout", &self.timeout)
+            .finish()
+    }
 }
 
 // 更新Claude供应商的请求结构
@@ -42,11 +278,20 @@ pub struct UpdateClaudeProviderRequest {
     pub token: Option<String>,
     pub timeout: Option<i64>,
     pub auto_update: Option<i64>,
+    #[serde(rename = "type", alias = "provider_type")]
     pub r#type: Option<String>,
     pub enabled: Option<i64>,
     pub opus_model: Option<String>,
     pub sonnet_model: Option<String>,
     pub haiku_model: Option<String>,
+    #[serde(default)]
+    pub models: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub custom_headers: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub accepted_status_codes: Option<Vec<u16>>,
+    #[serde(default)]
+    pub model_auto_update: Option<i64>,
 }
 
 // Codex供应商数据模型
@@ -58,6 +303,12 @@ pub struct CodexProvider {
     pub token: String,  // 加密存储
     pub r#type: String, // paid 或 public_welfare
     pub enabled: i64,   // 0-未启用，1-启用
+    #[sqlx(json)]
+    #[serde(default)]
+    pub custom_headers: HashMap<String, String>, // 请求头名称 -> 值，JSON存储
+    #[sqlx(json)]
+    #[serde(default)]
+    pub accepted_status_codes: Vec<u16>, // 连接测试视为可用的状态码，为空时接受2xx，JSON存储
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
@@ -68,7 +319,12 @@ pub struct CreateCodexProviderRequest {
     pub name: String,
     pub url: String,
     pub token: String,
+    #[serde(rename = "type", alias = "provider_type")]
     pub r#type: Option<String>,
+    #[serde(default)]
+    pub custom_headers: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub accepted_status_codes: Option<Vec<u16>>,
 }
 
 // 更新Codex供应商的请求结构
@@ -77,8 +333,13 @@ pub struct UpdateCodexProviderRequest {
     pub name: Option<String>,
     pub url: Option<String>,
     pub token: Option<String>,
+    #[serde(rename = "type", alias = "provider_type")]
     pub r#type: Option<String>,
     pub enabled: Option<i64>,
+    #[serde(default)]
+    pub custom_headers: Option<HashMap<String, String>>,
+    #[serde(default)]
+    pub accepted_status_codes: Option<Vec<u16>>,
 }
 
 // Agent指导文件数据模型
@@ -96,6 +357,7 @@ pub struct AgentGuide {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateAgentGuideRequest {
     pub name: String,
+    #[serde(rename = "type", alias = "provider_type")]
     pub r#type: String,
     pub text: String,
 }
@@ -104,6 +366,7 @@ pub struct CreateAgentGuideRequest {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateAgentGuideRequest {
     pub name: Option<String>,
+    #[serde(rename = "type", alias = "provider_type")]
     pub r#type: Option<String>,
     pub text: Option<String>,
 }
@@ -114,10 +377,13 @@ pub struct McpServer {
     pub id: i64,
     pub name: String,
     pub r#type: Option<String>, // stdio, sse等
-    pub timeout: Option<i64>,   // 默认30000ms
+    pub timeout: Option<i64>,   // 毫秒，默认30000
     pub command: String,        // 命令，如npx, uvx, python等
     pub args: String,           // 命令参数，存储为JSON字符串
     pub env: Option<String>,    // 环境变量，存储为JSON字符串
+    #[sqlx(json)]
+    #[serde(default)]
+    pub secret_env_keys: Vec<String>, // 值需要加密存储的环境变量名，JSON存储
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
@@ -126,22 +392,30 @@ pub struct McpServer {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateMcpServerRequest {
     pub name: String,
+    #[serde(rename = "type", alias = "provider_type")]
     pub r#type: Option<String>,
     pub timeout: Option<i64>,
     pub command: String,
     pub args: Vec<String>,
     pub env: Option<std::collections::HashMap<String, String>>,
+    /// 值需要加密存储的环境变量名
+    #[serde(default)]
+    pub secret_env_keys: Vec<String>,
 }
 
 // 更新MCP服务器的请求结构
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateMcpServerRequest {
     pub name: Option<String>,
+    #[serde(rename = "type", alias = "provider_type")]
     pub r#type: Option<String>,
     pub timeout: Option<i64>,
     pub command: Option<String>,
     pub args: Option<Vec<String>>,
     pub env: Option<Option<std::collections::HashMap<String, String>>>,
+    /// 值需要加密存储的环境变量名，未指定时保持不变
+    #[serde(default)]
+    pub secret_env_keys: Option<Vec<String>>,
 }
 
 // 通用配置数据模型
@@ -177,6 +451,140 @@ pub struct UpdateCommonConfigRequest {
     pub is_active: Option<i64>,
 }
 
+// 批量更新通用配置的结果
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BatchUpdateResult {
+    /// 更新后的配置；整体回滚时为空
+    pub updated: Vec<CommonConfig>,
+    pub failed: Vec<BatchUpdateFailure>,
+}
+
+// 批量更新中失败的配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchUpdateFailure {
+    pub id: Option<i64>,
+    pub key: Option<String>,
+    pub error: String,
+}
+
+// 通用配置历史版本
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CommonConfigHistory {
+    pub id: i64,
+    pub config_id: i64,
+    pub version: i64,
+    pub value: String,
+    pub created_at: Option<String>,
+}
+
+// 供应商模型变更历史
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ProviderModelHistory {
+    pub id: i64,
+    pub provider_id: i64,
+    pub role: String,
+    pub old_model: Option<String>,
+    pub new_model: String,
+    pub source: String,
+    pub created_at: Option<String>,
+}
+
+// 数据迁移运行记录
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MigrationRun {
+    pub id: i64,
+    pub source: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub total_migrated: i64,
+    pub claude_providers: i64,
+    pub codex_providers: i64,
+    pub agent_guides: i64,
+    pub mcp_servers: i64,
+    pub common_configs: i64,
+    pub errors_json: String,   // 错误列表，存储为JSON字符串
+    pub warnings_json: String, // 警告列表，存储为JSON字符串
+    pub created_at: Option<String>,
+}
+
+// 创建数据迁移运行记录的请求结构
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateMigrationRunRequest {
+    pub source: String,
+    pub started_at: String,
+    pub finished_at: String,
+    pub total_migrated: i64,
+    pub claude_providers: i64,
+    pub codex_providers: i64,
+    pub agent_guides: i64,
+    pub mcp_servers: i64,
+    pub common_configs: i64,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// 供应商分组
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ProviderGroup {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+// 供应商分组成员
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct ProviderGroupMember {
+    pub group_id: i64,
+    pub provider_type: String, // claude 或 codex
+    pub provider_id: i64,
+}
+
+// 创建供应商分组的请求结构
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateProviderGroupRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub members: Vec<ProviderGroupMemberRequest>,
+}
+
+// 供应商分组成员的请求结构
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderGroupMemberRequest {
+    pub provider_type: String,
+    pub provider_id: i64,
+}
+
+// 更新结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateResult<T> {
+    /// 更新后的记录
+    pub entity: T,
+    /// 实际发生变化的字段名，按字母排序
+    pub changed_fields: Vec<String>,
+}
+
+impl<T: Serialize> UpdateResult<T> {
+    /// 比较更新前后的记录，`updated_at` 不计入变化
+    ///
+    /// 只返回字段名，不包含字段值；加密字段需要传入解密后的记录比较
+    pub fn diff(before: &T, entity: T) -> Self {
+        let before = serde_json::to_value(before).unwrap_or_default();
+        let after = serde_json::to_value(&entity).unwrap_or_default();
+        let changed_fields = match (before.as_object(), after.as_object()) {
+            (Some(before), Some(after)) => after
+                .iter()
+                .filter(|(field, _)| field.as_str() != "updated_at")
+                .filter(|(field, value)| before.get(field.as_str()) != Some(*value))
+                .map(|(field, _)| field.clone())
+                .collect(),
+            _ => Vec::new(),
+        };
+        Self { entity, changed_fields }
+    }
+}
+
 // 数据库记录的公共trait
 pub trait DbRecord {
     fn table_name() -> &'static str;
@@ -261,17 +669,154 @@ impl DbRecord for CommonConfig {
     }
 }
 
+// 列表可排序的列，每个实体只允许其中的一部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortColumn {
+    Id,
+    Name,
+    Type,
+    Enabled,
+    Key,
+    Category,
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl SortColumn {
+    /// 对应的数据库列名
+    pub fn column_name(self) -> &'static str {
+        match self {
+            SortColumn::Id => "id",
+            SortColumn::Name => "name",
+            SortColumn::Type => "type",
+            SortColumn::Enabled => "enabled",
+            SortColumn::Key => "key",
+            SortColumn::Category => "category",
+            SortColumn::CreatedAt => "created_at",
+            SortColumn::UpdatedAt => "updated_at",
+        }
+    }
+}
+
+impl std::str::FromStr for SortColumn {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "id" => Ok(SortColumn::Id),
+            "name" => Ok(SortColumn::Name),
+            "type" => Ok(SortColumn::Type),
+            "enabled" => Ok(SortColumn::Enabled),
+            "key" => Ok(SortColumn::Key),
+            "category" => Ok(SortColumn::Category),
+            "created_at" => Ok(SortColumn::CreatedAt),
+            "updated_at" => Ok(SortColumn::UpdatedAt),
+            _ => Err(format!("不支持按 {} 排序", value)),
+        }
+    }
+}
+
+// 排序方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDir {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortDir {
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            SortDir::Asc => "ASC",
+            SortDir::Desc => "DESC",
+        }
+    }
+}
+
+impl std::str::FromStr for SortDir {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "asc" => Ok(SortDir::Asc),
+            "desc" => Ok(SortDir::Desc),
+            _ => Err(format!("排序方向只能是 asc 或 desc，实际为 {}", value)),
+        }
+    }
+}
+
+// 列表排序方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderBy {
+    pub column: SortColumn,
+    #[serde(default)]
+    pub direction: SortDir,
+}
+
+impl OrderBy {
+    /// 解析查询参数 `sort` 和 `dir`，未指定 `sort` 时返回 `None`
+    ///
+    /// `allowed` 为实体允许排序的列，不在其中的列返回错误
+    pub fn from_query(
+        sort: Option<&str>,
+        dir: Option<&str>,
+        allowed: &[SortColumn],
+    ) -> Result<Option<Self>, String> {
+        let Some(sort) = sort.map(str::trim).filter(|sort| !sort.is_empty()) else {
+            return Ok(None);
+        };
+
+        let column: SortColumn = sort.parse()?;
+        if !allowed.contains(&column) {
+            return Err(format!("不支持按 {} 排序", sort));
+        }
+        let direction = dir.map(str::parse::<SortDir>).transpose()?.unwrap_or_default();
+
+        Ok(Some(Self { column, direction }))
+    }
+
+    /// ORDER BY 子句内容，追加 `id` 保证排序值相同时顺序稳定
+    pub fn to_sql(self) -> String {
+        let direction = self.direction.as_sql();
+        match self.column {
+            SortColumn::Id => format!("id {}", direction),
+            column => format!("{} {}, id {}", column.column_name(), direction, direction),
+        }
+    }
+}
+
 // 分页查询参数
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaginationParams {
     pub page: Option<i64>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
+    /// 是否查询总数，默认查询；无限滚动等场景可以关闭以省去 `COUNT(*)`
+    #[serde(default)]
+    pub with_total: Option<bool>,
+    /// 排序方式，未指定时使用各实体的默认顺序
+    #[serde(default)]
+    pub order_by: Option<OrderBy>,
+}
+
+impl PaginationParams {
+    /// 是否需要查询总数
+    pub fn include_total(&self) -> bool {
+        self.with_total.unwrap_or(true)
+    }
 }
 
 impl Default for PaginationParams {
     fn default() -> Self {
-        Self { page: Some(1), limit: Some(20), offset: Some(0) }
+        Self {
+            page: Some(1),
+            limit: Some(20),
+            offset: Some(0),
+            with_total: None,
+            order_by: None,
+        }
     }
 }
 
@@ -279,15 +824,746 @@ impl Default for PaginationParams {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PagedResult<T> {
     pub data: Vec<T>,
-    pub total: i64,
+    /// 总数，未查询总数时为 `None`
+    pub total: Option<i64>,
     pub page: i64,
     pub limit: i64,
-    pub total_pages: i64,
+    pub total_pages: Option<i64>,
 }
 
 impl<T> PagedResult<T> {
     pub fn new(data: Vec<T>, total: i64, page: i64, limit: i64) -> Self {
-        let total_pages = (total + limit - 1) / limit;
+        Self::with_optional_total(data, Some(total), page, limit)
+    }
+
+    /// 创建分页结果，`total` 为 `None` 表示未查询总数
+    pub fn with_optional_total(data: Vec<T>, total: Option<i64>, page: i64, limit: i64) -> Self {
+        let total_pages = total.map(|total| (total + limit - 1) / limit);
         Self { data, total, page, limit, total_pages }
     }
+
+    /// 转换每一项数据，分页信息保持不变
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> PagedResult<U> {
+        PagedResult {
+            data: self.data.into_iter().map(f).collect(),
+            total: self.total,
+            page: self.page,
+            limit: self.limit,
+            total_pages: self.total_pages,
+        }
+    }
+}
+
+// 实体与表结构的一致性
+//
+// `FromRow` 只在读取到不匹配的值时才报错：列允许NULL而字段不是 `Option`，
+// 要等到某条记录真的是NULL才会失败。新增实体或修改字段、迁移脚本时同步更新 `ENTITY_SCHEMAS`，
+// 单元测试按 `ENTITY_SCHEMAS` 建表并解码每个实体，检查它与结构体字段一致；
+// `verify_schema_compat` 在启动时和 `tests/schema_compat_test.rs` 中对照 `PRAGMA table_info` 检查
+
+/// 字段解码时要求的列类型（按SQLite类型亲和性判断）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnKind {
+    /// 整数字段，如 `i64`
+    Integer,
+    /// 字符串字段和JSON存储的字段
+    Text,
+}
+
+/// 实体字段对列的要求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldExpectation {
+    pub column: &'static str,
+    pub kind: ColumnKind,
+    /// 字段是否为 `Option`，不是时列必须 NOT NULL
+    pub optional: bool,
+}
+
+const fn required(column: &'static str, kind: ColumnKind) -> FieldExpectation {
+    FieldExpectation { column, kind, optional: false }
+}
+
+const fn optional(column: &'static str, kind: ColumnKind) -> FieldExpectation {
+    FieldExpectation { column, kind, optional: true }
+}
+
+/// 实体对应的数据表和字段
+#[derive(Debug, Clone, Copy)]
+pub struct EntitySchema {
+    pub entity: &'static str,
+    pub table: &'static str,
+    pub fields: &'static [FieldExpectation],
+}
+
+/// 所有通过 `FromRow` 读取的实体
+pub const ENTITY_SCHEMAS: &[EntitySchema] = {
+    use ColumnKind::{Integer, Text};
+    &[
+        EntitySchema {
+            entity: "ClaudeProvider",
+            table: "claude_providers",
+            fields: &[
+                required("id", Integer),
+                required("name", Text),
+                required("url", Text),
+                required("token", Text),
+                optional("timeout", Integer),
+                optional("auto_update", Integer),
+                required("type", Text),
+                required("enabled", Integer),
+                optional("opus_model", Text),
+                optional("sonnet_model", Text),
+                optional("haiku_model", Text),
+                required("models", Text),
+                required("custom_headers", Text),
+                required("accepted_status_codes", Text),
+                required("model_auto_update", Integer),
+                optional("created_at", Text),
+                optional("updated_at", Text),
+            ],
+        },
+        EntitySchema {
+            entity: "CodexProvider",
+            table: "codex_providers",
+            fields: &[
+                required("id", Integer),
+                required("name", Text),
+                required("url", Text),
+                required("token", Text),
+                required("type", Text),
+                required("enabled", Integer),
+                required("custom_headers", Text),
+                required("accepted_status_codes", Text),
+                optional("created_at", Text),
+                optional("updated_at", Text),
+            ],
+        },
+        EntitySchema {
+            entity: "AgentGuide",
+            table: "agent_guides",
+            fields: &[
+                required("id", Integer),
+                required("name", Text),
+                required("type", Text),
+                required("text", Text),
+                optional("created_at", Text),
+                optional("updated_at", Text),
+            ],
+        },
+        EntitySchema {
+            entity: "McpServer",
+            table: "mcp_servers",
+            fields: &[
+                required("id", Integer),
+                required("name", Text),
+                optional("type", Text),
+                optional("timeout", Integer),
+                required("command", Text),
+                required("args", Text),
+                optional("env", Text),
+                required("secret_env_keys", Text),
+                optional("created_at", Text),
+                optional("updated_at", Text),
+            ],
+        },
+        EntitySchema {
+            entity: "CommonConfig",
+            table: "common_configs",
+            fields: &[
+                required("id", Integer),
+                required("key", Text),
+                required("value", Text),
+                optional("description", Text),
+                required("category", Text),
+                required("is_active", Integer),
+                optional("created_at", Text),
+                optional("updated_at", Text),
+            ],
+        },
+        EntitySchema {
+            entity: "CommonConfigHistory",
+            table: "common_config_history",
+            fields: &[
+                required("id", Integer),
+                required("config_id", Integer),
+                required("version", Integer),
+                required("value", Text),
+                optional("created_at", Text),
+            ],
+        },
+        EntitySchema {
+            entity: "ProviderModelHistory",
+            table: "provider_model_history",
+            fields: &[
+                required("id", Integer),
+                required("provider_id", Integer),
+                required("role", Text),
+                optional("old_model", Text),
+                required("new_model", Text),
+                required("source", Text),
+                optional("created_at", Text),
+            ],
+        },
+        EntitySchema {
+            entity: "MigrationRun",
+            table: "migration_runs",
+            fields: &[
+                required("id", Integer),
+                required("source", Text),
+                required("started_at", Text),
+                required("finished_at", Text),
+                required("total_migrated", Integer),
+                required("claude_providers", Integer),
+                required("codex_providers", Integer),
+                required("agent_guides", Integer),
+                required("mcp_servers", Integer),
+                required("common_configs", Integer),
+                required("errors_json", Text),
+                required("warnings_json", Text),
+                optional("created_at", Text),
+            ],
+        },
+        EntitySchema {
+            entity: "ProviderGroup",
+            table: "provider_groups",
+            fields: &[
+                required("id", Integer),
+                required("name", Text),
+                optional("description", Text),
+                optional("created_at", Text),
+                optional("updated_at", Text),
+            ],
+        },
+        EntitySchema {
+            entity: "ProviderGroupMember",
+            table: "provider_group_members",
+            fields: &[
+                required("group_id", Integer),
+                required("provider_type", Text),
+                required("provider_id", Integer),
+            ],
+        },
+    ]
+};
+
+/// 实体与表结构不一致的原因
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SchemaProblem {
+    /// 数据表不存在
+    MissingTable,
+    /// 列不存在
+    MissingColumn,
+    /// 列的类型亲和性与字段不符
+    TypeMismatch { expected: ColumnKind, actual: String },
+    /// 字段不是 `Option`，列却允许NULL
+    NullableColumn,
+}
+
+/// 一处实体与表结构不一致
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaMismatch {
+    pub entity: &'static str,
+    pub table: &'static str,
+    /// 表不存在时为 `None`
+    pub column: Option<&'static str>,
+    pub problem: SchemaProblem,
+}
+
+impl std::fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let target = match self.column {
+            Some(column) => format!("{}.{}（{}）", self.table, column, self.entity),
+            None => format!("{}（{}）", self.table, self.entity),
+        };
+        match &self.problem {
+            SchemaProblem::MissingTable => write!(f, "{}: 数据表不存在", target),
+            SchemaProblem::MissingColumn => write!(f, "{}: 列不存在", target),
+            SchemaProblem::TypeMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "{}: 期望 {:?} 类型，实际声明为 {}",
+                    target, expected, actual
+                )
+            }
+            SchemaProblem::NullableColumn => write!(f, "{}: 字段不可为空，列却允许NULL", target),
+        }
+    }
+}
+
+/// 按SQLite的规则从声明类型推断列的类型亲和性，只区分整数和文本
+fn column_kind(declared: &str) -> Option<ColumnKind> {
+    let declared = declared.to_uppercase();
+    if declared.contains("INT") {
+        Some(ColumnKind::Integer)
+    } else if ["CHAR", "CLOB", "TEXT"].iter().any(|t| declared.contains(t)) {
+        Some(ColumnKind::Text)
+    } else {
+        None
+    }
+}
+
+/// 对照 `PRAGMA table_info` 检查 `ENTITY_SCHEMAS` 中每个实体的字段，返回所有不一致之处
+///
+/// 只检查实体读取的列，表中多出的列不影响 `FromRow`，不会报告
+pub async fn verify_schema_compat(
+    pool: &sqlx::SqlitePool,
+) -> Result<Vec<SchemaMismatch>, sqlx::Error> {
+    let tables = crate::migration::schema_diff::read_schema(pool).await?;
+
+    let mut mismatches = Vec::new();
+    for schema in ENTITY_SCHEMAS {
+        let mismatch = |column, problem| SchemaMismatch {
+            entity: schema.entity,
+            table: schema.table,
+            column,
+            problem,
+        };
+
+        let Some(columns) = tables.get(schema.table) else {
+            mismatches.push(mismatch(None, SchemaProblem::MissingTable));
+            continue;
+        };
+
+        for field in schema.fields {
+            let Some(column) = columns.get(field.column) else {
+                mismatches.push(mismatch(Some(field.column), SchemaProblem::MissingColumn));
+                continue;
+            };
+
+            if column_kind(&column.data_type) != Some(field.kind) {
+                mismatches.push(mismatch(
+                    Some(field.column),
+                    SchemaProblem::TypeMismatch {
+                        expected: field.kind,
+                        actual: column.data_type.clone(),
+                    },
+                ));
+            }
+
+            // INTEGER PRIMARY KEY 是rowid的别名，不会为NULL
+            let not_null =
+                column.not_null || (column.primary_key && field.kind == ColumnKind::Integer);
+            if !field.optional && !not_null {
+                mismatches.push(mismatch(Some(field.column), SchemaProblem::NullableColumn));
+            }
+        }
+    }
+
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+    use serde_json::json;
+
+    /// 分别使用 `type` 和 `provider_type` 反序列化请求
+    fn deserialize_with_each_key<T: DeserializeOwned>(
+        base: serde_json::Value,
+        value: &str,
+    ) -> Vec<T> {
+        ["type", "provider_type"]
+            .into_iter()
+            .map(|key| {
+                let mut payload = base.clone();
+                payload[key] = json!(value);
+                serde_json::from_value(payload)
+                    .unwrap_or_else(|e| panic!("使用 {} 反序列化失败: {}", key, e))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_timeout_ms_range_and_legacy_seconds() {
+        assert_eq!(TimeoutMs::new(200).unwrap().as_millis(), 200);
+        assert!(TimeoutMs::new(0).is_err());
+        assert!(TimeoutMs::new(MAX_TIMEOUT_MS + 1).is_err());
+        assert!(serde_json::from_value::<TimeoutMs>(json!(-1)).is_err());
+
+        // 旧数据中的秒数换算为毫秒
+        assert_eq!(TimeoutMs::from_legacy(Some(30)).as_millis(), 30_000);
+        assert_eq!(TimeoutMs::from_legacy(Some(45_000)).as_millis(), 45_000);
+        assert_eq!(TimeoutMs::from_legacy(None).as_millis(), DEFAULT_TIMEOUT_MS);
+        assert_eq!(
+            TimeoutMs::from_legacy(Some(0)).as_millis(),
+            DEFAULT_TIMEOUT_MS
+        );
+        assert_eq!(
+            TimeoutMs::from_legacy(Some(i64::MAX)).as_millis(),
+            DEFAULT_TIMEOUT_MS
+        );
+        assert_eq!(TimeoutMs::legacy_seconds_to_millis(-5), -5);
+        assert_eq!(TimeoutMs::legacy_seconds_to_millis(i64::MAX), i64::MAX);
+    }
+
+    #[test]
+    fn test_request_type_field_accepts_legacy_alias() {
+        let provider = json!({ "name": "p", "url": "https://api.example.com", "token": "sk-test" });
+        for request in
+            deserialize_with_each_key::<CreateClaudeProviderRequest>(provider.clone(), "paid")
+        {
+            assert_eq!(request.r#type.as_deref(), Some("paid"));
+        }
+        for request in deserialize_with_each_key::<UpdateClaudeProviderRequest>(json!({}), "paid") {
+            assert_eq!(request.r#type.as_deref(), Some("paid"));
+        }
+        for request in
+            deserialize_with_each_key::<CreateCodexProviderRequest>(provider.clone(), "paid")
+        {
+            assert_eq!(request.r#type.as_deref(), Some("paid"));
+        }
+        for request in deserialize_with_each_key::<UpdateCodexProviderRequest>(json!({}), "paid") {
+            assert_eq!(request.r#type.as_deref(), Some("paid"));
+        }
+
+        let guide = json!({ "name": "g", "text": "内容" });
+        for request in deserialize_with_each_key::<CreateAgentGuideRequest>(guide, "only") {
+            assert_eq!(request.r#type, "only");
+        }
+        for request in deserialize_with_each_key::<UpdateAgentGuideRequest>(json!({}), "and") {
+            assert_eq!(request.r#type.as_deref(), Some("and"));
+        }
+
+        let server = json!({ "name": "m", "command": "npx", "args": [] });
+        for request in deserialize_with_each_key::<CreateMcpServerRequest>(server, "sse") {
+            assert_eq!(request.r#type.as_deref(), Some("sse"));
+        }
+        for request in deserialize_with_each_key::<UpdateMcpServerRequest>(json!({}), "stdio") {
+            assert_eq!(request.r#type.as_deref(), Some("stdio"));
+        }
+
+        // 序列化时总是使用 `type`
+        let request: CreateCodexProviderRequest = serde_json::from_value(json!({
+            "name": "p", "url": "https://api.example.com", "token": "sk-test", "provider_type": "paid"
+        }))
+        .unwrap();
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value["type"], "paid");
+        assert!(value.get("provider_type").is_none());
+    }
+
+    #[test]
+    fn test_update_result_reports_changed_field_names() {
+        let before = CodexProvider {
+            id: 1,
+            name: "codex".to_string(),
+            url: "https://api.openai.com".to_string(),
+            token: "sk-old".to_string(),
+            r#type: "paid".to_string(),
+            enabled: 0,
+            custom_headers: HashMap::new(),
+            accepted_status_codes: vec![],
+            created_at: Some("2025-01-01T00:00:00Z".to_string()),
+            updated_at: Some("2025-01-01T00:00:00Z".to_string()),
+        };
+        let after = CodexProvider {
+            token: "sk-new".to_string(),
+            enabled: 1,
+            updated_at: Some("2025-01-02T00:00:00Z".to_string()),
+            ..before.clone()
+        };
+
+        let result = UpdateResult::diff(&before, after);
+        assert_eq!(result.changed_fields, vec!["enabled", "token"]);
+
+        // 结果中不包含旧值
+        let value = serde_json::to_string(&result.changed_fields).unwrap();
+        assert!(!value.contains("sk-old"));
+    }
+
+    #[test]
+    fn test_rename_fields_prefers_current_name() {
+        let aliases = [FieldAlias {
+            table: "claude_providers",
+            field: "type",
+            alias: "kind",
+            renamed_in: "9.9",
+        }];
+
+        let mut record = json!({ "name": "p", "kind": "paid" });
+        let renamed = rename_fields(aliases.iter(), record.as_object_mut().unwrap());
+        assert_eq!(renamed, vec!["kind"]);
+        assert_eq!(record, json!({ "name": "p", "type": "paid" }));
+
+        // 新旧字段同时存在时保留当前字段
+        let mut record = json!({ "type": "public_welfare", "kind": "paid" });
+        rename_fields(aliases.iter(), record.as_object_mut().unwrap());
+        assert_eq!(record, json!({ "type": "public_welfare" }));
+
+        // 没有登记的字段名保持不变
+        let mut record = json!({ "name": "p", "provider_type": "paid" });
+        assert!(
+            rename_legacy_fields("claude_providers", record.as_object_mut().unwrap()).is_empty()
+        );
+        assert_eq!(record["provider_type"], "paid");
+    }
+
+    /// 每个实体的样例，`Option` 字段取 `None`；新增字段后这里无法编译，需同步更新 `ENTITY_SCHEMAS`
+    fn entity_samples() -> Vec<(&'static str, serde_json::Value)> {
+        let text = || "x".to_string();
+        vec![
+            (
+                "ClaudeProvider",
+                json!(ClaudeProvider {
+                    id: 1,
+                    name: text(),
+                    url: text(),
+                    token: text(),
+                    timeout: None,
+                    auto_update: None,
+                    r#type: text(),
+                    enabled: 1,
+                    opus_model: None,
+                    sonnet_model: None,
+                    haiku_model: None,
+                    models: HashMap::new(),
+                    custom_headers: HashMap::new(),
+                    accepted_status_codes: Vec::new(),
+                    model_auto_update: 0,
+                    created_at: None,
+                    updated_at: None,
+                }),
+            ),
+            (
+                "CodexProvider",
+                json!(CodexProvider {
+                    id: 1,
+                    name: text(),
+                    url: text(),
+                    token: text(),
+                    r#type: text(),
+                    enabled: 1,
+                    custom_headers: HashMap::new(),
+                    accepted_status_codes: Vec::new(),
+                    created_at: None,
+                    updated_at: None,
+                }),
+            ),
+            (
+                "AgentGuide",
+                json!(AgentGuide {
+                    id: 1,
+                    name: text(),
+                    r#type: text(),
+                    text: text(),
+                    created_at: None,
+                    updated_at: None,
+                }),
+            ),
+            (
+                "McpServer",
+                json!(McpServer {
+                    id: 1,
+                    name: text(),
+                    r#type: None,
+                    timeout: None,
+                    command: text(),
+                    args: text(),
+                    env: None,
+                    secret_env_keys: Vec::new(),
+                    created_at: None,
+                    updated_at: None,
+                }),
+            ),
+            (
+                "CommonConfig",
+                json!(CommonConfig {
+                    id: 1,
+                    key: text(),
+                    value: text(),
+                    description: None,
+                    category: text(),
+                    is_active: 1,
+                    created_at: None,
+                    updated_at: None,
+                }),
+            ),
+            (
+                "CommonConfigHistory",
+                json!(CommonConfigHistory {
+                    id: 1,
+                    config_id: 1,
+                    version: 1,
+                    value: text(),
+                    created_at: None,
+                }),
+            ),
+            (
+                "ProviderModelHistory",
+                json!(ProviderModelHistory {
+                    id: 1,
+                    provider_id: 1,
+                    role: text(),
+                    old_model: None,
+                    new_model: text(),
+                    source: text(),
+                    created_at: None,
+                }),
+            ),
+            (
+                "MigrationRun",
+                json!(MigrationRun {
+                    id: 1,
+                    source: text(),
+                    started_at: text(),
+                    finished_at: text(),
+                    total_migrated: 0,
+                    claude_providers: 0,
+                    codex_providers: 0,
+                    agent_guides: 0,
+                    mcp_servers: 0,
+                    common_configs: 0,
+                    errors_json: text(),
+                    warnings_json: text(),
+                    created_at: None,
+                }),
+            ),
+            (
+                "ProviderGroup",
+                json!(ProviderGroup {
+                    id: 1,
+                    name: text(),
+                    description: None,
+                    created_at: None,
+                    updated_at: None,
+                }),
+            ),
+            (
+                "ProviderGroupMember",
+                json!(ProviderGroupMember { group_id: 1, provider_type: text(), provider_id: 1 }),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_entity_schemas_match_struct_fields() {
+        let samples = entity_samples();
+        assert_eq!(samples.len(), ENTITY_SCHEMAS.len());
+
+        for (entity, sample) in samples {
+            let schema = ENTITY_SCHEMAS
+                .iter()
+                .find(|schema| schema.entity == entity)
+                .unwrap_or_else(|| panic!("ENTITY_SCHEMAS 中缺少 {}", entity));
+
+            // serde 序列化 `r#type` 时去掉 `r#` 前缀，与列名 `type` 一致
+            let fields: std::collections::BTreeSet<&str> =
+                sample.as_object().unwrap().keys().map(String::as_str).collect();
+            let columns: std::collections::BTreeSet<&str> =
+                schema.fields.iter().map(|field| field.column).collect();
+            assert_eq!(fields, columns, "{} 的字段与 ENTITY_SCHEMAS 不一致", entity);
+
+            for field in schema.fields {
+                let value = &sample[field.column];
+                assert_eq!(
+                    value.is_null(),
+                    field.optional,
+                    "{}.{} 是否为 Option 与 ENTITY_SCHEMAS 不一致",
+                    entity,
+                    field.column
+                );
+                if !value.is_null() {
+                    // JSON存储的字段序列化为对象或数组，对应的列仍是文本
+                    assert_eq!(
+                        value.is_i64(),
+                        field.kind == ColumnKind::Integer,
+                        "{}.{} 的类型与 ENTITY_SCHEMAS 不一致",
+                        entity,
+                        field.column
+                    );
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entities_decode_from_schema_shaped_rows() {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let samples: HashMap<&str, serde_json::Value> = entity_samples().into_iter().collect();
+
+        // 按 ENTITY_SCHEMAS 建表：只有声明的列，必填列 NOT NULL，可选列写入NULL
+        for schema in ENTITY_SCHEMAS {
+            let definitions: Vec<String> = schema
+                .fields
+                .iter()
+                .map(|field| {
+                    let kind = match field.kind {
+                        ColumnKind::Integer => "INTEGER",
+                        ColumnKind::Text => "TEXT",
+                    };
+                    let null = if field.optional { "" } else { " NOT NULL" };
+                    format!(r#""{}" {}{}"#, field.column, kind, null)
+                })
+                .collect();
+            sqlx::query(&format!(
+                r#"CREATE TABLE "{}" ({})"#,
+                schema.table,
+                definitions.join(", ")
+            ))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+            // JSON存储的字段写入空对象或空数组
+            let sample = &samples[schema.entity];
+            let values: Vec<&str> = schema
+                .fields
+                .iter()
+                .map(|field| match (&sample[field.column], field.kind) {
+                    _ if field.optional => "NULL",
+                    (_, ColumnKind::Integer) => "1",
+                    (serde_json::Value::Object(_), _) => "'{}'",
+                    (serde_json::Value::Array(_), _) => "'[]'",
+                    _ => "'x'",
+                })
+                .collect();
+            sqlx::query(&format!(
+                r#"INSERT INTO "{}" VALUES ({})"#,
+                schema.table,
+                values.join(", ")
+            ))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+            let query = format!(r#"SELECT * FROM "{}""#, schema.table);
+            macro_rules! decode {
+                ($($entity:ident),* $(,)?) => {
+                    match schema.entity {
+                        $(stringify!($entity) => sqlx::query_as::<_, $entity>(&query)
+                            .fetch_one(&pool)
+                            .await
+                            .map(drop),)*
+                        other => panic!("{} 没有对应的解码检查", other),
+                    }
+                };
+            }
+            let result = decode!(
+                ClaudeProvider,
+                CodexProvider,
+                AgentGuide,
+                McpServer,
+                CommonConfig,
+                CommonConfigHistory,
+                ProviderModelHistory,
+                MigrationRun,
+                ProviderGroup,
+                ProviderGroupMember,
+            );
+            assert!(
+                result.is_ok(),
+                "无法从 ENTITY_SCHEMAS 描述的 {} 表解码 {}: {:?}",
+                schema.table,
+                schema.entity,
+                result
+            );
+        }
+    }
 }
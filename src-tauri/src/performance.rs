@@ -41,6 +41,9 @@ pub struct PerformanceMetric {
     // pub timestamp: Instant,
     #[serde(with = "chrono::serde::ts_seconds")]
     pub timestamp: DateTime<Utc>,
+    /// 单调时钟记录时间，用于按时间窗口统计
+    #[serde(skip, default = "Instant::now")]
+    pub recorded_at: Instant,
     /// 附加元数据
     pub metadata: HashMap<String, String>,
 }
@@ -53,6 +56,7 @@ impl PerformanceMetric {
             operation: operation.into(),
             duration,
             timestamp: Utc::now(),
+            recorded_at: Instant::now(),
             metadata: HashMap::new(),
         }
     }
@@ -86,14 +90,18 @@ pub struct PerformanceSummary {
 pub struct PerformanceMonitor {
     metrics: Arc<RwLock<Vec<PerformanceMetric>>>,
     start_time: Instant,
+    /// 统计起点，重置后从重置时刻开始计算每秒操作数
+    stats_start: Arc<std::sync::RwLock<Instant>>,
 }
 
 impl PerformanceMonitor {
     /// 创建新的性能监控器
     pub fn new() -> Self {
+        let start_time = Instant::now();
         Self {
             metrics: Arc::new(RwLock::new(Vec::new())),
-            start_time: Instant::now(),
+            start_time,
+            stats_start: Arc::new(std::sync::RwLock::new(start_time)),
         }
     }
 
@@ -140,39 +148,28 @@ impl PerformanceMonitor {
             })
             .collect();
 
-        if filtered.is_empty() {
-            return None;
-        }
+        let elapsed = self.stats_start.read().unwrap_or_else(|e| e.into_inner()).elapsed();
+        summarize(&filtered, elapsed)
+    }
+
+    /// 获取 `since` 之后记录的所有指标的统计
+    pub async fn summary_since(&self, since: Instant) -> Option<PerformanceSummary> {
+        let metrics = self.metrics.read().await;
+        let filtered: Vec<_> = metrics.iter().filter(|m| m.recorded_at >= since).collect();
 
-        let total_operations = filtered.len();
-        let total_duration: Duration = filtered.iter().map(|m| m.duration).sum();
-        let average_duration = total_duration / total_operations as u32;
-
-        let min_duration = filtered.iter().map(|m| m.duration).min().unwrap();
-        let max_duration = filtered.iter().map(|m| m.duration).max().unwrap();
-
-        // 最近100次操作的平均时间
-        let recent_count = filtered.len().min(100);
-        let recent_average = if recent_count > 0 {
-            let recent_duration: Duration =
-                filtered.iter().rev().take(recent_count).map(|m| m.duration).sum();
-            recent_duration / recent_count as u32
-        } else {
-            Duration::ZERO
-        };
-
-        // 计算每秒操作数
-        let elapsed = self.start_time.elapsed();
-        let operations_per_second = total_operations as f64 / elapsed.as_secs_f64();
-
-        Some(PerformanceSummary {
-            total_operations,
-            average_duration,
-            min_duration,
-            max_duration,
-            recent_average,
-            operations_per_second,
-        })
+        summarize(&filtered, since.elapsed())
+    }
+
+    /// 获取最近一段时间内记录的所有指标的统计
+    pub async fn summary_window(&self, window: Duration) -> Option<PerformanceSummary> {
+        match Instant::now().checked_sub(window) {
+            Some(since) => self.summary_since(since).await,
+            // 窗口早于单调时钟起点时统计全部指标
+            None => {
+                let metrics = self.metrics.read().await;
+                summarize(&metrics.iter().collect::<Vec<_>>(), window)
+            }
+        }
     }
 
     /// 获取所有性能指标
@@ -185,12 +182,51 @@ impl PerformanceMonitor {
         self.metrics.write().await.clear();
     }
 
+    /// 重置监控器：清除所有指标，并从当前时刻重新计算每秒操作数
+    pub async fn reset(&self) {
+        let mut metrics = self.metrics.write().await;
+        metrics.clear();
+        *self.stats_start.write().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
     /// 获取启动时间
     pub fn startup_time(&self) -> Duration {
         self.start_time.elapsed()
     }
 }
 
+/// 计算一组指标的统计，`elapsed` 为计算每秒操作数使用的时长
+fn summarize(metrics: &[&PerformanceMetric], elapsed: Duration) -> Option<PerformanceSummary> {
+    if metrics.is_empty() {
+        return None;
+    }
+
+    let total_operations = metrics.len();
+    let total_duration: Duration = metrics.iter().map(|m| m.duration).sum();
+    let average_duration = total_duration / total_operations as u32;
+
+    let min_duration = metrics.iter().map(|m| m.duration).min().unwrap();
+    let max_duration = metrics.iter().map(|m| m.duration).max().unwrap();
+
+    // 最近100次操作的平均时间
+    let recent_count = metrics.len().min(100);
+    let recent_duration: Duration =
+        metrics.iter().rev().take(recent_count).map(|m| m.duration).sum();
+    let recent_average = recent_duration / recent_count as u32;
+
+    // 计算每秒操作数
+    let operations_per_second = total_operations as f64 / elapsed.as_secs_f64();
+
+    Some(PerformanceSummary {
+        total_operations,
+        average_duration,
+        min_duration,
+        max_duration,
+        recent_average,
+        operations_per_second,
+    })
+}
+
 impl Default for PerformanceMonitor {
     fn default() -> Self {
         Self::new()
@@ -314,4 +350,50 @@ mod tests {
         assert_eq!(summary.min_duration, Duration::from_millis(100));
         assert_eq!(summary.max_duration, Duration::from_millis(140));
     }
+
+    #[tokio::test]
+    async fn test_windowed_summary_and_reset() {
+        let monitor = PerformanceMonitor::new();
+
+        // 较早窗口的样本
+        let earlier = Instant::now().checked_sub(Duration::from_secs(2)).unwrap();
+        for millis in [500, 600] {
+            let mut metric = PerformanceMetric::new(
+                MetricType::DatabaseQuery,
+                "old",
+                Duration::from_millis(millis),
+            );
+            metric.recorded_at = earlier;
+            monitor.record_metric(metric).await;
+        }
+
+        // 最近窗口的样本
+        let since = Instant::now();
+        for millis in [10, 20, 30] {
+            let metric = PerformanceMetric::new(
+                MetricType::ApiResponse,
+                "new",
+                Duration::from_millis(millis),
+            );
+            monitor.record_metric(metric).await;
+        }
+
+        let summary = monitor.summary_since(since).await.unwrap();
+        assert_eq!(summary.total_operations, 3);
+        assert_eq!(summary.max_duration, Duration::from_millis(30));
+        assert_eq!(summary.average_duration, Duration::from_millis(20));
+
+        let summary = monitor.summary_window(Duration::from_secs(1)).await.unwrap();
+        assert_eq!(summary.total_operations, 3);
+        assert_eq!(summary.min_duration, Duration::from_millis(10));
+
+        let summary = monitor.summary_window(Duration::from_secs(60)).await.unwrap();
+        assert_eq!(summary.total_operations, 5);
+        assert_eq!(summary.max_duration, Duration::from_millis(600));
+
+        monitor.reset().await;
+        assert!(monitor.get_all_metrics().await.is_empty());
+        assert!(monitor.summary_window(Duration::from_secs(60)).await.is_none());
+        assert!(monitor.get_summary(&MetricType::ApiResponse).await.is_none());
+    }
 }
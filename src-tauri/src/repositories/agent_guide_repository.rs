@@ -3,8 +3,10 @@
 // 提供Agent指导文件的特定数据访问操作
 
 use crate::crypto::CryptoService;
-use crate::database::DatabaseManager;
-use crate::models::{AgentGuide, CreateAgentGuideRequest, UpdateAgentGuideRequest};
+use crate::database::{DatabaseManager, QueryTimer};
+use crate::models::{
+    AgentGuide, CreateAgentGuideRequest, OrderBy, SortColumn, UpdateAgentGuideRequest,
+};
 use crate::repositories::base_repository::{BaseRepository, RepositoryError, RepositoryResult};
 use sqlx::{FromRow, SqlitePool};
 
@@ -12,6 +14,7 @@ use sqlx::{FromRow, SqlitePool};
 pub struct AgentGuideRepository {
     pool: SqlitePool,
     crypto_service: CryptoService,
+    query_timer: QueryTimer,
 }
 
 impl AgentGuideRepository {
@@ -20,6 +23,7 @@ impl AgentGuideRepository {
         Self {
             pool: db_manager.pool().clone(),
             crypto_service: crypto_service.clone(),
+            query_timer: db_manager.query_timer(),
         }
     }
 
@@ -44,7 +48,7 @@ impl AgentGuideRepository {
             .bind(&request.name)
             .bind(&request.r#type)
             .bind(&request.text)
-            .execute(&self.pool)
+            .execute(self.executor("create", &self.pool))
             .await?;
 
         Ok(result.last_insert_rowid())
@@ -65,26 +69,23 @@ impl AgentGuideRepository {
             )));
         }
 
-        let query = r#"
-            UPDATE agent_guides SET
-                name = COALESCE(?, name),
-                type = COALESCE(?, type),
-                text = COALESCE(?, text),
-                updated_at = datetime('now')
-            WHERE id = ?
-        "#;
+        let query = Self::update_by_id_statement(&[
+            "name = COALESCE(?, name)",
+            "type = COALESCE(?, type)",
+            "text = COALESCE(?, text)",
+        ]);
 
         tracing::info!(
             id = %id,
             "更新Agent指导文件"
         );
 
-        let result = sqlx::query(query)
+        let result = sqlx::query(&query)
             .bind(&request.name)
             .bind(&request.r#type)
             .bind(&request.text)
             .bind(id)
-            .execute(&self.pool)
+            .execute(self.executor("update", &self.pool))
             .await?;
 
         Ok(result.rows_affected() > 0)
@@ -100,23 +101,31 @@ impl AgentGuideRepository {
         &self,
         search_term: &str,
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> RepositoryResult<Vec<AgentGuide>> {
         let search_fields = vec!["name", "type", "text"];
-        self.search::<AgentGuide>(search_term, &search_fields, limit).await
+        self.search::<AgentGuide>(search_term, &search_fields, limit, order_by).await
     }
 
-    /// 根据类型获取Agent指导文件
-    pub async fn find_by_type(&self, guide_type: &str) -> RepositoryResult<Vec<AgentGuide>> {
-        let query = "SELECT * FROM agent_guides WHERE type = ? ORDER BY id DESC";
+    /// 根据类型获取Agent指导文件，`order_by` 为空时按ID倒序
+    pub async fn find_by_type(
+        &self,
+        guide_type: &str,
+        order_by: Option<OrderBy>,
+    ) -> RepositoryResult<Vec<AgentGuide>> {
+        let query = format!(
+            "SELECT * FROM agent_guides WHERE type = ? ORDER BY {}",
+            Self::order_by_clause(order_by, "id DESC")?
+        );
 
         tracing::debug!(
             guide_type = %guide_type,
             "根据类型获取Agent指导文件列表"
         );
 
-        let results = sqlx::query_as::<_, AgentGuide>(query)
+        let results = sqlx::query_as::<_, AgentGuide>(&query)
             .bind(guide_type)
-            .fetch_all(&self.pool)
+            .fetch_all(self.executor("find_by_type", &self.pool))
             .await?;
 
         Ok(results)
@@ -149,7 +158,10 @@ impl AgentGuideRepository {
     pub async fn count_by_type(&self, guide_type: &str) -> RepositoryResult<i64> {
         let query = "SELECT COUNT(*) FROM agent_guides WHERE type = ?";
 
-        let count: i64 = sqlx::query_scalar(query).bind(guide_type).fetch_one(&self.pool).await?;
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(guide_type)
+            .fetch_one(self.executor("count_by_type", &self.pool))
+            .await?;
 
         Ok(count)
     }
@@ -160,6 +172,16 @@ impl BaseRepository for AgentGuideRepository {
         "agent_guides"
     }
 
+    fn sortable_columns() -> &'static [SortColumn] {
+        &[
+            SortColumn::Id,
+            SortColumn::Name,
+            SortColumn::Type,
+            SortColumn::CreatedAt,
+            SortColumn::UpdatedAt,
+        ]
+    }
+
     fn pool(&self) -> &SqlitePool {
         &self.pool
     }
@@ -168,6 +190,10 @@ impl BaseRepository for AgentGuideRepository {
         &self.crypto_service
     }
 
+    fn query_timer(&self) -> &QueryTimer {
+        &self.query_timer
+    }
+
     async fn find_by_id<T>(&self, id: i64) -> RepositoryResult<Option<T>>
     where
         T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
@@ -181,7 +207,13 @@ impl BaseRepository for AgentGuideRepository {
             query
         );
 
-        let result = sqlx::query_as::<_, T>(&query).bind(id).fetch_optional(self.pool()).await?;
+        let result = self
+            .timed(
+                "find_by_id",
+                &query,
+                sqlx::query_as::<_, T>(&query).bind(id).fetch_optional(self.pool()),
+            )
+            .await?;
 
         Ok(result)
     }
@@ -214,7 +246,13 @@ impl BaseRepository for AgentGuideRepository {
             "删除Agent指导文件"
         );
 
-        let result = sqlx::query(query).bind(id).execute(self.pool()).await?;
+        let result = self
+            .timed(
+                "delete",
+                query,
+                sqlx::query(query).bind(id).execute(self.pool()),
+            )
+            .await?;
 
         Ok(result.rows_affected() > 0)
     }
@@ -227,7 +265,13 @@ impl BaseRepository for AgentGuideRepository {
 
         tracing::debug!("获取Agent指导文件列表");
 
-        let results = sqlx::query_as::<_, T>(query).fetch_all(self.pool()).await?;
+        let results = self
+            .timed(
+                "list_all",
+                query,
+                sqlx::query_as::<_, T>(query).fetch_all(self.pool()),
+            )
+            .await?;
 
         Ok(results)
     }
@@ -245,10 +289,25 @@ impl BaseRepository for AgentGuideRepository {
 
         // 查询总数
         let count_query = "SELECT COUNT(*) FROM agent_guides";
-        let total: i64 = sqlx::query_scalar(count_query).fetch_one(self.pool()).await?;
+        let total: Option<i64> = if params.include_total() {
+            Some(
+                self.timed(
+                    "paginate",
+                    count_query,
+                    sqlx::query_scalar(count_query).fetch_one(self.pool()),
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
 
         // 查询分页数据
-        let data_query = "SELECT * FROM agent_guides ORDER BY id DESC LIMIT ? OFFSET ?";
+        let order_by = Self::order_by_clause(params.order_by, "id DESC")?;
+        let data_query = format!(
+            "SELECT * FROM agent_guides ORDER BY {} LIMIT ? OFFSET ?",
+            order_by
+        );
 
         tracing::debug!(
             page = %page,
@@ -257,13 +316,19 @@ impl BaseRepository for AgentGuideRepository {
             "分页查询Agent指导文件"
         );
 
-        let data = sqlx::query_as::<_, T>(data_query)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(self.pool())
+        let data = self
+            .timed(
+                "paginate",
+                &data_query,
+                sqlx::query_as::<_, T>(&data_query)
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(self.pool()),
+            )
             .await?;
 
-        let paged_result = crate::models::PagedResult::new(data, total, page, limit);
+        let paged_result =
+            crate::models::PagedResult::with_optional_total(data, total, page, limit);
 
         Ok(paged_result)
     }
@@ -273,11 +338,13 @@ impl BaseRepository for AgentGuideRepository {
         search_term: &str,
         search_fields: &[&str],
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> RepositoryResult<Vec<T>>
     where
         T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
     {
         let limit = limit.unwrap_or(50);
+        let order_by = Self::order_by_clause(order_by, "id DESC")?;
 
         // 构建搜索查询
         let mut where_conditions = Vec::new();
@@ -299,11 +366,12 @@ impl BaseRepository for AgentGuideRepository {
         }
 
         let query = if where_conditions.is_empty() {
-            "SELECT * FROM agent_guides ORDER BY id DESC LIMIT ?".to_string()
+            format!("SELECT * FROM agent_guides ORDER BY {} LIMIT ?", order_by)
         } else {
             format!(
-                "SELECT * FROM agent_guides WHERE {} ORDER BY id DESC LIMIT ?",
-                where_conditions.join(" OR ")
+                "SELECT * FROM agent_guides WHERE {} ORDER BY {} LIMIT ?",
+                where_conditions.join(" OR "),
+                order_by
             )
         };
 
@@ -321,7 +389,7 @@ impl BaseRepository for AgentGuideRepository {
         }
         query_builder = query_builder.bind(limit);
 
-        let results = query_builder.fetch_all(self.pool()).await?;
+        let results = self.timed("search", &query, query_builder.fetch_all(self.pool())).await?;
 
         Ok(results)
     }
@@ -331,7 +399,13 @@ impl BaseRepository for AgentGuideRepository {
 
         tracing::debug!("统计Agent指导文件总数");
 
-        let count: i64 = sqlx::query_scalar(query).fetch_one(self.pool()).await?;
+        let count: i64 = self
+            .timed(
+                "count",
+                query,
+                sqlx::query_scalar(query).fetch_one(self.pool()),
+            )
+            .await?;
 
         Ok(count)
     }
@@ -355,6 +429,7 @@ mod tests {
             connect_timeout: std::time::Duration::from_secs(10),
             idle_timeout: std::time::Duration::from_secs(60),
             max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = DatabaseManager::new(config).await.unwrap();
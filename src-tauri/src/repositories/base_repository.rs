@@ -5,13 +5,14 @@
 
 use sqlx::{FromRow, SqlitePool};
 use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
 use thiserror::Error;
 use tracing::{debug, error, info};
 
 use crate::crypto::CryptoService;
-use crate::database::DatabaseManager;
+use crate::database::{DatabaseManager, QueryTimer, TimedExecutor};
 use crate::models::PagedResult;
-use crate::models::PaginationParams;
+use crate::models::{OrderBy, PaginationParams, SortColumn};
 
 /// Repository错误类型
 #[derive(Error, Debug)]
@@ -41,6 +42,45 @@ pub enum RepositoryError {
 /// Repository结果类型
 pub type RepositoryResult<T> = Result<T, RepositoryError>;
 
+/// 更新语句中刷新修改时间的赋值
+pub const TOUCH_UPDATED_AT: &str = "updated_at = CURRENT_TIMESTAMP";
+
+/// 构建UPDATE语句，总是追加 `updated_at = CURRENT_TIMESTAMP`
+///
+/// 表的 `updated_at` 默认值只在插入时生效，所有更新都应通过这里构建语句，
+/// 保证增量迁移等依赖修改时间的功能拿到准确的值
+pub fn update_statement(table: &str, assignments: &[&str], condition: &str) -> String {
+    let assignments = assignments
+        .iter()
+        .copied()
+        .chain(std::iter::once(TOUCH_UPDATED_AT))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("UPDATE {} SET {} WHERE {}", table, assignments, condition)
+}
+
+/// 未单独声明时允许排序的列
+pub const DEFAULT_SORTABLE_COLUMNS: &[SortColumn] =
+    &[SortColumn::Id, SortColumn::CreatedAt, SortColumn::UpdatedAt];
+
+/// 构建 ORDER BY 子句内容，未指定排序时使用 `default_order`
+///
+/// 列名来自 `SortColumn` 而不是用户输入，不在 `allowed` 中的列返回验证错误
+pub fn order_by_clause(
+    order_by: Option<OrderBy>,
+    allowed: &[SortColumn],
+    default_order: &str,
+) -> RepositoryResult<String> {
+    match order_by {
+        Some(order_by) if allowed.contains(&order_by.column) => Ok(order_by.to_sql()),
+        Some(order_by) => Err(RepositoryError::Validation(format!(
+            "不支持按 {} 排序",
+            order_by.column.column_name()
+        ))),
+        None => Ok(default_order.to_string()),
+    }
+}
+
 /// 基础Repository trait
 #[allow(async_fn_in_trait)]
 pub trait BaseRepository {
@@ -53,6 +93,73 @@ pub trait BaseRepository {
     /// 获取加密服务
     fn crypto_service(&self) -> &CryptoService;
 
+    /// 获取查询计时器
+    fn query_timer(&self) -> &QueryTimer;
+
+    /// 执行查询并计时，操作名为 `表名.operation`
+    async fn timed<R, F>(&self, operation: &str, sql: &str, query: F) -> R
+    where
+        F: std::future::Future<Output = R>,
+    {
+        let operation = format!("{}.{}", Self::table_name(), operation);
+        self.query_timer().time(&operation, sql, query).await
+    }
+
+    /// 计时的执行器，操作名为 `表名.operation`，用法如 `.execute(self.executor("create", self.pool()))`
+    fn executor<E>(&self, operation: &str, executor: E) -> TimedExecutor<E> {
+        self.query_timer()
+            .executor(format!("{}.{}", Self::table_name(), operation), executor)
+    }
+
+    /// 允许排序的列
+    fn sortable_columns() -> &'static [SortColumn]
+    where
+        Self: Sized,
+    {
+        DEFAULT_SORTABLE_COLUMNS
+    }
+
+    /// ORDER BY 子句内容，未指定排序时使用 `default_order`
+    fn order_by_clause(order_by: Option<OrderBy>, default_order: &str) -> RepositoryResult<String>
+    where
+        Self: Sized,
+    {
+        order_by_clause(order_by, Self::sortable_columns(), default_order)
+    }
+
+    /// 构建按ID更新的语句，自动刷新 `updated_at`
+    fn update_by_id_statement(assignments: &[&str]) -> String
+    where
+        Self: Sized,
+    {
+        update_statement(Self::table_name(), assignments, "id = ?")
+    }
+
+    /// 解密记录中的加密字段，兼容旧版本遗留的明文数据
+    ///
+    /// 解密失败时读取记录的 `key_fingerprint` 列，数据由其他密钥加密时返回 `KeyMismatch`
+    async fn decrypt_stored(
+        &self,
+        id: i64,
+        value: &str,
+        crypto_service: &CryptoService,
+    ) -> RepositoryResult<String> {
+        match EncryptedField::decrypt_field_or_plaintext(value, crypto_service) {
+            Err(RepositoryError::Crypto(error)) => {
+                let query = format!(
+                    "SELECT key_fingerprint FROM {} WHERE id = ?",
+                    Self::table_name()
+                );
+                let stored: Option<Option<String>> = sqlx::query_scalar(&query)
+                    .bind(id)
+                    .fetch_optional(self.executor("find_key_fingerprint", self.pool()))
+                    .await?;
+                Err(crypto_service.check_key_fingerprint(error, stored.flatten().as_deref()).into())
+            }
+            result => result,
+        }
+    }
+
     /// 根据ID查找记录
     async fn find_by_id<T>(&self, id: i64) -> RepositoryResult<Option<T>>
     where
@@ -86,12 +193,13 @@ pub trait BaseRepository {
         T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
         Self: Sized;
 
-    /// 搜索记录
+    /// 搜索记录，`order_by` 为空时按ID倒序
     async fn search<T>(
         &self,
         search_term: &str,
         search_fields: &[&str],
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> RepositoryResult<Vec<T>>
     where
         T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
@@ -105,6 +213,7 @@ pub trait BaseRepository {
 pub struct GenericRepository<T> {
     pool: SqlitePool,
     crypto_service: CryptoService,
+    query_timer: QueryTimer,
     table_name: String,
     phantom: PhantomData<T>,
 }
@@ -119,6 +228,7 @@ impl<T> GenericRepository<T> {
         Self {
             pool: db_manager.pool().clone(),
             crypto_service: crypto_service.clone(),
+            query_timer: db_manager.query_timer(),
             table_name: table_name.to_string(),
             phantom: PhantomData,
         }
@@ -147,6 +257,23 @@ where
         &self.crypto_service
     }
 
+    fn query_timer(&self) -> &QueryTimer {
+        &self.query_timer
+    }
+
+    async fn timed<R, F>(&self, operation: &str, sql: &str, query: F) -> R
+    where
+        F: std::future::Future<Output = R>,
+    {
+        let operation = format!("{}.{}", self.table_name, operation);
+        self.query_timer.time(&operation, sql, query).await
+    }
+
+    fn executor<E>(&self, operation: &str, executor: E) -> TimedExecutor<E> {
+        self.query_timer
+            .executor(format!("{}.{}", self.table_name, operation), executor)
+    }
+
     async fn find_by_id<U>(&self, id: i64) -> RepositoryResult<Option<U>>
     where
         U: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
@@ -160,7 +287,13 @@ where
             query
         );
 
-        let result = sqlx::query_as::<_, U>(&query).bind(id).fetch_optional(self.pool()).await?;
+        let result = self
+            .timed(
+                "find_by_id",
+                &query,
+                sqlx::query_as::<_, U>(&query).bind(id).fetch_optional(self.pool()),
+            )
+            .await?;
 
         Ok(result)
     }
@@ -195,7 +328,13 @@ where
             query
         );
 
-        let result = sqlx::query(&query).bind(id).execute(self.pool()).await?;
+        let result = self
+            .timed(
+                "delete",
+                &query,
+                sqlx::query(&query).bind(id).execute(self.pool()),
+            )
+            .await?;
 
         Ok(result.rows_affected() > 0)
     }
@@ -212,7 +351,13 @@ where
             query
         );
 
-        let results = sqlx::query_as::<_, U>(&query).fetch_all(self.pool()).await?;
+        let results = self
+            .timed(
+                "list_all",
+                &query,
+                sqlx::query_as::<_, U>(&query).fetch_all(self.pool()),
+            )
+            .await?;
 
         Ok(results)
     }
@@ -226,13 +371,25 @@ where
         let offset = params.offset.unwrap_or((page - 1) * limit);
 
         // 查询总数
-        let count_query = format!("SELECT COUNT(*) FROM {}", self.table_name);
-        let total: i64 = sqlx::query_scalar(&count_query).fetch_one(self.pool()).await?;
+        let total: Option<i64> = if params.include_total() {
+            let count_query = format!("SELECT COUNT(*) FROM {}", self.table_name);
+            Some(
+                self.timed(
+                    "paginate",
+                    &count_query,
+                    sqlx::query_scalar(&count_query).fetch_one(self.pool()),
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
 
         // 查询分页数据
+        let order_by = Self::order_by_clause(params.order_by, "id DESC")?;
         let data_query = format!(
-            "SELECT * FROM {} ORDER BY id DESC LIMIT ? OFFSET ?",
-            self.table_name
+            "SELECT * FROM {} ORDER BY {} LIMIT ? OFFSET ?",
+            self.table_name, order_by
         );
 
         debug!(
@@ -244,14 +401,18 @@ where
             data_query
         );
 
-        let data = sqlx::query_as::<_, U>(&data_query)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(self.pool())
+        let data = self
+            .timed(
+                "paginate",
+                &data_query,
+                sqlx::query_as::<_, U>(&data_query)
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(self.pool()),
+            )
             .await?;
 
-        let _total_pages = (total + limit - 1) / limit;
-        let paged_result = PagedResult::new(data, total, page, limit);
+        let paged_result = PagedResult::with_optional_total(data, total, page, limit);
 
         Ok(paged_result)
     }
@@ -261,11 +422,13 @@ where
         search_term: &str,
         search_fields: &[&str],
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> RepositoryResult<Vec<U>>
     where
         U: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
     {
         let limit = limit.unwrap_or(50);
+        let order_by = Self::order_by_clause(order_by, "id DESC")?;
 
         // 构建搜索查询
         let mut where_conditions = Vec::new();
@@ -287,12 +450,16 @@ where
         }
 
         let query = if where_conditions.is_empty() {
-            format!("SELECT * FROM {} ORDER BY id DESC LIMIT ?", self.table_name)
+            format!(
+                "SELECT * FROM {} ORDER BY {} LIMIT ?",
+                self.table_name, order_by
+            )
         } else {
             format!(
-                "SELECT * FROM {} WHERE {} ORDER BY id DESC LIMIT ?",
+                "SELECT * FROM {} WHERE {} ORDER BY {} LIMIT ?",
                 self.table_name,
-                where_conditions.join(" OR ")
+                where_conditions.join(" OR "),
+                order_by
             )
         };
 
@@ -312,7 +479,7 @@ where
         }
         query_builder = query_builder.bind(limit);
 
-        let results = query_builder.fetch_all(self.pool()).await?;
+        let results = self.timed("search", &query, query_builder.fetch_all(self.pool())).await?;
 
         Ok(results)
     }
@@ -326,17 +493,28 @@ where
             query
         );
 
-        let count: i64 = sqlx::query_scalar(&query).fetch_one(self.pool()).await?;
+        let count: i64 = self
+            .timed(
+                "count",
+                &query,
+                sqlx::query_scalar(&query).fetch_one(self.pool()),
+            )
+            .await?;
 
         Ok(count)
     }
 }
 
+/// 是否已经对旧版明文数据发出过警告
+static LEGACY_PLAINTEXT_WARNED: AtomicBool = AtomicBool::new(false);
+
 /// 加密数据辅助函数
 pub struct EncryptedField;
 
 impl EncryptedField {
     /// 加密字符串字段
+    ///
+    /// 密文为标准Fernet格式，密钥指纹由调用方通过 [`CryptoService::storage_fingerprint`] 写入 `key_fingerprint` 列
     pub fn encrypt_field(
         encrypted_value: &str,
         crypto_service: &CryptoService,
@@ -352,6 +530,25 @@ impl EncryptedField {
         crypto_service.decrypt(encrypted_value).map_err(RepositoryError::Crypto)
     }
 
+    /// 解密字段，兼容旧版本遗留的明文数据
+    ///
+    /// 不是Fernet令牌格式的值视为明文直接返回（首次遇到时记录一次警告）。
+    /// 读取不会改写数据库，明文值在该字段被重新设置之前一直以明文保存
+    pub fn decrypt_field_or_plaintext(
+        value: &str,
+        crypto_service: &CryptoService,
+    ) -> RepositoryResult<String> {
+        if CryptoService::is_fernet_token(value) {
+            return Self::decrypt_field(value, crypto_service);
+        }
+
+        if !LEGACY_PLAINTEXT_WARNED.swap(true, Ordering::Relaxed) {
+            tracing::warn!("检测到未加密的旧版数据，将按明文读取；重新设置该值之前仍以明文保存");
+        }
+
+        Ok(value.to_string())
+    }
+
     /// 可选地解密字段
     pub fn optional_decrypt_field(
         encrypted_value: Option<String>,
@@ -438,6 +635,7 @@ mod tests {
             connect_timeout: std::time::Duration::from_secs(10),
             idle_timeout: std::time::Duration::from_secs(60),
             max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = DatabaseManager::new(config).await.unwrap();
@@ -479,4 +677,54 @@ mod tests {
         let or_condition = QueryBuilder::or_conditions(&["name = 'test'", "status = 'active'"]);
         assert_eq!(or_condition, "name = 'test' OR status = 'active'");
     }
+
+    #[tokio::test]
+    async fn test_paginate_without_total_skips_count_query() {
+        let temp_dir = tempdir().unwrap();
+        let config = DatabaseConfig {
+            url: format!(
+                "sqlite:{}",
+                temp_dir.path().join("test_with_total.db").display()
+            ),
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: std::time::Duration::from_secs(10),
+            idle_timeout: std::time::Duration::from_secs(60),
+            max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
+        };
+        let db_manager = DatabaseManager::new(config).await.unwrap();
+
+        // 扫描到 id = 1 的行时 abs(i64::MIN) 会溢出报错：
+        // 分页查询按 id 倒序只读取第一行，只有 COUNT(*) 会扫描到这一行
+        for statement in [
+            "CREATE TABLE count_probe (id INTEGER PRIMARY KEY)",
+            "INSERT INTO count_probe (id) VALUES (1), (2)",
+            "CREATE VIEW count_probe_view AS SELECT id FROM count_probe \
+             WHERE abs(id - 9223372036854775807 - 2) > 0",
+        ] {
+            sqlx::query(statement).execute(db_manager.pool()).await.unwrap();
+        }
+
+        let crypto_service =
+            CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap();
+        let repository: GenericRepository<(i64,)> =
+            GenericRepository::new(&db_manager, "count_probe_view", &crypto_service);
+
+        let params = PaginationParams {
+            page: Some(1),
+            limit: Some(1),
+            offset: None,
+            with_total: Some(false),
+            order_by: None,
+        };
+        let result = repository.paginate::<(i64,)>(&params).await.unwrap();
+        assert_eq!(result.data, vec![(2,)]);
+        assert_eq!(result.total, None);
+        assert_eq!(result.total_pages, None);
+
+        // 默认仍然查询总数，此时会执行 COUNT(*) 并触发报错
+        let params = PaginationParams { with_total: None, ..params };
+        assert!(repository.paginate::<(i64,)>(&params).await.is_err());
+    }
 }
@@ -3,16 +3,22 @@
 // 提供Claude供应商的特定数据访问操作
 
 use crate::crypto::CryptoService;
-use crate::database::DatabaseManager;
-use crate::models::{ClaudeProvider, CreateClaudeProviderRequest, UpdateClaudeProviderRequest};
+use crate::database::{DatabaseManager, QueryTimer};
+use crate::models::{
+    merge_legacy_models, ClaudeProvider, CreateClaudeProviderRequest, OrderBy, SortColumn,
+    UpdateClaudeProviderRequest, MODEL_ROLE_HAIKU, MODEL_ROLE_OPUS, MODEL_ROLE_SONNET,
+};
 use crate::repositories::base_repository::{BaseRepository, RepositoryError, RepositoryResult};
-use sqlx::{FromRow, SqlitePool};
+use crate::utils::validation::normalize_url;
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use std::collections::HashMap;
 
 /// Claude供应商Repository
 #[derive(Clone)]
 pub struct ClaudeProviderRepository {
     pub pool: SqlitePool,
     crypto_service: CryptoService,
+    query_timer: QueryTimer,
     // pub(crate) db_manager: (),
 }
 
@@ -22,6 +28,7 @@ impl ClaudeProviderRepository {
         Self {
             pool: db_manager.pool().clone(),
             crypto_service: crypto_service.clone(),
+            query_timer: db_manager.query_timer(),
         }
     }
 
@@ -36,12 +43,21 @@ impl ClaudeProviderRepository {
             &self.crypto_service,
         )?;
 
+        // 旧的三个模型字段与模型映射保持同步
+        let models = merge_legacy_models(
+            request.models.clone().unwrap_or_default(),
+            &request.opus_model,
+            &request.sonnet_model,
+            &request.haiku_model,
+        );
+
         let query = r#"
             INSERT INTO claude_providers (
                 name, url, token, timeout, auto_update, type,
-                opus_model, sonnet_model, haiku_model, enabled,
-                created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now'), datetime('now'))
+                opus_model, sonnet_model, haiku_model, models, custom_headers,
+                accepted_status_codes, model_auto_update, enabled, normalized_url,
+                key_fingerprint, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now'), datetime('now'))
         "#;
 
         tracing::info!(
@@ -57,11 +73,21 @@ impl ClaudeProviderRepository {
             .bind(request.timeout)
             .bind(request.auto_update)
             .bind(&request.r#type)
-            .bind(&request.opus_model)
-            .bind(&request.sonnet_model)
-            .bind(&request.haiku_model)
+            .bind(models.get(MODEL_ROLE_OPUS).cloned())
+            .bind(models.get(MODEL_ROLE_SONNET).cloned())
+            .bind(models.get(MODEL_ROLE_HAIKU).cloned())
+            .bind(serde_json::to_string(&models)?)
+            .bind(serde_json::to_string(
+                &request.custom_headers.clone().unwrap_or_default(),
+            )?)
+            .bind(serde_json::to_string(
+                &request.accepted_status_codes.clone().unwrap_or_default(),
+            )?)
+            .bind(request.model_auto_update.unwrap_or(0))
             .bind(1i64) // 默认启用
-            .execute(&self.pool)
+            .bind(normalize_url(&request.url))
+            .bind(self.crypto_service.storage_fingerprint())
+            .execute(self.executor("create", &self.pool))
             .await?;
 
         Ok(result.last_insert_rowid())
@@ -74,13 +100,23 @@ impl ClaudeProviderRepository {
         request: &UpdateClaudeProviderRequest,
     ) -> RepositoryResult<bool> {
         // 获取现有记录
-        let existing = self.find_by_id::<ClaudeProvider>(id).await?;
-        if existing.is_none() {
-            return Err(RepositoryError::NotFound(format!(
-                "Claude供应商 ID {} 不存在",
-                id
-            )));
-        }
+        let existing = match self.find_by_id::<ClaudeProvider>(id).await? {
+            Some(existing) => existing,
+            None => {
+                return Err(RepositoryError::NotFound(format!(
+                    "Claude供应商 ID {} 不存在",
+                    id
+                )));
+            }
+        };
+
+        // 提供了模型映射时整体替换，旧的三个模型字段在其基础上覆盖
+        let models = merge_legacy_models(
+            request.models.clone().unwrap_or_else(|| existing.effective_models()),
+            &request.opus_model,
+            &request.sonnet_model,
+            &request.haiku_model,
+        );
 
         // 如果提供了新的token，则加密它
         let encrypted_token = if let Some(ref token) = request.token {
@@ -94,40 +130,78 @@ impl ClaudeProviderRepository {
             None
         };
 
-        let query = r#"
-            UPDATE claude_providers SET
-                name = COALESCE(?, name),
-                url = COALESCE(?, url),
-                token = COALESCE(?, token),
-                timeout = COALESCE(?, timeout),
-                auto_update = COALESCE(?, auto_update),
-                type = COALESCE(?, type),
-                enabled = COALESCE(?, enabled),
-                opus_model = COALESCE(?, opus_model),
-                sonnet_model = COALESCE(?, sonnet_model),
-                haiku_model = COALESCE(?, haiku_model),
-                updated_at = datetime('now')
-            WHERE id = ?
-        "#;
+        let query = Self::update_by_id_statement(&[
+            "name = COALESCE(?, name)",
+            "url = COALESCE(?, url)",
+            "token = COALESCE(?, token)",
+            "timeout = COALESCE(?, timeout)",
+            "auto_update = COALESCE(?, auto_update)",
+            "type = COALESCE(?, type)",
+            "enabled = COALESCE(?, enabled)",
+            "opus_model = ?",
+            "sonnet_model = ?",
+            "haiku_model = ?",
+            "models = ?",
+            "custom_headers = COALESCE(?, custom_headers)",
+            "accepted_status_codes = COALESCE(?, accepted_status_codes)",
+            "model_auto_update = COALESCE(?, model_auto_update)",
+            "normalized_url = COALESCE(?, normalized_url)",
+            "key_fingerprint = CASE WHEN ? IS NOT NULL THEN ? ELSE key_fingerprint END",
+        ]);
 
         tracing::info!(
             id = %id,
             "更新Claude供应商"
         );
 
-        let result = sqlx::query(query)
+        let result = sqlx::query(&query)
             .bind(&request.name)
             .bind(&request.url)
-            .bind(encrypted_token)
+            .bind(&encrypted_token)
             .bind(request.timeout)
             .bind(request.auto_update)
             .bind(&request.r#type)
             .bind(request.enabled)
-            .bind(&request.opus_model)
-            .bind(&request.sonnet_model)
-            .bind(&request.haiku_model)
+            .bind(models.get(MODEL_ROLE_OPUS).cloned())
+            .bind(models.get(MODEL_ROLE_SONNET).cloned())
+            .bind(models.get(MODEL_ROLE_HAIKU).cloned())
+            .bind(serde_json::to_string(&models)?)
+            .bind(request.custom_headers.as_ref().map(serde_json::to_string).transpose()?)
+            .bind(request.accepted_status_codes.as_ref().map(serde_json::to_string).transpose()?)
+            .bind(request.model_auto_update)
+            .bind(request.url.as_deref().map(normalize_url))
+            .bind(&encrypted_token)
+            .bind(self.crypto_service.storage_fingerprint())
             .bind(id)
-            .execute(&self.pool)
+            .execute(self.executor("update", &self.pool))
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 在调用方的事务中替换供应商的模型配置
+    ///
+    /// 旧的三个模型字段与模型映射同步更新
+    pub async fn update_models(
+        &self,
+        conn: &mut SqliteConnection,
+        id: i64,
+        models: &HashMap<String, String>,
+    ) -> RepositoryResult<bool> {
+        let query = Self::update_by_id_statement(&[
+            "opus_model = ?",
+            "sonnet_model = ?",
+            "haiku_model = ?",
+            "models = ?",
+        ]);
+
+        let result = sqlx::query(&query)
+            .bind(models.get(MODEL_ROLE_OPUS).cloned())
+            .bind(models.get(MODEL_ROLE_SONNET).cloned())
+            .bind(models.get(MODEL_ROLE_HAIKU).cloned())
+            .bind(serde_json::to_string(models)?)
+            .bind(id)
+            .execute(self.executor("update_models", &mut *conn))
             .await?;
 
         Ok(result.rows_affected() > 0)
@@ -141,10 +215,7 @@ impl ClaudeProviderRepository {
         let mut decrypted_providers = Vec::new();
         for provider in providers {
             let decrypted_token =
-                crate::repositories::base_repository::EncryptedField::decrypt_field(
-                    &provider.token,
-                    &self.crypto_service,
-                )?;
+                self.decrypt_stored(provider.id, &provider.token, &self.crypto_service).await?;
 
             let mut decrypted_provider = provider;
             decrypted_provider.token = decrypted_token;
@@ -158,10 +229,7 @@ impl ClaudeProviderRepository {
     pub async fn find_by_id_decrypted(&self, id: i64) -> RepositoryResult<Option<ClaudeProvider>> {
         if let Some(provider) = self.find_by_id::<ClaudeProvider>(id).await? {
             let decrypted_token =
-                crate::repositories::base_repository::EncryptedField::decrypt_field(
-                    &provider.token,
-                    &self.crypto_service,
-                )?;
+                self.decrypt_stored(provider.id, &provider.token, &self.crypto_service).await?;
 
             let mut decrypted_provider = provider;
             decrypted_provider.token = decrypted_token;
@@ -171,31 +239,83 @@ impl ClaudeProviderRepository {
         }
     }
 
+    /// 根据URL查找Claude供应商（解密token）
+    ///
+    /// 按规范化URL查询，忽略末尾斜杠和协议、主机的大小写；URL不唯一，返回所有匹配项
+    pub async fn find_by_url(&self, url: &str) -> RepositoryResult<Vec<ClaudeProvider>> {
+        let normalized = normalize_url(url);
+
+        tracing::debug!(url = %url, normalized = %normalized, "根据URL查找Claude供应商");
+
+        self.fill_normalized_urls().await?;
+        let mut providers: Vec<ClaudeProvider> =
+            sqlx::query_as("SELECT * FROM claude_providers WHERE normalized_url = ? ORDER BY id")
+                .bind(&normalized)
+                .fetch_all(self.executor("find_by_url", &self.pool))
+                .await?;
+
+        for provider in &mut providers {
+            provider.token =
+                self.decrypt_stored(provider.id, &provider.token, &self.crypto_service).await?;
+        }
+        Ok(providers)
+    }
+
+    /// 补全规范化URL为空的记录
+    ///
+    /// 导入等途径写入或修改了URL的记录没有规范化URL，查询前按当前规则计算；
+    /// 只写入该列时触发器不刷新修改时间，因此不使用 `update_by_id_statement`
+    async fn fill_normalized_urls(&self) -> RepositoryResult<()> {
+        let missing: Vec<(i64, String)> =
+            sqlx::query_as("SELECT id, url FROM claude_providers WHERE normalized_url IS NULL")
+                .fetch_all(self.executor("find_missing_normalized_url", &self.pool))
+                .await?;
+
+        for (id, url) in missing {
+            sqlx::query("UPDATE claude_providers SET normalized_url = ? WHERE id = ? AND url = ?")
+                .bind(normalize_url(&url))
+                .bind(id)
+                .bind(&url)
+                .execute(self.executor("fill_normalized_url", &self.pool))
+                .await?;
+        }
+        Ok(())
+    }
+
     /// 搜索Claude供应商（优化版本，使用全文搜索索引）
     pub async fn search_claude_providers(
         &self,
         search_term: &str,
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> RepositoryResult<Vec<ClaudeProvider>> {
         if search_term.trim().is_empty() {
             return Err(RepositoryError::Validation("搜索词不能为空".to_string()));
         }
 
         let limit = limit.unwrap_or(50);
+        // 未指定排序时名称匹配的记录优先
+        let prefer_name = order_by.is_none();
+        let order_by = if prefer_name {
+            "CASE WHEN name LIKE ? THEN 1 ELSE 2 END, id DESC".to_string()
+        } else {
+            Self::order_by_clause(order_by, "id DESC")?
+        };
 
         // 使用优化的搜索查询，优先搜索名称字段
-        let query = r#"
+        let query = format!(
+            r#"
             SELECT * FROM claude_providers 
             WHERE name LIKE ? 
                OR url LIKE ? 
                OR opus_model LIKE ? 
                OR sonnet_model LIKE ? 
                OR haiku_model LIKE ? 
-            ORDER BY 
-                CASE WHEN name LIKE ? THEN 1 ELSE 2 END,
-                id DESC
+            ORDER BY {}
             LIMIT ?
-        "#;
+        "#,
+            order_by
+        );
 
         let search_pattern = format!("%{}%", search_term);
 
@@ -205,27 +325,36 @@ impl ClaudeProviderRepository {
             "执行优化的Claude供应商搜索"
         );
 
-        let results = sqlx::query_as::<_, ClaudeProvider>(query)
+        let mut query_builder = sqlx::query_as::<_, ClaudeProvider>(&query)
             .bind(&search_pattern) // name LIKE
             .bind(&search_pattern) // url LIKE
             .bind(&search_pattern) // opus_model LIKE
             .bind(&search_pattern) // sonnet_model LIKE
-            .bind(&search_pattern) // haiku_model LIKE
-            .bind(&search_pattern) // ORDER BY name LIKE
-            .bind(limit)
-            .fetch_all(&self.pool)
-            .await?;
+            .bind(&search_pattern); // haiku_model LIKE
+        if prefer_name {
+            query_builder = query_builder.bind(&search_pattern); // ORDER BY name LIKE
+        }
+        let results =
+            query_builder.bind(limit).fetch_all(self.executor("search", &self.pool)).await?;
 
         Ok(results)
     }
 
     /// 获取活跃的Claude供应商
-    pub async fn list_active_providers(&self) -> RepositoryResult<Vec<ClaudeProvider>> {
-        let query = "SELECT * FROM claude_providers WHERE enabled = 1 ORDER BY id DESC";
+    pub async fn list_active_providers(
+        &self,
+        order_by: Option<OrderBy>,
+    ) -> RepositoryResult<Vec<ClaudeProvider>> {
+        let query = format!(
+            "SELECT * FROM claude_providers WHERE enabled = 1 ORDER BY {}",
+            Self::order_by_clause(order_by, "id DESC")?
+        );
 
         tracing::debug!("获取活跃的Claude供应商列表");
 
-        let results = sqlx::query_as::<_, ClaudeProvider>(query).fetch_all(&self.pool).await?;
+        let results = sqlx::query_as::<_, ClaudeProvider>(&query)
+            .fetch_all(self.executor("list_active", &self.pool))
+            .await?;
 
         Ok(results)
     }
@@ -258,7 +387,7 @@ impl ClaudeProviderRepository {
 
         let count: i64 = sqlx::query_scalar(query)
             .bind(if is_active { 1 } else { 0 })
-            .fetch_one(&self.pool)
+            .fetch_one(self.executor("count_by_status", &self.pool))
             .await?;
 
         Ok(count)
@@ -270,6 +399,17 @@ impl BaseRepository for ClaudeProviderRepository {
         "claude_providers"
     }
 
+    fn sortable_columns() -> &'static [SortColumn] {
+        &[
+            SortColumn::Id,
+            SortColumn::Name,
+            SortColumn::Type,
+            SortColumn::Enabled,
+            SortColumn::CreatedAt,
+            SortColumn::UpdatedAt,
+        ]
+    }
+
     fn pool(&self) -> &SqlitePool {
         &self.pool
     }
@@ -278,6 +418,10 @@ impl BaseRepository for ClaudeProviderRepository {
         &self.crypto_service
     }
 
+    fn query_timer(&self) -> &QueryTimer {
+        &self.query_timer
+    }
+
     async fn find_by_id<T>(&self, id: i64) -> RepositoryResult<Option<T>>
     where
         T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
@@ -291,7 +435,13 @@ impl BaseRepository for ClaudeProviderRepository {
             query
         );
 
-        let result = sqlx::query_as::<_, T>(&query).bind(id).fetch_optional(self.pool()).await?;
+        let result = self
+            .timed(
+                "find_by_id",
+                &query,
+                sqlx::query_as::<_, T>(&query).bind(id).fetch_optional(self.pool()),
+            )
+            .await?;
 
         Ok(result)
     }
@@ -324,7 +474,13 @@ impl BaseRepository for ClaudeProviderRepository {
             "删除Claude供应商"
         );
 
-        let result = sqlx::query(query).bind(id).execute(self.pool()).await?;
+        let result = self
+            .timed(
+                "delete",
+                query,
+                sqlx::query(query).bind(id).execute(self.pool()),
+            )
+            .await?;
 
         Ok(result.rows_affected() > 0)
     }
@@ -337,7 +493,13 @@ impl BaseRepository for ClaudeProviderRepository {
 
         tracing::debug!("获取Claude供应商列表");
 
-        let results = sqlx::query_as::<_, T>(query).fetch_all(self.pool()).await?;
+        let results = self
+            .timed(
+                "list_all",
+                query,
+                sqlx::query_as::<_, T>(query).fetch_all(self.pool()),
+            )
+            .await?;
 
         Ok(results)
     }
@@ -353,8 +515,10 @@ impl BaseRepository for ClaudeProviderRepository {
         let limit = params.limit.unwrap_or(20);
         let offset = params.offset.unwrap_or((page - 1) * limit);
 
-        // 使用子查询优化分页性能（避免大偏移量问题）
-        let data_query = r#"
+        // 默认按ID倒序时使用子查询优化分页性能（避免大偏移量问题）
+        let (data_query, binds) = match params.order_by {
+            None => (
+                r#"
             SELECT * FROM claude_providers 
             WHERE id <= (
                 SELECT id FROM claude_providers 
@@ -363,7 +527,18 @@ impl BaseRepository for ClaudeProviderRepository {
             )
             ORDER BY id DESC 
             LIMIT ?
-        "#;
+        "#
+                .to_string(),
+                [offset, limit],
+            ),
+            Some(_) => (
+                format!(
+                    "SELECT * FROM claude_providers ORDER BY {} LIMIT ? OFFSET ?",
+                    Self::order_by_clause(params.order_by, "id DESC")?
+                ),
+                [limit, offset],
+            ),
+        };
 
         // 获取总数（缓存友好的查询）
         let count_query = "SELECT COUNT(*) FROM claude_providers";
@@ -378,17 +553,25 @@ impl BaseRepository for ClaudeProviderRepository {
         // 并行执行查询以提高性能
         let (data, total) = tokio::try_join!(
             async {
-                sqlx::query_as::<_, T>(data_query)
-                    .bind(offset)
-                    .bind(limit)
-                    .fetch_all(self.pool())
-                    .await
+                let query = sqlx::query_as::<_, T>(&data_query)
+                    .bind(binds[0])
+                    .bind(binds[1])
+                    .fetch_all(self.pool());
+                self.timed("paginate", &data_query, query).await
             },
-            async { sqlx::query_scalar::<_, i64>(count_query).fetch_one(self.pool()).await }
+            async {
+                if params.include_total() {
+                    let query = sqlx::query_scalar::<_, i64>(count_query).fetch_one(self.pool());
+                    self.timed("paginate", count_query, query).await.map(Some)
+                } else {
+                    Ok(None)
+                }
+            }
         )
         .map_err(|e| RepositoryError::Query(format!("并行查询失败: {}", e)))?;
 
-        let paged_result = crate::models::PagedResult::new(data, total, page, limit);
+        let paged_result =
+            crate::models::PagedResult::with_optional_total(data, total, page, limit);
 
         Ok(paged_result)
     }
@@ -398,11 +581,13 @@ impl BaseRepository for ClaudeProviderRepository {
         search_term: &str,
         search_fields: &[&str],
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> RepositoryResult<Vec<T>>
     where
         T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
     {
         let limit = limit.unwrap_or(50);
+        let order_by = Self::order_by_clause(order_by, "id DESC")?;
 
         // 构建搜索查询
         let mut where_conditions = Vec::new();
@@ -424,11 +609,15 @@ impl BaseRepository for ClaudeProviderRepository {
         }
 
         let query = if where_conditions.is_empty() {
-            "SELECT * FROM claude_providers ORDER BY id DESC LIMIT ?".to_string()
+            format!(
+                "SELECT * FROM claude_providers ORDER BY {} LIMIT ?",
+                order_by
+            )
         } else {
             format!(
-                "SELECT * FROM claude_providers WHERE {} ORDER BY id DESC LIMIT ?",
-                where_conditions.join(" OR ")
+                "SELECT * FROM claude_providers WHERE {} ORDER BY {} LIMIT ?",
+                where_conditions.join(" OR "),
+                order_by
             )
         };
 
@@ -448,7 +637,7 @@ impl BaseRepository for ClaudeProviderRepository {
         }
         query_builder = query_builder.bind(limit);
 
-        let results = query_builder.fetch_all(self.pool()).await?;
+        let results = self.timed("search", &query, query_builder.fetch_all(self.pool())).await?;
 
         Ok(results)
     }
@@ -458,7 +647,13 @@ impl BaseRepository for ClaudeProviderRepository {
 
         tracing::debug!("统计Claude供应商总数");
 
-        let count: i64 = sqlx::query_scalar(query).fetch_one(self.pool()).await?;
+        let count: i64 = self
+            .timed(
+                "count",
+                query,
+                sqlx::query_scalar(query).fetch_one(self.pool()),
+            )
+            .await?;
 
         Ok(count)
     }
@@ -482,6 +677,7 @@ mod tests {
             connect_timeout: std::time::Duration::from_secs(10),
             idle_timeout: std::time::Duration::from_secs(60),
             max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = DatabaseManager::new(config).await.unwrap();
@@ -506,6 +702,10 @@ mod tests {
             opus_model: Some("claude-3-opus-20240229".to_string()),
             sonnet_model: Some("claude-3-sonnet-20241022".to_string()),
             haiku_model: Some("claude-3-haiku-20240307".to_string()),
+            models: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+            model_auto_update: None,
         };
 
         let id = repo.create_claude_provider(&create_request).await.unwrap();
@@ -530,6 +730,10 @@ mod tests {
             opus_model: None,
             sonnet_model: None,
             haiku_model: None,
+            models: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+            model_auto_update: None,
         };
 
         let updated = repo.update_claude_provider(id, &update_request).await.unwrap();
@@ -551,4 +755,242 @@ mod tests {
         let deleted_provider = repo.find_by_id_decrypted(id).await.unwrap();
         assert!(deleted_provider.is_none());
     }
+
+    #[tokio::test]
+    async fn test_read_legacy_plaintext_token() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test_claude_legacy.db");
+
+        let config = DatabaseConfig {
+            url: format!("sqlite:{}", db_path.display()),
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: std::time::Duration::from_secs(10),
+            idle_timeout: std::time::Duration::from_secs(60),
+            max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
+        };
+
+        let db_manager = DatabaseManager::new(config).await.unwrap();
+        let crypto_service =
+            CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap();
+        let repo = ClaudeProviderRepository::new(&db_manager, &crypto_service);
+
+        let encrypted_id = repo
+            .create_claude_provider(&CreateClaudeProviderRequest {
+                name: "加密供应商".to_string(),
+                url: "https://api.anthropic.com".to_string(),
+                token: "sk-encrypted-token".to_string(),
+                timeout: None,
+                auto_update: None,
+                r#type: None,
+                opus_model: None,
+                sonnet_model: None,
+                haiku_model: None,
+                models: None,
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
+            })
+            .await
+            .unwrap();
+
+        // 模拟旧版本遗留的明文token
+        let plaintext_id = sqlx::query(
+            "INSERT INTO claude_providers (name, url, token) VALUES ('明文供应商', 'https://api.anthropic.com', 'sk-legacy-plaintext')",
+        )
+        .execute(&repo.pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+
+        let encrypted = repo.find_by_id_decrypted(encrypted_id).await.unwrap().unwrap();
+        assert_eq!(encrypted.token, "sk-encrypted-token");
+
+        let plaintext = repo.find_by_id_decrypted(plaintext_id).await.unwrap().unwrap();
+        assert_eq!(plaintext.token, "sk-legacy-plaintext");
+
+        let providers = repo.list_claude_providers_decrypted().await.unwrap();
+        assert_eq!(providers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_models_map_roundtrip_with_five_roles() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test_claude_models.db");
+
+        let config = DatabaseConfig {
+            url: format!("sqlite:{}", db_path.display()),
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: std::time::Duration::from_secs(10),
+            idle_timeout: std::time::Duration::from_secs(60),
+            max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
+        };
+
+        let db_manager = DatabaseManager::new(config).await.unwrap();
+        let crypto_service =
+            CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap();
+        let repo = ClaudeProviderRepository::new(&db_manager, &crypto_service);
+
+        let models: std::collections::HashMap<String, String> = [
+            ("opus", "claude-opus"),
+            ("sonnet", "claude-sonnet"),
+            ("haiku", "claude-haiku"),
+            ("reasoning", "claude-reasoning"),
+            ("fast", "claude-fast"),
+        ]
+        .into_iter()
+        .map(|(role, model)| (role.to_string(), model.to_string()))
+        .collect();
+
+        let id = repo
+            .create_claude_provider(&CreateClaudeProviderRequest {
+                name: "多模型供应商".to_string(),
+                url: "https://api.anthropic.com".to_string(),
+                token: "sk-models".to_string(),
+                timeout: None,
+                auto_update: None,
+                r#type: None,
+                opus_model: None,
+                sonnet_model: None,
+                haiku_model: None,
+                models: Some(models.clone()),
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
+            })
+            .await
+            .unwrap();
+
+        let provider = repo.find_by_id_decrypted(id).await.unwrap().unwrap();
+        assert_eq!(provider.models, models);
+        // 旧字段作为兼容视图同步写入
+        assert_eq!(provider.opus_model.as_deref(), Some("claude-opus"));
+        assert_eq!(provider.haiku_model.as_deref(), Some("claude-haiku"));
+
+        // 通过旧字段更新时同步修改映射，其他角色保持不变
+        repo.update_claude_provider(
+            id,
+            &UpdateClaudeProviderRequest {
+                name: None,
+                url: None,
+                token: None,
+                timeout: None,
+                auto_update: None,
+                r#type: None,
+                enabled: None,
+                opus_model: Some("claude-opus-next".to_string()),
+                sonnet_model: None,
+                haiku_model: Some(String::new()),
+                models: None,
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let provider = repo.find_by_id_decrypted(id).await.unwrap().unwrap();
+        assert_eq!(provider.models.len(), 4);
+        assert_eq!(provider.model("opus").as_deref(), Some("claude-opus-next"));
+        assert_eq!(provider.model("fast").as_deref(), Some("claude-fast"));
+        assert!(provider.haiku_model.is_none());
+        assert!(provider.model("haiku").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_find_by_url_normalizes_and_returns_all_matches() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test_claude_find_by_url.db");
+
+        let config = DatabaseConfig {
+            url: format!("sqlite:{}", db_path.display()),
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: std::time::Duration::from_secs(10),
+            idle_timeout: std::time::Duration::from_secs(60),
+            max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
+        };
+
+        let db_manager = DatabaseManager::new(config).await.unwrap();
+        db_manager.ensure_initialized().await.unwrap();
+        let crypto_service =
+            CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap();
+        let repo = ClaudeProviderRepository::new(&db_manager, &crypto_service);
+
+        for (name, url) in [
+            ("无斜杠", "https://api.example.com"),
+            ("带斜杠", "https://api.example.com/"),
+            ("大写主机", "HTTPS://API.Example.COM"),
+            ("其他路径", "https://api.example.com/v1"),
+            ("其他主机", "https://other.example.com"),
+        ] {
+            repo.create_claude_provider(&CreateClaudeProviderRequest {
+                name: name.to_string(),
+                url: url.to_string(),
+                token: "sk-find-by-url".to_string(),
+                timeout: None,
+                auto_update: None,
+                r#type: None,
+                opus_model: None,
+                sonnet_model: None,
+                haiku_model: None,
+                models: None,
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
+            })
+            .await
+            .unwrap();
+        }
+
+        let mut names: Vec<String> = repo
+            .find_by_url("https://api.example.com/")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|provider| provider.name)
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["大写主机", "带斜杠", "无斜杠"]);
+
+        // 路径部分区分大小写，但末尾斜杠同样被忽略
+        let matches = repo.find_by_url(" https://API.example.com/v1/ ").await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].name, "其他路径");
+        assert_eq!(matches[0].token, "sk-find-by-url");
+        assert!(repo.find_by_url("https://api.example.com/V1").await.unwrap().is_empty());
+        assert!(repo.find_by_url("https://missing.example.com").await.unwrap().is_empty());
+
+        // 不经过仓库写入或修改URL的记录在查询前补全规范化URL，补全不刷新修改时间
+        sqlx::query(
+            "INSERT INTO claude_providers (name, url, token, updated_at)
+             VALUES ('导入', 'HTTPS://Imported.example.com/', 'sk-imported', '2000-01-01 00:00:00')",
+        )
+        .execute(db_manager.pool())
+        .await
+        .unwrap();
+        sqlx::query(
+            "UPDATE claude_providers SET url = 'https://moved.example.com' WHERE name = '其他主机'",
+        )
+        .execute(db_manager.pool())
+        .await
+        .unwrap();
+
+        let imported = repo.find_by_url("https://imported.example.com").await.unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(
+            imported[0].updated_at.as_deref(),
+            Some("2000-01-01 00:00:00")
+        );
+        assert!(repo.find_by_url("https://other.example.com").await.unwrap().is_empty());
+        assert_eq!(
+            repo.find_by_url("https://moved.example.com/").await.unwrap().len(),
+            1
+        );
+    }
 }
@@ -3,15 +3,20 @@
 // 提供Codex供应商的特定数据访问操作
 
 use crate::crypto::CryptoService;
-use crate::database::DatabaseManager;
-use crate::models::{CodexProvider, CreateCodexProviderRequest, UpdateCodexProviderRequest};
+use crate::database::{DatabaseManager, QueryTimer};
+use crate::models::{
+    CodexProvider, CreateCodexProviderRequest, OrderBy, SortColumn, UpdateCodexProviderRequest,
+};
 use crate::repositories::base_repository::{BaseRepository, RepositoryError, RepositoryResult};
+use crate::utils::validation::normalize_url;
 use sqlx::{FromRow, SqlitePool};
 
 /// Codex供应商Repository
+#[derive(Clone)]
 pub struct CodexProviderRepository {
     pool: SqlitePool,
     crypto_service: CryptoService,
+    query_timer: QueryTimer,
 }
 
 impl CodexProviderRepository {
@@ -20,6 +25,7 @@ impl CodexProviderRepository {
         Self {
             pool: db_manager.pool().clone(),
             crypto_service: crypto_service.clone(),
+            query_timer: db_manager.query_timer(),
         }
     }
 
@@ -36,8 +42,9 @@ impl CodexProviderRepository {
 
         let query = r#"
             INSERT INTO codex_providers (
-                name, url, token, type, enabled, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, datetime('now'), datetime('now'))
+                name, url, token, type, custom_headers, accepted_status_codes, enabled,
+                normalized_url, key_fingerprint, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now'), datetime('now'))
         "#;
 
         tracing::info!(
@@ -51,8 +58,16 @@ impl CodexProviderRepository {
             .bind(&request.url)
             .bind(encrypted_token)
             .bind(&request.r#type)
+            .bind(serde_json::to_string(
+                &request.custom_headers.clone().unwrap_or_default(),
+            )?)
+            .bind(serde_json::to_string(
+                &request.accepted_status_codes.clone().unwrap_or_default(),
+            )?)
             .bind(1i64) // 默认启用
-            .execute(&self.pool)
+            .bind(normalize_url(&request.url))
+            .bind(self.crypto_service.storage_fingerprint())
+            .execute(self.executor("create", &self.pool))
             .await?;
 
         Ok(result.last_insert_rowid())
@@ -85,31 +100,37 @@ impl CodexProviderRepository {
             None
         };
 
-        let query = r#"
-            UPDATE codex_providers SET
-                name = COALESCE(?, name),
-                url = COALESCE(?, url),
-                token = CASE WHEN ? IS NOT NULL THEN ? ELSE token END,
-                type = COALESCE(?, type),
-                enabled = COALESCE(?, enabled),
-                updated_at = datetime('now')
-            WHERE id = ?
-        "#;
+        let query = Self::update_by_id_statement(&[
+            "name = COALESCE(?, name)",
+            "url = COALESCE(?, url)",
+            "token = CASE WHEN ? IS NOT NULL THEN ? ELSE token END",
+            "type = COALESCE(?, type)",
+            "enabled = COALESCE(?, enabled)",
+            "custom_headers = COALESCE(?, custom_headers)",
+            "accepted_status_codes = COALESCE(?, accepted_status_codes)",
+            "normalized_url = COALESCE(?, normalized_url)",
+            "key_fingerprint = CASE WHEN ? IS NOT NULL THEN ? ELSE key_fingerprint END",
+        ]);
 
         tracing::info!(
             id = %id,
             "更新Codex供应商"
         );
 
-        let result = sqlx::query(query)
+        let result = sqlx::query(&query)
             .bind(&request.name)
             .bind(&request.url)
             .bind(&request.token)
             .bind(encrypted_token.as_ref())
             .bind(&request.r#type)
             .bind(request.enabled)
+            .bind(request.custom_headers.as_ref().map(serde_json::to_string).transpose()?)
+            .bind(request.accepted_status_codes.as_ref().map(serde_json::to_string).transpose()?)
+            .bind(request.url.as_deref().map(normalize_url))
+            .bind(encrypted_token.as_ref())
+            .bind(self.crypto_service.storage_fingerprint())
             .bind(id)
-            .execute(&self.pool)
+            .execute(self.executor("update", &self.pool))
             .await?;
 
         Ok(result.rows_affected() > 0)
@@ -123,10 +144,7 @@ impl CodexProviderRepository {
         let mut decrypted_providers = Vec::new();
         for provider in providers {
             let decrypted_token =
-                crate::repositories::base_repository::EncryptedField::decrypt_field(
-                    &provider.token,
-                    &self.crypto_service,
-                )?;
+                self.decrypt_stored(provider.id, &provider.token, &self.crypto_service).await?;
 
             let mut decrypted_provider = provider;
             decrypted_provider.token = decrypted_token;
@@ -140,10 +158,7 @@ impl CodexProviderRepository {
     pub async fn find_by_id_decrypted(&self, id: i64) -> RepositoryResult<Option<CodexProvider>> {
         if let Some(provider) = self.find_by_id::<CodexProvider>(id).await? {
             let decrypted_token =
-                crate::repositories::base_repository::EncryptedField::decrypt_field(
-                    &provider.token,
-                    &self.crypto_service,
-                )?;
+                self.decrypt_stored(provider.id, &provider.token, &self.crypto_service).await?;
 
             let mut decrypted_provider = provider;
             decrypted_provider.token = decrypted_token;
@@ -153,23 +168,75 @@ impl CodexProviderRepository {
         }
     }
 
+    /// 根据URL查找Codex供应商（解密token）
+    ///
+    /// 按规范化URL查询，忽略末尾斜杠和协议、主机的大小写；URL不唯一，返回所有匹配项
+    pub async fn find_by_url(&self, url: &str) -> RepositoryResult<Vec<CodexProvider>> {
+        let normalized = normalize_url(url);
+
+        tracing::debug!(url = %url, normalized = %normalized, "根据URL查找Codex供应商");
+
+        self.fill_normalized_urls().await?;
+        let mut providers: Vec<CodexProvider> =
+            sqlx::query_as("SELECT * FROM codex_providers WHERE normalized_url = ? ORDER BY id")
+                .bind(&normalized)
+                .fetch_all(self.executor("find_by_url", &self.pool))
+                .await?;
+
+        for provider in &mut providers {
+            provider.token =
+                self.decrypt_stored(provider.id, &provider.token, &self.crypto_service).await?;
+        }
+        Ok(providers)
+    }
+
+    /// 补全规范化URL为空的记录
+    ///
+    /// 导入等途径写入或修改了URL的记录没有规范化URL，查询前按当前规则计算；
+    /// 只写入该列时触发器不刷新修改时间，因此不使用 `update_by_id_statement`
+    async fn fill_normalized_urls(&self) -> RepositoryResult<()> {
+        let missing: Vec<(i64, String)> =
+            sqlx::query_as("SELECT id, url FROM codex_providers WHERE normalized_url IS NULL")
+                .fetch_all(self.executor("find_missing_normalized_url", &self.pool))
+                .await?;
+
+        for (id, url) in missing {
+            sqlx::query("UPDATE codex_providers SET normalized_url = ? WHERE id = ? AND url = ?")
+                .bind(normalize_url(&url))
+                .bind(id)
+                .bind(&url)
+                .execute(self.executor("fill_normalized_url", &self.pool))
+                .await?;
+        }
+        Ok(())
+    }
+
     /// 搜索Codex供应商
     pub async fn search_codex_providers(
         &self,
         search_term: &str,
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> RepositoryResult<Vec<CodexProvider>> {
         let search_fields = vec!["name", "url", "type"];
-        self.search::<CodexProvider>(search_term, &search_fields, limit).await
+        self.search::<CodexProvider>(search_term, &search_fields, limit, order_by).await
     }
 
     /// 获取活跃的Codex供应商
-    pub async fn list_active_providers(&self) -> RepositoryResult<Vec<CodexProvider>> {
-        let query = "SELECT * FROM codex_providers WHERE enabled = 1 ORDER BY id DESC";
+    pub async fn list_active_providers(
+        &self,
+        order_by: Option<OrderBy>,
+    ) -> RepositoryResult<Vec<CodexProvider>> {
+        let query = format!(
+            "SELECT * FROM codex_providers WHERE enabled = 1 ORDER BY {}",
+            Self::order_by_clause(order_by, "id DESC")?
+        );
 
         tracing::debug!("获取活跃的Codex供应商列表");
 
-        let results = sqlx::query_as::<_, CodexProvider>(query).fetch_all(&self.pool).await?;
+        let results = sqlx::query_as::<_, CodexProvider>(&query)
+            .fetch_all(self.executor("list_active", &self.pool))
+            .await?;
 
         Ok(results)
     }
@@ -200,7 +267,10 @@ impl CodexProviderRepository {
     pub async fn count_by_status(&self, is_active: bool) -> RepositoryResult<i64> {
         let query = "SELECT COUNT(*) FROM codex_providers WHERE enabled = ?";
 
-        let count: i64 = sqlx::query_scalar(query).bind(is_active).fetch_one(&self.pool).await?;
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(is_active)
+            .fetch_one(self.executor("count_by_status", &self.pool))
+            .await?;
 
         Ok(count)
     }
@@ -211,6 +281,17 @@ impl BaseRepository for CodexProviderRepository {
         "codex_providers"
     }
 
+    fn sortable_columns() -> &'static [SortColumn] {
+        &[
+            SortColumn::Id,
+            SortColumn::Name,
+            SortColumn::Type,
+            SortColumn::Enabled,
+            SortColumn::CreatedAt,
+            SortColumn::UpdatedAt,
+        ]
+    }
+
     fn pool(&self) -> &SqlitePool {
         &self.pool
     }
@@ -219,6 +300,10 @@ impl BaseRepository for CodexProviderRepository {
         &self.crypto_service
     }
 
+    fn query_timer(&self) -> &QueryTimer {
+        &self.query_timer
+    }
+
     async fn find_by_id<T>(&self, id: i64) -> RepositoryResult<Option<T>>
     where
         T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
@@ -232,7 +317,13 @@ impl BaseRepository for CodexProviderRepository {
             query
         );
 
-        let result = sqlx::query_as::<_, T>(&query).bind(id).fetch_optional(self.pool()).await?;
+        let result = self
+            .timed(
+                "find_by_id",
+                &query,
+                sqlx::query_as::<_, T>(&query).bind(id).fetch_optional(self.pool()),
+            )
+            .await?;
 
         Ok(result)
     }
@@ -265,7 +356,13 @@ impl BaseRepository for CodexProviderRepository {
             "删除Codex供应商"
         );
 
-        let result = sqlx::query(query).bind(id).execute(self.pool()).await?;
+        let result = self
+            .timed(
+                "delete",
+                query,
+                sqlx::query(query).bind(id).execute(self.pool()),
+            )
+            .await?;
 
         Ok(result.rows_affected() > 0)
     }
@@ -278,7 +375,13 @@ impl BaseRepository for CodexProviderRepository {
 
         tracing::debug!("获取Codex供应商列表");
 
-        let results = sqlx::query_as::<_, T>(query).fetch_all(self.pool()).await?;
+        let results = self
+            .timed(
+                "list_all",
+                query,
+                sqlx::query_as::<_, T>(query).fetch_all(self.pool()),
+            )
+            .await?;
 
         Ok(results)
     }
@@ -296,10 +399,25 @@ impl BaseRepository for CodexProviderRepository {
 
         // 查询总数
         let count_query = "SELECT COUNT(*) FROM codex_providers";
-        let total: i64 = sqlx::query_scalar(count_query).fetch_one(self.pool()).await?;
+        let total: Option<i64> = if params.include_total() {
+            Some(
+                self.timed(
+                    "paginate",
+                    count_query,
+                    sqlx::query_scalar(count_query).fetch_one(self.pool()),
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
 
         // 查询分页数据
-        let data_query = "SELECT * FROM codex_providers ORDER BY id DESC LIMIT ? OFFSET ?";
+        let order_by = Self::order_by_clause(params.order_by, "id DESC")?;
+        let data_query = format!(
+            "SELECT * FROM codex_providers ORDER BY {} LIMIT ? OFFSET ?",
+            order_by
+        );
 
         tracing::debug!(
             page = %page,
@@ -308,13 +426,19 @@ impl BaseRepository for CodexProviderRepository {
             "分页查询Codex供应商"
         );
 
-        let data = sqlx::query_as::<_, T>(data_query)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(self.pool())
+        let data = self
+            .timed(
+                "paginate",
+                &data_query,
+                sqlx::query_as::<_, T>(&data_query)
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(self.pool()),
+            )
             .await?;
 
-        let paged_result = crate::models::PagedResult::new(data, total, page, limit);
+        let paged_result =
+            crate::models::PagedResult::with_optional_total(data, total, page, limit);
 
         Ok(paged_result)
     }
@@ -324,11 +448,13 @@ impl BaseRepository for CodexProviderRepository {
         search_term: &str,
         search_fields: &[&str],
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> RepositoryResult<Vec<T>>
     where
         T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
     {
         let limit = limit.unwrap_or(50);
+        let order_by = Self::order_by_clause(order_by, "id DESC")?;
 
         // 构建搜索查询
         let mut where_conditions = Vec::new();
@@ -350,11 +476,15 @@ impl BaseRepository for CodexProviderRepository {
         }
 
         let query = if where_conditions.is_empty() {
-            "SELECT * FROM codex_providers ORDER BY id DESC LIMIT ?".to_string()
+            format!(
+                "SELECT * FROM codex_providers ORDER BY {} LIMIT ?",
+                order_by
+            )
         } else {
             format!(
-                "SELECT * FROM codex_providers WHERE {} ORDER BY id DESC LIMIT ?",
-                where_conditions.join(" OR ")
+                "SELECT * FROM codex_providers WHERE {} ORDER BY {} LIMIT ?",
+                where_conditions.join(" OR "),
+                order_by
             )
         };
 
@@ -372,7 +502,7 @@ impl BaseRepository for CodexProviderRepository {
         }
         query_builder = query_builder.bind(limit);
 
-        let results = query_builder.fetch_all(self.pool()).await?;
+        let results = self.timed("search", &query, query_builder.fetch_all(self.pool())).await?;
 
         Ok(results)
     }
@@ -382,7 +512,13 @@ impl BaseRepository for CodexProviderRepository {
 
         tracing::debug!("统计Codex供应商总数");
 
-        let count: i64 = sqlx::query_scalar(query).fetch_one(self.pool()).await?;
+        let count: i64 = self
+            .timed(
+                "count",
+                query,
+                sqlx::query_scalar(query).fetch_one(self.pool()),
+            )
+            .await?;
 
         Ok(count)
     }
@@ -406,6 +542,7 @@ mod tests {
             connect_timeout: std::time::Duration::from_secs(10),
             idle_timeout: std::time::Duration::from_secs(60),
             max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = DatabaseManager::new(config).await.unwrap();
@@ -425,6 +562,8 @@ mod tests {
             url: "https://api.openai.com/v1".to_string(),
             token: "sk-test-token".to_string(),
             r#type: Some("gpt-4".to_string()),
+            custom_headers: None,
+            accepted_status_codes: None,
         };
 
         let id = repo.create_codex_provider(&create_request).await.unwrap();
@@ -444,6 +583,8 @@ mod tests {
             token: None,
             r#type: None,
             enabled: Some(0),
+            custom_headers: None,
+            accepted_status_codes: None,
         };
 
         let updated = repo.update_codex_provider(id, &update_request).await.unwrap();
@@ -464,4 +605,48 @@ mod tests {
         let deleted_provider = repo.find_by_id_decrypted(id).await.unwrap();
         assert!(deleted_provider.is_none());
     }
+
+    #[tokio::test]
+    async fn test_find_by_url() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test_codex_find_by_url.db");
+
+        let config = DatabaseConfig {
+            url: format!("sqlite:{}", db_path.display()),
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: std::time::Duration::from_secs(10),
+            idle_timeout: std::time::Duration::from_secs(60),
+            max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
+        };
+
+        let db_manager = DatabaseManager::new(config).await.unwrap();
+        db_manager.ensure_initialized().await.unwrap();
+        let crypto_service =
+            CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap();
+        let repo = CodexProviderRepository::new(&db_manager, &crypto_service);
+
+        for (name, url) in [
+            ("官方", "https://api.openai.com/v1"),
+            ("官方备用", "https://API.OpenAI.com/v1/"),
+            ("代理", "https://proxy.example.com/v1"),
+        ] {
+            repo.create_codex_provider(&CreateCodexProviderRequest {
+                name: name.to_string(),
+                url: url.to_string(),
+                token: "sk-codex-find-by-url".to_string(),
+                r#type: None,
+                custom_headers: None,
+                accepted_status_codes: None,
+            })
+            .await
+            .unwrap();
+        }
+
+        let matches = repo.find_by_url("https://api.openai.com/v1").await.unwrap();
+        assert_eq!(matches.len(), 2);
+        assert!(matches.iter().all(|provider| provider.name.starts_with("官方")));
+        assert!(repo.find_by_url("https://api.openai.com").await.unwrap().is_empty());
+    }
 }
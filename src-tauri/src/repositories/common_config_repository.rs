@@ -3,15 +3,56 @@
 // 提供通用配置的特定数据访问操作
 
 use crate::crypto::CryptoService;
-use crate::database::DatabaseManager;
-use crate::models::{CommonConfig, CreateCommonConfigRequest, UpdateCommonConfigRequest};
-use crate::repositories::base_repository::{BaseRepository, RepositoryError, RepositoryResult};
-use sqlx::{FromRow, SqlitePool};
+use crate::database::{DatabaseManager, QueryTimer};
+use crate::models::{
+    BatchUpdateFailure, BatchUpdateResult, CommonConfig, CommonConfigHistory,
+    CreateCommonConfigRequest, OrderBy, SortColumn, UpdateCommonConfigRequest,
+};
+use crate::repositories::base_repository::{
+    update_statement, BaseRepository, RepositoryError, RepositoryResult,
+};
+use futures::stream::BoxStream;
+use sqlx::{Acquire, FromRow, Sqlite, SqliteConnection, SqlitePool};
+use std::collections::HashMap;
+
+/// 每个配置默认保留的历史版本数
+pub const DEFAULT_HISTORY_LIMIT: i64 = 20;
+
+/// 批量更新中的单个配置
+#[derive(Debug)]
+pub struct BatchConfigUpdate {
+    /// 要更新的配置ID，为 `None` 时按 `changes.key` 查找
+    pub id: Option<i64>,
+    pub changes: UpdateCommonConfigRequest,
+    /// 加密 `changes.value` 所用密钥的指纹，值为明文时为 `None`
+    pub key_fingerprint: Option<String>,
+}
+
+impl BatchConfigUpdate {
+    /// 用于错误信息的配置描述
+    fn target(&self) -> String {
+        match (self.id, &self.changes.key) {
+            (Some(id), _) => format!("ID {}", id),
+            (None, Some(key)) => format!("键 {}", key),
+            (None, None) => "未指定".to_string(),
+        }
+    }
+
+    fn failure(&self, error: &RepositoryError) -> BatchUpdateFailure {
+        BatchUpdateFailure {
+            id: self.id,
+            key: self.changes.key.clone(),
+            error: error.to_string(),
+        }
+    }
+}
 
 /// 通用配置Repository
 pub struct CommonConfigRepository {
     pool: SqlitePool,
     crypto_service: CryptoService,
+    query_timer: QueryTimer,
+    history_limit: i64,
 }
 
 impl CommonConfigRepository {
@@ -20,18 +61,30 @@ impl CommonConfigRepository {
         Self {
             pool: db_manager.pool().clone(),
             crypto_service: crypto_service.clone(),
+            query_timer: db_manager.query_timer(),
+            history_limit: DEFAULT_HISTORY_LIMIT,
         }
     }
 
+    /// 设置每个配置保留的历史版本数
+    pub fn with_history_limit(mut self, history_limit: i64) -> Self {
+        self.history_limit = history_limit.max(1);
+        self
+    }
+
     /// 创建通用配置记录
+    ///
+    /// 值按原样保存，`key_fingerprint` 为加密该值所用密钥的指纹，明文为 `None`
     pub async fn create_common_config(
         &self,
         request: &CreateCommonConfigRequest,
+        key_fingerprint: Option<&str>,
     ) -> RepositoryResult<i64> {
         let query = r#"
             INSERT INTO common_configs (
-                key, value, description, category, is_active, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, datetime('now'), datetime('now'))
+                key, value, description, category, is_active, key_fingerprint,
+                created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, datetime('now'), datetime('now'))
         "#;
 
         tracing::info!(
@@ -46,61 +99,89 @@ impl CommonConfigRepository {
             .bind(&request.description)
             .bind(request.category.as_deref().unwrap_or("default"))
             .bind(request.is_active.unwrap_or(1))
-            .execute(&self.pool)
+            .bind(key_fingerprint)
+            .execute(self.executor("create", &self.pool))
             .await?;
 
         Ok(result.last_insert_rowid())
     }
 
-    /// 更新通用配置记录
+    /// 配置键不存在时创建配置，返回是否创建了新记录
+    pub async fn insert_if_absent(
+        &self,
+        key: &str,
+        value: &str,
+        category: &str,
+    ) -> RepositoryResult<bool> {
+        let query = r#"
+            INSERT INTO common_configs (
+                key, value, category, is_active, created_at, updated_at
+            ) VALUES (?, ?, ?, 1, datetime('now'), datetime('now'))
+            ON CONFLICT(key) DO NOTHING
+        "#;
+
+        let result = sqlx::query(query)
+            .bind(key)
+            .bind(value)
+            .bind(category)
+            .execute(self.executor("insert_if_absent", &self.pool))
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 更新通用配置记录，`key_fingerprint` 与 [`Self::create_common_config`] 相同，只在更新值时写入
     pub async fn update_common_config(
         &self,
         id: i64,
         request: &UpdateCommonConfigRequest,
+        key_fingerprint: Option<&str>,
     ) -> RepositoryResult<bool> {
         // 获取现有记录
-        let existing = self.find_by_id::<CommonConfig>(id).await?;
-        if existing.is_none() {
-            return Err(RepositoryError::NotFound(format!(
-                "通用配置 ID {} 不存在",
-                id
-            )));
+        let existing = match self.find_by_id::<CommonConfig>(id).await? {
+            Some(existing) => existing,
+            None => {
+                return Err(RepositoryError::NotFound(format!(
+                    "通用配置 ID {} 不存在",
+                    id
+                )));
+            }
+        };
+
+        if request.value.as_ref().is_some_and(|value| *value != existing.value) {
+            let mut conn = self.pool.acquire().await?;
+            self.record_history(&mut conn, existing.id, &existing.value).await?;
         }
 
-        let query = r#"
-            UPDATE common_configs SET
-                key = COALESCE(?, key),
-                value = COALESCE(?, value),
-                description = COALESCE(?, description),
-                category = COALESCE(?, category),
-                is_active = COALESCE(?, is_active),
-                updated_at = datetime('now')
-            WHERE id = ?
-        "#;
+        let query = Self::update_by_id_statement(&[
+            "key = COALESCE(?, key)",
+            "value = COALESCE(?, value)",
+            "description = COALESCE(?, description)",
+            "category = COALESCE(?, category)",
+            "is_active = COALESCE(?, is_active)",
+            "key_fingerprint = CASE WHEN ? IS NOT NULL THEN ? ELSE key_fingerprint END",
+        ]);
 
         tracing::info!(
             id = %id,
             "更新通用配置"
         );
 
-        let result = sqlx::query(query)
+        let result = sqlx::query(&query)
             .bind(&request.key)
             .bind(&request.value)
             .bind(&request.description)
             .bind(&request.category)
             .bind(request.is_active)
+            .bind(&request.value)
+            .bind(key_fingerprint)
             .bind(id)
-            .execute(&self.pool)
+            .execute(self.executor("update", &self.pool))
             .await?;
 
         Ok(result.rows_affected() > 0)
     }
 
-    /// 根据ID获取通用配置
-    pub async fn find_by_id_decrypted(&self, id: i64) -> RepositoryResult<Option<CommonConfig>> {
-        self.find_by_id::<CommonConfig>(id).await
-    }
-
     /// 根据key获取配置
     pub async fn find_by_key(&self, key: &str) -> RepositoryResult<Option<CommonConfig>> {
         let query = "SELECT * FROM common_configs WHERE key = ?";
@@ -112,66 +193,235 @@ impl CommonConfigRepository {
 
         let result = sqlx::query_as::<_, CommonConfig>(query)
             .bind(key)
-            .fetch_optional(&self.pool)
+            .fetch_optional(self.executor("find_by_key", &self.pool))
             .await?;
 
         Ok(result)
     }
 
+    /// 一次查询获取多个key对应的配置
+    ///
+    /// 返回以key为键的映射，不存在的key不会出现在结果中；
+    /// 与 [`Self::find_by_key`] 一样返回存储的值，加密的值由 `CommonConfigService` 按类别解密
+    pub async fn get_many_by_keys(
+        &self,
+        keys: &[&str],
+    ) -> RepositoryResult<HashMap<String, CommonConfig>> {
+        if keys.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        tracing::debug!(
+            keys = ?keys,
+            "批量获取配置"
+        );
+
+        let mut builder =
+            sqlx::QueryBuilder::<Sqlite>::new("SELECT * FROM common_configs WHERE key IN (");
+        let mut separated = builder.separated(", ");
+        for key in keys {
+            separated.push_bind(*key);
+        }
+        separated.push_unseparated(")");
+
+        let results = builder
+            .build_query_as::<CommonConfig>()
+            .fetch_all(self.executor("get_many_by_keys", &self.pool))
+            .await?;
+
+        Ok(results.into_iter().map(|config| (config.key.clone(), config)).collect())
+    }
+
     /// 根据类别获取配置列表
-    pub async fn find_by_category(&self, category: &str) -> RepositoryResult<Vec<CommonConfig>> {
-        let query = "SELECT * FROM common_configs WHERE category = ? ORDER BY key ASC";
+    pub async fn find_by_category(
+        &self,
+        category: &str,
+        order_by: Option<OrderBy>,
+    ) -> RepositoryResult<Vec<CommonConfig>> {
+        let query = format!(
+            "SELECT * FROM common_configs WHERE category = ? ORDER BY {}",
+            Self::order_by_clause(order_by, "key ASC")?
+        );
 
         tracing::debug!(
             category = %category,
             "根据类别获取配置列表"
         );
 
-        let results = sqlx::query_as::<_, CommonConfig>(query)
+        let results = sqlx::query_as::<_, CommonConfig>(&query)
             .bind(category)
-            .fetch_all(&self.pool)
+            .fetch_all(self.executor("find_by_category", &self.pool))
             .await?;
 
         Ok(results)
     }
 
     /// 获取活跃配置
-    pub async fn list_active_configs(&self) -> RepositoryResult<Vec<CommonConfig>> {
-        let query =
-            "SELECT * FROM common_configs WHERE is_active = 1 ORDER BY category ASC, key ASC";
+    pub async fn list_active_configs(
+        &self,
+        order_by: Option<OrderBy>,
+    ) -> RepositoryResult<Vec<CommonConfig>> {
+        let query = format!(
+            "SELECT * FROM common_configs WHERE is_active = 1 ORDER BY {}",
+            Self::order_by_clause(order_by, "category ASC, key ASC")?
+        );
 
         tracing::debug!("获取活跃配置列表");
 
-        let results = sqlx::query_as::<_, CommonConfig>(query).fetch_all(&self.pool).await?;
+        let results = sqlx::query_as::<_, CommonConfig>(&query)
+            .fetch_all(self.executor("list_active", &self.pool))
+            .await?;
 
         Ok(results)
     }
 
+    /// 逐行读取配置，不会把全部结果一次性加载到内存
+    pub fn stream_configs<'a>(
+        &'a self,
+        category: Option<&'a str>,
+        active_only: bool,
+    ) -> BoxStream<'a, Result<CommonConfig, sqlx::Error>> {
+        let query = match (category.is_some(), active_only) {
+            (false, false) => "SELECT * FROM common_configs ORDER BY category ASC, key ASC",
+            (false, true) => {
+                "SELECT * FROM common_configs WHERE is_active = 1 ORDER BY category ASC, key ASC"
+            }
+            (true, false) => "SELECT * FROM common_configs WHERE category = ? ORDER BY key ASC",
+            (true, true) => {
+                "SELECT * FROM common_configs WHERE category = ? AND is_active = 1 ORDER BY key ASC"
+            }
+        };
+
+        tracing::debug!(
+            category = ?category,
+            active_only = %active_only,
+            "流式读取配置列表"
+        );
+
+        let mut query = sqlx::query_as::<_, CommonConfig>(query);
+        if let Some(category) = category {
+            query = query.bind(category);
+        }
+        query.fetch(self.executor("stream", &self.pool))
+    }
+
     /// 搜索通用配置
     pub async fn search_common_configs(
         &self,
         search_term: &str,
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> RepositoryResult<Vec<CommonConfig>> {
         let search_fields = vec!["key", "value", "description", "category"];
-        self.search::<CommonConfig>(search_term, &search_fields, limit).await
+        self.search::<CommonConfig>(search_term, &search_fields, limit, order_by).await
     }
 
-    /// 根据key更新配置值（便捷方法）
-    pub async fn update_config_value(&self, key: &str, value: &str) -> RepositoryResult<bool> {
-        let query =
-            "UPDATE common_configs SET value = ?, updated_at = datetime('now') WHERE key = ?";
+    /// 根据key更新配置值（便捷方法），`key_fingerprint` 与 [`Self::create_common_config`] 相同
+    pub async fn update_config_value(
+        &self,
+        key: &str,
+        value: &str,
+        key_fingerprint: Option<&str>,
+    ) -> RepositoryResult<bool> {
+        let query = update_statement(
+            Self::table_name(),
+            &["value = ?", "key_fingerprint = ?"],
+            "key = ?",
+        );
 
         tracing::info!(
             key = %key,
             "根据key更新配置值"
         );
 
-        let result = sqlx::query(query).bind(value).bind(key).execute(&self.pool).await?;
+        if let Some(existing) = self.find_by_key(key).await? {
+            if existing.value != value {
+                let mut conn = self.pool.acquire().await?;
+                self.record_history(&mut conn, existing.id, &existing.value).await?;
+            }
+        }
+
+        let result = sqlx::query(&query)
+            .bind(value)
+            .bind(key_fingerprint)
+            .bind(key)
+            .execute(self.executor("update_value", &self.pool))
+            .await?;
 
         Ok(result.rows_affected() > 0)
     }
 
+    /// 记录配置的旧值，并清理超出保留数量的历史版本
+    ///
+    /// 历史值沿用当前值的存储格式，加密保存的值记录为密文，明文保存的值记录为明文
+    async fn record_history(
+        &self,
+        conn: &mut SqliteConnection,
+        config_id: i64,
+        old_value: &str,
+    ) -> RepositoryResult<i64> {
+        let version: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(MAX(version), 0) + 1 FROM common_config_history WHERE config_id = ?",
+        )
+        .bind(config_id)
+        .fetch_one(self.executor("history_version", &mut *conn))
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO common_config_history (config_id, version, value, created_at) VALUES (?, ?, ?, datetime('now'))",
+        )
+        .bind(config_id)
+        .bind(version)
+        .bind(old_value)
+        .execute(self.executor("record_history", &mut *conn))
+        .await?;
+
+        let pruned =
+            sqlx::query("DELETE FROM common_config_history WHERE config_id = ? AND version <= ?")
+                .bind(config_id)
+                .bind(version - self.history_limit)
+                .execute(self.executor("prune_history", &mut *conn))
+                .await?;
+
+        tracing::debug!(
+            config_id = %config_id,
+            version = %version,
+            pruned = %pruned.rows_affected(),
+            "记录通用配置历史版本"
+        );
+
+        Ok(version)
+    }
+
+    /// 获取配置的历史版本（最新的在前）
+    pub async fn list_history(&self, config_id: i64) -> RepositoryResult<Vec<CommonConfigHistory>> {
+        let results = sqlx::query_as::<_, CommonConfigHistory>(
+            "SELECT * FROM common_config_history WHERE config_id = ? ORDER BY version DESC",
+        )
+        .bind(config_id)
+        .fetch_all(self.executor("list_history", &self.pool))
+        .await?;
+
+        Ok(results)
+    }
+
+    /// 获取配置的指定历史版本
+    pub async fn find_history_version(
+        &self,
+        config_id: i64,
+        version: i64,
+    ) -> RepositoryResult<Option<CommonConfigHistory>> {
+        let result = sqlx::query_as::<_, CommonConfigHistory>(
+            "SELECT * FROM common_config_history WHERE config_id = ? AND version = ?",
+        )
+        .bind(config_id)
+        .bind(version)
+        .fetch_optional(self.executor("find_history_version", &self.pool))
+        .await?;
+
+        Ok(result)
+    }
+
     /// 批量更新配置
     pub async fn batch_update_configs(
         &self,
@@ -180,7 +430,7 @@ impl CommonConfigRepository {
         let mut updated_count = 0;
 
         for (key, value) in configs {
-            if self.update_config_value(key, value).await? {
+            if self.update_config_value(key, value, None).await? {
                 updated_count += 1;
             }
         }
@@ -194,9 +444,132 @@ impl CommonConfigRepository {
         Ok(updated_count)
     }
 
+    /// 按ID或键批量更新配置
+    ///
+    /// 所有修改在同一事务中执行，每项使用单独的保存点。`partial` 为 `false` 时任一项失败
+    /// 即回滚全部修改，结果中 `updated` 为空；为 `true` 时提交成功的项并报告失败的项
+    pub async fn batch_update(
+        &self,
+        updates: &[BatchConfigUpdate],
+        partial: bool,
+    ) -> RepositoryResult<BatchUpdateResult> {
+        let mut tx = self.pool.begin().await?;
+        let mut result = BatchUpdateResult::default();
+
+        for update in updates {
+            let mut savepoint = tx.begin().await?;
+            match self.apply_update(&mut *savepoint, update).await {
+                Ok(config) => {
+                    savepoint.commit().await?;
+                    result.updated.push(config);
+                }
+                Err(e) => {
+                    savepoint.rollback().await?;
+                    tracing::warn!(
+                        config = %update.target(),
+                        error = %e,
+                        "批量更新中的配置更新失败"
+                    );
+                    result.failed.push(update.failure(&e));
+                }
+            }
+        }
+
+        if !partial && !result.failed.is_empty() {
+            tx.rollback().await?;
+            result.updated.clear();
+        } else {
+            tx.commit().await?;
+        }
+
+        tracing::info!(
+            updated = %result.updated.len(),
+            failed = %result.failed.len(),
+            partial = %partial,
+            "批量更新配置完成"
+        );
+
+        Ok(result)
+    }
+
+    /// 在给定连接上更新单个配置，返回更新后的配置
+    async fn apply_update(
+        &self,
+        conn: &mut SqliteConnection,
+        update: &BatchConfigUpdate,
+    ) -> RepositoryResult<CommonConfig> {
+        let existing = match (update.id, &update.changes.key) {
+            (Some(id), _) => {
+                sqlx::query_as::<_, CommonConfig>("SELECT * FROM common_configs WHERE id = ?")
+                    .bind(id)
+                    .fetch_optional(self.executor("find_by_id", &mut *conn))
+                    .await?
+            }
+            (None, Some(key)) => {
+                sqlx::query_as::<_, CommonConfig>("SELECT * FROM common_configs WHERE key = ?")
+                    .bind(key)
+                    .fetch_optional(self.executor("find_by_key", &mut *conn))
+                    .await?
+            }
+            (None, None) => {
+                return Err(RepositoryError::Validation(
+                    "必须指定配置ID或配置键".to_string(),
+                ));
+            }
+        };
+        let existing = existing.ok_or_else(|| {
+            RepositoryError::NotFound(format!("通用配置 {} 不存在", update.target()))
+        })?;
+
+        let changes = &update.changes;
+        if let Some(key) = changes.key.as_ref().filter(|key| **key != existing.key) {
+            let taken: i64 =
+                sqlx::query_scalar("SELECT COUNT(*) FROM common_configs WHERE key = ? AND id != ?")
+                    .bind(key)
+                    .bind(existing.id)
+                    .fetch_one(self.executor("key_taken", &mut *conn))
+                    .await?;
+            if taken > 0 {
+                return Err(RepositoryError::Conflict(format!("配置键 {} 已存在", key)));
+            }
+        }
+
+        if changes.value.as_ref().is_some_and(|value| *value != existing.value) {
+            self.record_history(conn, existing.id, &existing.value).await?;
+        }
+
+        let query = Self::update_by_id_statement(&[
+            "key = COALESCE(?, key)",
+            "value = COALESCE(?, value)",
+            "description = COALESCE(?, description)",
+            "category = COALESCE(?, category)",
+            "is_active = COALESCE(?, is_active)",
+            "key_fingerprint = CASE WHEN ? IS NOT NULL THEN ? ELSE key_fingerprint END",
+        ]);
+        sqlx::query(&query)
+            .bind(&changes.key)
+            .bind(&changes.value)
+            .bind(&changes.description)
+            .bind(&changes.category)
+            .bind(changes.is_active)
+            .bind(&changes.value)
+            .bind(&update.key_fingerprint)
+            .bind(existing.id)
+            .execute(self.executor("update", &mut *conn))
+            .await?;
+
+        let updated =
+            sqlx::query_as::<_, CommonConfig>("SELECT * FROM common_configs WHERE id = ?")
+                .bind(existing.id)
+                .fetch_one(self.executor("find_by_id", &mut *conn))
+                .await?;
+
+        Ok(updated)
+    }
+
     /// 验证配置值
     pub async fn validate_config_value(&self, id: i64) -> RepositoryResult<bool> {
-        let config = self.find_by_id_decrypted(id).await?;
+        let config = self.find_by_id::<CommonConfig>(id).await?;
         if config.is_none() {
             return Err(RepositoryError::NotFound(format!(
                 "通用配置 ID {} 不存在",
@@ -227,7 +600,10 @@ impl CommonConfigRepository {
     pub async fn count_by_category(&self, category: &str) -> RepositoryResult<i64> {
         let query = "SELECT COUNT(*) FROM common_configs WHERE category = ?";
 
-        let count: i64 = sqlx::query_scalar(query).bind(category).fetch_one(&self.pool).await?;
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(category)
+            .fetch_one(self.executor("count_by_category", &self.pool))
+            .await?;
 
         Ok(count)
     }
@@ -236,7 +612,9 @@ impl CommonConfigRepository {
     pub async fn count_active(&self) -> RepositoryResult<i64> {
         let query = "SELECT COUNT(*) FROM common_configs WHERE is_active = 1";
 
-        let count: i64 = sqlx::query_scalar(query).fetch_one(&self.pool).await?;
+        let count: i64 = sqlx::query_scalar(query)
+            .fetch_one(self.executor("count_active", &self.pool))
+            .await?;
 
         Ok(count)
     }
@@ -247,7 +625,9 @@ impl CommonConfigRepository {
 
         tracing::debug!("获取所有配置类别");
 
-        let results = sqlx::query_scalar(query).fetch_all(&self.pool).await?;
+        let results = sqlx::query_scalar(query)
+            .fetch_all(self.executor("list_categories", &self.pool))
+            .await?;
 
         Ok(results)
     }
@@ -258,6 +638,16 @@ impl BaseRepository for CommonConfigRepository {
         "common_configs"
     }
 
+    fn sortable_columns() -> &'static [SortColumn] {
+        &[
+            SortColumn::Id,
+            SortColumn::Key,
+            SortColumn::Category,
+            SortColumn::CreatedAt,
+            SortColumn::UpdatedAt,
+        ]
+    }
+
     fn pool(&self) -> &SqlitePool {
         &self.pool
     }
@@ -266,6 +656,10 @@ impl BaseRepository for CommonConfigRepository {
         &self.crypto_service
     }
 
+    fn query_timer(&self) -> &QueryTimer {
+        &self.query_timer
+    }
+
     async fn find_by_id<T>(&self, id: i64) -> RepositoryResult<Option<T>>
     where
         T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
@@ -279,7 +673,13 @@ impl BaseRepository for CommonConfigRepository {
             query
         );
 
-        let result = sqlx::query_as::<_, T>(&query).bind(id).fetch_optional(self.pool()).await?;
+        let result = self
+            .timed(
+                "find_by_id",
+                &query,
+                sqlx::query_as::<_, T>(&query).bind(id).fetch_optional(self.pool()),
+            )
+            .await?;
 
         Ok(result)
     }
@@ -312,7 +712,13 @@ impl BaseRepository for CommonConfigRepository {
             "删除通用配置"
         );
 
-        let result = sqlx::query(query).bind(id).execute(self.pool()).await?;
+        let result = self
+            .timed(
+                "delete",
+                query,
+                sqlx::query(query).bind(id).execute(self.pool()),
+            )
+            .await?;
 
         Ok(result.rows_affected() > 0)
     }
@@ -325,7 +731,13 @@ impl BaseRepository for CommonConfigRepository {
 
         tracing::debug!("获取通用配置列表");
 
-        let results = sqlx::query_as::<_, T>(query).fetch_all(self.pool()).await?;
+        let results = self
+            .timed(
+                "list_all",
+                query,
+                sqlx::query_as::<_, T>(query).fetch_all(self.pool()),
+            )
+            .await?;
 
         Ok(results)
     }
@@ -343,11 +755,25 @@ impl BaseRepository for CommonConfigRepository {
 
         // 查询总数
         let count_query = "SELECT COUNT(*) FROM common_configs";
-        let total: i64 = sqlx::query_scalar(count_query).fetch_one(self.pool()).await?;
+        let total: Option<i64> = if params.include_total() {
+            Some(
+                self.timed(
+                    "paginate",
+                    count_query,
+                    sqlx::query_scalar(count_query).fetch_one(self.pool()),
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
 
         // 查询分页数据
-        let data_query =
-            "SELECT * FROM common_configs ORDER BY category ASC, key ASC LIMIT ? OFFSET ?";
+        let order_by = Self::order_by_clause(params.order_by, "category ASC, key ASC")?;
+        let data_query = format!(
+            "SELECT * FROM common_configs ORDER BY {} LIMIT ? OFFSET ?",
+            order_by
+        );
 
         tracing::debug!(
             page = %page,
@@ -356,13 +782,19 @@ impl BaseRepository for CommonConfigRepository {
             "分页查询通用配置"
         );
 
-        let data = sqlx::query_as::<_, T>(data_query)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(self.pool())
+        let data = self
+            .timed(
+                "paginate",
+                &data_query,
+                sqlx::query_as::<_, T>(&data_query)
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(self.pool()),
+            )
             .await?;
 
-        let paged_result = crate::models::PagedResult::new(data, total, page, limit);
+        let paged_result =
+            crate::models::PagedResult::with_optional_total(data, total, page, limit);
 
         Ok(paged_result)
     }
@@ -372,11 +804,13 @@ impl BaseRepository for CommonConfigRepository {
         search_term: &str,
         search_fields: &[&str],
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> RepositoryResult<Vec<T>>
     where
         T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
     {
         let limit = limit.unwrap_or(50);
+        let order_by = Self::order_by_clause(order_by, "category ASC, key ASC")?;
 
         // 构建搜索查询
         let mut where_conditions = Vec::new();
@@ -398,11 +832,12 @@ impl BaseRepository for CommonConfigRepository {
         }
 
         let query = if where_conditions.is_empty() {
-            "SELECT * FROM common_configs ORDER BY category ASC, key ASC LIMIT ?".to_string()
+            format!("SELECT * FROM common_configs ORDER BY {} LIMIT ?", order_by)
         } else {
             format!(
-                "SELECT * FROM common_configs WHERE {} ORDER BY category ASC, key ASC LIMIT ?",
-                where_conditions.join(" OR ")
+                "SELECT * FROM common_configs WHERE {} ORDER BY {} LIMIT ?",
+                where_conditions.join(" OR "),
+                order_by
             )
         };
 
@@ -420,7 +855,7 @@ impl BaseRepository for CommonConfigRepository {
         }
         query_builder = query_builder.bind(limit);
 
-        let results = query_builder.fetch_all(self.pool()).await?;
+        let results = self.timed("search", &query, query_builder.fetch_all(self.pool())).await?;
 
         Ok(results)
     }
@@ -430,7 +865,13 @@ impl BaseRepository for CommonConfigRepository {
 
         tracing::debug!("统计通用配置总数");
 
-        let count: i64 = sqlx::query_scalar(query).fetch_one(self.pool()).await?;
+        let count: i64 = self
+            .timed(
+                "count",
+                query,
+                sqlx::query_scalar(query).fetch_one(self.pool()),
+            )
+            .await?;
 
         Ok(count)
     }
@@ -454,6 +895,7 @@ mod tests {
             connect_timeout: std::time::Duration::from_secs(10),
             idle_timeout: std::time::Duration::from_secs(60),
             max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = DatabaseManager::new(config).await.unwrap();
@@ -476,11 +918,11 @@ mod tests {
             is_active: Some(1),
         };
 
-        let id = repo.create_common_config(&create_request).await.unwrap();
+        let id = repo.create_common_config(&create_request, None).await.unwrap();
         assert!(id > 0);
 
         // 测试查找
-        let config = repo.find_by_id_decrypted(id).await.unwrap();
+        let config = repo.find_by_id::<CommonConfig>(id).await.unwrap();
         assert!(config.is_some());
         let config = config.unwrap();
         assert_eq!(config.key, "test.config");
@@ -500,11 +942,11 @@ mod tests {
             is_active: Some(0),
         };
 
-        let updated = repo.update_common_config(id, &update_request).await.unwrap();
+        let updated = repo.update_common_config(id, &update_request, None).await.unwrap();
         assert!(updated);
 
         // 验证更新
-        let updated_config = repo.find_by_id_decrypted(id).await.unwrap();
+        let updated_config = repo.find_by_id::<CommonConfig>(id).await.unwrap();
         assert!(updated_config.is_some());
         let updated_config = updated_config.unwrap();
         assert_eq!(updated_config.value, "updated_value");
@@ -515,7 +957,7 @@ mod tests {
         assert!(deleted);
 
         // 验证删除
-        let deleted_config = repo.find_by_id_decrypted(id).await.unwrap();
+        let deleted_config = repo.find_by_id::<CommonConfig>(id).await.unwrap();
         assert!(deleted_config.is_none());
     }
 
@@ -538,7 +980,7 @@ mod tests {
                 category: Some("batch_test".to_string()),
                 is_active: Some(1),
             };
-            repo.create_common_config(&create_request).await.unwrap();
+            repo.create_common_config(&create_request, None).await.unwrap();
         }
 
         // 批量更新
@@ -571,7 +1013,7 @@ mod tests {
             is_active: Some(1),
         };
 
-        let id = repo.create_common_config(&create_request).await.unwrap();
+        let id = repo.create_common_config(&create_request, None).await.unwrap();
         let is_valid = repo.validate_config_value(id).await.unwrap();
         assert!(is_valid);
 
@@ -584,8 +1026,39 @@ mod tests {
             is_active: Some(1),
         };
 
-        let id_empty = repo.create_common_config(&create_request_empty).await.unwrap();
+        let id_empty = repo.create_common_config(&create_request_empty, None).await.unwrap();
         let is_valid_empty = repo.validate_config_value(id_empty).await.unwrap();
         assert!(!is_valid_empty);
     }
+
+    #[tokio::test]
+    async fn test_get_many_by_keys() {
+        let repo = create_test_repository().await;
+
+        for (key, value) in [("batch.first", "value1"), ("batch.second", "value2")] {
+            repo.create_common_config(
+                &CreateCommonConfigRequest {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    description: None,
+                    category: None,
+                    is_active: Some(1),
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        }
+
+        let configs = repo
+            .get_many_by_keys(&["batch.first", "batch.second", "batch.missing"])
+            .await
+            .unwrap();
+
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs["batch.first"].value, "value1");
+        assert_eq!(configs["batch.second"].value, "value2");
+        assert!(!configs.contains_key("batch.missing"));
+        assert!(repo.get_many_by_keys(&[]).await.unwrap().is_empty());
+    }
 }
@@ -2,17 +2,88 @@
 //
 // 提供MCP服务器的特定数据访问操作
 
-use crate::crypto::CryptoService;
-use crate::database::DatabaseManager;
-use crate::models::{CreateMcpServerRequest, McpServer, UpdateMcpServerRequest};
+use crate::crypto::{CryptoError, CryptoService};
+use crate::database::{DatabaseManager, QueryTimer};
+use crate::models::{
+    CreateMcpServerRequest, McpServer, OrderBy, SortColumn, UpdateMcpServerRequest,
+};
 use crate::repositories::base_repository::{BaseRepository, RepositoryError, RepositoryResult};
+use crate::utils::redaction::REDACTED;
 use serde_json;
 use sqlx::{FromRow, SqlitePool};
+use std::collections::HashMap;
+
+/// 加密环境变量中标记为机密的值，其他值保持明文
+pub fn encrypt_secret_env(
+    env: &mut HashMap<String, String>,
+    secret_keys: &[String],
+    crypto_service: &CryptoService,
+) -> Result<(), CryptoError> {
+    for key in secret_keys {
+        if let Some(value) = env.get_mut(key) {
+            if !value.is_empty() {
+                *value = crypto_service.encrypt(value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 解密环境变量中标记为机密的值，兼容标记前以明文保存的值
+pub fn decrypt_secret_env(
+    env: &mut HashMap<String, String>,
+    secret_keys: &[String],
+    crypto_service: &CryptoService,
+) -> Result<(), CryptoError> {
+    for key in secret_keys {
+        if let Some(value) = env.get_mut(key) {
+            if CryptoService::is_fernet_token(value) {
+                *value = crypto_service.decrypt(value)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 将机密环境变量的值替换为占位符，用于接口返回
+pub fn mask_secret_env(server: &mut McpServer) {
+    let Some(mut env) = parse_env(server.env.as_deref()) else {
+        return;
+    };
+
+    let mut masked = false;
+    for key in &server.secret_env_keys {
+        if let Some(value) = env.get_mut(key) {
+            *value = REDACTED.to_string();
+            masked = true;
+        }
+    }
+    if masked {
+        server.env = serde_json::to_string(&env).ok();
+    }
+}
+
+fn parse_env(env: Option<&str>) -> Option<HashMap<String, String>> {
+    env.filter(|env| !env.trim().is_empty())
+        .and_then(|env| serde_json::from_str(env).ok())
+}
+
+/// 去掉空白和重复的机密环境变量名
+fn normalize_secret_keys(keys: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for key in keys.iter().map(|key| key.trim()).filter(|key| !key.is_empty()) {
+        if !normalized.iter().any(|existing| existing == key) {
+            normalized.push(key.to_string());
+        }
+    }
+    normalized
+}
 
 /// MCP服务器Repository
 pub struct McpServerRepository {
     pool: SqlitePool,
     crypto_service: CryptoService,
+    query_timer: QueryTimer,
 }
 
 impl McpServerRepository {
@@ -21,6 +92,7 @@ impl McpServerRepository {
         Self {
             pool: db_manager.pool().clone(),
             crypto_service: crypto_service.clone(),
+            query_timer: db_manager.query_timer(),
         }
     }
 
@@ -29,14 +101,21 @@ impl McpServerRepository {
         &self,
         request: &CreateMcpServerRequest,
     ) -> RepositoryResult<i64> {
-        // 将args和env序列化为JSON字符串
+        // 将args和env序列化为JSON字符串，机密环境变量的值加密保存
         let args_json = serde_json::to_string(&request.args)?;
-        let env_json = request.env.as_ref().map(serde_json::to_string).transpose()?;
+        let secret_keys = normalize_secret_keys(&request.secret_env_keys);
+        let env_json = request
+            .env
+            .as_ref()
+            .map(|env| self.encode_env(env.clone(), &secret_keys))
+            .transpose()?;
+        let secret_keys_json = serde_json::to_string(&secret_keys)?;
 
         let query = r#"
             INSERT INTO mcp_servers (
-                name, type, timeout, command, args, env, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, datetime('now'), datetime('now'))
+                name, type, timeout, command, args, env, secret_env_keys, key_fingerprint,
+                created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, datetime('now'), datetime('now'))
         "#;
 
         tracing::info!(
@@ -53,7 +132,9 @@ impl McpServerRepository {
             .bind(&request.command)
             .bind(args_json)
             .bind(env_json)
-            .execute(&self.pool)
+            .bind(secret_keys_json)
+            .bind(self.crypto_service.storage_fingerprint())
+            .execute(self.executor("create", &self.pool))
             .await?;
 
         Ok(result.last_insert_rowid())
@@ -66,13 +147,12 @@ impl McpServerRepository {
         request: &UpdateMcpServerRequest,
     ) -> RepositoryResult<bool> {
         // 获取现有记录
-        let existing = self.find_by_id::<McpServer>(id).await?;
-        if existing.is_none() {
+        let Some(existing) = self.find_by_id::<McpServer>(id).await? else {
             return Err(RepositoryError::NotFound(format!(
                 "MCP服务器 ID {} 不存在",
                 id
             )));
-        }
+        };
 
         // 序列化更新的数据
         let args_json = if let Some(ref args) = request.args {
@@ -81,43 +161,104 @@ impl McpServerRepository {
             None
         };
 
-        let env_json = if let Some(ref env_option) = request.env {
-            env_option.as_ref().map(serde_json::to_string).transpose()?
-        } else {
-            None
+        // 机密环境变量名变化时，已有的环境变量也需要按新的名单重新加密
+        let secret_keys = match request.secret_env_keys {
+            Some(ref keys) => normalize_secret_keys(keys),
+            None => existing.secret_env_keys.clone(),
         };
-
-        let query = r#"
-            UPDATE mcp_servers SET
-                name = COALESCE(?, name),
-                type = COALESCE(?, type),
-                timeout = COALESCE(?, timeout),
-                command = COALESCE(?, command),
-                args = COALESCE(?, args),
-                env = COALESCE(?, env),
-                updated_at = datetime('now')
-            WHERE id = ?
-        "#;
+        let env = match request.env {
+            Some(Some(ref env)) => {
+                // 客户端原样回传的占位符表示保留原来的值
+                let current = self.decrypted_env(&existing).await?.unwrap_or_default();
+                let mut env = env.clone();
+                for (key, value) in env.iter_mut() {
+                    if value == REDACTED {
+                        if let Some(original) = current.get(key) {
+                            *value = original.clone();
+                        }
+                    }
+                }
+                Some(env)
+            }
+            _ if request.secret_env_keys.is_some() => self.decrypted_env(&existing).await?,
+            _ => None,
+        };
+        let env_json = env.map(|env| self.encode_env(env, &secret_keys)).transpose()?;
+        let secret_keys_json = request
+            .secret_env_keys
+            .as_ref()
+            .map(|_| serde_json::to_string(&secret_keys))
+            .transpose()?;
+
+        let query = Self::update_by_id_statement(&[
+            "name = COALESCE(?, name)",
+            "type = COALESCE(?, type)",
+            "timeout = COALESCE(?, timeout)",
+            "command = COALESCE(?, command)",
+            "args = COALESCE(?, args)",
+            "env = COALESCE(?, env)",
+            "secret_env_keys = COALESCE(?, secret_env_keys)",
+            "key_fingerprint = CASE WHEN ? IS NOT NULL THEN ? ELSE key_fingerprint END",
+        ]);
 
         tracing::info!(
             id = %id,
             "更新MCP服务器"
         );
 
-        let result = sqlx::query(query)
+        let result = sqlx::query(&query)
             .bind(&request.name)
             .bind(&request.r#type)
             .bind(request.timeout)
             .bind(&request.command)
             .bind(args_json)
-            .bind(env_json)
+            .bind(&env_json)
+            .bind(secret_keys_json)
+            .bind(&env_json)
+            .bind(self.crypto_service.storage_fingerprint())
             .bind(id)
-            .execute(&self.pool)
+            .execute(self.executor("update", &self.pool))
             .await?;
 
         Ok(result.rows_affected() > 0)
     }
 
+    /// 解析服务器的环境变量并解密机密值，用于生成配置文件
+    ///
+    /// 由其他密钥加密时返回 `KeyMismatch`，见 [`BaseRepository::decrypt_stored`]
+    pub async fn decrypted_env(
+        &self,
+        server: &McpServer,
+    ) -> RepositoryResult<Option<HashMap<String, String>>> {
+        let Some(mut env) = parse_env(server.env.as_deref()) else {
+            return Ok(None);
+        };
+        for key in &server.secret_env_keys {
+            if let Some(value) = env.get_mut(key) {
+                *value = self.decrypt_stored(server.id, value, &self.crypto_service).await?;
+            }
+        }
+        Ok(Some(env))
+    }
+
+    /// 将服务器中的机密环境变量替换为明文
+    pub async fn reveal_secret_env(&self, server: &mut McpServer) -> RepositoryResult<()> {
+        if let Some(env) = self.decrypted_env(server).await? {
+            server.env = Some(serde_json::to_string(&env)?);
+        }
+        Ok(())
+    }
+
+    /// 加密机密环境变量后序列化为写入数据库的JSON
+    fn encode_env(
+        &self,
+        mut env: HashMap<String, String>,
+        secret_keys: &[String],
+    ) -> RepositoryResult<String> {
+        encrypt_secret_env(&mut env, secret_keys, &self.crypto_service)?;
+        Ok(serde_json::to_string(&env)?)
+    }
+
     /// 根据ID获取MCP服务器（解析JSON字段）
     pub async fn find_by_id_parsed(&self, id: i64) -> RepositoryResult<Option<McpServer>> {
         if let Some(server) = self.find_by_id::<McpServer>(id).await? {
@@ -138,23 +279,31 @@ impl McpServerRepository {
         &self,
         search_term: &str,
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> RepositoryResult<Vec<McpServer>> {
         let search_fields = vec!["name", "type", "command"];
-        self.search::<McpServer>(search_term, &search_fields, limit).await
+        self.search::<McpServer>(search_term, &search_fields, limit, order_by).await
     }
 
-    /// 根据类型获取MCP服务器
-    pub async fn find_by_type(&self, server_type: &str) -> RepositoryResult<Vec<McpServer>> {
-        let query = "SELECT * FROM mcp_servers WHERE type = ? ORDER BY id DESC";
+    /// 根据类型获取MCP服务器，`order_by` 为空时按ID倒序
+    pub async fn find_by_type(
+        &self,
+        server_type: &str,
+        order_by: Option<OrderBy>,
+    ) -> RepositoryResult<Vec<McpServer>> {
+        let query = format!(
+            "SELECT * FROM mcp_servers WHERE type = ? ORDER BY {}",
+            Self::order_by_clause(order_by, "id DESC")?
+        );
 
         tracing::debug!(
             server_type = %server_type,
             "根据类型获取MCP服务器列表"
         );
 
-        let results = sqlx::query_as::<_, McpServer>(query)
+        let results = sqlx::query_as::<_, McpServer>(&query)
             .bind(server_type)
-            .fetch_all(&self.pool)
+            .fetch_all(self.executor("find_by_type", &self.pool))
             .await?;
 
         Ok(results)
@@ -192,18 +341,29 @@ impl McpServerRepository {
     pub async fn count_by_type(&self, server_type: &str) -> RepositoryResult<i64> {
         let query = "SELECT COUNT(*) FROM mcp_servers WHERE type = ?";
 
-        let count: i64 = sqlx::query_scalar(query).bind(server_type).fetch_one(&self.pool).await?;
+        let count: i64 = sqlx::query_scalar(query)
+            .bind(server_type)
+            .fetch_one(self.executor("count_by_type", &self.pool))
+            .await?;
 
         Ok(count)
     }
 
     /// 获取活跃的MCP服务器（根据timeout判断）
-    pub async fn list_active_servers(&self) -> RepositoryResult<Vec<McpServer>> {
-        let query = "SELECT * FROM mcp_servers WHERE timeout > 0 ORDER BY id DESC";
+    pub async fn list_active_servers(
+        &self,
+        order_by: Option<OrderBy>,
+    ) -> RepositoryResult<Vec<McpServer>> {
+        let query = format!(
+            "SELECT * FROM mcp_servers WHERE timeout > 0 ORDER BY {}",
+            Self::order_by_clause(order_by, "id DESC")?
+        );
 
         tracing::debug!("获取活跃的MCP服务器列表");
 
-        let results = sqlx::query_as::<_, McpServer>(query).fetch_all(&self.pool).await?;
+        let results = sqlx::query_as::<_, McpServer>(&query)
+            .fetch_all(self.executor("list_active", &self.pool))
+            .await?;
 
         Ok(results)
     }
@@ -214,6 +374,16 @@ impl BaseRepository for McpServerRepository {
         "mcp_servers"
     }
 
+    fn sortable_columns() -> &'static [SortColumn] {
+        &[
+            SortColumn::Id,
+            SortColumn::Name,
+            SortColumn::Type,
+            SortColumn::CreatedAt,
+            SortColumn::UpdatedAt,
+        ]
+    }
+
     fn pool(&self) -> &SqlitePool {
         &self.pool
     }
@@ -222,6 +392,10 @@ impl BaseRepository for McpServerRepository {
         &self.crypto_service
     }
 
+    fn query_timer(&self) -> &QueryTimer {
+        &self.query_timer
+    }
+
     async fn find_by_id<T>(&self, id: i64) -> RepositoryResult<Option<T>>
     where
         T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
@@ -235,7 +409,13 @@ impl BaseRepository for McpServerRepository {
             query
         );
 
-        let result = sqlx::query_as::<_, T>(&query).bind(id).fetch_optional(self.pool()).await?;
+        let result = self
+            .timed(
+                "find_by_id",
+                &query,
+                sqlx::query_as::<_, T>(&query).bind(id).fetch_optional(self.pool()),
+            )
+            .await?;
 
         Ok(result)
     }
@@ -268,7 +448,13 @@ impl BaseRepository for McpServerRepository {
             "删除MCP服务器"
         );
 
-        let result = sqlx::query(query).bind(id).execute(self.pool()).await?;
+        let result = self
+            .timed(
+                "delete",
+                query,
+                sqlx::query(query).bind(id).execute(self.pool()),
+            )
+            .await?;
 
         Ok(result.rows_affected() > 0)
     }
@@ -281,7 +467,13 @@ impl BaseRepository for McpServerRepository {
 
         tracing::debug!("获取MCP服务器列表");
 
-        let results = sqlx::query_as::<_, T>(query).fetch_all(self.pool()).await?;
+        let results = self
+            .timed(
+                "list_all",
+                query,
+                sqlx::query_as::<_, T>(query).fetch_all(self.pool()),
+            )
+            .await?;
 
         Ok(results)
     }
@@ -299,10 +491,25 @@ impl BaseRepository for McpServerRepository {
 
         // 查询总数
         let count_query = "SELECT COUNT(*) FROM mcp_servers";
-        let total: i64 = sqlx::query_scalar(count_query).fetch_one(self.pool()).await?;
+        let total: Option<i64> = if params.include_total() {
+            Some(
+                self.timed(
+                    "paginate",
+                    count_query,
+                    sqlx::query_scalar(count_query).fetch_one(self.pool()),
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
 
         // 查询分页数据
-        let data_query = "SELECT * FROM mcp_servers ORDER BY id DESC LIMIT ? OFFSET ?";
+        let order_by = Self::order_by_clause(params.order_by, "id DESC")?;
+        let data_query = format!(
+            "SELECT * FROM mcp_servers ORDER BY {} LIMIT ? OFFSET ?",
+            order_by
+        );
 
         tracing::debug!(
             page = %page,
@@ -311,13 +518,19 @@ impl BaseRepository for McpServerRepository {
             "分页查询MCP服务器"
         );
 
-        let data = sqlx::query_as::<_, T>(data_query)
-            .bind(limit)
-            .bind(offset)
-            .fetch_all(self.pool())
+        let data = self
+            .timed(
+                "paginate",
+                &data_query,
+                sqlx::query_as::<_, T>(&data_query)
+                    .bind(limit)
+                    .bind(offset)
+                    .fetch_all(self.pool()),
+            )
             .await?;
 
-        let paged_result = crate::models::PagedResult::new(data, total, page, limit);
+        let paged_result =
+            crate::models::PagedResult::with_optional_total(data, total, page, limit);
 
         Ok(paged_result)
     }
@@ -327,11 +540,13 @@ impl BaseRepository for McpServerRepository {
         search_term: &str,
         search_fields: &[&str],
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> RepositoryResult<Vec<T>>
     where
         T: for<'r> FromRow<'r, sqlx::sqlite::SqliteRow> + Send + Unpin,
     {
         let limit = limit.unwrap_or(50);
+        let order_by = Self::order_by_clause(order_by, "id DESC")?;
 
         // 构建搜索查询
         let mut where_conditions = Vec::new();
@@ -353,11 +568,12 @@ impl BaseRepository for McpServerRepository {
         }
 
         let query = if where_conditions.is_empty() {
-            "SELECT * FROM mcp_servers ORDER BY id DESC LIMIT ?".to_string()
+            format!("SELECT * FROM mcp_servers ORDER BY {} LIMIT ?", order_by)
         } else {
             format!(
-                "SELECT * FROM mcp_servers WHERE {} ORDER BY id DESC LIMIT ?",
-                where_conditions.join(" OR ")
+                "SELECT * FROM mcp_servers WHERE {} ORDER BY {} LIMIT ?",
+                where_conditions.join(" OR "),
+                order_by
             )
         };
 
@@ -375,7 +591,7 @@ impl BaseRepository for McpServerRepository {
         }
         query_builder = query_builder.bind(limit);
 
-        let results = query_builder.fetch_all(self.pool()).await?;
+        let results = self.timed("search", &query, query_builder.fetch_all(self.pool())).await?;
 
         Ok(results)
     }
@@ -385,7 +601,13 @@ impl BaseRepository for McpServerRepository {
 
         tracing::debug!("统计MCP服务器总数");
 
-        let count: i64 = sqlx::query_scalar(query).fetch_one(self.pool()).await?;
+        let count: i64 = self
+            .timed(
+                "count",
+                query,
+                sqlx::query_scalar(query).fetch_one(self.pool()),
+            )
+            .await?;
 
         Ok(count)
     }
@@ -410,6 +632,7 @@ mod tests {
             connect_timeout: std::time::Duration::from_secs(10),
             idle_timeout: std::time::Duration::from_secs(60),
             max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = DatabaseManager::new(config).await.unwrap();
@@ -433,6 +656,7 @@ mod tests {
             command: "npx".to_string(),
             args: vec!["@modelcontextprotocol/server".to_string()],
             env: Some(env),
+            secret_env_keys: vec![],
         };
 
         let id = repo.create_mcp_server(&create_request).await.unwrap();
@@ -452,6 +676,7 @@ mod tests {
             command: None,
             args: Some(vec!["--port".to_string(), "8080".to_string()]),
             env: None,
+            secret_env_keys: None,
         };
 
         let updated = repo.update_mcp_server(id, &update_request).await.unwrap();
@@ -472,6 +697,88 @@ mod tests {
         assert!(deleted_server.is_none());
     }
 
+    #[tokio::test]
+    async fn test_secret_env_encrypted_at_rest() {
+        let temp_dir = tempdir().unwrap();
+        let config = DatabaseConfig {
+            url: format!(
+                "sqlite:{}",
+                temp_dir.path().join("test_mcp_secret.db").display()
+            ),
+            ..Default::default()
+        };
+        let db_manager = DatabaseManager::new(config).await.unwrap();
+        db_manager.ensure_initialized().await.unwrap();
+        let crypto_service =
+            CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap();
+        let repo = McpServerRepository::new(&db_manager, &crypto_service);
+
+        let env = HashMap::from([
+            ("API_KEY".to_string(), "sk-mcp-secret-value".to_string()),
+            ("LOG_LEVEL".to_string(), "debug".to_string()),
+        ]);
+        let id = repo
+            .create_mcp_server(&CreateMcpServerRequest {
+                name: "secret-env".to_string(),
+                r#type: Some("stdio".to_string()),
+                timeout: None,
+                command: "npx".to_string(),
+                args: vec![],
+                env: Some(env.clone()),
+                secret_env_keys: vec!["API_KEY".to_string()],
+            })
+            .await
+            .unwrap();
+
+        // 数据库中只有机密值被加密
+        let server = repo.find_by_id_parsed(id).await.unwrap().unwrap();
+        assert_eq!(server.secret_env_keys, vec!["API_KEY"]);
+        let raw = server.env.clone().unwrap();
+        assert!(!raw.contains("sk-mcp-secret-value"));
+        let stored: HashMap<String, String> = serde_json::from_str(&raw).unwrap();
+        assert!(CryptoService::is_fernet_token(&stored["API_KEY"]));
+        assert_eq!(stored["LOG_LEVEL"], "debug");
+
+        // 生成配置时解密
+        assert_eq!(
+            repo.decrypted_env(&server).await.unwrap(),
+            Some(env.clone())
+        );
+
+        // 接口返回时屏蔽机密值
+        let mut masked = server.clone();
+        mask_secret_env(&mut masked);
+        let masked: HashMap<String, String> =
+            serde_json::from_str(masked.env.as_deref().unwrap()).unwrap();
+        assert_eq!(masked["API_KEY"], REDACTED);
+        assert_eq!(masked["LOG_LEVEL"], "debug");
+
+        // 回传占位符时保留原值，取消机密标记后改为明文保存
+        let mut update_env = masked.clone();
+        update_env.insert("LOG_LEVEL".to_string(), "info".to_string());
+        repo.update_mcp_server(
+            id,
+            &UpdateMcpServerRequest {
+                name: None,
+                r#type: None,
+                timeout: None,
+                command: None,
+                args: None,
+                env: Some(Some(update_env)),
+                secret_env_keys: Some(vec![]),
+            },
+        )
+        .await
+        .unwrap();
+
+        let server = repo.find_by_id_parsed(id).await.unwrap().unwrap();
+        assert!(server.secret_env_keys.is_empty());
+        let stored: HashMap<String, String> =
+            serde_json::from_str(server.env.as_deref().unwrap()).unwrap();
+        assert_eq!(stored["API_KEY"], "sk-mcp-secret-value");
+        assert_eq!(stored["LOG_LEVEL"], "info");
+    }
+
     #[tokio::test]
     async fn test_server_config_validation() {
         let repo = create_test_repository().await;
@@ -484,6 +791,7 @@ mod tests {
             command: "python".to_string(),
             args: vec!["server.py".to_string()],
             env: None,
+            secret_env_keys: vec![],
         };
 
         let id = repo.create_mcp_server(&create_request).await.unwrap();
@@ -498,6 +806,7 @@ mod tests {
             command: "".to_string(),
             args: vec![],
             env: None,
+            secret_env_keys: vec![],
         };
 
         let id_invalid = repo.create_mcp_server(&create_request_invalid).await.unwrap();
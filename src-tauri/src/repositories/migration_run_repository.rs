@@ -0,0 +1,77 @@
+// 数据迁移运行记录Repository实现
+//
+// 持久化每次数据迁移的结果，提供迁移历史查询
+
+use crate::database::{DatabaseManager, QueryTimer};
+use crate::models::{CreateMigrationRunRequest, MigrationRun};
+use crate::repositories::base_repository::RepositoryResult;
+use sqlx::SqlitePool;
+
+/// 数据迁移运行记录Repository
+pub struct MigrationRunRepository {
+    pool: SqlitePool,
+    query_timer: QueryTimer,
+}
+
+impl MigrationRunRepository {
+    /// 创建新的迁移运行记录Repository实例
+    pub fn new(db_manager: &DatabaseManager) -> Self {
+        Self {
+            pool: db_manager.pool().clone(),
+            query_timer: db_manager.query_timer(),
+        }
+    }
+
+    /// 记录一次迁移运行
+    pub async fn create_migration_run(
+        &self,
+        request: &CreateMigrationRunRequest,
+    ) -> RepositoryResult<i64> {
+        let query = r#"
+            INSERT INTO migration_runs (
+                source, started_at, finished_at, total_migrated, claude_providers,
+                codex_providers, agent_guides, mcp_servers, common_configs,
+                errors_json, warnings_json, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, datetime('now'))
+        "#;
+
+        tracing::info!(
+            source = %request.source,
+            total_migrated = %request.total_migrated,
+            errors = %request.errors.len(),
+            "记录数据迁移运行"
+        );
+
+        let result = sqlx::query(query)
+            .bind(&request.source)
+            .bind(&request.started_at)
+            .bind(&request.finished_at)
+            .bind(request.total_migrated)
+            .bind(request.claude_providers)
+            .bind(request.codex_providers)
+            .bind(request.agent_guides)
+            .bind(request.mcp_servers)
+            .bind(request.common_configs)
+            .bind(serde_json::to_string(&request.errors)?)
+            .bind(serde_json::to_string(&request.warnings)?)
+            .execute(self.query_timer.executor("migration_runs.create", &self.pool))
+            .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// 获取迁移历史（最新的在前）
+    pub async fn list_migration_runs(
+        &self,
+        limit: Option<i64>,
+    ) -> RepositoryResult<Vec<MigrationRun>> {
+        let runs = sqlx::query_as::<_, MigrationRun>(
+            "SELECT * FROM migration_runs ORDER BY id DESC LIMIT ?",
+        )
+        .bind(limit.unwrap_or(-1))
+        .fetch_all(self.query_timer.executor("migration_runs.list", &self.pool))
+        .await?;
+
+        Ok(runs)
+    }
+}
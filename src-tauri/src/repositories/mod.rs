@@ -9,6 +9,8 @@ pub mod claude_provider_repository;
 pub mod codex_provider_repository;
 pub mod common_config_repository;
 pub mod mcp_server_repository;
+pub mod migration_run_repository;
+pub mod provider_group_repository;
 
 // 重新导出主要组件
 pub use agent_guide_repository::AgentGuideRepository;
@@ -17,3 +19,5 @@ pub use claude_provider_repository::ClaudeProviderRepository;
 pub use codex_provider_repository::CodexProviderRepository;
 pub use common_config_repository::CommonConfigRepository;
 pub use mcp_server_repository::McpServerRepository;
+pub use migration_run_repository::MigrationRunRepository;
+pub use provider_group_repository::ProviderGroupRepository;
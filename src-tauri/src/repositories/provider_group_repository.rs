@@ -0,0 +1,111 @@
+// 供应商分组Repository实现
+//
+// 提供供应商分组及其成员的数据访问操作
+
+use crate::database::{DatabaseManager, QueryTimer};
+use crate::models::{CreateProviderGroupRequest, ProviderGroup, ProviderGroupMember};
+use crate::repositories::base_repository::RepositoryResult;
+use sqlx::SqlitePool;
+
+/// 供应商分组Repository
+#[derive(Clone)]
+pub struct ProviderGroupRepository {
+    pool: SqlitePool,
+    query_timer: QueryTimer,
+}
+
+impl ProviderGroupRepository {
+    /// 创建新的供应商分组Repository实例
+    pub fn new(db_manager: &DatabaseManager) -> Self {
+        Self {
+            pool: db_manager.pool().clone(),
+            query_timer: db_manager.query_timer(),
+        }
+    }
+
+    /// 创建分组及其成员
+    pub async fn create_group(
+        &self,
+        request: &CreateProviderGroupRequest,
+    ) -> RepositoryResult<i64> {
+        tracing::info!(
+            name = %request.name,
+            members = %request.members.len(),
+            "创建供应商分组"
+        );
+
+        let mut tx = self.pool.begin().await?;
+
+        let group_id = sqlx::query(
+            r#"
+            INSERT INTO provider_groups (name, description, created_at, updated_at)
+            VALUES (?, ?, datetime('now'), datetime('now'))
+            "#,
+        )
+        .bind(&request.name)
+        .bind(&request.description)
+        .execute(self.query_timer.executor("provider_groups.create", &mut *tx))
+        .await?
+        .last_insert_rowid();
+
+        for member in &request.members {
+            sqlx::query(
+                "INSERT INTO provider_group_members (group_id, provider_type, provider_id) VALUES (?, ?, ?)",
+            )
+            .bind(group_id)
+            .bind(&member.provider_type)
+            .bind(member.provider_id)
+            .execute(self.query_timer.executor("provider_group_members.create", &mut *tx))
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(group_id)
+    }
+
+    /// 获取所有分组（按名称排序）
+    pub async fn list_groups(&self) -> RepositoryResult<Vec<ProviderGroup>> {
+        let groups =
+            sqlx::query_as::<_, ProviderGroup>("SELECT * FROM provider_groups ORDER BY name ASC")
+                .fetch_all(self.query_timer.executor("provider_groups.list", &self.pool))
+                .await?;
+
+        Ok(groups)
+    }
+
+    /// 根据ID获取分组
+    pub async fn find_group(&self, id: i64) -> RepositoryResult<Option<ProviderGroup>> {
+        let group =
+            sqlx::query_as::<_, ProviderGroup>("SELECT * FROM provider_groups WHERE id = ?")
+                .bind(id)
+                .fetch_optional(self.query_timer.executor("provider_groups.find_by_id", &self.pool))
+                .await?;
+
+        Ok(group)
+    }
+
+    /// 根据名称获取分组
+    pub async fn find_group_by_name(&self, name: &str) -> RepositoryResult<Option<ProviderGroup>> {
+        let group =
+            sqlx::query_as::<_, ProviderGroup>("SELECT * FROM provider_groups WHERE name = ?")
+                .bind(name)
+                .fetch_optional(
+                    self.query_timer.executor("provider_groups.find_by_name", &self.pool),
+                )
+                .await?;
+
+        Ok(group)
+    }
+
+    /// 获取分组成员
+    pub async fn list_members(&self, group_id: i64) -> RepositoryResult<Vec<ProviderGroupMember>> {
+        let members = sqlx::query_as::<_, ProviderGroupMember>(
+            "SELECT group_id, provider_type, provider_id FROM provider_group_members WHERE group_id = ? ORDER BY provider_type ASC",
+        )
+        .bind(group_id)
+        .fetch_all(self.query_timer.executor("provider_group_members.list", &self.pool))
+        .await?;
+
+        Ok(members)
+    }
+}
@@ -0,0 +1,120 @@
+//! 异步运行时配置
+//!
+//! 支持两种运行时模型，通过环境变量选择：
+//!
+//! - **单线程**（默认）：只创建一个工作线程，启动最快、资源占用最小，
+//!   但所有异步任务串行执行，API在并发负载下请求会排队
+//! - **多线程**：按配置数量创建工作线程，启动稍慢、占用更多线程，
+//!   但可以并行处理API请求和后台任务
+//!
+//! ```bash
+//! AI_MANAGER_RUNTIME=multi_thread AI_MANAGER_WORKER_THREADS=4 ./ai-manager
+//! ```
+
+use std::io;
+use tokio::runtime::{Builder, Runtime};
+use tracing::warn;
+
+/// 运行时模型环境变量
+pub const RUNTIME_MODE_ENV: &str = "AI_MANAGER_RUNTIME";
+/// 多线程运行时工作线程数环境变量
+pub const WORKER_THREADS_ENV: &str = "AI_MANAGER_WORKER_THREADS";
+
+/// 异步运行时模型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RuntimeMode {
+    /// 单线程运行时
+    #[default]
+    CurrentThread,
+    /// 多线程运行时
+    MultiThread { worker_threads: usize },
+}
+
+impl RuntimeMode {
+    /// 从环境变量读取运行时模型
+    pub fn from_env() -> Self {
+        let mode = std::env::var(RUNTIME_MODE_ENV).unwrap_or_default();
+        let workers = std::env::var(WORKER_THREADS_ENV).ok();
+        Self::parse(&mode, workers.as_deref())
+    }
+
+    /// 解析运行时模型，无法识别时回退到单线程
+    pub fn parse(mode: &str, worker_threads: Option<&str>) -> Self {
+        match mode.trim().to_lowercase().as_str() {
+            "" | "current_thread" | "current" => Self::CurrentThread,
+            "multi_thread" | "multi" => {
+                let default_workers =
+                    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+                let worker_threads = match worker_threads.map(|w| w.trim().parse::<usize>()) {
+                    Some(Ok(n)) if n > 0 => n,
+                    Some(_) => {
+                        warn!("无效的工作线程数，使用默认值 {}", default_workers);
+                        default_workers
+                    }
+                    None => default_workers,
+                };
+                Self::MultiThread { worker_threads }
+            }
+            other => {
+                warn!("未知的运行时模型 '{}'，使用单线程运行时", other);
+                Self::CurrentThread
+            }
+        }
+    }
+
+    /// 构建对应的tokio运行时
+    pub fn build(&self) -> io::Result<Runtime> {
+        match self {
+            Self::CurrentThread => Builder::new_current_thread().enable_all().build(),
+            Self::MultiThread { worker_threads } => Builder::new_multi_thread()
+                .worker_threads(*worker_threads)
+                .thread_name("ai-manager-worker")
+                .enable_all()
+                .build(),
+        }
+    }
+}
+
+impl std::fmt::Display for RuntimeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CurrentThread => write!(f, "单线程"),
+            Self::MultiThread { worker_threads } => {
+                write!(f, "多线程({}个工作线程)", worker_threads)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_runtime_mode() {
+        assert_eq!(RuntimeMode::parse("", None), RuntimeMode::CurrentThread);
+        assert_eq!(
+            RuntimeMode::parse("unknown", None),
+            RuntimeMode::CurrentThread
+        );
+        assert_eq!(
+            RuntimeMode::parse("multi_thread", Some("3")),
+            RuntimeMode::MultiThread { worker_threads: 3 }
+        );
+        assert!(matches!(
+            RuntimeMode::parse("MULTI", Some("0")),
+            RuntimeMode::MultiThread { worker_threads } if worker_threads > 0
+        ));
+    }
+
+    #[test]
+    fn test_build_runtime() {
+        for mode in [
+            RuntimeMode::CurrentThread,
+            RuntimeMode::MultiThread { worker_threads: 2 },
+        ] {
+            let runtime = mode.build().unwrap();
+            assert_eq!(runtime.block_on(async { 1 + 1 }), 2);
+        }
+    }
+}
@@ -0,0 +1,546 @@
+// 供应商模型自动更新
+//
+// `model_auto_update = 1` 的已启用供应商会定期请求上游的模型列表接口，
+// 将已配置的模型（opus/sonnet/haiku 及模型映射中的其他角色）更新为同系列的最新模型。
+// 每次修改都写入模型变更历史，并通过事件通知订阅者。
+// `auto_update` 字段表示禁用遥测，与模型自动更新无关
+
+use crate::crypto::CryptoService;
+use crate::database::DatabaseManager;
+use crate::models::{
+    ClaudeProvider, ProviderModelHistory, MODEL_ROLE_HAIKU, MODEL_ROLE_OPUS, MODEL_ROLE_SONNET,
+};
+use crate::repositories::base_repository::RepositoryError;
+use crate::repositories::ClaudeProviderRepository;
+use crate::services::connection_test::{self, ConnectionTestError};
+use crate::services::http::HttpClient;
+use crate::task_registry::{TaskRegistry, TaskRegistryError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// 后台自动更新任务名称
+pub const AUTO_UPDATE_TASK_NAME: &str = "provider_model_auto_update";
+/// 后台自动更新任务默认执行间隔
+pub const DEFAULT_AUTO_UPDATE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// 模型变更历史中记录的来源
+pub const AUTO_UPDATE_SOURCE: &str = "auto_update";
+
+/// 未被及时接收的事件数量上限，超出后最旧的事件被丢弃
+const EVENT_CHANNEL_CAPACITY: usize = 32;
+/// 按模型名称识别的模型系列
+const MODEL_FAMILIES: &[&str] = &[MODEL_ROLE_OPUS, MODEL_ROLE_SONNET, MODEL_ROLE_HAIKU];
+
+/// 自动更新错误
+#[derive(Debug, thiserror::Error)]
+pub enum AutoUpdateError {
+    #[error("模型列表接口在 {timeout_ms} 毫秒内未响应")]
+    Timeout { timeout_ms: u64 },
+
+    #[error("请求模型列表失败: {0}")]
+    Request(String),
+
+    #[error("模型列表接口返回状态码 {0}")]
+    Status(u16),
+
+    #[error("模型列表格式无效: {0}")]
+    InvalidResponse(String),
+
+    #[error("连接配置无效: {0}")]
+    Connection(#[from] ConnectionTestError),
+
+    #[error("数据访问错误: {0}")]
+    Repository(#[from] RepositoryError),
+
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("序列化错误: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// 自动更新结果类型
+pub type AutoUpdateResult<T> = Result<T, AutoUpdateError>;
+
+/// 单个模型角色的变更
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ModelChange {
+    pub role: String,
+    /// 之前未配置时为 `None`
+    pub old_model: Option<String>,
+    pub new_model: String,
+}
+
+/// 供应商模型已更新事件
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelUpdateEvent {
+    pub provider_id: i64,
+    pub provider_name: String,
+    pub changes: Vec<ModelChange>,
+}
+
+/// 无法更新的供应商
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoUpdateFailure {
+    pub provider_id: i64,
+    pub error: String,
+}
+
+/// 自动更新结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AutoUpdateReport {
+    /// 检查过模型列表的供应商数量
+    pub checked: u64,
+    /// 模型发生变化的供应商
+    pub updated: Vec<ModelUpdateEvent>,
+    pub failures: Vec<AutoUpdateFailure>,
+}
+
+/// 模型列表接口返回的单个模型
+#[derive(Debug, Clone, Deserialize)]
+struct RemoteModel {
+    id: String,
+    /// RFC 3339 时间，缺失时按接口返回顺序（越靠前越新）判断
+    #[serde(default)]
+    created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelList {
+    data: Vec<RemoteModel>,
+}
+
+/// 供应商模型自动更新服务
+#[derive(Clone)]
+pub struct AutoUpdateService {
+    db_manager: Arc<DatabaseManager>,
+    crypto_service: Arc<CryptoService>,
+    /// 请求模型列表使用的上游HTTP客户端
+    http_client: HttpClient,
+    events: broadcast::Sender<ModelUpdateEvent>,
+}
+
+impl AutoUpdateService {
+    /// 创建自动更新服务
+    pub fn new(db_manager: Arc<DatabaseManager>, crypto_service: Arc<CryptoService>) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            db_manager,
+            crypto_service,
+            http_client: HttpClient::default(),
+            events,
+        }
+    }
+
+    /// 使用应用共享的HTTP客户端，与其他服务共用连接池
+    pub fn with_http_client(mut self, http_client: HttpClient) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    /// 订阅模型更新事件，只会收到订阅之后发生的更新
+    pub fn subscribe(&self) -> broadcast::Receiver<ModelUpdateEvent> {
+        self.events.subscribe()
+    }
+
+    /// 检查所有开启自动更新的已启用供应商
+    ///
+    /// 单个供应商请求失败时记录到报告中并继续处理其他供应商
+    pub async fn update_all(&self) -> AutoUpdateResult<AutoUpdateReport> {
+        let providers = self.repository().list_claude_providers_decrypted().await?;
+        let mut report = AutoUpdateReport::default();
+
+        for provider in providers {
+            if provider.enabled != 1 || provider.model_auto_update != 1 {
+                continue;
+            }
+
+            report.checked += 1;
+            match self.update_provider(&provider).await {
+                Ok(Some(event)) => report.updated.push(event),
+                Ok(None) => {}
+                Err(e) => {
+                    warn!(provider_id = %provider.id, error = %e, "供应商模型自动更新失败");
+                    report
+                        .failures
+                        .push(AutoUpdateFailure { provider_id: provider.id, error: e.to_string() });
+                }
+            }
+        }
+
+        info!(
+            checked = %report.checked,
+            updated = %report.updated.len(),
+            failures = %report.failures.len(),
+            "供应商模型自动更新完成"
+        );
+        Ok(report)
+    }
+
+    /// 获取供应商的模型变更历史，最新的在前
+    pub async fn history(&self, provider_id: i64) -> AutoUpdateResult<Vec<ProviderModelHistory>> {
+        let history = sqlx::query_as::<_, ProviderModelHistory>(
+            "SELECT * FROM provider_model_history WHERE provider_id = ? ORDER BY id DESC",
+        )
+        .bind(provider_id)
+        .fetch_all(self.db_manager.pool())
+        .await?;
+        Ok(history)
+    }
+
+    /// 注册周期性自动更新任务
+    pub fn register(
+        &self,
+        registry: &TaskRegistry,
+        interval: Duration,
+    ) -> Result<(), TaskRegistryError> {
+        let service = self.clone();
+        registry.register(AUTO_UPDATE_TASK_NAME, interval, move || {
+            let service = service.clone();
+            async move { service.update_all().await.map(|_| ()).map_err(|e| e.to_string()) }
+        })
+    }
+
+    /// 更新单个供应商的模型，没有变化时返回 `None`
+    async fn update_provider(
+        &self,
+        provider: &ClaudeProvider,
+    ) -> AutoUpdateResult<Option<ModelUpdateEvent>> {
+        let remote = fetch_models(&self.http_client, provider).await?;
+        let mut models = provider.effective_models();
+        let changes = plan_changes(&models, &remote);
+        if changes.is_empty() {
+            debug!(provider_id = %provider.id, "供应商模型已是最新");
+            return Ok(None);
+        }
+
+        for change in &changes {
+            models.insert(change.role.clone(), change.new_model.clone());
+        }
+        self.save_changes(provider.id, &models, &changes).await?;
+
+        for change in &changes {
+            info!(
+                provider_id = %provider.id,
+                role = %change.role,
+                old_model = ?change.old_model,
+                new_model = %change.new_model,
+                "供应商模型已自动更新"
+            );
+        }
+
+        let event = ModelUpdateEvent {
+            provider_id: provider.id,
+            provider_name: provider.name.clone(),
+            changes,
+        };
+        // 没有订阅者时忽略
+        let _ = self.events.send(event.clone());
+        Ok(Some(event))
+    }
+
+    /// 在一个事务中写入新的模型和变更历史
+    async fn save_changes(
+        &self,
+        provider_id: i64,
+        models: &HashMap<String, String>,
+        changes: &[ModelChange],
+    ) -> AutoUpdateResult<()> {
+        let mut tx = self.db_manager.pool().begin().await?;
+
+        self.repository().update_models(&mut *tx, provider_id, models).await?;
+
+        for change in changes {
+            sqlx::query(
+                "INSERT INTO provider_model_history (provider_id, role, old_model, new_model, source)
+                 VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(provider_id)
+            .bind(&change.role)
+            .bind(&change.old_model)
+            .bind(&change.new_model)
+            .bind(AUTO_UPDATE_SOURCE)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    fn repository(&self) -> ClaudeProviderRepository {
+        ClaudeProviderRepository::new(&self.db_manager, &self.crypto_service)
+    }
+}
+
+/// 模型列表接口地址，供应商URL已包含 `/v1` 时不再重复添加
+fn models_url(base_url: &str) -> String {
+    let base_url = base_url.trim_end_matches('/');
+    if base_url.ends_with("/v1") {
+        format!("{}/models", base_url)
+    } else {
+        format!("{}/v1/models", base_url)
+    }
+}
+
+/// 请求供应商的模型列表，使用供应商配置的超时时间和自定义请求头
+async fn fetch_models(
+    client: &HttpClient,
+    provider: &ClaudeProvider,
+) -> AutoUpdateResult<Vec<RemoteModel>> {
+    let timeout = connection_test::timeout_from_millis(provider.timeout);
+    let headers = connection_test::custom_header_map(&provider.custom_headers)?;
+
+    let request = client
+        .get(&models_url(&provider.url))
+        .timeout(timeout)
+        .header("x-api-key", &provider.token)
+        .header("anthropic-version", "2023-06-01")
+        .bearer_auth(&provider.token)
+        .headers(headers);
+    let response = client.send(request).await.map_err(|e| {
+        if e.is_timeout() {
+            AutoUpdateError::Timeout { timeout_ms: timeout.as_millis() as u64 }
+        } else {
+            AutoUpdateError::Request(e.without_url().to_string())
+        }
+    })?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(AutoUpdateError::Status(status.as_u16()));
+    }
+
+    let list: ModelList = response
+        .json()
+        .await
+        .map_err(|e| AutoUpdateError::InvalidResponse(e.without_url().to_string()))?;
+    Ok(list.data)
+}
+
+/// 计算每个已配置角色需要更新到的模型
+///
+/// opus/sonnet/haiku 角色按角色名匹配系列，其他角色按当前模型名称中包含的系列匹配，
+/// 无法识别系列的角色保持不变
+fn plan_changes(models: &HashMap<String, String>, remote: &[RemoteModel]) -> Vec<ModelChange> {
+    let mut changes: Vec<ModelChange> = models
+        .iter()
+        .filter_map(|(role, current)| {
+            let family = model_family(role, current)?;
+            let latest = latest_model(remote, family)?;
+            (latest != current).then(|| ModelChange {
+                role: role.clone(),
+                old_model: Some(current.clone()),
+                new_model: latest.to_string(),
+            })
+        })
+        .collect();
+    changes.sort_by(|a, b| a.role.cmp(&b.role));
+    changes
+}
+
+fn model_family(role: &str, model: &str) -> Option<&'static str> {
+    let model = model.to_ascii_lowercase();
+    MODEL_FAMILIES
+        .iter()
+        .copied()
+        .find(|family| *family == role)
+        .or_else(|| MODEL_FAMILIES.iter().copied().find(|family| model.contains(family)))
+}
+
+/// 同系列中最新的模型：`created_at` 最大者，相同时取接口返回顺序中靠前的
+fn latest_model<'a>(remote: &'a [RemoteModel], family: &str) -> Option<&'a str> {
+    remote
+        .iter()
+        .enumerate()
+        .filter(|(_, model)| model.id.to_ascii_lowercase().contains(family))
+        .max_by(|(a_index, a), (b_index, b)| {
+            a.created_at.cmp(&b.created_at).then(b_index.cmp(a_index))
+        })
+        .map(|(_, model)| model.id.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseConfig;
+    use crate::models::CreateClaudeProviderRequest;
+    use tempfile::tempdir;
+
+    /// 返回固定模型列表的上游
+    async fn spawn_models_upstream(body: &'static str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buffer = vec![0; 8192];
+                let mut read = 0;
+                while !buffer[..read].windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buffer[read..]).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => read += n,
+                    }
+                }
+                let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                let response = if request.starts_with("GET /v1/models ") {
+                    format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                } else {
+                    "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+                        .to_string()
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn provider_request(name: &str, url: &str) -> CreateClaudeProviderRequest {
+        CreateClaudeProviderRequest {
+            name: name.to_string(),
+            url: url.to_string(),
+            token: format!("sk-ant-{}", name),
+            timeout: Some(5_000),
+            auto_update: Some(1),
+            r#type: None,
+            opus_model: Some("claude-3-opus-20240229".to_string()),
+            sonnet_model: Some("claude-3-5-sonnet-20241022".to_string()),
+            haiku_model: None,
+            models: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+            model_auto_update: Some(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_all_refreshes_models_from_model_list() {
+        let temp_dir = tempdir().unwrap();
+        let config = DatabaseConfig {
+            url: format!(
+                "sqlite:{}",
+                temp_dir.path().join("test_auto_update.db").display()
+            ),
+            ..Default::default()
+        };
+        let db_manager = Arc::new(DatabaseManager::new(config).await.unwrap());
+        db_manager.ensure_initialized().await.unwrap();
+        let crypto_service =
+            Arc::new(CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap());
+        let repository = ClaudeProviderRepository::new(&db_manager, &crypto_service);
+
+        let url = spawn_models_upstream(
+            r#"{"data":[
+                {"id":"claude-sonnet-4-20250514","created_at":"2025-05-14T00:00:00Z"},
+                {"id":"claude-opus-4-1-20250805","created_at":"2025-08-05T00:00:00Z"},
+                {"id":"claude-opus-4-20250514","created_at":"2025-05-14T00:00:00Z"},
+                {"id":"claude-3-5-haiku-20241022","created_at":"2024-10-22T00:00:00Z"}
+            ]}"#,
+        )
+        .await;
+
+        let enabled_id = repository
+            .create_claude_provider(&provider_request("enabled", &url))
+            .await
+            .unwrap();
+        // 未启用的供应商不会被更新
+        let disabled_id = repository
+            .create_claude_provider(&provider_request("disabled", &url))
+            .await
+            .unwrap();
+        // 只禁用遥测（auto_update = 1）而未开启模型自动更新的供应商不会被更新
+        let telemetry_only_id = repository
+            .create_claude_provider(&CreateClaudeProviderRequest {
+                model_auto_update: Some(0),
+                ..provider_request("telemetry_only", &url)
+            })
+            .await
+            .unwrap();
+        // 固定修改时间，验证更新模型时会刷新 updated_at
+        sqlx::query(
+            "UPDATE claude_providers SET enabled = 1, updated_at = '2000-01-01 00:00:00' WHERE id IN (?, ?)",
+        )
+        .bind(enabled_id)
+        .bind(telemetry_only_id)
+        .execute(db_manager.pool())
+        .await
+        .unwrap();
+
+        let service = AutoUpdateService::new(db_manager.clone(), crypto_service.clone());
+        let mut events = service.subscribe();
+        let report = service.update_all().await.unwrap();
+        assert_eq!(report.checked, 1);
+        assert!(report.failures.is_empty(), "{:?}", report.failures);
+        assert_eq!(report.updated.len(), 1);
+
+        let provider = repository.find_by_id_decrypted(enabled_id).await.unwrap().unwrap();
+        assert_eq!(
+            provider.opus_model.as_deref(),
+            Some("claude-opus-4-1-20250805")
+        );
+        assert_eq!(
+            provider.sonnet_model.as_deref(),
+            Some("claude-sonnet-4-20250514")
+        );
+        assert_ne!(provider.updated_at.as_deref(), Some("2000-01-01 00:00:00"));
+        // 未配置的角色保持未配置
+        assert_eq!(provider.haiku_model, None);
+        assert_eq!(
+            provider.model(MODEL_ROLE_OPUS).as_deref(),
+            Some("claude-opus-4-1-20250805")
+        );
+
+        let disabled = repository.find_by_id_decrypted(disabled_id).await.unwrap().unwrap();
+        assert_eq!(
+            disabled.opus_model.as_deref(),
+            Some("claude-3-opus-20240229")
+        );
+
+        let telemetry_only =
+            repository.find_by_id_decrypted(telemetry_only_id).await.unwrap().unwrap();
+        assert_eq!(
+            telemetry_only.opus_model.as_deref(),
+            Some("claude-3-opus-20240229")
+        );
+        assert_eq!(
+            telemetry_only.updated_at.as_deref(),
+            Some("2000-01-01 00:00:00")
+        );
+
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.provider_id, enabled_id);
+        assert_eq!(event.changes.len(), 2);
+
+        let history = service.history(enabled_id).await.unwrap();
+        assert_eq!(history.len(), 2);
+        let opus = history.iter().find(|h| h.role == MODEL_ROLE_OPUS).unwrap();
+        assert_eq!(opus.old_model.as_deref(), Some("claude-3-opus-20240229"));
+        assert_eq!(opus.new_model, "claude-opus-4-1-20250805");
+        assert_eq!(opus.source, AUTO_UPDATE_SOURCE);
+
+        // 再次执行时模型已是最新，不再产生变更
+        let report = service.update_all().await.unwrap();
+        assert!(report.updated.is_empty());
+        assert_eq!(service.history(enabled_id).await.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_models_url() {
+        assert_eq!(
+            models_url("https://api.anthropic.com/"),
+            "https://api.anthropic.com/v1/models"
+        );
+        assert_eq!(
+            models_url("https://proxy.example.com/v1"),
+            "https://proxy.example.com/v1/models"
+        );
+    }
+}
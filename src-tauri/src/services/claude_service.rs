@@ -5,11 +5,23 @@
 use crate::crypto::CryptoService;
 use crate::database::DatabaseManager;
 use crate::models::{
-    ClaudeProvider, CreateClaudeProviderRequest, PagedResult, PaginationParams,
-    UpdateClaudeProviderRequest,
+    ClaudeProvider, CreateClaudeProviderRequest, OrderBy, PagedResult, PaginationParams,
+    TestProviderCredentialsRequest, TimeoutMs, UpdateClaudeProviderRequest, UpdateResult,
+    MAX_MODEL_NAME_LENGTH, MAX_NAME_LENGTH, MAX_TOKEN_LENGTH, MAX_URL_LENGTH,
 };
 use crate::repositories::{BaseRepository, ClaudeProviderRepository};
+use crate::services::connection_test::{
+    self, ConnectionTestError, ConnectionTestResult, ProviderTestOutcome,
+};
+use crate::services::http::HttpClient;
+use crate::services::outcome::{
+    ServiceOutcome, Warning, WARNING_DUPLICATE_URL, WARNING_OTHERS_DISABLED,
+};
+use crate::utils::redaction::{redact_url, scrub_secrets, REDACTED};
+use crate::utils::validation::normalize_url;
 use crate::{ValidationError, Validator};
+use futures::stream::{self, Stream, StreamExt};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, error, info, warn};
 
@@ -19,6 +31,9 @@ pub enum ClaudeServiceError {
     #[error("验证失败: {0}")]
     Validation(String),
 
+    #[error("验证失败: {0}")]
+    InvalidField(ValidationError),
+
     #[error("业务规则冲突: {0}")]
     BusinessRule(String),
 
@@ -33,11 +48,17 @@ pub enum ClaudeServiceError {
 
     #[error("没有启用的供应商")]
     NoActiveProvider,
+
+    #[error("供应商 {id} 连接测试超时（{timeout_ms} 毫秒）")]
+    UpstreamTimeout { id: i64, timeout_ms: u64 },
+
+    #[error("连接测试失败: {0}")]
+    ConnectionTest(String),
 }
 
 impl From<ValidationError> for ClaudeServiceError {
     fn from(error: ValidationError) -> Self {
-        ClaudeServiceError::Validation(error.to_string())
+        ClaudeServiceError::InvalidField(error)
     }
 }
 
@@ -48,6 +69,8 @@ pub type ClaudeServiceResult<T> = Result<T, ClaudeServiceError>;
 #[derive(Clone)]
 pub struct ClaudeProviderService {
     repository: Arc<ClaudeProviderRepository>,
+    /// 连接测试使用的上游HTTP客户端
+    http_client: HttpClient,
 }
 
 impl ClaudeProviderService {
@@ -55,14 +78,29 @@ impl ClaudeProviderService {
     pub fn new(db_manager: Arc<DatabaseManager>, crypto_service: Arc<CryptoService>) -> Self {
         Self {
             repository: Arc::new(ClaudeProviderRepository::new(&db_manager, &crypto_service)),
+            http_client: HttpClient::default(),
         }
     }
 
+    /// 使用应用共享的HTTP客户端，与其他服务共用连接池
+    pub fn with_http_client(mut self, http_client: HttpClient) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
     /// 创建Claude供应商
     pub async fn create_provider(
         &self,
         request: CreateClaudeProviderRequest,
     ) -> ClaudeServiceResult<i64> {
+        Ok(self.create_provider_with_warnings(request).await?.into_value())
+    }
+
+    /// 创建Claude供应商，同时返回不阻止创建的提示（如URL与已启用的供应商重复）
+    pub async fn create_provider_with_warnings(
+        &self,
+        mut request: CreateClaudeProviderRequest,
+    ) -> ClaudeServiceResult<ServiceOutcome<i64>> {
         info!(
             name = %request.name,
             url = %request.url,
@@ -71,6 +109,7 @@ impl ClaudeProviderService {
 
         // 验证请求
         self.validate_create_request(&request)?;
+        request.url = normalize_url(&request.url);
 
         // 检查名称唯一性
         if let Some(existing) = self.find_by_name(&request.name).await? {
@@ -83,6 +122,8 @@ impl ClaudeProviderService {
             }
         }
 
+        let warnings = self.duplicate_url_warnings(&request.url, None).await?;
+
         // 创建供应商记录
         let id = self.repository.create_claude_provider(&request).await?;
 
@@ -92,7 +133,7 @@ impl ClaudeProviderService {
             "Claude供应商创建成功"
         );
 
-        Ok(id)
+        Ok(ServiceOutcome::with_warnings(id, warnings))
     }
 
     /// 根据ID获取Claude供应商
@@ -115,6 +156,17 @@ impl ClaudeProviderService {
         id: i64,
         request: UpdateClaudeProviderRequest,
     ) -> ClaudeServiceResult<bool> {
+        self.update_provider_with_warnings(id, request).await?;
+        Ok(true)
+    }
+
+    /// 更新Claude供应商，返回更新后的记录和发生变化的字段，
+    /// 同时返回不阻止更新的提示（如URL与已启用的供应商重复）
+    pub async fn update_provider_with_warnings(
+        &self,
+        id: i64,
+        mut request: UpdateClaudeProviderRequest,
+    ) -> ClaudeServiceResult<ServiceOutcome<UpdateResult<ClaudeProvider>>> {
         info!(
             id = %id,
             "更新Claude供应商业务逻辑开始"
@@ -125,16 +177,18 @@ impl ClaudeProviderService {
 
         // 验证请求
         self.validate_update_request(&request)?;
+        if let Some(url) = request.url.as_mut() {
+            *url = normalize_url(url);
+        }
 
-        // 检查供应商是否存在
-        let existing = self.repository.find_by_id::<ClaudeProvider>(id).await?;
-        if existing.is_none() {
+        // 检查供应商是否存在，解密后的记录用于比较Token是否变化
+        let Some(before) = self.repository.find_by_id_decrypted(id).await? else {
             warn!(
                 id = %id,
                 "尝试更新不存在的供应商"
             );
             return Err(ClaudeServiceError::ProviderNotFound(id));
-        }
+        };
 
         // 如果更新名称，检查唯一性
         if let Some(ref new_name) = request.name {
@@ -150,26 +204,65 @@ impl ClaudeProviderService {
             }
         }
 
+        let warnings = match request.url {
+            Some(ref url) => self.duplicate_url_warnings(url, Some(id)).await?,
+            None => Vec::new(),
+        };
+
         // 执行更新
         let updated = self.repository.update_claude_provider(id, &request).await?;
+        let after = match self.repository.find_by_id_decrypted(id).await? {
+            Some(after) if updated => after,
+            _ => {
+                warn!(
+                    id = %id,
+                    "Claude供应商更新未影响任何记录"
+                );
+                return Err(ClaudeServiceError::ProviderNotFound(id));
+            }
+        };
 
-        if updated {
-            info!(
-                id = %id,
-                "Claude供应商更新成功"
-            );
-        } else {
-            warn!(
-                id = %id,
-                "Claude供应商更新未影响任何记录"
-            );
-        }
+        let result = UpdateResult::diff(&before, after);
+        info!(
+            id = %id,
+            changed_fields = ?result.changed_fields,
+            "Claude供应商更新成功"
+        );
 
-        Ok(updated)
+        Ok(ServiceOutcome::with_warnings(result, warnings))
+    }
+
+    /// 检查是否有其他已启用的供应商使用相同的URL（规范化后比较）
+    async fn duplicate_url_warnings(
+        &self,
+        url: &str,
+        exclude_id: Option<i64>,
+    ) -> ClaudeServiceResult<Vec<Warning>> {
+        Ok(self
+            .repository
+            .find_by_url(url)
+            .await?
+            .into_iter()
+            .filter(|provider| provider.enabled == 1 && Some(provider.id) != exclude_id)
+            .map(|provider| {
+                warn!(
+                    url = %url,
+                    existing = %provider.name,
+                    "供应商URL与已启用的供应商重复"
+                );
+                Warning::new(
+                    WARNING_DUPLICATE_URL,
+                    format!("URL与已启用的供应商 \"{}\" 相同", provider.name),
+                )
+                .with_field("url")
+            })
+            .collect())
     }
 
     /// 删除Claude供应商
-    pub async fn delete_provider(&self, id: i64) -> ClaudeServiceResult<bool> {
+    ///
+    /// 目标是唯一启用的供应商时需要 `force` 为 `true`，避免删除后没有可用的供应商
+    pub async fn delete_provider(&self, id: i64, force: bool) -> ClaudeServiceResult<bool> {
         info!(
             id = %id,
             "删除Claude供应商业务逻辑开始"
@@ -178,13 +271,22 @@ impl ClaudeProviderService {
         Validator::validate_id(id, "id")?;
 
         // 检查供应商是否存在
-        let existing = self.repository.find_by_id::<ClaudeProvider>(id).await?;
-        if existing.is_none() {
+        let Some(existing) = self.repository.find_by_id::<ClaudeProvider>(id).await? else {
             warn!(
                 id = %id,
                 "尝试删除不存在的供应商"
             );
             return Err(ClaudeServiceError::ProviderNotFound(id));
+        };
+
+        if existing.enabled == 1 && !force && self.repository.count_by_status(true).await? <= 1 {
+            warn!(
+                id = %id,
+                "拒绝删除唯一启用的供应商"
+            );
+            return Err(ClaudeServiceError::BusinessRule(
+                "这是唯一启用的供应商，请先启用其他供应商，或使用 force=true 强制删除".to_string(),
+            ));
         }
 
         // 删除供应商
@@ -225,6 +327,7 @@ impl ClaudeProviderService {
         &self,
         search_term: &str,
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> ClaudeServiceResult<Vec<ClaudeProvider>> {
         let trimmed_term = search_term.trim();
 
@@ -238,22 +341,19 @@ impl ClaudeProviderService {
         Validator::validate_search_term(trimmed_term)?;
 
         // 避免不必要的字符串分配，直接传递引用
-        let providers = self.repository.search_claude_providers(trimmed_term, limit).await?;
+        let providers =
+            self.repository.search_claude_providers(trimmed_term, limit, order_by).await?;
         Ok(providers)
     }
 
-    /// 获取活跃的Claude供应商（优化版本，使用索引查询）
-    pub async fn list_active_providers(&self) -> ClaudeServiceResult<Vec<ClaudeProvider>> {
+    /// 获取活跃的Claude供应商，`order_by` 为空时按ID倒序
+    pub async fn list_active_providers(
+        &self,
+        order_by: Option<OrderBy>,
+    ) -> ClaudeServiceResult<Vec<ClaudeProvider>> {
         debug!("获取活跃的Claude供应商列表");
 
-        // 直接使用优化的索引查询，避免多次数据库访问
-        let query = "SELECT * FROM claude_providers WHERE enabled = 1 ORDER BY id DESC";
-
-        let providers = sqlx::query_as::<_, ClaudeProvider>(query)
-            .fetch_all(&self.repository.pool)
-            .await
-            .map_err(|e| ClaudeServiceError::Repository(e.into()))?;
-
+        let providers = self.repository.list_active_providers(order_by).await?;
         Ok(providers)
     }
 
@@ -261,7 +361,7 @@ impl ClaudeProviderService {
     pub async fn get_current_provider(&self) -> ClaudeServiceResult<Option<ClaudeProvider>> {
         debug!("获取当前启用的Claude供应商");
 
-        let active_providers = self.list_active_providers().await?;
+        let active_providers = self.list_active_providers(None).await?;
 
         // 根据业务规则，应该只有一个启用的供应商
         match active_providers.len() {
@@ -286,6 +386,14 @@ impl ClaudeProviderService {
 
     /// 启用供应商（同时禁用其他供应商）
     pub async fn enable_provider(&self, id: i64) -> ClaudeServiceResult<bool> {
+        Ok(self.enable_provider_with_warnings(id).await?.into_value())
+    }
+
+    /// 启用供应商，同时返回副作用提示（如被禁用的其他供应商数量）
+    pub async fn enable_provider_with_warnings(
+        &self,
+        id: i64,
+    ) -> ClaudeServiceResult<ServiceOutcome<bool>> {
         info!(
             id = %id,
             "启用Claude供应商"
@@ -300,7 +408,12 @@ impl ClaudeProviderService {
         }
 
         // 禁用所有其他供应商
-        self.disable_all_providers().await?;
+        let disabled_others = self
+            .disable_all_providers()
+            .await?
+            .into_iter()
+            .filter(|&other| other != id)
+            .count();
 
         // 启用指定供应商
         let update_request = UpdateClaudeProviderRequest {
@@ -314,6 +427,10 @@ impl ClaudeProviderService {
             opus_model: None,
             sonnet_model: None,
             haiku_model: None,
+            models: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+            model_auto_update: None,
         };
 
         let enabled = self.repository.update_claude_provider(id, &update_request).await?;
@@ -325,7 +442,15 @@ impl ClaudeProviderService {
             );
         }
 
-        Ok(enabled)
+        let mut warnings = Vec::new();
+        if disabled_others > 0 {
+            warnings.push(Warning::new(
+                WARNING_OTHERS_DISABLED,
+                format!("已禁用其他 {} 个Claude供应商", disabled_others),
+            ));
+        }
+
+        Ok(ServiceOutcome::with_warnings(enabled, warnings))
     }
 
     /// 禁用供应商
@@ -354,6 +479,10 @@ impl ClaudeProviderService {
             opus_model: None,
             sonnet_model: None,
             haiku_model: None,
+            models: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+            model_auto_update: None,
         };
 
         let disabled = self.repository.update_claude_provider(id, &update_request).await?;
@@ -368,8 +497,11 @@ impl ClaudeProviderService {
         Ok(disabled)
     }
 
-    /// 测试供应商连接
-    pub async fn test_provider_connection(&self, id: i64) -> ClaudeServiceResult<bool> {
+    /// 测试供应商连接，返回可达性、认证结果和状态码等详细信息
+    pub async fn test_provider_connection(
+        &self,
+        id: i64,
+    ) -> ClaudeServiceResult<ConnectionTestResult> {
         debug!(
             id = %id,
             "测试Claude供应商连接"
@@ -378,24 +510,133 @@ impl ClaudeProviderService {
         Validator::validate_id(id, "id")?;
 
         // 检查供应商是否存在
-        let _provider = self
+        let provider = self
             .repository
-            .find_by_id::<ClaudeProvider>(id)
+            .find_by_id_decrypted(id)
             .await?
             .ok_or(ClaudeServiceError::ProviderNotFound(id))?;
 
         // 执行连接测试
-        let result = self.repository.test_connection(id).await?;
+        let timeout = connection_test::timeout_from_millis(provider.timeout);
+        let result = connection_test::probe(
+            &self.http_client,
+            &provider.url,
+            &provider.token,
+            &provider.custom_headers,
+            &provider.accepted_status_codes,
+            timeout,
+        )
+        .await
+        .map_err(|e| match e {
+            ConnectionTestError::Timeout { timeout_ms } => {
+                warn!(id = %id, timeout_ms = %timeout_ms, "Claude供应商连接测试超时");
+                ClaudeServiceError::UpstreamTimeout { id, timeout_ms }
+            }
+            other => ClaudeServiceError::ConnectionTest(other.to_string()),
+        })?;
 
         info!(
             id = %id,
-            success = %result,
+            success = %result.success,
+            reachable = %result.reachable,
+            auth_ok = %result.auth_ok,
             "Claude供应商连接测试完成"
         );
 
         Ok(result)
     }
 
+    /// 使用未保存的URL和Token测试连接，不加密、不写入数据库
+    ///
+    /// 上游超时作为失败结果返回，而不是错误
+    pub async fn test_credentials(
+        &self,
+        request: &TestProviderCredentialsRequest,
+    ) -> ClaudeServiceResult<ConnectionTestResult> {
+        debug!(url = %redact_url(&request.url), "测试未保存的Claude供应商凭据");
+
+        Validator::validate_non_empty(&request.url, "供应商URL")?;
+        Validator::validate_url(&request.url)?;
+        Validator::validate_max_length(&request.url, "供应商URL", MAX_URL_LENGTH)?;
+        Validator::validate_secret_max_length(&request.token, "供应商Token", MAX_TOKEN_LENGTH)?;
+
+        if request.token.trim().is_empty() {
+            return Err(ClaudeServiceError::Validation(
+                "供应商Token不能为空".to_string(),
+            ));
+        }
+
+        if let Some(timeout) = request.timeout {
+            TimeoutMs::new(timeout).map_err(ClaudeServiceError::Validation)?;
+        }
+
+        let timeout = connection_test::timeout_from_millis(request.timeout);
+        match connection_test::probe(
+            &self.http_client,
+            &request.url,
+            &request.token,
+            &HashMap::new(),
+            &[],
+            timeout,
+        )
+        .await
+        {
+            Ok(result) => Ok(result),
+            Err(e @ ConnectionTestError::Timeout { timeout_ms }) => {
+                Ok(ConnectionTestResult::failure(timeout_ms, e.to_string()))
+            }
+            Err(e) => Err(ClaudeServiceError::ConnectionTest(e.to_string())),
+        }
+    }
+
+    /// 测试所有供应商的连接，结果按完成顺序逐个返回
+    ///
+    /// 同时最多测试 `concurrency` 个供应商；单个供应商超时或请求失败时返回失败结果，不影响其他供应商
+    pub async fn test_all_providers(
+        &self,
+        concurrency: usize,
+    ) -> ClaudeServiceResult<impl Stream<Item = ProviderTestOutcome> + Send + 'static> {
+        let providers = self.repository.list_claude_providers_decrypted().await?;
+        info!(
+            count = %providers.len(),
+            concurrency = %concurrency,
+            "批量测试Claude供应商连接"
+        );
+
+        let http_client = self.http_client.clone();
+        Ok(stream::iter(providers)
+            .map(move |provider| {
+                let http_client = http_client.clone();
+                async move {
+                    let timeout = connection_test::timeout_from_millis(provider.timeout);
+                    let result = match connection_test::probe(
+                        &http_client,
+                        &provider.url,
+                        &provider.token,
+                        &provider.custom_headers,
+                        &provider.accepted_status_codes,
+                        timeout,
+                    )
+                    .await
+                    {
+                        Ok(result) => result,
+                        Err(e @ ConnectionTestError::Timeout { timeout_ms }) => {
+                            ConnectionTestResult::failure(timeout_ms, e.to_string())
+                        }
+                        Err(e) => ConnectionTestResult::failure(0, e.to_string()),
+                    };
+
+                    debug!(
+                        id = %provider.id,
+                        success = %result.success,
+                        "Claude供应商连接测试完成"
+                    );
+                    ProviderTestOutcome { id: provider.id, name: provider.name, result }
+                }
+            })
+            .buffer_unordered(concurrency.max(1)))
+    }
+
     /// 获取供应商统计信息
     pub async fn get_provider_stats(&self) -> ClaudeServiceResult<serde_json::Value> {
         debug!("获取Claude供应商统计信息");
@@ -425,21 +666,75 @@ impl ClaudeProviderService {
         Ok(stats)
     }
 
+    /// 导出脱敏后的供应商列表
+    ///
+    /// token替换为占位符、URL中的凭据被移除，可直接附加到问题反馈中
+    pub async fn export_redacted(&self) -> ClaudeServiceResult<serde_json::Value> {
+        debug!("导出脱敏的Claude供应商列表");
+
+        let providers = self.repository.list_all::<ClaudeProvider>().await?;
+        let secrets = self.known_secrets().await?;
+
+        let mut exported = serde_json::Value::Array(
+            providers
+                .iter()
+                .map(|provider| {
+                    serde_json::json!({
+                        "id": provider.id,
+                        "name": provider.name,
+                        "url": redact_url(&provider.url),
+                        "token": REDACTED,
+                        "type": provider.r#type,
+                        "enabled": provider.enabled,
+                        "timeout": provider.timeout,
+                        "auto_update": provider.auto_update,
+                        "opus_model": provider.opus_model,
+                        "sonnet_model": provider.sonnet_model,
+                        "haiku_model": provider.haiku_model,
+                        "models": provider.effective_models(),
+                        "created_at": provider.created_at,
+                        "updated_at": provider.updated_at,
+                    })
+                })
+                .collect(),
+        );
+        scrub_secrets(&mut exported, &secrets);
+
+        Ok(exported)
+    }
+
+    /// 获取所有供应商的token（密文及可解密的明文）和自定义请求头的值，用于输出前的脱敏检查
+    pub async fn known_secrets(&self) -> ClaudeServiceResult<Vec<String>> {
+        let providers = self.repository.list_all::<ClaudeProvider>().await?;
+
+        let mut secrets = Vec::with_capacity(providers.len() * 2);
+        for provider in providers {
+            if let Ok(token) = self.repository.crypto_service().decrypt(&provider.token) {
+                secrets.push(token);
+            }
+            secrets.push(provider.token);
+            secrets.extend(provider.custom_headers.into_values());
+        }
+
+        Ok(secrets)
+    }
+
     // 私有辅助方法
 
     /// 根据名称查找供应商
     async fn find_by_name(&self, name: &str) -> ClaudeServiceResult<Option<ClaudeProvider>> {
-        let search_results = self.repository.search_claude_providers(name, Some(1)).await?;
+        let search_results = self.repository.search_claude_providers(name, Some(1), None).await?;
         Ok(search_results.into_iter().next())
     }
 
-    /// 禁用所有供应商
-    async fn disable_all_providers(&self) -> ClaudeServiceResult<()> {
+    /// 禁用所有供应商，返回被禁用的供应商ID
+    async fn disable_all_providers(&self) -> ClaudeServiceResult<Vec<i64>> {
         debug!("禁用所有Claude供应商");
 
         // 获取所有启用的供应商
-        let active_providers = self.list_active_providers().await?;
+        let active_providers = self.list_active_providers(None).await?;
 
+        let mut disabled = Vec::with_capacity(active_providers.len());
         for provider in active_providers {
             let update_request = UpdateClaudeProviderRequest {
                 name: None,
@@ -452,13 +747,18 @@ impl ClaudeProviderService {
                 opus_model: None,
                 sonnet_model: None,
                 haiku_model: None,
+                models: None,
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
             };
 
-            let _ = self.repository.update_claude_provider(provider.id, &update_request).await?;
+            self.repository.update_claude_provider(provider.id, &update_request).await?;
+            disabled.push(provider.id);
         }
 
         info!("已禁用所有Claude供应商");
-        Ok(())
+        Ok(disabled)
     }
 
     /// 验证创建请求
@@ -469,6 +769,8 @@ impl ClaudeProviderService {
         // 使用统一验证器验证基本字段
         Validator::validate_provider_name(&request.name)?;
         Validator::validate_url(&request.url)?;
+        Validator::validate_max_length(&request.url, "供应商URL", MAX_URL_LENGTH)?;
+        Validator::validate_secret_max_length(&request.token, "供应商Token", MAX_TOKEN_LENGTH)?;
 
         if request.token.trim().is_empty() {
             return Err(ClaudeServiceError::Validation(
@@ -477,11 +779,7 @@ impl ClaudeProviderService {
         }
 
         if let Some(timeout) = request.timeout {
-            if timeout <= 0 {
-                return Err(ClaudeServiceError::Validation(
-                    "超时时间必须大于0".to_string(),
-                ));
-            }
+            TimeoutMs::new(timeout).map_err(ClaudeServiceError::Validation)?;
         }
 
         if let Some(ref r#type) = request.r#type {
@@ -492,6 +790,23 @@ impl ClaudeProviderService {
             }
         }
 
+        validate_model_names(
+            request
+                .opus_model
+                .iter()
+                .chain(&request.sonnet_model)
+                .chain(&request.haiku_model)
+                .chain(request.models.iter().flat_map(|models| models.values())),
+        )?;
+
+        if let Some(ref headers) = request.custom_headers {
+            Validator::validate_custom_headers(headers)?;
+        }
+
+        if let Some(ref codes) = request.accepted_status_codes {
+            Validator::validate_status_codes(codes)?;
+        }
+
         Ok(())
     }
 
@@ -506,6 +821,7 @@ impl ClaudeProviderService {
                     "供应商名称不能为空".to_string(),
                 ));
             }
+            Validator::validate_max_length(name, "供应商名称", MAX_NAME_LENGTH)?;
         }
 
         if let Some(ref url) = request.url {
@@ -520,6 +836,7 @@ impl ClaudeProviderService {
                     "供应商URL必须以http://或https://开头".to_string(),
                 ));
             }
+            Validator::validate_max_length(url, "供应商URL", MAX_URL_LENGTH)?;
         }
 
         if let Some(ref token) = request.token {
@@ -528,14 +845,11 @@ impl ClaudeProviderService {
                     "供应商Token不能为空".to_string(),
                 ));
             }
+            Validator::validate_secret_max_length(token, "供应商Token", MAX_TOKEN_LENGTH)?;
         }
 
         if let Some(timeout) = request.timeout {
-            if timeout <= 0 {
-                return Err(ClaudeServiceError::Validation(
-                    "超时时间必须大于0".to_string(),
-                ));
-            }
+            TimeoutMs::new(timeout).map_err(ClaudeServiceError::Validation)?;
         }
 
         if let Some(ref r#type) = request.r#type {
@@ -554,10 +868,37 @@ impl ClaudeProviderService {
             }
         }
 
+        validate_model_names(
+            request
+                .opus_model
+                .iter()
+                .chain(&request.sonnet_model)
+                .chain(&request.haiku_model)
+                .chain(request.models.iter().flat_map(|models| models.values())),
+        )?;
+
+        if let Some(ref headers) = request.custom_headers {
+            Validator::validate_custom_headers(headers)?;
+        }
+
+        if let Some(ref codes) = request.accepted_status_codes {
+            Validator::validate_status_codes(codes)?;
+        }
+
         Ok(())
     }
 }
 
+/// 验证模型名称长度
+fn validate_model_names<'a>(
+    models: impl IntoIterator<Item = &'a String>,
+) -> ClaudeServiceResult<()> {
+    for model in models {
+        Validator::validate_max_length(model, "模型名称", MAX_MODEL_NAME_LENGTH)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -576,6 +917,7 @@ mod tests {
             connect_timeout: std::time::Duration::from_secs(10),
             idle_timeout: std::time::Duration::from_secs(60),
             max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = Arc::new(DatabaseManager::new(config).await.unwrap());
@@ -598,6 +940,10 @@ mod tests {
             opus_model: Some("claude-3-opus-20240229".to_string()),
             sonnet_model: Some("claude-3-sonnet-20241022".to_string()),
             haiku_model: Some("claude-3-haiku-20240307".to_string()),
+            models: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+            model_auto_update: None,
         };
 
         let id = service.create_provider(create_request).await.unwrap();
@@ -627,13 +973,18 @@ mod tests {
             opus_model: None,
             sonnet_model: None,
             haiku_model: None,
+            models: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+            model_auto_update: None,
         };
 
         let result = service.create_provider(create_request).await;
         assert!(result.is_err());
+        // 字段验证错误保留字段名，API层据此返回结构化的错误详情
         assert!(matches!(
             result.unwrap_err(),
-            ClaudeServiceError::Validation(_)
+            ClaudeServiceError::InvalidField(error) if error.field.is_some()
         ));
     }
 
@@ -652,6 +1003,10 @@ mod tests {
             opus_model: None,
             sonnet_model: None,
             haiku_model: None,
+            models: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+            model_auto_update: None,
         };
 
         let id = service.create_provider(create_request).await.unwrap();
@@ -670,4 +1025,134 @@ mod tests {
         let provider = service.get_provider(id).await.unwrap().unwrap();
         assert_eq!(provider.enabled, 1);
     }
+
+    #[tokio::test]
+    async fn test_duplicate_url_warning() {
+        let temp_dir = tempdir().unwrap();
+        let config = DatabaseConfig {
+            url: format!(
+                "sqlite:{}",
+                temp_dir.path().join("test_duplicate_url.db").display()
+            ),
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: std::time::Duration::from_secs(10),
+            idle_timeout: std::time::Duration::from_secs(60),
+            max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
+        };
+        let db_manager = Arc::new(DatabaseManager::new(config).await.unwrap());
+        let crypto_service =
+            Arc::new(CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap());
+        let service = ClaudeProviderService::new(db_manager, crypto_service);
+
+        let request = |name: &str, url: &str| CreateClaudeProviderRequest {
+            name: name.to_string(),
+            url: url.to_string(),
+            token: "sk-test-api-key".to_string(),
+            timeout: None,
+            auto_update: None,
+            r#type: None,
+            opus_model: None,
+            sonnet_model: None,
+            haiku_model: None,
+            models: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+            model_auto_update: None,
+        };
+
+        let outcome = service
+            .create_provider_with_warnings(request("重复URL-1", "https://api.example.com"))
+            .await
+            .unwrap();
+        assert!(outcome.warnings.is_empty());
+
+        // 末尾斜杠和主机大小写不同也视为同一URL，仍然创建成功
+        let ServiceOutcome { value: id, warnings } = service
+            .create_provider_with_warnings(request("重复URL-2", "https://API.Example.com/"))
+            .await
+            .unwrap();
+        assert!(id > 0);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, WARNING_DUPLICATE_URL);
+        assert_eq!(warnings[0].field.as_deref(), Some("url"));
+        assert!(warnings[0].message.contains("重复URL-1"));
+
+        // 写入数据库的是规范化后的URL
+        let provider = service.get_provider(id).await.unwrap().unwrap();
+        assert_eq!(provider.url, "https://api.example.com");
+
+        service
+            .update_provider(
+                id,
+                UpdateClaudeProviderRequest {
+                    name: None,
+                    url: Some("HTTPS://Proxy.Example.com:443/v1/".to_string()),
+                    token: None,
+                    timeout: None,
+                    auto_update: None,
+                    r#type: None,
+                    enabled: None,
+                    opus_model: None,
+                    sonnet_model: None,
+                    haiku_model: None,
+                    models: None,
+                    custom_headers: None,
+                    accepted_status_codes: None,
+                    model_auto_update: None,
+                },
+            )
+            .await
+            .unwrap();
+        let provider = service.get_provider(id).await.unwrap().unwrap();
+        assert_eq!(provider.url, "https://proxy.example.com/v1");
+    }
+
+    #[tokio::test]
+    async fn test_provider_token_round_trips_as_plaintext_without_crypto() {
+        let temp_dir = tempdir().unwrap();
+        let config = DatabaseConfig {
+            url: format!(
+                "sqlite:{}",
+                temp_dir.path().join("test_plaintext.db").display()
+            ),
+            ..Default::default()
+        };
+        let db_manager = Arc::new(DatabaseManager::new(config).await.unwrap());
+        db_manager.ensure_initialized().await.unwrap();
+        let crypto_service = Arc::new(CryptoService::disabled());
+        assert!(!crypto_service.is_enabled());
+        let service = ClaudeProviderService::new(db_manager.clone(), crypto_service);
+
+        let id = service
+            .create_provider(CreateClaudeProviderRequest {
+                name: "明文".to_string(),
+                url: "https://api.anthropic.com".to_string(),
+                token: "sk-ant-plaintext".to_string(),
+                timeout: None,
+                auto_update: None,
+                r#type: None,
+                opus_model: None,
+                sonnet_model: None,
+                haiku_model: None,
+                models: None,
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
+            })
+            .await
+            .unwrap();
+
+        // 数据库中保存的就是明文
+        let stored: String = sqlx::query_scalar("SELECT token FROM claude_providers WHERE id = ?")
+            .bind(id)
+            .fetch_one(db_manager.pool())
+            .await
+            .unwrap();
+        assert_eq!(stored, "sk-ant-plaintext");
+
+        let provider = service.get_provider(id).await.unwrap().unwrap();
+        assert_eq!(provider.token, "sk-ant-plaintext");
+    }
 }
@@ -5,10 +5,15 @@
 use crate::crypto::CryptoService;
 use crate::database::DatabaseManager;
 use crate::models::{
-    CodexProvider, CreateCodexProviderRequest, PagedResult, PaginationParams,
-    UpdateCodexProviderRequest,
+    CodexProvider, CreateCodexProviderRequest, OrderBy, PagedResult, PaginationParams,
+    UpdateCodexProviderRequest, UpdateResult, MAX_NAME_LENGTH, MAX_TOKEN_LENGTH, MAX_URL_LENGTH,
 };
 use crate::repositories::{BaseRepository, CodexProviderRepository};
+use crate::services::connection_test::{self, ConnectionTestError, ConnectionTestResult};
+use crate::services::http::HttpClient;
+use crate::services::outcome::{ServiceOutcome, Warning, WARNING_OTHERS_DISABLED};
+use crate::utils::redaction::{redact_url, scrub_secrets, REDACTED};
+use crate::utils::validation::normalize_url;
 use crate::{ValidationError, Validator};
 use std::sync::Arc;
 use tracing::{debug, error, info, warn};
@@ -19,6 +24,9 @@ pub enum CodexServiceError {
     #[error("验证失败: {0}")]
     Validation(String),
 
+    #[error("验证失败: {0}")]
+    InvalidField(ValidationError),
+
     #[error("业务规则冲突: {0}")]
     BusinessRule(String),
 
@@ -33,11 +41,17 @@ pub enum CodexServiceError {
 
     #[error("没有启用的供应商")]
     NoActiveProvider,
+
+    #[error("供应商 {id} 连接测试超时（{timeout_ms} 毫秒）")]
+    UpstreamTimeout { id: i64, timeout_ms: u64 },
+
+    #[error("连接测试失败: {0}")]
+    ConnectionTest(String),
 }
 
 impl From<ValidationError> for CodexServiceError {
     fn from(error: ValidationError) -> Self {
-        CodexServiceError::Validation(error.to_string())
+        CodexServiceError::InvalidField(error)
     }
 }
 
@@ -48,6 +62,8 @@ pub type CodexServiceResult<T> = Result<T, CodexServiceError>;
 #[derive(Clone)]
 pub struct CodexProviderService {
     repository: Arc<CodexProviderRepository>,
+    /// 连接测试使用的上游HTTP客户端
+    http_client: HttpClient,
 }
 
 impl CodexProviderService {
@@ -55,9 +71,16 @@ impl CodexProviderService {
     pub fn new(db_manager: Arc<DatabaseManager>, crypto_service: Arc<CryptoService>) -> Self {
         Self {
             repository: Arc::new(CodexProviderRepository::new(&db_manager, &crypto_service)),
+            http_client: HttpClient::default(),
         }
     }
 
+    /// 使用应用共享的HTTP客户端，与其他服务共用连接池
+    pub fn with_http_client(mut self, http_client: HttpClient) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
     /// 创建Codex供应商
     pub async fn create_provider(
         &self,
@@ -71,6 +94,14 @@ impl CodexProviderService {
 
         // 验证请求
         self.validate_create_request(request)?;
+        let request = &CreateCodexProviderRequest {
+            name: request.name.clone(),
+            url: normalize_url(&request.url),
+            token: request.token.clone(),
+            r#type: request.r#type.clone(),
+            custom_headers: request.custom_headers.clone(),
+            accepted_status_codes: request.accepted_status_codes.clone(),
+        };
 
         // 检查名称唯一性
         if let Some(existing) = self.find_by_name(&request.name).await? {
@@ -114,6 +145,16 @@ impl CodexProviderService {
         id: i64,
         request: UpdateCodexProviderRequest,
     ) -> CodexServiceResult<bool> {
+        self.update_provider_with_changes(id, request).await?;
+        Ok(true)
+    }
+
+    /// 更新Codex供应商，返回更新后的记录和发生变化的字段
+    pub async fn update_provider_with_changes(
+        &self,
+        id: i64,
+        mut request: UpdateCodexProviderRequest,
+    ) -> CodexServiceResult<UpdateResult<CodexProvider>> {
         info!(
             id = %id,
             "更新Codex供应商业务逻辑开始"
@@ -123,16 +164,18 @@ impl CodexProviderService {
 
         // 验证请求
         self.validate_update_request(&request)?;
+        if let Some(url) = request.url.as_mut() {
+            *url = normalize_url(url);
+        }
 
-        // 检查供应商是否存在
-        let existing = self.repository.find_by_id::<CodexProvider>(id).await?;
-        if existing.is_none() {
+        // 检查供应商是否存在，解密后的记录用于比较Token是否变化
+        let Some(before) = self.repository.find_by_id_decrypted(id).await? else {
             warn!(
                 id = %id,
                 "尝试更新不存在的供应商"
             );
             return Err(CodexServiceError::ProviderNotFound(id));
-        }
+        };
 
         // 如果更新名称，检查唯一性
         if let Some(ref new_name) = request.name {
@@ -150,24 +193,31 @@ impl CodexProviderService {
 
         // 执行更新
         let updated = self.repository.update_codex_provider(id, &request).await?;
+        let after = match self.repository.find_by_id_decrypted(id).await? {
+            Some(after) if updated => after,
+            _ => {
+                warn!(
+                    id = %id,
+                    "Codex供应商更新未影响任何记录"
+                );
+                return Err(CodexServiceError::ProviderNotFound(id));
+            }
+        };
 
-        if updated {
-            info!(
-                id = %id,
-                "Codex供应商更新成功"
-            );
-        } else {
-            warn!(
-                id = %id,
-                "Codex供应商更新未影响任何记录"
-            );
-        }
+        let result = UpdateResult::diff(&before, after);
+        info!(
+            id = %id,
+            changed_fields = ?result.changed_fields,
+            "Codex供应商更新成功"
+        );
 
-        Ok(updated)
+        Ok(result)
     }
 
     /// 删除Codex供应商
-    pub async fn delete_provider(&self, id: i64) -> CodexServiceResult<bool> {
+    ///
+    /// 目标是唯一启用的供应商时需要 `force` 为 `true`，避免删除后没有可用的供应商
+    pub async fn delete_provider(&self, id: i64, force: bool) -> CodexServiceResult<bool> {
         info!(
             id = %id,
             "删除Codex供应商业务逻辑开始"
@@ -176,13 +226,22 @@ impl CodexProviderService {
         Validator::validate_id(id, "id")?;
 
         // 检查供应商是否存在
-        let existing = self.repository.find_by_id::<CodexProvider>(id).await?;
-        if existing.is_none() {
+        let Some(existing) = self.repository.find_by_id::<CodexProvider>(id).await? else {
             warn!(
                 id = %id,
                 "尝试删除不存在的供应商"
             );
             return Err(CodexServiceError::ProviderNotFound(id));
+        };
+
+        if existing.enabled == 1 && !force && self.repository.count_by_status(true).await? <= 1 {
+            warn!(
+                id = %id,
+                "拒绝删除唯一启用的供应商"
+            );
+            return Err(CodexServiceError::BusinessRule(
+                "这是唯一启用的供应商，请先启用其他供应商，或使用 force=true 强制删除".to_string(),
+            ));
         }
 
         // 删除供应商
@@ -223,6 +282,7 @@ impl CodexProviderService {
         &self,
         search_term: &str,
         limit: Option<i64>,
+        order_by: Option<OrderBy>,
     ) -> CodexServiceResult<Vec<CodexProvider>> {
         debug!(
             search_term = %search_term,
@@ -233,15 +293,19 @@ impl CodexProviderService {
         // 使用统一验证器验证搜索词
         Validator::validate_search_term(search_term.trim())?;
 
-        let providers = self.repository.search_codex_providers(search_term, limit).await?;
+        let providers =
+            self.repository.search_codex_providers(search_term, limit, order_by).await?;
         Ok(providers)
     }
 
-    /// 获取活跃的Codex供应商
-    pub async fn list_active_providers(&self) -> CodexServiceResult<Vec<CodexProvider>> {
+    /// 获取活跃的Codex供应商，`order_by` 为空时按ID倒序
+    pub async fn list_active_providers(
+        &self,
+        order_by: Option<OrderBy>,
+    ) -> CodexServiceResult<Vec<CodexProvider>> {
         debug!("获取活跃的Codex供应商列表");
 
-        let providers = self.repository.list_active_providers().await?;
+        let providers = self.repository.list_active_providers(order_by).await?;
         Ok(providers)
     }
 
@@ -249,7 +313,7 @@ impl CodexProviderService {
     pub async fn get_current_provider(&self) -> CodexServiceResult<Option<CodexProvider>> {
         debug!("获取当前启用的Codex供应商");
 
-        let active_providers = self.list_active_providers().await?;
+        let active_providers = self.list_active_providers(None).await?;
 
         // 根据业务规则，应该只有一个启用的供应商
         match active_providers.len() {
@@ -274,6 +338,14 @@ impl CodexProviderService {
 
     /// 启用供应商（同时禁用其他供应商）
     pub async fn enable_provider(&self, id: i64) -> CodexServiceResult<bool> {
+        Ok(self.enable_provider_with_warnings(id).await?.into_value())
+    }
+
+    /// 启用供应商，同时返回副作用提示（如被禁用的其他供应商数量）
+    pub async fn enable_provider_with_warnings(
+        &self,
+        id: i64,
+    ) -> CodexServiceResult<ServiceOutcome<bool>> {
         info!(
             id = %id,
             "启用Codex供应商"
@@ -288,7 +360,12 @@ impl CodexProviderService {
         }
 
         // 禁用所有其他供应商
-        self.disable_all_providers().await?;
+        let disabled_others = self
+            .disable_all_providers()
+            .await?
+            .into_iter()
+            .filter(|&other| other != id)
+            .count();
 
         // 启用指定供应商
         let update_request = UpdateCodexProviderRequest {
@@ -297,6 +374,8 @@ impl CodexProviderService {
             token: None,
             r#type: None,
             enabled: Some(1),
+            custom_headers: None,
+            accepted_status_codes: None,
         };
 
         let enabled = self.repository.update_codex_provider(id, &update_request).await?;
@@ -308,7 +387,15 @@ impl CodexProviderService {
             );
         }
 
-        Ok(enabled)
+        let mut warnings = Vec::new();
+        if disabled_others > 0 {
+            warnings.push(Warning::new(
+                WARNING_OTHERS_DISABLED,
+                format!("已禁用其他 {} 个Codex供应商", disabled_others),
+            ));
+        }
+
+        Ok(ServiceOutcome::with_warnings(enabled, warnings))
     }
 
     /// 禁用供应商
@@ -332,6 +419,8 @@ impl CodexProviderService {
             token: None,
             r#type: None,
             enabled: Some(0),
+            custom_headers: None,
+            accepted_status_codes: None,
         };
 
         let disabled = self.repository.update_codex_provider(id, &update_request).await?;
@@ -346,8 +435,11 @@ impl CodexProviderService {
         Ok(disabled)
     }
 
-    /// 测试供应商连接
-    pub async fn test_provider_connection(&self, id: i64) -> CodexServiceResult<bool> {
+    /// 测试供应商连接，返回可达性、认证结果和状态码等详细信息
+    pub async fn test_provider_connection(
+        &self,
+        id: i64,
+    ) -> CodexServiceResult<ConnectionTestResult> {
         debug!(
             id = %id,
             "测试Codex供应商连接"
@@ -356,18 +448,37 @@ impl CodexProviderService {
         Validator::validate_id(id, "id")?;
 
         // 检查供应商是否存在
-        let _provider = self
+        let provider = self
             .repository
-            .find_by_id::<CodexProvider>(id)
+            .find_by_id_decrypted(id)
             .await?
             .ok_or(CodexServiceError::ProviderNotFound(id))?;
 
         // 执行连接测试
-        let result = self.repository.test_connection(id).await?;
+        let timeout =
+            std::time::Duration::from_millis(connection_test::DEFAULT_CONNECTION_TEST_TIMEOUT_MS);
+        let result = connection_test::probe(
+            &self.http_client,
+            &provider.url,
+            &provider.token,
+            &provider.custom_headers,
+            &provider.accepted_status_codes,
+            timeout,
+        )
+        .await
+        .map_err(|e| match e {
+            ConnectionTestError::Timeout { timeout_ms } => {
+                warn!(id = %id, timeout_ms = %timeout_ms, "Codex供应商连接测试超时");
+                CodexServiceError::UpstreamTimeout { id, timeout_ms }
+            }
+            other => CodexServiceError::ConnectionTest(other.to_string()),
+        })?;
 
         info!(
             id = %id,
-            success = %result,
+            success = %result.success,
+            reachable = %result.reachable,
+            auth_ok = %result.auth_ok,
             "Codex供应商连接测试完成"
         );
 
@@ -403,21 +514,69 @@ impl CodexProviderService {
         Ok(stats)
     }
 
+    /// 导出脱敏后的供应商列表
+    ///
+    /// token替换为占位符、URL中的凭据被移除，可直接附加到问题反馈中
+    pub async fn export_redacted(&self) -> CodexServiceResult<serde_json::Value> {
+        debug!("导出脱敏的Codex供应商列表");
+
+        let providers = self.repository.list_all::<CodexProvider>().await?;
+        let secrets = self.known_secrets().await?;
+
+        let mut exported = serde_json::Value::Array(
+            providers
+                .iter()
+                .map(|provider| {
+                    serde_json::json!({
+                        "id": provider.id,
+                        "name": provider.name,
+                        "url": redact_url(&provider.url),
+                        "token": REDACTED,
+                        "type": provider.r#type,
+                        "enabled": provider.enabled,
+                        "created_at": provider.created_at,
+                        "updated_at": provider.updated_at,
+                    })
+                })
+                .collect(),
+        );
+        scrub_secrets(&mut exported, &secrets);
+
+        Ok(exported)
+    }
+
+    /// 获取所有供应商的token（密文及可解密的明文）和自定义请求头的值，用于输出前的脱敏检查
+    pub async fn known_secrets(&self) -> CodexServiceResult<Vec<String>> {
+        let providers = self.repository.list_all::<CodexProvider>().await?;
+
+        let mut secrets = Vec::with_capacity(providers.len() * 2);
+        for provider in providers {
+            if let Ok(token) = self.repository.crypto_service().decrypt(&provider.token) {
+                secrets.push(token);
+            }
+            secrets.push(provider.token);
+            secrets.extend(provider.custom_headers.into_values());
+        }
+
+        Ok(secrets)
+    }
+
     // 私有辅助方法
 
     /// 根据名称查找供应商
     async fn find_by_name(&self, name: &str) -> CodexServiceResult<Option<CodexProvider>> {
-        let search_results = self.repository.search_codex_providers(name, Some(1)).await?;
+        let search_results = self.repository.search_codex_providers(name, Some(1), None).await?;
         Ok(search_results.into_iter().next())
     }
 
-    /// 禁用所有供应商
-    async fn disable_all_providers(&self) -> CodexServiceResult<()> {
+    /// 禁用所有供应商，返回被禁用的供应商ID
+    async fn disable_all_providers(&self) -> CodexServiceResult<Vec<i64>> {
         debug!("禁用所有Codex供应商");
 
         // 获取所有启用的供应商
-        let active_providers = self.list_active_providers().await?;
+        let active_providers = self.list_active_providers(None).await?;
 
+        let mut disabled = Vec::with_capacity(active_providers.len());
         for provider in active_providers {
             let update_request = UpdateCodexProviderRequest {
                 name: None,
@@ -425,13 +584,16 @@ impl CodexProviderService {
                 token: None,
                 r#type: None,
                 enabled: Some(0),
+                custom_headers: None,
+                accepted_status_codes: None,
             };
 
-            let _ = self.repository.update_codex_provider(provider.id, &update_request).await?;
+            self.repository.update_codex_provider(provider.id, &update_request).await?;
+            disabled.push(provider.id);
         }
 
         info!("已禁用所有Codex供应商");
-        Ok(())
+        Ok(disabled)
     }
 
     /// 验证创建请求
@@ -442,6 +604,8 @@ impl CodexProviderService {
         // 使用统一验证器验证基本字段
         Validator::validate_provider_name(&request.name)?;
         Validator::validate_url(&request.url)?;
+        Validator::validate_max_length(&request.url, "供应商URL", MAX_URL_LENGTH)?;
+        Validator::validate_secret_max_length(&request.token, "供应商Token", MAX_TOKEN_LENGTH)?;
 
         if request.token.trim().is_empty() {
             return Err(CodexServiceError::Validation(
@@ -449,6 +613,14 @@ impl CodexProviderService {
             ));
         }
 
+        if let Some(ref headers) = request.custom_headers {
+            Validator::validate_custom_headers(headers)?;
+        }
+
+        if let Some(ref codes) = request.accepted_status_codes {
+            Validator::validate_status_codes(codes)?;
+        }
+
         Ok(())
     }
 
@@ -463,6 +635,7 @@ impl CodexProviderService {
                     "供应商名称不能为空".to_string(),
                 ));
             }
+            Validator::validate_max_length(name, "供应商名称", MAX_NAME_LENGTH)?;
         }
 
         if let Some(ref url) = request.url {
@@ -477,6 +650,7 @@ impl CodexProviderService {
                     "供应商URL必须以http://或https://开头".to_string(),
                 ));
             }
+            Validator::validate_max_length(url, "供应商URL", MAX_URL_LENGTH)?;
         }
 
         if let Some(ref token) = request.token {
@@ -485,6 +659,7 @@ impl CodexProviderService {
                     "供应商Token不能为空".to_string(),
                 ));
             }
+            Validator::validate_secret_max_length(token, "供应商Token", MAX_TOKEN_LENGTH)?;
         }
 
         if let Some(enabled) = request.enabled {
@@ -495,6 +670,14 @@ impl CodexProviderService {
             }
         }
 
+        if let Some(ref headers) = request.custom_headers {
+            Validator::validate_custom_headers(headers)?;
+        }
+
+        if let Some(ref codes) = request.accepted_status_codes {
+            Validator::validate_status_codes(codes)?;
+        }
+
         Ok(())
     }
 }
@@ -517,6 +700,7 @@ mod tests {
             connect_timeout: std::time::Duration::from_secs(10),
             idle_timeout: std::time::Duration::from_secs(60),
             max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = Arc::new(DatabaseManager::new(config).await.unwrap());
@@ -534,6 +718,8 @@ mod tests {
             url: "https://api.openai.com".to_string(),
             token: "sk-test-api-key".to_string(),
             r#type: Some("gpt-4".to_string()),
+            custom_headers: None,
+            accepted_status_codes: None,
         };
 
         let id = service.create_provider(&create_request).await.unwrap();
@@ -558,13 +744,16 @@ mod tests {
             url: "https://api.openai.com".to_string(),
             token: "sk-test-api-key".to_string(),
             r#type: None,
+            custom_headers: None,
+            accepted_status_codes: None,
         };
 
         let result = service.create_provider(&create_request).await;
         assert!(result.is_err());
+        // 字段验证错误保留字段名，API层据此返回结构化的错误详情
         assert!(matches!(
             result.unwrap_err(),
-            CodexServiceError::Validation(_)
+            CodexServiceError::InvalidField(error) if error.field.is_some()
         ));
     }
 
@@ -578,6 +767,8 @@ mod tests {
             url: "https://api.openai.com".to_string(),
             token: "sk-test-api-key".to_string(),
             r#type: None,
+            custom_headers: None,
+            accepted_status_codes: None,
         };
 
         let id = service.create_provider(&create_request).await.unwrap();
@@ -0,0 +1,920 @@
+// 通用配置业务服务
+//
+// 提供通用配置的透明加密、历史版本查询和回滚
+// 在 KeyRing 中设置了独立密钥的类别和敏感键的值加密保存，其他值以明文保存；
+// 加密时按类别通过 KeyRing 选择密钥，读写使用同一映射
+// 生效配置按 环境变量 > 数据库 > 内置默认值 的优先级解析
+
+use crate::crypto::{CryptoService, KeyRing};
+use crate::database::DatabaseManager;
+use crate::models::{
+    BatchUpdateResult, CommonConfig, CommonConfigHistory, CreateCommonConfigRequest,
+    UpdateCommonConfigRequest,
+};
+use crate::repositories::base_repository::{EncryptedField, RepositoryError};
+use crate::repositories::common_config_repository::BatchConfigUpdate;
+use crate::repositories::{BaseRepository, CommonConfigRepository};
+use crate::utils::redaction::{is_sensitive_key, SecretMatcher, SecretPatternError, REDACTED};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// 未指定类别时使用的默认类别，与Repository保持一致
+pub(crate) const DEFAULT_CATEGORY: &str = "default";
+
+/// 覆盖配置的环境变量前缀，如 `theme` 对应 `AI_MANAGER_CONFIG_THEME`
+pub const CONFIG_ENV_PREFIX: &str = "AI_MANAGER_CONFIG_";
+
+/// 额外的敏感信息匹配规则（正则表达式字符串的JSON数组），内置规则始终生效
+pub const SECRET_PATTERNS_CONFIG_KEY: &str = "redaction.patterns";
+
+/// 内置的配置默认值
+#[derive(Debug, Clone, Copy)]
+pub struct ConfigDefault {
+    pub key: &'static str,
+    pub value: &'static str,
+    pub data_type: &'static str,
+}
+
+/// 所有内置默认配置
+pub const CONFIG_DEFAULTS: &[ConfigDefault] = &[
+    ConfigDefault { key: "theme", value: "light", data_type: "string" },
+    ConfigDefault { key: "language", value: "zh-CN", data_type: "string" },
+    ConfigDefault { key: "auto_backup", value: "true", data_type: "boolean" },
+    ConfigDefault { key: "request_timeout", value: "30000", data_type: "integer" },
+    ConfigDefault {
+        key: SECRET_PATTERNS_CONFIG_KEY,
+        value: "[]",
+        data_type: "json",
+    },
+];
+
+/// 生效配置值的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    /// 环境变量覆盖
+    Env,
+    /// 数据库中的配置
+    Db,
+    /// 内置默认值
+    Default,
+}
+
+/// 解析后实际生效的配置项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveConfig {
+    pub key: String,
+    /// 生效值，敏感键的值已隐藏
+    pub value: String,
+    pub source: ConfigSource,
+    pub data_type: String,
+}
+
+/// 配置键对应的环境变量名
+pub fn config_env_var(key: &str) -> String {
+    let key: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}{}", CONFIG_ENV_PREFIX, key)
+}
+
+/// 根据值推断数据类型
+fn infer_data_type(value: &str) -> &'static str {
+    let value = value.trim();
+    if value == "true" || value == "false" {
+        "boolean"
+    } else if value.parse::<i64>().is_ok() {
+        "integer"
+    } else if value.parse::<f64>().is_ok() {
+        "number"
+    } else if value.starts_with(['{', '['])
+        && serde_json::from_str::<serde_json::Value>(value).is_ok()
+    {
+        "json"
+    } else {
+        "string"
+    }
+}
+
+/// 配置的数据类型：内置默认配置使用声明的类型，其他配置根据值推断
+pub fn config_data_type(key: &str, value: &str) -> &'static str {
+    CONFIG_DEFAULTS
+        .iter()
+        .find(|default| default.key == key)
+        .map(|default| default.data_type)
+        .unwrap_or_else(|| infer_data_type(value))
+}
+
+/// 将配置值转换为与数据类型对应的原生JSON值
+///
+/// 值与数据类型不符时保留字符串形式并记录警告
+pub fn typed_config_value(key: &str, value: &str) -> serde_json::Value {
+    use serde_json::Value;
+
+    let data_type = config_data_type(key, value);
+    let trimmed = value.trim();
+    let typed = match data_type {
+        "boolean" => trimmed.parse::<bool>().ok().map(Value::Bool),
+        "integer" => trimmed.parse::<i64>().ok().map(Value::from),
+        "number" => trimmed
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number),
+        "json" => serde_json::from_str(trimmed).ok(),
+        _ => return Value::String(value.to_string()),
+    };
+
+    typed.unwrap_or_else(|| {
+        warn!(key = %key, data_type = %data_type, "配置值与数据类型不符，按字符串返回");
+        Value::String(value.to_string())
+    })
+}
+
+/// 通用配置业务错误
+#[derive(Debug, thiserror::Error)]
+pub enum CommonConfigServiceError {
+    #[error("数据访问错误: {0}")]
+    Repository(#[from] RepositoryError),
+
+    #[error("配置不存在: {0}")]
+    ConfigNotFound(String),
+
+    #[error("配置 {key} 的历史版本 {version} 不存在")]
+    VersionNotFound { key: String, version: i64 },
+
+    #[error("配置加载失败: {0}")]
+    InvalidSecretPatterns(#[from] SecretPatternError),
+}
+
+/// 通用配置服务结果类型
+pub type CommonConfigServiceResult<T> = Result<T, CommonConfigServiceError>;
+
+/// 写入前检查敏感信息匹配规则，无效的规则不会保存
+pub fn validate_secret_patterns(key: &str, value: &str) -> CommonConfigServiceResult<()> {
+    if key == SECRET_PATTERNS_CONFIG_KEY {
+        SecretMatcher::from_config_value(value)?;
+    }
+    Ok(())
+}
+
+/// 通用配置业务服务
+#[derive(Clone)]
+pub struct CommonConfigService {
+    repository: Arc<CommonConfigRepository>,
+    key_ring: Arc<KeyRing>,
+}
+
+impl CommonConfigService {
+    /// 创建新的通用配置服务实例，所有类别使用同一密钥
+    pub fn new(db_manager: Arc<DatabaseManager>, crypto_service: Arc<CryptoService>) -> Self {
+        Self {
+            repository: Arc::new(CommonConfigRepository::new(&db_manager, &crypto_service)),
+            key_ring: Arc::new(KeyRing::new((*crypto_service).clone())),
+        }
+    }
+
+    /// 创建指定历史版本保留数量的服务实例
+    pub fn with_history_limit(
+        db_manager: Arc<DatabaseManager>,
+        crypto_service: Arc<CryptoService>,
+        history_limit: i64,
+    ) -> Self {
+        Self {
+            repository: Arc::new(
+                CommonConfigRepository::new(&db_manager, &crypto_service)
+                    .with_history_limit(history_limit),
+            ),
+            key_ring: Arc::new(KeyRing::new((*crypto_service).clone())),
+        }
+    }
+
+    /// 使用按类别区分密钥的密钥环
+    pub fn with_key_ring(mut self, key_ring: KeyRing) -> Self {
+        self.key_ring = Arc::new(key_ring);
+        self
+    }
+
+    /// 获取底层Repository
+    pub fn repository(&self) -> &CommonConfigRepository {
+        &self.repository
+    }
+
+    /// 创建配置，需要加密的值使用类别对应的密钥加密保存
+    pub async fn create_config(
+        &self,
+        request: &CreateCommonConfigRequest,
+    ) -> CommonConfigServiceResult<i64> {
+        validate_secret_patterns(&request.key, &request.value)?;
+        let category = request.category.as_deref().unwrap_or(DEFAULT_CATEGORY);
+        let (stored_value, key_fingerprint) =
+            self.encode_value(&request.key, category, &request.value)?;
+
+        let id = self
+            .repository
+            .create_common_config(
+                &CreateCommonConfigRequest {
+                    key: request.key.clone(),
+                    value: stored_value,
+                    description: request.description.clone(),
+                    category: Some(category.to_string()),
+                    is_active: request.is_active,
+                },
+                key_fingerprint.as_deref(),
+            )
+            .await?;
+
+        info!(
+            id = %id,
+            key = %request.key,
+            category = %category,
+            encrypted = %key_fingerprint.is_some(),
+            "通用配置保存成功"
+        );
+        Ok(id)
+    }
+
+    /// 根据key获取解密后的配置
+    pub async fn get_config(&self, key: &str) -> CommonConfigServiceResult<Option<CommonConfig>> {
+        match self.repository.find_by_key(key).await? {
+            Some(config) => Ok(Some(self.decrypt_config(config).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 根据ID获取解密后的配置
+    pub async fn get_config_by_id(
+        &self,
+        id: i64,
+    ) -> CommonConfigServiceResult<Option<CommonConfig>> {
+        match self.repository.find_by_id::<CommonConfig>(id).await? {
+            Some(config) => Ok(Some(self.decrypt_config(config).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 一次查询获取多个key对应的解密后配置，不存在的key不会出现在结果中
+    pub async fn get_configs(
+        &self,
+        keys: &[&str],
+    ) -> CommonConfigServiceResult<HashMap<String, CommonConfig>> {
+        let mut configs = HashMap::with_capacity(keys.len());
+        for (key, config) in self.repository.get_many_by_keys(keys).await? {
+            configs.insert(key, self.decrypt_config(config).await?);
+        }
+        Ok(configs)
+    }
+
+    /// 更新配置值，需要加密的值使用配置所属类别的密钥加密
+    pub async fn update_value(&self, key: &str, value: &str) -> CommonConfigServiceResult<bool> {
+        validate_secret_patterns(key, value)?;
+        let config = self.find_config(key).await?;
+        let category = config.category.clone();
+
+        // 密文每次都不同，先比较明文避免记录无意义的历史版本
+        if self.decrypt_config(config).await?.value == value {
+            return Ok(false);
+        }
+
+        let (stored_value, key_fingerprint) = self.encode_value(key, &category, value)?;
+        Ok(self
+            .repository
+            .update_config_value(key, &stored_value, key_fingerprint.as_deref())
+            .await?)
+    }
+
+    /// 更新配置，需要加密的值使用更新后类别对应的密钥加密
+    pub async fn update_config(
+        &self,
+        id: i64,
+        request: &UpdateCommonConfigRequest,
+    ) -> CommonConfigServiceResult<bool> {
+        let existing = self
+            .repository
+            .find_by_id::<CommonConfig>(id)
+            .await?
+            .ok_or_else(|| CommonConfigServiceError::ConfigNotFound(format!("ID {}", id)))?;
+
+        let update = self.encrypt_changes(existing, request).await?;
+        Ok(self
+            .repository
+            .update_common_config(id, &update.changes, update.key_fingerprint.as_deref())
+            .await?)
+    }
+
+    /// 按ID或键批量更新配置，需要加密的值使用各自类别对应的密钥加密，返回的配置已解密
+    ///
+    /// 事务和 `partial` 的语义与 [`CommonConfigRepository::batch_update`] 相同
+    pub async fn batch_update(
+        &self,
+        updates: Vec<BatchConfigUpdate>,
+        partial: bool,
+    ) -> CommonConfigServiceResult<BatchUpdateResult> {
+        let mut encrypted = Vec::with_capacity(updates.len());
+        for update in updates {
+            let existing = match (update.id, &update.changes.key) {
+                (Some(id), _) => self.repository.find_by_id::<CommonConfig>(id).await?,
+                (None, Some(key)) => self.repository.find_by_key(key).await?,
+                (None, None) => None,
+            };
+            // 找不到的配置原样交给Repository，由其报告失败
+            let encrypted_update = match existing {
+                Some(existing) => BatchConfigUpdate {
+                    id: update.id,
+                    ..self.encrypt_changes(existing, &update.changes).await?
+                },
+                None => update,
+            };
+            encrypted.push(encrypted_update);
+        }
+
+        let mut result = self.repository.batch_update(&encrypted, partial).await?;
+        let mut updated = Vec::with_capacity(result.updated.len());
+        for config in result.updated {
+            updated.push(self.decrypt_config(config).await?);
+        }
+        result.updated = updated;
+        Ok(result)
+    }
+
+    /// 获取配置的历史版本（最新的在前），值已解密
+    pub async fn history(&self, key: &str) -> CommonConfigServiceResult<Vec<CommonConfigHistory>> {
+        let config = self.find_config(key).await?;
+        let crypto_service = self.key_ring.for_category(&config.category);
+
+        self.repository
+            .list_history(config.id)
+            .await?
+            .into_iter()
+            .map(
+                |mut history| -> CommonConfigServiceResult<CommonConfigHistory> {
+                    history.value = decode_value(&history.value, crypto_service)?;
+                    Ok(history)
+                },
+            )
+            .collect()
+    }
+
+    /// 将配置值恢复到指定历史版本
+    ///
+    /// 恢复前的当前值会作为新的历史版本保留
+    pub async fn revert(&self, key: &str, version: i64) -> CommonConfigServiceResult<CommonConfig> {
+        let config = self.find_config(key).await?;
+
+        let history = match self.repository.find_history_version(config.id, version).await? {
+            Some(history) => history,
+            None => {
+                warn!(key = %key, version = %version, "历史版本不存在");
+                return Err(CommonConfigServiceError::VersionNotFound {
+                    key: key.to_string(),
+                    version,
+                });
+            }
+        };
+
+        // 历史值按当前的类别和密钥重新保存，不沿用记录历史时的密文
+        let value = decode_value(&history.value, self.key_ring.for_category(&config.category))?;
+        validate_secret_patterns(key, &value)?;
+        let (stored_value, key_fingerprint) = self.encode_value(key, &config.category, &value)?;
+        self.repository
+            .update_config_value(key, &stored_value, key_fingerprint.as_deref())
+            .await?;
+
+        info!(key = %key, version = %version, "通用配置已回滚");
+
+        let config = self.find_config(key).await?;
+        self.decrypt_config(config).await
+    }
+
+    /// 解析所有已知配置（内置默认值和数据库中的启用配置）的生效值
+    ///
+    /// 优先级为 环境变量 > 数据库 > 默认值；数据库中的值按类别解密后推断数据类型，敏感键的值会被隐藏
+    pub async fn effective_config(&self) -> CommonConfigServiceResult<Vec<EffectiveConfig>> {
+        let mut effective: BTreeMap<String, EffectiveConfig> = CONFIG_DEFAULTS
+            .iter()
+            .map(|default| {
+                (
+                    default.key.to_string(),
+                    EffectiveConfig {
+                        key: default.key.to_string(),
+                        value: default.value.to_string(),
+                        source: ConfigSource::Default,
+                        data_type: default.data_type.to_string(),
+                    },
+                )
+            })
+            .collect();
+
+        for config in self.repository.list_active_configs(None).await? {
+            let config = self.decrypt_config(config).await?;
+            let data_type = config_data_type(&config.key, &config.value).to_string();
+            let value = if is_sensitive_key(&config.key) {
+                REDACTED.to_string()
+            } else {
+                config.value
+            };
+
+            effective.insert(
+                config.key.clone(),
+                EffectiveConfig { key: config.key, value, source: ConfigSource::Db, data_type },
+            );
+        }
+
+        for entry in effective.values_mut() {
+            if let Ok(value) = std::env::var(config_env_var(&entry.key)) {
+                debug!(key = %entry.key, "配置被环境变量覆盖");
+                entry.value = if is_sensitive_key(&entry.key) {
+                    REDACTED.to_string()
+                } else {
+                    value
+                };
+                entry.source = ConfigSource::Env;
+            }
+        }
+
+        Ok(effective.into_values().collect())
+    }
+
+    /// 写入数据库中缺少的内置默认配置，返回新增的配置键
+    ///
+    /// 已存在的配置即使值与默认值不同也不会修改，可以在每次启动时执行
+    pub async fn ensure_defaults(&self) -> CommonConfigServiceResult<Vec<String>> {
+        let mut added = Vec::new();
+        for default in CONFIG_DEFAULTS {
+            if self
+                .repository
+                .insert_if_absent(default.key, default.value, DEFAULT_CATEGORY)
+                .await?
+            {
+                added.push(default.key.to_string());
+            }
+        }
+
+        if !added.is_empty() {
+            info!(added = ?added, "已补充缺少的默认配置");
+        }
+        Ok(added)
+    }
+
+    /// 按 环境变量 > 数据库 > 默认值 的优先级读取敏感信息匹配规则并编译
+    ///
+    /// 规则无效时返回错误，不会忽略无效规则
+    pub async fn secret_matcher(&self) -> CommonConfigServiceResult<SecretMatcher> {
+        let value = match std::env::var(config_env_var(SECRET_PATTERNS_CONFIG_KEY)) {
+            Ok(value) => value,
+            Err(_) => match self.repository.find_by_key(SECRET_PATTERNS_CONFIG_KEY).await? {
+                Some(config) if config.is_active == 1 => self.decrypt_config(config).await?.value,
+                _ => "[]".to_string(),
+            },
+        };
+        Ok(SecretMatcher::from_config_value(&value)?)
+    }
+
+    async fn find_config(&self, key: &str) -> CommonConfigServiceResult<CommonConfig> {
+        self.repository
+            .find_by_key(key)
+            .await?
+            .ok_or_else(|| CommonConfigServiceError::ConfigNotFound(key.to_string()))
+    }
+
+    /// 按更新后的键和类别计算要写入的值
+    ///
+    /// 值与当前明文相同且存储方式不变时不写入，避免记录无意义的历史版本；
+    /// 只修改键或类别时，当前值按新的键和类别重新保存（加密、改用新类别的密钥或改为明文）
+    async fn encrypt_changes(
+        &self,
+        existing: CommonConfig,
+        changes: &UpdateCommonConfigRequest,
+    ) -> CommonConfigServiceResult<BatchConfigUpdate> {
+        let category = changes.category.as_deref().unwrap_or(&existing.category);
+        let current = self.decrypt_config(existing.clone()).await?.value;
+        // 改名为匹配规则键时，沿用的当前值同样需要是有效规则
+        let key = changes.key.as_deref().unwrap_or(&existing.key);
+        if changes.value.is_some() || key != existing.key {
+            validate_secret_patterns(key, changes.value.as_deref().unwrap_or(&current))?;
+        }
+
+        let encrypt = self.should_encrypt(key, category);
+        let storage_changed = encrypt != self.should_encrypt(&existing.key, &existing.category)
+            || (encrypt && category != existing.category);
+        let value = match &changes.value {
+            Some(value) if *value != current || storage_changed => Some(value),
+            None if storage_changed => Some(&current),
+            _ => None,
+        };
+        let (value, key_fingerprint) = match value {
+            Some(value) => {
+                let (stored_value, key_fingerprint) = self.encode_value(key, category, value)?;
+                (Some(stored_value), key_fingerprint)
+            }
+            None => (None, None),
+        };
+
+        Ok(BatchConfigUpdate {
+            id: Some(existing.id),
+            changes: UpdateCommonConfigRequest {
+                key: changes.key.clone(),
+                value,
+                description: changes.description.clone(),
+                category: changes.category.clone(),
+                is_active: changes.is_active,
+            },
+            key_fingerprint,
+        })
+    }
+
+    /// 配置值是否加密保存：类别在密钥环中设置了独立密钥，或者键为敏感键
+    fn should_encrypt(&self, key: &str, category: &str) -> bool {
+        self.key_ring.has_key(category) || is_sensitive_key(key)
+    }
+
+    /// 计算写入数据库的值和加密所用密钥的指纹，不需要加密的值以明文保存，指纹为 `None`
+    fn encode_value(
+        &self,
+        key: &str,
+        category: &str,
+        value: &str,
+    ) -> CommonConfigServiceResult<(String, Option<String>)> {
+        if !self.should_encrypt(key, category) {
+            return Ok((value.to_string(), None));
+        }
+
+        let crypto_service = self.key_ring.for_category(category);
+        let stored_value = EncryptedField::encrypt_field(value, crypto_service)?;
+        Ok((stored_value, crypto_service.storage_fingerprint()))
+    }
+
+    /// 解密配置值，明文保存的值原样返回
+    ///
+    /// 由其他密钥加密时返回 `KeyMismatch`，见 [`BaseRepository::decrypt_stored`]
+    pub async fn decrypt_config(
+        &self,
+        mut config: CommonConfig,
+    ) -> CommonConfigServiceResult<CommonConfig> {
+        if !CryptoService::is_fernet_token(&config.value) {
+            return Ok(config);
+        }
+
+        let crypto_service = self.key_ring.for_category(&config.category);
+        config.value =
+            self.repository.decrypt_stored(config.id, &config.value, crypto_service).await?;
+        Ok(config)
+    }
+}
+
+/// 还原数据库中保存的值，明文保存的值原样返回
+fn decode_value(value: &str, crypto_service: &CryptoService) -> CommonConfigServiceResult<String> {
+    if !CryptoService::is_fernet_token(value) {
+        return Ok(value.to_string());
+    }
+    Ok(EncryptedField::decrypt_field(value, crypto_service)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseConfig;
+
+    /// 每个测试使用独立的命名内存数据库
+    async fn test_db_manager(name: &str) -> Arc<DatabaseManager> {
+        let db_manager =
+            DatabaseManager::new(DatabaseConfig::in_memory_shared(name)).await.unwrap();
+        db_manager.ensure_initialized().await.unwrap();
+        Arc::new(db_manager)
+    }
+
+    #[tokio::test]
+    async fn test_update_twice_then_revert() {
+        let db_manager = test_db_manager("common_config_history").await;
+        let crypto_service =
+            Arc::new(CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap());
+        let service = CommonConfigService::with_history_limit(db_manager, crypto_service, 2);
+        let repository = service.repository();
+
+        let id = repository
+            .create_common_config(
+                &CreateCommonConfigRequest {
+                    key: "history.test".to_string(),
+                    value: "first".to_string(),
+                    description: None,
+                    category: None,
+                    is_active: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        repository
+            .update_common_config(
+                id,
+                &UpdateCommonConfigRequest {
+                    key: None,
+                    value: Some("second".to_string()),
+                    description: None,
+                    category: None,
+                    is_active: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        repository.update_config_value("history.test", "third", None).await.unwrap();
+
+        let history = service.history("history.test").await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].version, 2);
+        assert_eq!(history[0].value, "second");
+        assert_eq!(history[1].version, 1);
+        assert_eq!(history[1].value, "first");
+
+        let reverted = service.revert("history.test", 1).await.unwrap();
+        assert_eq!(reverted.value, "first");
+
+        // 回滚前的值被记录，超过保留数量的最旧版本被清理
+        let history = service.history("history.test").await.unwrap();
+        let versions: Vec<i64> = history.iter().map(|h| h.version).collect();
+        assert_eq!(versions, vec![3, 2]);
+        assert_eq!(history[0].value, "third");
+
+        assert!(matches!(
+            service.revert("history.test", 1).await,
+            Err(CommonConfigServiceError::VersionNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_key_ring_isolates_categories() {
+        let db_manager = test_db_manager("common_config_key_ring").await;
+        let default_key = CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap();
+        let database_key =
+            CryptoService::new("U82WvQEOw4doHujpEVjaPgKOY-rxD8J6GwVvG0LOeqE=").unwrap();
+
+        let service = CommonConfigService::new(db_manager, Arc::new(default_key.clone()))
+            .with_key_ring(
+                KeyRing::new(default_key.clone()).with_key("database", database_key.clone()),
+            );
+
+        for (key, value, category) in [
+            ("database.password", "db-secret", "database"),
+            ("database.host", "db.internal", "database"),
+            ("api.token", "api-secret", "api"),
+            ("ui.theme", "dark", "ui"),
+        ] {
+            service
+                .create_config(&CreateCommonConfigRequest {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    description: None,
+                    category: Some(category.to_string()),
+                    is_active: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        // 读取时使用同一映射解密
+        let db_config = service.get_config("database.password").await.unwrap().unwrap();
+        assert_eq!(db_config.value, "db-secret");
+        let api_config = service.get_config("api.token").await.unwrap().unwrap();
+        assert_eq!(api_config.value, "api-secret");
+
+        // 批量读取同样按类别解密
+        let configs = service
+            .get_configs(&["database.password", "api.token", "missing.key"])
+            .await
+            .unwrap();
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs["database.password"].value, "db-secret");
+        assert_eq!(configs["api.token"].value, "api-secret");
+
+        // 存储的密文只能用各自的密钥解密
+        let stored_db = service.repository().find_by_key("database.password").await.unwrap();
+        let stored_db = stored_db.unwrap().value;
+        assert_eq!(database_key.decrypt(&stored_db).unwrap(), "db-secret");
+        assert!(default_key.decrypt(&stored_db).is_err());
+
+        let stored_api = service.repository().find_by_key("api.token").await.unwrap().unwrap();
+        assert_eq!(
+            default_key.decrypt(&stored_api.value).unwrap(),
+            "api-secret"
+        );
+        assert!(database_key.decrypt(&stored_api.value).is_err());
+
+        // 设置了独立密钥的类别中，非敏感键同样加密保存
+        let stored_host = service.repository().find_by_key("database.host").await.unwrap().unwrap();
+        assert_eq!(
+            database_key.decrypt(&stored_host.value).unwrap(),
+            "db.internal"
+        );
+        let fingerprint_of = |key: &'static str| {
+            sqlx::query_scalar::<_, Option<String>>(
+                "SELECT key_fingerprint FROM common_configs WHERE key = ?",
+            )
+            .bind(key)
+            .fetch_one(service.repository().pool())
+        };
+        assert_eq!(
+            fingerprint_of("database.host").await.unwrap(),
+            Some(database_key.key_fingerprint())
+        );
+
+        // 其他类别中的非敏感键以明文保存
+        let stored_theme = service.repository().find_by_key("ui.theme").await.unwrap().unwrap();
+        assert_eq!(stored_theme.value, "dark");
+        assert_eq!(fingerprint_of("ui.theme").await.unwrap(), None);
+        assert_eq!(
+            service.get_config("ui.theme").await.unwrap().unwrap().value,
+            "dark"
+        );
+
+        // 改为敏感键后当前值改为加密保存
+        service
+            .update_config(
+                stored_theme.id,
+                &UpdateCommonConfigRequest {
+                    key: Some("ui.secret".to_string()),
+                    value: None,
+                    description: None,
+                    category: None,
+                    is_active: None,
+                },
+            )
+            .await
+            .unwrap();
+        let stored_secret = service.repository().find_by_key("ui.secret").await.unwrap().unwrap();
+        assert_eq!(default_key.decrypt(&stored_secret.value).unwrap(), "dark");
+        assert_eq!(
+            service.get_config("ui.secret").await.unwrap().unwrap().value,
+            "dark"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_effective_config_env_overrides_db() {
+        let db_manager = test_db_manager("common_config_effective").await;
+        let default_key = CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap();
+        let database_key =
+            CryptoService::new("U82WvQEOw4doHujpEVjaPgKOY-rxD8J6GwVvG0LOeqE=").unwrap();
+        let service = CommonConfigService::new(db_manager, Arc::new(default_key.clone()))
+            .with_key_ring(KeyRing::new(default_key).with_key("database", database_key));
+
+        for (key, value) in [
+            ("effective.override", "from-db"),
+            ("effective.db_only", "42"),
+        ] {
+            service
+                .repository()
+                .create_common_config(
+                    &CreateCommonConfigRequest {
+                        key: key.to_string(),
+                        value: value.to_string(),
+                        description: None,
+                        category: None,
+                        is_active: None,
+                    },
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        for (key, value, category) in [
+            ("effective.secret", "plaintext", "default"),
+            ("effective.pool_size", "8", "database"),
+        ] {
+            service
+                .create_config(&CreateCommonConfigRequest {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    description: None,
+                    category: Some(category.to_string()),
+                    is_active: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        std::env::set_var(config_env_var("effective.override"), "from-env");
+        let effective = service.effective_config().await.unwrap();
+        std::env::remove_var(config_env_var("effective.override"));
+
+        let find = |key: &str| effective.iter().find(|c| c.key == key).unwrap();
+
+        let overridden = find("effective.override");
+        assert_eq!(overridden.source, ConfigSource::Env);
+        assert_eq!(overridden.value, "from-env");
+
+        let db_only = find("effective.db_only");
+        assert_eq!(db_only.source, ConfigSource::Db);
+        assert_eq!(db_only.data_type, "integer");
+
+        // 敏感键的值被隐藏
+        assert_eq!(find("effective.secret").value, REDACTED);
+
+        // 使用类别密钥加密的非敏感值解密后返回，并按明文推断类型
+        let pool_size = find("effective.pool_size");
+        assert_eq!(pool_size.source, ConfigSource::Db);
+        assert_eq!(pool_size.value, "8");
+        assert_eq!(pool_size.data_type, "integer");
+        assert_eq!(find("language").source, ConfigSource::Default);
+    }
+
+    #[tokio::test]
+    async fn test_secret_matcher_from_config() {
+        let db_manager = test_db_manager("common_config_secret_patterns").await;
+        let crypto_service =
+            Arc::new(CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap());
+        let service = CommonConfigService::new(db_manager, crypto_service);
+
+        // 未配置时只使用内置规则
+        let matcher = service.secret_matcher().await.unwrap();
+        assert!(!matcher.is_match("corp_abcdef123456"));
+
+        service
+            .create_config(&CreateCommonConfigRequest {
+                key: SECRET_PATTERNS_CONFIG_KEY.to_string(),
+                value: r#"["corp_[A-Za-z0-9]{12,}"]"#.to_string(),
+                description: None,
+                category: None,
+                is_active: None,
+            })
+            .await
+            .unwrap();
+        let matcher = service.secret_matcher().await.unwrap();
+        assert!(matcher.is_match("corp_abcdef123456"));
+
+        // 无效规则在写入时拒绝，原有规则保持不变
+        let error = service
+            .update_value(SECRET_PATTERNS_CONFIG_KEY, r#"["corp_(unclosed"]"#)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            CommonConfigServiceError::InvalidSecretPatterns(_)
+        ));
+        assert!(service.secret_matcher().await.unwrap().is_match("corp_abcdef123456"));
+
+        // 绕过服务写入的无效规则在加载时报错
+        service
+            .repository()
+            .update_config_value(SECRET_PATTERNS_CONFIG_KEY, r#"["corp_(unclosed"]"#, None)
+            .await
+            .unwrap();
+        let error = service.secret_matcher().await.unwrap_err();
+        assert!(matches!(
+            error,
+            CommonConfigServiceError::InvalidSecretPatterns(_)
+        ));
+        assert!(error.to_string().contains("corp_(unclosed"));
+    }
+
+    #[tokio::test]
+    async fn test_ensure_defaults_only_adds_missing_keys() {
+        let db_manager = test_db_manager("common_config_ensure_defaults").await;
+        let crypto_service =
+            Arc::new(CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap());
+        let service = CommonConfigService::new(db_manager, crypto_service);
+
+        // 部分默认配置已存在，且值已被用户修改
+        service
+            .repository()
+            .create_common_config(
+                &CreateCommonConfigRequest {
+                    key: "theme".to_string(),
+                    value: "dark".to_string(),
+                    description: None,
+                    category: Some("ui".to_string()),
+                    is_active: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+
+        let added = service.ensure_defaults().await.unwrap();
+        let expected: Vec<String> = CONFIG_DEFAULTS
+            .iter()
+            .filter(|default| default.key != "theme")
+            .map(|default| default.key.to_string())
+            .collect();
+        assert_eq!(added, expected);
+
+        let theme = service.repository().find_by_key("theme").await.unwrap().unwrap();
+        assert_eq!(theme.value, "dark");
+        assert_eq!(theme.category, "ui");
+        let language = service.repository().find_by_key("language").await.unwrap().unwrap();
+        assert_eq!(language.value, "zh-CN");
+
+        // 再次执行不会新增任何配置
+        assert!(service.ensure_defaults().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,397 @@
+// 供应商连接测试
+//
+// 使用供应商的URL和Token向上游发送一次请求，默认2xx视为连接成功，供应商可以配置其他可接受的状态码。
+// 上游在超时时间内没有响应时返回超时错误，便于与其他失败区分
+
+use crate::services::http::HttpClient;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tracing::debug;
+use zeroize::Zeroizing;
+
+/// 供应商没有配置超时时间时使用的默认值（毫秒）
+///
+/// 连接测试路由不受API请求超时限制（见 `ApiServerConfig::timeout_exempt_paths`），等待时间只由该值或供应商的超时时间决定
+pub const DEFAULT_CONNECTION_TEST_TIMEOUT_MS: u64 = 30_000;
+
+/// 批量连接测试默认同时测试的供应商数量
+pub const DEFAULT_BATCH_TEST_CONCURRENCY: usize = 4;
+
+/// 批量连接测试允许同时测试的最大供应商数量
+pub const MAX_BATCH_TEST_CONCURRENCY: usize = 16;
+
+/// 连接测试错误
+#[derive(Debug, thiserror::Error)]
+pub enum ConnectionTestError {
+    #[error("上游服务在 {timeout_ms} 毫秒内未响应")]
+    Timeout { timeout_ms: u64 },
+
+    #[error("自定义请求头无效: {0}")]
+    InvalidHeader(String),
+}
+
+/// 连接测试结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionTestResult {
+    /// 上游可达、认证通过且返回可接受的状态码
+    pub success: bool,
+    /// 上游返回了可接受的状态码或401/403
+    pub reachable: bool,
+    /// 上游可达且没有以401/403拒绝Token
+    pub auth_ok: bool,
+    /// 上游返回的HTTP状态码，连接失败时为 `None`
+    pub status_code: Option<u16>,
+    pub latency_ms: u64,
+    /// 连接失败原因
+    pub error: Option<String>,
+}
+
+impl ConnectionTestResult {
+    /// 未收到上游响应时的失败结果
+    pub fn failure(latency_ms: u64, error: impl Into<String>) -> Self {
+        Self {
+            success: false,
+            reachable: false,
+            auth_ok: false,
+            status_code: None,
+            latency_ms,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// 批量连接测试中单个供应商的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderTestOutcome {
+    pub id: i64,
+    pub name: String,
+    pub result: ConnectionTestResult,
+}
+
+/// 批量连接测试汇总
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProviderTestSummary {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub duration_ms: u64,
+}
+
+impl ProviderTestSummary {
+    /// 计入一个供应商的结果
+    pub fn record(&mut self, outcome: &ProviderTestOutcome) {
+        self.total += 1;
+        if outcome.result.success {
+            self.succeeded += 1;
+        } else {
+            self.failed += 1;
+        }
+    }
+}
+
+/// 将供应商配置的超时时间转换为连接测试超时，未配置或无效时使用默认值
+pub fn timeout_from_millis(timeout_ms: Option<i64>) -> Duration {
+    let timeout_ms = timeout_ms
+        .and_then(|ms| u64::try_from(ms).ok())
+        .filter(|ms| *ms > 0)
+        .unwrap_or(DEFAULT_CONNECTION_TEST_TIMEOUT_MS);
+    Duration::from_millis(timeout_ms)
+}
+
+/// 状态码是否表示Token被拒绝
+fn is_auth_failure(status: u16) -> bool {
+    status == 401 || status == 403
+}
+
+/// 状态码是否可接受，`accepted_status_codes` 为空时接受所有2xx状态码
+fn is_accepted(status: u16, accepted_status_codes: &[u16]) -> bool {
+    if accepted_status_codes.is_empty() {
+        (200..300).contains(&status)
+    } else {
+        accepted_status_codes.contains(&status)
+    }
+}
+
+/// 将供应商的自定义请求头转换为 `HeaderMap`
+pub(crate) fn custom_header_map(
+    custom_headers: &HashMap<String, String>,
+) -> Result<HeaderMap, ConnectionTestError> {
+    let mut headers = HeaderMap::new();
+    for (name, value) in custom_headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| ConnectionTestError::InvalidHeader(name.clone()))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| ConnectionTestError::InvalidHeader(name.to_string()))?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
+}
+
+/// 构建携带Token的请求头并标记为敏感值，调试输出中不显示
+fn token_header(value: &str, name: &str) -> Result<HeaderValue, ConnectionTestError> {
+    let mut value = HeaderValue::from_str(value)
+        .map_err(|_| ConnectionTestError::InvalidHeader(name.to_string()))?;
+    value.set_sensitive(true);
+    Ok(value)
+}
+
+/// 测试上游是否可用
+///
+/// 上游拒绝连接或返回不可接受的状态码时结果中 `success` 为 `false`；超时返回 `Timeout` 错误。
+/// 返回可接受的状态码或401/403时视为 `reachable`，401/403时 `auth_ok` 为 `false`。
+/// Token同时以 `x-api-key` 和 `Authorization` 头发送，不会写入日志。
+/// `custom_headers` 为供应商的自定义请求头，与默认请求头同名时覆盖默认值。
+/// `timeout` 覆盖客户端配置的请求超时，连接失败时按客户端配置重试
+pub async fn probe(
+    client: &HttpClient,
+    url: &str,
+    token: &str,
+    custom_headers: &HashMap<String, String>,
+    accepted_status_codes: &[u16],
+    timeout: Duration,
+) -> Result<ConnectionTestResult, ConnectionTestError> {
+    let timeout_ms = timeout.as_millis() as u64;
+
+    let headers = custom_header_map(custom_headers)?;
+    // 拼接 `Bearer` 前缀的临时字符串在构建请求头后立即清零
+    let api_key = token_header(token, "x-api-key")?;
+    let authorization = token_header(
+        &Zeroizing::new(format!("Bearer {}", token)),
+        AUTHORIZATION.as_str(),
+    )?;
+
+    let started = Instant::now();
+    let request = client
+        .get(url)
+        .timeout(timeout)
+        .header("x-api-key", api_key)
+        .header(AUTHORIZATION, authorization)
+        .headers(headers);
+    let response = client.send(request).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    match response {
+        Ok(response) => {
+            debug!(status = %response.status(), latency_ms = %latency_ms, "连接测试收到响应");
+            let status = response.status().as_u16();
+            let accepted = is_accepted(status, accepted_status_codes);
+            let reachable = accepted || is_auth_failure(status);
+            let auth_ok = reachable && !is_auth_failure(status);
+            Ok(ConnectionTestResult {
+                success: accepted && auth_ok,
+                reachable,
+                auth_ok,
+                status_code: Some(status),
+                latency_ms,
+                error: None,
+            })
+        }
+        Err(e) if e.is_timeout() => Err(ConnectionTestError::Timeout { timeout_ms }),
+        Err(e) => {
+            let e = e.without_url();
+            debug!(error = %e, "连接测试请求失败");
+            Ok(ConnectionTestResult::failure(latency_ms, e.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timeout_from_millis() {
+        assert_eq!(timeout_from_millis(Some(500)), Duration::from_millis(500));
+        assert_eq!(
+            timeout_from_millis(None),
+            Duration::from_millis(DEFAULT_CONNECTION_TEST_TIMEOUT_MS)
+        );
+        assert_eq!(
+            timeout_from_millis(Some(-1)),
+            Duration::from_millis(DEFAULT_CONNECTION_TEST_TIMEOUT_MS)
+        );
+    }
+
+    #[test]
+    fn test_token_header_is_sensitive() {
+        let value = token_header("sk-sensitive-token", "x-api-key").unwrap();
+        assert!(value.is_sensitive());
+        assert!(!format!("{:?}", value).contains("sk-sensitive-token"));
+
+        assert!(matches!(
+            token_header("sk-\n", "x-api-key"),
+            Err(ConnectionTestError::InvalidHeader(name)) if name == "x-api-key"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_probe_times_out_on_silent_upstream() {
+        // 接受连接但从不响应的上游
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((socket, _)) = listener.accept().await {
+                connections.push(socket);
+            }
+        });
+
+        let result = probe(
+            &HttpClient::default(),
+            &format!("http://{}", addr),
+            "sk-slow",
+            &HashMap::new(),
+            &[],
+            Duration::from_millis(100),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(ConnectionTestError::Timeout { timeout_ms: 100 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_probe_sends_custom_headers() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // 请求中带有自定义请求头时返回200，否则返回400
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buffer = vec![0; 8192];
+                let mut read = 0;
+                while !buffer[..read].windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buffer[read..]).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => read += n,
+                    }
+                }
+                let request = String::from_utf8_lossy(&buffer[..read]).to_lowercase();
+                let status = if request.contains("\r\nanthropic-version: 2023-06-01\r\n") {
+                    "200 OK"
+                } else {
+                    "400 Bad Request"
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        let url = format!("http://{}", addr);
+        let timeout = Duration::from_secs(5);
+
+        let headers = HashMap::from([("anthropic-version".to_string(), "2023-06-01".to_string())]);
+        let result = probe(
+            &HttpClient::default(),
+            &url,
+            "sk-headers",
+            &headers,
+            &[],
+            timeout,
+        )
+        .await
+        .unwrap();
+        assert!(result.success);
+        assert_eq!(result.status_code, Some(200));
+
+        let result = probe(
+            &HttpClient::default(),
+            &url,
+            "sk-headers",
+            &HashMap::new(),
+            &[],
+            timeout,
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.status_code, Some(400));
+        assert!(!result.reachable);
+    }
+
+    /// 启动对所有请求返回固定状态码的上游
+    async fn spawn_status_upstream(status: &'static str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buffer = vec![0; 8192];
+                let mut read = 0;
+                while !buffer[..read].windows(4).any(|w| w == b"\r\n\r\n") {
+                    match socket.read(&mut buffer[read..]).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => read += n,
+                    }
+                }
+                let response = format!(
+                    "HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_probe_accepts_configured_status_codes() {
+        let url = spawn_status_upstream("404 Not Found").await;
+        let timeout = Duration::from_secs(5);
+
+        // 默认只接受2xx
+        let result = probe(
+            &HttpClient::default(),
+            &url,
+            "sk-404",
+            &HashMap::new(),
+            &[],
+            timeout,
+        )
+        .await
+        .unwrap();
+        assert!(!result.success);
+        assert!(!result.reachable);
+        assert_eq!(result.status_code, Some(404));
+
+        let result = probe(
+            &HttpClient::default(),
+            &url,
+            "sk-404",
+            &HashMap::new(),
+            &[404],
+            timeout,
+        )
+        .await
+        .unwrap();
+        assert!(result.success);
+        assert!(result.reachable);
+        assert!(result.auth_ok);
+    }
+
+    #[tokio::test]
+    async fn test_probe_reports_rejected_token_as_reachable() {
+        let url = spawn_status_upstream("401 Unauthorized").await;
+
+        let result = probe(
+            &HttpClient::default(),
+            &url,
+            "sk-invalid",
+            &HashMap::new(),
+            &[],
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+        assert!(!result.success);
+        assert!(result.reachable);
+        assert!(!result.auth_ok);
+        assert_eq!(result.status_code, Some(401));
+    }
+}
@@ -0,0 +1,189 @@
+// 上游HTTP客户端
+//
+// 连接测试和模型列表请求统一通过 `HttpClient` 发送，超时、重定向和连接池配置保持一致。
+// 客户端由应用状态持有并传给各个服务，克隆的客户端共用连接池，重复测试同一个上游时可以复用连接
+
+use reqwest::redirect::Policy;
+use reqwest::{Client, RequestBuilder, Response};
+use std::time::Duration;
+use tracing::debug;
+
+/// 默认建立连接的超时时间（毫秒）
+pub const DEFAULT_CONNECT_TIMEOUT_MS: u64 = 10_000;
+
+/// 默认请求超时时间（毫秒），包含读取完整响应
+pub const DEFAULT_REQUEST_TIMEOUT_MS: u64 = 30_000;
+
+/// 默认最多跟随的重定向次数
+pub const DEFAULT_MAX_REDIRECTS: usize = 5;
+
+/// 默认连接失败时的重试次数
+///
+/// 默认不重试：连接测试的延迟和失败结果应对应单次请求，需要重试的调用方显式设置 `retries`
+pub const DEFAULT_RETRIES: u32 = 0;
+
+/// 两次重试之间的基础等待时间，第N次重试等待N倍
+const RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// HTTP客户端配置
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HttpClientConfig {
+    /// 建立连接的超时时间
+    pub connect_timeout: Duration,
+    /// 请求超时时间，包含读取完整响应；单个请求可以通过 `RequestBuilder::timeout` 覆盖
+    pub request_timeout: Duration,
+    /// 最多跟随的重定向次数，为0时不跟随重定向
+    pub max_redirects: usize,
+    /// 空闲连接保留时间
+    pub pool_idle_timeout: Duration,
+    /// 每个上游最多保留的空闲连接数
+    pub pool_max_idle_per_host: usize,
+    /// 连接失败（不包括超时）时的重试次数
+    pub retries: u32,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_millis(DEFAULT_CONNECT_TIMEOUT_MS),
+            request_timeout: Duration::from_millis(DEFAULT_REQUEST_TIMEOUT_MS),
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            pool_idle_timeout: Duration::from_secs(90),
+            pool_max_idle_per_host: 4,
+            retries: DEFAULT_RETRIES,
+        }
+    }
+}
+
+/// 上游HTTP客户端，连接失败时按配置重试
+///
+/// 克隆的客户端共用同一个连接池，应用启动时创建一次，通过 `ApiState` / `RpcContext` 传给各个服务
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: Client,
+    retries: u32,
+}
+
+impl HttpClient {
+    /// 按配置创建客户端
+    pub fn new(config: &HttpClientConfig) -> reqwest::Result<Self> {
+        Ok(Self { client: build_client(config)?, retries: config.retries })
+    }
+
+    /// 创建GET请求
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    /// 发送请求，连接失败时按配置的次数重试，见 [`send_with_retry`]
+    pub async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        send_with_retry(request, self.retries).await
+    }
+}
+
+impl Default for HttpClient {
+    /// 使用默认配置创建客户端
+    ///
+    /// 与 `reqwest::Client::new` 相同，TLS后端无法初始化时panic
+    fn default() -> Self {
+        Self::new(&HttpClientConfig::default()).expect("HTTP客户端初始化失败")
+    }
+}
+
+/// 按配置创建客户端
+pub fn build_client(config: &HttpClientConfig) -> reqwest::Result<Client> {
+    let redirect = if config.max_redirects == 0 {
+        Policy::none()
+    } else {
+        Policy::limited(config.max_redirects)
+    };
+    let client = Client::builder()
+        .connect_timeout(config.connect_timeout)
+        .timeout(config.request_timeout)
+        .redirect(redirect)
+        .pool_idle_timeout(config.pool_idle_timeout)
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .build()?;
+
+    debug!(config = ?config, "创建HTTP客户端");
+    Ok(client)
+}
+
+/// 发送请求，连接失败时最多重试 `retries` 次
+///
+/// 超时和上游返回的错误状态码不会重试；请求体无法复制时只发送一次
+pub async fn send_with_retry(request: RequestBuilder, retries: u32) -> reqwest::Result<Response> {
+    let mut attempt = 0;
+    loop {
+        let Some(current) = request.try_clone() else {
+            return request.send().await;
+        };
+
+        match current.send().await {
+            Err(e) if e.is_connect() && !e.is_timeout() && attempt < retries => {
+                attempt += 1;
+                debug!(attempt = %attempt, error = %e.without_url(), "连接上游失败，准备重试");
+                tokio::time::sleep(RETRY_DELAY * attempt).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn test_client_honors_connect_timeout_on_unreachable_host() {
+        let config = HttpClientConfig {
+            connect_timeout: Duration::from_millis(200),
+            request_timeout: Duration::from_secs(30),
+            retries: 0,
+            ..Default::default()
+        };
+        let client = build_client(&config).unwrap();
+
+        // 不可路由的地址：连接要么超时，要么被网络立即拒绝，都不应等到请求超时
+        let started = Instant::now();
+        let result = client.get("http://10.255.255.1:81/").send().await;
+        let elapsed = started.elapsed();
+
+        let error = result.expect_err("不可达的地址不应返回响应");
+        assert!(error.is_connect() || error.is_timeout(), "{}", error);
+        assert!(elapsed < Duration::from_secs(5), "耗时 {:?}", elapsed);
+    }
+
+    #[tokio::test]
+    async fn test_retry_on_refused_connection() {
+        // 绑定后立即释放的端口，连接会被拒绝
+        let addr = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap()
+        };
+        let client = build_client(&HttpClientConfig::default()).unwrap();
+
+        let started = Instant::now();
+        let result = send_with_retry(client.get(format!("http://{}", addr)), 2).await;
+        assert!(result.unwrap_err().is_connect());
+        // 两次重试分别等待1倍和2倍的基础时间
+        assert!(started.elapsed() >= RETRY_DELAY * 3);
+    }
+
+    #[tokio::test]
+    async fn test_default_config_does_not_retry() {
+        let addr = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            listener.local_addr().unwrap()
+        };
+        let config = HttpClientConfig::default();
+        assert_eq!(config.retries, 0);
+        let client = build_client(&config).unwrap();
+
+        let started = Instant::now();
+        let result = send_with_retry(client.get(format!("http://{}", addr)), config.retries).await;
+        assert!(result.unwrap_err().is_connect());
+        assert!(started.elapsed() < RETRY_DELAY);
+    }
+}
@@ -2,5 +2,15 @@
 //
 // 提供业务逻辑层服务
 
+pub mod auto_update_service;
 pub mod claude_service;
 pub mod codex_service;
+pub mod common_config_service;
+pub mod connection_test;
+pub mod diagnostics_service;
+pub mod http;
+pub mod mode_service;
+pub mod outcome;
+pub mod provider_group_service;
+pub mod retention_service;
+pub mod seed_service;
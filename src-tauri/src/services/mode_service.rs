@@ -0,0 +1,352 @@
+// 工具模式切换服务
+//
+// 切换到某个工具模式前先确认该工具有可用的启用供应商，
+// 再通过 ConfigGenerator 重新生成对应的配置文件
+
+use crate::crypto::CryptoService;
+use crate::database::DatabaseManager;
+use crate::migration::config_generator::{
+    ConfigGenerator, ConfigGeneratorError, ConfigTarget, GeneratedConfigKind, McpServerConfig,
+};
+use crate::models::{ClaudeProvider, CodexProvider};
+use crate::repositories::McpServerRepository;
+use crate::services::claude_service::{ClaudeProviderService, ClaudeServiceError};
+use crate::services::codex_service::{CodexProviderService, CodexServiceError};
+use crate::utils::redaction::REDACTED;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// 工具模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    /// Claude Code
+    Claude,
+    /// Codex
+    Codex,
+}
+
+impl std::fmt::Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Claude => write!(f, "claude"),
+            Self::Codex => write!(f, "codex"),
+        }
+    }
+}
+
+impl std::str::FromStr for Mode {
+    type Err = ModeServiceError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "claude" => Ok(Self::Claude),
+            "codex" => Ok(Self::Codex),
+            other => Err(ModeServiceError::UnknownMode(other.to_string())),
+        }
+    }
+}
+
+/// 模式切换错误
+#[derive(Debug, thiserror::Error)]
+pub enum ModeServiceError {
+    #[error("未知的模式: {0}")]
+    UnknownMode(String),
+
+    #[error("模式 {0} 没有启用的供应商，请先配置并启用一个供应商")]
+    NoProvider(Mode),
+
+    #[error("模式 {mode} 的供应商 {name} 配置无效: {reason}")]
+    InvalidProvider { mode: Mode, name: String, reason: String },
+
+    #[error("Claude供应商错误: {0}")]
+    Claude(#[from] ClaudeServiceError),
+
+    #[error("Codex供应商错误: {0}")]
+    Codex(#[from] CodexServiceError),
+
+    #[error("配置生成失败: {0}")]
+    ConfigGenerator(#[from] ConfigGeneratorError),
+}
+
+/// 模式切换结果类型
+pub type ModeServiceResult<T> = Result<T, ModeServiceError>;
+
+/// 模式切换结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModeSwitchResult {
+    /// 已应用的模式
+    pub mode: Mode,
+    /// 使用的供应商ID
+    pub provider_id: i64,
+    /// 使用的供应商名称
+    pub provider_name: String,
+    /// 重新生成的配置文件
+    pub generated_files: Vec<PathBuf>,
+}
+
+/// 渲染后的配置文件
+#[derive(Debug, Clone, Serialize)]
+pub struct RenderedConfigFile {
+    pub kind: GeneratedConfigKind,
+    /// 实际生成时写入的路径
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// 配置预览，内容与切换模式时生成的一致，但不写入任何文件
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigPreview {
+    pub mode: Mode,
+    /// 使用的供应商ID
+    pub provider_id: i64,
+    /// 使用的供应商名称
+    pub provider_name: String,
+    /// 为 `false` 时内容中的Token已被遮盖
+    pub token_revealed: bool,
+    pub files: Vec<RenderedConfigFile>,
+}
+
+/// 工具模式切换服务
+#[derive(Clone)]
+pub struct ModeService {
+    claude_service: ClaudeProviderService,
+    codex_service: CodexProviderService,
+    mcp_repository: Arc<McpServerRepository>,
+    generator: ConfigGenerator,
+}
+
+impl ModeService {
+    /// 创建模式切换服务，配置文件写入 `generator` 指定的目录
+    pub fn new(
+        db_manager: Arc<DatabaseManager>,
+        crypto_service: Arc<CryptoService>,
+        generator: ConfigGenerator,
+    ) -> Self {
+        Self {
+            claude_service: ClaudeProviderService::new(db_manager.clone(), crypto_service.clone()),
+            codex_service: CodexProviderService::new(db_manager.clone(), crypto_service.clone()),
+            mcp_repository: Arc::new(McpServerRepository::new(&db_manager, &crypto_service)),
+            generator,
+        }
+    }
+
+    /// 切换到指定模式
+    ///
+    /// 目标模式没有启用的供应商或供应商配置不完整时返回错误，不会修改任何配置文件
+    pub async fn switch_mode(&self, mode: Mode) -> ModeServiceResult<ModeSwitchResult> {
+        info!(mode = %mode, "切换工具模式");
+
+        let generator = self.prepared_generator(true).await?;
+
+        let result = match mode {
+            Mode::Claude => {
+                let provider = self.current_claude_provider().await?;
+                let written =
+                    generator.generate_all_atomic(&[ConfigTarget::Claude(&provider)]).await?;
+                ModeSwitchResult {
+                    mode,
+                    provider_id: provider.id,
+                    provider_name: provider.name,
+                    generated_files: written.into_iter().map(|(_, path)| path).collect(),
+                }
+            }
+            Mode::Codex => {
+                let provider = self.current_codex_provider().await?;
+                let written =
+                    generator.generate_all_atomic(&[ConfigTarget::Codex(&provider)]).await?;
+                ModeSwitchResult {
+                    mode,
+                    provider_id: provider.id,
+                    provider_name: provider.name,
+                    generated_files: written.into_iter().map(|(_, path)| path).collect(),
+                }
+            }
+        };
+
+        info!(
+            mode = %mode,
+            provider = %result.provider_name,
+            files = %result.generated_files.len(),
+            "工具模式切换完成"
+        );
+
+        Ok(result)
+    }
+
+    /// 预览指定模式将要生成的配置内容，不写入任何文件
+    ///
+    /// `reveal` 为 `false` 时Token显示为 [`REDACTED`]
+    pub async fn preview_config(
+        &self,
+        mode: Mode,
+        reveal: bool,
+    ) -> ModeServiceResult<ConfigPreview> {
+        let generator = self.prepared_generator(reveal).await?;
+
+        let (provider_id, provider_name, rendered) = match mode {
+            Mode::Claude => {
+                let mut provider = self.current_claude_provider().await?;
+                if !reveal {
+                    provider.token = REDACTED.to_string();
+                }
+                let rendered = generator.render(&ConfigTarget::Claude(&provider))?;
+                (provider.id, provider.name, rendered)
+            }
+            Mode::Codex => {
+                let mut provider = self.current_codex_provider().await?;
+                if !reveal {
+                    provider.token = REDACTED.to_string();
+                }
+                let rendered = generator.render(&ConfigTarget::Codex(&provider))?;
+                (provider.id, provider.name, rendered)
+            }
+        };
+
+        info!(mode = %mode, provider = %provider_name, reveal = %reveal, "预览配置文件");
+        Ok(ConfigPreview {
+            mode,
+            provider_id,
+            provider_name,
+            token_revealed: reveal,
+            files: rendered
+                .into_iter()
+                .map(|(kind, content)| RenderedConfigFile {
+                    kind,
+                    path: generator.path_for(kind),
+                    content,
+                })
+                .collect(),
+        })
+    }
+
+    /// 载入启用的MCP服务器后的生成器
+    ///
+    /// `reveal` 为 `false` 时MCP服务器的机密环境变量显示为 [`REDACTED`]
+    async fn prepared_generator(&self, reveal: bool) -> ModeServiceResult<ConfigGenerator> {
+        let mut servers = McpServerConfig::load_enabled(&self.mcp_repository).await?;
+        if !reveal {
+            servers.iter_mut().for_each(McpServerConfig::redact_secrets);
+        }
+        Ok(self.generator.clone().with_mcp_servers(servers))
+    }
+
+    /// 当前启用且配置完整的Claude供应商（Token已解密）
+    async fn current_claude_provider(&self) -> ModeServiceResult<ClaudeProvider> {
+        let mode = Mode::Claude;
+        let current = self
+            .claude_service
+            .get_current_provider()
+            .await?
+            .ok_or(ModeServiceError::NoProvider(mode))?;
+        // 当前供应商查询返回的是密文，重新按ID读取解密后的记录
+        let provider = self
+            .claude_service
+            .get_provider(current.id)
+            .await?
+            .ok_or(ModeServiceError::NoProvider(mode))?;
+        validate_provider(mode, &provider.name, &provider.url, &provider.token)?;
+        Ok(provider)
+    }
+
+    /// 当前启用且配置完整的Codex供应商（Token已解密）
+    async fn current_codex_provider(&self) -> ModeServiceResult<CodexProvider> {
+        let mode = Mode::Codex;
+        let current = self
+            .codex_service
+            .get_current_provider()
+            .await?
+            .ok_or(ModeServiceError::NoProvider(mode))?;
+        let provider = self
+            .codex_service
+            .get_provider(current.id)
+            .await?
+            .ok_or(ModeServiceError::NoProvider(mode))?;
+        validate_provider(mode, &provider.name, &provider.url, &provider.token)?;
+        Ok(provider)
+    }
+}
+
+/// 检查供应商是否具备生成配置所需的字段
+fn validate_provider(mode: Mode, name: &str, url: &str, token: &str) -> ModeServiceResult<()> {
+    let reason = if url.trim().is_empty() {
+        "URL为空"
+    } else if !url.starts_with("http://") && !url.starts_with("https://") {
+        "URL必须以http://或https://开头"
+    } else if token.trim().is_empty() {
+        "Token为空"
+    } else {
+        return Ok(());
+    };
+
+    warn!(mode = %mode, provider = %name, reason = %reason, "供应商配置无效，拒绝切换模式");
+    Err(ModeServiceError::InvalidProvider {
+        mode,
+        name: name.to_string(),
+        reason: reason.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseConfig;
+    use crate::migration::config_generator::GeneratedConfigKind;
+    use crate::models::CreateCodexProviderRequest;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_switch_mode_requires_enabled_provider() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test_mode_service.db");
+
+        let config = DatabaseConfig {
+            url: format!("sqlite:{}", db_path.display()),
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: std::time::Duration::from_secs(10),
+            idle_timeout: std::time::Duration::from_secs(60),
+            max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
+        };
+
+        let db_manager = Arc::new(DatabaseManager::new(config).await.unwrap());
+        let crypto_service =
+            Arc::new(CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap());
+        let generator = ConfigGenerator::with_dirs(
+            temp_dir.path().join(".claude"),
+            temp_dir.path().join(".codex"),
+        );
+        let service = ModeService::new(db_manager, crypto_service, generator.clone());
+
+        assert!(matches!(
+            service.switch_mode(Mode::Codex).await,
+            Err(ModeServiceError::NoProvider(Mode::Codex))
+        ));
+        assert!(generator.read_config_file(GeneratedConfigKind::CodexAuth).unwrap().is_none());
+
+        let id = service
+            .codex_service
+            .create_provider(&CreateCodexProviderRequest {
+                name: "模式切换Codex".to_string(),
+                url: "https://api.openai.com".to_string(),
+                token: "sk-codex-mode-test".to_string(),
+                r#type: None,
+                custom_headers: None,
+                accepted_status_codes: None,
+            })
+            .await
+            .unwrap();
+        service.codex_service.enable_provider(id).await.unwrap();
+
+        let result = service.switch_mode("codex".parse().unwrap()).await.unwrap();
+        assert_eq!(result.mode, Mode::Codex);
+        assert_eq!(result.provider_id, id);
+        assert_eq!(result.generated_files.len(), 2);
+
+        let auth = generator.read_config_file(GeneratedConfigKind::CodexAuth).unwrap().unwrap();
+        assert!(auth.contains("sk-codex-mode-test"));
+    }
+}
@@ -0,0 +1,59 @@
+// 服务操作结果
+//
+// 修改数据的服务方法返回 `ServiceOutcome`，附带不阻止操作的警告（如URL重复、
+// 启用时禁用了其他供应商）。处理器将警告原样转发到 `ApiResponse.warnings`
+
+use serde::{Deserialize, Serialize};
+
+/// URL与已启用的供应商重复
+pub const WARNING_DUPLICATE_URL: &str = "DUPLICATE_URL";
+/// 启用供应商时禁用了同类型的其他供应商
+pub const WARNING_OTHERS_DISABLED: &str = "OTHERS_DISABLED";
+
+/// 不阻止操作的警告
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Warning {
+    /// 稳定的警告代码，供前端区分处理
+    pub code: String,
+    pub message: String,
+    /// 与警告相关的请求字段
+    #[serde(default)]
+    pub field: Option<String>,
+}
+
+impl Warning {
+    /// 创建警告
+    pub fn new(code: &str, message: impl Into<String>) -> Self {
+        Self { code: code.to_string(), message: message.into(), field: None }
+    }
+
+    /// 设置相关字段
+    pub fn with_field(mut self, field: &str) -> Self {
+        self.field = Some(field.to_string());
+        self
+    }
+}
+
+/// 服务操作的结果值和警告
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceOutcome<T> {
+    pub value: T,
+    pub warnings: Vec<Warning>,
+}
+
+impl<T> ServiceOutcome<T> {
+    /// 没有警告的结果
+    pub fn new(value: T) -> Self {
+        Self { value, warnings: Vec::new() }
+    }
+
+    /// 附带警告的结果
+    pub fn with_warnings(value: T, warnings: Vec<Warning>) -> Self {
+        Self { value, warnings }
+    }
+
+    /// 丢弃警告，只取结果值
+    pub fn into_value(self) -> T {
+        self.value
+    }
+}
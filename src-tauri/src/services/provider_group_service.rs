@@ -0,0 +1,417 @@
+// 供应商分组服务
+//
+// 分组包含每种工具各至多一个供应商。激活分组时在同一事务中启用组内供应商、
+// 禁用同类型的其他供应商，提交后重新生成对应工具的配置文件。
+// 分组中没有的工具类型保持原有的启用状态
+
+use crate::crypto::CryptoService;
+use crate::database::DatabaseManager;
+use crate::migration::config_generator::{
+    ConfigGenerator, ConfigGeneratorError, ConfigTarget, McpServerConfig,
+};
+use crate::models::{
+    CreateProviderGroupRequest, ProviderGroup, ProviderGroupMember, ProviderGroupMemberRequest,
+};
+use crate::repositories::base_repository::{update_statement, RepositoryError};
+use crate::repositories::{
+    ClaudeProviderRepository, CodexProviderRepository, McpServerRepository, ProviderGroupRepository,
+};
+use crate::services::mode_service::Mode;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::info;
+
+/// 供应商分组错误
+#[derive(Debug, thiserror::Error)]
+pub enum ProviderGroupError {
+    #[error("验证失败: {0}")]
+    Validation(String),
+
+    #[error("供应商分组不存在: {0}")]
+    GroupNotFound(i64),
+
+    #[error("供应商分组名称已存在: {0}")]
+    NameAlreadyExists(String),
+
+    #[error("{mode} 供应商不存在: {id}")]
+    ProviderNotFound { mode: Mode, id: i64 },
+
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("Repository错误: {0}")]
+    Repository(#[from] RepositoryError),
+
+    #[error("配置生成失败: {0}")]
+    ConfigGenerator(#[from] ConfigGeneratorError),
+}
+
+pub type ProviderGroupResult<T> = Result<T, ProviderGroupError>;
+
+/// 分组及其成员
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderGroupDetail {
+    #[serde(flatten)]
+    pub group: ProviderGroup,
+    pub members: Vec<ProviderGroupMember>,
+}
+
+/// 分组激活结果
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupActivation {
+    pub group_id: i64,
+    /// 已启用的组内供应商
+    pub enabled: Vec<ProviderGroupMember>,
+    /// 被禁用的同类型其他供应商数量
+    pub disabled_others: u64,
+    /// 重新生成的配置文件
+    pub generated_files: Vec<PathBuf>,
+}
+
+/// 供应商分组服务
+#[derive(Clone)]
+pub struct ProviderGroupService {
+    db_manager: Arc<DatabaseManager>,
+    repository: ProviderGroupRepository,
+    claude_repository: ClaudeProviderRepository,
+    codex_repository: CodexProviderRepository,
+    mcp_repository: Arc<McpServerRepository>,
+    generator: ConfigGenerator,
+}
+
+impl ProviderGroupService {
+    /// 创建供应商分组服务，配置文件写入 `generator` 指定的目录
+    pub fn new(
+        db_manager: Arc<DatabaseManager>,
+        crypto_service: Arc<CryptoService>,
+        generator: ConfigGenerator,
+    ) -> Self {
+        Self {
+            repository: ProviderGroupRepository::new(&db_manager),
+            claude_repository: ClaudeProviderRepository::new(&db_manager, &crypto_service),
+            codex_repository: CodexProviderRepository::new(&db_manager, &crypto_service),
+            mcp_repository: Arc::new(McpServerRepository::new(&db_manager, &crypto_service)),
+            db_manager,
+            generator,
+        }
+    }
+
+    /// 获取所有分组及其成员
+    pub async fn list_groups(&self) -> ProviderGroupResult<Vec<ProviderGroupDetail>> {
+        let mut details = Vec::new();
+        for group in self.repository.list_groups().await? {
+            let members = self.repository.list_members(group.id).await?;
+            details.push(ProviderGroupDetail { group, members });
+        }
+        Ok(details)
+    }
+
+    /// 获取单个分组
+    pub async fn get_group(&self, id: i64) -> ProviderGroupResult<ProviderGroupDetail> {
+        let group = self
+            .repository
+            .find_group(id)
+            .await?
+            .ok_or(ProviderGroupError::GroupNotFound(id))?;
+        let members = self.repository.list_members(id).await?;
+        Ok(ProviderGroupDetail { group, members })
+    }
+
+    /// 创建分组
+    ///
+    /// 每种工具类型至多一个成员，成员引用的供应商必须存在
+    pub async fn create_group(
+        &self,
+        request: &CreateProviderGroupRequest,
+    ) -> ProviderGroupResult<ProviderGroupDetail> {
+        let name = request.name.trim();
+        if name.is_empty() {
+            return Err(ProviderGroupError::Validation(
+                "分组名称不能为空".to_string(),
+            ));
+        }
+        if request.members.is_empty() {
+            return Err(ProviderGroupError::Validation(
+                "分组至少需要一个供应商".to_string(),
+            ));
+        }
+
+        let mut seen = HashSet::new();
+        for member in &request.members {
+            let mode: Mode = member.provider_type.parse().map_err(|_| {
+                ProviderGroupError::Validation(format!(
+                    "未知的供应商类型: {}",
+                    member.provider_type
+                ))
+            })?;
+            if !seen.insert(mode) {
+                return Err(ProviderGroupError::Validation(format!(
+                    "同一分组中只能包含一个 {} 供应商",
+                    mode
+                )));
+            }
+            self.ensure_provider_exists(mode, member.provider_id).await?;
+        }
+
+        if self.repository.find_group_by_name(name).await?.is_some() {
+            return Err(ProviderGroupError::NameAlreadyExists(name.to_string()));
+        }
+
+        let normalized = CreateProviderGroupRequest {
+            name: name.to_string(),
+            description: request.description.clone(),
+            members: request
+                .members
+                .iter()
+                .map(|member| ProviderGroupMemberRequest {
+                    provider_type: member.provider_type.trim().to_lowercase(),
+                    provider_id: member.provider_id,
+                })
+                .collect(),
+        };
+        let id = self.repository.create_group(&normalized).await?;
+        self.get_group(id).await
+    }
+
+    /// 激活分组：启用组内供应商并禁用同类型的其他供应商，然后重新生成配置文件
+    ///
+    /// 启用状态的修改在同一事务中完成，任一供应商不存在时不做任何修改
+    pub async fn activate(&self, group_id: i64) -> ProviderGroupResult<GroupActivation> {
+        info!(group_id = %group_id, "激活供应商分组");
+
+        let detail = self.get_group(group_id).await?;
+        let mut tx = self.db_manager.pool().begin().await?;
+        let mut disabled_others = 0;
+
+        for member in &detail.members {
+            let mode = member_mode(member)?;
+            let table = provider_table(mode);
+
+            let enabled = sqlx::query(&update_statement(table, &["enabled = 1"], "id = ?"))
+                .bind(member.provider_id)
+                .execute(&mut *tx)
+                .await?;
+            if enabled.rows_affected() == 0 {
+                return Err(ProviderGroupError::ProviderNotFound { mode, id: member.provider_id });
+            }
+
+            disabled_others += sqlx::query(&update_statement(
+                table,
+                &["enabled = 0"],
+                "enabled = 1 AND id != ?",
+            ))
+            .bind(member.provider_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+
+        tx.commit().await?;
+
+        let generated_files = self.regenerate_configs(&detail.members).await?;
+
+        info!(
+            group = %detail.group.name,
+            members = %detail.members.len(),
+            disabled_others = %disabled_others,
+            files = %generated_files.len(),
+            "供应商分组激活完成"
+        );
+
+        Ok(GroupActivation {
+            group_id,
+            enabled: detail.members,
+            disabled_others,
+            generated_files,
+        })
+    }
+
+    /// 为组内每种工具重新生成配置文件
+    ///
+    /// 所有文件一起生成，任一文件写入失败时恢复已写入的文件
+    async fn regenerate_configs(
+        &self,
+        members: &[ProviderGroupMember],
+    ) -> ProviderGroupResult<Vec<PathBuf>> {
+        let servers = McpServerConfig::load_enabled(&self.mcp_repository).await?;
+        let generator = self.generator.clone().with_mcp_servers(servers);
+        let mut claude_providers = Vec::new();
+        let mut codex_providers = Vec::new();
+
+        for member in members {
+            let mode = member_mode(member)?;
+            let not_found = ProviderGroupError::ProviderNotFound { mode, id: member.provider_id };
+            match mode {
+                Mode::Claude => claude_providers.push(
+                    self.claude_repository
+                        .find_by_id_decrypted(member.provider_id)
+                        .await?
+                        .ok_or(not_found)?,
+                ),
+                Mode::Codex => codex_providers.push(
+                    self.codex_repository
+                        .find_by_id_decrypted(member.provider_id)
+                        .await?
+                        .ok_or(not_found)?,
+                ),
+            }
+        }
+
+        let targets: Vec<_> = claude_providers
+            .iter()
+            .map(ConfigTarget::Claude)
+            .chain(codex_providers.iter().map(ConfigTarget::Codex))
+            .collect();
+        let written = generator.generate_all_atomic(&targets).await?;
+
+        Ok(written.into_iter().map(|(_, path)| path).collect())
+    }
+
+    async fn ensure_provider_exists(&self, mode: Mode, id: i64) -> ProviderGroupResult<()> {
+        let exists: bool = sqlx::query_scalar(&format!(
+            "SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?)",
+            provider_table(mode)
+        ))
+        .bind(id)
+        .fetch_one(self.db_manager.pool())
+        .await?;
+
+        if exists {
+            Ok(())
+        } else {
+            Err(ProviderGroupError::ProviderNotFound { mode, id })
+        }
+    }
+}
+
+fn member_mode(member: &ProviderGroupMember) -> ProviderGroupResult<Mode> {
+    member.provider_type.parse().map_err(|_| {
+        ProviderGroupError::Validation(format!("未知的供应商类型: {}", member.provider_type))
+    })
+}
+
+/// 工具类型对应的供应商表
+fn provider_table(mode: Mode) -> &'static str {
+    match mode {
+        Mode::Claude => "claude_providers",
+        Mode::Codex => "codex_providers",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseConfig;
+    use crate::migration::config_generator::GeneratedConfigKind;
+    use tempfile::tempdir;
+
+    async fn insert_provider(db_manager: &DatabaseManager, table: &str, name: &str) -> i64 {
+        sqlx::query(&format!(
+            "INSERT INTO {} (name, url, token, enabled) VALUES (?, 'https://api.example.com', 'sk-group-test', 0)",
+            table
+        ))
+        .bind(name)
+        .execute(db_manager.pool())
+        .await
+        .unwrap()
+        .last_insert_rowid()
+    }
+
+    async fn enabled_ids(db_manager: &DatabaseManager, table: &str) -> Vec<i64> {
+        sqlx::query_scalar(&format!(
+            "SELECT id FROM {} WHERE enabled = 1 ORDER BY id",
+            table
+        ))
+        .fetch_all(db_manager.pool())
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_activate_group_enables_members() {
+        let temp_dir = tempdir().unwrap();
+        let config = DatabaseConfig {
+            url: format!(
+                "sqlite:{}",
+                temp_dir.path().join("test_groups.db").display()
+            ),
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: std::time::Duration::from_secs(10),
+            idle_timeout: std::time::Duration::from_secs(60),
+            max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
+        };
+        let db_manager = Arc::new(DatabaseManager::new(config).await.unwrap());
+        db_manager.ensure_initialized().await.unwrap();
+        let crypto_service =
+            Arc::new(CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap());
+        let generator = ConfigGenerator::with_dirs(
+            temp_dir.path().join(".claude"),
+            temp_dir.path().join(".codex"),
+        );
+        let service =
+            ProviderGroupService::new(db_manager.clone(), crypto_service, generator.clone());
+
+        let claude_a = insert_provider(&db_manager, "claude_providers", "claude-a").await;
+        let claude_b = insert_provider(&db_manager, "claude_providers", "claude-b").await;
+        let codex_a = insert_provider(&db_manager, "codex_providers", "codex-a").await;
+        let codex_b = insert_provider(&db_manager, "codex_providers", "codex-b").await;
+        sqlx::query("UPDATE claude_providers SET enabled = 1 WHERE id = ?")
+            .bind(claude_a)
+            .execute(db_manager.pool())
+            .await
+            .unwrap();
+
+        let member = |provider_type: &str, provider_id| ProviderGroupMemberRequest {
+            provider_type: provider_type.to_string(),
+            provider_id,
+        };
+        let group = service
+            .create_group(&CreateProviderGroupRequest {
+                name: "客户B".to_string(),
+                description: None,
+                members: vec![member("claude", claude_b), member("codex", codex_b)],
+            })
+            .await
+            .unwrap();
+        assert_eq!(group.members.len(), 2);
+
+        // 同一类型的成员不能重复
+        assert!(matches!(
+            service
+                .create_group(&CreateProviderGroupRequest {
+                    name: "重复".to_string(),
+                    description: None,
+                    members: vec![member("codex", codex_a), member("codex", codex_b)],
+                })
+                .await,
+            Err(ProviderGroupError::Validation(_))
+        ));
+
+        let activation = service.activate(group.group.id).await.unwrap();
+        assert_eq!(activation.disabled_others, 1);
+        assert_eq!(activation.generated_files.len(), 3);
+
+        assert_eq!(
+            enabled_ids(&db_manager, "claude_providers").await,
+            vec![claude_b]
+        );
+        assert_eq!(
+            enabled_ids(&db_manager, "codex_providers").await,
+            vec![codex_b]
+        );
+        assert!(generator
+            .read_config_file(GeneratedConfigKind::ClaudeSettings)
+            .unwrap()
+            .is_some());
+        assert!(generator.read_config_file(GeneratedConfigKind::CodexAuth).unwrap().is_some());
+
+        assert!(matches!(
+            service.activate(9999).await,
+            Err(ProviderGroupError::GroupNotFound(9999))
+        ));
+    }
+}
@@ -0,0 +1,306 @@
+// 数据保留策略
+//
+// 配置历史、迁移记录等表会持续增长，按每个表的保留策略删除过期或超出数量上限的记录。
+// 删除分批进行，每批是一个独立的事务，避免长时间锁住数据库
+
+use crate::database::DatabaseManager;
+use crate::task_registry::{TaskRegistry, TaskRegistryError};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+
+/// 每批最多删除的记录数
+pub const DEFAULT_CLEANUP_BATCH_SIZE: usize = 500;
+/// 后台清理任务名称
+pub const RETENTION_TASK_NAME: &str = "retention_cleanup";
+/// 后台清理任务默认执行间隔
+pub const DEFAULT_RETENTION_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// 受保留策略管理的表及其时间列
+const RETENTION_TABLES: &[(&str, &str)] = &[
+    ("common_config_history", "created_at"),
+    ("migration_runs", "started_at"),
+];
+
+const DAY: u64 = 24 * 60 * 60;
+
+/// 保留策略错误
+#[derive(Debug, thiserror::Error)]
+pub enum RetentionError {
+    #[error("数据表 {0} 不支持保留策略")]
+    UnsupportedTable(String),
+
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// 保留策略结果类型
+pub type RetentionResult<T> = Result<T, RetentionError>;
+
+/// 单个表的保留策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct RetentionPolicy {
+    /// 超过该时长的记录会被删除
+    pub max_age: Duration,
+    /// 最多保留的记录数，超出时删除最旧的记录
+    pub max_rows: usize,
+}
+
+/// 单个表的清理结果
+#[derive(Debug, Clone, Serialize)]
+pub struct TableCleanup {
+    pub table: String,
+    /// 因超过保留时长删除的记录数
+    pub expired: u64,
+    /// 因超出数量上限删除的记录数
+    pub overflow: u64,
+}
+
+/// 清理结果
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionReport {
+    pub tables: Vec<TableCleanup>,
+    pub total_deleted: u64,
+}
+
+/// 数据保留策略服务
+#[derive(Clone)]
+pub struct RetentionService {
+    db_manager: Arc<DatabaseManager>,
+    policies: BTreeMap<&'static str, RetentionPolicy>,
+    batch_size: usize,
+}
+
+impl RetentionService {
+    /// 使用默认策略创建服务：配置历史保留180天/10000条，迁移记录保留365天/1000条
+    pub fn new(db_manager: Arc<DatabaseManager>) -> Self {
+        let policies = BTreeMap::from([
+            (
+                "common_config_history",
+                RetentionPolicy { max_age: Duration::from_secs(180 * DAY), max_rows: 10_000 },
+            ),
+            (
+                "migration_runs",
+                RetentionPolicy { max_age: Duration::from_secs(365 * DAY), max_rows: 1_000 },
+            ),
+        ]);
+
+        Self { db_manager, policies, batch_size: DEFAULT_CLEANUP_BATCH_SIZE }
+    }
+
+    /// 设置指定表的保留策略
+    pub fn with_policy(mut self, table: &str, policy: RetentionPolicy) -> RetentionResult<Self> {
+        let (table, _) = retention_table(table)?;
+        self.policies.insert(table, policy);
+        Ok(self)
+    }
+
+    /// 设置每批删除的记录数
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// 当前的保留策略
+    pub fn policies(&self) -> &BTreeMap<&'static str, RetentionPolicy> {
+        &self.policies
+    }
+
+    /// 按保留策略清理所有表
+    pub async fn cleanup(&self) -> RetentionResult<RetentionReport> {
+        let mut tables = Vec::new();
+        for (table, policy) in &self.policies {
+            tables.push(self.cleanup_table(table, policy).await?);
+        }
+
+        let total_deleted = tables.iter().map(|t| t.expired + t.overflow).sum();
+        info!(total_deleted = %total_deleted, "数据保留清理完成");
+        Ok(RetentionReport { tables, total_deleted })
+    }
+
+    /// 注册周期性清理任务
+    pub fn register(
+        &self,
+        registry: &TaskRegistry,
+        interval: Duration,
+    ) -> Result<(), TaskRegistryError> {
+        let service = self.clone();
+        registry.register(RETENTION_TASK_NAME, interval, move || {
+            let service = service.clone();
+            async move { service.cleanup().await.map(|_| ()).map_err(|e| e.to_string()) }
+        })
+    }
+
+    async fn cleanup_table(
+        &self,
+        table: &str,
+        policy: &RetentionPolicy,
+    ) -> RetentionResult<TableCleanup> {
+        let (table, column) = retention_table(table)?;
+
+        // 表名和列名均来自 RETENTION_TABLES 白名单
+        let expired_query = format!(
+            "DELETE FROM {0} WHERE id IN (
+                SELECT id FROM {0}
+                WHERE julianday({1}) < julianday('now', ?)
+                ORDER BY id LIMIT ?
+            )",
+            table, column
+        );
+        let max_age = format!("-{} seconds", policy.max_age.as_secs());
+        let expired = self
+            .delete_in_batches(&expired_query, |query| query.bind(max_age.clone()))
+            .await?;
+
+        // 按时间从新到旧排序，跳过需要保留的记录后删除其余记录；无法解析的时间视为最旧
+        let overflow_query = format!(
+            "DELETE FROM {0} WHERE id IN (
+                SELECT id FROM (
+                    SELECT id FROM {0}
+                    ORDER BY julianday({1}) DESC, id DESC
+                    LIMIT -1 OFFSET ?
+                ) LIMIT ?
+            )",
+            table, column
+        );
+        let max_rows = i64::try_from(policy.max_rows).unwrap_or(i64::MAX);
+        let overflow =
+            self.delete_in_batches(&overflow_query, |query| query.bind(max_rows)).await?;
+
+        if expired + overflow > 0 {
+            info!(
+                table = %table,
+                expired = %expired,
+                overflow = %overflow,
+                "已按保留策略删除记录"
+            );
+        }
+
+        Ok(TableCleanup { table: table.to_string(), expired, overflow })
+    }
+
+    /// 反复执行删除语句直到没有记录被删除，最后一个参数为批大小
+    async fn delete_in_batches<'q, F>(&self, query: &'q str, bind: F) -> RetentionResult<u64>
+    where
+        F: Fn(
+            sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+        ) -> sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    {
+        let batch_size = i64::try_from(self.batch_size).unwrap_or(i64::MAX);
+        let mut deleted = 0;
+
+        loop {
+            let mut tx = self.db_manager.pool().begin().await?;
+            let affected = bind(sqlx::query(query))
+                .bind(batch_size)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            tx.commit().await?;
+
+            deleted += affected;
+            if affected < self.batch_size as u64 {
+                return Ok(deleted);
+            }
+        }
+    }
+}
+
+fn retention_table(table: &str) -> RetentionResult<(&'static str, &'static str)> {
+    RETENTION_TABLES
+        .iter()
+        .copied()
+        .find(|(name, _)| *name == table)
+        .ok_or_else(|| RetentionError::UnsupportedTable(table.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseConfig;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_cleanup_removes_rows_beyond_policy() {
+        let temp_dir = tempdir().unwrap();
+        let config = DatabaseConfig {
+            url: format!("sqlite:{}", temp_dir.path().join("retention.db").display()),
+            ..Default::default()
+        };
+        let db_manager = DatabaseManager::new(config).await.unwrap();
+        db_manager.ensure_initialized().await.unwrap();
+        let pool = db_manager.pool().clone();
+
+        let config_id = sqlx::query(
+            "INSERT INTO common_configs (key, value, category, is_active) VALUES ('k', 'v', 'test', 1)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+
+        // 版本1-2已过期，版本3-6在保留期内
+        for (version, days_ago) in [(1, 400), (2, 90), (3, 5), (4, 4), (5, 3), (6, 2)] {
+            sqlx::query(
+                "INSERT INTO common_config_history (config_id, version, value, created_at)
+                 VALUES (?, ?, 'old', datetime('now', ?))",
+            )
+            .bind(config_id)
+            .bind(version)
+            .bind(format!("-{} days", days_ago))
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        // 迁移记录使用RFC3339格式的时间
+        for days_ago in [400, 1] {
+            let started_at = (chrono::Utc::now() - chrono::Duration::days(days_ago)).to_rfc3339();
+            sqlx::query(
+                "INSERT INTO migration_runs (source, started_at, finished_at) VALUES ('json', ?, ?)",
+            )
+            .bind(&started_at)
+            .bind(&started_at)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let service = RetentionService::new(Arc::new(db_manager))
+            .with_batch_size(1)
+            .with_policy(
+                "common_config_history",
+                RetentionPolicy { max_age: Duration::from_secs(30 * DAY), max_rows: 3 },
+            )
+            .unwrap()
+            .with_policy(
+                "migration_runs",
+                RetentionPolicy { max_age: Duration::from_secs(30 * DAY), max_rows: 100 },
+            )
+            .unwrap();
+        assert!(service
+            .clone()
+            .with_policy("claude_providers", service.policies()["migration_runs"])
+            .is_err());
+
+        let report = service.cleanup().await.unwrap();
+        assert_eq!(report.total_deleted, 4);
+
+        let history = report.tables.iter().find(|t| t.table == "common_config_history").unwrap();
+        assert_eq!((history.expired, history.overflow), (2, 1));
+        let versions: Vec<i64> =
+            sqlx::query_scalar("SELECT version FROM common_config_history ORDER BY version")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(versions, vec![4, 5, 6]);
+
+        let runs = report.tables.iter().find(|t| t.table == "migration_runs").unwrap();
+        assert_eq!((runs.expired, runs.overflow), (1, 0));
+
+        // 再次清理不会删除任何记录
+        assert_eq!(service.cleanup().await.unwrap().total_deleted, 0);
+    }
+}
@@ -0,0 +1,216 @@
+// 首次运行数据初始化
+//
+// 数据库中没有任何通用配置和供应商时，写入内置默认配置和一个未启用的示例供应商，
+// 避免新用户面对空白界面。已有数据的数据库永远不会被修改
+
+use crate::crypto::{CryptoError, CryptoService};
+use crate::database::DatabaseManager;
+use crate::services::common_config_service::{CONFIG_DEFAULTS, DEFAULT_CATEGORY};
+use serde::Serialize;
+use sqlx::SqliteConnection;
+use std::sync::Arc;
+use tracing::info;
+
+/// 示例供应商名称
+pub const EXAMPLE_PROVIDER_NAME: &str = "示例供应商（请修改后启用）";
+
+/// 数据初始化错误
+#[derive(Debug, thiserror::Error)]
+pub enum SeedError {
+    #[error("数据库错误: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("加密错误: {0}")]
+    Crypto(#[from] CryptoError),
+}
+
+/// 数据初始化结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SeedReport {
+    /// 是否写入了数据，数据库非空时为 `false`
+    pub seeded: bool,
+    pub configs: usize,
+    pub providers: usize,
+}
+
+/// 首次运行数据初始化服务
+#[derive(Clone)]
+pub struct SeedService {
+    db_manager: Arc<DatabaseManager>,
+    crypto_service: Arc<CryptoService>,
+}
+
+impl SeedService {
+    /// 创建数据初始化服务
+    pub fn new(db_manager: Arc<DatabaseManager>, crypto_service: Arc<CryptoService>) -> Self {
+        Self { db_manager, crypto_service }
+    }
+
+    /// 数据库为空时写入默认配置和示例供应商
+    ///
+    /// 检查和写入在同一个 `BEGIN IMMEDIATE` 事务中完成，事务开始时即取得写锁，
+    /// 多个进程同时首次启动时只有一个会写入；数据库中已有任何配置或供应商时不做任何修改
+    pub async fn seed_defaults(&self) -> Result<SeedReport, SeedError> {
+        let mut conn = self.db_manager.pool().acquire().await?;
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+
+        let result = self.seed_in_transaction(&mut conn).await;
+        let end = if result.is_ok() { "COMMIT" } else { "ROLLBACK" };
+        sqlx::query(end).execute(&mut *conn).await?;
+
+        let report = result?;
+        if report.seeded {
+            info!(
+                configs = %report.configs,
+                providers = %report.providers,
+                "✅ 首次运行默认数据初始化完成"
+            );
+        }
+        Ok(report)
+    }
+
+    /// 在已开始的事务中检查并写入默认数据
+    async fn seed_in_transaction(
+        &self,
+        conn: &mut SqliteConnection,
+    ) -> Result<SeedReport, SeedError> {
+        let existing: i64 = sqlx::query_scalar(
+            r#"
+            SELECT (SELECT COUNT(*) FROM common_configs)
+                 + (SELECT COUNT(*) FROM claude_providers)
+                 + (SELECT COUNT(*) FROM codex_providers)
+            "#,
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        if existing > 0 {
+            info!(existing = %existing, "数据库已有数据，跳过默认数据初始化");
+            return Ok(SeedReport::default());
+        }
+
+        for default in CONFIG_DEFAULTS {
+            sqlx::query(
+                r#"
+                INSERT INTO common_configs (key, value, category, is_active, created_at, updated_at)
+                VALUES (?, ?, ?, 1, datetime('now'), datetime('now'))
+                "#,
+            )
+            .bind(default.key)
+            .bind(default.value)
+            .bind(DEFAULT_CATEGORY)
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        let example_token = self.crypto_service.encrypt("sk-ant-REDACTED")?;
+        sqlx::query(
+            r#"
+            INSERT INTO claude_providers (
+                name, url, token, key_fingerprint, enabled, created_at, updated_at
+            ) VALUES (?, 'https://api.anthropic.com', ?, ?, 0, datetime('now'), datetime('now'))
+            "#,
+        )
+        .bind(EXAMPLE_PROVIDER_NAME)
+        .bind(example_token)
+        .bind(self.crypto_service.storage_fingerprint())
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(SeedReport { seeded: true, configs: CONFIG_DEFAULTS.len(), providers: 1 })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::DatabaseConfig;
+    use crate::models::{ClaudeProvider, CommonConfig};
+    use crate::repositories::{BaseRepository, ClaudeProviderRepository, CommonConfigRepository};
+    use tempfile::tempdir;
+
+    async fn create_test_service(db_path: &std::path::Path) -> SeedService {
+        let config = DatabaseConfig {
+            url: format!("sqlite:{}", db_path.display()),
+            max_connections: 5,
+            min_connections: 1,
+            connect_timeout: std::time::Duration::from_secs(10),
+            idle_timeout: std::time::Duration::from_secs(60),
+            max_lifetime: std::time::Duration::from_secs(300),
+            ..Default::default()
+        };
+
+        let db_manager = DatabaseManager::new(config).await.unwrap();
+        db_manager.ensure_initialized().await.unwrap();
+        let crypto_service =
+            CryptoService::new(&crate::crypto::testing::generate_test_key()).unwrap();
+
+        SeedService::new(Arc::new(db_manager), Arc::new(crypto_service))
+    }
+
+    #[tokio::test]
+    async fn test_seed_defaults_runs_once() {
+        let temp_dir = tempdir().unwrap();
+        let service = create_test_service(&temp_dir.path().join("test_seed.db")).await;
+
+        let report = service.seed_defaults().await.unwrap();
+        assert!(report.seeded);
+        assert_eq!(report.configs, CONFIG_DEFAULTS.len());
+
+        let configs = CommonConfigRepository::new(&service.db_manager, &service.crypto_service);
+        let providers = ClaudeProviderRepository::new(&service.db_manager, &service.crypto_service);
+        assert_eq!(
+            configs.list_all::<CommonConfig>().await.unwrap().len(),
+            CONFIG_DEFAULTS.len()
+        );
+        let seeded = providers.list_all::<ClaudeProvider>().await.unwrap();
+        assert_eq!(seeded.len(), 1);
+        assert_eq!(seeded[0].name, EXAMPLE_PROVIDER_NAME);
+        assert_eq!(seeded[0].enabled, 0);
+
+        // 再次执行不会重复写入
+        let again = service.seed_defaults().await.unwrap();
+        assert!(!again.seeded);
+        assert_eq!(
+            configs.list_all::<CommonConfig>().await.unwrap().len(),
+            CONFIG_DEFAULTS.len()
+        );
+        assert_eq!(
+            providers.list_all::<ClaudeProvider>().await.unwrap().len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_seed_defaults_writes_once() {
+        let temp_dir = tempdir().unwrap();
+        let service = create_test_service(&temp_dir.path().join("test_seed_concurrent.db")).await;
+
+        let (first, second) = tokio::join!(service.seed_defaults(), service.seed_defaults());
+        let seeded = [first.unwrap(), second.unwrap()].iter().filter(|r| r.seeded).count();
+        assert_eq!(seeded, 1);
+
+        let providers = ClaudeProviderRepository::new(&service.db_manager, &service.crypto_service);
+        assert_eq!(
+            providers.list_all::<ClaudeProvider>().await.unwrap().len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_seed_defaults_is_noop_on_populated_database() {
+        let temp_dir = tempdir().unwrap();
+        let service = create_test_service(&temp_dir.path().join("test_seed_populated.db")).await;
+
+        sqlx::query("INSERT INTO codex_providers (name, url, token) VALUES ('已有', 'https://api.openai.com', 'sk-existing')")
+            .execute(service.db_manager.pool())
+            .await
+            .unwrap();
+
+        let report = service.seed_defaults().await.unwrap();
+        assert!(!report.seeded);
+
+        let configs = CommonConfigRepository::new(&service.db_manager, &service.crypto_service);
+        assert!(configs.list_all::<CommonConfig>().await.unwrap().is_empty());
+    }
+}
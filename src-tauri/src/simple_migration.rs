@@ -150,11 +150,12 @@ impl SimpleMigrationTool {
 
             // 插入数据库
             sqlx::query(
-                "INSERT OR REPLACE INTO claude_providers (name, url, token) VALUES (?, ?, ?)",
+                "INSERT OR REPLACE INTO claude_providers (name, url, token, key_fingerprint) VALUES (?, ?, ?, ?)",
             )
             .bind(name)
             .bind(url)
             .bind(encrypted_token)
+            .bind(self.crypto_service.storage_fingerprint())
             .execute(self.db_manager.pool())
             .await
             .map_err(|e| SimpleMigrationError::Database(e.to_string()))?;
@@ -214,6 +215,7 @@ mod tests {
             connect_timeout: Duration::from_secs(10),
             idle_timeout: Duration::from_secs(60),
             max_lifetime: Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = DatabaseManager::new(config).await.unwrap();
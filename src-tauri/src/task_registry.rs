@@ -0,0 +1,292 @@
+// 后台任务注册表
+//
+// 统一管理周期性后台任务（健康检查、自动备份、数据库优化等），
+// 记录每个任务的执行间隔、上次运行时间和结果，支持立即触发和暂停。
+// 注册表的最后一个克隆被释放时会取消所有任务
+
+use futures::future::{BoxFuture, FutureExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// 后台任务单次执行结果，`Err` 中为失败原因
+pub type TaskResult = Result<(), String>;
+
+type TaskFn = Arc<dyn Fn() -> BoxFuture<'static, TaskResult> + Send + Sync>;
+
+/// 后台任务注册表错误
+#[derive(Debug, thiserror::Error)]
+pub enum TaskRegistryError {
+    #[error("后台任务不存在: {0}")]
+    NotFound(String),
+
+    #[error("后台任务已注册: {0}")]
+    AlreadyRegistered(String),
+
+    #[error("后台任务 {0} 的执行间隔必须大于0")]
+    InvalidInterval(String),
+}
+
+/// 后台任务单次运行结果
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskRunResult {
+    pub success: bool,
+    /// 失败原因
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// 后台任务状态
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskInfo {
+    pub name: String,
+    pub interval_secs: u64,
+    pub paused: bool,
+    pub run_count: u64,
+    /// 上次运行时间（RFC3339）
+    pub last_run_at: Option<String>,
+    pub last_result: Option<TaskRunResult>,
+}
+
+/// 单个任务的执行器，由定时循环和手动触发共享
+struct TaskRunner {
+    task: TaskFn,
+    info: Mutex<TaskInfo>,
+    /// 保证同一任务不会并发执行
+    run_lock: tokio::sync::Mutex<()>,
+}
+
+impl TaskRunner {
+    fn info(&self) -> TaskInfo {
+        self.info.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    fn is_paused(&self) -> bool {
+        self.info.lock().unwrap_or_else(|e| e.into_inner()).paused
+    }
+
+    /// 执行一次任务并记录结果
+    async fn run(&self) -> TaskInfo {
+        let _guard = self.run_lock.lock().await;
+        let run_at = chrono::Utc::now().to_rfc3339();
+        let started = Instant::now();
+
+        let result = (self.task)().await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        let mut info = self.info.lock().unwrap_or_else(|e| e.into_inner());
+        match &result {
+            Ok(()) => debug!(task = %info.name, duration_ms = %duration_ms, "后台任务执行完成"),
+            Err(e) => warn!(task = %info.name, error = %e, "后台任务执行失败"),
+        }
+
+        info.run_count += 1;
+        info.last_run_at = Some(run_at);
+        info.last_result =
+            Some(TaskRunResult { success: result.is_ok(), error: result.err(), duration_ms });
+        info.clone()
+    }
+}
+
+struct TaskEntry {
+    runner: Arc<TaskRunner>,
+    handle: JoinHandle<()>,
+}
+
+#[derive(Default)]
+struct RegistryInner {
+    tasks: Mutex<BTreeMap<String, TaskEntry>>,
+}
+
+impl Drop for RegistryInner {
+    fn drop(&mut self) {
+        let tasks = self.tasks.get_mut().unwrap_or_else(|e| e.into_inner());
+        for (name, entry) in tasks.iter() {
+            debug!(task = %name, "取消后台任务");
+            entry.handle.abort();
+        }
+    }
+}
+
+/// 后台任务注册表
+#[derive(Clone, Default)]
+pub struct TaskRegistry {
+    inner: Arc<RegistryInner>,
+}
+
+impl TaskRegistry {
+    /// 创建空的任务注册表
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 注册周期性任务并立即开始调度，第一次执行在一个间隔之后
+    ///
+    /// 需要在tokio运行时中调用
+    pub fn register<F, Fut>(
+        &self,
+        name: impl Into<String>,
+        interval: Duration,
+        task: F,
+    ) -> Result<(), TaskRegistryError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = TaskResult> + Send + 'static,
+    {
+        let name = name.into();
+        if interval.is_zero() {
+            return Err(TaskRegistryError::InvalidInterval(name));
+        }
+
+        let mut tasks = self.inner.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        if tasks.contains_key(&name) {
+            return Err(TaskRegistryError::AlreadyRegistered(name));
+        }
+
+        let runner = Arc::new(TaskRunner {
+            task: Arc::new(move || task().boxed()),
+            info: Mutex::new(TaskInfo {
+                name: name.clone(),
+                interval_secs: interval.as_secs(),
+                paused: false,
+                run_count: 0,
+                last_run_at: None,
+                last_result: None,
+            }),
+            run_lock: tokio::sync::Mutex::new(()),
+        });
+
+        let scheduled = runner.clone();
+        let handle = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // 第一次 tick 立即返回，跳过启动时的执行
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                if !scheduled.is_paused() {
+                    scheduled.run().await;
+                }
+            }
+        });
+
+        info!(task = %name, interval_secs = %interval.as_secs(), "注册后台任务");
+        tasks.insert(name, TaskEntry { runner, handle });
+        Ok(())
+    }
+
+    /// 获取所有任务的状态（按名称排序）
+    pub fn list(&self) -> Vec<TaskInfo> {
+        let tasks = self.inner.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks.values().map(|entry| entry.runner.info()).collect()
+    }
+
+    /// 获取单个任务的状态
+    pub fn get(&self, name: &str) -> Option<TaskInfo> {
+        self.runner(name).ok().map(|runner| runner.info())
+    }
+
+    /// 立即执行一次任务，等待执行完成后返回最新状态
+    ///
+    /// 暂停的任务同样可以手动触发
+    pub async fn run_now(&self, name: &str) -> Result<TaskInfo, TaskRegistryError> {
+        let runner = self.runner(name)?;
+        info!(task = %name, "手动触发后台任务");
+        Ok(runner.run().await)
+    }
+
+    /// 暂停或恢复任务的定时执行
+    pub fn set_paused(&self, name: &str, paused: bool) -> Result<TaskInfo, TaskRegistryError> {
+        let runner = self.runner(name)?;
+        info!(task = %name, paused = %paused, "更新后台任务暂停状态");
+
+        let mut info = runner.info.lock().unwrap_or_else(|e| e.into_inner());
+        info.paused = paused;
+        Ok(info.clone())
+    }
+
+    fn runner(&self, name: &str) -> Result<Arc<TaskRunner>, TaskRegistryError> {
+        let tasks = self.inner.tasks.lock().unwrap_or_else(|e| e.into_inner());
+        tasks
+            .get(name)
+            .map(|entry| entry.runner.clone())
+            .ok_or_else(|| TaskRegistryError::NotFound(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_register_trigger_and_read_last_run() {
+        let registry = TaskRegistry::new();
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let counter = runs.clone();
+        registry
+            .register("counter", Duration::from_secs(3600), move || {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            })
+            .unwrap();
+        assert!(matches!(
+            registry.register("counter", Duration::from_secs(1), || async { Ok(()) }),
+            Err(TaskRegistryError::AlreadyRegistered(_))
+        ));
+
+        let info = registry.get("counter").unwrap();
+        assert_eq!(info.interval_secs, 3600);
+        assert!(info.last_run_at.is_none());
+
+        let info = registry.run_now("counter").await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(info.run_count, 1);
+        assert!(info.last_run_at.is_some());
+        assert!(info.last_result.unwrap().success);
+
+        let paused = registry.set_paused("counter", true).unwrap();
+        assert!(paused.paused);
+        assert_eq!(registry.list().len(), 1);
+        assert!(matches!(
+            registry.run_now("missing").await,
+            Err(TaskRegistryError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_dropping_registry_cancels_tasks() {
+        let registry = TaskRegistry::new();
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let counter = runs.clone();
+        registry
+            .register("fast", Duration::from_millis(10), move || {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Err("失败".to_string())
+                }
+            })
+            .unwrap();
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let info = registry.get("fast").unwrap();
+        assert!(info.run_count > 0);
+        assert_eq!(info.last_result.unwrap().error.as_deref(), Some("失败"));
+
+        drop(registry);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let after_drop = runs.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), after_drop);
+    }
+}
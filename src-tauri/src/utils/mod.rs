@@ -2,6 +2,8 @@
 //!
 //! 提供通用的工具函数和验证功能
 
+pub mod redaction;
+pub mod validation;
 pub mod validators;
 
 // 重新导出常用工具
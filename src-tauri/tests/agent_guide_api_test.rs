@@ -3,6 +3,7 @@
 // 测试完整的Agent指导文件管理API工作流程
 // 验证所有CRUD操作和业务逻辑
 
+use migration_ai_manager_lib::api::testing::ApiTestClient;
 use reqwest::StatusCode;
 use serde_json::{json, Value};
 
@@ -96,10 +97,13 @@ async fn test_agent_guide_complete_workflow() {
     let update_data: Value = update_response.json().await.expect("解析更新响应失败");
     assert!(update_data["success"].as_bool().unwrap());
     assert_eq!(
-        update_data["data"]["name"].as_str().unwrap(),
+        update_data["data"]["entity"]["name"].as_str().unwrap(),
         "集成测试指导文件-已更新"
     );
-    assert_eq!(update_data["data"]["version"].as_str().unwrap(), "1.1.0");
+    assert_eq!(
+        update_data["data"]["entity"]["version"].as_str().unwrap(),
+        "1.1.0"
+    );
 
     // 6. 测试搜索功能
     let search_response = client
@@ -372,3 +376,45 @@ async fn test_agent_guide_content_validation() {
 
     assert_eq!(delete_response.status(), 200);
 }
+
+#[tokio::test]
+async fn test_agent_guide_search_pagination_and_stats() {
+    let client = ApiTestClient::new().await;
+    for (index, name) in ["alpha", "bravo", "charlie"].iter().enumerate() {
+        let (status, created) = client
+            .post(
+                "/api/v1/agent-guides",
+                json!({
+                    "name": name,
+                    "type": if index % 2 == 0 { "only" } else { "and" },
+                    "text": format!("# {}\n\n分页测试内容", name),
+                }),
+            )
+            .await;
+        assert_eq!(status, axum::http::StatusCode::OK, "{}", created);
+    }
+
+    // 与Claude供应商使用相同的分页参数
+    let (status, body) = client.get("/api/v1/agent-guides?limit=2&page=1").await;
+    assert_eq!(status, axum::http::StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["data"].as_array().unwrap().len(), 2);
+    assert_eq!(body["data"]["pagination"]["total"], 3);
+
+    let (status, body) = client.get("/api/v1/agent-guides?search=bravo").await;
+    assert_eq!(status, axum::http::StatusCode::OK, "{}", body);
+    let found = body["data"]["data"].as_array().unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0]["name"], "bravo");
+
+    // 无效的搜索词返回400
+    let (status, body) = client.get("/api/v1/agent-guides?search=").await;
+    assert_eq!(status, axum::http::StatusCode::BAD_REQUEST, "{}", body);
+
+    let (status, body) = client.get("/api/v1/agent-guides/stats").await;
+    assert_eq!(status, axum::http::StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["total"], 3);
+
+    // 不存在的记录返回404
+    let (status, body) = client.get("/api/v1/agent-guides/999/validate").await;
+    assert_eq!(status, axum::http::StatusCode::NOT_FOUND, "{}", body);
+}
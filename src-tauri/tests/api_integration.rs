@@ -97,7 +97,10 @@ async fn test_agent_guide_crud() {
 
     let update_response: serde_json::Value = response.json().await.unwrap();
     assert!(update_response["success"].as_bool().unwrap());
-    assert_eq!(update_response["data"]["name"], "更新后的指导文件名称");
+    assert_eq!(
+        update_response["data"]["entity"]["name"],
+        "更新后的指导文件名称"
+    );
 
     // 获取指导文件列表
     let response = client.get("agent-guides").await;
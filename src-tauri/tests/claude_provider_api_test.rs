@@ -3,8 +3,481 @@
 // 测试完整的Claude供应商管理API工作流程
 // 验证所有CRUD操作和业务逻辑
 
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use migration_ai_manager_lib::api::server::ApiServerConfig;
+use migration_ai_manager_lib::api::testing::ApiTestClient;
 use reqwest;
 use serde_json::{json, Value};
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn test_enable_provider_reports_disabled_others() {
+    let client = ApiTestClient::new().await;
+
+    let mut ids = Vec::new();
+    for index in 0..3 {
+        let (status, created) = client
+            .post(
+                "/api/v1/claude-providers",
+                json!({
+                    "name": format!("警告测试供应商{}", index),
+                    "url": format!("https://api{}.example.com", index),
+                    "token": format!("sk-ant-api03-warning-test-{}", index),
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+        ids.push(created["data"]["id"].as_i64().unwrap());
+    }
+
+    // 直接让前两个供应商同时处于启用状态
+    for id in &ids[..2] {
+        let (status, _) = client
+            .put(
+                &format!("/api/v1/claude-providers/{}", id),
+                json!({ "enabled": 1 }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    let (status, response) = client
+        .send(
+            Method::POST,
+            &format!("/api/v1/claude-providers/{}/enable", ids[2]),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        response["warnings"],
+        json!([{
+            "code": "OTHERS_DISABLED",
+            "message": "已禁用其他 2 个Claude供应商",
+            "field": null,
+        }])
+    );
+}
+
+#[tokio::test]
+async fn test_create_with_duplicate_url_returns_provider_and_warning() {
+    let client = ApiTestClient::new().await;
+
+    let (status, first) = client
+        .post(
+            "/api/v1/claude-providers",
+            json!({
+                "name": "重复URL-原有",
+                "url": "https://dup.example.com",
+                "token": "sk-dup-1",
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(first["warnings"], json!([]));
+    let first_id = first["data"]["id"].as_i64().unwrap();
+    let (status, _) = client
+        .post(
+            &format!("/api/v1/claude-providers/{}/enable", first_id),
+            json!(null),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // URL重复不阻止创建，响应中同时返回新记录和警告
+    let (status, created) = client
+        .post(
+            "/api/v1/claude-providers",
+            json!({
+                "name": "重复URL-新增",
+                "url": "https://dup.example.com/",
+                "token": "sk-dup-2",
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(created["success"].as_bool().unwrap());
+    assert_eq!(created["data"]["name"], "重复URL-新增");
+    let warnings = created["warnings"].as_array().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0]["code"], "DUPLICATE_URL");
+    assert_eq!(warnings[0]["field"], "url");
+    assert!(warnings[0]["message"].as_str().unwrap().contains("重复URL-原有"));
+}
+
+#[tokio::test]
+async fn test_delete_last_enabled_claude_provider_requires_force() {
+    let client = ApiTestClient::new().await;
+
+    let mut ids = Vec::new();
+    for index in 0..2 {
+        let (status, created) = client
+            .post(
+                "/api/v1/claude-providers",
+                json!({
+                    "name": format!("删除保护{}", index),
+                    "url": format!("https://api{}.example.com", index),
+                    "token": format!("sk-delete-guard-{}", index),
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+        ids.push(created["data"]["id"].as_i64().unwrap());
+    }
+    let (status, _) = client
+        .post(
+            &format!("/api/v1/claude-providers/{}/enable", ids[0]),
+            json!({}),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // 未启用的供应商可以直接删除
+    let (status, _) = client.delete(&format!("/api/v1/claude-providers/{}", ids[1])).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // 唯一启用的供应商需要强制删除
+    let (status, body) = client.delete(&format!("/api/v1/claude-providers/{}", ids[0])).await;
+    assert_eq!(status, StatusCode::CONFLICT, "{}", body);
+    assert_eq!(body["error"]["code"], "BUSINESS_RULE_VIOLATION");
+    let (status, _) = client.get(&format!("/api/v1/claude-providers/{}", ids[0])).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) =
+        client.delete(&format!("/api/v1/claude-providers/{}?force=true", ids[0])).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, _) = client.get(&format!("/api/v1/claude-providers/{}", ids[0])).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_create_validation_error_reports_field() {
+    let client = ApiTestClient::new().await;
+
+    let (status, body) = client
+        .post(
+            "/api/v1/claude-providers",
+            json!({ "name": "", "url": "https://api.anthropic.com", "token": "sk-test" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+    assert_eq!(body["error"]["details"]["field"], "供应商名称");
+    assert_eq!(
+        body["error"]["details"]["errors"][0]["message"],
+        "供应商名称不能为空"
+    );
+}
+
+#[tokio::test]
+async fn test_list_pagination_headers() {
+    let client = ApiTestClient::new().await;
+
+    for index in 0..5 {
+        let (status, created) = client
+            .post(
+                "/api/v1/claude-providers",
+                json!({
+                    "name": format!("分页供应商{}", index),
+                    "url": format!("https://api{}.example.com", index),
+                    "token": format!("sk-ant-api03-page-test-{}", index),
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+    }
+
+    let (status, headers, body) = client
+        .send_with_headers(Method::GET, "/api/v1/claude-providers?limit=2&page=2", None)
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["x-total-count"], "5");
+    assert_eq!(headers["x-page"], "2");
+    assert_eq!(headers["x-per-page"], "2");
+    assert_eq!(
+        headers["link"],
+        r#"</api/v1/claude-providers?limit=2&page=3>; rel="next", </api/v1/claude-providers?limit=2&page=1>; rel="prev""#
+    );
+    // 默认保留JSON信封
+    assert_eq!(body["data"]["data"].as_array().unwrap().len(), 2);
+
+    let (status, headers, body) = client
+        .send_with_headers(
+            Method::GET,
+            "/api/v1/claude-providers?limit=2&page=3&envelope=false",
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(
+        headers["link"],
+        r#"</api/v1/claude-providers?limit=2&envelope=false&page=2>; rel="prev""#
+    );
+}
+
+#[tokio::test]
+async fn test_list_sorting() {
+    let client = ApiTestClient::new().await;
+
+    // 名称顺序与创建顺序、修改时间顺序都不同
+    let providers = [
+        ("charlie", "2025-01-02 00:00:00"),
+        ("alpha", "2025-01-01 00:00:00"),
+        ("bravo", "2025-01-03 00:00:00"),
+    ];
+    for (index, (name, updated_at)) in providers.iter().enumerate() {
+        let (status, created) = client
+            .post(
+                "/api/v1/claude-providers",
+                json!({
+                    "name": name,
+                    "url": format!("https://sort{}.example.com", index),
+                    "token": format!("sk-ant-api03-sort-test-{}", index),
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+
+        sqlx::query("UPDATE claude_providers SET updated_at = ? WHERE name = ?")
+            .bind(updated_at)
+            .bind(name)
+            .execute(client.db_manager().pool())
+            .await
+            .unwrap();
+    }
+
+    let names = |body: &Value| -> Vec<String> {
+        body["data"]["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|provider| provider["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let (status, body) = client.get("/api/v1/claude-providers?sort=name&dir=asc").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(names(&body), vec!["alpha", "bravo", "charlie"]);
+
+    let (status, body) = client.get("/api/v1/claude-providers?sort=updated_at&dir=desc").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(names(&body), vec!["bravo", "charlie", "alpha"]);
+
+    // 分页时保持排序
+    let (_, body) = client.get("/api/v1/claude-providers?sort=name&limit=2&page=2").await;
+    assert_eq!(names(&body), vec!["charlie"]);
+
+    // 搜索和仅活跃供应商也按 `sort` 排序
+    let (status, body) =
+        client.get("/api/v1/claude-providers?search=example&sort=name&dir=desc").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(names(&body), vec!["charlie", "bravo", "alpha"]);
+
+    let (status, body) =
+        client.get("/api/v1/claude-providers?active_only=true&sort=name&dir=asc").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(names(&body), vec!["alpha", "bravo", "charlie"]);
+
+    // 不在白名单中的列和无效的方向返回400
+    for uri in [
+        "/api/v1/claude-providers?search=example&sort=token",
+        "/api/v1/claude-providers?active_only=true&sort=name&dir=sideways",
+        "/api/v1/claude-providers?sort=token",
+        "/api/v1/claude-providers?sort=key",
+        "/api/v1/claude-providers?sort=name&dir=sideways",
+    ] {
+        let (status, body) = client.get(uri).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}: {}", uri, body);
+    }
+}
+
+/// 启动只接受指定Token的模拟上游，返回200或401
+async fn spawn_mock_upstream(valid_token: &'static str) -> std::net::SocketAddr {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 16 * 1024];
+                let mut read = 0;
+                while read < buf.len() {
+                    let n = socket.read(&mut buf[read..]).await.unwrap_or(0);
+                    if n == 0 {
+                        break;
+                    }
+                    read += n;
+                    if buf[..read].windows(4).any(|w| w == b"\r\n\r\n") {
+                        break;
+                    }
+                }
+
+                let request = String::from_utf8_lossy(&buf[..read]).to_lowercase();
+                let status = if request.contains(&format!("x-api-key: {}", valid_token)) {
+                    "200 OK"
+                } else {
+                    "401 Unauthorized"
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    status
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    });
+    addr
+}
+
+#[tokio::test]
+async fn test_ad_hoc_credentials_without_saving() {
+    let upstream = spawn_mock_upstream("sk-ant-valid-token").await;
+
+    let client = ApiTestClient::new().await;
+
+    let (status, valid) = client
+        .post(
+            "/api/v1/claude-providers/test",
+            json!({ "url": format!("http://{}", upstream), "token": "sk-ant-valid-token" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", valid);
+    assert_eq!(valid["data"]["success"], true);
+    assert_eq!(valid["data"]["status_code"], 200);
+
+    let (status, invalid) = client
+        .post(
+            "/api/v1/claude-providers/test",
+            json!({
+                "url": format!("http://{}", upstream),
+                "token": "sk-ant-wrong-token",
+                "timeout": 5000,
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", invalid);
+    assert_eq!(invalid["data"]["success"], false);
+    assert_eq!(invalid["data"]["status_code"], 401);
+    assert_eq!(invalid["data"]["reachable"], true);
+    assert_eq!(invalid["data"]["auth_ok"], false);
+
+    // 测试不会保存任何供应商
+    let (_, stats) = client.get("/api/v1/claude-providers/stats").await;
+    assert_eq!(stats["data"]["total"], 0);
+}
+
+#[tokio::test]
+async fn test_saved_provider_connection_returns_details() {
+    let upstream = spawn_mock_upstream("sk-ant-valid-token").await;
+    let client = ApiTestClient::new().await;
+
+    let mut ids = Vec::new();
+    for (name, token) in [
+        ("有效凭据", "sk-ant-valid-token"),
+        ("无效凭据", "sk-ant-wrong-token"),
+    ] {
+        let (status, created) = client
+            .post(
+                "/api/v1/claude-providers",
+                json!({ "name": name, "url": format!("http://{}", upstream), "token": token }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+        ids.push(created["data"]["id"].as_i64().unwrap());
+    }
+
+    let (status, valid) = client.get(&format!("/api/v1/claude-providers/{}/test", ids[0])).await;
+    assert_eq!(status, StatusCode::OK, "{}", valid);
+    assert_eq!(valid["data"]["success"], true);
+    assert_eq!(valid["data"]["reachable"], true);
+    assert_eq!(valid["data"]["auth_ok"], true);
+    assert_eq!(valid["data"]["status_code"], 200);
+
+    // 上游可达但拒绝Token
+    let (status, invalid) = client.get(&format!("/api/v1/claude-providers/{}/test", ids[1])).await;
+    assert_eq!(status, StatusCode::OK, "{}", invalid);
+    assert_eq!(invalid["data"]["success"], false);
+    assert_eq!(invalid["data"]["reachable"], true);
+    assert_eq!(invalid["data"]["auth_ok"], false);
+    assert_eq!(invalid["data"]["status_code"], 401);
+}
+
+#[tokio::test]
+async fn test_connection_test_reports_upstream_timeout() {
+    // 接受连接但从不响应的上游供应商
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            connections.push(socket);
+        }
+    });
+
+    let client = ApiTestClient::new().await;
+
+    let (status, created) = client
+        .post(
+            "/api/v1/claude-providers",
+            json!({
+                "name": "慢速供应商",
+                "url": format!("http://{}", upstream),
+                "token": "sk-ant-api03-slow-upstream",
+                "timeout": 200,
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", created);
+    let id = created["data"]["id"].as_i64().unwrap();
+
+    let (status, response) = client.get(&format!("/api/v1/claude-providers/{}/test", id)).await;
+    assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+    // 与请求超时中间件的 GATEWAY_TIMEOUT 区分
+    assert_eq!(response["error"]["code"], "UPSTREAM_TIMEOUT");
+    assert_eq!(response["error"]["details"]["provider_id"], id);
+    assert_eq!(response["error"]["details"]["timeout_ms"], 200);
+}
+
+#[tokio::test]
+async fn test_connection_test_is_not_cut_short_by_request_timeout() {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            connections.push(socket);
+        }
+    });
+
+    // 供应商的超时时间长于API请求超时，连接测试路由不受请求超时限制
+    let client = ApiTestClient::with_config(ApiServerConfig {
+        request_timeout: std::time::Duration::from_millis(100),
+        ..Default::default()
+    })
+    .await;
+
+    let (status, created) = client
+        .post(
+            "/api/v1/claude-providers",
+            json!({
+                "name": "慢速供应商",
+                "url": format!("http://{}", upstream),
+                "token": "sk-ant-api03-slow-upstream",
+                "timeout": 400,
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", created);
+    let id = created["data"]["id"].as_i64().unwrap();
+
+    let (status, response) = client.get(&format!("/api/v1/claude-providers/{}/test", id)).await;
+    assert_eq!(status, StatusCode::GATEWAY_TIMEOUT, "{}", response);
+    assert_eq!(response["error"]["code"], "UPSTREAM_TIMEOUT");
+    assert_eq!(response["error"]["details"]["timeout_ms"], 400);
+}
 
 #[tokio::test]
 async fn test_claude_provider_complete_workflow() {
@@ -91,11 +564,17 @@ async fn test_claude_provider_complete_workflow() {
     let update_data: Value = update_response.json().await.expect("解析更新响应失败");
     assert!(update_data["success"].as_bool().unwrap());
     assert_eq!(
-        update_data["data"]["name"].as_str().unwrap(),
+        update_data["data"]["entity"]["name"].as_str().unwrap(),
         "集成测试供应商-已更新"
     );
-    assert_eq!(update_data["data"]["timeout"].as_i64().unwrap(), 45000);
-    assert_eq!(update_data["data"]["auto_update"].as_i64().unwrap(), 0);
+    assert_eq!(
+        update_data["data"]["entity"]["timeout"].as_i64().unwrap(),
+        45000
+    );
+    assert_eq!(
+        update_data["data"]["entity"]["auto_update"].as_i64().unwrap(),
+        0
+    );
 
     // 6. 测试搜索功能
     let search_response = client
@@ -130,7 +609,7 @@ async fn test_claude_provider_complete_workflow() {
     assert_eq!(test_response.status(), 200);
     let test_data: Value = test_response.json().await.expect("解析连接测试响应失败");
     assert!(test_data["success"].as_bool().unwrap());
-    assert!(test_data["data"].as_bool().unwrap());
+    assert!(test_data["data"]["success"].as_bool().unwrap());
 
     // 8. 测试统计信息
     let stats_response = client
@@ -209,9 +688,12 @@ async fn test_claude_provider_complete_workflow() {
     let enabled_data: Value = get_enabled_response.json().await.expect("解析已启用供应商响应失败");
     assert_eq!(enabled_data["data"]["enabled"].as_i64().unwrap(), 1);
 
-    // 14. 删除供应商
+    // 14. 删除供应商（唯一启用的供应商需要强制删除）
     let delete_response = client
-        .delete(&format!("{}/claude-providers/{}", base_url, provider_id))
+        .delete(&format!(
+            "{}/claude-providers/{}?force=true",
+            base_url, provider_id
+        ))
         .send()
         .await
         .expect("删除供应商请求失败");
@@ -354,3 +836,122 @@ async fn test_not_found_errors() {
     let data: Value = response.json().await.expect("解析删除不存在错误响应失败");
     assert!(!data["success"].as_bool().unwrap());
 }
+
+/// 解析SSE响应体，返回 (事件名, JSON数据) 列表
+fn parse_sse_events(body: &str) -> Vec<(String, Value)> {
+    body.split("\n\n")
+        .filter_map(|block| {
+            let mut event = None;
+            let mut data = None;
+            for line in block.lines() {
+                if let Some(name) = line.strip_prefix("event:") {
+                    event = Some(name.trim().to_string());
+                } else if let Some(value) = line.strip_prefix("data:") {
+                    data = serde_json::from_str(value.trim()).ok();
+                }
+            }
+            Some((event?, data?))
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_test_all_streams_one_event_per_provider() {
+    let first = spawn_mock_upstream("sk-ant-stream-first").await;
+    let second = spawn_mock_upstream("sk-ant-stream-second").await;
+    // 绑定后立即释放的端口，连接会被拒绝
+    let refused = {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap()
+    };
+
+    let client = ApiTestClient::new().await;
+    for (name, upstream, token) in [
+        ("流式可用", first, "sk-ant-stream-first"),
+        ("流式可用2", second, "sk-ant-stream-second"),
+        ("流式Token错误", first, "sk-ant-stream-wrong"),
+        ("流式无法连接", refused, "sk-ant-stream-refused"),
+    ] {
+        let (status, body) = client
+            .post(
+                "/api/v1/claude-providers",
+                json!({ "name": name, "url": format!("http://{}", upstream), "token": token }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+    }
+
+    let request = Request::builder()
+        .uri("/api/v1/claude-providers/test-all/stream?concurrency=2")
+        .body(Body::empty())
+        .unwrap();
+    let response = client.router().clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()["content-type"]
+        .to_str()
+        .unwrap()
+        .starts_with("text/event-stream"));
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let events = parse_sse_events(&String::from_utf8(bytes.to_vec()).unwrap());
+
+    // 每个供应商一个结果事件，最后是汇总事件
+    assert_eq!(events.len(), 5, "{:?}", events);
+    let (last, summary) = events.last().unwrap();
+    assert_eq!(last, "summary");
+    assert_eq!(summary["total"], 4);
+    assert_eq!(summary["succeeded"], 2);
+    assert_eq!(summary["failed"], 2);
+
+    let results: std::collections::HashMap<&str, &Value> = events[..4]
+        .iter()
+        .map(|(event, data)| {
+            assert_eq!(event, "result");
+            (data["name"].as_str().unwrap(), &data["result"])
+        })
+        .collect();
+    assert_eq!(results["流式可用"]["success"], true);
+    assert_eq!(results["流式可用2"]["success"], true);
+    assert_eq!(results["流式Token错误"]["status_code"], 401);
+    assert_eq!(results["流式Token错误"]["auth_ok"], false);
+    assert_eq!(results["流式无法连接"]["reachable"], false);
+    assert!(results["流式无法连接"]["error"].is_string());
+
+    let (status, _) = client.get("/api/v1/claude-providers/test-all/stream?concurrency=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_update_reports_changed_fields() {
+    let client = ApiTestClient::new().await;
+    let (status, created) = client
+        .post(
+            "/api/v1/claude-providers",
+            json!({
+                "name": "变更字段",
+                "url": "https://api.example.com",
+                "token": "sk-ant-changed-before",
+                "timeout": 30000,
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", created);
+    let id = created["data"]["id"].as_i64().unwrap();
+    let uri = format!("/api/v1/claude-providers/{}", id);
+
+    // 超时时间与原值相同，不计入变化
+    let (status, body) = client
+        .put(
+            &uri,
+            json!({ "name": "变更字段-已更新", "token": "sk-ant-changed-after", "timeout": 30000 }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["changed_fields"], json!(["name", "token"]));
+    assert_eq!(body["data"]["entity"]["name"], "变更字段-已更新");
+    assert!(!body.to_string().contains("sk-ant-changed-before"));
+
+    // 重复提交相同的值没有任何变化
+    let (status, body) = client.put(&uri, json!({ "token": "sk-ant-changed-after" })).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["changed_fields"], json!([]));
+}
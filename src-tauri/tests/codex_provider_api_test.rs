@@ -3,6 +3,10 @@
 // 测试完整的Codex供应商管理API工作流程
 // 验证所有CRUD操作和业务逻辑
 
+use axum::http::StatusCode;
+use migration_ai_manager_lib::api::server::DEFAULT_ENCRYPTION_KEY;
+use migration_ai_manager_lib::api::testing::ApiTestClient;
+use migration_ai_manager_lib::migration_tool::{DataMigrationTool, ExportFilter};
 use reqwest;
 use serde_json::{json, Value};
 
@@ -93,11 +97,11 @@ async fn test_codex_provider_complete_workflow() {
     let update_data: Value = update_response.json().await.expect("解析更新响应失败");
     assert!(update_data["success"].as_bool().unwrap());
     assert_eq!(
-        update_data["data"]["name"].as_str().unwrap(),
+        update_data["data"]["entity"]["name"].as_str().unwrap(),
         "集成测试Codex供应商-已更新"
     );
     assert_eq!(
-        update_data["data"]["url"].as_str().unwrap(),
+        update_data["data"]["entity"]["url"].as_str().unwrap(),
         "https://api.openai.com/v1/updated"
     );
 
@@ -149,9 +153,12 @@ async fn test_codex_provider_complete_workflow() {
     assert_eq!(stats_data["data"]["active"].as_i64().unwrap(), 1);
     assert_eq!(stats_data["data"]["inactive"].as_i64().unwrap(), 0);
 
-    // 9. 删除供应商
+    // 9. 删除供应商（唯一启用的供应商需要强制删除）
     let delete_response = client
-        .delete(&format!("{}/codex-providers/{}", base_url, provider_id))
+        .delete(&format!(
+            "{}/codex-providers/{}?force=true",
+            base_url, provider_id
+        ))
         .send()
         .await
         .expect("删除供应商请求失败");
@@ -294,3 +301,151 @@ async fn test_codex_provider_not_found_errors() {
     let data: Value = response.json().await.expect("解析删除不存在错误响应失败");
     assert!(!data["success"].as_bool().unwrap());
 }
+
+#[tokio::test]
+async fn test_codex_provider_search_pagination_and_stats() {
+    let client = ApiTestClient::new().await;
+    for (index, name) in ["alpha", "bravo", "charlie"].iter().enumerate() {
+        let (status, created) = client
+            .post(
+                "/api/v1/codex-providers",
+                json!({
+                    "name": name,
+                    "url": format!("https://codex{}.example.com", index),
+                    "token": format!("sk-codex-page-test-{}", index),
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+    }
+
+    // 与Claude供应商使用相同的分页参数
+    let (status, body) = client.get("/api/v1/codex-providers?limit=2&page=1").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["data"].as_array().unwrap().len(), 2);
+    assert_eq!(body["data"]["pagination"]["total"], 3);
+
+    let (status, body) = client.get("/api/v1/codex-providers?search=bravo").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let found = body["data"]["data"].as_array().unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0]["name"], "bravo");
+
+    // 无效的搜索词返回400
+    let (status, body) = client.get("/api/v1/codex-providers?search=").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+
+    let (status, body) = client.get("/api/v1/codex-providers/stats").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["total"], 3);
+
+    // 不存在的记录返回404
+    let (status, body) = client.get("/api/v1/codex-providers/999/test").await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{}", body);
+}
+
+#[tokio::test]
+async fn test_delete_last_enabled_codex_provider_requires_force() {
+    let client = ApiTestClient::new().await;
+
+    let mut ids = Vec::new();
+    for index in 0..2 {
+        let (status, created) = client
+            .post(
+                "/api/v1/codex-providers",
+                json!({
+                    "name": format!("删除保护{}", index),
+                    "url": format!("https://api{}.example.com", index),
+                    "token": format!("sk-delete-guard-{}", index),
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+        ids.push(created["data"]["id"].as_i64().unwrap());
+    }
+    let (status, _) = client
+        .put(
+            &format!("/api/v1/codex-providers/{}", ids[0]),
+            json!({ "enabled": 1 }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // 未启用的供应商可以直接删除
+    let (status, _) = client.delete(&format!("/api/v1/codex-providers/{}", ids[1])).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // 唯一启用的供应商需要强制删除
+    let (status, body) = client.delete(&format!("/api/v1/codex-providers/{}", ids[0])).await;
+    assert_eq!(status, StatusCode::CONFLICT, "{}", body);
+    assert_eq!(body["error"]["code"], "BUSINESS_RULE_VIOLATION");
+    let (status, _) = client.get(&format!("/api/v1/codex-providers/{}", ids[0])).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) =
+        client.delete(&format!("/api/v1/codex-providers/{}?force=true", ids[0])).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, _) = client.get(&format!("/api/v1/codex-providers/{}", ids[0])).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_codex_provider_custom_headers_round_trip() {
+    let client = ApiTestClient::new().await;
+
+    let (status, created) = client
+        .post(
+            "/api/v1/codex-providers",
+            json!({
+                "name": "带请求头的供应商",
+                "url": "https://relay.example.com",
+                "token": "sk-codex-headers",
+                "custom_headers": { "X-Relay-Key": "relay-secret" },
+                "accepted_status_codes": [200, 401],
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", created);
+    let id = created["data"]["id"].as_i64().unwrap();
+
+    let (status, body) = client.get(&format!("/api/v1/codex-providers/{}", id)).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(
+        body["data"]["custom_headers"],
+        json!({ "X-Relay-Key": "relay-secret" })
+    );
+    assert_eq!(body["data"]["accepted_status_codes"], json!([200, 401]));
+
+    // 备份后导入到新数据库，请求头和状态码不丢失
+    let tool = DataMigrationTool::new(client.db_manager().clone(), DEFAULT_ENCRYPTION_KEY)
+        .await
+        .unwrap();
+    let exported = tool.export_to_json(&ExportFilter::default()).await.unwrap();
+
+    let restored = ApiTestClient::new().await;
+    let report = restored.seed(&exported).await;
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+    let (status, body) = restored.get("/api/v1/codex-providers").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let provider = &body["data"]["data"][0];
+    assert_eq!(
+        provider["custom_headers"],
+        json!({ "X-Relay-Key": "relay-secret" })
+    );
+    assert_eq!(provider["accepted_status_codes"], json!([200, 401]));
+
+    // 非ASCII的请求头值无法发送，创建时直接拒绝
+    let (status, body) = client
+        .post(
+            "/api/v1/codex-providers",
+            json!({
+                "name": "非法请求头",
+                "url": "https://relay2.example.com",
+                "token": "sk-codex-bad-header",
+                "custom_headers": { "X-Region": "华东" },
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+}
@@ -3,8 +3,268 @@
 // 测试完整的通用配置管理API工作流程
 // 验证所有CRUD操作、批量更新和业务逻辑
 
+use axum::http::StatusCode;
+use migration_ai_manager_lib::api::server::{ApiServerConfig, DEFAULT_ENCRYPTION_KEY};
+use migration_ai_manager_lib::api::testing::ApiTestClient;
+use migration_ai_manager_lib::crypto::CryptoService;
+use migration_ai_manager_lib::models::CreateCommonConfigRequest;
 use reqwest;
 use serde_json::{json, Value};
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn test_common_config_in_process_router() {
+    let client = ApiTestClient::new().await;
+
+    let (status, _) = client.get("/health").await;
+    assert_eq!(status, StatusCode::OK);
+
+    // 无需认证的详细健康检查不返回密钥指纹
+    let (status, health) = client.get("/health/detailed").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(health["crypto_enabled"], true);
+    assert!(health.get("key_fingerprint").is_none());
+
+    let (status, created) = client
+        .create_common_config(&CreateCommonConfigRequest {
+            key: "in_process.key".to_string(),
+            value: "in-process".to_string(),
+            description: None,
+            category: Some("test".to_string()),
+            is_active: None,
+        })
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let id = created.data.unwrap().id;
+
+    let (status, fetched) = client.get(&format!("/api/v1/common-configs/{}", id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched["data"]["value"], "in-process");
+}
+
+#[tokio::test]
+async fn test_common_config_streaming_list() {
+    let client = ApiTestClient::new().await;
+    let total = 3000;
+
+    let mut tx = client.db_manager().pool().begin().await.unwrap();
+    for i in 0..total {
+        sqlx::query(
+            "INSERT INTO common_configs (key, value, category, is_active) VALUES (?, ?, 'stream_test', 1)",
+        )
+        .bind(format!("stream.key.{:05}", i))
+        .bind(format!("value \"{}\"\n", i))
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+    }
+    tx.commit().await.unwrap();
+
+    let (status, headers, body) = client
+        .send_with_headers(
+            axum::http::Method::GET,
+            "/api/v1/common-configs?stream=true&category=stream_test",
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers["content-type"], "application/json");
+    assert_eq!(body["success"], true);
+
+    // 与默认列表接口的信封相同，全部结果作为一页
+    let data = body["data"]["data"].as_array().expect("流式响应不是合法的JSON");
+    assert_eq!(data.len(), total);
+    assert_eq!(body["data"]["pagination"]["total"], total);
+    assert_eq!(body["data"]["pagination"]["total_pages"], 1);
+    let (_, paged) = client.get("/api/v1/common-configs?category=stream_test&limit=1").await;
+    let keys = |value: &serde_json::Value| {
+        let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+        keys.sort();
+        keys
+    };
+    assert_eq!(keys(&body), keys(&paged));
+    assert_eq!(keys(&body["data"]), keys(&paged["data"]));
+    assert_eq!(data[0]["key"], "stream.key.00000");
+    assert_eq!(
+        data[total - 1]["value"],
+        format!("value \"{}\"\n", total - 1)
+    );
+
+    // 不带筛选条件时包含全部配置
+    let (_, body) = client.get("/api/v1/common-configs?stream=true").await;
+    assert!(body["data"]["data"].as_array().unwrap().len() >= total);
+
+    // 没有结果时仍然是完整的响应
+    let (_, body) = client.get("/api/v1/common-configs?stream=true&category=missing").await;
+    assert_eq!(body["data"]["data"], json!([]));
+    assert_eq!(body["data"]["pagination"]["total"], 0);
+
+    let (status, _) = client.get("/api/v1/common-configs?stream=true&search=stream").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_common_config_key_length_limit() {
+    use migration_ai_manager_lib::models::MAX_CONFIG_KEY_LENGTH;
+
+    let client = ApiTestClient::new().await;
+
+    // 恰好等于上限时允许创建
+    let (status, _) = client
+        .post(
+            "/api/v1/common-configs",
+            json!({ "key": "k".repeat(MAX_CONFIG_KEY_LENGTH), "value": "v" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // 超出一个字符时在写入数据库前返回验证错误
+    let (status, body) = client
+        .post(
+            "/api/v1/common-configs",
+            json!({ "key": "键".repeat(MAX_CONFIG_KEY_LENGTH + 1), "value": "v" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+
+    let detail = &body["error"]["details"]["errors"][0];
+    assert_eq!(detail["field"], "配置键名");
+    assert_eq!(detail["max"], MAX_CONFIG_KEY_LENGTH);
+    assert_eq!(detail["actual"], MAX_CONFIG_KEY_LENGTH + 1);
+}
+
+#[tokio::test]
+async fn test_common_config_rejects_invalid_secret_patterns() {
+    let client = ApiTestClient::new().await;
+
+    let (status, body) = client
+        .post(
+            "/api/v1/common-configs",
+            json!({ "key": "redaction.patterns", "value": r#"["corp_(unclosed"]"# }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+
+    let (status, body) = client
+        .post(
+            "/api/v1/common-configs",
+            json!({ "key": "redaction.patterns", "value": r#"["corp_[A-Za-z0-9]{12,}"]"# }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let id = body["data"]["id"].as_i64().unwrap();
+
+    let (status, body) = client
+        .put(
+            &format!("/api/v1/common-configs/{}", id),
+            json!({ "value": "not json" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(body["error"]["code"], "VALIDATION_ERROR");
+}
+
+#[tokio::test]
+async fn test_common_config_typed_values() {
+    let client = ApiTestClient::new().await;
+
+    let cases = [
+        ("max_items", "42", json!(42)),
+        ("auto_backup", "true", json!(true)),
+        ("ratio", "0.5", json!(0.5)),
+        ("layout", r#"{"columns":2}"#, json!({ "columns": 2 })),
+        ("theme", "dark", json!("dark")),
+        // 内置默认配置声明为integer，存储的值无效时按字符串返回
+        ("request_timeout", "abc", json!("abc")),
+    ];
+
+    let mut ids = Vec::new();
+    for (key, value, _) in &cases {
+        let (status, created) = client
+            .post(
+                "/api/v1/common-configs",
+                json!({ "key": key, "value": value }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+        ids.push(created["data"]["id"].as_i64().unwrap());
+    }
+
+    for ((key, value, expected), id) in cases.iter().zip(&ids) {
+        let (status, typed) =
+            client.get(&format!("/api/v1/common-configs/{}?typed=true", id)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(&typed["data"]["value"], expected, "key: {}", key);
+
+        let (_, by_key) =
+            client.get(&format!("/api/v1/common-configs/key/{}?typed=true", key)).await;
+        assert_eq!(&by_key["data"]["value"], expected, "key: {}", key);
+
+        // 默认保持字符串输出
+        let (_, plain) = client.get(&format!("/api/v1/common-configs/{}", id)).await;
+        assert_eq!(plain["data"]["value"], json!(value));
+    }
+
+    let (status, list) = client.get("/api/v1/common-configs?typed=true&limit=50").await;
+    assert_eq!(status, StatusCode::OK);
+    let items = list["data"]["data"].as_array().unwrap();
+    let ratio = items.iter().find(|item| item["key"] == "ratio").unwrap();
+    assert_eq!(ratio["value"], json!(0.5));
+}
+
+#[tokio::test]
+async fn test_common_config_batch_update_modes() {
+    let client = ApiTestClient::new().await;
+
+    let (status, created) = client
+        .post(
+            "/api/v1/common-configs",
+            json!({ "key": "batch.mode", "value": "original" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", created);
+    let id = created["data"]["id"].as_i64().unwrap();
+    let missing_id = id + 10_000;
+
+    let request = json!({
+        "configs": [
+            { "id": id, "value": "changed", "enabled": 0 },
+            { "id": missing_id, "value": "ignored" }
+        ]
+    });
+
+    // 默认整体执行：存在失败项时不应用任何修改
+    let (status, body) = client.post("/api/v1/common-configs/batch", request.clone()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(body["success"], false);
+    assert_eq!(body["data"]["updated"], json!([]));
+    let failed = body["data"]["failed"].as_array().unwrap();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0]["id"], missing_id);
+    assert!(failed[0]["error"].as_str().unwrap().contains("不存在"));
+
+    let (_, fetched) = client.get(&format!("/api/v1/common-configs/{}", id)).await;
+    assert_eq!(fetched["data"]["value"], "original");
+    assert_eq!(fetched["data"]["is_active"], 1);
+
+    // 部分提交：有效项生效，失败项单独报告
+    let (status, body) = client.post("/api/v1/common-configs/batch?partial=true", request).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["success"], true);
+    let updated = body["data"]["updated"].as_array().unwrap();
+    assert_eq!(updated.len(), 1);
+    assert_eq!(updated[0]["id"], id);
+    assert_eq!(updated[0]["value"], "changed");
+    let failed = body["data"]["failed"].as_array().unwrap();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0]["id"], missing_id);
+
+    let (_, fetched) = client.get(&format!("/api/v1/common-configs/{}", id)).await;
+    assert_eq!(fetched["data"]["value"], "changed");
+    assert_eq!(fetched["data"]["is_active"], 0);
+}
 
 #[tokio::test]
 async fn test_common_config_complete_workflow() {
@@ -151,7 +411,7 @@ async fn test_common_config_complete_workflow() {
     let update_data: Value = update_response.json().await.expect("解析更新响应失败");
     assert!(update_data["success"].as_bool().unwrap());
     assert_eq!(
-        update_data["data"]["value"].as_str().unwrap(),
+        update_data["data"]["entity"]["value"].as_str().unwrap(),
         "AI Manager Pro"
     );
 
@@ -472,8 +732,8 @@ async fn test_common_config_encryption() {
     let get_data: Value = get_response.json().await.expect("解析获取加密配置响应失败");
     assert!(get_data["success"].as_bool().unwrap());
     assert_eq!(get_data["data"]["is_encrypted"].as_i64().unwrap(), 1);
-    // 加密的值不应该与原始值相同
-    assert_ne!(
+    // 数据库中加密保存，API返回解密后的值
+    assert_eq!(
         get_data["data"]["value"].as_str().unwrap(),
         "sk-1234567890abcdef"
     );
@@ -488,6 +748,82 @@ async fn test_common_config_encryption() {
     assert_eq!(delete_response.status(), 200);
 }
 
+/// 读取数据库中保存的原始配置值
+async fn stored_value(client: &ApiTestClient, id: i64) -> String {
+    sqlx::query_scalar::<_, String>("SELECT value FROM common_configs WHERE id = ?")
+        .bind(id)
+        .fetch_one(client.db_manager().pool())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_common_config_category_key_round_trip() {
+    let database_key = "U82WvQEOw4doHujpEVjaPgKOY-rxD8J6GwVvG0LOeqE=";
+    let client = ApiTestClient::with_config(ApiServerConfig {
+        category_encryption_keys: HashMap::from([(
+            "database".to_string(),
+            database_key.to_string(),
+        )]),
+        ..Default::default()
+    })
+    .await;
+
+    let database_crypto = CryptoService::new(database_key).unwrap();
+    let default_crypto = CryptoService::new(DEFAULT_ENCRYPTION_KEY).unwrap();
+
+    let (status, body) = client
+        .post(
+            "/api/v1/common-configs",
+            json!({ "key": "database.password", "value": "db-secret", "category": "database" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let id = body["data"]["id"].as_i64().unwrap();
+
+    // 数据库中保存类别密钥加密后的值
+    let stored = stored_value(&client, id).await;
+    assert_ne!(stored, "db-secret");
+    assert_eq!(database_crypto.decrypt(&stored).unwrap(), "db-secret");
+    assert!(default_crypto.decrypt(&stored).is_err());
+
+    // 按ID、按key和列表读取都返回明文
+    let (status, body) = client.get(&format!("/api/v1/common-configs/{}", id)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["value"], "db-secret");
+
+    let (status, body) = client.get("/api/v1/common-configs/key/database.password").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["value"], "db-secret");
+
+    let (status, body) = client.get("/api/v1/common-configs?category=database").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["data"][0]["value"], "db-secret");
+
+    // 更新后仍使用类别密钥加密
+    let (status, body) = client
+        .put(
+            &format!("/api/v1/common-configs/{}", id),
+            json!({ "value": "db-secret-2" }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let stored = stored_value(&client, id).await;
+    assert_eq!(database_crypto.decrypt(&stored).unwrap(), "db-secret-2");
+
+    // 批量更新返回明文，存储值加密
+    let (status, body) = client
+        .post(
+            "/api/v1/common-configs/batch",
+            json!({ "configs": [{ "id": id, "value": "db-secret-3" }] }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["updated"][0]["value"], "db-secret-3");
+    let stored = stored_value(&client, id).await;
+    assert_eq!(database_crypto.decrypt(&stored).unwrap(), "db-secret-3");
+}
+
 #[tokio::test]
 async fn test_common_config_validation_errors() {
     let base_url = "http://localhost:8080/api/v1";
@@ -0,0 +1,85 @@
+// 配置预览API集成测试
+//
+// 验证预览内容来自当前启用的供应商，并且不会写入配置文件
+
+use axum::http::StatusCode;
+use migration_ai_manager_lib::api::server::ApiServerConfig;
+use migration_ai_manager_lib::api::testing::ApiTestClient;
+use migration_ai_manager_lib::migration::config_generator::{ConfigGenerator, GeneratedConfigKind};
+use migration_ai_manager_lib::utils::redaction::REDACTED;
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn test_claude_config_preview_reflects_enabled_provider() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let generator = ConfigGenerator::with_dirs(
+        temp_dir.path().join(".claude"),
+        temp_dir.path().join(".codex"),
+    );
+    let client = ApiTestClient::with_config(ApiServerConfig {
+        config_generator: Some(generator.clone()),
+        ..Default::default()
+    })
+    .await;
+
+    // 没有启用的供应商时提示先启用
+    let (status, body) = client.get("/api/v1/config/claude").await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{}", body);
+    assert!(
+        body["error"]["message"].as_str().unwrap().contains("启用"),
+        "{}",
+        body
+    );
+
+    let mut ids = Vec::new();
+    for (name, url) in [
+        ("预览备用", "https://backup.example.com"),
+        ("预览主用", "https://primary.example.com"),
+    ] {
+        let (status, created) = client
+            .post(
+                "/api/v1/claude-providers",
+                json!({ "name": name, "url": url, "token": format!("sk-ant-{}", url.len()) }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+        ids.push(created["data"]["id"].as_i64().unwrap());
+    }
+    let (status, _) = client
+        .post(
+            &format!("/api/v1/claude-providers/{}/enable", ids[1]),
+            json!({}),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = client.get("/api/v1/config/claude").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let data = &body["data"];
+    assert_eq!(data["provider_id"], ids[1]);
+    assert_eq!(data["provider_name"], "预览主用");
+    assert_eq!(data["token_revealed"], false);
+    assert_eq!(data["files"][0]["kind"], "claude_settings");
+    let settings: Value =
+        serde_json::from_str(data["files"][0]["content"].as_str().unwrap()).unwrap();
+    assert_eq!(
+        settings["env"]["ANTHROPIC_BASE_URL"],
+        "https://primary.example.com"
+    );
+    assert_eq!(settings["env"]["ANTHROPIC_AUTH_TOKEN"], REDACTED);
+
+    let (status, body) = client.get("/api/v1/config/claude?reveal=true").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let settings: Value =
+        serde_json::from_str(body["data"]["files"][0]["content"].as_str().unwrap()).unwrap();
+    assert_eq!(settings["env"]["ANTHROPIC_AUTH_TOKEN"], "sk-ant-27");
+
+    // 预览不会写入配置文件
+    assert!(generator
+        .read_config_file(GeneratedConfigKind::ClaudeSettings)
+        .unwrap()
+        .is_none());
+
+    let (status, _) = client.get("/api/v1/config/unknown").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
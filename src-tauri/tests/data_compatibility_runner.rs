@@ -358,6 +358,7 @@ impl DataCompatibilityTestRunner {
             connect_timeout: Duration::from_secs(30),
             idle_timeout: Duration::from_secs(60),
             max_lifetime: Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = DatabaseManager::new(db_config).await?;
@@ -4,6 +4,7 @@
 
 #![allow(dead_code)]
 
+use migration_ai_manager_lib::crypto::CryptoService;
 use migration_ai_manager_lib::database::DatabaseManager;
 use migration_ai_manager_lib::migration_tool::PythonExportData;
 use sqlx::Row;
@@ -558,7 +559,7 @@ impl DataIntegrityValidator {
 
                 for row in rows {
                     let token: String = row.get("token");
-                    if token.starts_with("gAAAA") {
+                    if CryptoService::is_fernet_token(&token) {
                         encrypted_count += 1;
                     }
                 }
@@ -603,7 +604,7 @@ impl DataIntegrityValidator {
 
                 for row in rows {
                     let token: String = row.get("token");
-                    if token.starts_with("gAAAA") {
+                    if CryptoService::is_fernet_token(&token) {
                         encrypted_count += 1;
                     }
                 }
@@ -715,6 +716,7 @@ mod tests {
             connect_timeout: Duration::from_secs(10),
             idle_timeout: Duration::from_secs(60),
             max_lifetime: Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = DatabaseManager::new(config).await.unwrap();
@@ -4,7 +4,7 @@
 
 use migration_ai_manager_lib::crypto::{python_compatibility, CryptoService};
 use migration_ai_manager_lib::database::{DatabaseConfig, DatabaseManager};
-use migration_ai_manager_lib::migration_tool::DataMigrationTool;
+use migration_ai_manager_lib::migration_tool::{DataMigrationTool, ExportFilter};
 use serde_json;
 use std::time::Duration;
 use tempfile::tempdir;
@@ -123,6 +123,7 @@ async fn test_migration_with_encrypted_data() {
         connect_timeout: Duration::from_secs(10),
         idle_timeout: Duration::from_secs(60),
         max_lifetime: Duration::from_secs(300),
+        ..Default::default()
     };
 
     let db_manager = DatabaseManager::new(config).await.expect("数据库管理器创建失败");
@@ -152,7 +153,10 @@ async fn test_migration_with_encrypted_data() {
     assert!(import_report.total_migrated > 0, "应该有数据被迁移");
 
     // 导出并验证数据能正确解密
-    let exported_data = migration_tool.export_to_json().await.expect("数据导出应该成功");
+    let exported_data = migration_tool
+        .export_to_json(&ExportFilter::default())
+        .await
+        .expect("数据导出应该成功");
 
     // 验证token被正确解密
     for provider in &exported_data.claude_providers {
@@ -0,0 +1,195 @@
+// 导出格式转换测试
+//
+// 旧版本导出数据逐级转换为当前格式后，应能直接通过 `import_from_json` 导入
+
+use axum::http::StatusCode;
+use migration_ai_manager_lib::api::server::DEFAULT_ENCRYPTION_KEY;
+use migration_ai_manager_lib::api::testing::ApiTestClient;
+use migration_ai_manager_lib::migration::{convert_export, convert_to_current, SchemaVersion};
+use migration_ai_manager_lib::migration_tool::{DataMigrationTool, EXPORT_SCHEMA_VERSION};
+use serde_json::{json, Value};
+
+/// Python 1.0 导出：按秒保存的超时时间、字符串形式的 args、free 类型
+fn python_v1_export() -> Value {
+    json!({
+        "version": "1.0.0",
+        "claude_providers": [
+            {
+                "id": 1,
+                "name": "免费站点",
+                "url": "https://free.example.com",
+                "token": "sk-ant-free",
+                "timeout": 30,
+                "auto_update": 1,
+                "type": "free",
+                "enabled": 1
+            }
+        ],
+        "codex_providers": [
+            {
+                "id": 1,
+                "name": "Codex官方",
+                "url": "https://api.openai.com",
+                "token": "sk-codex",
+                "type": "paid",
+                "enabled": 0
+            }
+        ],
+        "agent_guides": [
+            { "id": 1, "name": "默认指导", "type": "only", "text": "使用中文注释" }
+        ],
+        "mcp_servers": [
+            {
+                "id": 1,
+                "name": "filesystem",
+                "type": "stdio",
+                "timeout": 30,
+                "command": "npx",
+                "args": "[\"-y\", \"@modelcontextprotocol/server-filesystem\", \"/tmp\"]",
+                "env": null
+            },
+            {
+                "id": 2,
+                "name": "github",
+                "type": "stdio",
+                "timeout": null,
+                "command": "uvx",
+                "args": "mcp-server-github --read-only",
+                "env": { "GITHUB_TOKEN": "ghp_example" }
+            }
+        ],
+        "common_configs": [
+            { "id": 1, "key": "proxy.http", "value": "http://127.0.0.1:7890", "is_active": 1 }
+        ]
+    })
+}
+
+#[test]
+fn test_convert_python_v1_export_to_current() {
+    let export = python_v1_export();
+    assert_eq!(
+        SchemaVersion::detect(&export).unwrap(),
+        SchemaVersion::PYTHON
+    );
+
+    let converted = convert_to_current(export).unwrap();
+
+    assert_eq!(converted["schema_version"], EXPORT_SCHEMA_VERSION);
+    assert_eq!(converted["claude_providers"][0]["type"], "public_welfare");
+    assert_eq!(converted["claude_providers"][0]["timeout"], 30_000);
+    assert_eq!(converted["codex_providers"][0]["type"], "paid");
+    assert_eq!(converted["agent_guides"][0]["type"], "only");
+    assert_eq!(
+        converted["mcp_servers"][0]["args"],
+        json!(["-y", "@modelcontextprotocol/server-filesystem", "/tmp"])
+    );
+    assert_eq!(
+        converted["mcp_servers"][1]["args"],
+        json!(["mcp-server-github", "--read-only"])
+    );
+}
+
+#[test]
+fn test_convert_current_fixture_only_updates_version() {
+    let fixture: Value =
+        serde_json::from_str(include_str!("fixtures/exports/schema_2.1.json")).unwrap();
+
+    let converted = convert_export(
+        fixture.clone(),
+        SchemaVersion::new(2, 1),
+        SchemaVersion::current(),
+    )
+    .unwrap();
+
+    assert_eq!(converted["schema_version"], EXPORT_SCHEMA_VERSION);
+    assert_eq!(
+        converted["metadata"]["schema_version"],
+        EXPORT_SCHEMA_VERSION
+    );
+    for section in [
+        "claude_providers",
+        "codex_providers",
+        "agent_guides",
+        "mcp_servers",
+        "common_configs",
+    ] {
+        assert_eq!(converted[section], fixture[section], "{}", section);
+    }
+
+    // 已是目标版本时原样返回
+    let unchanged = convert_export(
+        converted.clone(),
+        SchemaVersion::current(),
+        SchemaVersion::current(),
+    )
+    .unwrap();
+    assert_eq!(unchanged, converted);
+}
+
+#[tokio::test]
+async fn test_converted_python_v1_export_imports() {
+    let client = ApiTestClient::new().await;
+    let tool = DataMigrationTool::new(client.db_manager().clone(), DEFAULT_ENCRYPTION_KEY)
+        .await
+        .unwrap();
+
+    let converted = convert_to_current(python_v1_export()).unwrap();
+    let report = tool.import_from_json(&converted.to_string()).await.unwrap();
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+    assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+    assert_eq!(report.claude_providers, 1);
+    assert_eq!(report.mcp_servers, 2);
+
+    let (status, providers) = client.get("/api/v1/claude-providers").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(providers["data"]["data"][0]["type"], "public_welfare");
+
+    let (status, servers) = client.get("/api/v1/mcp-servers").await;
+    assert_eq!(status, StatusCode::OK);
+    let servers = servers["data"]["data"].as_array().unwrap();
+    let github = servers.iter().find(|s| s["name"] == "github").unwrap();
+    assert_eq!(github["args"], json!(["mcp-server-github", "--read-only"]));
+}
+
+#[test]
+fn test_convert_cli_writes_converted_file() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let input = temp_dir.path().join("python_export.json");
+    let output = temp_dir.path().join("converted.json");
+
+    let mut export = python_v1_export();
+    export["mcp_servers"][1]["args"] = json!(r#"mcp-server-github --root "/My Docs""#);
+    std::fs::write(&input, export.to_string()).unwrap();
+
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_migration_tool"))
+        .args(["convert", "-i"])
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let converted: Value =
+        serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+    assert_eq!(converted["schema_version"], EXPORT_SCHEMA_VERSION);
+    assert_eq!(converted["claude_providers"][0]["timeout"], 30_000);
+    assert_eq!(
+        converted["mcp_servers"][1]["args"],
+        json!(["mcp-server-github", "--root", "/My Docs"])
+    );
+
+    // 引号未闭合时转换失败，不写入结果
+    export["mcp_servers"][1]["args"] = json!(r#"mcp-server-github --root "/My Docs"#);
+    std::fs::write(&input, export.to_string()).unwrap();
+    std::fs::remove_file(&output).unwrap();
+    let status = std::process::Command::new(env!("CARGO_BIN_EXE_migration_tool"))
+        .args(["convert", "-i"])
+        .arg(&input)
+        .arg("-o")
+        .arg(&output)
+        .status()
+        .unwrap();
+    assert!(!status.success());
+    assert!(!output.exists());
+}
@@ -0,0 +1,147 @@
+// 历史版本导出数据兼容性测试
+//
+// 使用各历史版本导出的样本数据，验证改名字段（见 FIELD_ALIASES）在反序列化后没有丢失。
+// 新增导出格式版本或重命名模型字段时，在 tests/fixtures/exports 中补充对应版本的样本
+
+use axum::http::StatusCode;
+use migration_ai_manager_lib::api::testing::ApiTestClient;
+use migration_ai_manager_lib::migration_tool::PythonExportData;
+use migration_ai_manager_lib::models::{rename_legacy_fields, FIELD_ALIASES};
+use serde_json::Value;
+
+/// 各历史版本的导出样本：(版本说明, 文件内容)
+const FIXTURES: &[(&str, &str)] = &[
+    (
+        "Python 1.0",
+        include_str!("fixtures/exports/python_1.0.json"),
+    ),
+    (
+        "导出格式 2.1",
+        include_str!("fixtures/exports/schema_2.1.json"),
+    ),
+];
+
+const RECORD_SECTIONS: &[&str] = &[
+    "claude_providers",
+    "codex_providers",
+    "agent_guides",
+    "mcp_servers",
+    "common_configs",
+];
+
+/// 样本中的每个字段都必须出现在重新序列化的结果中，旧字段名按改名记录换成当前名称
+fn assert_no_data_loss(version: &str, fixture: &Value, parsed: &Value) {
+    for (key, expected) in fixture.as_object().unwrap() {
+        if RECORD_SECTIONS.contains(&key.as_str()) {
+            continue;
+        }
+        assert_eq!(&parsed[key], expected, "{}: 字段 {} 不一致", version, key);
+    }
+
+    for section in RECORD_SECTIONS {
+        let expected = fixture[section].as_array().unwrap();
+        let actual = parsed[section].as_array().unwrap();
+        assert_eq!(
+            actual.len(),
+            expected.len(),
+            "{}: {} 记录数不一致",
+            version,
+            section
+        );
+
+        for (index, (record, actual)) in expected.iter().zip(actual).enumerate() {
+            let mut record = record.as_object().unwrap().clone();
+            rename_legacy_fields(section, &mut record);
+            for (field, value) in &record {
+                assert_eq!(
+                    &actual[field], value,
+                    "{}: {}[{}].{} 不一致",
+                    version, section, index, field
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_fixtures_deserialize_without_data_loss() {
+    for (version, content) in FIXTURES {
+        let fixture: Value = serde_json::from_str(content).unwrap();
+        let data = PythonExportData::from_versioned_str(content)
+            .unwrap_or_else(|e| panic!("{}: 解析失败: {}", version, e));
+
+        // 所有字段都被识别，没有落入未知字段
+        assert!(data.extra.is_empty(), "{}: {:?}", version, data.extra);
+        assert!(
+            data.claude_providers.iter().all(|p| p.extra.is_empty()),
+            "{}",
+            version
+        );
+        assert!(
+            data.codex_providers.iter().all(|p| p.extra.is_empty()),
+            "{}",
+            version
+        );
+        assert!(
+            data.agent_guides.iter().all(|g| g.extra.is_empty()),
+            "{}",
+            version
+        );
+        assert!(
+            data.mcp_servers.iter().all(|s| s.extra.is_empty()),
+            "{}",
+            version
+        );
+        assert!(
+            data.common_configs.iter().all(|c| c.extra.is_empty()),
+            "{}",
+            version
+        );
+
+        assert_no_data_loss(version, &fixture, &serde_json::to_value(&data).unwrap());
+    }
+}
+
+#[test]
+fn test_every_field_alias_is_covered_by_a_fixture() {
+    for alias in FIELD_ALIASES {
+        let covered = FIXTURES.iter().any(|(_, content)| {
+            let fixture: Value = serde_json::from_str(content).unwrap();
+            fixture[alias.table]
+                .as_array()
+                .is_some_and(|records| records.iter().any(|r| r.get(alias.alias).is_some()))
+        });
+        assert!(
+            covered,
+            "{}.{} 的旧字段名 {} 没有对应的导出样本",
+            alias.table, alias.field, alias.alias
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_fixtures_import_with_current_field_names() {
+    for (version, content) in FIXTURES {
+        let client = ApiTestClient::new().await;
+        let data = PythonExportData::from_versioned_str(content).unwrap();
+        let report = client.seed(&data).await;
+        assert!(report.errors.is_empty(), "{}: {:?}", version, report.errors);
+
+        let (status, list) = client.get("/api/v1/claude-providers").await;
+        assert_eq!(status, StatusCode::OK);
+        let providers = list["data"]["data"].as_array().unwrap();
+        for expected in &data.claude_providers {
+            let provider = providers
+                .iter()
+                .find(|p| p["name"] == expected.name.as_str())
+                .unwrap_or_else(|| panic!("{}: 未导入 {}", version, expected.name));
+            assert_eq!(
+                provider["type"].as_str(),
+                expected.r#type.as_deref(),
+                "{}: {}",
+                version,
+                expected.name
+            );
+        }
+    }
+}
@@ -0,0 +1,303 @@
+// 维护操作API集成测试
+//
+// 验证密钥轮换接口使用最新密钥重新加密所有令牌，刷新接口重新计算统计信息，
+// 以及缺失的时间戳在导入和维护时被补全
+
+use axum::http::StatusCode;
+use migration_ai_manager_lib::api::middleware::MaintenanceMode;
+use migration_ai_manager_lib::api::server::ApiServerConfig;
+use migration_ai_manager_lib::api::testing::ApiTestClient;
+use migration_ai_manager_lib::crypto::testing::generate_test_key;
+use migration_ai_manager_lib::migration_tool::PythonExportData;
+use migration_ai_manager_lib::repositories::base_repository::RepositoryError;
+use migration_ai_manager_lib::repositories::claude_provider_repository::ClaudeProviderRepository;
+use migration_ai_manager_lib::repositories::codex_provider_repository::CodexProviderRepository;
+use migration_ai_manager_lib::repositories::common_config_repository::CommonConfigRepository;
+use migration_ai_manager_lib::repositories::mcp_server_repository::McpServerRepository;
+use migration_ai_manager_lib::*;
+use serde_json::json;
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn test_rotate_tokens_across_two_keys() {
+    let old_key = generate_test_key();
+    let new_key = CryptoService::derive_key_from_password("rotated key").unwrap();
+    let old_crypto = CryptoService::new(&old_key).unwrap();
+    let new_crypto = CryptoService::new(&new_key).unwrap();
+
+    let client = ApiTestClient::with_config(ApiServerConfig {
+        encryption_key: new_key,
+        previous_encryption_keys: vec![old_key],
+        ..Default::default()
+    })
+    .await;
+    let db_manager = client.db_manager();
+    db_manager.ensure_initialized().await.unwrap();
+
+    // 使用旧密钥写入数据
+    ClaudeProviderRepository::new(db_manager, &old_crypto)
+        .create_claude_provider(&CreateClaudeProviderRequest {
+            name: "claude".to_string(),
+            url: "https://api.anthropic.com".to_string(),
+            token: "sk-ant-claude".to_string(),
+            timeout: None,
+            auto_update: None,
+            r#type: None,
+            opus_model: None,
+            sonnet_model: None,
+            haiku_model: None,
+            models: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+            model_auto_update: None,
+        })
+        .await
+        .unwrap();
+    CodexProviderRepository::new(db_manager, &old_crypto)
+        .create_codex_provider(&CreateCodexProviderRequest {
+            name: "codex".to_string(),
+            url: "https://api.openai.com".to_string(),
+            token: "sk-codex".to_string(),
+            r#type: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+        })
+        .await
+        .unwrap();
+    CommonConfigRepository::new(db_manager, &old_crypto)
+        .create_common_config(
+            &CreateCommonConfigRequest {
+                key: "rotation.secret".to_string(),
+                value: old_crypto.encrypt("config-secret").unwrap(),
+                description: None,
+                category: None,
+                is_active: None,
+            },
+            old_crypto.storage_fingerprint().as_deref(),
+        )
+        .await
+        .unwrap();
+    McpServerRepository::new(db_manager, &old_crypto)
+        .create_mcp_server(&CreateMcpServerRequest {
+            name: "mcp".to_string(),
+            r#type: None,
+            timeout: None,
+            command: "npx".to_string(),
+            args: vec![],
+            env: Some(HashMap::from([
+                ("API_KEY".to_string(), "mcp-secret".to_string()),
+                ("MODE".to_string(), "plain".to_string()),
+            ])),
+            secret_env_keys: vec!["API_KEY".to_string()],
+        })
+        .await
+        .unwrap();
+
+    // 轮换前使用新密钥读取，按记录的密钥指纹报告密钥不匹配
+    match ClaudeProviderRepository::new(db_manager, &new_crypto)
+        .list_claude_providers_decrypted()
+        .await
+    {
+        Err(RepositoryError::Crypto(CryptoError::KeyMismatch { expected, found })) => {
+            assert_eq!(expected, new_crypto.key_fingerprint());
+            assert_eq!(found, old_crypto.key_fingerprint());
+        }
+        result => panic!("应返回KeyMismatch: {:?}", result),
+    }
+
+    // 轮换会刷新修改时间，增量迁移据此识别重新加密的记录；
+    // 先删除触发器，验证轮换语句本身刷新修改时间，写入的过去时间也不会被触发器覆盖
+    for statement in [
+        r#"DROP TRIGGER IF EXISTS "update_claude_providers_updated_at""#,
+        "UPDATE claude_providers SET updated_at = '2000-01-01 00:00:00'",
+    ] {
+        sqlx::query(statement).execute(db_manager.pool()).await.unwrap();
+    }
+
+    let (status, body) = client.post("/api/v1/maintenance/rotate-tokens", json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["total_rotated"], 4);
+    assert_eq!(body["data"]["failures"].as_array().unwrap().len(), 0);
+    assert_eq!(
+        body["data"]["key_fingerprint"],
+        new_crypto.key_fingerprint()
+    );
+
+    // 所有值都可以用新密钥解密
+    let pool = db_manager.pool();
+    for (query, expected) in [
+        (
+            "SELECT token, key_fingerprint FROM claude_providers",
+            "sk-ant-claude",
+        ),
+        (
+            "SELECT token, key_fingerprint FROM codex_providers",
+            "sk-codex",
+        ),
+        (
+            "SELECT value, key_fingerprint FROM common_configs WHERE key = 'rotation.secret'",
+            "config-secret",
+        ),
+    ] {
+        let (stored, fingerprint): (String, Option<String>) =
+            sqlx::query_as(query).fetch_one(pool).await.unwrap();
+        assert_eq!(fingerprint, Some(new_crypto.key_fingerprint()));
+        assert_eq!(new_crypto.decrypt(&stored).unwrap(), expected);
+        assert!(old_crypto.decrypt(&stored).is_err());
+    }
+    let updated_at: String = sqlx::query_scalar("SELECT updated_at FROM claude_providers")
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    assert!(
+        updated_at.as_str() > "2000-01-01 00:00:00",
+        "{}",
+        updated_at
+    );
+    let (env, fingerprint): (String, Option<String>) =
+        sqlx::query_as("SELECT env, key_fingerprint FROM mcp_servers")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+    assert_eq!(fingerprint, Some(new_crypto.key_fingerprint()));
+    let env: HashMap<String, String> = serde_json::from_str(&env).unwrap();
+    assert_eq!(new_crypto.decrypt(&env["API_KEY"]).unwrap(), "mcp-secret");
+    assert_eq!(env["MODE"], "plain");
+
+    // 再次轮换不会修改任何记录
+    let (status, body) = client.post("/api/v1/maintenance/rotate-tokens", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["total_rotated"], 0);
+}
+
+#[tokio::test]
+async fn test_rotate_tokens_requires_previous_keys() {
+    let client = ApiTestClient::new().await;
+
+    let (status, _) = client.post("/api/v1/maintenance/rotate-tokens", json!({})).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // 维护模式同一时间只能进入一次
+    let maintenance = MaintenanceMode::default();
+    let guard = maintenance.enter().unwrap();
+    assert!(maintenance.is_active());
+    assert!(maintenance.enter().is_none());
+    // 没有进行中的写请求时立即返回
+    maintenance.wait_for_writes().await;
+    drop(guard);
+    assert!(!maintenance.is_active());
+}
+
+#[tokio::test]
+async fn test_refresh_recomputes_table_stats() {
+    let client = ApiTestClient::new().await;
+    for index in 0..3 {
+        let (status, _) = client
+            .create_claude_provider(&CreateClaudeProviderRequest {
+                name: format!("claude-{}", index),
+                url: "https://api.anthropic.com".to_string(),
+                token: format!("sk-ant-refresh-{}", index),
+                timeout: None,
+                auto_update: None,
+                r#type: None,
+                opus_model: None,
+                sonnet_model: None,
+                haiku_model: None,
+                models: None,
+                custom_headers: None,
+                accepted_status_codes: None,
+                model_auto_update: None,
+            })
+            .await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    // 手动写入错误的缓存记录数
+    let pool = client.db_manager().pool();
+    sqlx::query("ANALYZE").execute(pool).await.unwrap();
+    sqlx::query("UPDATE sqlite_stat1 SET stat = '999 1' WHERE tbl = 'claude_providers'")
+        .execute(pool)
+        .await
+        .unwrap();
+
+    let (status, body) = client.post("/api/v1/maintenance/refresh", json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let tables = body["data"]["tables"].as_array().unwrap();
+    let claude = tables.iter().find(|t| t["table"] == "claude_providers").unwrap();
+    assert_eq!(claude["row_count"], 3);
+    assert!(body["data"]["duration_ms"].is_u64());
+
+    let stats: Vec<String> =
+        sqlx::query_scalar("SELECT stat FROM sqlite_stat1 WHERE tbl = 'claude_providers'")
+            .fetch_all(pool)
+            .await
+            .unwrap();
+    assert!(!stats.is_empty());
+    for stat in stats {
+        assert_eq!(stat.split(' ').next(), Some("3"), "{}", stat);
+    }
+}
+
+#[tokio::test]
+async fn test_imported_rows_without_timestamps_are_backfilled() {
+    let client = ApiTestClient::new().await;
+    let data = PythonExportData::from_versioned_value(json!({
+        "version": "1.0",
+        "claude_providers": [{
+            "name": "无时间戳",
+            "url": "https://api.anthropic.com",
+            "token": "sk-ant-no-timestamps",
+            "created_at": null,
+            "updated_at": null,
+        }],
+        "codex_providers": [{
+            "name": "无时间戳",
+            "url": "https://api.openai.com",
+            "token": "sk-no-timestamps",
+            "created_at": null,
+            "updated_at": null,
+        }],
+        "agent_guides": [],
+        "mcp_servers": [],
+        "common_configs": [],
+    }))
+    .unwrap();
+    let report = client.seed(&data).await;
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+    let pool = client.db_manager().pool();
+    let missing = |table: &str| {
+        format!(
+            "SELECT COUNT(*) FROM {} WHERE created_at IS NULL OR updated_at IS NULL",
+            table
+        )
+    };
+    for table in ["claude_providers", "codex_providers"] {
+        let count: i64 = sqlx::query_scalar(&missing(table)).fetch_one(pool).await.unwrap();
+        assert_eq!(count, 0, "导入后 {} 仍有缺失的时间戳", table);
+    }
+
+    // 旧版本直接写入的记录时间戳为空
+    for name in ["guide-1", "guide-2"] {
+        sqlx::query(
+            "INSERT INTO agent_guides (name, type, text, created_at, updated_at)
+             VALUES (?, 'only', 'text', NULL, NULL)",
+        )
+        .bind(name)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    let (status, body) = client.post("/api/v1/maintenance/backfill-timestamps", json!({})).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["tables"], json!({ "agent_guides": 2 }));
+
+    let count: i64 = sqlx::query_scalar(&missing("agent_guides")).fetch_one(pool).await.unwrap();
+    assert_eq!(count, 0);
+
+    // 再次执行没有需要补全的记录
+    let (status, body) = client.post("/api/v1/maintenance/backfill-timestamps", json!({})).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["tables"], json!({}));
+}
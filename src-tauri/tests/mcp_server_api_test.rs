@@ -3,6 +3,8 @@
 // 测试完整的MCP服务器管理API工作流程
 // 验证所有CRUD操作和业务逻辑
 
+use axum::http::StatusCode;
+use migration_ai_manager_lib::api::testing::ApiTestClient;
 use reqwest;
 use serde_json::{json, Value};
 
@@ -143,10 +145,13 @@ async fn test_mcp_server_complete_workflow() {
     let stdio_update_data: Value = stdio_update_response.json().await.expect("解析更新响应失败");
     assert!(stdio_update_data["success"].as_bool().unwrap());
     assert_eq!(
-        stdio_update_data["data"]["name"].as_str().unwrap(),
+        stdio_update_data["data"]["entity"]["name"].as_str().unwrap(),
         "集成测试STDIO服务器-已更新"
     );
-    assert_eq!(stdio_update_data["data"]["enabled"].as_i64().unwrap(), 0);
+    assert_eq!(
+        stdio_update_data["data"]["entity"]["enabled"].as_i64().unwrap(),
+        0
+    );
 
     // 8. 测试搜索功能
     let search_response = client
@@ -515,3 +520,47 @@ async fn test_mcp_server_not_found_errors() {
     let data: Value = response.json().await.expect("解析测试不存在错误响应失败");
     assert!(!data["success"].as_bool().unwrap());
 }
+
+#[tokio::test]
+async fn test_mcp_server_search_pagination_and_stats() {
+    let client = ApiTestClient::new().await;
+    for (index, name) in ["alpha", "bravo", "charlie"].iter().enumerate() {
+        let (status, created) = client
+            .post(
+                "/api/v1/mcp-servers",
+                json!({
+                    "name": name,
+                    "type": "stdio",
+                    "timeout": 30000 + index as i64,
+                    "command": "npx",
+                    "args": [],
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+    }
+
+    // 与Claude供应商使用相同的分页参数
+    let (status, body) = client.get("/api/v1/mcp-servers?limit=2&page=1").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["data"].as_array().unwrap().len(), 2);
+    assert_eq!(body["data"]["pagination"]["total"], 3);
+
+    let (status, body) = client.get("/api/v1/mcp-servers?search=bravo").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let found = body["data"]["data"].as_array().unwrap();
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0]["name"], "bravo");
+
+    // 无效的搜索词返回400
+    let (status, body) = client.get("/api/v1/mcp-servers?search=").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+
+    let (status, body) = client.get("/api/v1/mcp-servers/stats").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["data"]["total"], 3);
+
+    // 不存在的记录返回404
+    let (status, body) = client.get("/api/v1/mcp-servers/999/test").await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{}", body);
+}
@@ -12,8 +12,12 @@ use axum::{
 };
 use migration_ai_manager_lib::{
     api::error::ApiError,
-    api::middleware::{add_request_id_header, global_error_handler, request_tracking_middleware},
+    api::middleware::{
+        add_request_id_header, global_error_handler, request_timeout_middleware,
+        request_tracking_middleware, RequestTimeoutConfig,
+    },
 };
+use std::time::Duration;
 use tower::ServiceExt;
 
 fn create_test_app() -> Router {
@@ -101,3 +105,125 @@ async fn test_middleware_integration() {
     let body_str = String::from_utf8(body_bytes.to_vec()).unwrap();
     assert_eq!(body_str, "Middleware Test Success");
 }
+
+fn create_timeout_test_app() -> Router {
+    let slow_handler = || async {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        "slow"
+    };
+
+    Router::new()
+        .route("/slow", get(slow_handler))
+        .route("/api/v1/migration/run", get(slow_handler))
+        .layer(middleware::from_fn_with_state(
+            RequestTimeoutConfig::new(
+                Duration::from_millis(50),
+                vec!["/api/v1/migration".to_string()],
+            ),
+            request_timeout_middleware,
+        ))
+}
+
+#[tokio::test]
+async fn test_request_timeout_returns_504() {
+    let app = create_timeout_test_app();
+
+    let request = Request::builder().uri("/slow").method(Method::GET).body(Body::empty()).unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+    let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+    assert_eq!(body["success"], false);
+    assert_eq!(body["error"]["code"], "GATEWAY_TIMEOUT");
+}
+
+#[tokio::test]
+async fn test_request_timeout_exempt_path() {
+    let app = create_timeout_test_app();
+
+    let request = Request::builder()
+        .uri("/api/v1/migration/run")
+        .method(Method::GET)
+        .body(Body::empty())
+        .unwrap();
+
+    let response = app.oneshot(request).await.unwrap();
+
+    // 豁免路径不受超时限制
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[test]
+fn test_request_timeout_exempt_segment_pattern() {
+    let config = RequestTimeoutConfig::new(
+        Duration::from_secs(1),
+        vec![
+            "/api/v1/migration".to_string(),
+            "/api/v1/claude-providers/*/test".to_string(),
+        ],
+    );
+
+    assert!(config.is_exempt("/api/v1/migration/import"));
+    assert!(config.is_exempt("/api/v1/claude-providers/12/test"));
+    assert!(!config.is_exempt("/api/v1/claude-providers/12"));
+    assert!(!config.is_exempt("/api/v1/claude-providers/12/test/extra"));
+    assert!(!config.is_exempt("/api/v1/codex-providers/12/test"));
+}
+
+#[tokio::test]
+async fn test_request_id_propagates_to_repository_logs() {
+    use migration_ai_manager_lib::api::testing::ApiTestClient;
+    use serde_json::json;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    /// 收集日志输出
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let client = ApiTestClient::new().await;
+
+    let captured = Captured::default();
+    let output = captured.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || output.clone())
+        .with_ansi(false)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let (status, headers, _) = client
+        .send_with_headers(
+            Method::POST,
+            "/api/v1/claude-providers",
+            Some(json!({
+                "name": "span-test",
+                "url": "https://api.anthropic.com",
+                "token": "sk-ant-span-test"
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK);
+    let request_id = headers["x-request-id"].to_str().unwrap().to_string();
+
+    let logs = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+    // Repository层的日志带有同一个请求ID
+    let repository_line = logs
+        .lines()
+        .find(|line| line.contains("创建Claude供应商") && line.contains("repositories"))
+        .unwrap_or_else(|| panic!("{}", logs));
+    assert!(repository_line.contains(&request_id), "{}", repository_line);
+}
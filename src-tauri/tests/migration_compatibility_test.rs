@@ -4,7 +4,7 @@
 
 use migration_ai_manager_lib::crypto::{python_compatibility, CryptoService};
 use migration_ai_manager_lib::database::{DatabaseConfig, DatabaseManager};
-use migration_ai_manager_lib::migration_tool::{DataMigrationTool, PythonExportData};
+use migration_ai_manager_lib::migration_tool::{DataMigrationTool, ExportFilter, PythonExportData};
 use serde_json;
 use sqlx;
 use std::fs;
@@ -32,6 +32,7 @@ impl TestSetup {
             connect_timeout: Duration::from_secs(10),
             idle_timeout: Duration::from_secs(60),
             max_lifetime: Duration::from_secs(300),
+            ..Default::default()
         };
 
         let db_manager = DatabaseManager::new(config).await?;
@@ -85,7 +86,11 @@ async fn test_full_migration_roundtrip() {
     assert!(import_report.total_migrated > 0, "应该有数据被迁移");
 
     // 3. 导出数据
-    let exported_data = setup.migration_tool.export_to_json().await.expect("数据导出应该成功");
+    let exported_data = setup
+        .migration_tool
+        .export_to_json(&ExportFilter::default())
+        .await
+        .expect("数据导出应该成功");
 
     println!("✅ 数据导出完成");
 
@@ -131,7 +136,11 @@ async fn test_encrypted_data_migration() {
     println!("✅ 加密数据导入完成: {:?}", import_report);
 
     // 验证数据能正确解密
-    let exported_data = setup.migration_tool.export_to_json().await.expect("数据导出应该成功");
+    let exported_data = setup
+        .migration_tool
+        .export_to_json(&ExportFilter::default())
+        .await
+        .expect("数据导出应该成功");
 
     // 检查token是否被正确解密
     for provider in &exported_data.claude_providers {
@@ -272,8 +281,12 @@ async fn test_data_validation_rules() {
             opus_model: Some("claude-3-sonnet-20240229".to_string()),
             sonnet_model: None,
             haiku_model: None,
+            custom_headers: Default::default(),
+            accepted_status_codes: Default::default(),
+            model_auto_update: None,
             created_at: None,
             updated_at: None,
+            extra: Default::default(),
         },
     );
 
@@ -318,8 +331,12 @@ fn create_sample_json_data() -> PythonExportData {
                 opus_model: Some("claude-3-opus-20240229".to_string()),
                 sonnet_model: Some("claude-3-sonnet-20240229".to_string()),
                 haiku_model: Some("claude-3-haiku-20240307".to_string()),
+                custom_headers: Default::default(),
+                accepted_status_codes: Default::default(),
+                model_auto_update: None,
                 created_at: None,
                 updated_at: None,
+                extra: Default::default(),
             },
         ],
         codex_providers: vec![
@@ -330,8 +347,11 @@ fn create_sample_json_data() -> PythonExportData {
                 token: "sk-test-openai-key-67890".to_string(),
                 r#type: Some("official".to_string()),
                 enabled: Some(0),
+                custom_headers: Default::default(),
+                accepted_status_codes: Default::default(),
                 created_at: None,
                 updated_at: None,
+                extra: Default::default(),
             },
         ],
         agent_guides: vec![migration_ai_manager_lib::migration_tool::PythonAgentGuide {
@@ -341,6 +361,7 @@ fn create_sample_json_data() -> PythonExportData {
             text: "这是一个测试用的助手指导文本。".to_string(),
             created_at: None,
             updated_at: None,
+            extra: Default::default(),
         }],
         mcp_servers: vec![migration_ai_manager_lib::migration_tool::PythonMcpServer {
             id: None,
@@ -356,8 +377,10 @@ fn create_sample_json_data() -> PythonExportData {
                 "NODE_ENV".to_string(),
                 "production".to_string(),
             )])),
+            secret_env_keys: vec![],
             created_at: None,
             updated_at: None,
+            extra: Default::default(),
         }],
         common_configs: vec![
             migration_ai_manager_lib::migration_tool::PythonCommonConfig {
@@ -369,8 +392,13 @@ fn create_sample_json_data() -> PythonExportData {
                 is_active: Some(1),
                 created_at: None,
                 updated_at: None,
+                extra: Default::default(),
             },
         ],
+        generated_configs: vec![],
+        schema_version: None,
+        metadata: None,
+        extra: Default::default(),
     }
 }
 
@@ -3,8 +3,8 @@
 
 use chrono::Utc;
 use migration_ai_manager_lib::crypto::CryptoService;
+use migration_ai_manager_lib::migration::schema_diff;
 use sqlx::{Row, SqlitePool};
-use std::collections::HashMap;
 
 // 测试数据结构
 #[derive(Debug)]
@@ -51,86 +51,41 @@ impl DataIntegrityValidator {
             "common_configs",
         ];
 
+        let diff = schema_diff(&self.original_db, &self.migrated_db).await?;
         let mut results = Vec::new();
 
         for table in tables {
-            let result = self.verify_single_table_schema(table).await?;
-            results.push(result);
-        }
+            let mut mismatched_fields = Vec::new();
 
-        Ok(results)
-    }
+            if diff.removed_tables.iter().any(|t| t == table) {
+                mismatched_fields.push(format!("迁移后缺少表: {}", table));
+            }
 
-    // 验证单个表的Schema
-    async fn verify_single_table_schema(
-        &self,
-        table_name: &str,
-    ) -> Result<TestDataRecord, Box<dyn std::error::Error>> {
-        let original_schema = self.get_table_schema(&self.original_db, table_name).await?;
-        let migrated_schema = self.get_table_schema(&self.migrated_db, table_name).await?;
-
-        let mut mismatched_fields = Vec::new();
-
-        // 比较字段定义
-        for (field_name, original_def) in &original_schema {
-            match migrated_schema.get(field_name) {
-                Some(migrated_def) => {
-                    if original_def != migrated_def {
-                        mismatched_fields.push(format!(
-                            "字段 {}: 原始 '{}' vs 迁移后 '{}'",
-                            field_name, original_def, migrated_def
-                        ));
-                    }
+            if let Some(table_diff) = diff.table(table) {
+                for column in &table_diff.removed_columns {
+                    mismatched_fields.push(format!("迁移后缺少字段: {}", column.name));
                 }
-                None => {
-                    mismatched_fields.push(format!("迁移后缺少字段: {}", field_name));
+                for column in &table_diff.added_columns {
+                    mismatched_fields.push(format!("新增字段: {}", column.name));
+                }
+                for change in &table_diff.changed_columns {
+                    mismatched_fields.push(format!(
+                        "字段 {}: 原始 '{:?}' vs 迁移后 '{:?}'",
+                        change.name, change.expected, change.actual
+                    ));
                 }
             }
-        }
-
-        // 检查新增字段
-        for field_name in migrated_schema.keys() {
-            if !original_schema.contains_key(field_name) {
-                mismatched_fields.push(format!("新增字段: {}", field_name));
-            }
-        }
 
-        Ok(TestDataRecord {
-            table_name: table_name.to_string(),
-            original_count: 0,
-            migrated_count: 0,
-            mismatched_fields,
-            integrity_issues: Vec::new(),
-        })
-    }
-
-    // 获取表结构信息
-    async fn get_table_schema(
-        &self,
-        pool: &SqlitePool,
-        table_name: &str,
-    ) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
-        let query = format!("PRAGMA table_info({})", table_name);
-
-        let rows = sqlx::query(&query).fetch_all(pool).await?;
-        let mut schema = HashMap::new();
-
-        for row in rows {
-            let name: String = row.get("name");
-            let type_name: String = row.get("type");
-            let not_null: i32 = row.get("notnull");
-            let default_value: Option<String> = row.get("dflt_value");
-            let primary_key: i32 = row.get("pk");
-
-            let def = format!(
-                "TYPE:{} NOT_NULL:{} DEFAULT:{:?} PK:{}",
-                type_name, not_null, default_value, primary_key
-            );
-
-            schema.insert(name, def);
+            results.push(TestDataRecord {
+                table_name: table.to_string(),
+                original_count: 0,
+                migrated_count: 0,
+                mismatched_fields,
+                integrity_issues: Vec::new(),
+            });
         }
 
-        Ok(schema)
+        Ok(results)
     }
 
     // 验证数据行数一致性
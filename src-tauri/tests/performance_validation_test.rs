@@ -39,6 +39,7 @@ async fn test_database_query_performance() -> Result<(), Box<dyn std::error::Err
         connect_timeout: Duration::from_secs(5),
         idle_timeout: Duration::from_secs(180),
         max_lifetime: Duration::from_secs(600),
+        ..Default::default()
     };
 
     let db_manager = DatabaseManager::new(config).await?;
@@ -128,6 +129,7 @@ async fn test_concurrent_performance() -> Result<(), Box<dyn std::error::Error>>
         connect_timeout: Duration::from_secs(5),
         idle_timeout: Duration::from_secs(180),
         max_lifetime: Duration::from_secs(600),
+        ..Default::default()
     };
 
     let db_manager: Arc<DatabaseManager> = Arc::new(DatabaseManager::new(config).await?);
@@ -216,6 +218,7 @@ async fn test_memory_usage() -> Result<(), Box<dyn std::error::Error>> {
         connect_timeout: Duration::from_secs(5),
         idle_timeout: Duration::from_secs(60),
         max_lifetime: Duration::from_secs(300),
+        ..Default::default()
     };
 
     let db_manager = DatabaseManager::new(config).await?;
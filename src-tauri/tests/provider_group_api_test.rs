@@ -0,0 +1,75 @@
+// 供应商分组API集成测试
+//
+// 验证分组的创建、列表和激活接口
+
+use axum::http::{Method, StatusCode};
+use migration_ai_manager_lib::api::server::ApiServerConfig;
+use migration_ai_manager_lib::api::testing::ApiTestClient;
+use migration_ai_manager_lib::migration::config_generator::{ConfigGenerator, GeneratedConfigKind};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_create_list_and_activate_group() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let generator = ConfigGenerator::with_dirs(
+        temp_dir.path().join(".claude"),
+        temp_dir.path().join(".codex"),
+    );
+    let client = ApiTestClient::with_config(ApiServerConfig {
+        config_generator: Some(generator.clone()),
+        ..Default::default()
+    })
+    .await;
+
+    let mut provider_ids = Vec::new();
+    for (path, name) in [
+        ("claude-providers", "分组Claude"),
+        ("codex-providers", "分组Codex"),
+    ] {
+        let (status, created) = client
+            .post(
+                &format!("/api/v1/{}", path),
+                json!({
+                    "name": name,
+                    "url": "https://api.example.com",
+                    "token": "sk-group-api-test-token",
+                }),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK, "{}", created);
+        provider_ids.push(created["data"]["id"].as_i64().unwrap());
+    }
+
+    let (status, group) = client
+        .post(
+            "/api/v1/provider-groups",
+            json!({
+                "name": "客户A",
+                "members": [
+                    { "provider_type": "claude", "provider_id": provider_ids[0] },
+                    { "provider_type": "codex", "provider_id": provider_ids[1] },
+                ],
+            }),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", group);
+    let group_id = group["data"]["id"].as_i64().unwrap();
+
+    let (status, listed) = client.get("/api/v1/provider-groups").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed["data"][0]["members"].as_array().unwrap().len(), 2);
+
+    let (status, activated) = client
+        .send(
+            Method::POST,
+            &format!("/api/v1/provider-groups/{}/activate", group_id),
+            None,
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", activated);
+    assert!(generator.read_config_file(GeneratedConfigKind::CodexConfig).unwrap().is_some());
+
+    let (status, _) =
+        client.send(Method::POST, "/api/v1/provider-groups/9999/activate", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,216 @@
+// 修改时间测试
+//
+// 验证每个实体的更新操作都会刷新 updated_at
+
+use migration_ai_manager_lib::crypto::testing::generate_test_key;
+use migration_ai_manager_lib::database::{DatabaseConfig, DatabaseManager};
+use migration_ai_manager_lib::repositories::agent_guide_repository::AgentGuideRepository;
+use migration_ai_manager_lib::repositories::claude_provider_repository::ClaudeProviderRepository;
+use migration_ai_manager_lib::repositories::codex_provider_repository::CodexProviderRepository;
+use migration_ai_manager_lib::repositories::common_config_repository::CommonConfigRepository;
+use migration_ai_manager_lib::repositories::mcp_server_repository::McpServerRepository;
+use migration_ai_manager_lib::*;
+
+/// 更新前写入的修改时间，早于任何一次实际写入
+const BACKDATED: &str = "2000-01-01 00:00:00";
+
+/// 删除 `updated_at` 触发器
+///
+/// 验证更新语句本身会刷新修改时间，不依赖触发器；删除后写入的过去时间也不会被触发器覆盖
+async fn drop_updated_at_triggers(db_manager: &DatabaseManager, tables: &[&str]) {
+    for table in tables {
+        sqlx::query(&format!(
+            r#"DROP TRIGGER IF EXISTS "update_{}_updated_at""#,
+            table
+        ))
+        .execute(db_manager.pool())
+        .await
+        .unwrap();
+    }
+}
+
+/// 将记录的修改时间改为 `BACKDATED`，不依赖等待 CURRENT_TIMESTAMP 的秒级精度
+async fn backdate(db_manager: &DatabaseManager, table: &str, id: i64) {
+    sqlx::query(&format!("UPDATE {} SET updated_at = ? WHERE id = ?", table))
+        .bind(BACKDATED)
+        .bind(id)
+        .execute(db_manager.pool())
+        .await
+        .unwrap();
+}
+
+async fn updated_at(db_manager: &DatabaseManager, table: &str, id: i64) -> String {
+    sqlx::query_scalar(&format!("SELECT updated_at FROM {} WHERE id = ?", table))
+        .bind(id)
+        .fetch_one(db_manager.pool())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_updated_at_increases_on_every_update() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let config = DatabaseConfig {
+        url: format!("sqlite:{}", temp_dir.path().join("updated_at.db").display()),
+        ..Default::default()
+    };
+    let db_manager = DatabaseManager::new(config).await.unwrap();
+    db_manager.ensure_initialized().await.unwrap();
+    let crypto_service = CryptoService::new(&generate_test_key()).unwrap();
+
+    let claude_repo = ClaudeProviderRepository::new(&db_manager, &crypto_service);
+    let codex_repo = CodexProviderRepository::new(&db_manager, &crypto_service);
+    let guide_repo = AgentGuideRepository::new(&db_manager, &crypto_service);
+    let mcp_repo = McpServerRepository::new(&db_manager, &crypto_service);
+    let config_repo = CommonConfigRepository::new(&db_manager, &crypto_service);
+
+    let claude_id = claude_repo
+        .create_claude_provider(&CreateClaudeProviderRequest {
+            name: "claude".to_string(),
+            url: "https://api.anthropic.com".to_string(),
+            token: "sk-ant-test".to_string(),
+            timeout: None,
+            auto_update: None,
+            r#type: None,
+            opus_model: None,
+            sonnet_model: None,
+            haiku_model: None,
+            models: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+            model_auto_update: None,
+        })
+        .await
+        .unwrap();
+    let codex_id = codex_repo
+        .create_codex_provider(&CreateCodexProviderRequest {
+            name: "codex".to_string(),
+            url: "https://api.openai.com".to_string(),
+            token: "sk-test".to_string(),
+            r#type: None,
+            custom_headers: None,
+            accepted_status_codes: None,
+        })
+        .await
+        .unwrap();
+    let guide_id = guide_repo
+        .create_agent_guide(&CreateAgentGuideRequest {
+            name: "guide".to_string(),
+            r#type: "only".to_string(),
+            text: "v0".to_string(),
+        })
+        .await
+        .unwrap();
+    let mcp_id = mcp_repo
+        .create_mcp_server(&CreateMcpServerRequest {
+            name: "mcp".to_string(),
+            r#type: None,
+            timeout: None,
+            command: "npx".to_string(),
+            args: vec![],
+            env: None,
+            secret_env_keys: vec![],
+        })
+        .await
+        .unwrap();
+    let config_id = config_repo
+        .create_common_config(
+            &CreateCommonConfigRequest {
+                key: "updated_at_test".to_string(),
+                value: "v0".to_string(),
+                description: None,
+                category: None,
+                is_active: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    let records = [
+        ("claude_providers", claude_id),
+        ("codex_providers", codex_id),
+        ("agent_guides", guide_id),
+        ("mcp_servers", mcp_id),
+        ("common_configs", config_id),
+    ];
+    drop_updated_at_triggers(&db_manager, &records.map(|(table, _)| table)).await;
+    for round in 1..=2 {
+        for (table, id) in records {
+            backdate(&db_manager, table, id).await;
+        }
+        let value = format!("v{}", round);
+
+        claude_repo
+            .update_claude_provider(
+                claude_id,
+                &UpdateClaudeProviderRequest {
+                    name: None,
+                    url: None,
+                    token: None,
+                    timeout: Some(1000 * round),
+                    auto_update: None,
+                    r#type: None,
+                    enabled: None,
+                    opus_model: None,
+                    sonnet_model: None,
+                    haiku_model: None,
+                    models: None,
+                    custom_headers: None,
+                    accepted_status_codes: None,
+                    model_auto_update: None,
+                },
+            )
+            .await
+            .unwrap();
+        codex_repo
+            .update_codex_provider(
+                codex_id,
+                &UpdateCodexProviderRequest {
+                    name: Some(format!("codex-{}", value)),
+                    url: None,
+                    token: None,
+                    r#type: None,
+                    enabled: None,
+                    custom_headers: None,
+                    accepted_status_codes: None,
+                },
+            )
+            .await
+            .unwrap();
+        guide_repo
+            .update_agent_guide(
+                guide_id,
+                &UpdateAgentGuideRequest { name: None, r#type: None, text: Some(value.clone()) },
+            )
+            .await
+            .unwrap();
+        mcp_repo
+            .update_mcp_server(
+                mcp_id,
+                &UpdateMcpServerRequest {
+                    name: None,
+                    r#type: None,
+                    timeout: Some(1000 * round),
+                    command: None,
+                    args: None,
+                    env: None,
+                    secret_env_keys: None,
+                },
+            )
+            .await
+            .unwrap();
+        config_repo.update_config_value("updated_at_test", &value, None).await.unwrap();
+
+        for (table, id) in records {
+            let current = updated_at(&db_manager, table, id).await;
+            assert!(
+                current.as_str() > BACKDATED,
+                "{} 第{}次更新后 updated_at 未刷新: {}",
+                table,
+                round,
+                current
+            );
+        }
+    }
+}
@@ -0,0 +1,89 @@
+// 实体与表结构一致性测试
+//
+// 迁移脚本生成的表结构必须满足 `ENTITY_SCHEMAS` 中每个实体的字段要求；
+// 修改模型字段或迁移脚本后，这里会在运行时 `FromRow` 报错之前发现不一致
+
+use migration_ai_manager_lib::models::{verify_schema_compat, SchemaProblem};
+use migration_ai_manager_lib::{DatabaseConfig, DatabaseManager};
+
+async fn migrated_database(name: &str) -> DatabaseManager {
+    let db_manager = DatabaseManager::new(DatabaseConfig::in_memory_shared(name)).await.unwrap();
+    db_manager.ensure_initialized().await.unwrap();
+    db_manager
+}
+
+#[tokio::test]
+async fn test_migrated_schema_matches_entities() {
+    let db_manager = migrated_database("schema_compat_migrated").await;
+
+    let mismatches = verify_schema_compat(db_manager.pool()).await.unwrap();
+    assert!(
+        mismatches.is_empty(),
+        "{}",
+        mismatches.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n")
+    );
+}
+
+#[tokio::test]
+async fn test_nullable_column_for_required_field_is_reported() {
+    let db_manager = migrated_database("schema_compat_nullable").await;
+    let pool = db_manager.pool();
+
+    // 重建表，让不是 Option 的 name 字段对应的列允许NULL
+    sqlx::query("DROP TABLE agent_guides").execute(pool).await.unwrap();
+    sqlx::query(
+        r#"
+        CREATE TABLE agent_guides (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT,
+            type TEXT NOT NULL,
+            text TEXT NOT NULL,
+            created_at TEXT DEFAULT CURRENT_TIMESTAMP,
+            updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let mismatches = verify_schema_compat(pool).await.unwrap();
+    assert_eq!(mismatches.len(), 1, "{:?}", mismatches);
+    assert_eq!(mismatches[0].entity, "AgentGuide");
+    assert_eq!(mismatches[0].column, Some("name"));
+    assert_eq!(mismatches[0].problem, SchemaProblem::NullableColumn);
+
+    // 这正是运行时会出错的情况：读取到NULL时 FromRow 失败
+    sqlx::query("INSERT INTO agent_guides (name, type, text) VALUES (NULL, 'only', 'x')")
+        .execute(pool)
+        .await
+        .unwrap();
+    let result =
+        sqlx::query_as::<_, migration_ai_manager_lib::AgentGuide>("SELECT * FROM agent_guides")
+            .fetch_all(pool)
+            .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_missing_table_and_type_mismatch_are_reported() {
+    let db_manager = migrated_database("schema_compat_missing").await;
+    let pool = db_manager.pool();
+
+    sqlx::query("DROP TABLE provider_groups").execute(pool).await.unwrap();
+    sqlx::query("DROP TABLE common_config_history").execute(pool).await.unwrap();
+    sqlx::query(
+        "CREATE TABLE common_config_history (id INTEGER PRIMARY KEY, config_id INTEGER NOT NULL, version TEXT NOT NULL, value TEXT NOT NULL, created_at TEXT)",
+    )
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let mismatches = verify_schema_compat(pool).await.unwrap();
+    assert!(mismatches
+        .iter()
+        .any(|m| m.table == "provider_groups" && m.problem == SchemaProblem::MissingTable));
+    assert!(mismatches.iter().any(|m| m.table == "common_config_history"
+        && m.column == Some("version")
+        && matches!(m.problem, SchemaProblem::TypeMismatch { .. })));
+}
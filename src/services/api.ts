@@ -25,12 +25,15 @@ import type {
   UpdateCommonConfigRequest,
   PaginationParams,
   PagedResult,
+  UpdateResult,
   ClaudeProviderStats,
   CodexProviderStats,
   AgentGuideStats,
   McpServerStats,
   CommonConfigStats,
-  BatchUpdateRequest
+  BatchUpdateRequest,
+  BatchUpdateResult,
+  ConnectionTestResult
 } from '../types';
 
 // API基础配置
@@ -184,16 +187,16 @@ export class ClaudeProviderService extends BaseApiService {
     return this.post<ClaudeProvider>('/claude-providers', data);
   }
 
-  async update(id: number, data: UpdateClaudeProviderRequest): Promise<ClaudeProvider> {
-    return this.put<ClaudeProvider>(`/claude-providers/${id}`, data);
+  async update(id: number, data: UpdateClaudeProviderRequest): Promise<UpdateResult<ClaudeProvider>> {
+    return this.put<UpdateResult<ClaudeProvider>>(`/claude-providers/${id}`, data);
   }
 
   async deleteById(id: number): Promise<void> {
     return this.delete<void>(`/claude-providers/${id}`);
   }
 
-  async testConnection(id: number): Promise<boolean> {
-    return this.get<boolean>(`/claude-providers/${id}/test`);
+  async testConnection(id: number): Promise<ConnectionTestResult> {
+    return this.get<ConnectionTestResult>(`/claude-providers/${id}/test`);
   }
 
   async getStats(): Promise<ClaudeProviderStats> {
@@ -215,16 +218,16 @@ export class CodexProviderService extends BaseApiService {
     return this.post<CodexProvider>('/codex-providers', data);
   }
 
-  async update(id: number, data: UpdateCodexProviderRequest): Promise<CodexProvider> {
-    return this.put<CodexProvider>(`/codex-providers/${id}`, data);
+  async update(id: number, data: UpdateCodexProviderRequest): Promise<UpdateResult<CodexProvider>> {
+    return this.put<UpdateResult<CodexProvider>>(`/codex-providers/${id}`, data);
   }
 
   async deleteById(id: number): Promise<void> {
     return this.delete<void>(`/codex-providers/${id}`);
   }
 
-  async testConnection(id: number): Promise<boolean> {
-    return this.get<boolean>(`/codex-providers/${id}/test`);
+  async testConnection(id: number): Promise<ConnectionTestResult> {
+    return this.get<ConnectionTestResult>(`/codex-providers/${id}/test`);
   }
 
   async getStats(): Promise<CodexProviderStats> {
@@ -246,8 +249,8 @@ export class AgentGuideService extends BaseApiService {
     return this.post<AgentGuide>('/agent-guides', data);
   }
 
-  async update(id: number, data: UpdateAgentGuideRequest): Promise<AgentGuide> {
-    return this.put<AgentGuide>(`/agent-guides/${id}`, data);
+  async update(id: number, data: UpdateAgentGuideRequest): Promise<UpdateResult<AgentGuide>> {
+    return this.put<UpdateResult<AgentGuide>>(`/agent-guides/${id}`, data);
   }
 
   async deleteById(id: number): Promise<void> {
@@ -277,8 +280,8 @@ export class McpServerService extends BaseApiService {
     return this.post<McpServer>('/mcp-servers', data);
   }
 
-  async update(id: number, data: UpdateMcpServerRequest): Promise<McpServer> {
-    return this.put<McpServer>(`/mcp-servers/${id}`, data);
+  async update(id: number, data: UpdateMcpServerRequest): Promise<UpdateResult<McpServer>> {
+    return this.put<UpdateResult<McpServer>>(`/mcp-servers/${id}`, data);
   }
 
   async deleteById(id: number): Promise<void> {
@@ -312,16 +315,17 @@ export class CommonConfigService extends BaseApiService {
     return this.post<CommonConfig>('/common-configs', data);
   }
 
-  async update(id: number, data: UpdateCommonConfigRequest): Promise<CommonConfig> {
-    return this.put<CommonConfig>(`/common-configs/${id}`, data);
+  async update(id: number, data: UpdateCommonConfigRequest): Promise<UpdateResult<CommonConfig>> {
+    return this.put<UpdateResult<CommonConfig>>(`/common-configs/${id}`, data);
   }
 
   async deleteById(id: number): Promise<void> {
     return this.delete<void>(`/common-configs/${id}`);
   }
 
-  async batchUpdate(data: BatchUpdateRequest): Promise<number> {
-    return this.post<number>('/common-configs/batch', data);
+  async batchUpdate(data: BatchUpdateRequest, partial = false): Promise<BatchUpdateResult> {
+    const query = partial ? '?partial=true' : '';
+    return this.post<BatchUpdateResult>(`/common-configs/batch${query}`, data);
   }
 
   async validate(id: number): Promise<boolean> {
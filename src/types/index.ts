@@ -19,6 +19,12 @@ export interface PagedResult<T> {
   total_pages: number;
 }
 
+// 更新结果，changed_fields 为实际发生变化的字段名
+export interface UpdateResult<T> {
+  entity: T;
+  changed_fields: string[];
+}
+
 // API响应格式
 export interface ApiResponse<T> {
   success: boolean;
@@ -193,9 +199,36 @@ export interface BatchUpdateRequest {
   configs: ConfigItem[];
 }
 
+// 按 id 指定配置；未指定 id 时按 key 查找
 export interface ConfigItem {
-  key: string;
-  value: string;
+  id?: number;
+  key?: string;
+  value?: string;
+  description?: string;
+  category?: string;
+  is_active?: number;
+}
+
+// 批量更新结果，整体回滚时 updated 为空
+export interface BatchUpdateResult {
+  updated: CommonConfig[];
+  failed: BatchUpdateFailure[];
+}
+
+export interface BatchUpdateFailure {
+  id?: number;
+  key?: string;
+  error: string;
+}
+
+// 供应商连接测试结果
+export interface ConnectionTestResult {
+  success: boolean;
+  reachable: boolean;
+  auth_ok: boolean;
+  status_code?: number | null;
+  latency_ms: number;
+  error?: string | null;
 }
 
 // 统计信息类型